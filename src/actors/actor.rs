@@ -9,14 +9,16 @@
 //!The `Handle` struct is used to create the API for actors, and it includes methods such as
 //!`send`, `tell`, `ask`, and `integrate`. These methods allow you to send messages to actors and
 //!receive responses, as well as to coordinate the instantiation of a new actor with the help of
-//!another actor.
+//!another actor.  An application that embeds navactor and ingests at a high rate can queue a
+//!batch with `tell_many` or push to the `observations_sink` of a director instead of waiting on
+//!each message.
 //!
 //!The `Actor` trait defines the functions that each actor must implement, namely `handle_envelope`
 //!and `stop`. The former is used to handle incoming messages, while the latter is used to stop the
 //!actor.
 //!
-//!The Rust code also includes some type aliases, such as `State<T>`, which is a hash map that maps
-//!an `i32` to a type `T`.
+//!The Rust code also includes some type aliases, such as `State<T>`, which is an ordered map that
+//!maps an `i32` to a type `T`.
 //!
 //!This Rust code uses Rust's `async_trait` library, which allows you to write asynchronous code
 //!using traits.
@@ -27,55 +29,72 @@ use crate::actors::message::Message;
 use crate::actors::message::MtHint;
 use crate::actors::message::NvError;
 use crate::actors::message::NvResult;
+use crate::actors::value::Value;
 use async_trait::async_trait;
+use std::fmt;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::Sender;
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 use tracing::instrument;
 use tracing::trace;
 
-/// in-mem state for an actor
-pub type State<T> = std::collections::HashMap<i32, T>;
+/// in-mem state for an actor - ordered by index so that every serialization
+/// of a state (stdout, API JSON, journal rows) is deterministic
+pub type State<T> = std::collections::BTreeMap<i32, T>;
 
 /// all actors must implement this trait
 #[async_trait]
-pub trait Actor {
+pub trait Actor<T = f64> {
     /// the function to implement per actor
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>);
+    async fn handle_envelope(&mut self, envelope: Envelope<T>);
     async fn start(&mut self);
     async fn stop(&self);
 }
 
 /// `ActorHandle` is the API for all actors
 #[derive(Debug)]
-pub struct Handle {
+pub struct Handle<T = f64> {
     #[doc(hidden)]
-    pub sender: mpsc::Sender<Envelope<f64>>,
+    pub sender: mpsc::Sender<Envelope<T>>,
 }
 
 ///The `Handle` struct is used to create the API for actors, and it includes methods such as
 ///`send`, `tell`, `ask`, and `integrate`. These methods allow you to send messages to actors and
 ///receive responses, as well as to coordinate the instantiation of a new actor with the help of
 ///another actor.
-impl<'a> Handle {
+impl<T: Value> Handle<T> {
     // INTERNAL: currently used by builtins (nv actors) implementing
     // actors that forward respond_to in workflows.
     #[doc(hidden)]
     #[instrument]
-    pub async fn send(&self, envelope: Envelope<f64>) -> NvResult<()> {
+    pub async fn send(&self, envelope: Envelope<T>) -> NvResult<()> {
         self.sender.send(envelope).await.map_err(|e| NvError {
             reason: e.to_string(),
         })
     }
 
+    /// the number of messages the mailbox of the actor can hold
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.sender.max_capacity()
+    }
+
+    /// the number of messages waiting in the mailbox of the actor
+    #[must_use]
+    pub fn queued(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
     /// fire and forget
     ///
     /// # Errors
     /// Returns [`NvError`](../message/struct.NvError.html) if the
     /// message is not received by the target actor
     #[instrument]
-    pub async fn tell(&self, message: Message<f64>) -> NvResult<()> {
+    pub async fn tell(&self, message: Message<T>) -> NvResult<()> {
         let envelope = Envelope {
             message,
             respond_to: None,
@@ -86,6 +105,63 @@ impl<'a> Handle {
         self.send(envelope).await
     }
 
+    /// fire and forget a batch - the messages are queued in order and each
+    /// run of up to `capacity` of them waits for room in the mailbox once, so
+    /// no message of another sender lands within a run.  nothing is replied
+    /// to, ask for the state afterwards to see the batch applied
+    ///
+    /// # Errors
+    /// Returns [`NvError`](../message/struct.NvError.html) if the
+    /// messages are not received by the target actor - the messages of the
+    /// runs before the failed one were queued
+    #[instrument(skip(messages))]
+    pub async fn tell_many(&self, messages: Vec<Message<T>>) -> NvResult<()> {
+        let mut messages = messages.into_iter();
+        loop {
+            let run = messages.len().min(self.capacity());
+            if run == 0 {
+                return Ok(());
+            }
+            let permits = self.sender.reserve_many(run).await.map_err(|e| NvError {
+                reason: e.to_string(),
+            })?;
+            for (permit, message) in permits.zip(messages.by_ref()) {
+                permit.send(Envelope {
+                    message,
+                    respond_to: None,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    /// a sender to push messages to the actor without waiting on each one,
+    /// ie: the observations of an embedding application.  the messages of
+    /// one sender are received in the order they are sent but may land
+    /// between the messages of other senders, including `ask`s of this
+    /// handle, and nothing is replied to.  the messages are forwarded by a
+    /// task on the current tokio runtime until every clone of the sender is
+    /// dropped or the actor stops
+    #[must_use]
+    pub fn observations_sink(&self) -> mpsc::Sender<Message<T>> {
+        let (sink, mut receiver) = mpsc::channel::<Message<T>>(self.capacity());
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let envelope = Envelope {
+                    message,
+                    respond_to: None,
+                    ..Default::default()
+                };
+                if sender.send(envelope).await.is_err() {
+                    trace!("observations sink closed by the actor");
+                    break;
+                }
+            }
+        });
+        sink
+    }
+
     /// request <-> response
     ///
     /// # Errors
@@ -93,7 +169,7 @@ impl<'a> Handle {
     /// Returns [`NvError`](../message/struct.NvError.html) if the
     /// message is not received and replied to by the target actor
     #[instrument]
-    pub async fn ask(&self, message: Message<f64>) -> NvResult<Message<f64>> {
+    pub async fn ask(&self, message: Message<T>) -> NvResult<Message<T>> {
         let (send, recv) = oneshot::channel();
 
         let envelope = Envelope {
@@ -112,6 +188,26 @@ impl<'a> Handle {
         }
     }
 
+    /// `ask` that stops waiting after `timeout` - the reply channel is dropped
+    /// so the actor can see that the requester is gone and abandon the work
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NvError`](../message/struct.NvError.html) if the
+    /// message is not replied to within `timeout`
+    #[instrument]
+    pub async fn ask_timeout(
+        &self,
+        message: Message<T>,
+        timeout: Duration,
+    ) -> NvResult<Message<T>> {
+        tokio::time::timeout(timeout, self.ask(message))
+            .await
+            .map_err(|_| NvError {
+                reason: format!("no reply within {timeout:?}"),
+            })?
+    }
+
     /// call to coordinate the instantiation of a new acotr with the help
     /// of another actor - usually a datastore journal service
     ///
@@ -125,14 +221,31 @@ impl<'a> Handle {
         path: String,
         helper: &Self,
         hint: MtHint,
-    ) -> NvResult<Message<f64>> {
-        type ResultSender = Sender<NvResult<Message<f64>>>;
-        type ResultReceiver = oneshot::Receiver<NvResult<Message<f64>>>;
-        type SendReceivePair = (ResultSender, ResultReceiver);
+    ) -> NvResult<Message<T>> {
+        self.integrate_until(path, helper, hint, CancellationToken::new())
+            .await
+    }
 
-        let (send, recv): SendReceivePair = oneshot::channel();
+    /// `integrate` that gives up when `cancel` is cancelled - the token is
+    /// handed to the helper so that it stops streaming rather than finishing
+    /// a load that nobody is waiting for
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NvError`](../message/struct.NvError.html) if the
+    /// two actors don't exchange lifecycle info or the load is cancelled
+    #[instrument]
+    pub async fn integrate_until(
+        &self,
+        path: String,
+        helper: &Self,
+        hint: MtHint,
+        cancel: CancellationToken,
+    ) -> NvResult<Message<T>> {
+        let (send, recv) = oneshot::channel::<NvResult<Message<T>>>();
 
-        let (init_cmd, load_cmd) = create_init_lifecycle(path, 8, send, hint);
+        let (init_cmd, mut load_cmd) = create_init_lifecycle(path, 8, send, hint);
+        load_cmd.cancel = Some(cancel.clone());
 
         helper.send(load_cmd).await.map_err(|e| NvError {
             reason: e.to_string(),
@@ -142,24 +255,48 @@ impl<'a> Handle {
             reason: e.to_string(),
         })?;
 
-        recv.await.map_err(|e| NvError {
-            reason: e.to_string(),
-        })?
+        tokio::select! {
+            result = recv => result.map_err(|e| NvError {
+                reason: e.to_string(),
+            })?,
+            () = cancel.cancelled() => Err(NvError {
+                reason: String::from("integration cancelled"),
+            }),
+        }
     }
 
     // ActorHandle constructor is an internal API use in the convenience functions
     // of the various per-actor ActorHandle impls
     #[doc(hidden)]
     #[must_use]
-    pub const fn new(sender: mpsc::Sender<Envelope<f64>>) -> Self {
+    pub const fn new(sender: mpsc::Sender<Envelope<T>>) -> Self {
         Self { sender }
     }
 }
 
+/// the end of the receiver loop of an actor sent a `Shutdown` - its `stop`
+/// cleanup runs and `Stopped` is the last thing it answers
+pub async fn shut_down<T: fmt::Debug, A: Actor<T> + Sync>(
+    actor: &A,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    actor.stop().await;
+    respond_or_log_error(respond_to, Ok(Message::Stopped));
+}
+
+/// the `stop` cleanup of an actor of a pipeline - the actor after it is shut
+/// down first
+pub async fn shut_down_output<T: Value>(output: &Handle<T>) {
+    match output.ask(Message::Shutdown).await {
+        Ok(Message::Stopped) => {}
+        r => error!("cannot shut down output: {r:?}"),
+    }
+}
+
 /// utility function most actors need to reply if a message is an 'ask'
-pub fn respond_or_log_error(
-    respond_to: Option<Sender<NvResult<Message<f64>>>>,
-    result: NvResult<Message<f64>>,
+pub fn respond_or_log_error<T: fmt::Debug>(
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+    result: NvResult<Message<T>>,
 ) {
     {
         if let Some(respond_to) = respond_to {
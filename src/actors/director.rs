@@ -2,7 +2,7 @@
 //!that it is forwarding commands to. The director also accepts metadata to create and store graph
 //!edges to support arbitrary paths.
 //!
-//!The ``Director`` is responsible for handling `Envelope<f64>` messages received from its
+//!The ``Director`` is responsible for handling `Envelope<T>` messages received from its
 //!associated `mpsc::Receiver`. The `Director` instantiates and forwards the received message to
 //!the appropriate actor specified in the message's path field. If the actor doesn't exist, the
 //!`Director` creates a new one by looking up the corresponding gene.
@@ -19,26 +19,43 @@
 //!`std::collections::HashMap`, and others.
 
 use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down_output;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
-use crate::actors::genes::accum_gene::AccumGene;
-use crate::actors::genes::gauge_and_accum_gene::GaugeAndAccumGene;
+use crate::actors::genes::aggregate_gene::CHILD_LABEL;
 use crate::actors::genes::gauge_gene::GaugeGene;
 use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
 use crate::actors::message::create_init_lifecycle;
+use crate::actors::message::DirectorInfo;
 use crate::actors::message::Envelope;
+use crate::actors::message::IndexLabels;
+use crate::actors::message::Labels;
 use crate::actors::message::Message;
 use crate::actors::message::MtHint;
 use crate::actors::message::NvError;
 use crate::actors::message::NvResult;
 use crate::actors::state_actor;
+use crate::actors::value::Value;
+use crate::utils::metrics;
+use crate::utils::metrics::Counter;
 use async_trait::async_trait;
-use std::collections::hash_map::Entry;
+use petgraph::graph::DiGraph;
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Instant;
+use time::Duration;
+use time::OffsetDateTime;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::Sender;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -46,29 +63,117 @@ use tracing::instrument;
 use tracing::trace;
 use tracing::warn;
 
-// TODO:
-// use rust std Path to update and persist petgraph graph Edges and
-// lookup/upsert actor for each input record msg send
-
 /// This struct represents a graph director that creates a graph and instantiates all the actors
 /// that it is forwarding commands to. The director also accepts metadata to create and store graph
 /// edges to support arbitrary paths.
 #[derive(Debug)]
-pub struct Director {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
-    pub store_actor: Option<Handle>,
-    pub output: Option<Handle>,
-    pub actors: HashMap<String, Handle>,
+pub struct Director<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub store_actor: Option<Handle<T>>,
+    pub output: Option<Handle<T>>,
+    pub actors: HashMap<String, Handle<T>>,
     pub gene_path_map: HashMap<String, GeneType>,
+    /// the params of the mappings in `gene_path_map` that have any
+    gene_params: HashMap<String, GeneParams>,
+    latest_observed: HashMap<String, OffsetDateTime>,
+    /// the rate limit of each path that sent observations
+    rate_limits: HashMap<String, TokenBucket>,
+    /// the buckets left in `rate_limits` by its last sweep
+    rate_limits_kept: usize,
+    /// observations applied to each live actor since its last snapshot
+    unsnapshotted: HashMap<String, usize>,
+    /// the live actors keyed by when they were last used, oldest first
+    access_order: BTreeMap<u64, String>,
+    /// when each live actor was last used - its key in `access_order`
+    last_access: HashMap<String, u64>,
+    access_count: u64,
+    /// observations of the current stream that were not journaled or applied
+    failed_observations: usize,
+    /// observations of the current stream that were journaled and applied
+    ingested_observations: usize,
+    /// observations of the current stream the journal already held
+    duplicate_observations: usize,
+    /// the path hierarchy of the actors observed so far, ie: `/a` -> `/a/b`
+    graph: DiGraph<String, ()>,
+    /// the node of each path in `graph`
+    graph_nodes: HashMap<String, NodeIndex>,
+    /// the mailboxes last seen above the high-water mark - a warning is
+    /// logged once per crossing, not once per message
+    over_high_water: HashSet<String>,
+    /// the prefix of the paths each `SubscribeEvents` subscriber is sent
+    event_subscribers: Vec<(String, mpsc::Sender<Message<T>>)>,
+    /// the latest `Event`s, oldest first, for a subscriber that reconnects
+    events: VecDeque<Message<T>>,
+    /// the id of the latest `Event`
+    event_id: u64,
+    /// the actors send the alerts they raise here, the director passes them
+    /// on from `alerts`
+    alert_sender: mpsc::Sender<Message<T>>,
+    alerts: mpsc::Receiver<Message<T>>,
+    /// the names the output prints in place of the indices of the state
+    index_labels: IndexLabels,
+    /// envelopes taken out of the mailbox by `coalesce_queries` that are
+    /// handled before the rest of it
+    deferred: VecDeque<Envelope<T>>,
+    options: DirectorOptions,
     namespace: String,
 }
 
+/// per-namespace behavior of a director - the defaults match a director
+/// created with `director::new`
+#[derive(Debug, Clone, Default)]
+pub struct DirectorOptions {
+    /// reject observations that are more than this much older than the
+    /// latest observation seen for the same path
+    pub observation_window: Option<Duration>,
+    /// persist a snapshot of an actor's state every time this many more
+    /// observations were applied to it so that its resurrection replays only
+    /// the journal written after the snapshot
+    pub snapshot_interval: Option<usize>,
+    /// keep at most this many actors in memory - the least recently used
+    /// actor is dropped to make room and is resurrected from the journal by
+    /// the next message to its path.  only applies with a store, an actor
+    /// without a journal would lose its state, and a subscription to an
+    /// evicted actor ends.
+    pub max_resident_actors: Option<usize>,
+    /// warn when the mailbox of the director, of its store or output, or of
+    /// one of its actors is at least this fraction full, ie: `0.8`
+    pub mailbox_high_water: Option<f64>,
+    /// reject the observations of a path beyond this many per second with a
+    /// `RateLimited` before they are journaled - a path may send a burst of
+    /// a second's worth, and at least one, after a quiet period
+    pub max_rate_per_path: Option<f64>,
+    /// round the values of every observation to this many decimal places
+    /// before it is journaled and applied so that the journal rows stay short
+    /// and a resurrected actor has the state of the live one.  only `f64`
+    /// values have decimals to round.
+    pub decimals: Option<u32>,
+    /// the gene of a path that no mapping applies to - `Gauge` without it.
+    /// any mapping of the path or of its ancestors still overrides it.
+    pub default_gene: Option<GeneType>,
+    /// wait this long before resurrecting a cold path so that the queries of
+    /// its state that arrive meanwhile share the resurrection - it is only
+    /// abandoned once every one of them gave up.  without it the resurrection
+    /// starts with the first message and the queries queued behind it find
+    /// the live actor.
+    pub resurrection_grace: Option<std::time::Duration>,
+}
+
+/// the observations a path may still send - refilled at `max_rate_per_path`
+/// per second up to a second's worth.  a full bucket is no different from
+/// the new one a path without a bucket gets, so the full ones are swept out.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
 #[async_trait]
-impl Actor for Director {
+impl<T: Value> Actor<T> for Director<T> {
     // This function is called when an envelope is received by the Director actor
     #[allow(clippy::too_many_lines)]
     #[instrument]
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
         trace!(
             "director namespace {} handling_envelope {envelope}",
             self.namespace
@@ -92,9 +197,16 @@ impl Actor for Director {
                             Message::EndOfStream {} => {
                                 break;
                             }
-                            Message::GeneMapping { path, gene_type } => {
+                            Message::GeneMapping {
+                                path,
+                                gene_type,
+                                params,
+                            } => {
                                 count += 1;
-                                self.gene_path_map.insert(path.clone(), *gene_type);
+                                self.set_gene_mapping(path, *gene_type, params.clone());
+                            }
+                            Message::Edges { edges } => {
+                                self.add_edges(edges);
                             }
                             _ => {}
                         }
@@ -112,9 +224,19 @@ impl Actor for Director {
             }
 
             // maintain the path-to-gene mappings
-            Message::GeneMapping { path, gene_type } => {
+            Message::GeneMapping {
+                path,
+                gene_type,
+                params,
+            } => {
                 debug!("setting new mapping: {path} {gene_type}");
-                self.handle_gene_mapping(path, *gene_type, message.clone(), respond_to)
+                self.handle_gene_mapping(path, *gene_type, params, message.clone(), respond_to)
+                    .await;
+            }
+
+            Message::GeneMappingBatch { mappings } => {
+                debug!("setting {} new mappings", mappings.len());
+                self.handle_gene_mapping_batch(mappings.clone(), respond_to)
                     .await;
             }
 
@@ -135,36 +257,161 @@ impl Actor for Director {
                 self.handle_update_or_query(&path.clone(), message, respond_to)
                     .await;
             }
+            Message::ConditionalObservations {
+                version,
+                observations,
+            } => {
+                self.handle_conditional(*version, *observations.clone(), respond_to)
+                    .await;
+            }
+            Message::SubscribeEvents {
+                path,
+                after,
+                stream_to,
+            } => {
+                debug!("new events subscriber under {path}");
+                let r = self.subscribe_events(path, *after, stream_to.clone());
+                respond_or_log_error(respond_to, Ok(r));
+            }
             Message::Query { path, hint, .. } if hint == &MtHint::State => {
-                // TODO: let query get all actor paths if path ends with a "/" otherwise get state
                 self.handle_update_or_query(&path.clone(), message, respond_to)
                     .await;
             }
-            Message::Query { path, hint, .. } if hint == &MtHint::GeneMapping => {
-                let path_string = String::from(path);
-
-                let prefix_string = if path_string.ends_with('/') {
-                    path_string
-                } else {
-                    path_string + "/"
+            Message::IndicesQuery { path, .. } => {
+                self.handle_update_or_query(&path.clone(), message, respond_to)
+                    .await;
+            }
+            Message::Query {
+                path,
+                hint: MtHint::Bucket(_),
+            } => {
+                self.handle_update_or_query(&path.clone(), message, respond_to)
+                    .await;
+            }
+            Message::Subscribe { path, .. } => {
+                self.handle_update_or_query(&path.clone(), message, respond_to)
+                    .await;
+            }
+            // series and histories are read straight from the journal - no actor is resurrected
+            Message::SeriesStream {
+                path, stream_to, ..
+            } => {
+                debug!("streaming the series of {path}");
+                let r = match &self.store_actor {
+                    Some(store_actor) => store_actor.tell(message.clone()).await,
+                    None => Err(NvError {
+                        reason: format!("no journal to read the observations of {path} from"),
+                    }),
                 };
-                let prefix: &str = prefix_string.as_str();
-
-                let mut clean_prefix_string: String = prefix_string.clone();
-                if clean_prefix_string.ends_with('/') {
-                    clean_prefix_string.pop();
+                if let Err(e) = r {
+                    let error = Message::Error {
+                        path: path.clone(),
+                        reason: e.reason,
+                    };
+                    stream_to.send(error).await.ok();
+                }
+            }
+            Message::SeriesQuery { path, .. }
+            | Message::Query {
+                path,
+                hint: MtHint::History { .. },
+            } => {
+                debug!("getting journal {message} for {path}");
+                let r = match &self.store_actor {
+                    Some(store_actor) => store_actor.ask(message.clone()).await,
+                    None => Err(NvError {
+                        reason: format!("no journal to read the observations of {path} from"),
+                    }),
                 };
-                let clean_prefix: &str = clean_prefix_string.as_str();
-
+                respond_or_log_error(respond_to, r);
+            }
+            Message::Query {
+                path,
+                hint: MtHint::ListPaths { limit, offset, .. },
+            } => {
+                debug!("listing paths under {path}");
+                self.handle_list_paths(path, *limit, *offset, message.clone(), respond_to)
+                    .await;
+            }
+            Message::Query {
+                path,
+                hint: MtHint::Descendants { limit, offset },
+            } => {
+                debug!("getting the state of the paths under {path}");
+                self.handle_descendants(path, *limit, *offset, respond_to)
+                    .await;
+            }
+            Message::Query {
+                path,
+                hint: MtHint::Adjacency,
+            } => {
+                let r = self.adjacency(path);
+                respond_or_log_error(respond_to, Ok(r));
+            }
+            Message::Query {
+                path,
+                hint: MtHint::Replay,
+            } => {
+                debug!("replaying the journal of {path}");
+                let r = self.replay(path).await;
+                respond_or_log_error(respond_to, r);
+            }
+            Message::Query {
+                path,
+                hint: MtHint::StateAsOf { datetime },
+            } => {
+                debug!("getting the state of {path} as of {datetime}");
+                let r = self.state_as_of(path, *datetime).await;
+                respond_or_log_error(respond_to, r);
+            }
+            // ready once the store answers too
+            Message::Ping => {
+                let r = match &self.store_actor {
+                    Some(store_actor) => store_actor.ask(message.clone()).await,
+                    None => Ok(Message::Pong),
+                };
+                respond_or_log_error(respond_to, r);
+            }
+            // stopped once the store has committed everything it was sent
+            Message::Stop => {
+                info!("stopping");
+                let r = match &self.store_actor {
+                    Some(store_actor) => store_actor.ask(message.clone()).await,
+                    None => Ok(Message::Stopped),
+                };
+                respond_or_log_error(respond_to, r);
+            }
+            Message::Shutdown => {
+                info!("shutting down");
+                self.shut_down().await;
+                respond_or_log_error(respond_to, Ok(Message::Stopped));
+            }
+            Message::DirectorInfo => {
+                let r = self.director_info().await;
+                respond_or_log_error(respond_to, r);
+            }
+            Message::PathStats { path } => {
+                debug!("getting the stats of {path}");
+                self.handle_path_stats(&path.clone(), respond_to).await;
+            }
+            Message::Delete { path, mapping } => {
+                debug!("deleting {path}");
+                self.handle_delete(path, *mapping, message.clone(), respond_to)
+                    .await;
+            }
+            Message::Compact { path, before } => {
+                debug!("compacting {path} before {before}");
+                let r = self.compact(path, *before).await;
+                respond_or_log_error(respond_to, r);
+            }
+            Message::Reset { path } => {
+                debug!("resetting {path}");
+                self.handle_reset(&path.clone(), message.clone(), respond_to)
+                    .await;
+            }
+            Message::Query { path, hint, .. } if hint == &MtHint::GeneMapping => {
                 let mut response: String = String::new();
-                let pairs: Vec<(&String, &GeneType)> = self
-                    .gene_path_map
-                    .iter()
-                    .filter(|(key, _)| {
-                        key == &path || key == &clean_prefix || key.starts_with(prefix)
-                    })
-                    .collect();
-                for (key, val) in pairs {
+                for (key, val, _) in self.mappings_under(path) {
                     if !response.is_empty() {
                         response += "\n";
                     }
@@ -181,6 +428,28 @@ impl Actor for Director {
                 self.forward_report(msg, respond_to).await;
             }
 
+            Message::Query { path, hint, .. } if hint == &MtHint::GeneMappingList => {
+                let mappings = self.mappings_under(path);
+                respond_or_log_error(respond_to, Ok(Message::GeneMappingList { mappings }));
+            }
+            Message::IndexLabel { idx, name } => {
+                debug!("labeling index {idx} {name}");
+                let r = self.handle_index_label(*idx, name, message.clone()).await;
+                respond_or_log_error(respond_to, r);
+            }
+            Message::Query {
+                hint: MtHint::IndexLabels,
+                ..
+            } => {
+                let labels = self.index_labels.clone();
+                respond_or_log_error(respond_to, Ok(Message::IndexLabels { labels }));
+            }
+            Message::DeleteGeneMapping { path } => {
+                debug!("deleting the gene mapping of {path}");
+                self.handle_delete_gene_mapping(path, message.clone(), respond_to)
+                    .await;
+            }
+
             // If the message is an EndOfStream message, forward it to the output actor
             // or send the response directly to the original requester
             Message::EndOfStream {} => self.handle_end_of_stream(message, respond_to).await,
@@ -199,11 +468,7 @@ impl Actor for Director {
     async fn start(&mut self) {
         info!("starting");
         if let Some(store_actor) = &self.store_actor {
-            type ResultSender = Sender<NvResult<Message<f64>>>;
-            type ResultReceiver = oneshot::Receiver<NvResult<Message<f64>>>;
-            type SendReceivePair = (ResultSender, ResultReceiver);
-
-            let (send, recv): SendReceivePair = oneshot::channel();
+            let (send, recv) = oneshot::channel::<NvResult<Message<T>>>();
 
             let (init_cmd, load_cmd) =
                 create_init_lifecycle(self.namespace.clone(), 8, send, MtHint::GeneMapping);
@@ -222,16 +487,17 @@ impl Actor for Director {
                 Ok(_) => {}
                 Err(e) => error!("cannot start director because of store error: {e}"),
             }
+            self.load_index_labels().await;
         }
     }
 }
 
 // wrapper to support flow when no persistence is configured
 #[instrument]
-async fn journal_message(
-    message: Message<f64>,
-    store_actor: &Option<Handle>,
-) -> Result<Message<f64>, NvError> {
+async fn journal_message<T: Value>(
+    message: Message<T>,
+    store_actor: &Option<Handle<T>>,
+) -> Result<Message<T>, NvError> {
     if let Some(store_actor) = store_actor {
         trace!("journal_message {message}");
         // jrnl the new msg
@@ -244,37 +510,54 @@ async fn journal_message(
 }
 
 #[instrument]
-async fn forward_actor_result(result: NvResult<Message<f64>>, output: &Option<Handle>) {
+async fn forward_actor_result<T: Value>(
+    path: &str,
+    result: NvResult<Message<T>>,
+    output: &Option<Handle<T>>,
+) {
     //forward to optional output
     trace!("forward_actor_result");
     if let Some(o) = output {
-        if let Ok(message) = result {
-            let senv = Envelope {
-                message,
-                respond_to: None,
-                ..Default::default()
-            };
-            match o.send(senv).await {
-                Ok(_) => {}
-                Err(e) => {
-                    error!("can not forward: {e:?}");
+        let message = match result {
+            Ok(rejected @ (Message::LateReport { .. } | Message::RateLimited { .. })) => {
+                Message::Error {
+                    path: path.to_string(),
+                    reason: rejected.to_string(),
                 }
             }
+            Ok(message) => message,
+            Err(e) => Message::Error {
+                path: path.to_string(),
+                reason: e.reason,
+            },
+        };
+        let senv = Envelope {
+            message,
+            respond_to: None,
+            ..Default::default()
+        };
+        match o.send(senv).await {
+            Ok(_) => {}
+            Err(e) => {
+                error!("can not forward: {e:?}");
+            }
         }
     }
 }
 
 #[instrument]
-async fn write_jrnl(
-    message: Message<f64>,
-    store_actor: &Option<Handle>,
-) -> Result<Message<f64>, NvError> {
+async fn write_jrnl<T: Value>(
+    message: Message<T>,
+    store_actor: &Option<Handle<T>>,
+) -> Result<Message<T>, NvError> {
     match message.clone() {
         Message::Observations { path: _, .. } => {
             trace!("write_jrnl");
             journal_message(message.clone(), store_actor).await
         }
-        Message::Query { path: _, .. } => Ok(Message::Persisted),
+        Message::Query { path: _, .. }
+        | Message::IndicesQuery { .. }
+        | Message::Subscribe { .. } => Ok(Message::Persisted),
         m => {
             warn!("unexpected message: {m}");
             Err(NvError {
@@ -284,41 +567,139 @@ async fn write_jrnl(
     }
 }
 
+/// the report of the actor if it applied the message, `None` if it did not
 #[instrument]
-async fn send_to_actor(
-    message: Message<f64>,
-    respond_to: Option<Sender<NvResult<Message<f64>>>>,
-    actor: &Handle,
-    output: &Option<Handle>,
-) {
+async fn send_to_actor<T: Value>(
+    path: &str,
+    message: Message<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+    actor: &Handle<T>,
+    output: &Option<Handle<T>>,
+) -> Option<Message<T>> {
     trace!("send_to_actor sending to actor");
     //send message to the actor and support ask results
     let r = actor.ask(message).await;
+    let report = match &r {
+        Ok(Message::LateReport { .. }) | Err(_) => None,
+        Ok(m) => Some(m.clone()),
+    };
     respond_or_log_error(respond_to, r.clone());
 
     //forward to optional output
-    forward_actor_result(r, output).await;
+    forward_actor_result(path, r, output).await;
+    report
+}
+
+/// how many of the latest state reports a director keeps for an events
+/// subscriber that reconnects after missing some
+const EVENT_BACKLOG: usize = 256;
+
+/// how many alerts of its actors may wait for the director to pass them on
+const ALERT_BUFFER: usize = 64;
+
+/// how many rate limit buckets a director holds before it first sweeps out
+/// the ones that refilled
+const RATE_LIMIT_SWEEP: usize = 1024;
+
+/// the path of a `StateReport` or an `Alert` - the reports that are events
+fn event_path<T>(report: &Message<T>) -> Option<&str> {
+    match report {
+        Message::StateReport { path, .. } | Message::Alert { path, .. } => Some(path),
+        _ => None,
+    }
 }
 
-fn get_gene(gene_type: GeneType) -> Box<dyn Gene<f64> + Send + Sync> {
+/// true if `path` is `prefix` or below it
+fn is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// the path one level up from `path` - the root, `/`, is not a path
+fn parent_path(path: &str) -> Option<String> {
+    Path::new(path)
+        .parent()
+        .and_then(Path::to_str)
+        .filter(|parent| !parent.is_empty() && *parent != "/")
+        .map(String::from)
+}
+
+/// the parent-to-child edges from the root of `path` down to it - the root
+/// itself, `/`, is not a node
+fn path_edges(path: &str) -> Vec<(String, String)> {
+    let ancestors: Vec<&str> = Path::new(path)
+        .ancestors()
+        .filter_map(Path::to_str)
+        .filter(|ancestor| !ancestor.is_empty() && *ancestor != "/")
+        .collect();
+    ancestors
+        .windows(2)
+        .rev()
+        .map(|pair| (pair[1].to_string(), pair[0].to_string()))
+        .collect()
+}
+
+/// construct the gene a mapping would create and check its configuration
+///
+/// # Errors
+///
+/// Returns [`NvError`](../message/struct.NvError.html) if the params do not
+/// apply to the gene type or the resulting gene does not validate
+pub fn configured_gene<T: Value>(
+    gene_type: GeneType,
+    params: &GeneParams,
+) -> NvResult<Box<dyn Gene<T> + Send + Sync>> {
+    // the params left once the ones of the gene type are taken out
+    let mut others = params.clone();
     match gene_type {
-        GeneType::Accum => Box::<AccumGene>::default(),
-        GeneType::Gauge => Box::<GaugeGene>::default(),
-        _ => Box::<GaugeAndAccumGene>::default(),
+        GeneType::GaugeAndAccum => {
+            others.guage_first_idx = None;
+            others.guage_slots = None;
+            others.accumulator_first_idx = None;
+            others.accumulator_slots = None;
+        }
+        GeneType::Accum => others.time_scope = None,
+        GeneType::Ewma => others.alpha = None,
+        GeneType::MovingAvg => others.window = None,
+        GeneType::Aggregate => others.aggregate = None,
+        GeneType::Alert => others.thresholds = None,
+        GeneType::Composite => others.ranges = None,
+        GeneType::Delta => {
+            others.source_idx = None;
+            others.delta_idx = None;
+        }
+        _ => {}
     }
+    let applies = others.is_empty();
+    if !applies {
+        return Err(NvError {
+            reason: format!("gene {gene_type} does not take params {params:?}"),
+        });
+    }
+    let gene = T::gene(gene_type, params).ok_or_else(|| NvError {
+        reason: format!("gene {gene_type} does not apply to {} values", T::NAME),
+    })?;
+    gene.validate().map_err(|e| NvError {
+        reason: format!("invalid {gene_type} gene: {e}"),
+    })?;
+    Ok(gene)
 }
 
 /// actor private constructor
-impl Director {
+impl<T: Value> Director<T> {
     fn handle_gene_mapping_query(
         &mut self,
         path: &str,
-        respond_to: Option<Sender<NvResult<Message<f64>>>>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
     ) {
         if let Some(gt) = self.gene_path_map.get(path) {
             let msg = Message::GeneMapping {
                 path: path.to_string(),
                 gene_type: *gt,
+                params: self.gene_params.get(path).cloned().unwrap_or_default(),
             };
             respond_or_log_error(respond_to, Ok(msg));
         } else {
@@ -329,16 +710,121 @@ impl Director {
         }
     }
 
+    /// the mappings of `path` and of the paths under it in path order
+    fn mappings_under(&self, path: &str) -> Vec<(String, GeneType, GeneParams)> {
+        let clean_prefix = path.trim_end_matches('/');
+        let prefix = format!("{clean_prefix}/");
+        let mut mappings: Vec<(String, GeneType, GeneParams)> = self
+            .gene_path_map
+            .iter()
+            .filter(|(key, _)| key == &path || key == &clean_prefix || key.starts_with(&prefix))
+            .map(|(key, gene_type)| {
+                (
+                    key.clone(),
+                    *gene_type,
+                    self.gene_params.get(key).cloned().unwrap_or_default(),
+                )
+            })
+            .collect();
+        mappings.sort_by(|a, b| a.0.cmp(&b.0));
+        mappings
+    }
+
+    /// the live mapping is only removed once the store has removed the
+    /// persisted one - without a store the live mapping decides if there was
+    /// anything to delete.  a path only mapped by the mapping every namespace
+    /// shares has nothing of its own to delete and is a constraint violation.
+    #[instrument]
+    async fn handle_delete_gene_mapping(
+        &mut self,
+        path: &str,
+        message: Message<T>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let found = match &self.store_actor {
+            Some(store_actor) => match store_actor.ask(message.clone()).await {
+                Ok(Message::NotFound { .. }) => false,
+                Ok(_) => true,
+                Err(e) => {
+                    respond_or_log_error(respond_to, Err(e));
+                    return;
+                }
+            },
+            None => self.gene_path_map.contains_key(path),
+        };
+        let shared = match self.fall_back_to_shared_mapping(path).await {
+            Ok(shared) => shared,
+            Err(e) => {
+                respond_or_log_error(respond_to, Err(e));
+                return;
+            }
+        };
+        if found {
+            respond_or_log_error(respond_to, Ok(message));
+        } else if shared {
+            respond_or_log_error(respond_to, Ok(Message::ConstraintViolation));
+        } else {
+            respond_or_log_error(
+                respond_to,
+                Ok(Message::NotFound {
+                    path: path.to_string(),
+                }),
+            );
+        }
+    }
+
+    /// once the namespace's own mapping of `path` is deleted the mapping
+    /// every namespace shares, if the store has one, is the live mapping of
+    /// `path` again - true if it still maps the path
+    async fn fall_back_to_shared_mapping(&mut self, path: &str) -> NvResult<bool> {
+        self.gene_path_map.remove(path);
+        self.gene_params.remove(path);
+        let Some(store_actor) = &self.store_actor else {
+            return Ok(false);
+        };
+        let query = Message::Query {
+            path: path.to_string(),
+            hint: MtHint::SharedGeneMapping,
+        };
+        match store_actor.ask(query).await? {
+            Message::GeneMapping {
+                gene_type, params, ..
+            } => {
+                debug!("{path} is still mapped by the mapping shared by every namespace");
+                self.set_gene_mapping(path, gene_type, params);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// replace the live mapping of a path
+    fn set_gene_mapping(&mut self, path: &str, gene_type: GeneType, params: GeneParams) {
+        self.gene_path_map.insert(String::from(path), gene_type);
+        if params.is_empty() {
+            self.gene_params.remove(path);
+        } else {
+            self.gene_params.insert(String::from(path), params);
+        }
+    }
+
+    /// a mapping whose params do not configure a valid gene is rejected
+    /// before it is persisted
     #[instrument]
     async fn handle_gene_mapping(
         &mut self,
         path: &str,
         gene_type: GeneType,
-        message: Message<f64>, // for jrnl
-        respond_to: Option<Sender<NvResult<Message<f64>>>>,
+        params: &GeneParams,
+        message: Message<T>, // for jrnl
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
     ) {
         debug!("new gene_mapping");
-        self.gene_path_map.insert(String::from(path), gene_type);
+        if let Err(e) = configured_gene::<T>(gene_type, params) {
+            respond_or_log_error(respond_to, Err(e));
+            return;
+        }
+        self.set_gene_mapping(path, gene_type, params.clone());
         if let Some(store_actor) = &self.store_actor {
             let jrnl_msg = store_actor.ask(message.clone()).await;
             match jrnl_msg {
@@ -359,11 +845,359 @@ impl Director {
         }
     }
 
+    /// a path may only appear once in a batch - later repeats are reported
+    /// and skipped, as are entries whose params do not configure a valid
+    /// gene.  the live mappings are only updated for the entries the store
+    /// accepted.
+    #[instrument]
+    async fn handle_gene_mapping_batch(
+        &mut self,
+        mappings: Vec<(String, GeneType, GeneParams)>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        let mut accepted = Vec::new();
+        let mut accepted_params = HashMap::new();
+        for (path, gene_type, params) in mappings {
+            if !seen.insert(path.clone()) {
+                results.push((path, Err(String::from("duplicate path in batch"))));
+            } else if let Err(e) = configured_gene::<T>(gene_type, &params) {
+                results.push((path, Err(e.reason)));
+            } else {
+                accepted_params.insert(path.clone(), params.clone());
+                accepted.push((path.clone(), gene_type, params));
+                results.push((path, Ok(gene_type)));
+            }
+        }
+
+        if let Some(store_actor) = &self.store_actor {
+            let persisted = match store_actor
+                .ask(Message::GeneMappingBatch { mappings: accepted })
+                .await
+            {
+                Ok(Message::GeneMappingBatchReport { results }) => results,
+                Ok(m) => {
+                    respond_or_log_error(
+                        respond_to,
+                        Err(NvError {
+                            reason: format!("unexpected store response: {m}"),
+                        }),
+                    );
+                    return;
+                }
+                Err(e) => {
+                    respond_or_log_error(respond_to, Err(e));
+                    return;
+                }
+            };
+            let mut persisted = persisted.into_iter();
+            for result in &mut results {
+                if result.1.is_ok() {
+                    if let Some(p) = persisted.next() {
+                        *result = p;
+                    }
+                }
+            }
+        }
+
+        for (path, result) in &results {
+            if let Ok(gene_type) = result {
+                let params = accepted_params.remove(path).unwrap_or_default();
+                self.set_gene_mapping(path, *gene_type, params);
+            }
+        }
+
+        respond_or_log_error(respond_to, Ok(Message::GeneMappingBatchReport { results }));
+    }
+
+    /// the paths with state that start with `path` are the journaled ones -
+    /// without a store they are the paths of the live actors, which can not
+    /// be listed `since` a sequence
+    #[instrument]
+    async fn handle_list_paths(
+        &self,
+        path: &str,
+        limit: usize,
+        offset: usize,
+        message: Message<T>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let r = match (&self.store_actor, &message) {
+            (Some(store_actor), _) => store_actor.ask(message).await,
+            (
+                None,
+                Message::Query {
+                    hint:
+                        MtHint::ListPaths {
+                            since: Some(since), ..
+                        },
+                    ..
+                },
+            ) => Err(NvError {
+                reason: format!("no journal to list the paths under {path} since {since} from"),
+            }),
+            (None, _) => {
+                let mut paths: Vec<String> = self
+                    .actors
+                    .keys()
+                    .filter(|key| key.starts_with(path))
+                    .cloned()
+                    .collect();
+                paths.sort();
+                let paths = paths.into_iter().skip(offset).take(limit).collect();
+                Ok(Message::PathList {
+                    paths,
+                    sequence: None,
+                })
+            }
+        };
+        respond_or_log_error(respond_to, r);
+    }
+
+    /// the paths under `path` are listed like `ListPaths` and each one is
+    /// resurrected and queried like a `State` query - paths without state,
+    /// ie: live actors that were only ever queried, are left out
+    #[instrument]
+    async fn handle_descendants(
+        &mut self,
+        path: &str,
+        limit: usize,
+        offset: usize,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let prefix = if path.ends_with('/') {
+            path.to_string()
+        } else {
+            format!("{path}/")
+        };
+        let (send, recv) = oneshot::channel();
+        let list = Message::Query {
+            path: prefix.clone(),
+            hint: MtHint::ListPaths {
+                limit,
+                offset,
+                since: None,
+            },
+        };
+        self.handle_list_paths(&prefix, limit, offset, list, Some(send))
+            .await;
+        let paths = match recv.await {
+            Ok(Ok(Message::PathList { paths, .. })) => paths,
+            Ok(Err(e)) => {
+                respond_or_log_error(respond_to, Err(e));
+                return;
+            }
+            r => {
+                respond_or_log_error(
+                    respond_to,
+                    Err(NvError {
+                        reason: format!("cannot list the paths under {prefix}: {r:?}"),
+                    }),
+                );
+                return;
+            }
+        };
+
+        let mut reports = Vec::with_capacity(paths.len());
+        for path in paths {
+            let (send, recv) = oneshot::channel();
+            let query = Message::Query {
+                path: path.clone(),
+                hint: MtHint::State,
+            };
+            self.handle_update_or_query(&path, query, Some(send)).await;
+            match recv.await {
+                Ok(Ok(report @ Message::StateReport { .. })) => {
+                    if matches!(&report, Message::StateReport { values, .. } if !values.is_empty())
+                    {
+                        reports.push(report);
+                    }
+                }
+                Ok(Ok(m)) => warn!("unexpected state of {path}: {m}"),
+                Ok(Err(e)) => {
+                    respond_or_log_error(respond_to, Err(e));
+                    return;
+                }
+                Err(e) => {
+                    respond_or_log_error(
+                        respond_to,
+                        Err(NvError {
+                            reason: format!("no state of {path}: {e}"),
+                        }),
+                    );
+                    return;
+                }
+            }
+        }
+        respond_or_log_error(
+            respond_to,
+            Ok(Message::StateReports {
+                path: prefix,
+                reports,
+            }),
+        );
+    }
+
+    /// add the edges that are not in the graph yet and return them
+    fn add_edges(&mut self, edges: &[(String, String)]) -> Vec<(String, String)> {
+        let mut added = Vec::new();
+        for (parent, child) in edges {
+            let parent_node = self.graph_node(parent);
+            let child_node = self.graph_node(child);
+            if !self.graph.contains_edge(parent_node, child_node) {
+                self.graph.add_edge(parent_node, child_node, ());
+                added.push((parent.clone(), child.clone()));
+            }
+        }
+        added
+    }
+
+    fn graph_node(&mut self, path: &str) -> NodeIndex {
+        if let Some(node) = self.graph_nodes.get(path) {
+            return *node;
+        }
+        let node = self.graph.add_node(path.to_string());
+        self.graph_nodes.insert(path.to_string(), node);
+        node
+    }
+
+    /// add the edges from the root of an observed path down to it, ie:
+    /// `/a` -> `/a/b` and `/a/b` -> `/a/b/c` for `/a/b/c`, and persist the
+    /// new ones - a failed write is logged and the edges are written again
+    /// by the next director that observes the path
+    async fn register_path(&mut self, path: &str) {
+        if self.graph_nodes.contains_key(path) {
+            return;
+        }
+        let added = self.add_edges(&path_edges(path));
+        if added.is_empty() {
+            return;
+        }
+        if let Some(store_actor) = &self.store_actor {
+            if let Err(e) = store_actor.ask(Message::Edges { edges: added }).await {
+                warn!("cannot persist the edges of {path}: {e}");
+            }
+        }
+    }
+
+    /// the paths directly above and below `path` in path order
+    fn adjacency(&self, path: &str) -> Message<T> {
+        let path = path.trim_end_matches('/');
+        let neighbors = |direction| {
+            let mut paths: Vec<String> = self
+                .graph_nodes
+                .get(path)
+                .map(|node| {
+                    self.graph
+                        .neighbors_directed(*node, direction)
+                        .map(|neighbor| self.graph[neighbor].clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            paths.sort();
+            paths
+        };
+        Message::Adjacency {
+            path: path.to_string(),
+            parents: neighbors(Direction::Incoming),
+            children: neighbors(Direction::Outgoing),
+        }
+    }
+
+    /// the live actor is only released once the store has removed the journal
+    /// so that a failed delete leaves the actor and its journal consistent.  a
+    /// path that has never been observed deletes quietly and is answered with
+    /// `NotFound`.  with `mapping` a mapping every namespace shares still maps
+    /// the path afterwards.
+    #[instrument]
+    async fn handle_delete(
+        &mut self,
+        path: &str,
+        mapping: bool,
+        message: Message<T>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        // with a store the journal decides if there was anything to delete - a
+        // live actor may just be the empty result of a query
+        let jrnl_found = match &self.store_actor {
+            Some(store_actor) => match store_actor.ask(message.clone()).await {
+                Ok(Message::NotFound { .. }) => Some(false),
+                Ok(_) => Some(true),
+                Err(e) => {
+                    respond_or_log_error(respond_to, Err(e));
+                    return;
+                }
+            },
+            None => None,
+        };
+        let live_found = self.release(path);
+        if mapping {
+            if let Err(e) = self.fall_back_to_shared_mapping(path).await {
+                warn!("cannot load the shared gene mapping of {path}: {e}");
+            }
+        }
+        if jrnl_found.unwrap_or(live_found) {
+            respond_or_log_error(respond_to, Ok(message));
+        } else {
+            respond_or_log_error(
+                respond_to,
+                Ok(Message::NotFound {
+                    path: path.to_string(),
+                }),
+            );
+        }
+    }
+
+    /// the actor is made live before the reset is journaled so that a
+    /// resurrection does not replay the reset it is about to be sent.  a path
+    /// that has never been observed is not found and nothing is journaled
+    async fn handle_reset(
+        &mut self,
+        path: &String,
+        message: Message<T>,
+        mut respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        if !self.make_live(path, respond_to.as_mut()).await {
+            return;
+        }
+        let Some(actor) = self.actors.get(path) else {
+            return;
+        };
+        let query = Message::Query {
+            path: path.clone(),
+            hint: MtHint::State,
+        };
+        match actor.ask(query).await {
+            Ok(Message::StateReport { version: 0, .. }) => {
+                respond_or_log_error(respond_to, Ok(Message::NotFound { path: path.clone() }));
+                return;
+            }
+            Ok(Message::StateReport { .. }) => {}
+            r => {
+                respond_or_log_error(respond_to, r);
+                return;
+            }
+        }
+        if let Some(store_actor) = &self.store_actor {
+            if let Err(e) = store_actor.ask(message.clone()).await {
+                respond_or_log_error(respond_to, Err(e));
+                return;
+            }
+        }
+        let report = actor.ask(message).await;
+        if let Ok(report @ Message::StateReport { .. }) = &report {
+            self.publish(report);
+            self.roll_up(path, report.clone()).await;
+        }
+        respond_or_log_error(respond_to, report);
+    }
+
     #[instrument]
     async fn forward_report(
         &self,
-        message: Message<f64>,
-        respond_to: Option<Sender<NvResult<Message<f64>>>>,
+        message: Message<T>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
     ) {
         if let Some(a) = &self.output {
             let senv = Envelope {
@@ -382,13 +1216,59 @@ impl Director {
         }
     }
 
+    /// tell the output how many observations of the stream were ingested and
+    /// how many were skipped as duplicates
+    async fn summarize_stream(&mut self) {
+        let (ingested, duplicates) = (self.ingested_observations, self.duplicate_observations);
+        self.ingested_observations = 0;
+        self.duplicate_observations = 0;
+        let Some(output) = &self.output else {
+            return;
+        };
+        if ingested + duplicates == 0 {
+            return;
+        }
+        let summary = Message::StreamSummary {
+            ingested,
+            duplicates,
+        };
+        if let Err(e) = output.tell(summary).await {
+            error!("cannot summarize stream: {e:?}");
+        }
+    }
+
     #[instrument]
     async fn handle_end_of_stream(
-        &self,
-        message: Message<f64>,
-        respond_to: Option<Sender<NvResult<Message<f64>>>>,
+        &mut self,
+        message: Message<T>,
+        respond_to: Option<Sender<NvResult<Message<T>>>>,
     ) {
         debug!("complete");
+        // the stream is not complete until the store has committed all of it
+        let flushed = match &self.store_actor {
+            Some(store_actor) => store_actor.ask(Message::Flush).await.map(|_| ()),
+            None => Ok(()),
+        };
+        self.summarize_stream().await;
+
+        // a stream with failures still completes but reports them to the requester
+        if self.failed_observations > 0 || flushed.is_err() {
+            if let Some(a) = &self.output {
+                a.ask(message)
+                    .await
+                    .map_err(|e| {
+                        error!("cannot complete output: {e:?}");
+                    })
+                    .ok();
+            }
+            let reason = match flushed {
+                Err(e) => format!("cannot flush the journal: {}", e.reason),
+                Ok(()) => format!("{} observations were not applied", self.failed_observations),
+            };
+            self.failed_observations = 0;
+            respond_or_log_error(respond_to, Err(NvError { reason }));
+            return;
+        }
 
         // forward message to output but direct response directly back to
         // original requester instead of here
@@ -409,91 +1289,988 @@ impl Director {
         }
     }
 
-    #[instrument]
-    async fn handle_update_or_query(
-        &mut self,
+    /// the state of `path` after every event of its journal - the journal is
+    /// replayed into an actor that is dropped afterwards so the live actor of
+    /// the path, if any, is left alone
+    async fn replay(&self, path: &String) -> NvResult<Message<T>> {
+        if self.store_actor.is_none() {
+            return Err(NvError {
+                reason: format!("no journal to replay {path} from"),
+            });
+        }
+        let (sender, mut receiver) = mpsc::channel::<Envelope<T>>(8);
+        let collector = tokio::spawn(async move {
+            let mut reports = Vec::new();
+            while let Some(envelope) = receiver.recv().await {
+                reports.push(envelope.message);
+            }
+            reports
+        });
+        let actor = self
+            .resurrect(path, &CancellationToken::new(), Some(Handle::new(sender)))
+            .await;
+        // the replay is over once the actor, the last holder of the sender, is gone
+        drop(actor);
+        let reports = collector.await.map_err(|e| NvError {
+            reason: format!("cannot replay {path}: {e}"),
+        })?;
+        if reports.is_empty() {
+            return Ok(Message::NotFound { path: path.clone() });
+        }
+        Ok(Message::StateReports {
+            path: path.clone(),
+            reports,
+        })
+    }
+
+    /// the state of `path` as of `datetime` - the journal up to then is
+    /// replayed into an actor that is dropped afterwards so the live actor of
+    /// the path, if any, is left alone
+    async fn state_as_of(&self, path: &String, datetime: OffsetDateTime) -> NvResult<Message<T>> {
+        let Some(store_actor) = &self.store_actor else {
+            return Err(NvError {
+                reason: format!("no journal to read the state of {path} as of {datetime} from"),
+            });
+        };
+        let actor = state_actor::new(path.clone(), 8, self.gene_of(path), None);
+        actor
+            .integrate_until(
+                path.clone(),
+                store_actor,
+                MtHint::StateAsOf { datetime },
+                CancellationToken::new(),
+            )
+            .await?;
+        actor
+            .ask(Message::Query {
+                path: path.clone(),
+                hint: MtHint::State,
+            })
+            .await
+    }
+
+    /// compact the journal of every path under `path` whose gene compacts,
+    /// each replayed through the gene it is mapped to.  a live actor already
+    /// has the state of its journal so it is left alone.
+    async fn compact(&self, path: &str, before: OffsetDateTime) -> NvResult<Message<T>> {
+        let Some(store_actor) = &self.store_actor else {
+            return Err(NvError {
+                reason: format!("no journal to compact {path} in"),
+            });
+        };
+        let paths = match store_actor
+            .ask(Message::Query {
+                path: path.to_string(),
+                hint: MtHint::ListPaths {
+                    limit: usize::MAX,
+                    offset: 0,
+                    since: None,
+                },
+            })
+            .await?
+        {
+            Message::PathList { paths, .. } => paths,
+            m => {
+                return Err(NvError {
+                    reason: format!("unexpected response to listing {path}: {m}"),
+                })
+            }
+        };
+        let mut compacted = 0;
+        let mut removed = 0;
+        for path in paths {
+            let (gene_type, params) = self.mapped_gene(&path);
+            if !T::gene(gene_type, &params).is_some_and(|gene| gene.compacts()) {
+                debug!("{path} is not compacted, its {gene_type} gene does not compact");
+                continue;
+            }
+            match store_actor
+                .ask(Message::CompactPath {
+                    path,
+                    before,
+                    gene_type,
+                    params,
+                })
+                .await?
+            {
+                Message::Compacted { paths, rows } => {
+                    compacted += paths;
+                    removed += rows;
+                }
+                m => {
+                    return Err(NvError {
+                        reason: format!("unexpected response to compaction: {m}"),
+                    })
+                }
+            }
+        }
+        Ok(Message::Compacted {
+            paths: compacted,
+            rows: removed,
+        })
+    }
+
+    /// drop the live actor of `path` and everything the director tracks for
+    /// it - returns false if the actor was not live
+    fn release(&mut self, path: &str) -> bool {
+        if let Some(access) = self.last_access.remove(path) {
+            self.access_order.remove(&access);
+        }
+        self.latest_observed.remove(path);
+        self.unsnapshotted.remove(path);
+        self.over_high_water.remove(path);
+        self.actors.remove(path).is_some()
+    }
+
+    /// warn when the mailbox `name` crosses the high-water mark and note
+    /// when it drains below it again
+    fn check_high_water(&mut self, name: &str, queued: usize, capacity: usize) {
+        let Some(high_water) = self.options.mailbox_high_water else {
+            return;
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let full = queued as f64 >= high_water * capacity as f64;
+        if full && self.over_high_water.insert(name.to_string()) {
+            warn!("mailbox of {name} holds {queued} of {capacity} messages - consider a larger buffer");
+        } else if !full && self.over_high_water.remove(name) {
+            info!("mailbox of {name} drained to {queued} of {capacity} messages");
+        }
+    }
+
+    /// record how full the mailboxes of the director and of its store and
+    /// output are and check them against the high-water mark
+    fn watch_mailboxes(&mut self) {
+        let mut mailboxes = vec![(
+            "director",
+            self.receiver.len(),
+            self.receiver.max_capacity(),
+        )];
+        if let Some(store_actor) = &self.store_actor {
+            mailboxes.push(("store", store_actor.queued(), store_actor.capacity()));
+        }
+        if let Some(output) = &self.output {
+            mailboxes.push(("output", output.queued(), output.capacity()));
+        }
+        for (mailbox, queued, capacity) in mailboxes {
+            metrics::set_mailbox(&self.namespace, mailbox, queued, capacity);
+            let name = format!("{} {mailbox}", self.namespace);
+            self.check_high_water(&name, queued, capacity);
+        }
+    }
+
+    /// mark the live actor of `path` as the most recently used
+    fn touch(&mut self, path: &str) {
+        self.access_count += 1;
+        if let Some(access) = self.last_access.insert(path.to_string(), self.access_count) {
+            self.access_order.remove(&access);
+        }
+        self.access_order
+            .insert(self.access_count, path.to_string());
+    }
+
+    /// drop the least recently used actors until no more than
+    /// `max_resident_actors` are live - dropping the handle closes the
+    /// actor's mailbox and ends it
+    fn evict(&mut self) {
+        let (Some(max), Some(_)) = (self.options.max_resident_actors, &self.store_actor) else {
+            return;
+        };
+        // the actor that was just used is never evicted
+        while self.actors.len() > max.max(1) {
+            let Some((_, path)) = self.access_order.pop_first() else {
+                return;
+            };
+            debug!("evicting {path}");
+            self.release(&path);
+        }
+    }
+
+    /// the gene of the deepest mapping of `path` or of its ancestors - an
+    /// `Aggregate` mapping only applies to its own path.  a path without a
+    /// mapping gets the `default_gene` of the options, a gauge without one.
+    fn mapped_gene(&self, path: &str) -> (GeneType, GeneParams) {
+        let components: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut current_path = String::new();
+        let mut reg_gene_type = None;
+        let mut reg_params = None;
+
+        for (i, component) in components.iter().enumerate() {
+            current_path.push('/');
+            current_path.push_str(component);
+
+            if let Some(gt) = self.gene_path_map.get(&current_path) {
+                if *gt == GeneType::Aggregate && i + 1 < components.len() {
+                    continue;
+                }
+                reg_gene_type = Some(*gt);
+                reg_params = self.gene_params.get(&current_path);
+            }
+        }
+        (
+            reg_gene_type.unwrap_or(self.options.default_gene.unwrap_or(GeneType::Gauge)),
+            reg_params.cloned().unwrap_or_default(),
+        )
+    }
+
+    /// make sure there is a live actor for `path`, resurrected from the
+    /// journal if there is none - a resurrected aggregate is replayed the
+    /// state of its children.  returns false if the requester, and every
+    /// query coalesced with it, gave up first.
+    ///
+    /// the resurrection is awaited inline and the director reads its mailbox
+    /// one envelope at a time, so there is never more than one resurrection
+    /// in flight for a path and the queries queued behind it find the live
+    /// actor.
+    async fn make_live(
+        &mut self,
         path: &String,
-        message: Message<f64>,
-        respond_to: Option<Sender<NvResult<Message<f64>>>>,
+        requester: Option<&mut Sender<NvResult<Message<T>>>>,
+    ) -> bool {
+        if self.actors.contains_key(path) {
+            trace!("make_live found live instance");
+        } else {
+            trace!("make_live creating new or resurrected instance");
+            let mut coalesced = self.coalesce_queries(path).await;
+            // a requester that gives up, ie: an http request that times out, cancels the
+            // journal load so that the store stops streaming rows nobody will read.  the
+            // half-integrated actor is dropped and the next request resurrects it again.
+            let cancel = CancellationToken::new();
+            let abandonable =
+                requester.is_some() && coalesced.iter().all(|e| e.respond_to.is_some());
+            let actor = if abandonable {
+                let requesters = requester
+                    .into_iter()
+                    .chain(coalesced.iter_mut().filter_map(|e| e.respond_to.as_mut()));
+                let gone = futures::future::join_all(requesters.map(|r| r.closed()));
+                tokio::select! {
+                    actor = self.resurrect(path, &cancel, None) => actor,
+                    _ = gone => {
+                        cancel.cancel();
+                        warn!("requesters for {path} are gone, resurrection abandoned");
+                        return false;
+                    }
+                }
+            } else {
+                self.resurrect(path, &cancel, None).await
+            };
+            // the coalesced queries are answered next, by the live actor
+            for envelope in coalesced.into_iter().rev() {
+                self.deferred.push_front(envelope);
+            }
+            if self.options.observation_window.is_some() {
+                let latest = actor
+                    .ask(Message::Query {
+                        path: path.clone(),
+                        hint: MtHint::LatestObservation,
+                    })
+                    .await;
+                if let Ok(Message::StateReport { datetime, .. }) = latest {
+                    self.latest_observed.insert(path.clone(), datetime);
+                }
+            }
+            self.actors.insert(path.clone(), actor); // put it where you can find it again
+            if self.mapped_gene(path).0 == GeneType::Aggregate {
+                self.replay_children(path).await;
+            }
+        }
+        self.touch(path);
+        self.evict();
+        true
+    }
+
+    /// with a `resurrection_grace` the queries of the state of a cold `path`
+    /// that arrive within it are taken out of the mailbox to share its
+    /// resurrection - the first other message ends the grace early so that
+    /// nothing is handled out of order
+    async fn coalesce_queries(&mut self, path: &str) -> Vec<Envelope<T>> {
+        let mut coalesced = Vec::new();
+        let Some(grace) = self.options.resurrection_grace else {
+            return coalesced;
+        };
+        let deadline = tokio::time::Instant::now() + grace;
+        loop {
+            let next = match self.deferred.pop_front() {
+                Some(envelope) => Some(envelope),
+                None => tokio::time::timeout_at(deadline, self.receiver.recv())
+                    .await
+                    .ok()
+                    .flatten(),
+            };
+            match next {
+                Some(envelope)
+                    if matches!(
+                        &envelope.message,
+                        Message::Query { path: p, hint: MtHint::State } if p == path
+                    ) =>
+                {
+                    coalesced.push(envelope);
+                }
+                Some(envelope) => {
+                    self.deferred.push_front(envelope);
+                    break;
+                }
+                None => break,
+            }
+        }
+        debug!(
+            "{} queries of {path} share its resurrection",
+            coalesced.len()
+        );
+        coalesced
+    }
+
+    /// the envelopes put back by `coalesce_queries` are handled before the
+    /// mailbox
+    async fn next_envelope(&mut self) -> Option<Envelope<T>> {
+        match self.deferred.pop_front() {
+            Some(envelope) => Some(envelope),
+            None => self.receiver.recv().await,
+        }
+    }
+
+    /// journal the label of `idx` and have the output print it from now on
+    async fn handle_index_label(
+        &mut self,
+        idx: i32,
+        name: &str,
+        message: Message<T>,
+    ) -> NvResult<Message<T>> {
+        if name.is_empty() {
+            return Err(NvError {
+                reason: format!("the label of index {idx} is empty"),
+            });
+        }
+        journal_message(message.clone(), &self.store_actor).await?;
+        self.index_labels.insert(idx, name.to_string());
+        self.tell_index_labels().await;
+        Ok(message)
+    }
+
+    /// the index labels journaled for the namespace
+    async fn load_index_labels(&mut self) {
+        if let Some(store_actor) = &self.store_actor {
+            let query = Message::Query {
+                path: self.namespace.clone(),
+                hint: MtHint::IndexLabels,
+            };
+            match store_actor.ask(query).await {
+                Ok(Message::IndexLabels { labels }) => self.index_labels = labels,
+                r => error!("cannot load index labels: {r:?}"),
+            }
+        }
+        self.tell_index_labels().await;
+    }
+
+    async fn tell_index_labels(&self) {
+        if self.index_labels.is_empty() {
+            return;
+        }
+        if let Some(output) = &self.output {
+            let labels = self.index_labels.clone();
+            if let Err(e) = output.tell(Message::IndexLabels { labels }).await {
+                error!("cannot tell the output the index labels: {e}");
+            }
+        }
+    }
+
+    /// shut down every live actor, then the output, then the store so that
+    /// nothing is still writing to the store when it closes
+    async fn shut_down(&mut self) {
+        for (path, actor) in self.actors.drain() {
+            match actor.ask(Message::Shutdown).await {
+                Ok(Message::Stopped) => {}
+                r => error!("cannot shut down {path}: {r:?}"),
+            }
+        }
+        if let Some(output) = &self.output {
+            shut_down_output(output).await;
+        }
+        if let Some(store_actor) = &self.store_actor {
+            shut_down_output(store_actor).await;
+        }
+    }
+
+    /// the number of live actors and of the entries of their states - an actor
+    /// that does not answer is counted without entries
+    async fn director_info(&self) -> NvResult<Message<T>> {
+        let mut info = DirectorInfo {
+            actors: self.actors.len(),
+            state_entries: 0,
+        };
+        for (path, actor) in &self.actors {
+            match actor
+                .ask(Message::Query {
+                    path: path.clone(),
+                    hint: MtHint::State,
+                })
+                .await
+            {
+                Ok(Message::StateReport { values, .. }) => info.state_entries += values.len(),
+                r => warn!("cannot count the state of {path}: {r:?}"),
+            }
+        }
+        Ok(Message::DirectorInfoReport { info })
+    }
+
+    /// the journal stats of `path` from the store and the version of its
+    /// state from its actor, resurrected if it is not live
+    async fn handle_path_stats(
+        &mut self,
+        path: &String,
+        mut respond_to: Option<Sender<NvResult<Message<T>>>>,
     ) {
-        // resurrect and forward if this is either Update or Query
-        match self.actors.entry(path.clone()) {
-            Entry::Vacant(entry) => {
-                trace!("handle_update_or_query creating new or resurrected instance");
+        let Some(store_actor) = &self.store_actor else {
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("no journal to count the observations of {path} in"),
+                }),
+            );
+            return;
+        };
+        let stats = match store_actor
+            .ask(Message::PathStats { path: path.clone() })
+            .await
+        {
+            Ok(Message::PathStatsReport { stats, .. }) if stats.observations == 0 => {
+                respond_or_log_error(respond_to, Ok(Message::NotFound { path: path.clone() }));
+                return;
+            }
+            Ok(Message::PathStatsReport { stats, .. }) => stats,
+            r => {
+                respond_or_log_error(respond_to, r);
+                return;
+            }
+        };
+        if !self.make_live(path, respond_to.as_mut()).await {
+            return;
+        }
+        let Some(actor) = self.actors.get(path) else {
+            return;
+        };
+        let query = Message::Query {
+            path: path.clone(),
+            hint: MtHint::State,
+        };
+        let r = match actor.ask(query).await {
+            Ok(Message::StateReport { version, .. }) => Ok(Message::PathStatsReport {
+                path: path.clone(),
+                stats,
+                version,
+            }),
+            r => r,
+        };
+        respond_or_log_error(respond_to, r);
+    }
 
-                //
-                // BEGIN inline because of single mutable share compiler error when I put this
-                // in Director impl and try to mut borrow self twice
-                //
+    /// the derived observations of an aggregate are not journaled - it is
+    /// rebuilt from the state of every child it has in the graph
+    async fn replay_children(&mut self, path: &str) {
+        let children = match self.adjacency(path) {
+            Message::Adjacency { children, .. } => children,
+            _ => return,
+        };
+        debug!("replaying {} children to {path}", children.len());
+        for child in children {
+            if !Box::pin(self.make_live(&child, None)).await {
+                continue;
+            }
+            let Some(actor) = self.actors.get(&child) else {
+                continue;
+            };
+            let query = Message::Query {
+                path: child.clone(),
+                hint: MtHint::State,
+            };
+            if let Ok(report @ Message::StateReport { .. }) = actor.ask(query).await {
+                self.derive(path, &child, report).await;
+            }
+        }
+    }
 
-                let components: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-                let mut current_path = String::new();
-                let mut reg_gene_type = None;
+    /// the state of an observed child is derived into every aggregate above
+    /// it - the walk only ever goes up the path so a parent that is itself
+    /// the child of an aggregate passes its new state on without looping
+    async fn roll_up(&mut self, path: &str, report: Message<T>) {
+        let mut child = path.to_string();
+        let mut report = report;
+        while let Some(parent) = parent_path(&child) {
+            if self.mapped_gene(&parent).0 != GeneType::Aggregate {
+                break;
+            }
+            // a resurrected parent is replayed every child, this one included
+            if !Box::pin(self.make_live(&parent, None)).await {
+                break;
+            }
+            let Some(parent_report) = self.derive(&parent, &child, report).await else {
+                break;
+            };
+            self.publish(&parent_report);
+            child = parent;
+            report = parent_report;
+        }
+    }
 
-                for component in &components {
-                    current_path.push('/');
-                    current_path.push_str(component);
+    /// the retained events under `path` after the event id `after` - the
+    /// subscriber gets every later one until it falls behind or goes away
+    fn subscribe_events(
+        &mut self,
+        path: &str,
+        after: Option<u64>,
+        stream_to: mpsc::Sender<Message<T>>,
+    ) -> Message<T> {
+        let events = self
+            .events
+            .iter()
+            .filter(|event| match event {
+                Message::Event { id, report } => {
+                    after.is_none_or(|after| *id > after)
+                        && event_path(report).is_some_and(|p| is_under(p, path))
+                }
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        self.event_subscribers.push((path.to_string(), stream_to));
+        Message::Events { events }
+    }
 
-                    if let Some(gt) = self.gene_path_map.get(&current_path) {
-                        reg_gene_type = Some(*gt);
-                    }
+    /// number the state report or alert and send it to every events
+    /// subscriber of its path.  a subscriber that is not keeping up is
+    /// dropped rather than holding up the director - it can reconnect and
+    /// resume from its last event.
+    fn publish(&mut self, report: &Message<T>) {
+        let Some(path) = event_path(report) else {
+            return;
+        };
+        self.event_id += 1;
+        let event = Message::Event {
+            id: self.event_id,
+            report: Box::new(report.clone()),
+        };
+        self.event_subscribers.retain(|(prefix, subscriber)| {
+            if !is_under(path, prefix) {
+                return !subscriber.is_closed();
+            }
+            match subscriber.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("events subscriber under {prefix} is behind, dropped");
+                    false
                 }
-                let gene_type = reg_gene_type.unwrap_or(GeneType::Gauge);
-
-                //
-                // END inline
-                //
-
-                let actor = state_actor::new(path.clone(), 8, get_gene(gene_type), None);
-                if let Some(store_actor) = &self.store_actor {
-                    actor
-                        .integrate(String::from(path), store_actor, MtHint::Update)
-                        .await
-                        .map_err(|e| {
-                            error!("can not load actor {e} from journal");
-                        })
-                        .ok();
-                }
-                let jrnled = write_jrnl(message.clone(), &self.store_actor).await;
-                match jrnled {
-                    Ok(Message::Persisted) => {
-                        send_to_actor(message, respond_to, &actor, &self.output).await;
-                    }
-                    Ok(Message::ConstraintViolation) => {
-                        respond_or_log_error(respond_to, Ok(Message::ConstraintViolation {}));
-                    }
-                    _ => {
-                        respond_or_log_error(respond_to, jrnled);
-                    }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    debug!("events subscriber under {prefix} is gone");
+                    false
                 }
-                entry.insert(actor); // put it where you can find it again
             }
-            Entry::Occupied(entry) => {
-                trace!("handle_update_or_query found live instance");
-                let actor = entry.get();
-                let jrnled = write_jrnl(message.clone(), &self.store_actor).await;
-                // todo: return meaningful errors
-                match jrnled {
-                    Ok(Message::Persisted) => {
-                        send_to_actor(message, respond_to, actor, &self.output).await;
-                    }
-                    Ok(Message::ConstraintViolation) => {
-                        respond_or_log_error(respond_to, Ok(Message::ConstraintViolation {}));
-                    }
-                    _ => {
-                        respond_or_log_error(respond_to, jrnled);
+        });
+        if self.events.len() == EVENT_BACKLOG {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// pass the alerts the actors raised on to the output and the events
+    /// subscribers - an actor sends its alerts before it answers the
+    /// observation that raised them
+    async fn forward_alerts(&mut self) {
+        while let Ok(alert) = self.alerts.try_recv() {
+            self.publish(&alert);
+            if let Some(output) = &self.output {
+                if let Err(e) = output.tell(alert).await {
+                    error!("cannot forward alert: {e:?}");
+                }
+            }
+        }
+    }
+
+    /// apply the state report of `child` to the live aggregate `path`
+    async fn derive(&self, path: &str, child: &str, report: Message<T>) -> Option<Message<T>> {
+        let Message::StateReport {
+            datetime, values, ..
+        } = report
+        else {
+            return None;
+        };
+        if values.is_empty() {
+            return None;
+        }
+        let actor = self.actors.get(path)?;
+        let mut labels = Labels::new();
+        labels.insert(CHILD_LABEL.to_string(), child.to_string());
+        let derived = Message::Observations {
+            path: path.to_string(),
+            datetime,
+            values,
+            labels,
+        };
+        match actor.ask(derived).await {
+            Ok(report @ Message::StateReport { .. }) => Some(report),
+            r => {
+                warn!("cannot derive {child} into {path}: {r:?}");
+                None
+            }
+        }
+    }
+
+    /// a new gene of the kind `path` is mapped to
+    fn gene_of(&self, path: &str) -> Box<dyn Gene<T> + Send + Sync> {
+        let (gene_type, params) = self.mapped_gene(path);
+        // a mapping from before the value type changed may name a gene that
+        // does not apply to it
+        T::gene(gene_type, &params).unwrap_or_else(|| {
+            warn!(
+                "gene {gene_type} does not apply to {} values of {path}",
+                T::NAME
+            );
+            Box::<GaugeGene>::default()
+        })
+    }
+
+    /// find the gene for the new actor, create it, and replay its journal -
+    /// an actor created with `replay_to` reports its state to it after every
+    /// event of the replay
+    #[instrument]
+    async fn resurrect(
+        &self,
+        path: &String,
+        cancel: &CancellationToken,
+        replay_to: Option<Handle<T>>,
+    ) -> Handle<T> {
+        let gene = self.gene_of(path);
+        let actor = match replay_to {
+            Some(replay_to) => state_actor::new_replaying(path.clone(), 8, gene, replay_to),
+            None => state_actor::new_alerting(path.clone(), 8, gene, self.alert_sender.clone()),
+        };
+        if let Some(store_actor) = &self.store_actor {
+            actor
+                .integrate_until(
+                    String::from(path),
+                    store_actor,
+                    MtHint::Update,
+                    cancel.clone(),
+                )
+                .await
+                .map_err(|e| {
+                    error!("can not load actor {e} from journal");
+                })
+                .ok();
+        }
+        actor
+    }
+
+    /// take a token from the bucket of `path` for an observation - false when
+    /// the path is over `max_rate_per_path` and the observation is rejected
+    fn within_rate_limit(&mut self, path: &str) -> bool {
+        let Some(rate) = self.options.max_rate_per_path else {
+            return true;
+        };
+        let burst = rate.max(1.0);
+        let now = Instant::now();
+        // sweep again once the buckets have doubled since the last sweep
+        if self.rate_limits.len() >= RATE_LIMIT_SWEEP.max(2 * self.rate_limits_kept) {
+            self.rate_limits.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.refilled).as_secs_f64() * rate < burst
+            });
+            self.rate_limits_kept = self.rate_limits.len();
+        }
+        let bucket = self
+            .rate_limits
+            .entry(path.to_string())
+            .or_insert(TokenBucket {
+                tokens: burst,
+                refilled: now,
+            });
+        let refill = now.duration_since(bucket.refilled).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refill).min(burst);
+        bucket.refilled = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// the observation with its values rounded to `decimals` - a `NaN` or an
+    /// infinity, that no decoder may have checked, is rejected rather than
+    /// journaled as a null that can not be replayed
+    fn checked_values(&self, message: Message<T>) -> NvResult<Message<T>> {
+        let Message::Observations {
+            path,
+            datetime,
+            mut values,
+            labels,
+        } = message
+        else {
+            return Ok(message);
+        };
+        if let Some((idx, _)) = values.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(NvError {
+                reason: format!("value of index {idx} is not finite"),
+            });
+        }
+        if let Some(decimals) = self.options.decimals {
+            for value in values.values_mut() {
+                *value = value.rounded(decimals);
+            }
+        }
+        Ok(Message::Observations {
+            path,
+            datetime,
+            values,
+            labels,
+        })
+    }
+
+    /// an observation more than `observation_window` older than the latest
+    /// one seen for its path is rejected before it is journaled so that a
+    /// resurrection replays exactly what the live actor accepted - a window
+    /// reaching back before the earliest datetime bounds nothing
+    fn check_observation_window(&self, path: &str, message: &Message<T>) -> NvResult<()> {
+        if let (Some(window), Message::Observations { datetime, .. }) =
+            (self.options.observation_window, message)
+        {
+            if let Some(latest) = self.latest_observed.get(path) {
+                if latest
+                    .checked_sub(window)
+                    .is_some_and(|earliest| *datetime < earliest)
+                {
+                    return Err(NvError {
+                        reason: format!(
+                            "{path} observation at {datetime} is more than {window} older than the latest at {latest}"
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// persist a snapshot of the actor of `path` once `snapshot_interval`
+    /// observations were applied to it since its last one - the snapshot is
+    /// only an optimization so failing to take one is not an error.  like
+    /// `compact`, a path whose gene does not compact is never snapshotted.
+    async fn snapshot_if_due(&mut self, path: &str) {
+        let (Some(interval), Some(store_actor)) =
+            (self.options.snapshot_interval, &self.store_actor)
+        else {
+            return;
+        };
+        let applied = self.unsnapshotted.entry(path.to_string()).or_insert(0);
+        *applied += 1;
+        if *applied < interval {
+            return;
+        }
+        *applied = 0;
+
+        let (gene_type, params) = self.mapped_gene(path);
+        if !T::gene(gene_type, &params).is_some_and(|gene| gene.compacts()) {
+            trace!("{path} is not snapshotted, its {gene_type} gene does not compact");
+            return;
+        }
+        let Some(actor) = self.actors.get(path) else {
+            return;
+        };
+        let latest = actor
+            .ask(Message::Query {
+                path: path.to_string(),
+                hint: MtHint::LatestObservation,
+            })
+            .await;
+        let Ok(Message::StateReport {
+            datetime,
+            values,
+            version,
+            ..
+        }) = latest
+        else {
+            warn!("cannot snapshot {path}: {latest:?}");
+            return;
+        };
+        let snapshot = Message::Snapshot {
+            path: path.to_string(),
+            datetime,
+            values,
+            version,
+        };
+        match store_actor.ask(snapshot).await {
+            Ok(_) => debug!("snapshot of {path} taken"),
+            Err(e) => warn!("cannot snapshot {path}: {e}"),
+        }
+    }
+
+    /// apply the observations only if their actor is still at `version` -
+    /// the director reads one envelope at a time so nothing can be applied
+    /// between the check and the update
+    async fn handle_conditional(
+        &mut self,
+        version: u64,
+        observations: Message<T>,
+        mut respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let Message::Observations { path, .. } = &observations else {
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("not observations: {observations}"),
+                }),
+            );
+            return;
+        };
+        let path = path.clone();
+        if !self.make_live(&path, respond_to.as_mut()).await {
+            return;
+        }
+        let Some(actor) = self.actors.get(&path) else {
+            return;
+        };
+        let current = actor
+            .ask(Message::Query {
+                path: path.clone(),
+                hint: MtHint::State,
+            })
+            .await;
+        match current {
+            Ok(Message::StateReport {
+                version: current, ..
+            }) if current != version => {
+                debug!("{path} is at version {current} not {version}");
+                respond_or_log_error(
+                    respond_to,
+                    Ok(Message::VersionConflict {
+                        path,
+                        version: current,
+                    }),
+                );
+            }
+            Ok(Message::StateReport { .. }) => {
+                self.handle_update_or_query(&path, observations, respond_to)
+                    .await;
+            }
+            r => respond_or_log_error(respond_to, r),
+        }
+    }
+
+    #[instrument(skip_all, fields(namespace = %self.namespace, path = %path, msg = message.kind()))]
+    async fn handle_update_or_query(
+        &mut self,
+        path: &String,
+        message: Message<T>,
+        mut respond_to: Option<Sender<NvResult<Message<T>>>>,
+    ) {
+        let is_observation = matches!(message, Message::Observations { .. });
+        let message = match self.checked_values(message) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("{e}");
+                self.failed_observations += 1;
+                respond_or_log_error(respond_to, Err(e.clone()));
+                forward_actor_result(path, Err(e), &self.output).await;
+                return;
+            }
+        };
+        // before the resurrection so that a flooded path costs the store
+        // nothing
+        if is_observation && !self.within_rate_limit(path) {
+            warn!("{path} is over its rate limit - observation rejected");
+            self.failed_observations += 1;
+            let limited = Message::RateLimited { path: path.clone() };
+            respond_or_log_error(respond_to, Ok(limited.clone()));
+            forward_actor_result(path, Ok(limited), &self.output).await;
+            return;
+        }
+
+        // resurrect and forward if this is either Update or Query
+        if !self.make_live(path, respond_to.as_mut()).await {
+            return;
+        }
+
+        if let Err(e) = self.check_observation_window(path, &message) {
+            warn!("{e}");
+            self.failed_observations += 1;
+            respond_or_log_error(respond_to, Err(e.clone()));
+            forward_actor_result(path, Err(e), &self.output).await;
+            return;
+        }
+
+        let mailbox = self
+            .actors
+            .get(path)
+            .map(|actor| (actor.queued(), actor.capacity()));
+        if let Some((queued, capacity)) = mailbox {
+            self.check_high_water(path, queued, capacity);
+        }
+        let Some(actor) = self.actors.get(path) else {
+            return;
+        };
+        let jrnled = write_jrnl(message.clone(), &self.store_actor).await;
+        // todo: return meaningful errors
+        match jrnled {
+            Ok(Message::Persisted) => {
+                let datetime = match &message {
+                    Message::Observations { datetime, .. } => Some(*datetime),
+                    _ => None,
+                };
+                let (counter, message_type) = match &message {
+                    Message::Query {
+                        hint: MtHint::Bucket(_),
+                        ..
+                    } => (Counter::Queries, "bucket"),
+                    Message::Query { .. } | Message::IndicesQuery { .. } => {
+                        (Counter::Queries, "state")
                     }
+                    Message::Subscribe { .. } => (Counter::Queries, "subscribe"),
+                    _ => (Counter::Observations, "observations"),
                 };
+                let report = send_to_actor(path, message, respond_to, actor, &self.output).await;
+                let applied = report.is_some();
+                if applied {
+                    metrics::inc(counter, &self.namespace, message_type);
+                }
+                if is_observation && applied {
+                    self.ingested_observations += 1;
+                }
+                if is_observation && !applied {
+                    self.failed_observations += 1;
+                }
+                if let (true, Some(report)) = (is_observation, report) {
+                    self.publish(&report);
+                    self.forward_alerts().await;
+                    self.snapshot_if_due(path).await;
+                    self.register_path(path).await;
+                    self.roll_up(path, report).await;
+                }
+                if let (Some(datetime), true) = (datetime, applied) {
+                    if self.options.observation_window.is_some() {
+                        let latest = self.latest_observed.entry(path.clone()).or_insert(datetime);
+                        if datetime > *latest {
+                            *latest = datetime;
+                        }
+                    }
+                }
+            }
+            Ok(Message::ConstraintViolation) => {
+                debug!("duplicate observation of {path} ignored");
+                self.duplicate_observations += 1;
+                respond_or_log_error(respond_to, Ok(Message::ConstraintViolation {}));
+            }
+            _ => {
+                if is_observation {
+                    self.failed_observations += 1;
+                }
+                respond_or_log_error(respond_to, jrnled);
             }
         };
     }
 
     fn new(
         namespace: String,
-        receiver: mpsc::Receiver<Envelope<f64>>,
-        output: Option<Handle>,
-        store_actor: Option<Handle>,
+        receiver: mpsc::Receiver<Envelope<T>>,
+        output: Option<Handle<T>>,
+        store_actor: Option<Handle<T>>,
+        options: DirectorOptions,
     ) -> Self {
+        let (alert_sender, alerts) = mpsc::channel(ALERT_BUFFER);
         Self {
             namespace,
             actors: HashMap::new(),
@@ -501,6 +2278,28 @@ impl Director {
             output,
             store_actor,
             gene_path_map: HashMap::new(),
+            gene_params: HashMap::new(),
+            latest_observed: HashMap::new(),
+            rate_limits: HashMap::new(),
+            rate_limits_kept: 0,
+            unsnapshotted: HashMap::new(),
+            access_order: BTreeMap::new(),
+            last_access: HashMap::new(),
+            access_count: 0,
+            failed_observations: 0,
+            ingested_observations: 0,
+            duplicate_observations: 0,
+            graph: DiGraph::new(),
+            graph_nodes: HashMap::new(),
+            over_high_water: HashSet::new(),
+            event_subscribers: Vec::new(),
+            events: VecDeque::new(),
+            event_id: 0,
+            alert_sender,
+            alerts,
+            index_labels: IndexLabels::new(),
+            deferred: VecDeque::new(),
+            options,
         }
     }
 }
@@ -513,17 +2312,59 @@ pub fn new(
     output: Option<Handle>,
     store_actor: Option<Handle>,
 ) -> Handle {
+    new_with_options(
+        namespace,
+        bufsz,
+        output,
+        store_actor,
+        DirectorOptions::default(),
+    )
+}
+
+/// actor handle public constructor for a director with non-default behavior
+#[must_use]
+pub fn new_with_options(
+    namespace: &str,
+    bufsz: usize,
+    output: Option<Handle>,
+    store_actor: Option<Handle>,
+    options: DirectorOptions,
+) -> Handle {
+    new_of_type(namespace, bufsz, output, store_actor, options)
+}
+
+/// `new_with_options` for a director whose actors keep their state in `T`,
+/// ie: `i64` - a gene that does not apply to `T` can not be mapped
+#[must_use]
+pub fn new_of_type<T: Value>(
+    namespace: &str,
+    bufsz: usize,
+    output: Option<Handle<T>>,
+    store_actor: Option<Handle<T>>,
+    options: DirectorOptions,
+) -> Handle<T> {
     #[instrument]
-    async fn start(mut actor: Director) {
+    async fn start<T: Value>(mut actor: Director<T>) {
         actor.start().await;
-        while let Some(envelope) = actor.receiver.recv().await {
+        while let Some(envelope) = actor.next_envelope().await {
+            let stop = matches!(envelope.message, Message::Stop | Message::Shutdown);
+            actor.watch_mailboxes();
             actor.handle_envelope(envelope).await;
+            if stop {
+                break;
+            }
         }
     }
 
     let (sender, receiver) = mpsc::channel(bufsz);
 
-    let actor = Director::new(namespace.to_string(), receiver, output, store_actor);
+    let actor = Director::new(
+        namespace.to_string(),
+        receiver,
+        output,
+        store_actor,
+        options,
+    );
 
     let actor_handle = Handle::new(sender);
 
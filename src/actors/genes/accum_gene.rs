@@ -7,6 +7,13 @@
 //! `AccumGene` struct includes a default implementation that sets the gene's time scope to
 //! `Forever` and its base time to the current UTC time.
 //!
+//! An `AccumGene` with a time scope other than `Forever` starts its sums fresh whenever an
+//! observation falls in a later period than the last one applied - the periods are counted from
+//! `base_time`, which defaults to the unix epoch so that they line up with UTC clock boundaries
+//! and come out the same when the journal is replayed. Observations from an earlier period than the
+//! current one are ignored. A gene that retains buckets leaves that to the state actor, which
+//! keeps every period's sums instead.
+//!
 //! The module exports the `AccumGene` struct, which can be used in the larger system for processing
 //! incoming data from `IoT` devices.
 use crate::actors::actor::State;
@@ -15,12 +22,41 @@ use crate::actors::genes::gene::TimeScope;
 use crate::actors::message::Message;
 use crate::actors::operator::{Accumulator, OpError, Operator, OperatorResult};
 use std::ops::Add;
+use std::sync::Mutex;
 use time::OffsetDateTime;
 use tracing::trace;
 
 pub struct AccumGene {
     pub time_scope: TimeScope,
     pub base_time: OffsetDateTime,
+    /// keep the sums of past periods rather than forgetting them
+    pub retain_buckets: bool,
+    /// the period of the latest observation applied
+    pub current_bucket: Mutex<Option<i64>>,
+}
+
+impl AccumGene {
+    /// whether an observation at `datetime` is applied and whether it
+    /// starts a new period - the current period moves forward as needed
+    fn enter_bucket(&self, datetime: OffsetDateTime) -> OperatorResult<(bool, bool)> {
+        if self.retain_buckets {
+            return Ok((true, false));
+        }
+        let Some(bucket) = self.time_scope.bucket(self.base_time, datetime) else {
+            return Ok((true, false));
+        };
+        let mut current = self.current_bucket.lock().map_err(|e| OpError {
+            reason: format!("current bucket unavailable: {e}"),
+        })?;
+        match *current {
+            Some(c) if bucket < c => Ok((false, false)),
+            Some(c) if bucket == c => Ok((true, false)),
+            _ => {
+                *current = Some(bucket);
+                Ok((true, true))
+            }
+        }
+    }
 }
 
 fn update_state_with_val<T: Add<Output = T> + Copy>(
@@ -38,10 +74,16 @@ impl<T: Add<Output = T> + Copy> Gene<T> for AccumGene {
     fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
         match update {
             Message::Observations {
-                path: _,
-                datetime,
-                values,
+                datetime, values, ..
             } => {
+                let (apply, fresh) = self.enter_bucket(datetime)?;
+                if !apply {
+                    trace!("ignoring observation at {datetime} from an earlier period");
+                    return Ok(state);
+                }
+                if fresh {
+                    state = State::new();
+                }
                 for &idx in values.keys() {
                     let in_val = *values.get(&idx).ok_or_else(|| OpError {
                         reason: format!("unsupported idx: {idx}"),
@@ -62,13 +104,28 @@ impl<T: Add<Output = T> + Copy> Gene<T> for AccumGene {
     fn get_time_scope(&self) -> &TimeScope {
         &self.time_scope
     }
+    fn retains_buckets(&self) -> bool {
+        self.retain_buckets
+    }
+    /// the current period of a time scoped accumulator is not in the state
+    /// so only sums kept forever compact
+    fn compacts(&self) -> bool {
+        matches!(self.time_scope, TimeScope::Forever) && !self.retain_buckets
+    }
+    fn reset(&self) {
+        if let Ok(mut current) = self.current_bucket.lock() {
+            *current = None;
+        }
+    }
 }
 
 impl Default for AccumGene {
     fn default() -> Self {
         Self {
             time_scope: TimeScope::Forever,
-            base_time: OffsetDateTime::now_utc(),
+            base_time: OffsetDateTime::UNIX_EPOCH,
+            retain_buckets: false,
+            current_bucket: Mutex::new(None),
         }
     }
 }
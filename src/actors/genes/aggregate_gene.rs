@@ -0,0 +1,112 @@
+//! This module provides the implementation for the `AggregateGene` struct, which represents a gene
+//! whose state is the sum, or the mean, of the states of the direct children of its path, ie: a
+//! floor that rolls up its rooms.
+//!
+//! The director derives an observation for the parent every time the state of a child changes and
+//! labels it with the path of the child under `CHILD_LABEL`. The gene keeps the latest state of
+//! every child itself and reports their aggregate index by index - an index is aggregated over the
+//! children that report it. The derived observations are not journaled, the director replays the
+//! state of every child to a resurrected parent instead.
+//!
+//! The function of a gene is configured with the gene mapping params, ie: `{"aggregate": "mean"}`,
+//! and is persisted along with the mapping.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::AggregateFn;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{OpError, OperatorResult};
+use std::collections::BTreeMap;
+use std::ops::Add;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+
+/// the label of a derived observation that names the child it came from
+pub const CHILD_LABEL: &str = "aggregate_child";
+
+/// the values an `AggregateGene` can combine - only fractions have a mean
+pub trait Aggregable: Add<Output = Self> + Copy {
+    /// the mean of `count` values that add up to `sum`, `None` if the type
+    /// can not hold it
+    fn mean(sum: Self, count: usize) -> Option<Self>;
+}
+
+impl Aggregable for f64 {
+    fn mean(sum: Self, count: usize) -> Option<Self> {
+        #[allow(clippy::cast_precision_loss)]
+        Some(sum / count as Self)
+    }
+}
+
+impl Aggregable for i64 {
+    fn mean(_sum: Self, _count: usize) -> Option<Self> {
+        None
+    }
+}
+
+pub struct AggregateGene<T> {
+    pub function: AggregateFn,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+    /// the latest state of every child by path
+    pub children: Mutex<BTreeMap<String, State<T>>>,
+}
+
+impl<T: Aggregable> Gene<T> for AggregateGene<T> {
+    fn apply_operators(&self, _state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations { values, labels, .. } => {
+                let Some(child) = labels.get(CHILD_LABEL) else {
+                    return Err(OpError {
+                        reason: "an aggregate is only observed through its children".to_string(),
+                    });
+                };
+                let mut children = self.children.lock().map_err(|e| OpError {
+                    reason: format!("children unavailable: {e}"),
+                })?;
+                children.insert(child.clone(), values);
+
+                let mut sums: State<(T, usize)> = State::new();
+                for values in children.values() {
+                    for (&idx, &in_val) in values {
+                        sums.entry(idx)
+                            .and_modify(|(sum, count)| {
+                                *sum = *sum + in_val;
+                                *count += 1;
+                            })
+                            .or_insert((in_val, 1));
+                    }
+                }
+                sums.into_iter()
+                    .map(|(idx, (sum, count))| {
+                        let value = match self.function {
+                            AggregateFn::Sum => Some(sum),
+                            AggregateFn::Mean => T::mean(sum, count),
+                        };
+                        value.map(|value| (idx, value)).ok_or_else(|| OpError {
+                            reason: format!("no {} of idx {idx}", self.function),
+                        })
+                    })
+                    .collect()
+            }
+            _ => Err(OpError {
+                reason: "unsupported message type".to_string(),
+            }),
+        }
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+}
+
+impl<T> AggregateGene<T> {
+    #[must_use]
+    pub fn new(function: AggregateFn) -> Self {
+        Self {
+            function,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+            children: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
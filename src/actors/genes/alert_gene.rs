@@ -0,0 +1,119 @@
+//! This module provides the implementation for the `AlertGene` struct, which represents a gene that
+//! keeps the latest reading of every index like a gauge and also watches some of them for
+//! anomalies. When an observation moves an index above its `high` bound or below its `low` bound
+//! the gene reports the crossing and the state actor forwards a `Message::Alert`. An index stays
+//! alerted until it is back within its bounds - readings that stay out of bounds do not raise
+//! another alert.
+//!
+//! The bounds are configured with the gene mapping params, ie:
+//! `{"thresholds": {"1": {"low": 0.0, "high": 80.0}}}`, and are persisted along with the mapping.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::Threshold;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{Gauge, OpError, Operator, OperatorResult};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+pub struct AlertGene {
+    pub thresholds: BTreeMap<i32, Threshold>,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl AlertGene {
+    /// the gene watching the `thresholds` of `params`
+    #[must_use]
+    pub fn with_params(params: &GeneParams) -> Self {
+        Self {
+            thresholds: params.thresholds.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Gene<f64> for AlertGene {
+    fn apply_operators(
+        &self,
+        mut state: State<f64>,
+        update: Message<f64>,
+    ) -> OperatorResult<State<f64>> {
+        match update {
+            Message::Observations {
+                datetime, values, ..
+            } => {
+                for (&idx, &in_val) in &values {
+                    let new_val = Gauge::apply(&state, idx, in_val, datetime)?;
+                    state.insert(idx, new_val);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.thresholds.is_empty() {
+            return Err(OpError {
+                reason: "no thresholds to watch".to_string(),
+            });
+        }
+        for (idx, threshold) in &self.thresholds {
+            match (threshold.low, threshold.high) {
+                (None, None) => {
+                    return Err(OpError {
+                        reason: format!("threshold of idx {idx} has no bounds"),
+                    })
+                }
+                (Some(low), Some(high)) if low > high => {
+                    return Err(OpError {
+                        reason: format!("threshold of idx {idx} has low {low} above high {high}"),
+                    })
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    fn accepts_late_reports(&self) -> bool {
+        false
+    }
+    fn crossings(&self, before: &State<f64>, after: &State<f64>) -> Vec<(i32, f64, f64)> {
+        self.thresholds
+            .iter()
+            .filter_map(|(&idx, threshold)| {
+                let value = *after.get(&idx)?;
+                let previous = before.get(&idx);
+                if let Some(high) = threshold.high {
+                    if value > high && previous.is_none_or(|previous| *previous <= high) {
+                        return Some((idx, value, high));
+                    }
+                }
+                if let Some(low) = threshold.low {
+                    if value < low && previous.is_none_or(|previous| *previous >= low) {
+                        return Some((idx, value, low));
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+}
+
+impl Default for AlertGene {
+    fn default() -> Self {
+        Self {
+            thresholds: BTreeMap::new(),
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
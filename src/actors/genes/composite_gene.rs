@@ -0,0 +1,126 @@
+//! This module provides the implementation for the `CompositeGene` struct, which represents a gene
+//! whose indexes are partitioned into ranges that each have their own operator - the
+//! generalization of the `GaugeAndAccumGene` and its two fixed ranges. A device that reports
+//! temperatures at 0 to 9, energy totals at 10 to 19, and peak loads at 20 to 29 can map its path
+//! to a single gene that keeps a gauge, an accumulator, and a max of those ranges.
+//!
+//! The ranges are configured with the gene mapping params, ie:
+//! `{"ranges": [{"first_idx": 0, "slots": 10, "operator": "gauge"}, {"first_idx": 10, "slots": 10,
+//! "operator": "accum"}]}`, and are persisted along with the mapping. An observation of an index
+//! outside of every range is rejected.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::IndexOperator;
+use crate::actors::genes::gene::IndexRange;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{
+    Accumulator, Gauge, MaxOperator, MinOperator, OpError, Operator, OperatorResult,
+};
+use std::ops::Add;
+use time::OffsetDateTime;
+
+pub struct CompositeGene {
+    pub ranges: Vec<IndexRange>,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl CompositeGene {
+    /// the gene partitioned into the `ranges` of `params`
+    #[must_use]
+    pub fn with_params(params: &GeneParams) -> Self {
+        Self {
+            ranges: params.ranges.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// the operator of the range `idx` falls in
+    fn operator(&self, idx: i32) -> Option<IndexOperator> {
+        self.ranges
+            .iter()
+            .find(|range| {
+                (range.first_idx..range.first_idx.saturating_add(range.slots)).contains(&idx)
+            })
+            .map(|range| range.operator)
+    }
+}
+
+impl<T: Add<Output = T> + Copy + PartialOrd> Gene<T> for CompositeGene {
+    fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations {
+                datetime, values, ..
+            } => {
+                for (&idx, &in_val) in &values {
+                    let new_val = match self.operator(idx) {
+                        Some(IndexOperator::Gauge) => Gauge::apply(&state, idx, in_val, datetime)?,
+                        Some(IndexOperator::Accum) => {
+                            Accumulator::apply(&state, idx, in_val, datetime)?
+                        }
+                        Some(IndexOperator::Min) => {
+                            MinOperator::apply(&state, idx, in_val, datetime)?
+                        }
+                        Some(IndexOperator::Max) => {
+                            MaxOperator::apply(&state, idx, in_val, datetime)?
+                        }
+                        None => {
+                            return Err(OpError {
+                                reason: format!("unsupported idx: {idx}"),
+                            })
+                        }
+                    };
+                    state.insert(idx, new_val);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.ranges.is_empty() {
+            return Err(OpError {
+                reason: "no ranges to apply operators to".to_string(),
+            });
+        }
+        for (i, range) in self.ranges.iter().enumerate() {
+            if range.slots < 0 {
+                return Err(OpError {
+                    reason: "slots can not be negative".to_string(),
+                });
+            }
+            let end = range.first_idx.saturating_add(range.slots);
+            for other in &self.ranges[i + 1..] {
+                let other_end = other.first_idx.saturating_add(other.slots);
+                if range.first_idx < other_end && other.first_idx < end {
+                    return Err(OpError {
+                        reason: format!(
+                            "{} range {}..{end} overlaps {} range {}..{other_end}",
+                            range.operator, range.first_idx, other.operator, other.first_idx
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CompositeGene {
+    fn default() -> Self {
+        Self {
+            ranges: vec![],
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! This module provides the implementation for the `DeltaGene` struct, which represents a gene for
+//! meters that report a cumulative reading, ie: the kWh of an electricity meter. The reading of its
+//! `source_idx` is kept as a gauge and the difference from the previous reading is written to the
+//! companion `delta_idx`, so that both the meter and the consumption since its last report are part
+//! of the state. Every other index is a gauge.
+//!
+//! The previous reading is the gauge of the source index in the state itself, so it is rebuilt by
+//! the ordinary journal replay, or comes with a snapshot, when the actor is resurrected. The delta
+//! index is left out of the state until the second reading, and a meter that rolls over or is
+//! replaced reports a negative delta. Observations must not report values for the delta index.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{OpError, OperatorResult};
+use std::ops::{Add, Sub};
+use time::OffsetDateTime;
+
+pub struct DeltaGene {
+    /// the index of the cumulative reading
+    pub source_idx: i32,
+    /// the index the difference from the previous reading is written to
+    pub delta_idx: i32,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl DeltaGene {
+    /// the default gene with the indices set in `params` replacing the defaults
+    #[must_use]
+    pub fn with_params(params: &GeneParams) -> Self {
+        let gene = Self::default();
+        Self {
+            source_idx: params.source_idx.unwrap_or(gene.source_idx),
+            delta_idx: params.delta_idx.unwrap_or(gene.delta_idx),
+            ..gene
+        }
+    }
+}
+
+impl<T: Add<Output = T> + Sub<Output = T> + Copy> Gene<T> for DeltaGene {
+    fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations { values, .. } => {
+                if values.contains_key(&self.delta_idx) {
+                    return Err(OpError {
+                        reason: format!(
+                            "unsupported idx: {} is the delta of idx {}",
+                            self.delta_idx, self.source_idx
+                        ),
+                    });
+                }
+                for (&idx, &in_val) in &values {
+                    if idx == self.source_idx {
+                        if let Some(&prev_val) = state.get(&idx) {
+                            state.insert(self.delta_idx, in_val - prev_val);
+                        }
+                    }
+                    state.insert(idx, in_val);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.source_idx == self.delta_idx {
+            return Err(OpError {
+                reason: format!(
+                    "delta idx {} is the source idx of the readings",
+                    self.delta_idx
+                ),
+            });
+        }
+        Ok(())
+    }
+    fn accepts_late_reports(&self) -> bool {
+        false
+    }
+}
+
+impl Default for DeltaGene {
+    fn default() -> Self {
+        Self {
+            source_idx: 0,
+            delta_idx: 100,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
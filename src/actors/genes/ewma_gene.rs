@@ -0,0 +1,71 @@
+//! This module provides the implementation for the `EwmaGene` struct, which represents a gene that
+//! smooths noisy readings with an exponentially weighted moving average. Every new observation
+//! moves the state of its index `alpha` of the way towards the reported value - a small `alpha`
+//! smooths more and reacts slower. The first observation of an index is applied as a plain gauge.
+//!
+//! The `alpha` of a gene is configured with the gene mapping params, ie: `{"alpha": 0.2}`, and is
+//! persisted along with the mapping.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{Gauge, OpError, Operator, OperatorResult};
+use time::OffsetDateTime;
+
+pub const DEFAULT_ALPHA: f64 = 0.5;
+
+pub struct EwmaGene {
+    pub alpha: f64,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl Gene<f64> for EwmaGene {
+    fn apply_operators(
+        &self,
+        mut state: State<f64>,
+        update: Message<f64>,
+    ) -> OperatorResult<State<f64>> {
+        match update {
+            Message::Observations {
+                datetime, values, ..
+            } => {
+                for (&idx, &in_val) in &values {
+                    let new_val = match state.get(&idx) {
+                        Some(old_val) => self.alpha.mul_add(in_val, (1.0 - self.alpha) * old_val),
+                        None => Gauge::apply(&state, idx, in_val, datetime)?,
+                    };
+                    state.insert(idx, new_val);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.alpha > 0.0 && self.alpha <= 1.0 {
+            Ok(())
+        } else {
+            Err(OpError {
+                reason: format!("alpha {} is not in (0, 1]", self.alpha),
+            })
+        }
+    }
+}
+
+impl Default for EwmaGene {
+    fn default() -> Self {
+        Self {
+            alpha: DEFAULT_ALPHA,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::actors::actor::State;
 use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::TimeScope;
 use crate::actors::message::Message;
 use crate::actors::operator::{Accumulator, Gauge, OpError, Operator, OperatorResult};
@@ -18,6 +19,21 @@ pub struct GaugeAndAccumGene {
 }
 
 impl GaugeAndAccumGene {
+    /// the default gene with any ranges set in `params` replacing the defaults
+    #[must_use]
+    pub fn with_params(params: &GeneParams) -> Self {
+        let gene = Self::default();
+        Self {
+            guage_first_idx: params.guage_first_idx.unwrap_or(gene.guage_first_idx),
+            guage_slots: params.guage_slots.unwrap_or(gene.guage_slots),
+            accumulator_first_idx: params
+                .accumulator_first_idx
+                .unwrap_or(gene.accumulator_first_idx),
+            accumulator_slots: params.accumulator_slots.unwrap_or(gene.accumulator_slots),
+            ..gene
+        }
+    }
+
     fn update_state_with_val<T: Add<Output = T> + Copy>(
         &self,
         in_val: T,
@@ -50,9 +66,7 @@ impl<T: Add<Output = T> + Copy> Gene<T> for GaugeAndAccumGene {
     fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
         match update {
             Message::Observations {
-                path: _,
-                datetime,
-                values,
+                datetime, values, ..
             } => {
                 for &idx in values.keys() {
                     let in_val = values.get(&idx).ok_or_else(|| OpError {
@@ -72,6 +86,26 @@ impl<T: Add<Output = T> + Copy> Gene<T> for GaugeAndAccumGene {
     fn get_time_scope(&self) -> &TimeScope {
         &self.time_scope
     }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.guage_slots < 0 || self.accumulator_slots < 0 {
+            return Err(OpError {
+                reason: "slots can not be negative".to_string(),
+            });
+        }
+        let guage_end = self.guage_first_idx.saturating_add(self.guage_slots);
+        let accumulator_end = self
+            .accumulator_first_idx
+            .saturating_add(self.accumulator_slots);
+        if self.guage_first_idx < accumulator_end && self.accumulator_first_idx < guage_end {
+            return Err(OpError {
+                reason: format!(
+                    "guage range {}..{guage_end} overlaps accumulator range {}..{accumulator_end}",
+                    self.guage_first_idx, self.accumulator_first_idx
+                ),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Default for GaugeAndAccumGene {
@@ -38,9 +38,7 @@ impl<T: Add<Output = T> + Copy> Gene<T> for GaugeGene {
     fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
         match update {
             Message::Observations {
-                path: _,
-                datetime,
-                values,
+                datetime, values, ..
             } => {
                 for &idx in values.keys() {
                     let in_val = values.get(&idx).ok_or_else(|| OpError {
@@ -60,6 +58,9 @@ impl<T: Add<Output = T> + Copy> Gene<T> for GaugeGene {
     fn get_time_scope(&self) -> &TimeScope {
         &self.time_scope
     }
+    fn accepts_late_reports(&self) -> bool {
+        false
+    }
 }
 
 impl Default for GaugeGene {
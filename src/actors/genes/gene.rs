@@ -18,8 +18,11 @@ use crate::actors::actor::State;
 use crate::actors::message::Message;
 use crate::actors::operator::OperatorResult;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::Add;
+use time::OffsetDateTime;
+use time::UtcOffset;
 
 /// A Gene is a collection of config information - mostly operators - that
 /// are applied to a path of actors.  A gene may apply to
@@ -39,6 +42,160 @@ pub trait Gene<T: Add<Output = T>> {
     /// index
     fn apply_operators(&self, state: State<T>, update: Message<T>) -> OperatorResult<State<T>>;
     fn get_time_scope(&self) -> &TimeScope;
+    /// Check that the gene's configuration is usable before it is applied
+    /// to any actors.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OperatorError`](../genes/struct.OperatorError.html) naming
+    /// the misconfiguration - ie: index ranges that overlap
+    fn validate(&self) -> OperatorResult<()> {
+        Ok(())
+    }
+    /// A gene that retains buckets has the state actor keep a separate state
+    /// for every day of its time scope rather than a single current one.
+    fn retains_buckets(&self) -> bool {
+        false
+    }
+    /// A gene that does not accept late reports has the state actor reject
+    /// an observation taken before the latest one it applied - a gauge must
+    /// not go back in time while an accumulator adds a late report like any
+    /// other.
+    fn accepts_late_reports(&self) -> bool {
+        true
+    }
+    /// A gene that compacts keeps everything it computed in the state so
+    /// that a snapshot of the state stands in for the journal it was
+    /// computed from - a gene that retains buckets would keep only the latest
+    /// day of them.
+    fn compacts(&self) -> bool {
+        !self.retains_buckets()
+    }
+    /// Forget whatever the gene keeps outside of the state when the state
+    /// actor is reset so that the observations after the reset are computed
+    /// from nothing before it.
+    fn reset(&self) {}
+    /// The indexes that an update moved out of the bounds the gene watches,
+    /// each with its new value and the bound it crossed.  An index that was
+    /// already out of bounds does not cross them again.
+    fn crossings(&self, _before: &State<T>, _after: &State<T>) -> Vec<(i32, T, T)> {
+        vec![]
+    }
+}
+
+/// Optional settings that override a gene's defaults.  The `GaugeAndAccum`
+/// gene has index ranges to configure, the `Ewma` gene its `alpha`, the
+/// `MovingAvg` gene its `window`, the `Aggregate` gene its `aggregate`
+/// function, the `Alert` gene the `thresholds` of its indexes, the
+/// `Composite` gene the operator `ranges` it partitions the indexes into,
+/// the `Delta` gene the `source_idx` of its readings and their `delta_idx`
+/// and the `Accum` gene the `time_scope` whose periods each start its sums
+/// fresh.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GeneParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guage_first_idx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guage_slots: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accumulator_first_idx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accumulator_slots: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<AggregateFn>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<BTreeMap<i32, Threshold>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranges: Option<Vec<IndexRange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_idx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_idx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_scope: Option<TimeScope>,
+}
+
+/// the `slots` indexes from `first_idx` that a `Composite` gene applies
+/// `operator` to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct IndexRange {
+    pub first_idx: i32,
+    pub slots: i32,
+    pub operator: IndexOperator,
+}
+
+/// the operators a `Composite` gene can apply to a range of indexes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexOperator {
+    Gauge,
+    Accum,
+    Min,
+    Max,
+}
+
+impl fmt::Display for IndexOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display_text = match self {
+            Self::Gauge => "gauge",
+            Self::Accum => "accum",
+            Self::Min => "min",
+            Self::Max => "max",
+        };
+        write!(f, "{display_text}")
+    }
+}
+
+/// the bounds an `Alert` gene keeps an index within - either may be left out
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high: Option<f64>,
+}
+
+/// how an `Aggregate` gene combines the states of its children
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateFn {
+    #[default]
+    Sum,
+    Mean,
+}
+
+impl fmt::Display for AggregateFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display_text = match self {
+            Self::Sum => "sum",
+            Self::Mean => "mean",
+        };
+        write!(f, "{display_text}")
+    }
+}
+
+impl GeneParams {
+    /// parse params from their JSON form, ie: `{"guage_slots": 10}`
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem if the text is not a JSON
+    /// object of known params
+    pub fn parse(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| format!("invalid gene params: {e}"))
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -47,7 +204,35 @@ pub enum GeneType {
     Accum,
     Gauge,
     GaugeAndAccum,
+    Latest,
+    /// an accumulator that keeps a separate total for every day
+    DailyAccum,
+    /// keeps the lowest value reported for every index
+    Min,
+    /// keeps the highest value reported for every index
+    Max,
+    /// keeps the running mean of every index and the count it covers
+    Mean,
+    /// keeps the per-second rate of change of every index
+    Rate,
+    /// an exponentially weighted moving average of every index
+    Ewma,
+    /// the mean of the last `window` observations of every index
+    MovingAvg,
+    /// the total of a counter that may reset to zero
+    Counter,
+    /// the sum, or the mean, of the states of the direct children of the path
+    Aggregate,
+    /// a gauge that raises an alert when an index crosses its `thresholds`
+    Alert,
+    /// a gauge, accumulator, min, or max for each of its index `ranges`
+    Composite,
+    /// a gauge of every index, the same as the gene of a path without a
+    /// mapping - unlike `GaugeAndAccum` it accumulates none of them
     Default,
+    /// a gauge of the cumulative reading of `source_idx` that writes the
+    /// difference from the previous reading to `delta_idx`
+    Delta,
 }
 
 impl fmt::Display for GeneType {
@@ -55,13 +240,28 @@ impl fmt::Display for GeneType {
         let display_text = match self {
             Self::Accum => "Accum",
             Self::GaugeAndAccum => "GaugeAndAccum",
-            Self::Gauge | Self::Default => "Gauge",
+            Self::Latest => "Latest",
+            Self::DailyAccum => "DailyAccum",
+            Self::Min => "Min",
+            Self::Max => "Max",
+            Self::Mean => "Mean",
+            Self::Rate => "Rate",
+            Self::Ewma => "Ewma",
+            Self::MovingAvg => "MovingAvg",
+            Self::Counter => "Counter",
+            Self::Aggregate => "Aggregate",
+            Self::Alert => "Alert",
+            Self::Composite => "Composite",
+            Self::Gauge => "Gauge",
+            Self::Default => "Default",
+            Self::Delta => "Delta",
         };
         write!(f, "{display_text}")
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum TimeScope {
     Forever,
     Year,
@@ -74,6 +274,41 @@ pub enum TimeScope {
     TenMinutes,
     Minute,
 }
+impl TimeScope {
+    /// The number of the period that `datetime` falls in counting from
+    /// `base_time`, or `None` for `Forever` which never rolls over.  Years
+    /// and months follow the UTC calendar, the shorter scopes are fixed
+    /// lengths of time from `base_time`.
+    #[must_use]
+    pub fn bucket(&self, base_time: OffsetDateTime, datetime: OffsetDateTime) -> Option<i64> {
+        let base_time = base_time.to_offset(UtcOffset::UTC);
+        let datetime = datetime.to_offset(UtcOffset::UTC);
+        let years = i64::from(datetime.year()) - i64::from(base_time.year());
+        let period_secs = match self {
+            Self::Forever => return None,
+            Self::Year => return Some(years),
+            Self::Month => {
+                return Some(
+                    years * 12 + i64::from(u8::from(datetime.month()))
+                        - i64::from(u8::from(base_time.month())),
+                )
+            }
+            Self::Day => 86_400,
+            Self::HalfDay => 43_200,
+            Self::QuarterDay => 21_600,
+            Self::Hour => 3_600,
+            Self::QuarterHour => 900,
+            Self::TenMinutes => 600,
+            Self::Minute => 60,
+        };
+        Some(
+            (datetime - base_time)
+                .whole_seconds()
+                .div_euclid(period_secs),
+        )
+    }
+}
+
 impl fmt::Display for TimeScope {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let display_text = match self {
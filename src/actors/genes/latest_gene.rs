@@ -0,0 +1,45 @@
+//! This module provides the implementation for the `LatestGene` struct, which represents a gene
+//! that keeps only the most recent observation as reported.  Where the `GaugeGene` merges each new
+//! observation into the state index by index, the `LatestGene` replaces the whole state so that
+//! indices missing from the newest report are cleared - last write wins for the entire record.
+//!
+//! The module exports the `LatestGene` struct, which can be used in the larger system for
+//! processing incoming data from `IoT` devices that report complete snapshots.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{OpError, OperatorResult};
+use std::ops::Add;
+use time::OffsetDateTime;
+
+pub struct LatestGene {
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl<T: Add<Output = T> + Copy> Gene<T> for LatestGene {
+    fn apply_operators(&self, _state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations { values, .. } => Ok(values),
+            _ => Err(OpError {
+                reason: "unsupported message type".to_string(),
+            }),
+        }
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn accepts_late_reports(&self) -> bool {
+        false
+    }
+}
+
+impl Default for LatestGene {
+    fn default() -> Self {
+        Self {
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! This module provides the implementation for the `MeanGene` struct, which represents a gene that
+//! keeps the running arithmetic mean of every index it is configured for. The mean is advanced by
+//! the `AvgOperator`, which also needs to know how many reports the mean already covers.
+//!
+//! The count of reports for an index is kept in the state itself at the companion index
+//! `idx + count_offset` - with the defaults the means of indices 0 to 99 are reported at 0 to 99
+//! and their counts at 100 to 199. Keeping the count in the state means it is rebuilt by the
+//! ordinary journal replay when the actor is resurrected. Observations must not report values for
+//! the companion indices.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{AvgOperator, OpError, OperatorResult};
+use std::ops::Add;
+use time::OffsetDateTime;
+
+pub struct MeanGene {
+    pub first_idx: i32,
+    pub slots: i32,
+    /// how far above its mean the count of each index is kept
+    pub count_offset: i32,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl<T: Add<Output = T> + Copy + Into<f64> + From<f64>> Gene<T> for MeanGene {
+    fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations { values, .. } => {
+                for (&idx, &in_val) in &values {
+                    if !(self.first_idx..self.first_idx.saturating_add(self.slots)).contains(&idx) {
+                        return Err(OpError {
+                            reason: format!("unsupported idx: {idx}"),
+                        });
+                    }
+                    let count_idx = idx.saturating_add(self.count_offset);
+                    let (mean, count) = AvgOperator::apply(&state, idx, count_idx, in_val)?;
+                    state.insert(idx, mean);
+                    state.insert(count_idx, count);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+}
+
+impl Default for MeanGene {
+    fn default() -> Self {
+        Self {
+            first_idx: 0,
+            slots: 100,
+            count_offset: 100,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! This module provides the implementation for the `MinMaxGene` struct, which represents a gene
+//! that keeps the lowest reported value for one range of indices and the highest reported value
+//! for another, the same way the `GaugeAndAccumGene` splits its indices between gauges and
+//! accumulators. The extremes are applied with the `MinOperator` and `MaxOperator` so replaying a
+//! journal in any order of values always arrives at the same state. The `Min` and `Max` gene types
+//! keep the extreme of every index, the ranges of a `Composite` gene mix them with other operators.
+//!
+//! The module exports the `MinMaxGene` struct, which can be used in the larger system for
+//! processing incoming data from `IoT` devices, ie: the daily low and high of a temperature.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{MaxOperator, MinOperator, OpError, Operator, OperatorResult};
+use std::ops::Add;
+use time::OffsetDateTime;
+
+pub struct MinMaxGene {
+    pub min_first_idx: i32,
+    pub min_slots: i32,
+    pub max_first_idx: i32,
+    pub max_slots: i32,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl MinMaxGene {
+    /// a gene that keeps the minimum of every index
+    #[must_use]
+    pub fn all_min() -> Self {
+        Self {
+            min_first_idx: 0,
+            min_slots: i32::MAX,
+            max_first_idx: 0,
+            max_slots: 0,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// a gene that keeps the maximum of every index
+    #[must_use]
+    pub fn all_max() -> Self {
+        Self {
+            min_first_idx: 0,
+            min_slots: 0,
+            max_first_idx: 0,
+            max_slots: i32::MAX,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn update_state_with_val<T: Add<Output = T> + Copy + PartialOrd>(
+        &self,
+        in_val: T,
+        idx: i32,
+        mut state: State<T>,
+        datetime: OffsetDateTime,
+    ) -> OperatorResult<State<T>> {
+        let new_val = if (self.min_first_idx..self.min_first_idx.saturating_add(self.min_slots))
+            .contains(&idx)
+        {
+            MinOperator::apply(&state, idx, in_val, datetime)?
+        } else if (self.max_first_idx..self.max_first_idx.saturating_add(self.max_slots))
+            .contains(&idx)
+        {
+            MaxOperator::apply(&state, idx, in_val, datetime)?
+        } else {
+            return Err(OpError {
+                reason: format!("unsupported idx: {idx}"),
+            });
+        };
+
+        state.insert(idx, new_val);
+        Ok(state)
+    }
+}
+
+impl<T: Add<Output = T> + Copy + PartialOrd> Gene<T> for MinMaxGene {
+    fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations {
+                datetime, values, ..
+            } => {
+                for (&idx, &in_val) in &values {
+                    state = self.update_state_with_val(in_val, idx, state, datetime)?;
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+}
@@ -1,4 +1,15 @@
 pub mod accum_gene;
+pub mod aggregate_gene;
+pub mod alert_gene;
+pub mod composite_gene;
+pub mod delta_gene;
+pub mod ewma_gene;
 pub mod gauge_and_accum_gene;
 pub mod gauge_gene;
 pub mod gene;
+pub mod latest_gene;
+pub mod mean_gene;
+pub mod minmax_gene;
+pub mod monotonic_counter_gene;
+pub mod moving_avg_gene;
+pub mod rate_gene;
@@ -0,0 +1,67 @@
+//! This module provides the implementation for the `MonotonicCounterGene` struct, which represents a
+//! gene that totals counters that may reset to zero, ie: the byte counters of a device that
+//! reboots. A plain accumulator would be corrupted by the drop, the `CounterOperator` treats it as a
+//! reset and only adds the new reading.
+//!
+//! The previous raw reading of an index is kept in the state itself at the companion index
+//! `idx + raw_offset` - with the defaults the totals of indices 0 to 99 are reported at 0 to 99 and
+//! their raw readings at 100 to 199. Keeping the raw reading in the state means it is rebuilt by
+//! the ordinary journal replay when the actor is resurrected. Observations must not report values
+//! for the companion indices.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{CounterOperator, OpError, OperatorResult};
+use std::ops::{Add, Sub};
+use time::OffsetDateTime;
+
+pub struct MonotonicCounterGene {
+    pub first_idx: i32,
+    pub slots: i32,
+    /// how far above its total the raw reading of each index is kept
+    pub raw_offset: i32,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+}
+
+impl<T: Add<Output = T> + Sub<Output = T> + Copy + PartialOrd> Gene<T> for MonotonicCounterGene {
+    fn apply_operators(&self, mut state: State<T>, update: Message<T>) -> OperatorResult<State<T>> {
+        match update {
+            Message::Observations { values, .. } => {
+                for (&idx, &in_val) in &values {
+                    if !(self.first_idx..self.first_idx.saturating_add(self.slots)).contains(&idx) {
+                        return Err(OpError {
+                            reason: format!("unsupported idx: {idx}"),
+                        });
+                    }
+                    let raw_idx = idx.saturating_add(self.raw_offset);
+                    let (total, raw) = CounterOperator::apply(&state, idx, raw_idx, in_val)?;
+                    state.insert(idx, total);
+                    state.insert(raw_idx, raw);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+}
+
+impl Default for MonotonicCounterGene {
+    fn default() -> Self {
+        Self {
+            first_idx: 0,
+            slots: 100,
+            raw_offset: 100,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+        }
+    }
+}
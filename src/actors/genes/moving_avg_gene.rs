@@ -0,0 +1,95 @@
+//! This module provides the implementation for the `MovingAvgGene` struct, which represents a gene
+//! whose state is the simple moving average of the last `window` observations of every index.
+//!
+//! The raw values of the window are not part of the reported state, so the gene keeps a ring
+//! buffer per index itself. The buffers are rebuilt by the ordinary journal replay when the actor
+//! is resurrected.
+//!
+//! The `window` of a gene is configured with the gene mapping params, ie: `{"window": 10}`, and is
+//! persisted along with the mapping.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{OpError, OperatorResult};
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+
+pub const DEFAULT_WINDOW: usize = 10;
+
+pub struct MovingAvgGene {
+    pub window: usize,
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+    /// the most recent raw values of every index, oldest first
+    pub samples: Mutex<HashMap<i32, VecDeque<f64>>>,
+}
+
+impl Gene<f64> for MovingAvgGene {
+    fn apply_operators(
+        &self,
+        mut state: State<f64>,
+        update: Message<f64>,
+    ) -> OperatorResult<State<f64>> {
+        match update {
+            Message::Observations { values, .. } => {
+                let mut samples = self.samples.lock().map_err(|e| OpError {
+                    reason: format!("samples unavailable: {e}"),
+                })?;
+                for (&idx, &in_val) in &values {
+                    let buffer = samples
+                        .entry(idx)
+                        .or_insert_with(|| VecDeque::with_capacity(self.window));
+                    if buffer.len() == self.window {
+                        buffer.pop_front();
+                    }
+                    buffer.push_back(in_val);
+                    #[allow(clippy::cast_precision_loss)]
+                    let mean = buffer.iter().sum::<f64>() / buffer.len() as f64;
+                    state.insert(idx, mean);
+                }
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn validate(&self) -> OperatorResult<()> {
+        if self.window > 0 {
+            Ok(())
+        } else {
+            Err(OpError {
+                reason: "window must hold at least one observation".to_string(),
+            })
+        }
+    }
+    /// the window is not in the state so a snapshot can not stand in for
+    /// the journal it is replayed from
+    fn compacts(&self) -> bool {
+        false
+    }
+    fn reset(&self) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.clear();
+        }
+    }
+}
+
+impl Default for MovingAvgGene {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+}
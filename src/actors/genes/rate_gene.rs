@@ -0,0 +1,95 @@
+//! This module provides the implementation for the `RateGene` struct, which represents a gene whose
+//! state is the per-second rate of change of every index rather than the reported value, ie: the
+//! flow of a meter that reports its running total.
+//!
+//! The rate needs the previous raw value and datetime of an index and those are not part of the
+//! reported state, so the gene remembers the latest sample of every index itself. The samples are
+//! rebuilt by the ordinary journal replay when the actor is resurrected. An index has no rate
+//! until its second observation, and an observation that is not later than the previous sample
+//! of its index is rejected - a late report is rejected before it reaches the gene so that the
+//! replay skips it rather than stopping at it.
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::Message;
+use crate::actors::operator::{OpError, OperatorResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+
+pub struct RateGene {
+    pub time_scope: TimeScope,
+    pub base_time: OffsetDateTime,
+    /// the latest raw value and datetime of every index
+    pub samples: Mutex<HashMap<i32, (f64, OffsetDateTime)>>,
+}
+
+impl Gene<f64> for RateGene {
+    fn apply_operators(
+        &self,
+        mut state: State<f64>,
+        update: Message<f64>,
+    ) -> OperatorResult<State<f64>> {
+        match update {
+            Message::Observations {
+                datetime, values, ..
+            } => {
+                let mut samples = self.samples.lock().map_err(|e| OpError {
+                    reason: format!("samples unavailable: {e}"),
+                })?;
+                // every index is checked before any sample is replaced so that a
+                // rejected observation leaves the gene as it was
+                let mut rates = State::new();
+                for (&idx, &in_val) in &values {
+                    if let Some(&(prev_val, prev_datetime)) = samples.get(&idx) {
+                        let elapsed = (datetime - prev_datetime).as_seconds_f64();
+                        if elapsed <= 0.0 {
+                            return Err(OpError {
+                                reason: format!(
+                                    "idx {idx} observation at {datetime} is not after the previous one at {prev_datetime}"
+                                ),
+                            });
+                        }
+                        rates.insert(idx, (in_val - prev_val) / elapsed);
+                    }
+                }
+                for (&idx, &in_val) in &values {
+                    samples.insert(idx, (in_val, datetime));
+                }
+                state.extend(rates);
+            }
+            _ => {
+                return Err(OpError {
+                    reason: "unsupported message type".to_string(),
+                })
+            }
+        };
+        Ok(state)
+    }
+    fn get_time_scope(&self) -> &TimeScope {
+        &self.time_scope
+    }
+    fn accepts_late_reports(&self) -> bool {
+        false
+    }
+    /// the samples are not in the state so a snapshot can not stand in for
+    /// the journal they are replayed from
+    fn compacts(&self) -> bool {
+        false
+    }
+    fn reset(&self) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.clear();
+        }
+    }
+}
+
+impl Default for RateGene {
+    fn default() -> Self {
+        Self {
+            time_scope: TimeScope::Forever,
+            base_time: OffsetDateTime::now_utc(),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+}
@@ -20,19 +20,60 @@
 //! communication process (`NvError` and `NvResult<T>`), as well as a type used to
 //! hint at the intent of a `Message<T>` (`MtHint`).
 
+use crate::actors::actor::State;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt;
+use time::Date;
 use time::OffsetDateTime;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 
 // deprecated - please use Messsage::GeneMapping
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GeneMapping {
     pub path: String,
     pub gene_type: GeneType,
+    #[serde(default, skip_serializing_if = "GeneParams::is_empty")]
+    pub params: GeneParams,
+}
+
+/// the size of a journal - see `Message::Stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JournalStats {
+    /// the distinct paths with journaled observations
+    pub paths: u64,
+    /// the journaled observations of all the paths
+    pub observations: u64,
+    /// when the first of the observations was journaled
+    pub earliest: Option<OffsetDateTime>,
+    /// when the last of the observations was journaled
+    pub latest: Option<OffsetDateTime>,
+    /// the gene mappings the namespace sees
+    pub gene_mappings: u64,
+}
+
+/// the journal of a single path - see `Message::PathStats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ObservationStats {
+    /// the journaled observations of the path
+    pub observations: u64,
+    /// when the earliest of the observations was taken
+    pub first: Option<OffsetDateTime>,
+    /// when the latest of the observations was taken
+    pub last: Option<OffsetDateTime>,
+}
+
+/// the actors a director holds in memory - see `Message::DirectorInfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DirectorInfo {
+    /// the actors resident in memory
+    pub actors: usize,
+    /// the indices of the state of all the resident actors
+    pub state_entries: usize,
 }
 
 pub type NvResult<T> = Result<T, NvError>;
@@ -53,6 +94,28 @@ pub struct PathQuery {
     pub path: String,
 }
 
+/// string tags reported alongside the numeric values of an observation, ie:
+/// `{"region": "west"}` - they are journaled but not applied to state
+pub type Labels = BTreeMap<String, String>;
+
+/// the names of the indices of the state of a namespace, ie: `1` ->
+/// `temperature_c` - the state stays keyed by index and the names are only
+/// printed in its place
+pub type IndexLabels = BTreeMap<i32, String>;
+
+/// `values` keyed by the label of each index, or by the index itself if it
+/// has none
+#[must_use]
+pub fn label_values<T: Clone>(values: &State<T>, labels: &IndexLabels) -> BTreeMap<String, T> {
+    values
+        .iter()
+        .map(|(idx, value)| {
+            let key = labels.get(idx).cloned().unwrap_or_else(|| idx.to_string());
+            (key, value.clone())
+        })
+        .collect()
+}
+
 /// all actor messages are delivered in envelops that contain optional
 /// sender objects - these are set when a `tell` message is sent so that
 /// the reply can be delivered.  These replies are not placed in envelopes.
@@ -63,6 +126,9 @@ pub struct Envelope<T> {
     pub datetime: OffsetDateTime,
     pub stream_to: Option<mpsc::Sender<Message<T>>>,
     pub stream_from: Option<mpsc::Receiver<Message<T>>>,
+    /// set on a `LoadCmd` whose requester may give up - the helper stops
+    /// streaming once it is cancelled
+    pub cancel: Option<CancellationToken>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -72,6 +138,56 @@ pub enum MtHint {
     State,
     GeneMapping,
     GeneMappingQuery,
+    /// the gene mappings of the query path and the paths under it in path
+    /// order
+    GeneMappingList,
+    LatestObservation,
+    /// the state of one day of a day-scoped gene
+    Bucket(Date),
+    /// the paths with state that start with the query path, ie: `/actors/`,
+    /// in order and one page at a time - with `since` only the paths with
+    /// observations journaled after it, ie: to sync a mirror incrementally
+    ListPaths {
+        limit: usize,
+        offset: usize,
+        since: Option<OffsetDateTime>,
+    },
+    /// every gene mapping and journaled observation under the path, ie:
+    /// `/actors/`, for a backup
+    Export,
+    /// the observations of the path journaled between `from` and `to`
+    /// inclusive, read straight from the journal without replaying them, in
+    /// the order they arrived and one page at a time
+    History {
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    },
+    /// the state of every path under the query path, ie:
+    /// `/domain/building/1/`, paged like `ListPaths`
+    Descendants {
+        limit: usize,
+        offset: usize,
+    },
+    /// the paths directly above and below the query path in the path
+    /// hierarchy of the actors created so far
+    Adjacency,
+    /// the state of the query path after every event of its journal,
+    /// replayed into a new actor that leaves the live one alone
+    Replay,
+    /// the state of the query path as of `datetime`, its journal up to then
+    /// replayed into a new actor that leaves the live one alone - empty
+    /// before its first observation
+    StateAsOf {
+        datetime: OffsetDateTime,
+    },
+    /// the index labels of the namespace - answered with `IndexLabels`
+    IndexLabels,
+    /// the gene mapping of the query path persisted before mappings had a
+    /// namespace, which every namespace shares - answered with `GeneMapping`
+    /// or `NotFound`
+    SharedGeneMapping,
 }
 
 impl fmt::Display for MtHint {
@@ -82,6 +198,18 @@ impl fmt::Display for MtHint {
             Self::Update => "update",
             Self::GeneMapping => "gene mapping",
             Self::GeneMappingQuery => "gene mapping query",
+            Self::GeneMappingList => "gene mapping list",
+            Self::LatestObservation => "latest observation",
+            Self::Bucket(_) => "bucket",
+            Self::ListPaths { .. } => "list paths",
+            Self::Export => "export",
+            Self::History { .. } => "history",
+            Self::Descendants { .. } => "descendants",
+            Self::Adjacency => "adjacency",
+            Self::Replay => "replay",
+            Self::StateAsOf { .. } => "state as of",
+            Self::IndexLabels => "index labels",
+            Self::SharedGeneMapping => "shared gene mapping",
         };
         write!(f, "[{display_text}]")
     }
@@ -99,17 +227,59 @@ pub enum Message<T> {
     Observations {
         datetime: OffsetDateTime,
         path: String,
-        values: HashMap<i32, T>,
+        values: State<T>,
+        labels: Labels,
+    },
+    /// `Observations` that are only applied if the actor of their path is
+    /// still at `version` - the response is a `VersionConflict` otherwise
+    ConditionalObservations {
+        version: u64,
+        observations: Box<Message<T>>,
     },
     /// the response to most Query/ask interactions
     StateReport {
         datetime: OffsetDateTime,
         path: String,
-        values: HashMap<i32, T>,
+        values: State<T>,
+        /// the number of observations applied to the state, counted again
+        /// by every replay of the journal
+        version: u64,
     },
     GeneMapping {
         path: String,
         gene_type: GeneType,
+        /// overrides of the gene defaults, empty for most mappings
+        params: GeneParams,
+    },
+    /// many path-to-gene mappings, each with its overrides of the gene
+    /// defaults, persisted in a single store transaction
+    GeneMappingBatch {
+        mappings: Vec<(String, GeneType, GeneParams)>,
+    },
+    /// the response to a `GeneMappingList` query
+    GeneMappingList {
+        mappings: Vec<(String, GeneType, GeneParams)>,
+    },
+    /// remove the gene mapping of a path - its live actors keep their gene
+    /// until they are resurrected
+    DeleteGeneMapping {
+        path: String,
+    },
+    /// the per-mapping outcome of a `GeneMappingBatch` in the order the
+    /// mappings were requested
+    GeneMappingBatchReport {
+        results: Vec<(String, Result<GeneType, String>)>,
+    },
+    /// name index `idx` of the state of the namespace `name` when it is
+    /// printed - a later label of the same index replaces it
+    IndexLabel {
+        idx: i32,
+        name: String,
+    },
+    /// every index label of the namespace - the answer to an `IndexLabels`
+    /// query and what the director tells its output when they change
+    IndexLabels {
+        labels: IndexLabels,
     },
     /// the actor init process is complicated in that the actors must recalculate
     /// their state from event source replays when they are first instantiated.
@@ -120,6 +290,233 @@ pub enum Message<T> {
         path: String,
     },
     ConstraintViolation,
+    /// told to the output at the end of a stream - the observations of the
+    /// stream that were journaled and applied and the ones the journal
+    /// already held and skipped
+    StreamSummary {
+        ingested: usize,
+        duplicates: usize,
+    },
+    /// ask the store for the journaled observations of a path that carry
+    /// every one of the labels
+    SeriesQuery {
+        path: String,
+        labels: Labels,
+    },
+    /// a `SeriesQuery` whose observations are sent to `stream_to` one at a
+    /// time as the journal is read, then an `EndOfStream` - an `Error` ends a
+    /// stream that can not be read.  it is told and not asked, everything
+    /// comes back on the stream
+    SeriesStream {
+        path: String,
+        labels: Labels,
+        stream_to: mpsc::Sender<Message<T>>,
+    },
+    /// a state `Query` answered with only the `indices` of the state - an
+    /// index the state does not have is left out
+    IndicesQuery {
+        path: String,
+        indices: Vec<i32>,
+    },
+    /// register `stream_to` for a `StateReport` of the path every time its
+    /// state changes - answered with the current state.  the subscription is
+    /// dropped once the receiver is closed.
+    Subscribe {
+        path: String,
+        stream_to: mpsc::Sender<Message<T>>,
+    },
+    /// the value of index `idx` of the path crossed the `threshold` its
+    /// `Alert` gene watches
+    Alert {
+        path: String,
+        idx: i32,
+        value: T,
+        threshold: T,
+    },
+    /// register `stream_to` for an `Event` with every `StateReport` and
+    /// `Alert` of a path under `path` - answered with `Events`, the retained ones after the
+    /// event id `after`.  a subscriber that falls behind is dropped.
+    SubscribeEvents {
+        path: String,
+        after: Option<u64>,
+        stream_to: mpsc::Sender<Message<T>>,
+    },
+    /// a `StateReport` or an `Alert` and its place in the director's sequence
+    /// of reports
+    Event {
+        id: u64,
+        report: Box<Message<T>>,
+    },
+    /// the `Event`s a new `SubscribeEvents` missed
+    Events {
+        events: Vec<Message<T>>,
+    },
+    /// a readiness check - answered with `Pong` once the director and its
+    /// store can serve requests
+    Ping,
+    Pong,
+    /// stop once the messages ahead of it are handled - the director passes
+    /// it on to its store, which commits its pending batch and closes the db,
+    /// and both answer with `Stopped` as the last thing they do
+    Stop,
+    Stopped,
+    /// stop every actor - the director passes it on to each of its live
+    /// actors, its output, and its store, and an actor of a pipeline to the
+    /// actor after it.  each runs its `stop` cleanup and answers `Stopped` as
+    /// the last thing it does before its receiver loop ends
+    Shutdown,
+    /// answered with `Flushed` by a store once every write sent to it before
+    /// the `Flush` is committed, ie: at the end of a stream before exiting
+    Flush,
+    Flushed,
+    /// the response to a `ListPaths` query - the `sequence` of a query with
+    /// `since` is when its latest observation was journaled, the `since` of
+    /// the next sync
+    PathList {
+        paths: Vec<String>,
+        sequence: Option<OffsetDateTime>,
+    },
+    /// remove the journal and the live actor of a path - its gene mapping is
+    /// removed as well when `mapping` is set
+    Delete {
+        path: String,
+        mapping: bool,
+    },
+    /// start the state of a path over, ie: zero an accumulator at the
+    /// rollover of a billing cycle - unlike `Delete` the journal is kept and
+    /// the reset is journaled among the observations so that a replay resets
+    /// at the same place
+    Reset {
+        path: String,
+    },
+    /// the `Observations` matching a `SeriesQuery` in timestamp order
+    Series {
+        path: String,
+        observations: Vec<Message<T>>,
+    },
+    /// the parent-to-child edges of the path hierarchy, ie: `/a` -> `/a/b`,
+    /// that a director registered as it created actors - persisted by the
+    /// store and streamed to a starting director after the gene mappings
+    Edges {
+        edges: Vec<(String, String)>,
+    },
+    /// the response to an `Adjacency` query
+    Adjacency {
+        path: String,
+        parents: Vec<String>,
+        children: Vec<String>,
+    },
+    /// the response to a `Descendants` query - the `StateReport` of each path
+    /// under `path` in path order
+    StateReports {
+        path: String,
+        reports: Vec<Message<T>>,
+    },
+    /// the response to a `History` query - the timestamp and values of each
+    /// observation of the page in the order they were journaled, and the
+    /// number of observations of all the pages
+    History {
+        path: String,
+        points: Vec<(OffsetDateTime, State<T>)>,
+        total: u64,
+    },
+    /// the state of a path as of the newest observation applied to it,
+    /// `datetime` - sent to the store to persist and streamed ahead of the
+    /// newer journal rows when the actor is resurrected
+    Snapshot {
+        path: String,
+        datetime: OffsetDateTime,
+        values: State<T>,
+        version: u64,
+    },
+    /// remove the journaled observations of the paths under `path`, ie:
+    /// `/actors/`, that arrived before `before` except the newest `keep_last`
+    /// of each path - a path's most recent observation is the base an
+    /// accumulator resumes from
+    Prune {
+        path: String,
+        before: OffsetDateTime,
+        keep_last: usize,
+    },
+    /// the response to a `Prune` with the number of observations removed
+    Pruned {
+        rows: u64,
+    },
+    /// collapse the journal of each path under `path`, ie: `/actors/`, up to
+    /// its first observation taken at or after `before` into a snapshot of
+    /// the state it adds up to - unlike a `Prune` the state is kept
+    Compact {
+        path: String,
+        before: OffsetDateTime,
+    },
+    /// a `Compact` of exactly `path` - sent to the store with the gene the
+    /// journal is replayed through
+    CompactPath {
+        path: String,
+        before: OffsetDateTime,
+        gene_type: GeneType,
+        params: GeneParams,
+    },
+    /// the response to a `Compact` with the number of paths compacted and of
+    /// journal rows removed
+    Compacted {
+        paths: usize,
+        rows: u64,
+    },
+    /// count the journal of the paths that start with `path`, ie:
+    /// `/actors/`, and the gene mappings of the store's namespace
+    Stats {
+        path: String,
+    },
+    /// the response to a `Stats`
+    StatsReport {
+        path: String,
+        stats: JournalStats,
+    },
+    /// count the actors the director holds in memory and the entries of
+    /// their state - nothing is resurrected or journaled to answer it
+    DirectorInfo,
+    /// the response to a `DirectorInfo`
+    DirectorInfoReport {
+        info: DirectorInfo,
+    },
+    /// count the journal of exactly `path` and get the version of its state
+    PathStats {
+        path: String,
+    },
+    /// the response to a `PathStats` - the store answers with the `stats` of
+    /// the journal and a `version` of 0 that the director sets to the one of
+    /// the actor
+    PathStatsReport {
+        path: String,
+        stats: ObservationStats,
+        version: u64,
+    },
+    /// an observation was rejected - forwarded to the output so that cli
+    /// users can see why, ie: which index the gene did not accept
+    Error {
+        path: String,
+        reason: String,
+    },
+    /// an observation taken before the latest one already applied to a
+    /// path whose gene does not accept late reports, ie: a gauge - it is
+    /// not applied
+    LateReport {
+        path: String,
+        datetime: OffsetDateTime,
+        latest: OffsetDateTime,
+    },
+    /// the response to observations of a path that is over the director's
+    /// `max_rate_per_path` - they are not journaled or applied
+    RateLimited {
+        path: String,
+    },
+    /// the response to `ConditionalObservations` for an actor that is at
+    /// `version` instead - they are not applied
+    VersionConflict {
+        path: String,
+        version: u64,
+    },
     /// InitCmd instructs the actor to flip into init mode and recalculate its
     /// state from the incoming eventstream using a tokio receiver in the
     /// envelope delivering the InitCmd.
@@ -151,6 +548,75 @@ impl<T> fmt::Display for Envelope<T> {
     }
 }
 
+impl<T> Message<T> {
+    /// the name of the variant, ie: `Observations`, for the `msg` field of a
+    /// tracing span
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Query { .. } => "Query",
+            Self::Observations { .. } => "Observations",
+            Self::ConditionalObservations { .. } => "ConditionalObservations",
+            Self::StateReport { .. } => "StateReport",
+            Self::GeneMapping { .. } => "GeneMapping",
+            Self::GeneMappingBatch { .. } => "GeneMappingBatch",
+            Self::GeneMappingList { .. } => "GeneMappingList",
+            Self::DeleteGeneMapping { .. } => "DeleteGeneMapping",
+            Self::GeneMappingBatchReport { .. } => "GeneMappingBatchReport",
+            Self::IndexLabel { .. } => "IndexLabel",
+            Self::IndexLabels { .. } => "IndexLabels",
+            Self::EndOfStream { .. } => "EndOfStream",
+            Self::Persisted => "Persisted",
+            Self::NotFound { .. } => "NotFound",
+            Self::ConstraintViolation => "ConstraintViolation",
+            Self::StreamSummary { .. } => "StreamSummary",
+            Self::SeriesQuery { .. } => "SeriesQuery",
+            Self::SeriesStream { .. } => "SeriesStream",
+            Self::IndicesQuery { .. } => "IndicesQuery",
+            Self::Subscribe { .. } => "Subscribe",
+            Self::Alert { .. } => "Alert",
+            Self::SubscribeEvents { .. } => "SubscribeEvents",
+            Self::Event { .. } => "Event",
+            Self::Events { .. } => "Events",
+            Self::Ping => "Ping",
+            Self::Pong => "Pong",
+            Self::Stop => "Stop",
+            Self::Stopped => "Stopped",
+            Self::Shutdown => "Shutdown",
+            Self::Flush => "Flush",
+            Self::Flushed => "Flushed",
+            Self::PathList { .. } => "PathList",
+            Self::Delete { .. } => "Delete",
+            Self::Reset { .. } => "Reset",
+            Self::Series { .. } => "Series",
+            Self::Edges { .. } => "Edges",
+            Self::Adjacency { .. } => "Adjacency",
+            Self::StateReports { .. } => "StateReports",
+            Self::History { .. } => "History",
+            Self::Snapshot { .. } => "Snapshot",
+            Self::Prune { .. } => "Prune",
+            Self::Pruned { .. } => "Pruned",
+            Self::Compact { .. } => "Compact",
+            Self::CompactPath { .. } => "CompactPath",
+            Self::Compacted { .. } => "Compacted",
+            Self::Stats { .. } => "Stats",
+            Self::StatsReport { .. } => "StatsReport",
+            Self::DirectorInfo => "DirectorInfo",
+            Self::DirectorInfoReport { .. } => "DirectorInfoReport",
+            Self::PathStats { .. } => "PathStats",
+            Self::PathStatsReport { .. } => "PathStatsReport",
+            Self::Error { .. } => "Error",
+            Self::LateReport { .. } => "LateReport",
+            Self::RateLimited { .. } => "RateLimited",
+            Self::VersionConflict { .. } => "VersionConflict",
+            Self::InitCmd { .. } => "InitCmd",
+            Self::LoadCmd { .. } => "LoadCmd",
+            Self::ReadAllCmd { .. } => "ReadAllCmd",
+            Self::Content { .. } => "Content",
+        }
+    }
+}
+
 impl<T> fmt::Display for Message<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let display_text = match self {
@@ -163,11 +629,115 @@ impl<T> fmt::Display for Message<T> {
             Self::InitCmd { hint } => format!("[InitCmd {hint}]"),
             Self::EndOfStream {} => "[EndOfStream]".to_string(),
             Self::Persisted {} => "[Persisted]".to_string(),
+            Self::Ping => "[Ping]".to_string(),
+            Self::Pong => "[Pong]".to_string(),
+            Self::Stop => "[Stop]".to_string(),
+            Self::Stopped => "[Stopped]".to_string(),
+            Self::Shutdown => "[Shutdown]".to_string(),
+            Self::Flush => "[Flush]".to_string(),
+            Self::Flushed => "[Flushed]".to_string(),
             Self::NotFound { path: _ } => "[Not Found]".to_string(),
             Self::ConstraintViolation {} => "[Contraint Violation]".to_string(),
+            Self::StreamSummary {
+                ingested,
+                duplicates,
+            } => format!("[StreamSummary {ingested} ingested {duplicates} duplicates]"),
+            Self::Error { path, reason } => format!("[Error {path}: {reason}]"),
+            Self::LateReport {
+                path,
+                datetime,
+                latest,
+            } => format!("[LateReport {path} at {datetime} is before {latest}]"),
+            Self::RateLimited { path } => format!("[RateLimited {path} is over its rate limit]"),
+            Self::VersionConflict { path, version } => {
+                format!("[VersionConflict {path} is at version {version}]")
+            }
+            Self::ConditionalObservations {
+                version,
+                observations,
+            } => format!("[ConditionalObservations at version {version} {observations}]"),
             Self::StateReport { .. } => "[StateReport]".to_string(), // TODO
             Self::GeneMapping { .. } => "[GeneMapping]".to_string(), // TODO
+            Self::GeneMappingBatch { mappings } => {
+                format!("[GeneMappingBatch of {}]", mappings.len())
+            }
+            Self::GeneMappingList { mappings } => {
+                format!("[GeneMappingList of {}]", mappings.len())
+            }
+            Self::DeleteGeneMapping { path } => format!("[DeleteGeneMapping {path}]"),
+            Self::GeneMappingBatchReport { results } => {
+                format!("[GeneMappingBatchReport of {}]", results.len())
+            }
+            Self::IndexLabel { idx, name } => format!("[IndexLabel {idx} -> {name}]"),
+            Self::IndexLabels { labels } => format!("[IndexLabels of {}]", labels.len()),
             Self::Observations { .. } => "[Observations]".to_string(),
+            Self::SeriesQuery { path, .. } => format!("[SeriesQuery {path}]"),
+            Self::SeriesStream { path, .. } => format!("[SeriesStream {path}]"),
+            Self::IndicesQuery { path, indices } => format!("[IndicesQuery {path} {indices:?}]"),
+            Self::Delete { path, .. } => format!("[Delete {path}]"),
+            Self::Reset { path } => format!("[Reset {path}]"),
+            Self::Snapshot { path, .. } => format!("[Snapshot {path}]"),
+            Self::Prune {
+                path,
+                before,
+                keep_last,
+            } => {
+                format!("[Prune {path} before {before} keeping {keep_last}]")
+            }
+            Self::Pruned { rows } => format!("[Pruned {rows}]"),
+            Self::Compact { path, before } => format!("[Compact {path} before {before}]"),
+            Self::CompactPath {
+                path,
+                before,
+                gene_type,
+                ..
+            } => format!("[CompactPath {path} before {before} as {gene_type}]"),
+            Self::Compacted { paths, rows } => format!("[Compacted {rows} of {paths} paths]"),
+            Self::Stats { path } => format!("[Stats {path}]"),
+            Self::StatsReport { path, stats } => {
+                format!("[StatsReport {path} of {} paths]", stats.paths)
+            }
+            Self::DirectorInfo => String::from("[DirectorInfo]"),
+            Self::DirectorInfoReport { info } => format!(
+                "[DirectorInfoReport of {} actors with {} entries]",
+                info.actors, info.state_entries
+            ),
+            Self::PathStats { path } => format!("[PathStats {path}]"),
+            Self::PathStatsReport { path, stats, .. } => {
+                format!("[PathStatsReport {path} of {}]", stats.observations)
+            }
+            Self::Alert { path, idx, .. } => format!("[Alert {path} {idx}]"),
+            Self::Subscribe { path, .. } => format!("[Subscribe {path}]"),
+            Self::SubscribeEvents { path, after, .. } => match after {
+                Some(after) => format!("[SubscribeEvents {path} after {after}]"),
+                None => format!("[SubscribeEvents {path}]"),
+            },
+            Self::Event { id, report } => format!("[Event {id} {report}]"),
+            Self::Events { events } => format!("[Events of {}]", events.len()),
+            Self::PathList { paths, .. } => format!("[PathList of {}]", paths.len()),
+            Self::Series { path, observations } => {
+                format!("[Series {path} of {}]", observations.len())
+            }
+            Self::History {
+                path,
+                points,
+                total,
+            } => {
+                format!("[History {path} of {} of {total}]", points.len())
+            }
+            Self::Edges { edges } => format!("[Edges of {}]", edges.len()),
+            Self::Adjacency {
+                path,
+                parents,
+                children,
+            } => format!(
+                "[Adjacency {path} of {} parents and {} children]",
+                parents.len(),
+                children.len()
+            ),
+            Self::StateReports { path, reports } => {
+                format!("[StateReports {path} of {}]", reports.len())
+            }
             Self::Query { .. } => "[Query]".to_string(),
         };
         write!(f, "{display_text}")
@@ -182,6 +752,7 @@ impl<T> Default for Envelope<T> {
             datetime: OffsetDateTime::now_utc(),
             stream_to: None,
             stream_from: None,
+            cancel: None,
         }
     }
 }
@@ -237,6 +808,7 @@ impl<T> LifeCycleBuilder<T> {
                 respond_to: self.respond_to,
                 stream_from: self.load_from,
                 stream_to: None,
+                cancel: None,
                 message: Message::InitCmd {
                     hint: self.hint.unwrap_or(MtHint::Update),
                 },
@@ -246,6 +818,7 @@ impl<T> LifeCycleBuilder<T> {
                 respond_to: None,
                 stream_from: None,
                 stream_to: self.send_to,
+                cancel: None,
                 message: Message::LoadCmd {
                     hint: self.hint.unwrap_or(MtHint::Update),
                     path: self.send_to_path.unwrap_or_default(),
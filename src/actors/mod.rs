@@ -3,5 +3,9 @@ pub mod director;
 pub mod genes;
 pub mod message;
 pub mod operator;
+pub mod postgres_store;
+pub mod sqlite_store;
 pub mod state_actor;
+pub mod store;
 pub mod store_actor_sqlite;
+pub mod value;
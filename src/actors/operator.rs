@@ -4,13 +4,14 @@
 //! crate to work with dates and times.
 //!
 //! The module exports the `Operator` trait, which defines the interface for all operators used in
-//! the system, and includes two structs (`Gauge` and `Accumulator`) that implement the `Operator`
-//! trait. The `Gauge` operator updates the current state of an actor with the most recent value of
-//! a given index, while the `Accumulator` operator accumulates the sum of all previously reported
-//! values for that index. The `Operator` trait also defines a `apply` method that applies an
-//! operator to an actor's current state to produce a new state, along with a custom error type
-//! (`OperatorError`) that is returned when an input is not valid for the operation, usually an
-//! invalid index.
+//! the system, and the structs that implement it or work alongside it. The `Gauge` operator
+//! updates the current state of an actor with the most recent value of a given index, while the
+//! `Accumulator` operator accumulates the sum of all previously reported values for that index.
+//! `MinOperator` and `MaxOperator` keep the extreme of all the values reported for an index,
+//! `AvgOperator` their running mean and `CounterOperator` the total of a counter that may reset.
+//! The `Operator` trait also defines a `apply` method that applies an operator to an actor's
+//! current state to produce a new state, along with a custom error type (`OperatorError`) that is
+//! returned when an input is not valid for the operation, usually an invalid index.
 //!
 //! The `OperatorResult` type is also defined in the module, which is used as the result type for
 //! all `apply` methods of operators, and the `Gauge` and `Accumulator` structs implement the
@@ -21,6 +22,7 @@
 use crate::actors::actor::State;
 use std::fmt;
 use std::ops::Add;
+use std::ops::Sub;
 use time::OffsetDateTime;
 
 pub(crate) type OperatorResult<T> = Result<T, OpError>;
@@ -47,7 +49,7 @@ pub trait Operator<T: Add<Output = T>>: Sync + Send {
     /// * `state`       - the current state of the actor
     /// * `idx`         - the index of the state being operated on
     /// * `value`       - the value from outside the actor to be
-    ///                 considered and applied to the current state
+    ///   considered and applied to the current state
     /// * `datetime`    - the datetime of the incoming observation
     ///
     /// # Errors
@@ -86,3 +88,91 @@ impl<T: Add<Output = T> + Copy> Operator<T> for Accumulator {
         )
     }
 }
+
+/// `MinOperator` keeps the lowest value ever reported for an index.  Like the
+/// accumulator, it is most useful when the actor is time-scoped, ie: a daily low.
+pub struct MinOperator {}
+impl<T: Add<Output = T> + Copy + PartialOrd> Operator<T> for MinOperator {
+    fn apply(state: &State<T>, idx: i32, value: T, _: OffsetDateTime) -> OperatorResult<T> {
+        match state.get(&idx) {
+            Some(old_val) if *old_val <= value => Ok(*old_val),
+            _ => Ok(value),
+        }
+    }
+}
+
+/// `MaxOperator` keeps the highest value ever reported for an index, ie: a daily high.
+pub struct MaxOperator {}
+impl<T: Add<Output = T> + Copy + PartialOrd> Operator<T> for MaxOperator {
+    fn apply(state: &State<T>, idx: i32, value: T, _: OffsetDateTime) -> OperatorResult<T> {
+        match state.get(&idx) {
+            Some(old_val) if *old_val >= value => Ok(*old_val),
+            _ => Ok(value),
+        }
+    }
+}
+
+/// `AvgOperator` keeps the running arithmetic mean of all reports for an index.
+/// The mean alone can not be advanced so the operator also needs the number of
+/// reports it covers - the caller stores that count alongside the mean.
+pub struct AvgOperator {}
+impl AvgOperator {
+    /// Returns the new `(mean, count)` after `value` is reported for `idx`
+    /// whose count is kept at `count_idx`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OperatorError`](../genes/struct.OperatorError.html) if the
+    /// state has a mean for `idx` but no count for it
+    pub fn apply<T: Copy + Into<f64> + From<f64>>(
+        state: &State<T>,
+        idx: i32,
+        count_idx: i32,
+        value: T,
+    ) -> OperatorResult<(T, T)> {
+        match (state.get(&idx), state.get(&count_idx)) {
+            (None, _) => Ok((value, T::from(1.0))),
+            (Some(mean), Some(count)) => {
+                let (mean, count): (f64, f64) = ((*mean).into(), (*count).into());
+                let count = count + 1.0;
+                let mean = mean + (value.into() - mean) / count;
+                Ok((T::from(mean), T::from(count)))
+            }
+            (Some(_), None) => Err(OpError {
+                reason: format!("no count at idx {count_idx} for the mean at idx {idx}"),
+            }),
+        }
+    }
+}
+
+/// `CounterOperator` keeps the running total of a monotonic counter that may
+/// reset to zero, ie: a network byte counter on a device that reboots.  The
+/// total advances by the increase since the previous raw reading, and a reading
+/// lower than the previous one is taken as a reset so the whole new reading is
+/// added.  The caller stores the previous raw reading alongside the total.
+pub struct CounterOperator {}
+impl CounterOperator {
+    /// Returns the new `(total, raw)` after `value` is reported for `idx`
+    /// whose previous raw reading is kept at `raw_idx`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OperatorError`](../genes/struct.OperatorError.html) if the
+    /// state has a total for `idx` but no raw reading for it
+    pub fn apply<T: Add<Output = T> + Sub<Output = T> + Copy + PartialOrd>(
+        state: &State<T>,
+        idx: i32,
+        raw_idx: i32,
+        value: T,
+    ) -> OperatorResult<(T, T)> {
+        match (state.get(&idx), state.get(&raw_idx)) {
+            // the counter started at zero
+            (None, _) => Ok((value, value)),
+            (Some(total), Some(raw)) if value < *raw => Ok((*total + value, value)),
+            (Some(total), Some(raw)) => Ok((*total + (value - *raw), value)),
+            (Some(_), None) => Err(OpError {
+                reason: format!("no raw reading at idx {raw_idx} for the counter at idx {idx}"),
+            }),
+        }
+    }
+}
@@ -0,0 +1,875 @@
+//!This module implements the `Store` trait on a `Postgres` database so that many `nv serve`
+//!processes can write to one journal.
+//!
+//!The tables match the ones of the `SqliteStore` with integer timestamps and an `id` column that
+//!keeps the order the journal was written in - the order `SQLite` keeps with its `rowid`.
+//!Connecting defines the tables if they do not exist.
+
+use crate::actors::actor::State;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::GeneType;
+use crate::actors::message::GeneMapping;
+use crate::actors::message::IndexLabels;
+use crate::actors::message::JournalStats;
+use crate::actors::message::Labels;
+use crate::actors::message::Message;
+use crate::actors::message::ObservationStats;
+use crate::actors::store::cap_replay;
+use crate::actors::store::gene_type_json;
+use crate::actors::store::labels_json;
+use crate::actors::store::params_json;
+use crate::actors::store::prefix_pattern;
+use crate::actors::store::reset_marker_timestamp;
+use crate::actors::store::row_datetime;
+use crate::actors::store::row_to_gene_mapping;
+use crate::actors::store::row_to_observations;
+use crate::actors::store::row_to_path_stats;
+use crate::actors::store::row_to_snapshot;
+use crate::actors::store::row_to_stats;
+use crate::actors::store::stream_rows;
+use crate::actors::store::values_json;
+use crate::actors::store::Journal;
+use crate::actors::store::Store;
+use crate::actors::store::UpdateOutcome;
+use crate::actors::store::UpdateRow;
+use crate::actors::store::RESET_MARKER;
+use crate::actors::store_actor_sqlite::StoreError;
+use crate::actors::store_actor_sqlite::StoreResult;
+use crate::actors::value::Value;
+use crate::utils::nvtime::OffsetDateTimeWrapper;
+use async_trait::async_trait;
+use sqlx::postgres::PgArguments;
+use sqlx::postgres::PgRow;
+use sqlx::postgres::Postgres;
+use sqlx::Acquire;
+use sqlx::PgPool;
+use sqlx::Row;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+/// the postgres code of a unique constraint violation
+const UNIQUE_VIOLATION: &str = "23505";
+
+pub struct PostgresStore {
+    pub dbconn: PgPool,
+}
+
+/// remove the mapping of `path` in `namespace` only - a mapping persisted
+/// before mappings had a namespace is shared by every namespace so it is
+/// never removed by one of them
+async fn delete_gene_mapping<'e, E>(
+    dbconn: E,
+    namespace: &str,
+    path: &str,
+) -> Result<u64, sqlx::error::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    Ok(
+        sqlx::query("DELETE FROM gene_mappings WHERE path = $1 AND namespace = $2")
+            .bind(path)
+            .bind(namespace)
+            .execute(dbconn)
+            .await?
+            .rows_affected(),
+    )
+}
+
+async fn insert_gene_mapping<'e, E>(
+    dbconn: E,
+    namespace: &str,
+    path: &str,
+    gene_type: &GeneType,
+    params: &GeneParams,
+) -> Result<(), sqlx::error::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    match sqlx::query(
+        "INSERT INTO gene_mappings (namespace, path, gene_type, params) VALUES ($1,$2,$3,$4)",
+    )
+    .bind(namespace)
+    .bind(path)
+    .bind(gene_type_json(gene_type))
+    .bind(params_json(params))
+    .execute(dbconn)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!("persisting gene mapping for {} failed: {:?}", path, e);
+            Err(e)
+        }
+    }
+}
+
+/// the observations of a path that carry every one of `labels` in
+/// observation time order
+fn series_sql(labels: &Labels) -> String {
+    let mut sql = String::from(
+        "SELECT timestamp, values_str, labels FROM updates WHERE path = $1 AND values_str <> $2",
+    );
+    for n in 0..labels.len() {
+        sql.push_str(&format!(
+            " AND (labels::jsonb ->> ${}) = ${}",
+            2 * n + 3,
+            2 * n + 4
+        ));
+    }
+    sql.push_str(" ORDER BY timestamp");
+    sql
+}
+
+/// bind the path and the `labels` of a `series_sql` query
+fn series_query<'q>(
+    sql: &'q str,
+    path: &'q str,
+    labels: &'q Labels,
+) -> sqlx::query::Query<'q, Postgres, PgArguments> {
+    let mut query = sqlx::query(sql).bind(path).bind(RESET_MARKER);
+    for (key, value) in labels {
+        query = query.bind(key).bind(value);
+    }
+    query
+}
+
+/// decode a `timestamp, values_str, labels` row into an observation
+fn decode_observations<T: Value>(
+    path: &str,
+    row: &PgRow,
+) -> Result<Message<T>, sqlx::error::Error> {
+    row_to_observations(path, row.try_get(0)?, row.try_get(1)?, row.try_get(2)?)
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+/// the journal rows of a path after a row with the key they replay in -
+/// observations replay in timestamp order and a reset right after the latest
+/// observation journaled before it, or first if there is none
+const REPLAY_ROWS: &str = "SELECT id, timestamp, values_str, labels, \
+     CASE WHEN values_str = $1 THEN COALESCE((SELECT MAX(earlier.timestamp) \
+     FROM updates AS earlier WHERE earlier.path = updates.path AND earlier.id < updates.id), \
+     timestamp) ELSE timestamp END AS replay_at, \
+     values_str = $1 AS is_reset FROM updates WHERE path = $2 AND id > $3";
+
+const REPLAY_ORDER: &str = "replay_at, is_reset, id";
+
+/// journal one observation - a duplicate of a journaled path and timestamp
+/// is reported as `UpdateOutcome::Duplicate`
+async fn insert_update<'e, E, T: Value>(
+    dbconn: E,
+    path: &str,
+    datetime: OffsetDateTime,
+    sequence: OffsetDateTime,
+    values: &State<T>,
+    labels: &Labels,
+) -> StoreResult<UpdateOutcome>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    let dt_wrapper = OffsetDateTimeWrapper::new(datetime);
+    let sequence_wrapper = OffsetDateTimeWrapper::new(sequence);
+    let values_str = values_json(values)?;
+    let labels_str = labels_json(labels)?;
+
+    match sqlx::query(
+        "INSERT INTO updates (path, timestamp, sequence, values_str, labels) VALUES ($1,$2,$3,$4,$5)",
+    )
+    .bind(path)
+    .bind(dt_wrapper.datetime_num)
+    .bind(sequence_wrapper.datetime_num)
+    .bind(values_str)
+    .bind(labels_str)
+    .execute(dbconn)
+    .await
+    {
+        Ok(_) => Ok(UpdateOutcome::Persisted),
+        Err(sqlx::Error::Database(db_error))
+            if db_error.code().as_deref() == Some(UNIQUE_VIOLATION) =>
+        {
+            debug!("jrnling for {path} is a duplicate: {db_error:?}");
+            Ok(UpdateOutcome::Duplicate)
+        }
+        Err(e) => {
+            warn!("jrnling for {} failed: {:?}", path, e);
+            Err(e.into())
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Value> Journal<T> for PostgresStore {
+    async fn insert_update(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        sequence: OffsetDateTime,
+        values: &State<T>,
+        labels: &Labels,
+    ) -> StoreResult<UpdateOutcome> {
+        insert_update(&self.dbconn, path, datetime, sequence, values, labels).await
+    }
+
+    async fn insert_updates(
+        &self,
+        rows: &[UpdateRow<T>],
+    ) -> StoreResult<Vec<StoreResult<UpdateOutcome>>> {
+        let mut tx = self.dbconn.begin().await?;
+        let mut outcomes = Vec::with_capacity(rows.len());
+        for row in rows {
+            // a failed statement aborts a postgres transaction so each insert
+            // gets a savepoint to roll back to
+            let mut savepoint = tx.begin().await?;
+            let outcome = insert_update(
+                &mut *savepoint,
+                &row.path,
+                row.datetime,
+                row.sequence,
+                &row.values,
+                &row.labels,
+            )
+            .await;
+            if matches!(outcome, Ok(UpdateOutcome::Persisted)) {
+                savepoint.commit().await?;
+            } else {
+                savepoint.rollback().await?;
+            }
+            outcomes.push(outcome);
+        }
+        tx.commit().await?;
+        Ok(outcomes)
+    }
+
+    async fn insert_reset(&self, path: &str, sequence: OffsetDateTime) -> StoreResult<()> {
+        sqlx::query(
+            "INSERT INTO updates (path, timestamp, sequence, values_str) VALUES ($1,$2,$3,$4)",
+        )
+        .bind(path)
+        .bind(reset_marker_timestamp(sequence))
+        .bind(OffsetDateTimeWrapper::new(sequence).datetime_num)
+        .bind(RESET_MARKER)
+        .execute(&self.dbconn)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_values(
+        &self,
+        path: &str,
+        after: i64,
+        max_replay_events: Option<usize>,
+    ) -> StoreResult<Vec<Message<T>>> {
+        let Some(max) = max_replay_events else {
+            return Ok(sqlx::query(&format!(
+                "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS}) AS journal \
+                 ORDER BY {REPLAY_ORDER}"
+            ))
+            .bind(RESET_MARKER)
+            .bind(path)
+            .bind(after)
+            .try_map(|row: PgRow| decode_observations(path, &row))
+            .fetch_all(&self.dbconn)
+            .await?);
+        };
+        // the newest rows in replay order, not the latest journaled
+        let limit = i64::try_from(max.saturating_add(1)).unwrap_or(i64::MAX);
+        let values = sqlx::query(&format!(
+            "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS} \
+             ORDER BY replay_at DESC, is_reset DESC, id DESC LIMIT $4) AS recent \
+             ORDER BY {REPLAY_ORDER}"
+        ))
+        .bind(RESET_MARKER)
+        .bind(path)
+        .bind(after)
+        .bind(limit)
+        .try_map(|row: PgRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok(cap_replay(path, max, values))
+    }
+
+    async fn get_journal(&self, prefix: &str) -> StoreResult<Vec<Message<T>>> {
+        Ok(sqlx::query(
+            "SELECT path, timestamp, values_str, labels FROM updates \
+             WHERE path LIKE $1 ESCAPE '\\' AND values_str <> $2 ORDER BY id",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(RESET_MARKER)
+        .try_map(|row: PgRow| {
+            let path: String = row.try_get(0)?;
+            row_to_observations(&path, row.try_get(1)?, row.try_get(2)?, row.try_get(3)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn insert_snapshot(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<()> {
+        sqlx::query(
+            "INSERT INTO snapshots (path, last_row, timestamp, version, values_str) \
+             SELECT $1, COALESCE(MAX(id), 0), $2, $3, $4 FROM updates WHERE path = $1 \
+             ON CONFLICT (path) DO UPDATE SET last_row = excluded.last_row, \
+             timestamp = excluded.timestamp, version = excluded.version, \
+             values_str = excluded.values_str",
+        )
+        .bind(path)
+        .bind(OffsetDateTimeWrapper::new(datetime).datetime_num)
+        .bind(i64::try_from(version).unwrap_or(i64::MAX))
+        .bind(values_json(values)?)
+        .execute(&self.dbconn)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_snapshot(&self, path: &str) -> StoreResult<Option<(i64, Message<T>)>> {
+        Ok(sqlx::query(
+            "SELECT last_row, timestamp, version, values_str FROM snapshots WHERE path = $1",
+        )
+        .bind(path)
+        .try_map(|row: PgRow| {
+            let snapshot = row_to_snapshot(path, row.try_get(1)?, row.try_get(2)?, row.try_get(3)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            Ok((row.try_get(0)?, snapshot))
+        })
+        .fetch_optional(&self.dbconn)
+        .await?)
+    }
+
+    async fn get_values_before(
+        &self,
+        path: &str,
+        after: i64,
+        before: OffsetDateTime,
+    ) -> StoreResult<(i64, Vec<Message<T>>)> {
+        let last_row: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(id) FROM updates WHERE path = $1 AND id > $2 AND id < \
+             COALESCE((SELECT MIN(id) FROM updates WHERE path = $1 AND values_str <> $3 \
+             AND timestamp >= $4), $5)",
+        )
+        .bind(path)
+        .bind(after)
+        .bind(RESET_MARKER)
+        .bind(OffsetDateTimeWrapper::new(before).datetime_num)
+        .bind(i64::MAX)
+        .fetch_one(&self.dbconn)
+        .await?;
+        let Some(last_row) = last_row else {
+            return Ok((after, vec![]));
+        };
+        // the same order as `get_values` replays them in
+        let values = sqlx::query(&format!(
+            "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS} AND id <= $4) AS journal \
+             ORDER BY {REPLAY_ORDER}"
+        ))
+        .bind(RESET_MARKER)
+        .bind(path)
+        .bind(after)
+        .bind(last_row)
+        .try_map(|row: PgRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok((last_row, values))
+    }
+
+    async fn compact(
+        &self,
+        path: &str,
+        last_row: i64,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<u64> {
+        let mut tx = self.dbconn.begin().await?;
+        sqlx::query(
+            "INSERT INTO snapshots (path, last_row, timestamp, version, values_str) \
+             VALUES ($1, $2, $3, $4, $5) ON CONFLICT (path) DO UPDATE SET \
+             last_row = excluded.last_row, timestamp = excluded.timestamp, \
+             version = excluded.version, values_str = excluded.values_str",
+        )
+        .bind(path)
+        .bind(last_row)
+        .bind(OffsetDateTimeWrapper::new(datetime).datetime_num)
+        .bind(i64::try_from(version).unwrap_or(i64::MAX))
+        .bind(values_json(values)?)
+        .execute(&mut *tx)
+        .await?;
+        let removed = sqlx::query("DELETE FROM updates WHERE path = $1 AND id <= $2")
+            .bind(path)
+            .bind(last_row)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    async fn get_series(&self, path: &str, labels: &Labels) -> StoreResult<Vec<Message<T>>> {
+        let sql = series_sql(labels);
+        Ok(series_query(&sql, path, labels)
+            .try_map(|row: PgRow| decode_observations(path, &row))
+            .fetch_all(&self.dbconn)
+            .await?)
+    }
+
+    fn stream_series(&self, path: &str, labels: &Labels, stream_to: mpsc::Sender<Message<T>>) {
+        let dbconn = self.dbconn.clone();
+        let path = path.to_string();
+        let labels = labels.clone();
+        tokio::spawn(async move {
+            let sql = series_sql(&labels);
+            let rows = series_query(&sql, &path, &labels)
+                .try_map(|row: PgRow| decode_observations(&path, &row))
+                .fetch(&dbconn);
+            stream_rows(&path, rows, &stream_to).await;
+        });
+    }
+
+    async fn get_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<Message<T>>> {
+        Ok(sqlx::query(
+            "SELECT timestamp, values_str, labels FROM updates WHERE path = $1 AND values_str <> $6 \
+             AND sequence BETWEEN $2 AND $3 ORDER BY sequence, id LIMIT $4 OFFSET $5",
+        )
+        .bind(path)
+        .bind(OffsetDateTimeWrapper::new(from).datetime_num)
+        .bind(OffsetDateTimeWrapper::new(to).datetime_num)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .bind(RESET_MARKER)
+        .try_map(|row: PgRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn count_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> StoreResult<u64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM updates WHERE path = $1 AND values_str <> $2 \
+             AND sequence BETWEEN $3 AND $4",
+        )
+        .bind(path)
+        .bind(RESET_MARKER)
+        .bind(OffsetDateTimeWrapper::new(from).datetime_num)
+        .bind(OffsetDateTimeWrapper::new(to).datetime_num)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(u64::try_from(count).unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn insert_gene_mapping(
+        &self,
+        namespace: &str,
+        path: &str,
+        gene_type: &GeneType,
+        params: &GeneParams,
+    ) -> StoreResult<()> {
+        Ok(insert_gene_mapping(&self.dbconn, namespace, path, gene_type, params).await?)
+    }
+
+    async fn insert_gene_mappings(
+        &self,
+        namespace: &str,
+        mappings: Vec<(String, GeneType, GeneParams)>,
+    ) -> StoreResult<Vec<(String, Result<GeneType, String>)>> {
+        let mut tx = self.dbconn.begin().await?;
+        let mut results = Vec::new();
+        for (path, gene_type, params) in mappings {
+            // a failed statement aborts a postgres transaction so each insert
+            // gets a savepoint to roll back to
+            let mut savepoint = tx.begin().await?;
+            match insert_gene_mapping(&mut *savepoint, namespace, &path, &gene_type, &params).await
+            {
+                Ok(()) => {
+                    savepoint.commit().await?;
+                    results.push((path, Ok(gene_type)));
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    results.push((path, Err(e.to_string())));
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn get_mappings_for_ns(&self, namespace: &str) -> StoreResult<Vec<GeneMapping>> {
+        debug!("loading mappings for namespace {namespace}");
+        Ok(sqlx::query(
+            "SELECT path, gene_type, params FROM gene_mappings \
+                 WHERE namespace = $1 OR namespace = '' ORDER BY namespace <> '', path",
+        )
+        .bind(namespace)
+        .try_map(|row: PgRow| {
+            let path = row.try_get(0).map_err(|e| {
+                error!("cannot read path");
+                sqlx::Error::Decode(Box::new(e))
+            })?;
+            row_to_gene_mapping(path, row.try_get(1)?, row.try_get(2)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn delete_gene_mapping(&self, namespace: &str, path: &str) -> StoreResult<u64> {
+        Ok(delete_gene_mapping(&self.dbconn, namespace, path).await?)
+    }
+
+    async fn get_shared_mapping(&self, path: &str) -> StoreResult<Option<GeneMapping>> {
+        Ok(sqlx::query(
+            "SELECT gene_type, params FROM gene_mappings WHERE path = $1 AND namespace = ''",
+        )
+        .bind(path)
+        .try_map(|row: PgRow| {
+            row_to_gene_mapping(String::from(path), row.try_get(0)?, row.try_get(1)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_optional(&self.dbconn)
+        .await?)
+    }
+
+    async fn insert_edges(&self, namespace: &str, edges: &[(String, String)]) -> StoreResult<()> {
+        let mut tx = self.dbconn.begin().await?;
+        for (parent, child) in edges {
+            sqlx::query(
+                "INSERT INTO edges (namespace, parent, child) VALUES ($1,$2,$3) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(namespace)
+            .bind(parent)
+            .bind(child)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_edges(&self, namespace: &str) -> StoreResult<Vec<(String, String)>> {
+        Ok(
+            sqlx::query_as("SELECT parent, child FROM edges WHERE namespace = $1 ORDER BY id")
+                .bind(namespace)
+                .fetch_all(&self.dbconn)
+                .await?,
+        )
+    }
+
+    async fn insert_index_label(&self, namespace: &str, idx: i32, name: &str) -> StoreResult<()> {
+        sqlx::query(
+            "INSERT INTO index_labels (namespace, idx, name) VALUES ($1,$2,$3) \
+             ON CONFLICT (namespace, idx) DO UPDATE SET name = EXCLUDED.name",
+        )
+        .bind(namespace)
+        .bind(idx)
+        .bind(name)
+        .execute(&self.dbconn)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_index_labels(&self, namespace: &str) -> StoreResult<IndexLabels> {
+        let labels: Vec<(i32, String)> =
+            sqlx::query_as("SELECT idx, name FROM index_labels WHERE namespace = $1")
+                .bind(namespace)
+                .fetch_all(&self.dbconn)
+                .await?;
+        Ok(labels.into_iter().collect())
+    }
+
+    async fn delete(&self, namespace: &str, path: &str, mapping: bool) -> StoreResult<u64> {
+        let mut tx = self.dbconn.begin().await?;
+        let updates = sqlx::query("DELETE FROM updates WHERE path = $1")
+            .bind(path)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM snapshots WHERE path = $1")
+            .bind(path)
+            .execute(&mut *tx)
+            .await?;
+        if mapping {
+            delete_gene_mapping(&mut *tx, namespace, path).await?;
+        }
+        tx.commit().await?;
+        Ok(updates)
+    }
+
+    async fn prune(
+        &self,
+        prefix: &str,
+        before: OffsetDateTime,
+        keep_last: usize,
+    ) -> StoreResult<u64> {
+        let pruned = sqlx::query(
+            "DELETE FROM updates WHERE id IN (SELECT id FROM \
+             (SELECT id, sequence, \
+             ROW_NUMBER() OVER (PARTITION BY path ORDER BY id DESC) AS newest FROM updates \
+             WHERE path LIKE $1 ESCAPE '\\') AS ranked WHERE sequence < $2 AND newest > $3)",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(OffsetDateTimeWrapper::new(before).datetime_num)
+        .bind(i64::try_from(keep_last).unwrap_or(i64::MAX))
+        .execute(&self.dbconn)
+        .await?
+        .rows_affected();
+        sqlx::query("VACUUM updates").execute(&self.dbconn).await?;
+        Ok(pruned)
+    }
+
+    async fn list_paths(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT DISTINCT path FROM updates WHERE path LIKE $1 ESCAPE '\\' ORDER BY path LIMIT $2 OFFSET $3",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn list_paths_since(
+        &self,
+        prefix: &str,
+        since: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<(Vec<String>, OffsetDateTime)> {
+        let after = OffsetDateTimeWrapper::new(since).datetime_num;
+        let latest: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(sequence) FROM updates WHERE path LIKE $1 ESCAPE '\\' AND sequence > $2",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(after)
+        .fetch_one(&self.dbconn)
+        .await?;
+        let Some(latest) = latest else {
+            return Ok((vec![], since));
+        };
+        let paths = sqlx::query_scalar(
+            "SELECT DISTINCT path FROM updates WHERE path LIKE $1 ESCAPE '\\' \
+             AND sequence > $2 AND sequence <= $3 ORDER BY path LIMIT $4 OFFSET $5",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(after)
+        .bind(latest)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok((paths, row_datetime(latest)))
+    }
+
+    async fn stats(&self, namespace: &str, prefix: &str) -> StoreResult<JournalStats> {
+        let (paths, observations, earliest, latest): (i64, i64, Option<i64>, Option<i64>) =
+            sqlx::query_as(
+                "SELECT COUNT(DISTINCT path), COUNT(*), MIN(sequence), MAX(sequence) \
+                 FROM updates WHERE path LIKE $1 ESCAPE '\\' AND values_str <> $2",
+            )
+            .bind(prefix_pattern(prefix))
+            .bind(RESET_MARKER)
+            .fetch_one(&self.dbconn)
+            .await?;
+        let gene_mappings: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT path) FROM gene_mappings WHERE namespace = $1 OR namespace = ''",
+        )
+        .bind(namespace)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(row_to_stats(
+            paths,
+            observations,
+            earliest,
+            latest,
+            gene_mappings,
+        ))
+    }
+
+    async fn path_stats(&self, path: &str) -> StoreResult<ObservationStats> {
+        let (observations, first, last): (i64, Option<i64>, Option<i64>) = sqlx::query_as(
+            "SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM updates \
+             WHERE path = $1 AND values_str <> $2",
+        )
+        .bind(path)
+        .bind(RESET_MARKER)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(row_to_path_stats(observations, first, last))
+    }
+
+    async fn ping(&self) -> StoreResult<()> {
+        sqlx::query("SELECT 1").execute(&self.dbconn).await?;
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.dbconn.close().await;
+    }
+}
+
+/// define the tables if they do not exist
+async fn define_tables_if_not_exist(dbconn: &PgPool) -> StoreResult<()> {
+    for ddl in [
+        "CREATE TABLE IF NOT EXISTS updates (
+              id BIGSERIAL,
+              path TEXT NOT NULL,
+              timestamp BIGINT NOT NULL,
+              sequence BIGINT NOT NULL,
+              values_str TEXT NOT NULL,
+              labels TEXT,
+              PRIMARY KEY (path, timestamp)
+        )",
+        "CREATE TABLE IF NOT EXISTS gene_mappings (
+              namespace TEXT NOT NULL DEFAULT '',
+              path TEXT NOT NULL,
+              gene_type TEXT NOT NULL,
+              params TEXT,
+              PRIMARY KEY (namespace, path)
+        )",
+        "CREATE TABLE IF NOT EXISTS snapshots (
+              path TEXT NOT NULL,
+              last_row BIGINT NOT NULL,
+              timestamp BIGINT NOT NULL,
+              version BIGINT NOT NULL DEFAULT 0,
+              values_str TEXT NOT NULL,
+              PRIMARY KEY (path)
+        )",
+        "CREATE TABLE IF NOT EXISTS edges (
+              id BIGSERIAL,
+              namespace TEXT NOT NULL,
+              parent TEXT NOT NULL,
+              child TEXT NOT NULL,
+              PRIMARY KEY (namespace, parent, child)
+        )",
+        "CREATE TABLE IF NOT EXISTS index_labels (
+              namespace TEXT NOT NULL,
+              idx INTEGER NOT NULL,
+              name TEXT NOT NULL,
+              PRIMARY KEY (namespace, idx)
+        )",
+    ] {
+        sqlx::query(ddl)
+            .execute(dbconn)
+            .await
+            .map_err(|e| StoreError {
+                reason: format!("Failed to define tables: {e}"),
+                transient: false,
+            })?;
+    }
+    Ok(())
+}
+
+/// migrate dbs created before gene mappings had a namespace - the existing
+/// mappings get the empty namespace and are loaded by every namespace like
+/// they were before
+async fn add_namespace_to_gene_mappings_if_not_exist(dbconn: &PgPool) -> StoreResult<()> {
+    let migrate = |e: sqlx::Error| StoreError {
+        reason: format!("Failed to add namespace to gene_mappings: {e}"),
+        transient: false,
+    };
+    let has_namespace: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = 'gene_mappings' \
+         AND column_name = 'namespace')",
+    )
+    .fetch_one(dbconn)
+    .await
+    .map_err(migrate)?;
+    if has_namespace {
+        return Ok(());
+    }
+
+    info!("adding namespace to the key of gene_mappings");
+    let mut tx = dbconn.begin().await.map_err(migrate)?;
+    for ddl in [
+        "ALTER TABLE gene_mappings ADD COLUMN namespace TEXT NOT NULL DEFAULT ''",
+        "ALTER TABLE gene_mappings DROP CONSTRAINT gene_mappings_pkey",
+        "ALTER TABLE gene_mappings ADD PRIMARY KEY (namespace, path)",
+    ] {
+        sqlx::query(ddl).execute(&mut *tx).await.map_err(migrate)?;
+    }
+    tx.commit().await.map_err(migrate)?;
+    Ok(())
+}
+
+/// migrate dbs created before snapshots had the version of the state.  a
+/// snapshot is only an optimization so the old ones are dropped rather than
+/// restoring their actors at version 0 - the journal replays the right count
+async fn add_version_to_snapshots_if_not_exist(dbconn: &PgPool) -> StoreResult<()> {
+    let migrate = |e: sqlx::Error| StoreError {
+        reason: format!("Failed to add version to snapshots: {e}"),
+        transient: false,
+    };
+    let has_version: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = 'snapshots' \
+         AND column_name = 'version')",
+    )
+    .fetch_one(dbconn)
+    .await
+    .map_err(migrate)?;
+    if has_version {
+        return Ok(());
+    }
+
+    info!("adding version to snapshots");
+    let mut tx = dbconn.begin().await.map_err(migrate)?;
+    for ddl in [
+        "DELETE FROM snapshots",
+        "ALTER TABLE snapshots ADD COLUMN version BIGINT NOT NULL DEFAULT 0",
+    ] {
+        sqlx::query(ddl).execute(&mut *tx).await.map_err(migrate)?;
+    }
+    tx.commit().await.map_err(migrate)?;
+    Ok(())
+}
+
+impl PostgresStore {
+    /// connect to the `postgres://` url and define the tables
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError`](../store_actor_sqlite/struct.StoreError.html) if the db can not be
+    /// connected to or initialized
+    pub async fn connect(db_url: &str) -> StoreResult<Self> {
+        let dbconn = PgPool::connect(db_url).await.map_err(|e| {
+            error!("cannot connect to db: {e:?}");
+            StoreError {
+                reason: format!("{e:?}"),
+                transient: false,
+            }
+        })?;
+        define_tables_if_not_exist(&dbconn).await?;
+        add_namespace_to_gene_mappings_if_not_exist(&dbconn).await?;
+        add_version_to_snapshots_if_not_exist(&dbconn).await?;
+        info!("connected to postgres db");
+        Ok(Self { dbconn })
+    }
+}
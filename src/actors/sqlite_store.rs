@@ -0,0 +1,1142 @@
+//!This module implements the `Store` trait on a `SQLite` database - the default backend that
+//!keeps a namespace in a single `<namespace>.db` file.
+//!
+//!Connecting creates the db file if it does not exist, enables write-ahead-logging mode for
+//!append-only-style use if asked to, and defines or migrates the tables.
+//!
+//!The connections are pooled - see `SqliteOptions` for the size of the pool and how long a
+//!connection waits on a locked db before failing with "database is locked".
+
+use crate::actors::actor::State;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::GeneType;
+use crate::actors::message::GeneMapping;
+use crate::actors::message::IndexLabels;
+use crate::actors::message::JournalStats;
+use crate::actors::message::Labels;
+use crate::actors::message::Message;
+use crate::actors::message::ObservationStats;
+use crate::actors::store::cap_replay;
+use crate::actors::store::gene_type_json;
+use crate::actors::store::labels_json;
+use crate::actors::store::params_json;
+use crate::actors::store::prefix_pattern;
+use crate::actors::store::reset_marker_timestamp;
+use crate::actors::store::row_datetime;
+use crate::actors::store::row_to_gene_mapping;
+use crate::actors::store::row_to_observations;
+use crate::actors::store::row_to_path_stats;
+use crate::actors::store::row_to_snapshot;
+use crate::actors::store::row_to_stats;
+use crate::actors::store::stream_rows;
+use crate::actors::store::values_json;
+use crate::actors::store::Journal;
+use crate::actors::store::Store;
+use crate::actors::store::UpdateOutcome;
+use crate::actors::store::UpdateRow;
+use crate::actors::store::RESET_MARKER;
+use crate::actors::store_actor_sqlite::StoreError;
+use crate::actors::store_actor_sqlite::StoreResult;
+use crate::actors::value::Value;
+use crate::utils::nvtime::OffsetDateTimeWrapper;
+use async_trait::async_trait;
+use serde_json::from_str;
+use sqlx::error::DatabaseError;
+use sqlx::sqlite::Sqlite;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::sqlite::SqliteJournalMode;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::sqlite::SqliteRow;
+pub use sqlx::sqlite::SqliteSynchronous;
+use sqlx::Row;
+use sqlx::SqlitePool;
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+/// the sqlite code of a primary key constraint violation
+const PRIMARY_KEY_VIOLATION: &str = "1555";
+
+pub struct SqliteStore {
+    pub dbconn: SqlitePool,
+}
+
+/// the most pooled connections to the db file when not configured
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 8;
+
+/// how long a connection waits on a locked db when not configured
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// tuning of the connections to a `SQLite` db file
+#[derive(Debug, Clone, Copy)]
+pub struct SqliteOptions {
+    /// the most connections in the pool - readers of a WAL db do not block
+    /// each other or the writer so more connections serve more concurrent
+    /// queries
+    pub max_connections: u32,
+    /// how long a statement waits for a lock held by another connection
+    /// before it fails with "database is locked"
+    pub busy_timeout: Duration,
+    /// the `synchronous` pragma - without it `NORMAL` with write-ahead-logging,
+    /// which can lose the last commits on a power loss but never corrupts the
+    /// db, and `FULL` without it
+    pub synchronous: Option<SqliteSynchronous>,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            busy_timeout: DEFAULT_BUSY_TIMEOUT,
+            synchronous: None,
+        }
+    }
+}
+
+/// remove the mapping of `path` in `namespace` only - a mapping persisted
+/// before mappings had a namespace is shared by every namespace so it is
+/// never removed by one of them
+async fn delete_gene_mapping<'e, E>(
+    dbconn: E,
+    namespace: &str,
+    path: &str,
+) -> Result<u64, sqlx::error::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    Ok(
+        sqlx::query("DELETE FROM gene_mappings WHERE path = ? AND namespace = ?")
+            .bind(path)
+            .bind(namespace)
+            .execute(dbconn)
+            .await?
+            .rows_affected(),
+    )
+}
+
+async fn insert_gene_mapping<'e, E>(
+    dbconn: E,
+    namespace: &str,
+    path: &str,
+    gene_type: &GeneType,
+    params: &GeneParams,
+) -> Result<(), sqlx::error::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    match sqlx::query(
+        "INSERT INTO gene_mappings (namespace, path, gene_type, params) VALUES (?,?,?,?)",
+    )
+    .bind(namespace)
+    .bind(path)
+    .bind(gene_type_json(gene_type))
+    .bind(params_json(params))
+    .execute(dbconn)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!("persisting gene mapping for {} failed: {:?}", path, e);
+            Err(e)
+        }
+    }
+}
+
+/// journal one observation - a duplicate of a journaled path and timestamp
+/// is reported as `UpdateOutcome::Duplicate`
+async fn insert_update<'e, E, T: Value>(
+    dbconn: E,
+    path: &str,
+    datetime: OffsetDateTime,
+    sequence: OffsetDateTime,
+    values: &State<T>,
+    labels: &Labels,
+) -> StoreResult<UpdateOutcome>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let dt_wrapper = OffsetDateTimeWrapper::new(datetime);
+    let sequence_wrapper = OffsetDateTimeWrapper::new(sequence);
+    let values_str = values_json(values)?;
+    let labels_str = labels_json(labels)?;
+
+    match sqlx::query(
+        "INSERT INTO updates (path, timestamp, sequence, values_str, labels) VALUES (?,?,?,?,?)",
+    )
+    .bind(path)
+    .bind(dt_wrapper.datetime_num)
+    .bind(sequence_wrapper.datetime_num)
+    .bind(values_str)
+    .bind(labels_str)
+    .execute(dbconn)
+    .await
+    {
+        Ok(_) => Ok(UpdateOutcome::Persisted),
+        Err(sqlx::Error::Database(db_error))
+            if db_error
+                .try_downcast_ref::<sqlx::sqlite::SqliteError>()
+                .and_then(|e| e.code())
+                .as_deref()
+                == Some(PRIMARY_KEY_VIOLATION) =>
+        {
+            debug!("jrnling for {path} is a duplicate: {db_error:?}");
+            Ok(UpdateOutcome::Duplicate)
+        }
+        Err(e) => {
+            warn!("jrnling for {} failed: {:?}", path, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// the observations of a path that carry every one of `labels` in
+/// observation time order
+fn series_sql(labels: &Labels) -> String {
+    let mut sql = String::from(
+        "SELECT timestamp, values_str, labels FROM updates WHERE path = ? AND values_str <> ?",
+    );
+    for _ in labels {
+        sql.push_str(" AND json_extract(labels, ?) = ?");
+    }
+    sql.push_str(" ORDER BY CAST(timestamp AS INTEGER)");
+    sql
+}
+
+/// bind the path and the `labels` of a `series_sql` query
+fn series_query<'q>(
+    sql: &'q str,
+    path: &'q str,
+    labels: &'q Labels,
+) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+    let mut query = sqlx::query(sql).bind(path).bind(RESET_MARKER);
+    for (key, value) in labels {
+        // quote the key so that it is never read as json path syntax
+        query = query
+            .bind(format!("$.\"{}\"", key.replace('"', "\\\"")))
+            .bind(value);
+    }
+    query
+}
+
+/// decode a `timestamp, values_str, labels` row into an observation
+fn decode_observations<T: Value>(
+    path: &str,
+    row: &SqliteRow,
+) -> Result<Message<T>, sqlx::error::Error> {
+    let timestamp = from_str(row.try_get(0)?).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    row_to_observations(path, timestamp, row.try_get(1)?, row.try_get(2)?)
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+/// the journal rows of a path after a row with the key they replay in -
+/// observations replay in timestamp order and a reset right after the latest
+/// observation journaled before it, or first if there is none
+const REPLAY_ROWS: &str = "SELECT rowid AS row_id, timestamp, values_str, labels, \
+     CASE WHEN values_str = ? THEN COALESCE((SELECT MAX(CAST(earlier.timestamp AS INTEGER)) \
+     FROM updates AS earlier WHERE earlier.path = updates.path AND earlier.rowid < updates.rowid), \
+     CAST(timestamp AS INTEGER)) ELSE CAST(timestamp AS INTEGER) END AS replay_at, \
+     values_str = ? AS is_reset FROM updates WHERE path = ? AND rowid > ?";
+
+const REPLAY_ORDER: &str = "replay_at, is_reset, row_id";
+
+#[async_trait]
+impl<T: Value> Journal<T> for SqliteStore {
+    async fn insert_update(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        sequence: OffsetDateTime,
+        values: &State<T>,
+        labels: &Labels,
+    ) -> StoreResult<UpdateOutcome> {
+        insert_update(&self.dbconn, path, datetime, sequence, values, labels).await
+    }
+
+    async fn insert_updates(
+        &self,
+        rows: &[UpdateRow<T>],
+    ) -> StoreResult<Vec<StoreResult<UpdateOutcome>>> {
+        let mut tx = self.dbconn.begin().await?;
+        let mut outcomes = Vec::with_capacity(rows.len());
+        for row in rows {
+            // a failed insert only rolls back its own statement
+            outcomes.push(
+                insert_update(
+                    &mut *tx,
+                    &row.path,
+                    row.datetime,
+                    row.sequence,
+                    &row.values,
+                    &row.labels,
+                )
+                .await,
+            );
+        }
+        tx.commit().await?;
+        Ok(outcomes)
+    }
+
+    async fn insert_reset(&self, path: &str, sequence: OffsetDateTime) -> StoreResult<()> {
+        sqlx::query("INSERT INTO updates (path, timestamp, sequence, values_str) VALUES (?,?,?,?)")
+            .bind(path)
+            .bind(reset_marker_timestamp(sequence))
+            .bind(OffsetDateTimeWrapper::new(sequence).datetime_num)
+            .bind(RESET_MARKER)
+            .execute(&self.dbconn)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_values(
+        &self,
+        path: &str,
+        after: i64,
+        max_replay_events: Option<usize>,
+    ) -> StoreResult<Vec<Message<T>>> {
+        let Some(max) = max_replay_events else {
+            return Ok(sqlx::query(&format!(
+                "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS}) ORDER BY {REPLAY_ORDER}"
+            ))
+            .bind(RESET_MARKER)
+            .bind(RESET_MARKER)
+            .bind(path)
+            .bind(after)
+            .try_map(|row: SqliteRow| decode_observations(path, &row))
+            .fetch_all(&self.dbconn)
+            .await?);
+        };
+        // the newest rows in replay order, not the latest journaled
+        let limit = i64::try_from(max.saturating_add(1)).unwrap_or(i64::MAX);
+        let values = sqlx::query(&format!(
+            "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS} \
+             ORDER BY replay_at DESC, is_reset DESC, row_id DESC LIMIT ?) ORDER BY {REPLAY_ORDER}"
+        ))
+        .bind(RESET_MARKER)
+        .bind(RESET_MARKER)
+        .bind(path)
+        .bind(after)
+        .bind(limit)
+        .try_map(|row: SqliteRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok(cap_replay(path, max, values))
+    }
+
+    async fn get_journal(&self, prefix: &str) -> StoreResult<Vec<Message<T>>> {
+        Ok(sqlx::query(
+            "SELECT path, timestamp, values_str, labels FROM updates \
+             WHERE path LIKE ? ESCAPE '\\' AND values_str <> ? ORDER BY rowid",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(RESET_MARKER)
+        .try_map(|row: SqliteRow| {
+            let path: String = row.try_get(0)?;
+            let timestamp =
+                from_str(row.try_get(1)?).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            row_to_observations(&path, timestamp, row.try_get(2)?, row.try_get(3)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn insert_snapshot(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<()> {
+        sqlx::query(
+            "INSERT INTO snapshots (path, last_row, timestamp, version, values_str) \
+             SELECT ?, COALESCE(MAX(rowid), 0), ?, ?, ? FROM updates WHERE path = ? \
+             ON CONFLICT (path) DO UPDATE SET last_row = excluded.last_row, \
+             timestamp = excluded.timestamp, version = excluded.version, \
+             values_str = excluded.values_str",
+        )
+        .bind(path)
+        .bind(OffsetDateTimeWrapper::new(datetime).datetime_num)
+        .bind(i64::try_from(version).unwrap_or(i64::MAX))
+        .bind(values_json(values)?)
+        .bind(path)
+        .execute(&self.dbconn)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_snapshot(&self, path: &str) -> StoreResult<Option<(i64, Message<T>)>> {
+        Ok(sqlx::query(
+            "SELECT last_row, timestamp, version, values_str FROM snapshots WHERE path = ?",
+        )
+        .bind(path)
+        .try_map(|row: SqliteRow| {
+            let snapshot = row_to_snapshot(path, row.try_get(1)?, row.try_get(2)?, row.try_get(3)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            Ok((row.try_get(0)?, snapshot))
+        })
+        .fetch_optional(&self.dbconn)
+        .await?)
+    }
+
+    async fn get_values_before(
+        &self,
+        path: &str,
+        after: i64,
+        before: OffsetDateTime,
+    ) -> StoreResult<(i64, Vec<Message<T>>)> {
+        let last_row: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(rowid) FROM updates WHERE path = ? AND rowid > ? AND rowid < \
+             COALESCE((SELECT MIN(rowid) FROM updates WHERE path = ? AND values_str <> ? \
+             AND CAST(timestamp AS INTEGER) >= ?), ?)",
+        )
+        .bind(path)
+        .bind(after)
+        .bind(path)
+        .bind(RESET_MARKER)
+        .bind(OffsetDateTimeWrapper::new(before).datetime_num)
+        .bind(i64::MAX)
+        .fetch_one(&self.dbconn)
+        .await?;
+        let Some(last_row) = last_row else {
+            return Ok((after, vec![]));
+        };
+        // the same order as `get_values` replays them in
+        let values = sqlx::query(&format!(
+            "SELECT timestamp, values_str, labels FROM ({REPLAY_ROWS} AND rowid <= ?) \
+             ORDER BY {REPLAY_ORDER}"
+        ))
+        .bind(RESET_MARKER)
+        .bind(RESET_MARKER)
+        .bind(path)
+        .bind(after)
+        .bind(last_row)
+        .try_map(|row: SqliteRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok((last_row, values))
+    }
+
+    async fn compact(
+        &self,
+        path: &str,
+        last_row: i64,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<u64> {
+        let mut tx = self.dbconn.begin().await?;
+        sqlx::query(
+            "INSERT INTO snapshots (path, last_row, timestamp, version, values_str) \
+             VALUES (?,?,?,?,?) ON CONFLICT (path) DO UPDATE SET last_row = excluded.last_row, \
+             timestamp = excluded.timestamp, version = excluded.version, \
+             values_str = excluded.values_str",
+        )
+        .bind(path)
+        .bind(last_row)
+        .bind(OffsetDateTimeWrapper::new(datetime).datetime_num)
+        .bind(i64::try_from(version).unwrap_or(i64::MAX))
+        .bind(values_json(values)?)
+        .execute(&mut *tx)
+        .await?;
+        let removed = sqlx::query("DELETE FROM updates WHERE path = ? AND rowid <= ?")
+            .bind(path)
+            .bind(last_row)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    async fn get_series(&self, path: &str, labels: &Labels) -> StoreResult<Vec<Message<T>>> {
+        let sql = series_sql(labels);
+        Ok(series_query(&sql, path, labels)
+            .try_map(|row: SqliteRow| decode_observations(path, &row))
+            .fetch_all(&self.dbconn)
+            .await?)
+    }
+
+    fn stream_series(&self, path: &str, labels: &Labels, stream_to: mpsc::Sender<Message<T>>) {
+        let dbconn = self.dbconn.clone();
+        let path = path.to_string();
+        let labels = labels.clone();
+        tokio::spawn(async move {
+            let sql = series_sql(&labels);
+            let rows = series_query(&sql, &path, &labels)
+                .try_map(|row: SqliteRow| decode_observations(&path, &row))
+                .fetch(&dbconn);
+            stream_rows(&path, rows, &stream_to).await;
+        });
+    }
+
+    async fn get_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<Message<T>>> {
+        Ok(sqlx::query(
+            "SELECT timestamp, values_str, labels FROM updates WHERE path = ? AND values_str <> ? \
+             AND CAST(sequence AS INTEGER) BETWEEN ? AND ? \
+             ORDER BY CAST(sequence AS INTEGER), rowid LIMIT ? OFFSET ?",
+        )
+        .bind(path)
+        .bind(RESET_MARKER)
+        .bind(OffsetDateTimeWrapper::new(from).datetime_num)
+        .bind(OffsetDateTimeWrapper::new(to).datetime_num)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .try_map(|row: SqliteRow| decode_observations(path, &row))
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn count_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> StoreResult<u64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM updates WHERE path = ? AND values_str <> ? \
+             AND CAST(sequence AS INTEGER) BETWEEN ? AND ?",
+        )
+        .bind(path)
+        .bind(RESET_MARKER)
+        .bind(OffsetDateTimeWrapper::new(from).datetime_num)
+        .bind(OffsetDateTimeWrapper::new(to).datetime_num)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(u64::try_from(count).unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn insert_gene_mapping(
+        &self,
+        namespace: &str,
+        path: &str,
+        gene_type: &GeneType,
+        params: &GeneParams,
+    ) -> StoreResult<()> {
+        Ok(insert_gene_mapping(&self.dbconn, namespace, path, gene_type, params).await?)
+    }
+
+    async fn insert_gene_mappings(
+        &self,
+        namespace: &str,
+        mappings: Vec<(String, GeneType, GeneParams)>,
+    ) -> StoreResult<Vec<(String, Result<GeneType, String>)>> {
+        let mut tx = self.dbconn.begin().await?;
+        let mut results = Vec::new();
+        for (path, gene_type, params) in mappings {
+            match insert_gene_mapping(&mut *tx, namespace, &path, &gene_type, &params).await {
+                Ok(()) => results.push((path, Ok(gene_type))),
+                Err(e) => results.push((path, Err(e.to_string()))),
+            }
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn get_mappings_for_ns(&self, namespace: &str) -> StoreResult<Vec<GeneMapping>> {
+        debug!("loading mappings for namespace {namespace}");
+        Ok(sqlx::query(
+            "SELECT path, gene_type, params FROM gene_mappings \
+                 WHERE namespace = ? OR namespace = '' ORDER BY namespace <> '', path",
+        )
+        .bind(namespace)
+        .try_map(|row: SqliteRow| {
+            let path = row.try_get(0).map_err(|e| {
+                error!("cannot read path");
+                sqlx::Error::Decode(Box::new(e))
+            })?;
+            row_to_gene_mapping(path, row.try_get(1)?, row.try_get(2)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn delete_gene_mapping(&self, namespace: &str, path: &str) -> StoreResult<u64> {
+        Ok(delete_gene_mapping(&self.dbconn, namespace, path).await?)
+    }
+
+    async fn get_shared_mapping(&self, path: &str) -> StoreResult<Option<GeneMapping>> {
+        Ok(sqlx::query(
+            "SELECT gene_type, params FROM gene_mappings WHERE path = ? AND namespace = ''",
+        )
+        .bind(path)
+        .try_map(|row: SqliteRow| {
+            row_to_gene_mapping(String::from(path), row.try_get(0)?, row.try_get(1)?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .fetch_optional(&self.dbconn)
+        .await?)
+    }
+
+    async fn insert_edges(&self, namespace: &str, edges: &[(String, String)]) -> StoreResult<()> {
+        let mut tx = self.dbconn.begin().await?;
+        for (parent, child) in edges {
+            sqlx::query("INSERT OR IGNORE INTO edges (namespace, parent, child) VALUES (?,?,?)")
+                .bind(namespace)
+                .bind(parent)
+                .bind(child)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_edges(&self, namespace: &str) -> StoreResult<Vec<(String, String)>> {
+        Ok(
+            sqlx::query_as("SELECT parent, child FROM edges WHERE namespace = ? ORDER BY rowid")
+                .bind(namespace)
+                .fetch_all(&self.dbconn)
+                .await?,
+        )
+    }
+
+    async fn insert_index_label(&self, namespace: &str, idx: i32, name: &str) -> StoreResult<()> {
+        sqlx::query("INSERT OR REPLACE INTO index_labels (namespace, idx, name) VALUES (?,?,?)")
+            .bind(namespace)
+            .bind(idx)
+            .bind(name)
+            .execute(&self.dbconn)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_index_labels(&self, namespace: &str) -> StoreResult<IndexLabels> {
+        let labels: Vec<(i32, String)> =
+            sqlx::query_as("SELECT idx, name FROM index_labels WHERE namespace = ?")
+                .bind(namespace)
+                .fetch_all(&self.dbconn)
+                .await?;
+        Ok(labels.into_iter().collect())
+    }
+
+    async fn delete(&self, namespace: &str, path: &str, mapping: bool) -> StoreResult<u64> {
+        let mut tx = self.dbconn.begin().await?;
+        let updates = sqlx::query("DELETE FROM updates WHERE path = ?")
+            .bind(path)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM snapshots WHERE path = ?")
+            .bind(path)
+            .execute(&mut *tx)
+            .await?;
+        if mapping {
+            delete_gene_mapping(&mut *tx, namespace, path).await?;
+        }
+        tx.commit().await?;
+        Ok(updates)
+    }
+
+    async fn prune(
+        &self,
+        prefix: &str,
+        before: OffsetDateTime,
+        keep_last: usize,
+    ) -> StoreResult<u64> {
+        let pruned = sqlx::query(
+            "DELETE FROM updates WHERE rowid IN (SELECT rowid FROM \
+             (SELECT rowid, CAST(sequence AS INTEGER) AS arrived, \
+             ROW_NUMBER() OVER (PARTITION BY path ORDER BY rowid DESC) AS newest FROM updates \
+             WHERE path LIKE ? ESCAPE '\\') WHERE arrived < ? AND newest > ?)",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(OffsetDateTimeWrapper::new(before).datetime_num)
+        .bind(i64::try_from(keep_last).unwrap_or(i64::MAX))
+        .execute(&self.dbconn)
+        .await?
+        .rows_affected();
+        sqlx::query("VACUUM").execute(&self.dbconn).await?;
+        Ok(pruned)
+    }
+
+    async fn list_paths(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT DISTINCT path FROM updates WHERE path LIKE ? ESCAPE '\\' ORDER BY path LIMIT ? OFFSET ?",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.dbconn)
+        .await?)
+    }
+
+    async fn list_paths_since(
+        &self,
+        prefix: &str,
+        since: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<(Vec<String>, OffsetDateTime)> {
+        let after = OffsetDateTimeWrapper::new(since).datetime_num;
+        let latest: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(CAST(sequence AS INTEGER)) FROM updates \
+             WHERE path LIKE ? ESCAPE '\\' AND CAST(sequence AS INTEGER) > ?",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(after)
+        .fetch_one(&self.dbconn)
+        .await?;
+        let Some(latest) = latest else {
+            return Ok((vec![], since));
+        };
+        let paths = sqlx::query_scalar(
+            "SELECT DISTINCT path FROM updates WHERE path LIKE ? ESCAPE '\\' \
+             AND CAST(sequence AS INTEGER) > ? AND CAST(sequence AS INTEGER) <= ? \
+             ORDER BY path LIMIT ? OFFSET ?",
+        )
+        .bind(prefix_pattern(prefix))
+        .bind(after)
+        .bind(latest)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.dbconn)
+        .await?;
+        Ok((paths, row_datetime(latest)))
+    }
+
+    async fn stats(&self, namespace: &str, prefix: &str) -> StoreResult<JournalStats> {
+        let (paths, observations, earliest, latest): (i64, i64, Option<i64>, Option<i64>) =
+            sqlx::query_as(
+                "SELECT COUNT(DISTINCT path), COUNT(*), MIN(CAST(sequence AS INTEGER)), MAX(CAST(sequence AS INTEGER)) \
+                 FROM updates WHERE path LIKE ? ESCAPE '\\' AND values_str <> ?",
+            )
+            .bind(prefix_pattern(prefix))
+            .bind(RESET_MARKER)
+            .fetch_one(&self.dbconn)
+            .await?;
+        let gene_mappings: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT path) FROM gene_mappings WHERE namespace = ? OR namespace = ''",
+        )
+        .bind(namespace)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(row_to_stats(
+            paths,
+            observations,
+            earliest,
+            latest,
+            gene_mappings,
+        ))
+    }
+
+    async fn path_stats(&self, path: &str) -> StoreResult<ObservationStats> {
+        let (observations, first, last): (i64, Option<i64>, Option<i64>) = sqlx::query_as(
+            "SELECT COUNT(*), MIN(CAST(timestamp AS INTEGER)), MAX(CAST(timestamp AS INTEGER)) \
+             FROM updates WHERE path = ? AND values_str <> ?",
+        )
+        .bind(path)
+        .bind(RESET_MARKER)
+        .fetch_one(&self.dbconn)
+        .await?;
+        Ok(row_to_path_stats(observations, first, last))
+    }
+
+    async fn ping(&self) -> StoreResult<()> {
+        sqlx::query("SELECT 1").execute(&self.dbconn).await?;
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.dbconn.close().await;
+    }
+}
+
+async fn define_gene_mapping_table_if_not_exist(
+    db_url: &str,
+    dbconn: &SqlitePool,
+) -> StoreResult<()> {
+    let rows = sqlx::query("PRAGMA journal_mode;")
+        .fetch_all(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to fetch journal_mode: {e}"),
+            transient: false,
+        })?;
+
+    let journal_mode: String = rows[0].get("journal_mode");
+    info!("connected to db in journal_mode for mappings: {journal_mode}");
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS gene_mappings (
+              namespace TEXT NOT NULL DEFAULT '',
+              path TEXT NOT NULL,
+              gene_type TEXT NOT NULL,
+              params TEXT,
+              PRIMARY KEY (namespace, path)
+        )",
+    )
+    .execute(dbconn)
+    .await
+    .map_err(|e| StoreError {
+        reason: format!("Failed to create file {db_url}: {e}"),
+        transient: false,
+    })?;
+
+    Ok(())
+}
+
+/// migrate dbs created before a table had the nullable TEXT `column`, ie:
+/// observations before they had labels or gene mappings before params
+async fn add_column_if_not_exist(
+    db_url: &str,
+    dbconn: &SqlitePool,
+    table: &str,
+    column: &str,
+) -> StoreResult<()> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({table});"))
+        .fetch_all(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to fetch columns of {table} in {db_url}: {e}"),
+            transient: false,
+        })?;
+
+    if columns.iter().any(|c| c.get::<String, _>("name") == column) {
+        return Ok(());
+    }
+
+    info!("adding {column} column to {table} in {db_url}");
+    sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN {column} TEXT"))
+        .execute(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to add {column} column to {table} in {db_url}: {e}"),
+            transient: false,
+        })?;
+
+    Ok(())
+}
+
+/// migrate dbs created before gene mappings had a namespace - sqlite can not
+/// change a primary key so the table is copied into one keyed by namespace and
+/// path.  the copied mappings have the empty namespace and are loaded by every
+/// namespace like they were before.
+async fn add_namespace_to_gene_mappings_if_not_exist(
+    db_url: &str,
+    dbconn: &SqlitePool,
+) -> StoreResult<()> {
+    let columns = sqlx::query("PRAGMA table_info(gene_mappings);")
+        .fetch_all(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to fetch columns of gene_mappings in {db_url}: {e}"),
+            transient: false,
+        })?;
+
+    if columns
+        .iter()
+        .any(|c| c.get::<String, _>("name") == "namespace")
+    {
+        return Ok(());
+    }
+
+    info!("adding namespace to the key of gene_mappings in {db_url}");
+    let migrate = |e: sqlx::Error| StoreError {
+        reason: format!("Failed to add namespace to gene_mappings in {db_url}: {e}"),
+        transient: false,
+    };
+    let mut tx = dbconn.begin().await.map_err(migrate)?;
+    for ddl in [
+        "CREATE TABLE gene_mappings_by_namespace (
+              namespace TEXT NOT NULL DEFAULT '',
+              path TEXT NOT NULL,
+              gene_type TEXT NOT NULL,
+              params TEXT,
+              PRIMARY KEY (namespace, path)
+        )",
+        "INSERT INTO gene_mappings_by_namespace (namespace, path, gene_type, params) \
+         SELECT '', path, gene_type, params FROM gene_mappings",
+        "DROP TABLE gene_mappings",
+        "ALTER TABLE gene_mappings_by_namespace RENAME TO gene_mappings",
+    ] {
+        sqlx::query(ddl).execute(&mut *tx).await.map_err(migrate)?;
+    }
+    tx.commit().await.map_err(migrate)?;
+
+    Ok(())
+}
+
+/// define table if it does not exist and log to console the journal mode
+async fn define_updates_table_if_not_exist(db_url: &str, dbconn: &SqlitePool) -> StoreResult<()> {
+    let rows = sqlx::query("PRAGMA journal_mode;")
+        .fetch_all(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to fetch journal_mode: {e}"),
+            transient: false,
+        })?;
+
+    let journal_mode: String = rows[0].get("journal_mode");
+    info!("connected to db in journal_mode: {journal_mode}");
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS updates (
+              path TEXT NOT NULL,
+              timestamp TEXT NOT NULL,
+              sequence TEXT NOT NULL,
+              values_str TEXT NOT NULL,
+              labels TEXT,
+              PRIMARY KEY (path, timestamp)
+        )",
+    )
+    .execute(dbconn)
+    .await
+    .map_err(|e| StoreError {
+        reason: format!("Failed to create file {db_url}: {e}"),
+        transient: false,
+    })?;
+
+    Ok(())
+}
+
+/// define the table of the latest snapshot of each path if it does not exist
+async fn define_snapshots_table_if_not_exist(db_url: &str, dbconn: &SqlitePool) -> StoreResult<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS snapshots (
+              path TEXT NOT NULL,
+              last_row INTEGER NOT NULL,
+              timestamp INTEGER NOT NULL,
+              version INTEGER NOT NULL DEFAULT 0,
+              values_str TEXT NOT NULL,
+              PRIMARY KEY (path)
+        )",
+    )
+    .execute(dbconn)
+    .await
+    .map_err(|e| StoreError {
+        reason: format!("Failed to create file {db_url}: {e}"),
+        transient: false,
+    })?;
+
+    Ok(())
+}
+
+/// migrate dbs created before snapshots had the version of the state.  a
+/// snapshot is only an optimization so the old ones are dropped rather than
+/// restoring their actors at version 0 - the journal replays the right count
+async fn add_version_to_snapshots_if_not_exist(
+    db_url: &str,
+    dbconn: &SqlitePool,
+) -> StoreResult<()> {
+    let columns = sqlx::query("PRAGMA table_info(snapshots);")
+        .fetch_all(dbconn)
+        .await
+        .map_err(|e| StoreError {
+            reason: format!("Failed to fetch columns of snapshots in {db_url}: {e}"),
+            transient: false,
+        })?;
+
+    if columns
+        .iter()
+        .any(|c| c.get::<String, _>("name") == "version")
+    {
+        return Ok(());
+    }
+
+    info!("adding version to snapshots in {db_url}");
+    let migrate = |e: sqlx::Error| StoreError {
+        reason: format!("Failed to add version to snapshots in {db_url}: {e}"),
+        transient: false,
+    };
+    let mut tx = dbconn.begin().await.map_err(migrate)?;
+    for ddl in [
+        "DELETE FROM snapshots",
+        "ALTER TABLE snapshots ADD COLUMN version INTEGER NOT NULL DEFAULT 0",
+    ] {
+        sqlx::query(ddl).execute(&mut *tx).await.map_err(migrate)?;
+    }
+    tx.commit().await.map_err(migrate)?;
+
+    Ok(())
+}
+
+/// define the table of the edges of the path hierarchy if it does not exist
+async fn define_edges_table_if_not_exist(db_url: &str, dbconn: &SqlitePool) -> StoreResult<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS edges (
+              namespace TEXT NOT NULL,
+              parent TEXT NOT NULL,
+              child TEXT NOT NULL,
+              PRIMARY KEY (namespace, parent, child)
+        )",
+    )
+    .execute(dbconn)
+    .await
+    .map_err(|e| StoreError {
+        reason: format!("Failed to create file {db_url}: {e}"),
+        transient: false,
+    })?;
+
+    Ok(())
+}
+
+/// define the table of the names of the indices of a namespace if it does not
+/// exist
+async fn define_index_labels_table_if_not_exist(
+    db_url: &str,
+    dbconn: &SqlitePool,
+) -> StoreResult<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS index_labels (
+              namespace TEXT NOT NULL,
+              idx INTEGER NOT NULL,
+              name TEXT NOT NULL,
+              PRIMARY KEY (namespace, idx)
+        )",
+    )
+    .execute(dbconn)
+    .await
+    .map_err(|e| StoreError {
+        reason: format!("Failed to create file {db_url}: {e}"),
+        transient: false,
+    })?;
+
+    Ok(())
+}
+
+/// the options of every connection of the pool - write-ahead-logging mode
+/// for append-only-style db if asked to, and the busy timeout and
+/// synchronous pragmas
+fn connect_options(
+    db_url: &str,
+    write_ahead_logging: bool,
+    options: &SqliteOptions,
+) -> StoreResult<SqliteConnectOptions> {
+    let connect_options = SqliteConnectOptions::from_str(db_url)
+        .map_err(|e| StoreError {
+            reason: format!("bad db url {db_url}: {e}"),
+            transient: false,
+        })?
+        .busy_timeout(options.busy_timeout);
+    Ok(if write_ahead_logging {
+        connect_options
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(options.synchronous.unwrap_or(SqliteSynchronous::Normal))
+    } else {
+        connect_options.synchronous(options.synchronous.unwrap_or(SqliteSynchronous::Full))
+    })
+}
+
+/// the file name of the db of a namespace when no db path is given - the
+/// levels of a `/` separated namespace are joined with `_` so that the file
+/// of `/acme/sensors` is `acme_sensors.db` in the working directory rather
+/// than a file under the filesystem root
+#[must_use]
+pub fn db_file_name(namespace: &str) -> String {
+    let stem = namespace
+        .split('/')
+        .filter(|level| !level.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if stem.is_empty() {
+        String::from("actors.db")
+    } else {
+        format!("{stem}.db")
+    }
+}
+
+impl SqliteStore {
+    /// open the `<namespace>.db` file - the namespace is used as a path
+    /// prefix as is, see [`db_file_name`] for the file of a namespace
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError`](../store_actor_sqlite/struct.StoreError.html) if the db can not be
+    /// created, connected to, or initialized
+    pub async fn open(namespace: &str, write_ahead_logging: bool) -> StoreResult<Self> {
+        Self::connect(&format!("{namespace}.db"), write_ahead_logging).await
+    }
+
+    /// `connect_with` the default `SqliteOptions`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError`](../store_actor_sqlite/struct.StoreError.html) if the db can not be
+    /// created, connected to, or initialized
+    pub async fn connect(db_url: &str, write_ahead_logging: bool) -> StoreResult<Self> {
+        Self::connect_with(db_url, write_ahead_logging, &SqliteOptions::default()).await
+    }
+
+    /// multiple operations:
+    /// 1. initialize the DB if it does not exist
+    /// 2. connect a pool of at most `max_connections` with wal if configured
+    ///    and the busy timeout and synchronous pragmas of `options`
+    /// 3. report to console
+    /// 4. define or migrate the tables
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError`](../store_actor_sqlite/struct.StoreError.html) if the db can not be
+    /// created, connected to, or initialized
+    pub async fn connect_with(
+        db_url: &str,
+        write_ahead_logging: bool,
+        options: &SqliteOptions,
+    ) -> StoreResult<Self> {
+        let file_name = db_url
+            .trim_start_matches("sqlite://")
+            .split('?')
+            .next()
+            .unwrap_or_default();
+        let db_path = Path::new(file_name);
+        if !db_path.exists() {
+            match File::create(db_path) {
+                Ok(_) => debug!("File {} has been created", db_url),
+                Err(e) => {
+                    return Err(StoreError {
+                        reason: format!("Failed to create file {db_url}: {e}"),
+                        transient: false,
+                    });
+                }
+            }
+        }
+
+        // connect to db with wal if configured, and report to the console on
+        // how the db is configured
+        let dbconn = SqlitePoolOptions::new()
+            .max_connections(options.max_connections.max(1))
+            .connect_with(connect_options(db_url, write_ahead_logging, options)?)
+            .await
+            .map_err(|e| {
+                error!("cannot connect to db: {e:?}");
+                StoreError {
+                    reason: format!("{e:?}"),
+                    transient: false,
+                }
+            })?;
+        define_updates_table_if_not_exist(db_url, &dbconn).await?;
+        add_column_if_not_exist(db_url, &dbconn, "updates", "labels").await?;
+        define_gene_mapping_table_if_not_exist(db_url, &dbconn).await?;
+        add_column_if_not_exist(db_url, &dbconn, "gene_mappings", "params").await?;
+        add_namespace_to_gene_mappings_if_not_exist(db_url, &dbconn).await?;
+        define_snapshots_table_if_not_exist(db_url, &dbconn).await?;
+        add_version_to_snapshots_if_not_exist(db_url, &dbconn).await?;
+        define_edges_table_if_not_exist(db_url, &dbconn).await?;
+        define_index_labels_table_if_not_exist(db_url, &dbconn).await?;
+        Ok(Self { dbconn })
+    }
+}
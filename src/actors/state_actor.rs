@@ -2,47 +2,71 @@
 //! an instance of the actor keeping state computed from an arriving stream of
 //! observations. It processes three types of messages: `InitCmd`, `Update`, and
 //! `Query`. The `InitCmd` message initializes the state of the actor from
-//! previous events. The `Update` message updates the state of the actor and
+//! previous events, starting from the snapshot of its state when the stream
+//! begins with one. The `Update` message updates the state of the actor and
 //! responds with the current state report. The `Query` message simply responds
 //! with a copy of the current state report. The state actor also reports the
-//! update to the state to the output actor if it is specified.
+//! update to the state to the output actor if it is specified, and to every
+//! subscriber registered with a `Subscribe` message.  An actor created with
+//! `new_replaying` also reports its state after every event of the `InitCmd`
+//! replay, a resurrection is otherwise silent.  When an observation moves an
+//! index across a threshold of its gene the actor sends an `Alert` to the
+//! output actor and, if it was created with `new_alerting`, to its alert
+//! receiver before it responds.  A `Reset` starts the state over from empty
+//! so that an accumulator counts from zero again - a replay resets at the
+//! same place in the journal.
 
 use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
 use crate::actors::actor::State;
 use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::TimeScope;
 use crate::actors::message::Envelope;
 use crate::actors::message::Message;
+use crate::actors::message::MtHint;
 use crate::actors::message::NvError;
+use crate::actors::message::NvResult;
+use crate::actors::value::Value;
 use async_trait::async_trait;
+use std::collections::BTreeMap;
+use time::Date;
 use time::OffsetDateTime;
+use time::UtcOffset;
 use tokio::sync::mpsc;
 use tracing::debug;
 use tracing::error;
 use tracing::trace;
 use tracing::warn;
 
-// TODO: reject late reporters based on genes
-// TODO: reject late reporters based on genes
-// TODO: reject late reporters based on genes
-// TODO: reject late reporters based on genes
-// TODO: by default, counters are ok for late reports but guages are not
-
 /// the state actor is the heart of the system.  each digital twin has an
 /// instance of actor keeping state computed from an arriving stream of
 /// observations.
-pub struct StateActor {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
-    pub output: Option<Handle>,
-    pub state: State<f64>,
+pub struct StateActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Option<Handle<T>>,
+    pub state: State<T>,
     pub path: String,
-    pub gene: Box<dyn Gene<f64> + Send + Sync>,
+    pub gene: Box<dyn Gene<T> + Send + Sync>,
+    latest_observation: Option<OffsetDateTime>,
+    /// the number of observations applied to `state` - a replay of the
+    /// journal applies the same ones so the count is the same afterwards
+    version: u64,
+    /// day-scoped genes that retain buckets keep a separate state for every day observed - the
+    /// latest day is also the current `state`
+    buckets: BTreeMap<Date, State<T>>,
+    /// receivers of a report of every state change
+    subscribers: Vec<mpsc::Sender<Message<T>>>,
+    /// the receiver of the state after every event of the init replay
+    replay_to: Option<Handle<T>>,
+    /// the receiver of every `Alert` the actor raises
+    alert_to: Option<mpsc::Sender<Message<T>>>,
 }
 
 #[async_trait]
-impl Actor for StateActor {
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+impl<T: Value> Actor<T> for StateActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
         let Envelope {
             message,
             respond_to,
@@ -63,9 +87,29 @@ impl Actor for StateActor {
                             Message::EndOfStream {} => {
                                 break;
                             }
+                            Message::Snapshot {
+                                datetime,
+                                values,
+                                version,
+                                ..
+                            } => {
+                                self.restore(*datetime, values.clone(), *version);
+                                self.report_replay(*datetime).await;
+                            }
+                            Message::Reset { .. } => {
+                                self.reset();
+                                if let Some(latest) = self.latest_observation {
+                                    self.report_replay(latest).await;
+                                }
+                            }
+                            // rejected when it was first reported so it is skipped again
+                            _ if self.late_report(&message).is_some() => {}
                             _ => {
-                                if self.update_state(message.clone()) {
+                                if self.update_state(message.clone()).is_ok() {
                                     count += 1;
+                                    if let Message::Observations { datetime, .. } = &message {
+                                        self.report_replay(*datetime).await;
+                                    }
                                 } else {
                                     trace!("{} init closing stream.", self.path);
                                     break;
@@ -84,17 +128,69 @@ impl Actor for StateActor {
             Message::Observations { .. } => {
                 trace!("{} handling update", self.path);
 
-                if self.update_state(message.clone()) {
-                    respond_or_log_error(respond_to, Ok(self.get_state_rpt()));
-                } else {
-                    error!("Error applying operators in ask");
-                    respond_or_log_error(
-                        respond_to,
-                        Err(NvError {
-                            reason: String::from("cannot apply operators"),
-                        }),
-                    );
+                if let Some(late) = self.late_report(&message) {
+                    warn!("{late}");
+                    respond_or_log_error(respond_to, Ok(late));
+                    return;
                 }
+                let before = self.state.clone();
+                match self.update_state(message.clone()) {
+                    Ok(()) => {
+                        self.raise_alerts(&before).await;
+                        respond_or_log_error(respond_to, Ok(self.get_state_rpt()));
+                        self.notify_subscribers();
+                    }
+                    Err(e) => respond_or_log_error(respond_to, Err(e)),
+                }
+            }
+            Message::Reset { .. } => {
+                debug!("{} reset", self.path);
+                self.reset();
+                respond_or_log_error(respond_to, Ok(self.get_state_rpt()));
+                self.notify_subscribers();
+            }
+            Message::Subscribe { stream_to, .. } => {
+                debug!("{} new subscriber", self.path);
+                self.subscribers.push(stream_to);
+                respond_or_log_error(respond_to, Ok(self.get_state_rpt()));
+            }
+            Message::Query {
+                hint: MtHint::LatestObservation,
+                ..
+            } => {
+                // the datetime of the newest observation applied to this state
+                let latest = self.latest_observation.map_or_else(
+                    || Message::NotFound {
+                        path: self.path.clone(),
+                    },
+                    |datetime| Message::StateReport {
+                        datetime,
+                        path: self.path.clone(),
+                        values: self.state.clone(),
+                        version: self.version,
+                    },
+                );
+                respond_or_log_error(respond_to, Ok(latest));
+            }
+            Message::Query {
+                hint: MtHint::Bucket(day),
+                ..
+            } => {
+                let bucket = self.buckets.get(&day).map_or_else(
+                    || Message::NotFound {
+                        path: self.path.clone(),
+                    },
+                    |values| Message::StateReport {
+                        datetime: day.midnight().assume_utc(),
+                        path: self.path.clone(),
+                        values: values.clone(),
+                        version: self.version,
+                    },
+                );
+                respond_or_log_error(respond_to, Ok(bucket));
+            }
+            Message::IndicesQuery { indices, .. } => {
+                respond_or_log_error(respond_to, Ok(self.get_state_rpt_of(&indices)));
             }
             Message::Query { .. } => {
                 // respond with a copy of our new state if this is an 'ask'
@@ -118,26 +214,181 @@ impl Actor for StateActor {
 }
 
 /// actor private constructor
-impl StateActor {
-    fn update_state(&mut self, message: Message<f64>) -> bool {
-        match self.gene.apply_operators(self.state.clone(), message) {
+impl<T: Value> StateActor<T> {
+    fn update_state(&mut self, message: Message<T>) -> NvResult<()> {
+        let observed = match &message {
+            Message::Observations { datetime, .. } => Some(*datetime),
+            _ => None,
+        };
+        let bucket = match (self.gene.get_time_scope(), observed) {
+            (TimeScope::Day, Some(observed)) if self.gene.retains_buckets() => {
+                Some(observed.to_offset(UtcOffset::UTC).date())
+            }
+            _ => None,
+        };
+        let state = match bucket {
+            Some(day) => self.buckets.get(&day).cloned().unwrap_or_default(),
+            None => self.state.clone(),
+        };
+        match self.gene.apply_operators(state, message) {
             Ok(new_state) => {
-                self.state = new_state;
-                true
+                if let Some(day) = bucket {
+                    self.buckets.insert(day, new_state);
+                    if let Some((_, current)) = self.buckets.last_key_value() {
+                        self.state = current.clone();
+                    }
+                } else {
+                    self.state = new_state;
+                }
+                if let Some(observed) = observed {
+                    self.version += 1;
+                    if self
+                        .latest_observation
+                        .is_none_or(|latest| observed > latest)
+                    {
+                        self.latest_observation = Some(observed);
+                    }
+                }
+                Ok(())
             }
             Err(e) => {
                 error!("Error applying operators in ask: {e:?}");
-                false
+                Err(NvError {
+                    reason: format!("{} cannot apply operators: {e}", self.path),
+                })
+            }
+        }
+    }
+
+    /// the `LateReport` of an observation taken before the latest one applied
+    /// when the gene does not accept late reports
+    fn late_report(&self, message: &Message<T>) -> Option<Message<T>> {
+        if self.gene.accepts_late_reports() {
+            return None;
+        }
+        match (message, self.latest_observation) {
+            (Message::Observations { datetime, .. }, Some(latest)) if *datetime < latest => {
+                Some(Message::LateReport {
+                    path: self.path.clone(),
+                    datetime: *datetime,
+                    latest,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// start from the state of a snapshot - a day-scoped gene that retains
+    /// buckets gets back the bucket of the snapshot's day only
+    fn restore(&mut self, datetime: OffsetDateTime, values: State<T>, version: u64) {
+        debug!("{} restoring snapshot of {datetime}", self.path);
+        if matches!(self.gene.get_time_scope(), TimeScope::Day) && self.gene.retains_buckets() {
+            self.buckets.clear();
+            self.buckets
+                .insert(datetime.to_offset(UtcOffset::UTC).date(), values.clone());
+        }
+        self.state = values;
+        self.latest_observation = Some(datetime);
+        self.version = version;
+    }
+
+    /// start over from an empty state and gene - the version and the latest
+    /// observation are kept so that the version still counts every
+    /// observation and a late report is still rejected
+    fn reset(&mut self) {
+        self.state = State::new();
+        self.buckets.clear();
+        self.gene.reset();
+    }
+
+    /// a subscriber that is not keeping up misses the report rather than
+    /// holding up the actor, and a closed one is dropped
+    fn notify_subscribers(&mut self) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let rpt = self.get_state_rpt();
+        let path = &self.path;
+        self.subscribers
+            .retain(|subscriber| match subscriber.try_send(rpt.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("{path} subscriber is behind, report dropped");
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    debug!("{path} subscriber is gone");
+                    false
+                }
+            });
+    }
+
+    /// an `Alert` for every index the last update moved across a threshold
+    /// of the gene.  the alert receiver gets it before the update is
+    /// answered and misses it rather than holding up the actor.
+    async fn raise_alerts(&self, before: &State<T>) {
+        for (idx, value, threshold) in self.gene.crossings(before, &self.state) {
+            let alert = Message::Alert {
+                path: self.path.clone(),
+                idx,
+                value,
+                threshold,
+            };
+            debug!("{} idx {idx} at {value} crossed {threshold}", self.path);
+            if let Some(alert_to) = &self.alert_to {
+                if let Err(e) = alert_to.try_send(alert.clone()) {
+                    warn!("{} alert dropped: {e}", self.path);
+                }
+            }
+            if let Some(output_handle) = &self.output {
+                if let Err(err) = output_handle.tell(alert).await {
+                    error!("Error telling output actor: {err:?}");
+                }
             }
         }
     }
 
-    fn get_state_rpt(&self) -> Message<f64> {
+    /// the state after a replayed event that happened at `datetime`
+    async fn report_replay(&self, datetime: OffsetDateTime) {
+        if let Some(replay_to) = &self.replay_to {
+            let rpt = Message::StateReport {
+                datetime,
+                path: self.path.clone(),
+                values: self.state.clone(),
+                version: self.version,
+            };
+            if let Err(err) = replay_to.tell(rpt).await {
+                error!("Error reporting replay: {err:?}");
+            }
+        }
+    }
+
+    /// the state as of the newest observation applied to it - an actor that
+    /// has not observed anything yet reports the current time
+    fn get_state_rpt(&self) -> Message<T> {
         Message::StateReport {
             path: self.path.clone(),
             values: self.state.clone(),
-            datetime: OffsetDateTime::now_utc(), // TODO: should be from latest observations
-                                                 // (maybe)
+            datetime: self
+                .latest_observation
+                .unwrap_or_else(OffsetDateTime::now_utc),
+            version: self.version,
+        }
+    }
+
+    /// the state report with only the `indices` of the state that it has
+    fn get_state_rpt_of(&self, indices: &[i32]) -> Message<T> {
+        let values = indices
+            .iter()
+            .filter_map(|idx| self.state.get(idx).map(|value| (*idx, *value)))
+            .collect();
+        Message::StateReport {
+            path: self.path.clone(),
+            values,
+            datetime: self
+                .latest_observation
+                .unwrap_or_else(OffsetDateTime::now_utc),
+            version: self.version,
         }
     }
 
@@ -145,9 +396,11 @@ impl StateActor {
     /// the lifecycle processing coordinated by the director
     fn new(
         path: String,
-        receiver: mpsc::Receiver<Envelope<f64>>,
-        output: Option<Handle>,
-        gene: Box<dyn Gene<f64> + Send + Sync>,
+        receiver: mpsc::Receiver<Envelope<T>>,
+        output: Option<Handle<T>>,
+        gene: Box<dyn Gene<T> + Send + Sync>,
+        replay_to: Option<Handle<T>>,
+        alert_to: Option<mpsc::Sender<Message<T>>>,
     ) -> Self {
         let state = State::new();
         Self {
@@ -156,27 +409,72 @@ impl StateActor {
             state,
             path,
             gene,
+            latest_observation: None,
+            version: 0,
+            buckets: BTreeMap::new(),
+            subscribers: Vec::new(),
+            replay_to,
+            alert_to,
         }
     }
 }
 
 /// actor handle public constructor
 #[must_use]
-pub fn new(
+pub fn new<T: Value>(
+    path: String,
+    bufsz: usize,
+    gene: Box<dyn Gene<T> + Send + Sync>,
+    output: Option<Handle<T>>,
+) -> Handle<T> {
+    spawn(path, bufsz, gene, output, None, None)
+}
+
+/// `new` for an actor that reports its state to `replay_to` after every
+/// event of its init replay, ie: to debug how a journal builds up a state
+#[must_use]
+pub fn new_replaying<T: Value>(
+    path: String,
+    bufsz: usize,
+    gene: Box<dyn Gene<T> + Send + Sync>,
+    replay_to: Handle<T>,
+) -> Handle<T> {
+    spawn(path, bufsz, gene, None, Some(replay_to), None)
+}
+
+/// `new` for an actor that sends every `Alert` it raises to `alert_to`, ie:
+/// for the director to pass on
+#[must_use]
+pub fn new_alerting<T: Value>(
+    path: String,
+    bufsz: usize,
+    gene: Box<dyn Gene<T> + Send + Sync>,
+    alert_to: mpsc::Sender<Message<T>>,
+) -> Handle<T> {
+    spawn(path, bufsz, gene, None, None, Some(alert_to))
+}
+
+fn spawn<T: Value>(
     path: String,
     bufsz: usize,
-    gene: Box<dyn Gene<f64> + Send + Sync>,
-    output: Option<Handle>,
-) -> Handle {
-    async fn start<'a>(mut actor: StateActor) {
+    gene: Box<dyn Gene<T> + Send + Sync>,
+    output: Option<Handle<T>>,
+    replay_to: Option<Handle<T>>,
+    alert_to: Option<mpsc::Sender<Message<T>>>,
+) -> Handle<T> {
+    async fn start<T: Value>(mut actor: StateActor<T>) {
         while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
             actor.handle_envelope(envelope).await;
         }
     }
 
     let (sender, receiver) = mpsc::channel(bufsz);
 
-    let actor = StateActor::new(path, receiver, output, gene);
+    let actor = StateActor::new(path, receiver, output, gene, replay_to, alert_to);
 
     let actor_handle = Handle::new(sender);
 
@@ -0,0 +1,526 @@
+//!This module defines the `Store` and `Journal` traits - the operations the `StoreActor` performs -
+//!so that the actor works the same on top of any database.  `Journal` is the part that reads and
+//!writes values and is implemented by every backend for every `Value` type.
+//!
+//!Every backend keeps the same five tables: `updates`, the journal of observations keyed by path
+//!and observation timestamp, `gene_mappings`, keyed by namespace and path, `snapshots`, the
+//!latest state of a path with the id of the last journal row it covers, `edges`, the
+//!parent-to-child edges of the path hierarchy of a namespace, and `index_labels`, the names of
+//!the indices of a namespace.  Values, labels, and gene
+//!params are journaled as the same json text by every backend with the helpers here so that a
+//!journal replays to the same state wherever it was written.
+//!
+//!A `Reset` of a path is journaled in `updates` as a marker row among its observations - its
+//!`values_str` is `RESET_MARKER` and its timestamp the negated nanoseconds of the reset so that it
+//!never collides with the timestamp of an observation.  A replay of the journal reads the marker
+//!back as a `Reset` at the same place; the queries of observations, ie: history, series, and
+//!stats, skip it.
+//!
+//!`SqliteStore` keeps a namespace in a single db file and `PostgresStore` lets many servers share
+//!one database.
+
+use crate::actors::actor::State;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::GeneType;
+use crate::actors::message::GeneMapping;
+use crate::actors::message::IndexLabels;
+use crate::actors::message::JournalStats;
+use crate::actors::message::Labels;
+use crate::actors::message::Message;
+use crate::actors::message::ObservationStats;
+use crate::actors::store_actor_sqlite::StoreError;
+use crate::actors::store_actor_sqlite::StoreResult;
+use crate::actors::value::Value;
+use crate::utils::nvtime::OffsetDateTimeWrapper;
+use async_trait::async_trait;
+use futures::Stream;
+use futures::StreamExt;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+
+/// the `values_str` of the journal row of a `Reset` - a json string where an
+/// observation has a json map of values
+pub(crate) const RESET_MARKER: &str = "\"reset\"";
+
+/// the `timestamp` of the journal row of a reset that arrived at `sequence`
+pub(crate) fn reset_marker_timestamp(sequence: OffsetDateTime) -> i64 {
+    i64::try_from(sequence.unix_timestamp_nanos())
+        .unwrap_or(i64::MAX)
+        .saturating_neg()
+}
+
+/// how a journal write ended when it did not fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Persisted,
+    /// the journal already holds an observation of the path at the timestamp
+    Duplicate,
+}
+
+/// an observation journaled as part of a batch - see `Journal::insert_update`
+#[derive(Debug, Clone)]
+pub struct UpdateRow<T = f64> {
+    pub path: String,
+    pub datetime: OffsetDateTime,
+    pub sequence: OffsetDateTime,
+    pub values: State<T>,
+    pub labels: Labels,
+}
+
+/// the operations of a `StoreActor` that do not read or write values
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// persist the mapping of `path` in `namespace`
+    async fn insert_gene_mapping(
+        &self,
+        namespace: &str,
+        path: &str,
+        gene_type: &GeneType,
+        params: &GeneParams,
+    ) -> StoreResult<()>;
+
+    /// persist every mapping in `namespace` in one transaction - a failed
+    /// insert is reported for its entry and does not stop the rest of the batch
+    async fn insert_gene_mappings(
+        &self,
+        namespace: &str,
+        mappings: Vec<(String, GeneType, GeneParams)>,
+    ) -> StoreResult<Vec<(String, Result<GeneType, String>)>>;
+
+    /// the mappings of `namespace` - mappings persisted before they had a
+    /// namespace are loaded ahead of them so that a namespace's own mapping
+    /// of a path wins
+    async fn get_mappings_for_ns(&self, namespace: &str) -> StoreResult<Vec<GeneMapping>>;
+
+    /// remove the mapping of `path` in `namespace` and return how many
+    /// mappings were removed - a mapping persisted before mappings had a
+    /// namespace is shared by every namespace and is kept
+    async fn delete_gene_mapping(&self, namespace: &str, path: &str) -> StoreResult<u64>;
+
+    /// the mapping of `path` persisted before mappings had a namespace,
+    /// which every namespace shares
+    async fn get_shared_mapping(&self, path: &str) -> StoreResult<Option<GeneMapping>>;
+
+    /// persist the parent-to-child `edges` of the path hierarchy of
+    /// `namespace` in one transaction - an edge that is already persisted is
+    /// left as it is
+    async fn insert_edges(&self, namespace: &str, edges: &[(String, String)]) -> StoreResult<()>;
+
+    /// the parent-to-child edges of the path hierarchy of `namespace` in the
+    /// order they were persisted
+    async fn get_edges(&self, namespace: &str) -> StoreResult<Vec<(String, String)>>;
+
+    /// persist the label of index `idx` in `namespace` - it replaces an
+    /// earlier label of the same index
+    async fn insert_index_label(&self, namespace: &str, idx: i32, name: &str) -> StoreResult<()>;
+
+    /// the index labels of `namespace`
+    async fn get_index_labels(&self, namespace: &str) -> StoreResult<IndexLabels>;
+
+    /// remove the journal and snapshot of `path`, and its gene mapping in
+    /// `namespace` if `mapping` is set, in one transaction and return how
+    /// many observations were removed - like `delete_gene_mapping`, a mapping
+    /// without a namespace is kept
+    async fn delete(&self, namespace: &str, path: &str, mapping: bool) -> StoreResult<u64>;
+
+    /// remove the observations of the paths that start with `prefix` that
+    /// were journaled before `before` except the newest `keep_last` of every
+    /// path, reclaim the space, and return how many observations were removed
+    async fn prune(
+        &self,
+        prefix: &str,
+        before: OffsetDateTime,
+        keep_last: usize,
+    ) -> StoreResult<u64>;
+
+    /// the distinct journaled paths that start with `prefix` in path order
+    async fn list_paths(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<String>>;
+
+    /// the distinct paths that start with `prefix` with anything journaled
+    /// after `since` in path order, and when the latest of it was journaled -
+    /// `since` itself when nothing was.  the paths are the ones journaled up
+    /// to that latest sequence so that a path journaled while they are listed
+    /// is left to the next sync
+    async fn list_paths_since(
+        &self,
+        prefix: &str,
+        since: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<(Vec<String>, OffsetDateTime)>;
+
+    /// the number of paths that start with `prefix` and of their
+    /// observations, when the observations were journaled, and the number of
+    /// gene mappings `namespace` sees
+    async fn stats(&self, namespace: &str, prefix: &str) -> StoreResult<JournalStats>;
+
+    /// the number of observations journaled for exactly `path` and when the
+    /// earliest and the latest of them were taken
+    async fn path_stats(&self, path: &str) -> StoreResult<ObservationStats>;
+
+    /// succeeds if the database answers
+    async fn ping(&self) -> StoreResult<()>;
+
+    async fn close(&self);
+}
+
+/// the operations of a `StoreActor` that read or write values of type `T` -
+/// a journal written with one value type is read back with another as long
+/// as its values parse, ie: integers written as `i64` read as `f64`
+#[async_trait]
+pub trait Journal<T = f64>: Store {
+    /// journal an observation keyed by `path` and `datetime` - `sequence`
+    /// is the time the observation arrived
+    async fn insert_update(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        sequence: OffsetDateTime,
+        values: &State<T>,
+        labels: &Labels,
+    ) -> StoreResult<UpdateOutcome>;
+
+    /// journal every row in one transaction and return the outcome of each
+    /// row in order - a duplicate or a failed insert is reported for its row
+    /// and does not stop the rest of the batch
+    async fn insert_updates(
+        &self,
+        rows: &[UpdateRow<T>],
+    ) -> StoreResult<Vec<StoreResult<UpdateOutcome>>>;
+
+    /// the journal of `path` written after the row `after` in the order it
+    /// was written, its resets included, only the newest `max_replay_events` when set - an
+    /// `after` of 0 is the whole journal
+    async fn get_values(
+        &self,
+        path: &str,
+        after: i64,
+        max_replay_events: Option<usize>,
+    ) -> StoreResult<Vec<Message<T>>>;
+
+    /// journal a `Reset` of `path` after its observations so far - a replay
+    /// starts the state over from there
+    async fn insert_reset(&self, path: &str, sequence: OffsetDateTime) -> StoreResult<()>;
+
+    /// the observations of every path that starts with `prefix` in the order
+    /// they were written
+    async fn get_journal(&self, prefix: &str) -> StoreResult<Vec<Message<T>>>;
+
+    /// persist the state and version of `path` as of its newest journal row,
+    /// replacing any earlier snapshot of the path
+    async fn insert_snapshot(
+        &self,
+        path: &str,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<()>;
+
+    /// the latest `Snapshot` of `path` and the id of the last journal row it
+    /// covers
+    async fn get_snapshot(&self, path: &str) -> StoreResult<Option<(i64, Message<T>)>>;
+
+    /// the journal of `path` written after the row `after` and before its
+    /// first observation taken at or after `before`, in the order it replays,
+    /// and the id of the last of those rows - `after` when there are none
+    async fn get_values_before(
+        &self,
+        path: &str,
+        after: i64,
+        before: OffsetDateTime,
+    ) -> StoreResult<(i64, Vec<Message<T>>)>;
+
+    /// replace the snapshot of `path` with one that covers the journal up to
+    /// the row `last_row` and remove those rows in one transaction - returns
+    /// how many rows were removed
+    async fn compact(
+        &self,
+        path: &str,
+        last_row: i64,
+        datetime: OffsetDateTime,
+        version: u64,
+        values: &State<T>,
+    ) -> StoreResult<u64>;
+
+    /// the observations of `path` carrying every one of the labels in
+    /// observation time order
+    async fn get_series(&self, path: &str, labels: &Labels) -> StoreResult<Vec<Message<T>>>;
+
+    /// `get_series` one row at a time to `stream_to` from a task of its own
+    /// so that neither the store nor the memory of the rows waits for a slow
+    /// reader.  the stream ends with `EndOfStream`, or with an `Error` if a
+    /// row can not be read
+    fn stream_series(&self, path: &str, labels: &Labels, stream_to: mpsc::Sender<Message<T>>);
+
+    /// the observations of `path` that arrived between `from` and `to`
+    /// inclusive in the order they were journaled, one page at a time
+    async fn get_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+        offset: usize,
+    ) -> StoreResult<Vec<Message<T>>>;
+
+    /// the number of observations of `path` that arrived between `from` and
+    /// `to` inclusive - all the pages of `get_history`
+    async fn count_history(
+        &self,
+        path: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> StoreResult<u64>;
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(e: sqlx::Error) -> Self {
+        Self {
+            transient: is_transient(&e),
+            reason: e.to_string(),
+        }
+    }
+}
+
+/// the `SQLite` result codes of a db that another connection holds a lock on
+/// and the `Postgres` states of a serialization failure or deadlock - the
+/// same statement may succeed once the other connection lets go.  a
+/// constraint violation never does.
+const TRANSIENT_CODES: [&str; 8] = ["5", "6", "261", "262", "517", "773", "40001", "40P01"];
+
+/// a busy or locked db, or a connection that could not be had from the pool
+/// in time, is worth trying again
+fn is_transient(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(e) => e
+            .code()
+            .is_some_and(|code| TRANSIENT_CODES.contains(&code.as_ref())),
+        sqlx::Error::PoolTimedOut => true,
+        _ => false,
+    }
+}
+
+/// send every observation of `rows` to `stream_to` as it is read and an
+/// `EndOfStream` after the last - a row that can not be read ends the stream
+/// with an `Error` and a reader that is gone stops it
+pub(crate) async fn stream_rows<T, S>(path: &str, mut rows: S, stream_to: &mpsc::Sender<Message<T>>)
+where
+    S: Stream<Item = Result<Message<T>, sqlx::Error>> + Unpin,
+{
+    while let Some(row) = rows.next().await {
+        let message = match row {
+            Ok(message) => message,
+            Err(e) => {
+                error!("cannot stream series for {path}: {e:?}");
+                let reason = format!(
+                    "cannot load series for {path}: {}",
+                    StoreError::from(e).reason
+                );
+                let path = path.to_string();
+                stream_to.send(Message::Error { path, reason }).await.ok();
+                return;
+            }
+        };
+        if stream_to.send(message).await.is_err() {
+            debug!("series reader of {path} is gone");
+            return;
+        }
+    }
+    stream_to.send(Message::EndOfStream {}).await.ok();
+}
+
+/// values that can not be serialized, or a `NaN` or infinity that would be
+/// serialized as a null, are an error rather than a NULL column so that a row
+/// is never journaled that can not be replayed
+pub(crate) fn values_json<T: Value>(values: &State<T>) -> StoreResult<String> {
+    if let Some((idx, _)) = values.iter().find(|(_, v)| !v.is_finite()) {
+        return Err(StoreError {
+            reason: format!("cannot serialize values: value of index {idx} is not finite"),
+            transient: false,
+        });
+    }
+    serde_json::to_string(values).map_err(|e| {
+        error!("cannot serialize values: {e:?}");
+        StoreError {
+            reason: format!("cannot serialize values: {e}"),
+            transient: false,
+        }
+    })
+}
+
+/// unlabeled observations keep a NULL labels column
+pub(crate) fn labels_json(labels: &Labels) -> StoreResult<Option<String>> {
+    if labels.is_empty() {
+        Ok(None)
+    } else {
+        serde_json::to_string(labels).map(Some).map_err(|e| {
+            error!("cannot serialize labels: {e:?}");
+            StoreError {
+                reason: format!("cannot serialize labels: {e}"),
+                transient: false,
+            }
+        })
+    }
+}
+
+pub(crate) fn gene_type_json(gene_type: &GeneType) -> Option<String> {
+    serde_json::to_string(gene_type)
+        .map_err(|e| {
+            error!("cannot serialize gene_type: {e:?}");
+        })
+        .ok()
+}
+
+/// mappings without params leave the column NULL
+pub(crate) fn params_json(params: &GeneParams) -> Option<String> {
+    if params.is_empty() {
+        None
+    } else {
+        serde_json::to_string(params)
+            .map_err(|e| {
+                error!("cannot serialize gene params: {e:?}");
+            })
+            .ok()
+    }
+}
+
+/// the `LIKE` pattern of the paths under `prefix` - the wildcards are
+/// escaped with `\` so that a prefix only ever matches literally
+pub(crate) fn prefix_pattern(prefix: &str) -> String {
+    format!(
+        "{}%",
+        prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
+
+/// the datetime of a journaled timestamp
+pub(crate) fn row_datetime(timestamp: i64) -> OffsetDateTime {
+    let date_parsed = OffsetDateTimeWrapper {
+        datetime_num: timestamp,
+    };
+    match date_parsed.to_ts() {
+        Ok(dt) => dt,
+        Err(e) => {
+            error!("can not parse date - using 'now': {e}");
+            OffsetDateTime::now_utc()
+        }
+    }
+}
+
+/// the stats of the counts and journal timestamp range a backend queried -
+/// a count is never negative
+pub(crate) fn row_to_stats(
+    paths: i64,
+    observations: i64,
+    earliest: Option<i64>,
+    latest: Option<i64>,
+    gene_mappings: i64,
+) -> JournalStats {
+    let count = |n: i64| u64::try_from(n).unwrap_or_default();
+    JournalStats {
+        paths: count(paths),
+        observations: count(observations),
+        earliest: earliest.map(row_datetime),
+        latest: latest.map(row_datetime),
+        gene_mappings: count(gene_mappings),
+    }
+}
+
+/// the stats of the journal of a path from its count and the timestamps of
+/// its earliest and latest observations
+pub(crate) fn row_to_path_stats(
+    observations: i64,
+    first: Option<i64>,
+    last: Option<i64>,
+) -> ObservationStats {
+    ObservationStats {
+        observations: u64::try_from(observations).unwrap_or_default(),
+        first: first.map(row_datetime),
+        last: last.map(row_datetime),
+    }
+}
+
+/// the observation, or the `Reset`, of a journal row
+pub(crate) fn row_to_observations<T: Value>(
+    path: &str,
+    timestamp: i64,
+    values_str: &str,
+    labels_str: Option<&str>,
+) -> Result<Message<T>, serde_json::Error> {
+    if values_str == RESET_MARKER {
+        return Ok(Message::Reset {
+            path: String::from(path),
+        });
+    }
+    let values = serde_json::from_str(values_str)?;
+    // rows journaled before labels were supported have none
+    let labels = match labels_str {
+        Some(labels_str) => serde_json::from_str(labels_str)?,
+        None => Labels::new(),
+    };
+
+    Ok(Message::Observations {
+        path: String::from(path),
+        datetime: row_datetime(timestamp),
+        values,
+        labels,
+    })
+}
+
+/// the `Snapshot` of a `snapshots` row
+pub(crate) fn row_to_snapshot<T: Value>(
+    path: &str,
+    timestamp: i64,
+    version: i64,
+    values_str: &str,
+) -> Result<Message<T>, serde_json::Error> {
+    Ok(Message::Snapshot {
+        path: String::from(path),
+        datetime: row_datetime(timestamp),
+        values: serde_json::from_str(values_str)?,
+        version: u64::try_from(version).unwrap_or_default(),
+    })
+}
+
+/// the gene mapping of a `gene_mappings` row
+pub(crate) fn row_to_gene_mapping(
+    path: String,
+    gene_type_str: &str,
+    params_str: Option<&str>,
+) -> Result<GeneMapping, serde_json::Error> {
+    let gene_type = serde_json::from_str(gene_type_str)?;
+    let params = match params_str {
+        Some(params_str) => serde_json::from_str(params_str)?,
+        None => GeneParams::default(),
+    };
+    Ok(GeneMapping {
+        path,
+        gene_type,
+        params,
+    })
+}
+
+/// the newest `max` of the journal, read with one extra row so that a cap
+/// that drops events can be reported
+pub(crate) fn cap_replay<T>(
+    path: &str,
+    max: usize,
+    mut values: Vec<Message<T>>,
+) -> Vec<Message<T>> {
+    if values.len() > max {
+        warn!("journal of {path} is longer than {max} events - replaying only the most recent");
+        values.remove(0);
+    }
+    values
+}
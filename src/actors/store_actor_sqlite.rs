@@ -1,19 +1,25 @@
 //!This module contains the implementation of the `StoreActor`, which is responsible for storing
-//!all the state changes of an actor into a `SQLite` database.
+//!all the state changes of an actor into a database.
 //!
 //!The module provides an API that is called by instances of the Actor trait when read and write
-//!requests arrive. `StoreActor` is designed to store only a single file for storage so all reading
-//!and writing must be done by messaging an instance of this actor type.
+//!requests arrive. `StoreActor` is designed to own the only connection to its storage so all
+//!reading and writing must be done by messaging an instance of this actor type.
 //!
 //!The module also defines `StoreError`, a custom error type used for error handling, and
 //!`StreamOption`, an enumeration type to define the behavior of temporary message streams.
 //!
-//!The `StoreActor` works with the `SqlitePool` object and the `sqlx` crate for interacting with
-//!the database.
+//!The `StoreActor` works with any implementation of the `Store` trait - a `SqliteStore` db file
+//!named after the namespace by default or, with a connection url, a `PostgresStore`.
 //!
 //!This module also provides methods to retrieve the time series of events for the actor being
-//!resurrected, define tables, enable write-ahead-logging mode for append-only-style db and
-//!initialize the DB if it does not exist.
+//!resurrected - the latest snapshot of the actor's state, if any, followed by only the events
+//!journaled after it.  A compaction replays the journal of a path up to a cutoff the same way and
+//!keeps the state it adds up to as the snapshot of the path in place of the rows it covers.
+//!
+//!Observations can be journaled in batches - the rows are buffered and written in one transaction
+//!once the batch is full or `BATCH_TIMEOUT` after its first row, whichever comes first, and each
+//!row is confirmed only after its batch commits.  Any other request first flushes the batch so
+//!that it sees every observation that arrived before it.
 //!
 //!The module is constructed as an actor handle that is expected to be used with the director
 //!module in creating a new actor system.
@@ -21,28 +27,38 @@
 use crate::actors::actor::respond_or_log_error;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
 use crate::actors::message::Envelope;
+use crate::actors::message::Labels;
 use crate::actors::message::Message;
 use crate::actors::message::MtHint;
 use crate::actors::message::NvError;
 use crate::actors::message::NvResult;
-use crate::utils::nvtime::OffsetDateTimeWrapper;
+use crate::actors::postgres_store::PostgresStore;
+use crate::actors::sqlite_store::SqliteOptions;
+use crate::actors::sqlite_store::SqliteStore;
+use crate::actors::state_actor;
+use crate::actors::store::Journal;
+use crate::actors::store::UpdateOutcome;
+use crate::actors::store::UpdateRow;
+use crate::actors::value::Value;
+use crate::utils::metrics;
+use crate::utils::metrics::Counter;
 use async_trait::async_trait;
-use serde_json::from_str;
-use sqlx::error::DatabaseError;
-use sqlx::Row;
-use sqlx::SqlitePool;
-use std::collections::HashMap;
 use std::fmt;
-use std::fs::File;
-use std::path::Path;
+use std::time::Duration;
 use time::OffsetDateTime;
 use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tokio::sync::oneshot::Sender;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::error;
-use tracing::info;
+use tracing::instrument;
 use tracing::trace;
 use tracing::warn;
 
@@ -52,6 +68,9 @@ pub type StoreResult<T> = Result<T, StoreError>;
 #[derive(Debug, Clone)]
 pub struct StoreError {
     pub reason: String,
+    /// the db was busy or locked and the same write may succeed if it is
+    /// tried again
+    pub transient: bool,
 }
 
 impl fmt::Display for StoreError {
@@ -66,99 +85,153 @@ enum StreamOption {
     LeaveOpen,
 }
 
-/// main persistence API - the navactor must have only a single file for
-/// storage so all reading and writing must be done by messaging an instance
-/// of this actor type
-pub struct StoreActor {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
-    pub dbconn: Option<SqlitePool>,
-    pub namespace: String,
-    pub disable_duplicate_detection: bool,
+/// tuning for the store that is not part of the db file itself
+#[derive(Debug, Clone, Copy)]
+pub struct StoreOptions {
+    /// the most journal rows replayed to resurrect an actor - only the most
+    /// recent are replayed when a journal is longer.  a gauge is exact as long
+    /// as the cap covers its most recent full set of indices but an
+    /// accumulator loses everything it counted before the cap and will
+    /// under-report - a long-lived accumulator needs a snapshot, not a cap
+    pub max_replay_events: Option<usize>,
+    /// the most observations journaled in one transaction - 0 or 1 journals
+    /// every observation as it arrives.  batching pays off when many
+    /// requests are in flight at once, a single sender that waits for each
+    /// confirmation only waits `BATCH_TIMEOUT` longer
+    pub batch_size: usize,
+    /// the connection pool and pragmas of a `SQLite` db - a `Postgres`
+    /// database ignores them
+    pub sqlite: SqliteOptions,
+    /// the most times a write of observations is tried when the db is busy
+    /// or locked before its error is answered - 0 or 1 tries once.  an
+    /// observation the journal already holds is never tried again.
+    pub max_write_attempts: usize,
 }
 
-async fn insert_gene_mapping(
-    dbconn: &SqlitePool,
-    path: &String,
-    gene_type: &GeneType,
-) -> Result<(), sqlx::error::Error> {
-    match sqlx::query("INSERT INTO gene_mappings (path, gene_type) VALUES (?,?)")
-        .bind(path)
-        .bind(
-            serde_json::to_string(&gene_type)
-                .map_err(|e| {
-                    error!("cannot serialize gene_type: {e:?}");
-                })
-                .ok(),
-        )
-        .execute(dbconn)
-        .await
-    {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            warn!("persisting gene mapping for {} failed: {:?}", path, e);
-            Err(e)
+/// the most times a write is tried when not configured
+pub const DEFAULT_MAX_WRITE_ATTEMPTS: usize = 4;
+
+/// how long the first retry of a write waits - each next retry waits twice
+/// as long as the one before
+pub const WRITE_RETRY_BACKOFF: Duration = Duration::from_millis(10);
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            max_replay_events: None,
+            batch_size: 0,
+            sqlite: SqliteOptions::default(),
+            max_write_attempts: DEFAULT_MAX_WRITE_ATTEMPTS,
         }
     }
 }
 
-/// record the latest event in the actors state
-async fn insert_update(
-    dbconn: &SqlitePool,
-    path: &String,
-    datetime: OffsetDateTime,
-    sequence: OffsetDateTime,
-    values: HashMap<i32, f64>,
-) -> Result<(), sqlx::error::Error> {
-    // store this is a db with the key as 'path'
-    let dt_wrapper = OffsetDateTimeWrapper::new(datetime);
-    let sequence_wrapper = OffsetDateTimeWrapper::new(sequence);
-
-    match sqlx::query(
-        "INSERT INTO updates (path, timestamp, sequence, values_str) VALUES (?,?,?,?)",
-    )
-    .bind(path.clone())
-    .bind(dt_wrapper.datetime_num)
-    .bind(sequence_wrapper.datetime_num)
-    .bind(
-        serde_json::to_string(&values)
-            .map_err(|e| {
-                error!("cannot serialize values: {e:?}");
-            })
-            .ok(),
-    )
-    .execute(dbconn)
-    .await
-    {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            // consider handling types of errors differently, ie: constraint violation is "debug"
-            warn!("jrnling for {} failed: {:?}", path, e);
-            Err(e)
+/// the longest an observation waits in a batch that is not full
+pub const BATCH_TIMEOUT: Duration = Duration::from_millis(5);
+
+/// try `write` until it succeeds, fails for good, or has been tried
+/// `max_attempts` times - only a busy or locked db is tried again, with the
+/// wait doubled each time
+async fn with_retries<R, F, Fut>(max_attempts: usize, mut write: F) -> StoreResult<R>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = StoreResult<R>>,
+{
+    let mut backoff = WRITE_RETRY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match write().await {
+            Err(e) if e.transient && attempt < max_attempts => {
+                warn!("write attempt {attempt} of {max_attempts} failed, retrying in {backoff:?}: {e}");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            r => return r,
         }
     }
 }
 
-/// retrieve the time series of events (observations) for the actor that is being resurrected
-async fn get_jrnl(dbconn: &SqlitePool, path: &str) -> StoreResult<Vec<Message<f64>>> {
-    match get_values(path, dbconn).await {
-        Ok(v) => Ok(v),
+/// a buffered observation and where to confirm it once its batch commits
+type PendingUpdate<T> = (UpdateRow<T>, Option<Sender<NvResult<Message<T>>>>);
+
+/// main persistence API - the navactor must have only a single file for
+/// storage so all reading and writing must be done by messaging an instance
+/// of this actor type
+pub struct StoreActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub dbconn: Option<Box<dyn Journal<T>>>,
+    /// the namespace the gene mappings are kept under, ie: `actors` - many
+    /// namespaces can share a db without seeing each other's mappings
+    pub namespace: String,
+    pub disable_duplicate_detection: bool,
+    pub options: StoreOptions,
+    /// the observations of the batch that is not yet committed and where to
+    /// confirm them
+    pending: Vec<PendingUpdate<T>>,
+    /// when the pending batch is committed if it does not fill up first
+    flush_at: Option<Instant>,
+}
+
+/// retrieve the time series of events (observations) for the actor that is being resurrected -
+/// with `as_of` only the events that replay before its first observation taken after `as_of`,
+/// every one of them regardless of `max_replay_events`
+async fn get_jrnl<T: Value>(
+    dbconn: &dyn Journal<T>,
+    path: &str,
+    after: i64,
+    max_replay_events: Option<usize>,
+    as_of: Option<OffsetDateTime>,
+) -> StoreResult<Vec<Message<T>>> {
+    let Some(as_of) = as_of else {
+        return match dbconn.get_values(path, after, max_replay_events).await {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                error!("cannot load update jrnl from db: {e:?}");
+                Err(StoreError {
+                    reason: format!("cannot load jrnl from db: {}", e.reason),
+                    transient: false,
+                })
+            }
+        };
+    };
+    // observations replay in timestamp order, a reset right after the one before it
+    match dbconn.get_values(path, after, None).await {
+        Ok(v) => Ok(v
+            .into_iter()
+            .take_while(
+                |m| !matches!(m, Message::Observations { datetime, .. } if *datetime > as_of),
+            )
+            .collect()),
         Err(e) => {
             error!("cannot load update jrnl from db: {e:?}");
             Err(StoreError {
-                reason: format!("cannot load jrnl from db: {e:?}"),
+                reason: format!("cannot load jrnl from db: {}", e.reason),
+                transient: false,
             })
         }
     }
 }
 
-/// retrieve the time series of events (observations) for the actor that is being resurrected
-async fn get_mappings(dbconn: &SqlitePool, path: &str) -> StoreResult<Vec<Message<f64>>> {
-    match get_mappings_for_ns(path, dbconn).await {
-        Ok(v) => Ok(v),
+/// retrieve the gene mappings of the namespace for the director that is starting
+async fn get_mappings<T: Value>(
+    dbconn: &dyn Journal<T>,
+    namespace: &str,
+) -> StoreResult<Vec<Message<T>>> {
+    match dbconn.get_mappings_for_ns(namespace).await {
+        Ok(v) => Ok(v
+            .into_iter()
+            .map(|m| Message::GeneMapping {
+                path: m.path,
+                gene_type: m.gene_type,
+                params: m.params,
+            })
+            .collect()),
         Err(e) => {
             error!("cannot load mappings from db: {e:?}");
             Err(StoreError {
-                reason: format!("cannot load from db: {e:?}"),
+                reason: format!("cannot load from db: {}", e.reason),
+                transient: false,
             })
         }
     }
@@ -167,9 +240,9 @@ async fn get_mappings(dbconn: &SqlitePool, path: &str) -> StoreResult<Vec<Messag
 /// internal actor-to-actor communication outside of input-to-state_actor is
 /// done with temporary streams (for now) and these streams are setup by
 /// an orchestrator (usually director).
-async fn stream_message(
-    stream_to: &Option<mpsc::Sender<Message<f64>>>,
-    message: Message<f64>,
+async fn stream_message<T: Value>(
+    stream_to: &Option<mpsc::Sender<Message<T>>>,
+    message: Message<T>,
     stream_option: StreamOption,
 ) {
     if let Some(stream_to) = stream_to {
@@ -187,87 +260,517 @@ async fn stream_message(
     }
 }
 
-async fn handle_gene_mapping(
+async fn handle_gene_mapping<T: Value>(
     path: String,
     gene_type: GeneType,
-    dbconn: &SqlitePool,
-    respond_to: Option<Sender<NvResult<Message<f64>>>>,
+    params: &GeneParams,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
 ) {
-    match insert_gene_mapping(dbconn, &path, &gene_type).await {
+    match dbconn
+        .insert_gene_mapping(namespace, &path, &gene_type, params)
+        .await
+    {
         Ok(_) => {
             debug!("gene_mapping '{path}' -> '{gene_type}' persisted");
             respond_or_log_error(respond_to, Ok(Message::EndOfStream {}));
         }
+        Err(e) => respond_or_log_error(respond_to, Err(NvError { reason: e.reason })),
+    }
+}
+
+/// persist every mapping of a batch in one transaction.  a failed insert
+/// is reported for its entry and does not stop the rest of the batch.
+async fn handle_gene_mapping_batch<T: Value>(
+    mappings: Vec<(String, GeneType, GeneParams)>,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.insert_gene_mappings(namespace, mappings).await {
+        Ok(results) => {
+            debug!("gene_mapping batch of {} committed", results.len());
+            respond_or_log_error(respond_to, Ok(Message::GeneMappingBatchReport { results }));
+        }
+        Err(e) => respond_or_log_error(respond_to, Err(NvError { reason: e.reason })),
+    }
+}
+
+/// remove the mapping of a path - a path without one is reported as
+/// `NotFound`
+async fn handle_delete_gene_mapping<T: Value>(
+    path: String,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.delete_gene_mapping(namespace, &path).await {
+        Ok(0) => respond_or_log_error(respond_to, Ok(Message::NotFound { path })),
+        Ok(_) => {
+            debug!("gene_mapping '{path}' deleted");
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot delete the gene mapping of {path}: {}", e.reason),
+            }),
+        ),
+    }
+}
+
+async fn handle_edges<T: Value>(
+    edges: &[(String, String)],
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.insert_edges(namespace, edges).await {
+        Ok(()) => {
+            debug!("{} edges persisted", edges.len());
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot persist edges: {}", e.reason),
+            }),
+        ),
+    }
+}
+
+/// remove the journal of a path, and its gene mapping if asked, in one
+/// transaction.  a path without rows is not an error but is reported as
+/// `NotFound`.
+async fn handle_delete<T: Value>(
+    path: String,
+    mapping: bool,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let deleted = dbconn.delete(namespace, &path, mapping).await;
+    match deleted {
+        Ok(0) => {
+            debug!("no journaled updates to delete for {path}");
+            respond_or_log_error(respond_to, Ok(Message::NotFound { path }));
+        }
+        Ok(updates) => {
+            debug!("deleted {path} and {updates} journaled updates");
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot delete {path}: {}", e.reason),
+            }),
+        ),
+    }
+}
+
+/// journal a reset of `path` that arrived at `sequence`
+async fn handle_reset<T: Value>(
+    path: String,
+    sequence: OffsetDateTime,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.insert_reset(&path, sequence).await {
+        Ok(()) => {
+            debug!("reset of {path} journaled");
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot reset {path}: {}", e.reason),
+            }),
+        ),
+    }
+}
+
+/// remove the observations under `path` that arrived before `before` and
+/// are not among the newest `keep_last` of their path
+async fn handle_prune<T: Value>(
+    path: String,
+    before: OffsetDateTime,
+    keep_last: usize,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.prune(&path, before, keep_last).await {
+        Ok(rows) => {
+            debug!("pruned {rows} journaled updates of {path} from before {before}");
+            respond_or_log_error(respond_to, Ok(Message::Pruned { rows }));
+        }
         Err(e) => respond_or_log_error(
             respond_to,
             Err(NvError {
+                reason: format!("cannot prune {path}: {}", e.reason),
+            }),
+        ),
+    }
+}
+
+/// the state the journal of `path` before `before` adds up to, replayed
+/// through `gene` the way a resurrection replays it, written as its snapshot
+/// in place of the rows it covers - returns how many rows were removed
+async fn compact_path<T: Value>(
+    path: &str,
+    before: OffsetDateTime,
+    gene: Box<dyn Gene<T> + Send + Sync>,
+    dbconn: &dyn Journal<T>,
+) -> StoreResult<u64> {
+    let snapshot = dbconn.get_snapshot(path).await?;
+    let after = snapshot.as_ref().map_or(0, |(last_row, _)| *last_row);
+    let (last_row, rows) = dbconn.get_values_before(path, after, before).await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let actor = state_actor::new(path.to_string(), 8, gene, None);
+    let (stream_to, stream_from) = mpsc::channel(8);
+    let (send, recv) = oneshot::channel();
+    let replayed = async {
+        actor
+            .send(Envelope {
+                message: Message::InitCmd {
+                    hint: MtHint::Update,
+                },
+                respond_to: Some(send),
+                stream_from: Some(stream_from),
+                ..Default::default()
+            })
+            .await?;
+        for message in snapshot
+            .map(|(_, snapshot)| snapshot)
+            .into_iter()
+            .chain(rows)
+            .chain(std::iter::once(Message::EndOfStream {}))
+        {
+            stream_to.send(message).await.map_err(|e| NvError {
                 reason: e.to_string(),
+            })?;
+        }
+        recv.await.map_err(|e| NvError {
+            reason: e.to_string(),
+        })??;
+        actor
+            .ask(Message::Query {
+                path: path.to_string(),
+                hint: MtHint::LatestObservation,
+            })
+            .await
+    };
+    match replayed.await {
+        Ok(Message::StateReport {
+            datetime,
+            values,
+            version,
+            ..
+        }) => {
+            dbconn
+                .compact(path, last_row, datetime, version, &values)
+                .await
+        }
+        // only resets with nothing observed before them
+        Ok(_) => Ok(0),
+        Err(e) => Err(StoreError {
+            reason: format!("cannot replay {path}: {e}"),
+            transient: false,
+        }),
+    }
+}
+
+/// compact the journal of `path` with the gene it is mapped to
+async fn handle_compact_path<T: Value>(
+    path: String,
+    before: OffsetDateTime,
+    gene_type: GeneType,
+    params: &GeneParams,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let Some(gene) = T::gene(gene_type, params) else {
+        respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("gene {gene_type} does not apply to {} values", T::NAME),
+            }),
+        );
+        return;
+    };
+    match compact_path(&path, before, gene, dbconn).await {
+        Ok(rows) => {
+            debug!("compacted {rows} journaled updates of {path} from before {before}");
+            respond_or_log_error(
+                respond_to,
+                Ok(Message::Compacted {
+                    paths: usize::from(rows > 0),
+                    rows,
+                }),
+            );
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot compact {path}: {}", e.reason),
             }),
         ),
     }
 }
 
-async fn handle_update(
+/// count the journal of `path` - the director sets the version
+async fn handle_path_stats<T: Value>(
+    path: String,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.path_stats(&path).await {
+        Ok(stats) => respond_or_log_error(
+            respond_to,
+            Ok(Message::PathStatsReport {
+                path,
+                stats,
+                version: 0,
+            }),
+        ),
+        Err(e) => {
+            error!("cannot count the journal of {path}: {e:?}");
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("cannot count the journal of {path}: {}", e.reason),
+                }),
+            );
+        }
+    }
+}
+
+/// count the journal under `prefix` and the mappings of the namespace
+async fn handle_stats<T: Value>(
+    prefix: String,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.stats(namespace, &prefix).await {
+        Ok(stats) => respond_or_log_error(
+            respond_to,
+            Ok(Message::StatsReport {
+                path: prefix,
+                stats,
+            }),
+        ),
+        Err(e) => {
+            error!("cannot count the journal under {prefix}: {e:?}");
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("cannot count the journal under {prefix}: {}", e.reason),
+                }),
+            );
+        }
+    }
+}
+
+async fn handle_list_paths<T: Value>(
+    prefix: String,
+    limit: usize,
+    offset: usize,
+    since: Option<OffsetDateTime>,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let listed = match since {
+        Some(since) => dbconn
+            .list_paths_since(&prefix, since, limit, offset)
+            .await
+            .map(|(paths, sequence)| (paths, Some(sequence))),
+        None => dbconn
+            .list_paths(&prefix, limit, offset)
+            .await
+            .map(|paths| (paths, None)),
+    };
+    match listed {
+        Ok((paths, sequence)) => {
+            respond_or_log_error(respond_to, Ok(Message::PathList { paths, sequence }));
+        }
+        Err(e) => {
+            error!("cannot list paths under {prefix}: {e:?}");
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("cannot list paths under {prefix}: {}", e.reason),
+                }),
+            );
+        }
+    }
+}
+
+/// the journal row of an observation - with duplicate detection disabled the
+/// row is keyed by its `sequence`, the envelope time, that should never
+/// cause a collision
+fn update_row<T: Value>(
     path: String,
     datetime: OffsetDateTime,
     sequence: OffsetDateTime,
-    values: HashMap<i32, f64>,
+    values: State<T>,
+    labels: Labels,
     disable_duplicate_detection: bool,
-    dbconn: &SqlitePool,
-    respond_to: Option<Sender<NvResult<Message<f64>>>>,
-) {
-    // sequence should be the envelope dt and should never cause a collision
-    let dt = if disable_duplicate_detection {
-        sequence
-    } else {
-        datetime
-    };
+) -> UpdateRow<T> {
+    UpdateRow {
+        path,
+        datetime: if disable_duplicate_detection {
+            sequence
+        } else {
+            datetime
+        },
+        sequence,
+        values,
+        labels,
+    }
+}
 
-    // this is bad ... figure out how to combine the extractor and the try_downcast_ref
-    match insert_update(dbconn, &path, dt, sequence, values).await {
-        Ok(_) => respond_or_log_error(respond_to, Ok(Message::Persisted {})),
+fn respond_to_update<T: Value>(
+    path: &str,
+    outcome: StoreResult<UpdateOutcome>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let namespace = metrics::namespace_of(path);
+    let namespace = namespace.as_str();
+    match outcome {
+        Ok(UpdateOutcome::Persisted) => respond_or_log_error(respond_to, Ok(Message::Persisted {})),
+        Ok(UpdateOutcome::Duplicate) => {
+            metrics::inc(Counter::ConstraintViolations, namespace, "observations");
+            respond_or_log_error(respond_to, Ok(Message::ConstraintViolation {}));
+        }
         Err(e) => {
-            let reason = e.to_string();
-            match e {
-                sqlx::Error::Database(db_error) => {
-                    if let Some(sqlite_error) =
-                        db_error.try_downcast_ref::<sqlx::sqlite::SqliteError>()
-                    {
-                        if sqlite_error.code().as_deref() == Some("1555") {
-                            // handle constraint violation here
-                            respond_or_log_error(respond_to, Ok(Message::ConstraintViolation {}));
-                        } else {
-                            // handle other Sqlite errors here
-                            respond_or_log_error(respond_to, Err(NvError { reason }));
-                        }
-                    } else {
-                        // handle other types of errors here
-                        respond_or_log_error(respond_to, Err(NvError { reason }));
-                    }
-                }
-                _ => {
-                    // handle other types of sqlx::Error here
-                    respond_or_log_error(respond_to, Err(NvError { reason }));
-                }
-            }
+            metrics::inc(Counter::StoreErrors, namespace, "observations");
+            respond_or_log_error(respond_to, Err(NvError { reason: e.reason }));
+        }
+    }
+}
+
+#[instrument(skip_all, fields(namespace = %metrics::namespace_of(&row.path), path = %row.path, msg = "Observations"))]
+async fn handle_update<T: Value>(
+    row: UpdateRow<T>,
+    dbconn: &dyn Journal<T>,
+    max_write_attempts: usize,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let outcome = insert_with_retries(&row, dbconn, max_write_attempts).await;
+    respond_to_update(&row.path, outcome, respond_to);
+}
+
+async fn insert_with_retries<T: Value>(
+    row: &UpdateRow<T>,
+    dbconn: &dyn Journal<T>,
+    max_write_attempts: usize,
+) -> StoreResult<UpdateOutcome> {
+    with_retries(max_write_attempts, || {
+        dbconn.insert_update(
+            &row.path,
+            row.datetime,
+            row.sequence,
+            &row.values,
+            &row.labels,
+        )
+    })
+    .await
+}
+
+/// persist the state of an actor as of its newest journal row
+async fn handle_snapshot<T: Value>(
+    path: String,
+    datetime: OffsetDateTime,
+    version: u64,
+    values: &State<T>,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn
+        .insert_snapshot(&path, datetime, version, values)
+        .await
+    {
+        Ok(()) => {
+            debug!("snapshot of {path} persisted");
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
         }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot snapshot {path}: {}", e.reason),
+            }),
+        ),
     }
 }
 
-/// a load command is indicates a new actor is expecting its journal.  the
-/// message contains a `stream_to` - read each row from the DB and write
-/// a message for each row to the actor at the other end of the `stream_to`
-/// connection.  after the last row, write an `EndOfStream` msg and close the
-/// connection
-async fn handle_load_cmd(
+/// a load command indicates a new actor is expecting its journal.  read the
+/// rows of `path` from the DB and write a message for each row to the actor
+/// at the other end of the `stream_to` connection.  after the last row, write
+/// an `EndOfStream` msg and close the connection.  a cancelled load stops
+/// reading the DB, or at the next row once it was read, and closes the stream
+/// without an `EndOfStream`.  with `as_of` the journal stops at its first
+/// observation taken after it.
+async fn handle_load_cmd<T: Value>(
     path: String,
-    dbconn: &SqlitePool,
-    stream_to: Option<mpsc::Sender<Message<f64>>>,
+    dbconn: &dyn Journal<T>,
+    stream_to: Option<mpsc::Sender<Message<T>>>,
+    cancel: Option<CancellationToken>,
+    max_replay_events: Option<usize>,
+    as_of: Option<OffsetDateTime>,
 ) {
-    match get_jrnl(dbconn, &path).await {
+    let cancel = cancel.unwrap_or_default();
+    // a snapshot stands in for every row up to the last one it covers - one
+    // newer than `as_of` is passed over for the rows it covers, which are
+    // gone if they were compacted into it
+    let snapshot = tokio::select! {
+        biased;
+        () = cancel.cancelled() => {
+            warn!("load of {path} cancelled before its snapshot was read");
+            return;
+        }
+        snapshot = dbconn.get_snapshot(&path) => snapshot,
+    };
+    let after = match snapshot {
+        Ok(Some((_, Message::Snapshot { datetime, .. })))
+            if as_of.is_some_and(|as_of| datetime > as_of) =>
+        {
+            debug!("{path} as of {as_of:?} replays from before its snapshot at {datetime}");
+            0
+        }
+        Ok(Some((last_row, snapshot))) => {
+            debug!("{path} resumes from its snapshot at row {last_row}");
+            stream_message(&stream_to, snapshot, StreamOption::LeaveOpen).await;
+            last_row
+        }
+        Ok(None) => 0,
+        Err(e) => {
+            error!("cannot load snapshot: {path} {e:?}");
+            0
+        }
+    };
+    let jrnl = tokio::select! {
+        biased;
+        () = cancel.cancelled() => {
+            warn!("load of {path} cancelled before its journal was read");
+            return;
+        }
+        jrnl = get_jrnl(dbconn, &path, after, max_replay_events, as_of) => jrnl,
+    };
+    match jrnl {
         Ok(rows) => {
-            for message in rows {
-                stream_message(&stream_to, message, StreamOption::LeaveOpen).await;
+            let total = rows.len();
+            for (streamed, message) in rows.into_iter().enumerate() {
+                tokio::select! {
+                    () = stream_message(&stream_to, message, StreamOption::LeaveOpen) => (),
+                    () = cancel.cancelled() => {
+                        warn!("load of {path} cancelled after {streamed} of {total} rows");
+                        return;
+                    }
+                }
             }
         }
         Err(e) => {
@@ -277,35 +780,92 @@ async fn handle_load_cmd(
     stream_message(&stream_to, Message::EndOfStream {}, StreamOption::Close).await;
 }
 
-async fn handle_gene_mapping_load_cmd(
-    path: String,
-    dbconn: &SqlitePool,
-    stream_to: Option<mpsc::Sender<Message<f64>>>,
+/// stream the gene mappings and then the path hierarchy edges of the
+/// store's namespace to a starting director
+async fn handle_gene_mapping_load_cmd<T: Value>(
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    stream_to: Option<mpsc::Sender<Message<T>>>,
 ) {
-    match get_mappings(dbconn, &path).await {
+    match get_mappings(dbconn, namespace).await {
         Ok(rows) => {
             for message in rows {
                 stream_message(&stream_to, message, StreamOption::LeaveOpen).await;
             }
         }
         Err(e) => {
-            error!("cannot load gene mapping jrnl: {path} {e:?}");
+            error!("cannot load gene mapping jrnl: {namespace} {e:?}");
+        }
+    };
+    match dbconn.get_edges(namespace).await {
+        Ok(edges) if edges.is_empty() => {}
+        Ok(edges) => {
+            stream_message(
+                &stream_to,
+                Message::Edges { edges },
+                StreamOption::LeaveOpen,
+            )
+            .await;
+        }
+        Err(e) => {
+            error!("cannot load edges: {namespace} {e:?}");
         }
     };
     stream_message(&stream_to, Message::EndOfStream {}, StreamOption::Close).await;
 }
 
+/// stream the gene mappings and then the journal of every path under the
+/// `prefix`, ie: `/actors/`, to an export
+async fn handle_export_load_cmd<T: Value>(
+    prefix: String,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    stream_to: Option<mpsc::Sender<Message<T>>>,
+) {
+    // the mapping of the namespace itself is `/actors` without the separator
+    let root = prefix.trim_end_matches('/');
+    match get_mappings(dbconn, namespace).await {
+        Ok(rows) => {
+            for message in rows {
+                if let Message::GeneMapping { path, .. } = &message {
+                    if path == root || path.starts_with(&prefix) {
+                        stream_message(&stream_to, message, StreamOption::LeaveOpen).await;
+                    }
+                }
+            }
+        }
+        Err(e) => error!("cannot export gene mappings: {prefix} {e:?}"),
+    }
+    match dbconn.get_journal(&prefix).await {
+        Ok(rows) => {
+            for message in rows {
+                stream_message(&stream_to, message, StreamOption::LeaveOpen).await;
+            }
+        }
+        Err(e) => error!("cannot export jrnl: {prefix} {e:?}"),
+    }
+    stream_message(&stream_to, Message::EndOfStream {}, StreamOption::Close).await;
+}
+
 #[async_trait]
-impl Actor for StoreActor {
+impl<T: Value> Actor<T> for StoreActor<T> {
     /// the main entry point to every actor - this is where the jrnl read and
     /// write requests arrive
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
-        if let Some(dbconn) = &self.dbconn {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        if self.options.batch_size > 1 && self.dbconn.is_some() {
+            if matches!(envelope.message, Message::Observations { .. }) {
+                self.buffer_update(envelope).await;
+                return;
+            }
+            self.flush().await;
+        }
+        if let Some(dbconn) = self.dbconn.as_deref() {
             let Envelope {
                 message,
                 respond_to,
                 stream_to,
                 datetime: sequence,
+                cancel,
                 ..
             } = envelope;
 
@@ -314,31 +874,187 @@ impl Actor for StoreActor {
                     path,
                     datetime,
                     values,
+                    labels,
                 } => {
-                    handle_update(
+                    let row = update_row(
                         path,
                         datetime,
                         sequence,
                         values,
+                        labels,
                         self.disable_duplicate_detection,
+                    );
+                    handle_update(row, dbconn, self.options.max_write_attempts, respond_to).await;
+                }
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::GeneMapping,
+                } => {
+                    debug!("loading mappings of {} for {path}", self.namespace);
+                    handle_gene_mapping_load_cmd(&self.namespace, dbconn, stream_to).await;
+                }
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::Export,
+                } => {
+                    handle_export_load_cmd(path, &self.namespace, dbconn, stream_to).await;
+                }
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::Update,
+                } => {
+                    handle_load_cmd(
+                        path,
+                        dbconn,
+                        stream_to,
+                        cancel,
+                        self.options.max_replay_events,
+                        None,
+                    )
+                    .await;
+                }
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::StateAsOf { datetime },
+                } => {
+                    handle_load_cmd(
+                        path,
+                        dbconn,
+                        stream_to,
+                        cancel,
+                        self.options.max_replay_events,
+                        Some(datetime),
+                    )
+                    .await;
+                }
+                Message::GeneMapping {
+                    path,
+                    gene_type,
+                    params,
+                } => {
+                    handle_gene_mapping(
+                        path,
+                        gene_type,
+                        &params,
+                        &self.namespace,
                         dbconn,
                         respond_to,
                     )
                     .await;
                 }
-                Message::LoadCmd { path, hint } if hint == MtHint::GeneMapping => {
-                    handle_gene_mapping_load_cmd(path, dbconn, stream_to).await;
+                Message::GeneMappingBatch { mappings } => {
+                    handle_gene_mapping_batch(mappings, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::DeleteGeneMapping { path } => {
+                    handle_delete_gene_mapping(path, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::IndexLabel { idx, name } => {
+                    handle_index_label(idx, &name, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::Query {
+                    hint: MtHint::IndexLabels,
+                    ..
+                } => {
+                    handle_index_labels_query(&self.namespace, dbconn, respond_to).await;
+                }
+                Message::Query {
+                    path,
+                    hint: MtHint::SharedGeneMapping,
+                } => {
+                    handle_shared_gene_mapping_query(path, dbconn, respond_to).await;
+                }
+                Message::Edges { edges } => {
+                    handle_edges(&edges, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::SeriesQuery { path, labels } => {
+                    handle_series_query(path, &labels, dbconn, respond_to).await;
+                }
+                Message::SeriesStream {
+                    path,
+                    labels,
+                    stream_to,
+                } => {
+                    dbconn.stream_series(&path, &labels, stream_to);
+                }
+                Message::Query {
+                    path,
+                    hint:
+                        MtHint::ListPaths {
+                            limit,
+                            offset,
+                            since,
+                        },
+                } => {
+                    handle_list_paths(path, limit, offset, since, dbconn, respond_to).await;
+                }
+                Message::Query {
+                    path,
+                    hint:
+                        MtHint::History {
+                            from,
+                            to,
+                            limit,
+                            offset,
+                        },
+                } => {
+                    handle_history(path, from, to, limit, offset, dbconn, respond_to).await;
+                }
+                Message::Delete { path, mapping } => {
+                    handle_delete(path, mapping, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::Reset { path } => {
+                    handle_reset(path, sequence, dbconn, respond_to).await;
+                }
+                Message::Prune {
+                    path,
+                    before,
+                    keep_last,
+                } => {
+                    handle_prune(path, before, keep_last, dbconn, respond_to).await;
+                }
+                Message::CompactPath {
+                    path,
+                    before,
+                    gene_type,
+                    params,
+                } => {
+                    handle_compact_path(path, before, gene_type, &params, dbconn, respond_to).await;
+                }
+                Message::Stats { path } => {
+                    handle_stats(path, &self.namespace, dbconn, respond_to).await;
+                }
+                Message::PathStats { path } => {
+                    handle_path_stats(path, dbconn, respond_to).await;
                 }
-                Message::LoadCmd { path, hint } if hint == MtHint::Update => {
-                    handle_load_cmd(path, dbconn, stream_to).await;
+                Message::Snapshot {
+                    path,
+                    datetime,
+                    values,
+                    version,
+                } => {
+                    handle_snapshot(path, datetime, version, &values, dbconn, respond_to).await;
                 }
-                Message::GeneMapping { path, gene_type } => {
-                    handle_gene_mapping(path, gene_type, dbconn, respond_to).await;
+                Message::Ping => {
+                    let r = match dbconn.ping().await {
+                        Ok(()) => Ok(Message::Pong),
+                        Err(e) => Err(NvError {
+                            reason: format!("db not ready: {}", e.reason),
+                        }),
+                    };
+                    respond_or_log_error(respond_to, r);
                 }
+                // the pending batch was committed before any other message is handled
+                Message::Flush => respond_or_log_error(respond_to, Ok(Message::Flushed)),
                 m => warn!("Unexpected: {m}"),
             }
         } else {
             error!("DB not configured");
+            respond_or_log_error(
+                envelope.respond_to,
+                Err(NvError {
+                    reason: String::from("DB not configured"),
+                }),
+            );
         }
     }
     async fn start(&mut self) {}
@@ -349,217 +1065,270 @@ impl Actor for StoreActor {
     }
 }
 
-// TODO: store mappings with namespace / path compound key
-async fn get_mappings_for_ns(
-    path: &str,
-    dbconn: &SqlitePool,
-) -> Result<Vec<Message<f64>>, sqlx::error::Error> {
-    debug!("loading mappings for path {path}");
-    sqlx::query("SELECT path, gene_type FROM gene_mappings;")
-        .bind(path)
-        .try_map(|row: sqlx::sqlite::SqliteRow| {
-            let path = match row.try_get(0) {
-                //let path = match from_str(row.get(0)) {
-                Ok(p) => p,
-                Err(e) => {
-                    error!("cannot read path");
-                    return Err(sqlx::Error::Decode(Box::new(e)));
-                }
-            };
-
-            let gene_type = match row.try_get(1) {
-                Ok(p) => match from_str(p) {
-                    Ok(gt) => gt,
-                    Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
-                },
-                Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
-            };
+async fn handle_index_label<T: Value>(
+    idx: i32,
+    name: &str,
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.insert_index_label(namespace, idx, name).await {
+        Ok(()) => {
+            debug!("index label {idx} -> '{name}' persisted");
+            respond_or_log_error(respond_to, Ok(Message::Persisted {}));
+        }
+        Err(e) => respond_or_log_error(
+            respond_to,
+            Err(NvError {
+                reason: format!("cannot label index {idx}: {}", e.reason),
+            }),
+        ),
+    }
+}
 
-            Ok(Message::GeneMapping { path, gene_type })
-        })
-        .fetch_all(dbconn)
+async fn handle_index_labels_query<T: Value>(
+    namespace: &str,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let r = dbconn
+        .get_index_labels(namespace)
         .await
+        .map(|labels| Message::IndexLabels { labels })
+        .map_err(|e| NvError {
+            reason: format!("cannot load the index labels of {namespace}: {}", e.reason),
+        });
+    respond_or_log_error(respond_to, r);
 }
 
-async fn get_values(
-    path: &str,
-    dbconn: &SqlitePool,
-) -> Result<Vec<Message<f64>>, sqlx::error::Error> {
-    sqlx::query("SELECT timestamp, values_str FROM updates WHERE path = ?")
-        .bind(path)
-        .try_map(|row: sqlx::sqlite::SqliteRow| {
-            let date_parsed_num = match from_str(row.get(0)) {
-                Ok(val) => val,
-                Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
-            };
-
-            let date_parsed = OffsetDateTimeWrapper {
-                datetime_num: date_parsed_num,
-            };
-
-            let values = match row.try_get(1) {
-                Ok(val_str) => match from_str(val_str) {
-                    Ok(val) => val,
-                    Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
-                },
-                Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
-            };
-
-            let dt = match date_parsed.to_ts() {
-                Ok(dt) => dt,
-                Err(e) => {
-                    error!("can not parse date - using 'now': {e}");
-                    OffsetDateTime::now_utc()
-                }
-            };
-            Ok(Message::Observations {
-                path: String::from(path),
-                datetime: dt,
-                values,
-            })
-        })
-        .fetch_all(dbconn)
-        .await
+async fn handle_shared_gene_mapping_query<T: Value>(
+    path: String,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let r = match dbconn.get_shared_mapping(&path).await {
+        Ok(Some(mapping)) => Ok(Message::GeneMapping {
+            path: mapping.path,
+            gene_type: mapping.gene_type,
+            params: mapping.params,
+        }),
+        Ok(None) => Ok(Message::NotFound { path }),
+        Err(e) => Err(NvError {
+            reason: format!(
+                "cannot load the shared gene mapping of {path}: {}",
+                e.reason
+            ),
+        }),
+    };
+    respond_or_log_error(respond_to, r);
+}
+
+async fn handle_series_query<T: Value>(
+    path: String,
+    labels: &Labels,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    match dbconn.get_series(&path, labels).await {
+        Ok(observations) => {
+            respond_or_log_error(respond_to, Ok(Message::Series { path, observations }));
+        }
+        Err(e) => {
+            error!("cannot load series for {path}: {e:?}");
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("cannot load series for {path}: {}", e.reason),
+                }),
+            );
+        }
+    }
 }
 
-impl StoreActor {
-    /// actor private constructor
-    const fn new(
-        receiver: mpsc::Receiver<Envelope<f64>>,
-        dbconn: Option<SqlitePool>,
-        namespace: String,
+async fn handle_history<T: Value>(
+    path: String,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+    limit: usize,
+    offset: usize,
+    dbconn: &dyn Journal<T>,
+    respond_to: Option<Sender<NvResult<Message<T>>>>,
+) {
+    let history = match dbconn.count_history(&path, from, to).await {
+        Ok(total) => dbconn
+            .get_history(&path, from, to, limit, offset)
+            .await
+            .map(|observations| (observations, total)),
+        Err(e) => Err(e),
+    };
+    match history {
+        Ok((observations, total)) => {
+            let points = observations
+                .into_iter()
+                .filter_map(|m| match m {
+                    Message::Observations {
+                        datetime, values, ..
+                    } => Some((datetime, values)),
+                    _ => None,
+                })
+                .collect();
+            respond_or_log_error(
+                respond_to,
+                Ok(Message::History {
+                    path,
+                    points,
+                    total,
+                }),
+            );
+        }
+        Err(e) => {
+            error!("cannot load history for {path}: {e:?}");
+            respond_or_log_error(
+                respond_to,
+                Err(NvError {
+                    reason: format!("cannot load history for {path}: {}", e.reason),
+                }),
+            );
+        }
+    }
+}
+
+impl<T: Value> StoreActor<T> {
+    /// actor private constructor - `actors`, `/actors`, and `/actors/` are
+    /// the same namespace
+    fn new(
+        receiver: mpsc::Receiver<Envelope<T>>,
+        dbconn: Option<Box<dyn Journal<T>>>,
+        namespace: &str,
         disable_duplicate_detection: bool,
+        options: StoreOptions,
     ) -> Self {
         Self {
             receiver,
             dbconn,
-            namespace,
+            namespace: namespace.trim_matches('/').to_string(),
             disable_duplicate_detection,
+            options,
+            pending: Vec::new(),
+            flush_at: None,
         }
     }
-}
-
-async fn define_gene_mapping_table_if_not_exist(
-    db_url: &str,
-    dbconn: &SqlitePool,
-) -> StoreResult<()> {
-    let rows = sqlx::query("PRAGMA journal_mode;")
-        .fetch_all(dbconn)
-        .await
-        .map_err(|e| StoreError {
-            reason: format!("Failed to fetch journal_mode: {e}"),
-        })?;
-
-    let journal_mode: String = rows[0].get("journal_mode");
-    info!("connected to db in journal_mode for mappings: {journal_mode}");
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS gene_mappings (
-              path TEXT NOT NULL,
-              gene_type TEXT NOT NULL,
-              PRIMARY KEY (path)
-        )",
-    )
-    .execute(dbconn)
-    .await
-    .map_err(|e| StoreError {
-        reason: format!("Failed to create file {db_url}: {e}"),
-    })?;
-
-    Ok(())
-}
-
-/// define table if it does not exist and log to console the journal mode
-async fn define_updates_table_if_not_exist(db_url: &str, dbconn: &SqlitePool) -> StoreResult<()> {
-    let rows = sqlx::query("PRAGMA journal_mode;")
-        .fetch_all(dbconn)
-        .await
-        .map_err(|e| StoreError {
-            reason: format!("Failed to fetch journal_mode: {e}"),
-        })?;
-
-    let journal_mode: String = rows[0].get("journal_mode");
-    info!("connected to db in journal_mode: {journal_mode}");
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS updates (
-              path TEXT NOT NULL,
-              timestamp TEXT NOT NULL,
-              sequence TEXT NOT NULL,
-              values_str TEXT NOT NULL,
-              PRIMARY KEY (path, timestamp)
-        )",
-    )
-    .execute(dbconn)
-    .await
-    .map_err(|e| StoreError {
-        reason: format!("Failed to create file {db_url}: {e}"),
-    })?;
-
-    Ok(())
-}
-
-/// enable write-ahead-logging mode for append-only-style db
-async fn enable_wal(db_url: &str, dbconn: &SqlitePool) -> StoreResult<()> {
-    match sqlx::query("PRAGMA journal_mode = WAL;")
-        .execute(dbconn)
-        .await
-    {
-        Ok(_) => Ok(()),
-        Err(e) => Err(StoreError {
-            reason: format!("Failed to create file {db_url}: {e}"),
-        }),
-    }
-}
 
-/// multiple operations:
-/// 1. initialize the DB if it does not exist
-/// 2. connect
-/// 3. configure wal
-/// 4  report to console
-/// 5. return a db connection object.
-async fn init_db(namespace: String, write_ahead_logging: bool) -> StoreResult<SqlitePool> {
-    let db_url_string: String = format!("{namespace}.db");
-    let db_url: &str = &db_url_string;
-    let db_path = Path::new(db_url);
-    if !db_path.exists() {
-        match File::create(db_url) {
-            Ok(_) => debug!("File {} has been created", db_url),
-            Err(e) => {
-                return Err(StoreError {
-                    reason: format!("Failed to create file {db_url}: {e}"),
-                });
-            }
+    /// add an observation to the pending batch and commit the batch once it
+    /// is full
+    async fn buffer_update(&mut self, envelope: Envelope<T>) {
+        let Envelope {
+            message:
+                Message::Observations {
+                    path,
+                    datetime,
+                    values,
+                    labels,
+                },
+            respond_to,
+            datetime: sequence,
+            ..
+        } = envelope
+        else {
+            return;
+        };
+        let row = update_row(
+            path,
+            datetime,
+            sequence,
+            values,
+            labels,
+            self.disable_duplicate_detection,
+        );
+        if self.pending.is_empty() {
+            self.flush_at = Some(Instant::now() + BATCH_TIMEOUT);
+        }
+        self.pending.push((row, respond_to));
+        if self.pending.len() >= self.options.batch_size {
+            self.flush().await;
         }
     }
 
-    // connect to db, enable wal if configured, and report to the console on
-    // how the db is configured
-    match SqlitePool::connect(db_url).await {
-        Ok(dbconn) => {
-            if write_ahead_logging {
-                match enable_wal(db_url, &dbconn).await {
-                    Ok(_) => {}
-                    Err(e) => return Err(e),
+    /// commit the pending batch and confirm each of its observations
+    #[instrument(skip_all, fields(namespace = %self.namespace, rows = self.pending.len(), msg = "Observations"))]
+    async fn flush(&mut self) {
+        self.flush_at = None;
+        if self.pending.is_empty() {
+            return;
+        }
+        let (rows, responders): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending).into_iter().unzip();
+        let max_write_attempts = self.options.max_write_attempts;
+        let outcomes = match self.dbconn.as_deref() {
+            Some(dbconn) => {
+                let outcomes =
+                    with_retries(max_write_attempts, || dbconn.insert_updates(&rows)).await;
+                match outcomes {
+                    // an observation the batch could not lock is tried on its own
+                    Ok(outcomes) => {
+                        let mut retried = Vec::with_capacity(outcomes.len());
+                        for (row, outcome) in rows.iter().zip(outcomes) {
+                            retried.push(match outcome {
+                                Err(e) if e.transient => {
+                                    insert_with_retries(row, dbconn, max_write_attempts).await
+                                }
+                                outcome => outcome,
+                            });
+                        }
+                        Ok(retried)
+                    }
+                    Err(e) => Err(e),
                 }
             }
-            match define_updates_table_if_not_exist(db_url, &dbconn).await {
-                Ok(_) => match define_gene_mapping_table_if_not_exist(db_url, &dbconn).await {
-                    Ok(_) => Ok(dbconn),
-                    Err(e) => Err(e),
-                },
-                Err(e) => Err(e),
+            None => Err(StoreError {
+                reason: String::from("DB not configured"),
+                transient: false,
+            }),
+        };
+        match outcomes {
+            Ok(outcomes) => {
+                debug!("batch of {} observations committed", rows.len());
+                for ((row, respond_to), outcome) in rows.iter().zip(responders).zip(outcomes) {
+                    respond_to_update(&row.path, outcome, respond_to);
+                }
+            }
+            Err(e) => {
+                error!("cannot commit batch of {} observations: {e:?}", rows.len());
+                for (row, respond_to) in rows.iter().zip(responders) {
+                    respond_to_update(&row.path, Err(e.clone()), respond_to);
+                }
             }
-        }
-        Err(e) => {
-            error!("cannot connect to db: {e:?}");
-            Err(StoreError {
-                reason: format!("{e:?}"),
-            })
         }
     }
 }
 
+/// open the `<namespace>.db` file of the default `SqliteStore`
+async fn init_db<T: Value>(
+    namespace: String,
+    write_ahead_logging: bool,
+    options: &SqliteOptions,
+) -> StoreResult<Box<dyn Journal<T>>> {
+    let store =
+        SqliteStore::connect_with(&format!("{namespace}.db"), write_ahead_logging, options).await?;
+    Ok(Box::new(store))
+}
+
+/// the backend of a connection url: `postgres://` and `postgresql://` urls
+/// use a `PostgresStore` and any other url is a `SQLite` db file
+async fn init_db_from_url<T: Value>(
+    db_url: String,
+    write_ahead_logging: bool,
+    options: &SqliteOptions,
+) -> StoreResult<Box<dyn Journal<T>>> {
+    if db_url.starts_with("postgres://") || db_url.starts_with("postgresql://") {
+        Ok(Box::new(PostgresStore::connect(&db_url).await?))
+    } else {
+        Ok(Box::new(
+            SqliteStore::connect_with(&db_url, write_ahead_logging, options).await?,
+        ))
+    }
+}
+
 /// actor handle public constructor
 #[must_use]
 pub fn new(
@@ -567,12 +1336,30 @@ pub fn new(
     namespace: String,
     write_ahead_logging: bool,
     disable_duplicate_detection: bool,
+) -> Handle {
+    new_with_options(
+        bufsz,
+        namespace,
+        write_ahead_logging,
+        disable_duplicate_detection,
+        StoreOptions::default(),
+    )
+}
+
+/// `new` with the store tuning set, ie: the `batch_size` of its writes
+#[must_use]
+pub fn new_with_options(
+    bufsz: usize,
+    namespace: String,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+    options: StoreOptions,
 ) -> Handle {
     async fn start(mut actor: StoreActor, namespace: String, write_ahead_logging: bool) {
         // create a db connection and put it in the actor state
         // the connection is made after spawning the new thread which is why
         // the db connection is not passed to the actor constructor
-        let dbconn = init_db(namespace, write_ahead_logging)
+        let dbconn = init_db(namespace, write_ahead_logging, &actor.options.sqlite)
             .await
             .map_err(|e| {
                 error!("cannot get dbconn: {e:?}");
@@ -581,11 +1368,7 @@ pub fn new(
 
         actor.dbconn = dbconn;
 
-        while let Some(envelope) = actor.receiver.recv().await {
-            actor.handle_envelope(envelope).await;
-        }
-
-        actor.stop().await;
+        run(actor).await;
     }
 
     let (sender, receiver) = mpsc::channel(bufsz);
@@ -593,8 +1376,9 @@ pub fn new(
     let actor = StoreActor::new(
         receiver,
         None,
-        namespace.clone(),
+        &namespace,
         disable_duplicate_detection,
+        options,
     );
 
     let actor_handle = Handle::new(sender);
@@ -603,3 +1387,183 @@ pub fn new(
 
     actor_handle
 }
+
+/// actor handle public constructor for the db at `db_url` - a `postgres://`
+/// or `postgresql://` url is a `Postgres` database that many servers can
+/// share and any other url is a `SQLite` db file, ie: `sqlite://actors.db` -
+/// the gene mappings are those of `namespace`
+#[must_use]
+pub fn new_from_url(
+    bufsz: usize,
+    db_url: String,
+    namespace: &str,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+) -> Handle {
+    new_from_url_with_options(
+        bufsz,
+        db_url,
+        namespace,
+        write_ahead_logging,
+        disable_duplicate_detection,
+        StoreOptions::default(),
+    )
+}
+
+/// `new_from_url` with the store tuning set
+#[must_use]
+pub fn new_from_url_with_options(
+    bufsz: usize,
+    db_url: String,
+    namespace: &str,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+    options: StoreOptions,
+) -> Handle {
+    async fn start(mut actor: StoreActor, db_url: String, write_ahead_logging: bool) {
+        let dbconn = init_db_from_url(db_url, write_ahead_logging, &actor.options.sqlite)
+            .await
+            .map_err(|e| {
+                error!("cannot get dbconn: {e:?}");
+            })
+            .ok();
+
+        actor.dbconn = dbconn;
+
+        run(actor).await;
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = StoreActor::new(
+        receiver,
+        None,
+        namespace,
+        disable_duplicate_detection,
+        options,
+    );
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor, db_url, write_ahead_logging));
+
+    actor_handle
+}
+
+async fn run<T: Value>(mut actor: StoreActor<T>) {
+    loop {
+        let envelope = match actor.flush_at {
+            Some(flush_at) => tokio::select! {
+                envelope = actor.receiver.recv() => envelope,
+                () = tokio::time::sleep_until(flush_at) => {
+                    actor.flush().await;
+                    continue;
+                }
+            },
+            None => actor.receiver.recv().await,
+        };
+        let Some(envelope) = envelope else {
+            break;
+        };
+        if matches!(envelope.message, Message::Stop | Message::Shutdown) {
+            actor.flush().await;
+            actor.stop().await;
+            debug!("store stopped");
+            respond_or_log_error(envelope.respond_to, Ok(Message::Stopped));
+            return;
+        }
+        actor.handle_envelope(envelope).await;
+    }
+
+    actor.flush().await;
+    actor.stop().await;
+}
+
+/// actor handle public constructor that opens the db before returning so
+/// that callers learn right away if the store is unusable
+///
+/// # Errors
+///
+/// Returns [`StoreError`](struct.StoreError.html) if the db can not be
+/// created, connected to, or initialized
+pub async fn try_new(
+    bufsz: usize,
+    namespace: String,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+) -> StoreResult<Handle> {
+    try_new_with_options(
+        bufsz,
+        namespace,
+        write_ahead_logging,
+        disable_duplicate_detection,
+        StoreOptions::default(),
+    )
+    .await
+}
+
+/// `try_new` with the store tuning set
+///
+/// # Errors
+///
+/// Returns [`StoreError`](struct.StoreError.html) if the db can not be
+/// created, connected to, or initialized
+pub async fn try_new_with_options(
+    bufsz: usize,
+    namespace: String,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+    options: StoreOptions,
+) -> StoreResult<Handle> {
+    let dbconn = init_db(namespace.clone(), write_ahead_logging, &options.sqlite).await?;
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = StoreActor::new(
+        receiver,
+        Some(dbconn),
+        &namespace,
+        disable_duplicate_detection,
+        options,
+    );
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(run(actor));
+
+    Ok(actor_handle)
+}
+
+/// `new_from_url` that connects before returning so that callers learn
+/// right away if the store is unusable
+///
+/// # Errors
+///
+/// Returns [`StoreError`](struct.StoreError.html) if the db can not be
+/// created, connected to, or initialized
+pub async fn try_new_from_url<T: Value>(
+    bufsz: usize,
+    db_url: String,
+    namespace: &str,
+    write_ahead_logging: bool,
+    disable_duplicate_detection: bool,
+    options: StoreOptions,
+) -> StoreResult<Handle<T>> {
+    let dbconn = init_db_from_url(db_url, write_ahead_logging, &options.sqlite).await?;
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = StoreActor::new(
+        receiver,
+        Some(dbconn),
+        namespace,
+        disable_duplicate_detection,
+        options,
+    );
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(run(actor));
+
+    Ok(actor_handle)
+}
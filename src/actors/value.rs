@@ -0,0 +1,162 @@
+//!This module defines the `Value` trait - the number type that actor state is kept in.
+//!
+//!The actors, the journal, and the json decoder are generic over their value type and `update`
+//!picks one per run with `--value-type`. `f64` is the default and every gene applies to it. `i64` keeps integers exact past
+//!the 2^53 where an `f64` starts rounding, ie: the totals of large counters, but only the genes that
+//!never need a fraction apply to it - a mean, a rate, or a moving average of integers is not an
+//!integer.  An `Aggregate` gene applies to both as a sum but only to `f64` as a mean, and the
+//!thresholds of an `Alert` gene are `f64` bounds.
+
+use crate::actors::genes::accum_gene::AccumGene;
+use crate::actors::genes::aggregate_gene::AggregateGene;
+use crate::actors::genes::alert_gene::AlertGene;
+use crate::actors::genes::composite_gene::CompositeGene;
+use crate::actors::genes::delta_gene::DeltaGene;
+use crate::actors::genes::ewma_gene::EwmaGene;
+use crate::actors::genes::ewma_gene::DEFAULT_ALPHA;
+use crate::actors::genes::gauge_and_accum_gene::GaugeAndAccumGene;
+use crate::actors::genes::gauge_gene::GaugeGene;
+use crate::actors::genes::gene::AggregateFn;
+use crate::actors::genes::gene::Gene;
+use crate::actors::genes::gene::GeneParams;
+use crate::actors::genes::gene::GeneType;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::genes::latest_gene::LatestGene;
+use crate::actors::genes::mean_gene::MeanGene;
+use crate::actors::genes::minmax_gene::MinMaxGene;
+use crate::actors::genes::monotonic_counter_gene::MonotonicCounterGene;
+use crate::actors::genes::moving_avg_gene::MovingAvgGene;
+use crate::actors::genes::moving_avg_gene::DEFAULT_WINDOW;
+use crate::actors::genes::rate_gene::RateGene;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::ops::Add;
+use std::ops::Sub;
+use std::str::FromStr;
+
+/// the number type of an actor's state
+pub trait Value:
+    Add<Output = Self>
+    + Sub<Output = Self>
+    + Copy
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + FromStr
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + Unpin
+    + 'static
+{
+    /// the name of the type as the CLI spells it, ie: `i64`
+    const NAME: &'static str;
+
+    /// the gene of `gene_type` for state of this type - `None` if the gene
+    /// does not apply to it
+    fn gene(gene_type: GeneType, params: &GeneParams) -> Option<Box<dyn Gene<Self> + Send + Sync>>;
+
+    /// false for the `NaN` and infinities that no observation may carry
+    fn is_finite(self) -> bool {
+        true
+    }
+
+    /// the value rounded to `decimals` decimal places - a whole number is
+    /// already as short as it gets
+    #[must_use]
+    fn rounded(self, _decimals: u32) -> Self {
+        self
+    }
+}
+
+impl Value for f64 {
+    const NAME: &'static str = "f64";
+
+    fn gene(gene_type: GeneType, params: &GeneParams) -> Option<Box<dyn Gene<Self> + Send + Sync>> {
+        let gene: Box<dyn Gene<Self> + Send + Sync> = match gene_type {
+            GeneType::Mean => Box::<MeanGene>::default(),
+            GeneType::Rate => Box::<RateGene>::default(),
+            GeneType::Ewma => Box::new(EwmaGene {
+                alpha: params.alpha.unwrap_or(DEFAULT_ALPHA),
+                ..Default::default()
+            }),
+            GeneType::MovingAvg => Box::new(MovingAvgGene {
+                window: params.window.unwrap_or(DEFAULT_WINDOW),
+                ..Default::default()
+            }),
+            GeneType::Aggregate => Box::new(AggregateGene::<Self>::new(
+                params.aggregate.unwrap_or_default(),
+            )),
+            GeneType::Alert => Box::new(AlertGene::with_params(params)),
+            gene_type => return whole_number_gene(gene_type, params),
+        };
+        Some(gene)
+    }
+
+    fn is_finite(self) -> bool {
+        Self::is_finite(self)
+    }
+
+    fn rounded(self, decimals: u32) -> Self {
+        let scale = 10_f64.powi(i32::try_from(decimals).unwrap_or(i32::MAX));
+        let rounded = (self * scale).round() / scale;
+        // a value too large to scale has no decimals to round
+        if rounded.is_finite() {
+            rounded
+        } else {
+            self
+        }
+    }
+}
+
+impl Value for i64 {
+    const NAME: &'static str = "i64";
+
+    fn gene(gene_type: GeneType, params: &GeneParams) -> Option<Box<dyn Gene<Self> + Send + Sync>> {
+        match (gene_type, params.aggregate.unwrap_or_default()) {
+            (GeneType::Aggregate, AggregateFn::Sum) => {
+                Some(Box::new(AggregateGene::<Self>::new(AggregateFn::Sum)))
+            }
+            (GeneType::Aggregate, AggregateFn::Mean) => None,
+            (gene_type, _) => whole_number_gene(gene_type, params),
+        }
+    }
+}
+
+/// the genes that only add, subtract, and compare - they apply to any value type
+fn whole_number_gene<T>(
+    gene_type: GeneType,
+    params: &GeneParams,
+) -> Option<Box<dyn Gene<T> + Send + Sync>>
+where
+    T: Add<Output = T> + Sub<Output = T> + Copy + PartialOrd + 'static,
+{
+    let gene: Box<dyn Gene<T> + Send + Sync> = match gene_type {
+        GeneType::Accum => Box::new(AccumGene {
+            time_scope: params.time_scope.unwrap_or(TimeScope::Forever),
+            ..Default::default()
+        }),
+        GeneType::Gauge | GeneType::Default => Box::<GaugeGene>::default(),
+        GeneType::Latest => Box::<LatestGene>::default(),
+        GeneType::DailyAccum => Box::new(AccumGene {
+            time_scope: TimeScope::Day,
+            retain_buckets: true,
+            ..Default::default()
+        }),
+        GeneType::Min => Box::new(MinMaxGene::all_min()),
+        GeneType::Max => Box::new(MinMaxGene::all_max()),
+        GeneType::Counter => Box::<MonotonicCounterGene>::default(),
+        GeneType::GaugeAndAccum => Box::new(GaugeAndAccumGene::with_params(params)),
+        GeneType::Composite => Box::new(CompositeGene::with_params(params)),
+        GeneType::Delta => Box::new(DeltaGene::with_params(params)),
+        GeneType::Mean
+        | GeneType::Rate
+        | GeneType::Ewma
+        | GeneType::MovingAvg
+        | GeneType::Aggregate
+        | GeneType::Alert => return None,
+    };
+    Some(gene)
+}
@@ -0,0 +1,136 @@
+//! `--config` reads the flags of `nv` from a TOML file, ie: for a systemd unit that would otherwise
+//! pass a dozen flags to `nv serve`. The top level keys are global flags and the keys of a table
+//! named after a command are the flags of that command, spelled with `_` or `-`:
+//!
+//! ```toml
+//! buffer = 16
+//!
+//! [serve]
+//! port = 8800
+//! namespace = "actors"
+//! disable_wal = true
+//! ```
+//!
+//! The file is turned into command line flags that are parsed ahead of the flags given on the
+//! command line, so an explicit flag overrides the file and the file overrides the defaults. A
+//! `true` switch is given and a `false` one is left out, a count is repeated, and an array is one
+//! flag per element that adds to the elements given on the command line. The tables of the other
+//! commands are ignored.
+
+use crate::cli::ifc::Cli;
+use crate::cli::runner::CliError;
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use std::ffi::OsString;
+use std::path::Path;
+
+/// the flags of the config file at `path` followed by the command line
+/// `args`, the program name first
+///
+/// # Errors
+///
+/// Returns [`CliError`](../runner/enum.CliError.html) if the file can not be
+/// read or a key of it is not a flag
+pub fn with_config_file(path: &Path, args: &[OsString]) -> Result<Vec<OsString>, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("cannot read config {}: {e}", path.display())))?;
+    let config: toml::Table = toml::from_str(&text)
+        .map_err(|e| CliError::Usage(format!("cannot parse config {}: {e}", path.display())))?;
+
+    let cli = Cli::command();
+    // the command is the first argument after the program that names one
+    let command_at = args
+        .iter()
+        .skip(1)
+        .position(|arg| {
+            arg.to_str()
+                .is_some_and(|arg| cli.find_subcommand(arg).is_some())
+        })
+        .map(|at| at + 1);
+    let command = command_at
+        .and_then(|at| args[at].to_str())
+        .and_then(|name| cli.find_subcommand(name));
+
+    let mut global_flags = Vec::new();
+    let mut command_flags = Vec::new();
+    for (key, value) in config {
+        match value {
+            toml::Value::Table(table) => {
+                let name = key.replace('_', "-");
+                if cli.find_subcommand(&name).is_none() {
+                    return Err(CliError::Usage(format!("no command `{key}` in config")));
+                }
+                let Some(command) = command.filter(|command| command.get_name() == name) else {
+                    continue;
+                };
+                for (key, value) in table {
+                    push_flag(command, &key, value, &mut command_flags)?;
+                }
+            }
+            value => push_flag(&cli, &key, value, &mut global_flags)?,
+        }
+    }
+
+    // the flags of the command go right after its name
+    let after_command = command_at.map_or(args.len(), |at| at + 1);
+    let mut with_config: Vec<OsString> = args.iter().take(1).cloned().collect();
+    with_config.extend(global_flags);
+    with_config.extend(args.iter().take(after_command).skip(1).cloned());
+    with_config.extend(command_flags);
+    with_config.extend(args.iter().skip(after_command).cloned());
+    Ok(with_config)
+}
+
+/// the command line flags of `key = value` for the flag named `key` of `command`
+fn push_flag(
+    command: &Command,
+    key: &str,
+    value: toml::Value,
+    flags: &mut Vec<OsString>,
+) -> Result<(), CliError> {
+    let long = key.replace('_', "-");
+    let arg: &Arg = command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(long.as_str()))
+        .ok_or_else(|| {
+            CliError::Usage(format!(
+                "no flag `{key}` for `{}` in config",
+                command.get_name()
+            ))
+        })?;
+    let flag = OsString::from(format!("--{long}"));
+    let invalid = |value: &toml::Value| {
+        CliError::Usage(format!("`{key} = {value}` in config is not a valid value"))
+    };
+    match (arg.get_action(), value) {
+        (ArgAction::SetTrue, toml::Value::Boolean(true)) => flags.push(flag),
+        (ArgAction::SetTrue, toml::Value::Boolean(false)) => {}
+        (ArgAction::Count, toml::Value::Integer(count)) => {
+            for _ in 0..count {
+                flags.push(flag.clone());
+            }
+        }
+        (ArgAction::Append, toml::Value::Array(values)) => {
+            for value in values {
+                flags.push(flag.clone());
+                flags.push(flag_value(&value).ok_or_else(|| invalid(&value))?);
+            }
+        }
+        (ArgAction::Set | ArgAction::Append, value) => {
+            flags.push(flag);
+            flags.push(flag_value(&value).ok_or_else(|| invalid(&value))?);
+        }
+        (_, value) => return Err(invalid(&value)),
+    }
+    Ok(())
+}
+
+/// a TOML scalar as the value of a flag
+fn flag_value(value: &toml::Value) -> Option<OsString> {
+    match value {
+        toml::Value::String(s) => Some(OsString::from(s)),
+        toml::Value::Integer(i) => Some(OsString::from(i.to_string())),
+        toml::Value::Float(f) => Some(OsString::from(f.to_string())),
+        toml::Value::Boolean(b) => Some(OsString::from(b.to_string())),
+        toml::Value::Datetime(_) | toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
@@ -9,7 +9,7 @@
 //! The `Cli` struct also defines a command field that holds a variant of the `Commands` enum,
 //! which is also derived from the `Subcommand` and Debug traits provided by Clap. The `Commands`
 //! enum represents the different `subcommands` that the program can accept, such as Update,
-//! Inspect, `Configure`, and `Completions`.
+//! Inspect, `Replay`, `Delete`, `Reset`, `Prune`, `Compact`, `Export`, `Import`, `IngestMqtt`, `Configure`, `Label`, and `Completions`.
 //!
 //! Each variant of the `Commands` enum defines its own set of command-line arguments that are
 //! specific to that `subcommand`. For example, the Update variant has several arguments such as
@@ -25,7 +25,10 @@
 //! arguments, making it easy for users to get up and running with the tool quickly and
 //! efficiently.
 
+use crate::actors::director::configured_gene;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
+use crate::actors::sqlite_store::SqliteSynchronous;
 use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -35,9 +38,17 @@ use clap::{Args, Parser, Subcommand};
     version,
     about,
     long_about = "nv is the CLI for the DtLaboratory project",
-    propagate_version = true
+    propagate_version = true,
+    args_override_self = true
 )] // Read from `Cargo.toml`
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Read flags from this TOML file",
+        long_help = "The top level keys of the TOML file are global flags, ie: 'buffer = 16', and the keys of a table named after a command, ie: '[serve]', are flags of that command, ie: 'port = 8800' or 'disable_wal = true'.  A flag given on the command line overrides the file and the file overrides the defaults."
+    )]
+    pub config: Option<std::path::PathBuf>,
     #[arg(
         short,
         long,
@@ -45,9 +56,16 @@ pub struct Cli {
         long_help = "The number of unread messages allowed in an actor's mailbox.  Small numbers can cause the system to single-thread / serialize work.  Large numbers can harm data integrity / commits and leave a lot of unfinished work if the server stops."
     )]
     pub buffer: Option<usize>,
+    #[arg(
+        long,
+        value_parser = high_water_fraction,
+        help = "Warn when a mailbox is this fraction full",
+        long_help = "A warning is logged when the mailbox of a director, of its store or output, or of one of its actors holds at least this fraction of 'buffer' messages, ie: '0.8', and a note when it drains below it again.  The fill of the director, store, and output mailboxes is always reported at '/metrics'.  Without the flag no warning is logged."
+    )]
+    pub mailbox_high_water: Option<f64>,
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
-    #[arg(long, action = clap::ArgAction::SetTrue, help = "No on-disk db file", long_help = "For best performance, but you should not run with '--silent' as you won't know what the in-memory data was since it is now ephemeral.")]
+    #[arg(long, global = true, action = clap::ArgAction::SetTrue, help = "No on-disk db file", long_help = "For best performance, but you should not run with '--silent' as you won't know what the in-memory data was since it is now ephemeral.")]
     pub memory_only: Option<bool>,
     #[clap(subcommand)]
     pub command: Commands,
@@ -65,26 +83,225 @@ pub enum Commands {
         #[arg(short, long, action = clap::ArgAction::Set, long_help = "the director and db file to default to", default_value = "actors")]
         namespace: String,
 
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to journal to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is a file in the working directory named after the namespace with its levels joined by '_', ie: 'acme_sensors.db' for the '/acme/sensors' namespace.")]
+        db_path: Option<String>,
+
         #[arg(long, action = clap::ArgAction::SetTrue, help = "Accept path+datetime collisions", long_help = "The journal stores and replays events in the order that they arrive but will ignore events that have a path and observation timestamp previously recorded - this is the best option for consistency and performance.  With 'disable-duplicate-detection' flag, the journal will accept observations regardless of the payload timestamp - this is good for testing and best for devices with unreliable notions of time.", default_value = "false")]
         disable_duplicate_detection: Option<bool>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Reject observations older than this many seconds", long_help = "Observations may arrive out of order but one that is more than 'observation-window' seconds older than the latest observation already applied to its actor is rejected and not journaled.  Without the flag every observation is accepted regardless of its timestamp.")]
+        observation_window: Option<u64>,
+
+        #[arg(long, value_parser = positive_rate, help = "Reject observations of a path beyond this many per second", long_help = "Each path may send 'max-rate-per-path' observations per second, and a burst of a second's worth after a quiet period.  Observations beyond the rate are rejected before they are journaled so that a flooding device does not starve the other paths of the store.  Without the flag every observation is accepted.")]
+        max_rate_per_path: Option<f64>,
+
+        #[arg(long, value_parser = clap::value_parser!(u32).range(..=15), help = "Round observed values to this many decimal places", long_help = "The values of every observation are rounded to 'decimals' decimal places before they are journaled and applied, ie: 21.456789 is journaled as 21.46 with '--decimals 2', so that the journal stays compact.  Values that are not finite, ie: NaN, are always rejected.  Without the flag values are kept as observed.")]
+        decimals: Option<u32>,
+
+        #[arg(long, value_parser = default_gene, help = "The gene of paths without a gene mapping", long_help = "Every path that no gene mapping of its own or of its ancestors applies to gets the 'default-gene', ie: 'accum' for a namespace of counters, instead of a gauge.  The gene must work with its default params, so the aggregate, alert, and composite genes can only be mapped.")]
+        default_gene: Option<GeneType>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Snapshot an actor's state every this many observations", long_help = "Every time 'snapshot-interval' more observations are applied to an actor its state is persisted as a snapshot and a resurrection replays only the journal written after the latest snapshot.  Without the flag the whole journal is replayed.")]
+        snapshot_interval: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Keep at most this many actors in memory", long_help = "When more than 'max-resident-actors' actors are live the least recently used one is dropped from memory and is resurrected from the journal by the next observation or query of its path.  A subscription to a dropped actor ends.  Without the flag, or without a journal, every actor stays in memory.")]
+        max_resident_actors: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Replay at most this many journal events per actor", long_help = "An actor resurrected from a journal longer than 'max-replay-events' replays only the most recent events and a warning is logged.  A gauge stays accurate if the cap covers its latest readings but an accumulator under-reports everything it counted before the cap.  Without the flag the whole journal is replayed.")]
+        max_replay_events: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Pool at most this many connections to the SQLite journal", long_help = "Readers of a write-ahead-logging journal do not block each other or the writer so more connections serve more concurrent queries.  Ignored by a 'postgres://' journal.", default_value = "8")]
+        max_connections: u32,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Milliseconds to wait on a locked SQLite journal", long_help = "A statement that finds the SQLite journal locked by another connection retries for 'busy-timeout-ms' milliseconds before it fails with 'database is locked'.  Ignored by a 'postgres://' journal.", default_value = "5000")]
+        busy_timeout_ms: u64,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Try a write of observations this many times on a busy journal", long_help = "A write of observations that fails because the journal is busy or locked by another connection, ie: after 'busy-timeout-ms', is tried again up to 'max-write-attempts' times in all, waiting 10ms before the first retry and twice as long before each next one.  An observation the journal already holds, or any other error, is not tried again.  1 tries once.", default_value = "4")]
+        max_write_attempts: usize,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The SQLite 'synchronous' pragma: off, normal, full or extra", long_help = "How often SQLite syncs the journal to disk.  Without the flag it is 'normal' with write-ahead-logging, which can lose the last commits on a power loss but never corrupts the journal, and 'full' without it.  Ignored by a 'postgres://' journal.")]
+        synchronous: Option<SqliteSynchronous>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the piped input", long_help = "Each line of 'json' input is one observation or gene mapping.  Each line of 'csv' input is one 'path,datetime,idx,value' reading and consecutive lines with the same path and datetime are one observation.  Each line of 'lineprotocol' input is one InfluxDB line protocol observation of the path made of its measurement and tag values.", default_value = "json")]
+        format: InputFormat,
+
+        #[arg(long, action = clap::ArgAction::Append, help = "Map a line protocol field name to an index", long_help = "A 'name=idx' pair that writes the line protocol field 'name' to index 'idx' of the actor state, ie: '--field-index usage_idle=1 --field-index usage_user=2'.  A field named with an integer is that index and other fields are skipped.")]
+        field_index: Vec<String>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the printed state", long_help = "'text' prints each new state for people to read and 'json' prints each new state as one NDJSON line for tools like jq.  Named 'output-format' because 'format' selects the format of the input.", default_value = "text")]
+        output_format: OutputFormat,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Read from this file instead of stdin")]
+        input: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Decompress gzipped input", long_help = "Decompress the gzipped stdin or 'input' file before reading its lines, ie: a backfill archive.  Input that is not gzip is rejected rather than decoded.")]
+        gzip: bool,
+
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Check the input without journaling it", long_help = "Decode and apply the input to actors that are only in memory and print a summary of the observations and of the paths that no gene mapping in the input applies to instead of their state.  Nothing is journaled.  The command fails if any line could not be decoded.")]
+        dry_run: bool,
+
+        #[arg(long, num_args = 0..=1, default_missing_value = "5", value_parser = clap::value_parser!(u64).range(1..), value_name = "SECONDS", help = "Print a count of the ingested observations every SECONDS seconds", long_help = "Print how many observations have been ingested to stderr every 'progress' seconds, 5 if no seconds are given, and once more at the end of the input, instead of the state of every observation.  For feedback on a long load of piped data.")]
+        progress: Option<u64>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Number type of the actor state", long_help = "'f64' keeps state as floating point numbers and every gene applies to it.  'i64' keeps state as whole numbers that stay exact past 2^53, ie: the totals of large counters, but only 'json' input is decoded to it and the mean, rate, ewma, and moving average genes do not apply to it.", default_value = "f64")]
+        value_type: ValueType,
     },
     Inspect {
         #[arg(action = clap::ArgAction::Set, help = "get the state of an actor")]
         path: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to inspect", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the printed state", long_help = "'text' prints the state for people to read, 'json' prints it as one NDJSON line for tools like jq, and 'csv' prints one 'path,datetime,idx,value' row per index for spreadsheets.  The observations printed with 'from' and 'to' are printed the same way.", default_value = "text")]
+        format: OutputFormat,
+
+        #[arg(long, action = clap::ArgAction::Set, requires = "to", help = "Print the observations journaled since this ISO 8601 time", long_help = "With 'from' and 'to' the observations of the path that were journaled between the two ISO 8601 times, ie: '2023-01-11T23:00:00Z', are printed one per line in the order they arrived instead of the current state.  The observations are read from the journal and are not applied to the actor.")]
+        from: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::Set, requires = "from", help = "Print the observations journaled until this ISO 8601 time")]
+        to: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::Set, requires = "from", help = "Print at most this many of the observations", long_help = "With 'from' and 'to' print at most 'limit' observations, starting 'offset' observations in.  When that is not all of them the number of observations between the two times is printed to stderr so the rest can be paged through.")]
+        limit: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, requires = "from", help = "Skip this many of the observations", default_value = "0")]
+        offset: usize,
+
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "from", help = "Print the state of every path under the path", long_help = "With 'recursive' the state of every path under the path, ie: every floor and room of '/domain/building/1', is printed in path order instead of the state of the path itself.")]
+        recursive: bool,
+
+        #[arg(long, action = clap::ArgAction::Set, conflicts_with_all = ["from", "recursive"], help = "Print the state as of this ISO 8601 time", long_help = "With 'as-of' the state of the path at an ISO 8601 time, ie: '2023-01-11T15:00:00Z', is printed instead of its current state.  The journal up to that time is replayed into a new actor and the state is empty if the path had no observations yet.")]
+        as_of: Option<String>,
+    },
+    Watch {
+        #[arg(action = clap::ArgAction::Set, help = "follow the state of an actor", long_help = "Print the state of an actor and print it again every time it changes, like 'tail -f', until interrupted with ctrl-c.  The journal is polled so the observations can be written by another process, ie: 'nv serve'.")]
+        path: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to watch", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the printed state", long_help = "'text' prints each new state for people to read and 'json' prints each new state as one NDJSON line for tools like jq.", default_value = "text")]
+        format: OutputFormat,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Seconds between polls of the journal", default_value = "1")]
+        interval: u64,
+    },
+    Replay {
+        #[arg(action = clap::ArgAction::Set, help = "print the state of an actor after every event of its journal", long_help = "Replay the journal of an actor and print its state after every observation is applied, one line per step, to debug how the journal adds up to the current state.  The live actors of a running server are not affected.")]
+        path: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to replay from", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the printed states", long_help = "'text' prints each state for people to read and 'json' prints each state as one NDJSON line for tools like jq.", default_value = "text")]
+        format: OutputFormat,
+    },
+    Delete {
+        #[arg(action = clap::ArgAction::Set, help = "remove the state and journal of an actor")]
+        path: String,
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to delete from", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "also remove the gene mapping of the path")]
+        mapping: bool,
+    },
+    Reset {
+        #[arg(action = clap::ArgAction::Set, help = "start the state of an actor over from empty", long_help = "Journal a reset of the actor so that its state starts over from empty, ie: to zero an accumulator at the rollover of a billing cycle.  Unlike 'delete' the journal is kept and a replay resets at the same place.  A running server does not see a reset journaled by another process until the actor is resurrected - reset through the API of the server instead.")]
+        path: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to journal the reset to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+    },
+    Prune {
+        #[arg(action = clap::ArgAction::Set, help = "the namespace whose journal to prune", long_help = "Only the observations of the paths in the namespace are pruned, ie: the paths that start with '/actors/' for the 'actors' namespace.")]
+        namespace: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to prune", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Remove observations that arrived longer ago than this", long_help = "A whole number of seconds, minutes, hours, days, or weeks, ie: '90s', '15m', '12h', '30d', or '2w'.  The age of an observation is the time it was journaled.")]
+        older_than: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Keep this many of the newest observations of every path", long_help = "The newest 'keep-last' observations of a path are kept however old they are - an accumulator resumes from its most recent observation and a path pruned of all of them is resurrected empty.", default_value = "1")]
+        keep_last: usize,
+    },
+    Compact {
+        #[arg(action = clap::ArgAction::Set, help = "the namespace whose journal to compact", long_help = "Only the paths in the namespace whose gene keeps all it computed in its state are compacted, ie: accumulators and gauges - a gene that keeps a bucket for every day is left alone.")]
+        namespace: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to compact", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Collapse the observations taken before this ISO 8601 datetime", long_help = "The observations of a path up to its first one taken at or after 'before' are replayed into the state they add up to, which is kept as the snapshot of the path in place of them, so that the path resurrects to the same state from fewer rows.")]
+        before: String,
+    },
+    Stats {
+        #[arg(action = clap::ArgAction::Set, required_unless_present = "resident", help = "the namespace to count", long_help = "Print the number of paths with journaled observations in the namespace, the number of their observations, when the first and the last of them were journaled, and the number of gene mappings of the namespace.")]
+        namespace: Option<String>,
+
+        #[arg(long = "runtime", action = clap::ArgAction::SetTrue, conflicts_with_all = ["namespace", "db_path"], help = "Count the actors a running server holds in memory", long_help = "With 'runtime' the number of actors the director of a running 'nv serve' holds in memory and the number of entries of their states are printed instead of the journal counts, read from the '/debug/director' endpoint at 'url'.")]
+        resident: bool,
+
+        #[arg(long, action = clap::ArgAction::Set, requires = "resident", help = "The server to count the actors of", long_help = "The base url of the 'nv serve' whose actors 'runtime' counts.", default_value = "http://127.0.0.1:8800")]
+        url: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to count", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long, value_enum, action = clap::ArgAction::Set, help = "Format of the printed counts", long_help = "'text' prints one count per line for people to read and 'json' prints the counts as one NDJSON line for tools like jq.", default_value = "text")]
+        format: OutputFormat,
+    },
+    Export {
+        #[arg(action = clap::ArgAction::Set, help = "the namespace to export")]
+        namespace: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to export", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The NDJSON file to write", long_help = "Every gene mapping of the namespace and then every journaled observation of its paths in the order they were journaled, one json object per line.")]
+        out: String,
+    },
+    Import {
+        #[arg(action = clap::ArgAction::Set, help = "the namespace to import into")]
+        namespace: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to import into", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long = "in", action = clap::ArgAction::Set, help = "The NDJSON file of an export to read", long_help = "Every line is applied like an update - observations the genes reject and observations already journaled are not imported.")]
+        input: String,
     },
     Explain {
         #[arg(action = clap::ArgAction::Set, help = "show all the genes possible for a path and its children")]
         path: String,
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to read the gene mappings from", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
     },
     Configure {
         #[arg(action = clap::ArgAction::Set, help = "the pattern to apply the gene to")]
         path: String,
         #[arg(value_enum, action = clap::ArgAction::Set, help = "the gene to apply to every actor in path")]
         gene: GeneType,
+        #[arg(long, action = clap::ArgAction::Set, help = "gene params as JSON", long_help = "Override the gene defaults, ie: '{\"guage_slots\": 10, \"accumulator_first_idx\": 10}' for the GaugeAndAccum gene, '{\"alpha\": 0.2}' for the Ewma gene, '{\"window\": 10}' for the MovingAvg gene, '{\"aggregate\": \"mean\"}' for the Aggregate gene, '{\"thresholds\": {\"1\": {\"low\": 0.0, \"high\": 80.0}}}' for the Alert gene, '{\"ranges\": [{\"first_idx\": 0, \"slots\": 10, \"operator\": \"gauge\"}, {\"first_idx\": 10, \"slots\": 10, \"operator\": \"accum\"}]}' for the Composite gene,, '{\"source_idx\": 1, \"delta_idx\": 101}' for the Delta gene, or '{\"time_scope\": \"hour\"}' for the Accum gene.  Params are validated and persisted with the mapping.")]
+        params: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "validate and print the configuration without persisting it")]
+        dry_run: bool,
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to persist the gene mapping to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace of the path.")]
+        db_path: Option<String>,
+    },
+    Label {
+        #[arg(action = clap::ArgAction::Set, help = "the namespace of the index")]
+        namespace: String,
+        #[arg(action = clap::ArgAction::Set, allow_negative_numbers = true, help = "the index to name")]
+        idx: i32,
+        #[arg(action = clap::ArgAction::Set, help = "the name printed in place of the index", long_help = "The name printed in place of the index, ie: 'temperature_c', in the state that 'update' and 'inspect' print and in the 'labeled' values of the API when asked for with 'labels=true'.  The state is still kept and journaled by index.  A later label of the same index replaces it.")]
+        name: String,
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to persist the label to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is the file in the working directory named after the namespace.")]
+        db_path: Option<String>,
     },
     Completions {
-        #[arg(short, long, action = clap::ArgAction::Set, help = "print script for shell tab completion", long_help = "Pipe the output of this command to a file or to a shell program as appropriate for 'bash', or 'zsh', etc... install via 'nv completions -s zsh > /usr/local/share/zsh/site-functions/_nv'")]
-        shell: clap_complete::Shell,
+        #[arg(short, long, action = clap::ArgAction::Set, help = "print script for shell tab completion", long_help = "Pipe the output of this command to a file or to a shell program as appropriate for 'bash', or 'zsh', etc... install via 'nv completions -s zsh > /usr/local/share/zsh/site-functions/_nv'.  Without '--shell' the shell is taken from the SHELL environment variable.")]
+        shell: Option<clap_complete::Shell>,
     },
     Serve {
         #[arg(short, long, action = clap::ArgAction::Set, help = "server listener port", default_value = "8800")]
@@ -99,6 +316,9 @@ pub enum Commands {
         #[arg(short, long, action = clap::ArgAction::Set, long_help = "the director and db file to default to", default_value = "actors")]
         namespace: String,
 
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to journal to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is a file in the working directory named after the namespace with its levels joined by '_', ie: 'acme_sensors.db' for the '/acme/sensors' namespace.")]
+        db_path: Option<String>,
+
         #[arg(long, action = clap::ArgAction::Set, help = "API Spec UI path", default_value = "/")]
         uipath: Option<String>,
 
@@ -110,8 +330,131 @@ pub enum Commands {
 
         #[arg(long, action = clap::ArgAction::SetTrue, help = "Accept path+datetime collisions", long_help = "The journal stores and replays events in the order that they arrive but will ignore events that have a path and observation timestamp previously recorded - this is the best option for consistency and performance.  With 'disable-duplicate-detection' flag, the journal will accept observations regardless of the payload timestamp - this is good for testing and best for devices with unreliable notions of time.")]
         disable_duplicate_detection: Option<bool>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Reject observations older than this many seconds", long_help = "Observations may arrive out of order but one that is more than 'observation-window' seconds older than the latest observation already applied to its actor is rejected and not journaled.  Without the flag every observation is accepted regardless of its timestamp.")]
+        observation_window: Option<u64>,
+
+        #[arg(long, value_parser = positive_rate, help = "Reject observations of a path beyond this many per second", long_help = "Each path may send 'max-rate-per-path' observations per second, and a burst of a second's worth after a quiet period.  Observations beyond the rate are rejected before they are journaled so that a flooding device does not starve the other paths of the store.  Without the flag every observation is accepted.")]
+        max_rate_per_path: Option<f64>,
+
+        #[arg(long, value_parser = clap::value_parser!(u32).range(..=15), help = "Round observed values to this many decimal places", long_help = "The values of every observation are rounded to 'decimals' decimal places before they are journaled and applied, ie: 21.456789 is journaled as 21.46 with '--decimals 2', so that the journal stays compact.  Values that are not finite, ie: NaN, are always rejected.  Without the flag values are kept as observed.")]
+        decimals: Option<u32>,
+
+        #[arg(long, value_parser = default_gene, help = "The gene of paths without a gene mapping", long_help = "Every path that no gene mapping of its own or of its ancestors applies to gets the 'default-gene', ie: 'accum' for a namespace of counters, instead of a gauge.  The gene must work with its default params, so the aggregate, alert, and composite genes can only be mapped.")]
+        default_gene: Option<GeneType>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Snapshot an actor's state every this many observations", long_help = "Every time 'snapshot-interval' more observations are applied to an actor its state is persisted as a snapshot and a resurrection replays only the journal written after the latest snapshot.  Without the flag the whole journal is replayed.")]
+        snapshot_interval: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Keep at most this many actors in memory", long_help = "When more than 'max-resident-actors' actors are live the least recently used one is dropped from memory and is resurrected from the journal by the next observation or query of its path.  A subscription to a dropped actor ends.  Without the flag, or without a journal, every actor stays in memory.")]
+        max_resident_actors: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Milliseconds to gather the queries of a cold path before resurrecting it", long_help = "The first message to a path whose actor is not in memory waits 'resurrection-grace-ms' milliseconds for more queries of its state, ie: a burst of dashboards at startup, that then share one resurrection from the journal.  The resurrection is only abandoned once every one of the queries timed out.  Without the flag the resurrection starts at once and the queries queued behind it find the resurrected actor.")]
+        resurrection_grace_ms: Option<u64>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Replay at most this many journal events per actor", long_help = "An actor resurrected from a journal longer than 'max-replay-events' replays only the most recent events and a warning is logged.  A gauge stays accurate if the cap covers its latest readings but an accumulator under-reports everything it counted before the cap.  Without the flag the whole journal is replayed.")]
+        max_replay_events: Option<usize>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Pool at most this many connections to the SQLite journal", long_help = "Readers of a write-ahead-logging journal do not block each other or the writer so more connections serve more concurrent queries.  Ignored by a 'postgres://' journal.", default_value = "8")]
+        max_connections: u32,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Milliseconds to wait on a locked SQLite journal", long_help = "A statement that finds the SQLite journal locked by another connection retries for 'busy-timeout-ms' milliseconds before it fails with 'database is locked'.  Ignored by a 'postgres://' journal.", default_value = "5000")]
+        busy_timeout_ms: u64,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Try a write of observations this many times on a busy journal", long_help = "A write of observations that fails because the journal is busy or locked by another connection, ie: after 'busy-timeout-ms', is tried again up to 'max-write-attempts' times in all, waiting 10ms before the first retry and twice as long before each next one.  An observation the journal already holds, or any other error, is not tried again.  1 tries once.", default_value = "4")]
+        max_write_attempts: usize,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The SQLite 'synchronous' pragma: off, normal, full or extra", long_help = "How often SQLite syncs the journal to disk.  Without the flag it is 'normal' with write-ahead-logging, which can lose the last commits on a power loss but never corrupts the journal, and 'full' without it.  Ignored by a 'postgres://' journal.")]
+        synchronous: Option<SqliteSynchronous>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Abandon API requests after this many seconds", long_help = "An API request that the actors have not answered within 'request-timeout' seconds is answered with an error and the journal load of any actor being resurrected for it is cancelled.")]
+        request_timeout: Option<u64>,
+
+        #[arg(long, action = clap::ArgAction::Set, conflicts_with = "request_timeout", help = "Abandon API requests after this many milliseconds", long_help = "The same as 'request-timeout' for timeouts shorter than a second or not in whole seconds.")]
+        request_timeout_ms: Option<u64>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Seconds to remember the Idempotency-Key of a post", long_help = "A post of an observation with an 'Idempotency-Key' header that repeats the key of a post to the same path within 'idempotency-window' seconds is answered with the response to that post instead of being applied again, ie: when a client retries a post that timed out.  The keys are kept in memory and are not shared between servers.", default_value = "300")]
+        idempotency_window: u64,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Write the API spec to this file", long_help = "The OpenAPI spec of the server is written to 'spec-out' before the server starts listening and the server does not start if the file can not be written.  The spec is always served at '/api/spec.json'.")]
+        spec_out: Option<std::path::PathBuf>,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "Require this API key on every API request", long_help = "Every request to '/api' and '/ws' must carry the key as either 'X-API-Key: <key>' or 'Authorization: Bearer <key>' and is answered with a 401 otherwise.  The '/health', '/ready' and '/metrics' endpoints stay open.  Without the flag the API is not authenticated.")]
+        api_key: Option<String>,
     },
+    IngestMqtt {
+        #[arg(long, action = clap::ArgAction::Set, help = "MQTT broker 'host[:port]'", default_value = "localhost:1883")]
+        broker: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "MQTT topic filter to subscribe to", long_help = "Every message published to a topic matching the filter is one json observation of the actor whose path is the topic, ie: a message published to 'actors/one' updates '/actors/one'.")]
+        topic: String,
+
+        #[arg(short, long, action = clap::ArgAction::Set, long_help = "the director and db file to default to", default_value = "actors")]
+        namespace: String,
+
+        #[arg(long, action = clap::ArgAction::Set, help = "The db file to journal to", long_help = "The SQLite file, or 'postgres://' url, of the journal.  Without 'db-path' the journal is a file in the working directory named after the namespace with its levels joined by '_', ie: 'acme_sensors.db' for the '/acme/sensors' namespace.")]
+        db_path: Option<String>,
+
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Disable Write Ahead Logging", long_help = "Disable Write Ahead Logging (WAL) performance improvements for use cases with frequent writes")]
+        disable_wal: Option<bool>,
+    },
+}
+
+/// how `update` decodes the lines it reads from stdin
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Csv,
+    #[value(name = "lineprotocol")]
+    LineProtocol,
+}
+
+/// the number type `update` keeps the state of actors in
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueType {
+    #[default]
+    F64,
+    I64,
+}
+
+/// how `update` and `inspect` print the state of actors
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    /// one `path,datetime,idx,value` row per index of a state
+    Csv,
 }
 
 #[derive(Args, Debug)]
 struct NoArgs {}
+
+/// a rate limit is a positive number of observations per second
+fn positive_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!("`{s}` is not a positive number per second")),
+    }
+}
+
+/// a default gene applies to any path with its default params
+fn default_gene(s: &str) -> Result<GeneType, String> {
+    let gene_type = <GeneType as clap::ValueEnum>::from_str(s, true)?;
+    if gene_type == GeneType::Aggregate {
+        return Err(String::from(
+            "an aggregate gene only applies to a mapped path",
+        ));
+    }
+    configured_gene::<f64>(gene_type, &GeneParams::default()).map_err(|e| e.reason)?;
+    Ok(gene_type)
+}
+
+/// a mailbox high-water mark is a fraction of the mailbox size
+fn high_water_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(fraction),
+        _ => Err(format!("`{s}` is not a fraction in (0, 1]")),
+    }
+}
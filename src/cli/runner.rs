@@ -1,169 +1,514 @@
 use crate::actors::actor::Handle;
 use crate::actors::director;
+use crate::actors::director::DirectorOptions;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
+use crate::actors::message::DirectorInfo;
+use crate::actors::message::Envelope;
 use crate::actors::message::Message;
 use crate::actors::message::Message::EndOfStream;
 use crate::actors::message::MtHint;
+use crate::actors::sqlite_store::db_file_name;
 use crate::actors::store_actor_sqlite;
+use crate::actors::store_actor_sqlite::StoreOptions;
+use crate::actors::value::Value;
+use crate::cli::ifc::InputFormat;
+use crate::cli::ifc::OutputFormat;
+use crate::cli::ifc::ValueType;
+use crate::io::csv_decoder;
+use crate::io::csv_output_actor;
+use crate::io::dry_run_actor;
+use crate::io::export;
+use crate::io::file_actor;
 use crate::io::json_decoder;
+use crate::io::json_output_actor;
+use crate::io::line_protocol_decoder;
 use crate::io::net::api_server::serve;
 use crate::io::net::api_server::HttpServerConfig;
+use crate::io::net::mqtt_source;
+use crate::io::progress_actor;
 use crate::io::stdin_actor;
 use crate::io::stdout_actor;
+use crate::utils::nvtime;
 use clap::Command;
-use clap_complete::{generate, Generator};
+use clap_complete::{generate, Generator, Shell};
+use std::fmt;
 use std::io;
 use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
 use tracing::error;
 use tracing::trace;
 use tracing::warn;
 
+/// why a command failed - each kind exits the process with its own code so
+/// that scripts can tell a bad invocation from a broken database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// the arguments or configuration can not be used
+    Usage(String),
+    /// some of the input was rejected
+    Input(String),
+    /// reading, printing, or listening failed
+    Io(String),
+    /// the db can not be opened or written
+    Store(String),
+}
+
+impl CliError {
+    #[must_use]
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => 2,
+            Self::Input(_) => 3,
+            Self::Io(_) => 4,
+            Self::Store(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(reason) => write!(f, "usage error: {reason}"),
+            Self::Input(reason) => write!(f, "input error: {reason}"),
+            Self::Io(reason) => write!(f, "io error: {reason}"),
+            Self::Store(reason) => write!(f, "store error: {reason}"),
+        }
+    }
+}
+
+pub type CliResult = Result<(), CliError>;
+
+/// the `db_path` if given or else the db file named after the namespace
+fn db_location(namespace: &str, db_path: Option<String>) -> String {
+    db_path.unwrap_or_else(|| db_file_name(namespace))
+}
+
+/// the prefix of the paths of the namespace, ie: `/actors/` for `actors`
+fn namespace_prefix(namespace: &str) -> String {
+    match namespace.trim_matches('/') {
+        "" => String::from("/"),
+        namespace => format!("/{namespace}/"),
+    }
+}
+
+/// `memory_only` serves from state that is never journaled - no db file is
+/// opened and nothing is resurrected when the server restarts
+///
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the server can not listen
+#[allow(clippy::too_many_arguments)]
 pub fn run_serve(
     server_config: HttpServerConfig,
+    db_path: Option<String>,
     runtime: &Runtime,
     uipath: Option<String>,
     disable_ui: Option<bool>,
+    memory_only: OptionVariant,
     write_ahead_logging: OptionVariant,
     disable_dupe_detection: OptionVariant,
-) {
+    options: DirectorOptions,
+    store_options: StoreOptions,
+) -> CliResult {
     let result = run_async_serve(
         server_config,
+        db_path,
         uipath,
         disable_ui,
+        memory_only,
         write_ahead_logging,
         disable_dupe_detection,
+        options,
+        store_options,
     );
-    match runtime.block_on(result) {
-        Ok(_) => {}
-        Err(e) => {
-            error!("can not launch server: {e}");
-        }
-    }
+    runtime.block_on(result).map_err(|e| {
+        error!("can not launch server: {e}");
+        e
+    })
 }
 
-fn setup_server_actor(
-    db_file_prefix: String,
+async fn setup_server_actor(
+    db_url: String,
     namespace: &str,
+    memory_only: OptionVariant,
     write_ahead_logging: OptionVariant,
     disable_dupe_detection: OptionVariant,
-) -> Arc<Handle> {
-    let store_actor: Handle = store_actor_sqlite::new(
-        8,
-        db_file_prefix,
-        write_ahead_logging == OptionVariant::On,
-        disable_dupe_detection == OptionVariant::On,
-    );
+    options: DirectorOptions,
+    store_options: StoreOptions,
+) -> Result<Arc<Handle>, CliError> {
+    let store_actor = match memory_only {
+        OptionVariant::Off => Some(
+            store_actor_sqlite::try_new_from_url(
+                8,
+                db_url,
+                namespace,
+                write_ahead_logging == OptionVariant::On,
+                disable_dupe_detection == OptionVariant::On,
+                store_options,
+            )
+            .await
+            .map_err(|e| CliError::Store(e.reason))?,
+        ),
+        OptionVariant::On => None,
+    };
 
-    let director_with_persistence = director::new(namespace, 8, None, Some(store_actor));
+    let director_with_persistence =
+        director::new_with_options(namespace, 8, None, store_actor, options);
 
-    Arc::new(director_with_persistence)
+    Ok(Arc::new(director_with_persistence))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_async_serve(
     server_config: HttpServerConfig,
+    db_path: Option<String>,
     uipath: Option<String>,
     disable_ui: Option<bool>,
+    memory_only: OptionVariant,
     write_ahead_logging: OptionVariant,
     disable_dupe_detection: OptionVariant,
-) -> Result<(), String> {
+    options: DirectorOptions,
+    store_options: StoreOptions,
+) -> CliResult {
     let shared_handle: Arc<Handle> = setup_server_actor(
-        server_config.namespace.clone(),
+        db_location(&server_config.namespace, db_path),
         server_config.namespace.as_str(),
+        memory_only,
         write_ahead_logging,
         disable_dupe_detection,
-    );
+        options,
+        store_options,
+    )
+    .await?;
     match serve(shared_handle, server_config, uipath, disable_ui).await {
         Ok(()) => Ok(()),
-        e => {
+        Err(e) => {
             error!("{e:?}");
-            Err(format!("{e:?}"))
+            Err(CliError::Io(e.to_string()))
         }
     }
 }
 
+/// the actor that prints state in the `format` asked for
+fn output_actor<T: Value>(format: OutputFormat, bufsz: usize) -> Handle<T> {
+    match format {
+        OutputFormat::Text => stdout_actor::new_of_type(bufsz),
+        OutputFormat::Json => json_output_actor::new_of_type(bufsz),
+        OutputFormat::Csv => csv_output_actor::new_of_type(bufsz),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened,
+/// the input format does not decode the value type, or any of the
+/// observations read from stdin or the input file were not applied
+#[allow(clippy::too_many_arguments)]
 pub fn update(
     namespace: String,
+    db_path: Option<String>,
     bufsz: usize,
     runtime: &Runtime,
     silent: OptionVariant,
     memory_only: OptionVariant,
     write_ahead_logging: OptionVariant,
     disable_dupe_detection: OptionVariant,
-) {
-    let result = run_async_update(
+    options: DirectorOptions,
+    store_options: StoreOptions,
+    format: InputFormat,
+    field_index: Vec<String>,
+    output_format: OutputFormat,
+    input: Option<String>,
+    gzip: bool,
+    value_type: ValueType,
+    dry_run: bool,
+    progress: Option<Duration>,
+) -> CliResult {
+    let fields =
+        line_protocol_decoder::parse_field_indices(&field_index).map_err(CliError::Usage)?;
+    let run = UpdateRun {
         namespace,
+        db_path,
         bufsz,
         silent,
         memory_only,
         write_ahead_logging,
         disable_dupe_detection,
-    );
-    match runtime.block_on(result) {
-        Ok(_) => {}
-        Err(e) => {
-            error!("can not launch thread: {e}");
+        options,
+        store_options,
+        output_format,
+        input,
+        gzip,
+        dry_run,
+        progress,
+    };
+    let result = match (value_type, format) {
+        (ValueType::F64, format) => {
+            runtime.block_on(run_async_update(run, |director| match format {
+                InputFormat::Json => json_decoder::new(bufsz, director),
+                InputFormat::Csv => csv_decoder::new(bufsz, director),
+                InputFormat::LineProtocol => line_protocol_decoder::new(bufsz, director, fields),
+            }))
         }
-    }
+        (ValueType::I64, InputFormat::Json) => runtime
+            .block_on(run_async_update::<i64>(run, |director| {
+                json_decoder::new(bufsz, director)
+            })),
+        (ValueType::I64, _) => Err(CliError::Usage(String::from(
+            "only json input is decoded to i64 values",
+        ))),
+    };
+    result.map_err(|e| {
+        error!("update failed: {e}");
+        e
+    })
 }
 
-async fn run_async_update(
+/// the settings of an `update` that do not depend on its value type
+struct UpdateRun {
     namespace: String,
+    db_path: Option<String>,
     bufsz: usize,
     silent: OptionVariant,
     memory_only: OptionVariant,
     write_ahead_logging: OptionVariant,
     disable_dupe_detection: OptionVariant,
-) -> Result<(), String> {
-    let output = match silent {
-        OptionVariant::Off => Some(stdout_actor::new(bufsz)),
-        OptionVariant::On => None,
+    options: DirectorOptions,
+    store_options: StoreOptions,
+    output_format: OutputFormat,
+    input: Option<String>,
+    gzip: bool,
+    /// check the input without journaling or printing the state
+    dry_run: bool,
+    /// print the count of ingested observations this often instead of the state
+    progress: Option<Duration>,
+}
+
+/// apply the input to a director of `T` values through the decoder that
+/// `decoder` puts in front of it
+async fn run_async_update<T: Value>(
+    run: UpdateRun,
+    decoder: impl FnOnce(Handle<T>) -> Handle<T>,
+) -> CliResult {
+    let UpdateRun {
+        namespace,
+        db_path,
+        bufsz,
+        silent,
+        memory_only,
+        write_ahead_logging,
+        disable_dupe_detection,
+        options,
+        store_options,
+        output_format,
+        input,
+        gzip,
+        dry_run,
+        progress,
+    } = run;
+
+    let output = match (silent, dry_run, progress) {
+        (OptionVariant::Off, false, None) => Some(output_actor(output_format, bufsz)),
+        _ => None,
     };
 
-    let store_actor = match memory_only {
-        OptionVariant::Off => Some(store_actor_sqlite::new(
-            bufsz,
-            namespace.clone(),
-            write_ahead_logging == OptionVariant::On,
-            disable_dupe_detection == OptionVariant::On,
-        )),
-        OptionVariant::On => None,
+    let store_actor = match (memory_only, dry_run) {
+        (OptionVariant::Off, false) => Some(
+            store_actor_sqlite::try_new_from_url(
+                bufsz,
+                db_location(&namespace, db_path),
+                &namespace,
+                write_ahead_logging == OptionVariant::On,
+                disable_dupe_detection == OptionVariant::On,
+                store_options,
+            )
+            .await
+            .map_err(|e| CliError::Store(e.reason))?,
+        ),
+        _ => None,
     };
 
-    let director_w_persist = director::new(namespace.as_str(), bufsz, output, store_actor);
+    let director_w_persist =
+        director::new_of_type(namespace.as_str(), bufsz, output, store_actor, options);
 
-    let json_decoder_actor = json_decoder::new(bufsz, director_w_persist);
+    let checked = if dry_run {
+        dry_run_actor::new_of_type(bufsz, director_w_persist)
+    } else {
+        director_w_persist
+    };
+    let decoder_actor = match progress {
+        Some(period) => decoder(progress_actor::new_of_type(bufsz, checked, period)),
+        None => decoder(checked),
+    };
 
-    let input = stdin_actor::new(bufsz, json_decoder_actor);
+    let input = match input {
+        Some(path) => file_actor::new(bufsz, decoder_actor, path, gzip),
+        None => stdin_actor::new(bufsz, decoder_actor, gzip),
+    };
 
     match input.ask(Message::ReadAllCmd {}).await {
         Ok(EndOfStream {}) => {
             trace!("end of stream");
             Ok(())
         }
-        e => {
-            error!("{:?}", e);
-            Err("END and response: sucks.".to_string())
-        }
+        Err(e) => Err(CliError::Input(e.reason)),
+        Ok(m) => Err(CliError::Io(format!("unexpected end of stream: {m}"))),
     }
 }
 
-pub fn configure(path: String, gene_type: GeneType, bufsz: usize, runtime: &Runtime) {
-    let result = run_async_configure(path, gene_type, bufsz);
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the broker address is invalid,
+/// the store can not be opened, or the actors stop reading
+#[allow(clippy::too_many_arguments)]
+pub fn ingest_mqtt(
+    broker: &str,
+    topic: String,
+    namespace: String,
+    db_path: Option<String>,
+    bufsz: usize,
+    runtime: &Runtime,
+    memory_only: OptionVariant,
+    write_ahead_logging: OptionVariant,
+) -> CliResult {
+    let (host, port) = mqtt_source::parse_broker(broker).map_err(CliError::Usage)?;
+    let result = run_async_ingest_mqtt(
+        host,
+        port,
+        topic,
+        namespace,
+        db_path,
+        bufsz,
+        memory_only,
+        write_ahead_logging,
+    );
+    runtime.block_on(result).map_err(|e| {
+        error!("mqtt ingest failed: {e}");
+        e
+    })
+}
 
-    match runtime.block_on(result) {
-        Ok(_) => {}
-        Err(e) => {
-            error!("cannot launch thread: {e}");
-        }
+#[allow(clippy::too_many_arguments)]
+async fn run_async_ingest_mqtt(
+    host: String,
+    port: u16,
+    topic: String,
+    namespace: String,
+    db_path: Option<String>,
+    bufsz: usize,
+    memory_only: OptionVariant,
+    write_ahead_logging: OptionVariant,
+) -> CliResult {
+    let store_actor = match memory_only {
+        OptionVariant::Off => Some(
+            store_actor_sqlite::try_new_from_url(
+                bufsz,
+                db_location(&namespace, db_path),
+                &namespace,
+                write_ahead_logging == OptionVariant::On,
+                false,
+                StoreOptions::default(),
+            )
+            .await
+            .map_err(|e| CliError::Store(e.reason))?,
+        ),
+        OptionVariant::On => None,
+    };
+
+    let director_w_persist = director::new(namespace.as_str(), bufsz, None, store_actor);
+
+    let decoder_actor = json_decoder::new(bufsz, director_w_persist);
+
+    let client_id = format!("nv-{namespace}-{}", std::process::id());
+    let input = mqtt_source::new(bufsz, decoder_actor, &client_id, &host, port, topic);
+
+    // the source only answers once it can no longer forward what it reads
+    match input.ask(Message::ReadAllCmd {}).await {
+        Ok(m) => Err(CliError::Io(format!("unexpected end of ingest: {m}"))),
+        Err(e) => Err(CliError::Io(e.reason)),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the configuration is invalid
+/// or can not be persisted
+pub fn configure(
+    path: String,
+    db_path: Option<String>,
+    gene_type: GeneType,
+    params: Option<String>,
+    dry_run: bool,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    if dry_run {
+        return match resolve_configuration(&path, gene_type, params.as_deref()) {
+            Ok(resolved) => {
+                println!("{resolved} (dry run, not persisted)");
+                Ok(())
+            }
+            Err(e) => {
+                error!("invalid configuration: {e}");
+                Err(CliError::Usage(e))
+            }
+        };
+    }
+
+    let result = run_async_configure(path, db_path, gene_type, params, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("configure failed: {e}");
+        e
+    })
+}
+
+/// parse the params and validate the gene a configuration would create
+///
+/// # Errors
+///
+/// Returns the reason the configuration is invalid
+pub fn resolve_configuration(
+    path: &str,
+    gene_type: GeneType,
+    params: Option<&str>,
+) -> Result<String, String> {
+    let params = params
+        .map(GeneParams::parse)
+        .transpose()?
+        .unwrap_or_default();
+    director::configured_gene::<f64>(gene_type, &params).map_err(|e| e.reason)?;
+    if params.is_empty() {
+        Ok(format!("{path} -> {gene_type}"))
+    } else {
+        let params = serde_json::to_string(&params).map_err(|e| e.to_string())?;
+        Ok(format!("{path} -> {gene_type} {params}"))
     }
 }
 
 async fn run_async_configure(
     path: String,
+    db_path: Option<String>,
     gene_type: GeneType,
+    params: Option<String>,
     bufsz: usize,
-) -> Result<(), String> {
+) -> CliResult {
+    // never persist a mapping that would not validate
+    let resolved =
+        resolve_configuration(&path, gene_type, params.as_deref()).map_err(CliError::Usage)?;
+    let params = params
+        .as_deref()
+        .map(GeneParams::parse)
+        .transpose()
+        .map_err(CliError::Usage)?
+        .unwrap_or_default();
+
     let p = std::path::Path::new(&path);
     let ns = p
         .components()
@@ -172,54 +517,75 @@ async fn run_async_configure(
         .unwrap_or("unk");
     let output = stdout_actor::new(bufsz); // print state
 
-    let store_actor = store_actor_sqlite::new(bufsz, String::from(ns), false, false); // print state
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(ns, db_path),
+        ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
 
     let director = director::new(path.as_str(), bufsz, None, Some(store_actor));
 
-    let gene_type_str = match gene_type {
-        GeneType::Accum => "accum",
-        GeneType::Gauge => "gauge",
-        _ => "gauge_and_accum",
-    };
-
     match director
-        .ask(Message::Content {
-            path: Some(path),
-            text: String::from(gene_type_str),
-            hint: MtHint::GeneMapping,
+        .ask(Message::GeneMapping {
+            path: path.clone(),
+            gene_type,
+            params,
         })
         .await
     {
-        Ok(m) => match output.tell(m).await {
-            Ok(_) => {}
-            Err(e) => {
-                warn!("cannot tell {e}");
+        Ok(Message::GeneMapping { .. }) => {
+            let m = Message::Content {
+                path: Some(path),
+                text: resolved,
+                hint: MtHint::GeneMapping,
+            };
+            match output.tell(m).await {
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("cannot tell {e}");
+                }
             }
-        },
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to gene mapping: {m}"
+            )));
+        }
         Err(e) => {
-            error!("error {e}");
+            return Err(CliError::Store(e.reason));
         }
     }
 
     // send complete to keep the job running long enough to print the above
     match output.ask(EndOfStream {}).await {
         Ok(EndOfStream {}) => Ok(()),
-        _ => Err("END and response: sucks.".to_string()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
     }
 }
 
-pub fn explain(path: String, bufsz: usize, runtime: &Runtime) {
-    let result = run_async_explain(path, bufsz);
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+pub fn explain(
+    path: String,
+    db_path: Option<String>,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_explain(path, db_path, bufsz);
 
-    match runtime.block_on(result) {
-        Ok(_) => {}
-        Err(e) => {
-            error!("cannot launch thread: {e}");
-        }
-    }
+    runtime.block_on(result).map_err(|e| {
+        error!("explain failed: {e}");
+        e
+    })
 }
 
-async fn run_async_explain(path: String, bufsz: usize) -> Result<(), String> {
+async fn run_async_explain(path: String, db_path: Option<String>, bufsz: usize) -> CliResult {
     let p = std::path::Path::new(&path);
     let ns = p
         .components()
@@ -228,7 +594,16 @@ async fn run_async_explain(path: String, bufsz: usize) -> Result<(), String> {
         .unwrap_or("unk");
     let output = stdout_actor::new(bufsz); // print state
 
-    let store_actor = store_actor_sqlite::new(bufsz, String::from(ns), false, false); // print state
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(ns, db_path),
+        ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
 
     let director = director::new(path.as_str(), bufsz, None, Some(store_actor));
 
@@ -253,57 +628,938 @@ async fn run_async_explain(path: String, bufsz: usize) -> Result<(), String> {
     // send complete to keep the job running long enough to print the above
     match output.ask(EndOfStream {}).await {
         Ok(EndOfStream {}) => Ok(()),
-        _ => Err("END and response: sucks.".to_string()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
     }
 }
 
-pub fn inspect(path: String, bufsz: usize, runtime: &Runtime) {
-    let result = run_async_inspect(path, bufsz);
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if `from`, `to`, or `as_of` is
+/// not an ISO 8601 time or the store can not be opened
+#[allow(clippy::too_many_arguments)]
+pub fn inspect(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    offset: usize,
+    recursive: bool,
+    as_of: Option<String>,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let parse = |s: &str| {
+        nvtime::extract_datetime(s)
+            .map_err(|e| CliError::Usage(format!("'{s}' is not an ISO 8601 time: {}", e.reason)))
+    };
+    let hint = match (from, to, as_of) {
+        _ if recursive => MtHint::Descendants {
+            limit: usize::MAX,
+            offset: 0,
+        },
+        (_, _, Some(as_of)) => MtHint::StateAsOf {
+            datetime: parse(&as_of)?,
+        },
+        (Some(from), Some(to), None) => MtHint::History {
+            from: parse(&from)?,
+            to: parse(&to)?,
+            limit: limit.unwrap_or(usize::MAX),
+            offset,
+        },
+        _ => MtHint::State,
+    };
+    let result = run_async_inspect(path, db_path, format, hint, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("inspect failed: {e}");
+        e
+    })
+}
+
+async fn run_async_inspect(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    hint: MtHint,
+    bufsz: usize,
+) -> CliResult {
+    let p = std::path::Path::new(&path);
+    let ns = p
+        .components()
+        .find(|c| *c != std::path::Component::RootDir)
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or("unk");
+    trace!("inspect of ns {ns}");
+    let output = output_actor(format, bufsz); // print state
 
-    match runtime.block_on(result) {
-        Ok(_) => {}
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(ns, db_path),
+        ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let director = director::new(path.as_str(), bufsz, None, Some(store_actor));
+
+    // the state is printed keyed by the index labels of the namespace
+    let query = Message::Query {
+        path: path.clone(),
+        hint: MtHint::IndexLabels,
+    };
+    if let Ok(labels @ Message::IndexLabels { .. }) = director.ask(query).await {
+        output
+            .tell(labels)
+            .await
+            .map_err(|e| CliError::Io(e.reason))?;
+    }
+
+    match director.ask(Message::Query { path, hint }).await {
+        Ok(m) => match output.tell(m).await {
+            Ok(_) => {}
+            Err(e) => {
+                warn!("cannot tell {e}");
+            }
+        },
         Err(e) => {
-            error!("cannot launch thread: {e}");
+            error!("error {e}");
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the state of the path can not be read from it
+pub fn watch(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    interval: Duration,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let stop = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => trace!("watch interrupted"),
+            Err(e) => {
+                error!("cannot listen for ctrl-c: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    let result = watch_until(path, db_path, format, interval, bufsz, stop);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("watch failed: {e}");
+        e
+    })
+}
+
+/// print the state of `path` and then print it again every time it changed
+/// since the last of the polls `interval` apart, until `stop` completes.
+/// the writer of the journal, ie: `nv serve`, is another process so every
+/// poll resurrects the actor from the journal as it is at the time.
+///
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the state of the path can not be read from it
+pub async fn watch_until(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    interval: Duration,
+    bufsz: usize,
+    stop: impl std::future::Future<Output = ()>,
+) -> CliResult {
+    let p = std::path::Path::new(&path);
+    let ns = p
+        .components()
+        .find(|c| *c != std::path::Component::RootDir)
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or("unk")
+        .to_string();
+    trace!("watch of ns {ns}");
+    let db_url = db_location(&ns, db_path);
+    let output = output_actor(format, bufsz); // print state
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tokio::pin!(stop);
+    let mut last = None;
+    loop {
+        tokio::select! {
+            () = &mut stop => break,
+            _ = ticker.tick() => {
+                let report = poll_state(&db_url, &ns, &path, bufsz).await?;
+                let Message::StateReport { datetime, values, .. } = &report else {
+                    return Err(CliError::Store(format!("unexpected state of {path}: {report}")));
+                };
+                let state = Some((*datetime, values.clone()));
+                if state != last {
+                    last = state;
+                    output
+                        .tell(report)
+                        .await
+                        .map_err(|e| CliError::Io(e.reason))?;
+                }
+            }
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// the state of `path` as its journal has it now
+async fn poll_state(
+    db_url: &str,
+    ns: &str,
+    path: &str,
+    bufsz: usize,
+) -> Result<Message<f64>, CliError> {
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        String::from(db_url),
+        ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let director = director::new(ns, bufsz, None, Some(store_actor));
+
+    director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+        .map_err(|e| CliError::Store(e.reason))
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the journal of the path can not be replayed
+pub fn replay(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_replay(path, db_path, format, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("replay failed: {e}");
+        e
+    })
+}
+
+/// print the state of `path` after every event of its journal
+async fn run_async_replay(
+    path: String,
+    db_path: Option<String>,
+    format: OutputFormat,
+    bufsz: usize,
+) -> CliResult {
+    let p = std::path::Path::new(&path);
+    let ns = p
+        .components()
+        .find(|c| *c != std::path::Component::RootDir)
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or("unk")
+        .to_string();
+    trace!("replay of ns {ns}");
+    let db_url = db_location(&ns, db_path);
+    let output = output_actor(format, bufsz); // print each step
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        &ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let director = director::new(&ns, bufsz, None, Some(store_actor));
+
+    match director
+        .ask(Message::Query {
+            path: path.clone(),
+            hint: MtHint::Replay,
+        })
+        .await
+    {
+        Ok(m @ Message::StateReports { .. }) => {
+            output.tell(m).await.map_err(|e| CliError::Io(e.reason))?;
         }
+        Ok(Message::NotFound { .. }) => {
+            return Err(CliError::Input(format!("no journal for {path}")));
+        }
+        Ok(m) => return Err(CliError::Store(format!("unexpected replay of {path}: {m}"))),
+        Err(e) => return Err(CliError::Store(e.reason)),
     }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the delete can not be persisted
+pub fn delete(
+    path: String,
+    db_path: Option<String>,
+    mapping: bool,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_delete(path, db_path, mapping, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("delete failed: {e}");
+        e
+    })
 }
 
-async fn run_async_inspect(path: String, bufsz: usize) -> Result<(), String> {
+async fn run_async_delete(
+    path: String,
+    db_path: Option<String>,
+    mapping: bool,
+    bufsz: usize,
+) -> CliResult {
     let p = std::path::Path::new(&path);
     let ns = p
         .components()
         .find(|c| *c != std::path::Component::RootDir)
         .and_then(|c| c.as_os_str().to_str())
         .unwrap_or("unk");
-    trace!("inspect of ns {ns}");
     let output = stdout_actor::new(bufsz); // print state
 
-    let store_actor = store_actor_sqlite::new(bufsz, String::from(ns), false, false); // print state
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(ns, db_path),
+        ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
 
     let director = director::new(path.as_str(), bufsz, None, Some(store_actor));
 
     match director
-        .ask(Message::Query {
-            path,
-            hint: MtHint::State,
+        .ask(Message::Delete {
+            path: path.clone(),
+            mapping,
         })
         .await
     {
-        Ok(m) => match output.tell(m).await {
-            Ok(_) => {}
+        Ok(Message::Delete { .. }) => {
+            let m = Message::Content {
+                path: Some(path.clone()),
+                text: format!("{path} deleted"),
+                hint: MtHint::Update,
+            };
+            match output.tell(m).await {
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("cannot tell {e}");
+                }
+            }
+        }
+        Ok(Message::NotFound { .. }) => {
+            trace!("nothing to delete for {path}");
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to delete: {m}"
+            )));
+        }
+        Err(e) => {
+            return Err(CliError::Store(e.reason));
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened,
+/// the path has no journal, or the reset can not be persisted
+pub fn reset(path: String, db_path: Option<String>, bufsz: usize, runtime: &Runtime) -> CliResult {
+    let result = run_async_reset(path, db_path, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("reset failed: {e}");
+        e
+    })
+}
+
+async fn run_async_reset(path: String, db_path: Option<String>, bufsz: usize) -> CliResult {
+    let p = std::path::Path::new(&path);
+    let ns = p
+        .components()
+        .find(|c| *c != std::path::Component::RootDir)
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or("unk")
+        .to_string();
+    let output = stdout_actor::new(bufsz);
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(&ns, db_path),
+        &ns,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    // the gene mappings of the namespace resurrect the actor with its gene
+    let director = director::new(&ns, bufsz, None, Some(store_actor));
+
+    match director.ask(Message::Reset { path: path.clone() }).await {
+        Ok(Message::StateReport { .. }) => {
+            let m = Message::Content {
+                path: Some(path.clone()),
+                text: format!("{path} reset"),
+                hint: MtHint::Update,
+            };
+            output.tell(m).await.map_err(|e| CliError::Io(e.reason))?;
+        }
+        Ok(Message::NotFound { .. }) => {
+            return Err(CliError::Input(format!("no journal for {path}")));
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to reset: {m}"
+            )));
+        }
+        Err(e) => return Err(CliError::Store(e.reason)),
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened or
+/// the label can not be persisted
+pub fn label(
+    namespace: &str,
+    idx: i32,
+    name: String,
+    db_path: Option<String>,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    if name.trim().is_empty() {
+        return Err(CliError::Usage(format!(
+            "the label of index {idx} is empty"
+        )));
+    }
+    let namespace = namespace.trim_matches('/');
+    let result = run_async_label(namespace, idx, name, db_location(namespace, db_path), bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("label failed: {e}");
+        e
+    })
+}
+
+async fn run_async_label(
+    namespace: &str,
+    idx: i32,
+    name: String,
+    db_url: String,
+    bufsz: usize,
+) -> CliResult {
+    let output = stdout_actor::new(bufsz);
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        namespace,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let director = director::new(namespace, bufsz, None, Some(store_actor));
+
+    match director.ask(Message::IndexLabel { idx, name }).await {
+        Ok(Message::IndexLabel { idx, name }) => {
+            let m = Message::Content {
+                path: None,
+                text: format!("{idx} -> {name}"),
+                hint: MtHint::Update,
+            };
+            output.tell(m).await.map_err(|e| CliError::Io(e.reason))?;
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to label: {m}"
+            )));
+        }
+        Err(e) => return Err(CliError::Store(e.reason)),
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the datetime is not valid or
+/// the store can not be opened or compacted
+pub fn compact(
+    namespace: String,
+    db_path: Option<String>,
+    before: &str,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let before = nvtime::extract_datetime(before)
+        .map_err(|e| CliError::Usage(format!("bad datetime '{before}': {e}")))?;
+    let result = run_async_compact(db_location(&namespace, db_path), namespace, before, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("compact failed: {e}");
+        e
+    })
+}
+
+async fn run_async_compact(
+    db_url: String,
+    namespace: String,
+    before: OffsetDateTime,
+    bufsz: usize,
+) -> CliResult {
+    let output = stdout_actor::new(bufsz);
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        &namespace,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    // the gene mappings of the namespace replay each path with its gene
+    let director = director::new(&namespace, bufsz, None, Some(store_actor));
+
+    match director
+        .ask(Message::Compact {
+            path: namespace_prefix(&namespace),
+            before,
+        })
+        .await
+    {
+        Ok(Message::Compacted { paths, rows }) => {
+            let m = Message::Content {
+                path: None,
+                text: format!("compacted {rows} observations of {paths} paths"),
+                hint: MtHint::Update,
+            };
+            output.tell(m).await.map_err(|e| CliError::Io(e.reason))?;
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to compact: {m}"
+            )));
+        }
+        Err(e) => {
+            return Err(CliError::Store(e.reason));
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or counted
+pub fn stats(
+    namespace: &str,
+    db_path: Option<String>,
+    format: OutputFormat,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_stats(
+        db_location(namespace, db_path),
+        namespace_prefix(namespace),
+        format,
+        bufsz,
+    );
+
+    runtime.block_on(result).map_err(|e| {
+        error!("stats failed: {e}");
+        e
+    })
+}
+
+async fn run_async_stats(
+    db_url: String,
+    prefix: String,
+    format: OutputFormat,
+    bufsz: usize,
+) -> CliResult {
+    let output = output_actor(format, bufsz);
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        &prefix,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    match store_actor.ask(Message::Stats { path: prefix }).await {
+        Ok(m @ Message::StatsReport { .. }) => match output.tell(m).await {
+            Ok(()) => {}
             Err(e) => {
                 warn!("cannot tell {e}");
             }
         },
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to stats: {m}"
+            )));
+        }
         Err(e) => {
-            error!("error {e}");
+            return Err(CliError::Store(e.reason));
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the server at `url` can not be
+/// reached or does not answer with the counts of its director
+pub fn resident_stats(
+    url: &str,
+    format: OutputFormat,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_resident_stats(url, format, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("stats failed: {e}");
+        e
+    })
+}
+
+async fn run_async_resident_stats(url: &str, format: OutputFormat, bufsz: usize) -> CliResult {
+    let url = format!("{}/debug/director", url.trim_end_matches('/'));
+    let info: DirectorInfo = reqwest::get(&url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| CliError::Io(format!("cannot get {url}: {e}")))?
+        .json()
+        .await
+        .map_err(|e| CliError::Io(format!("cannot read {url}: {e}")))?;
+
+    let output: Handle = output_actor(format, bufsz);
+    if let Err(e) = output.tell(Message::DirectorInfoReport { info }).await {
+        warn!("cannot tell {e}");
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the duration is not valid or
+/// the store can not be opened or pruned
+pub fn prune(
+    namespace: &str,
+    db_path: Option<String>,
+    older_than: &str,
+    keep_last: usize,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let age = nvtime::parse_duration(older_than).map_err(|e| CliError::Usage(e.reason))?;
+    let before = OffsetDateTime::now_utc() - age;
+    let result = run_async_prune(
+        db_location(namespace, db_path),
+        namespace_prefix(namespace),
+        before,
+        keep_last,
+        bufsz,
+    );
+
+    runtime.block_on(result).map_err(|e| {
+        error!("prune failed: {e}");
+        e
+    })
+}
+
+async fn run_async_prune(
+    db_url: String,
+    prefix: String,
+    before: OffsetDateTime,
+    keep_last: usize,
+    bufsz: usize,
+) -> CliResult {
+    let output = stdout_actor::new(bufsz);
+
+    let store_actor: Handle = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        &prefix,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    match store_actor
+        .ask(Message::Prune {
+            path: prefix,
+            before,
+            keep_last,
+        })
+        .await
+    {
+        Ok(Message::Pruned { rows }) => {
+            let m = Message::Content {
+                path: None,
+                text: format!("pruned {rows} observations"),
+                hint: MtHint::Update,
+            };
+            match output.tell(m).await {
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("cannot tell {e}");
+                }
+            }
+        }
+        Ok(m) => {
+            return Err(CliError::Store(format!(
+                "unexpected response to prune: {m}"
+            )));
+        }
+        Err(e) => {
+            return Err(CliError::Store(e.reason));
         }
     }
 
     // send complete to keep the job running long enough to print the above
     match output.ask(EndOfStream {}).await {
         Ok(EndOfStream {}) => Ok(()),
-        _ => Err("END and response: sucks.".to_string()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or the export file can not be written
+pub fn export(
+    namespace: &str,
+    db_path: Option<String>,
+    out: String,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_export(
+        db_location(namespace, db_path),
+        namespace_prefix(namespace),
+        out,
+        bufsz,
+    );
+
+    runtime.block_on(result).map_err(|e| {
+        error!("export failed: {e}");
+        e
+    })
+}
+
+async fn run_async_export(db_url: String, prefix: String, out: String, bufsz: usize) -> CliResult {
+    let output = stdout_actor::new(bufsz);
+
+    let store_actor = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_url,
+        &prefix,
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let file = tokio::fs::File::create(&out)
+        .await
+        .map_err(|e| CliError::Io(format!("cannot create {out}: {e}")))?;
+    let mut writer = tokio::io::BufWriter::new(file);
+
+    let (stream_to, mut stream_from) = mpsc::channel(bufsz);
+    store_actor
+        .send(Envelope {
+            message: Message::LoadCmd {
+                path: prefix,
+                hint: MtHint::Export,
+            },
+            stream_to: Some(stream_to),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| CliError::Store(e.reason))?;
+
+    let mut exported = 0;
+    while let Some(message) = stream_from.recv().await {
+        if matches!(message, EndOfStream {}) {
+            break;
+        }
+        let line = export::export_line(&message).map_err(CliError::Io)?;
+        writer
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .map_err(|e| CliError::Io(format!("cannot write {out}: {e}")))?;
+        exported += 1;
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|e| CliError::Io(format!("cannot write {out}: {e}")))?;
+
+    let m = Message::Content {
+        path: None,
+        text: format!("exported {exported} lines to {out}"),
+        hint: MtHint::Update,
+    };
+    match output.tell(m).await {
+        Ok(_) => {}
+        Err(e) => {
+            warn!("cannot tell {e}");
+        }
+    }
+
+    // send complete to keep the job running long enough to print the above
+    match output.ask(EndOfStream {}).await {
+        Ok(EndOfStream {}) => Ok(()),
+        _ => Err(CliError::Io("cannot complete output".to_string())),
+    }
+}
+
+/// # Errors
+///
+/// Returns [`CliError`](enum.CliError.html) if the store can not be opened
+/// or any of the lines of the export file were not applied
+pub fn import(
+    namespace: String,
+    db_path: Option<String>,
+    input: String,
+    bufsz: usize,
+    runtime: &Runtime,
+) -> CliResult {
+    let result = run_async_import(namespace, db_path, input, bufsz);
+
+    runtime.block_on(result).map_err(|e| {
+        error!("import failed: {e}");
+        e
+    })
+}
+
+async fn run_async_import(
+    namespace: String,
+    db_path: Option<String>,
+    input: String,
+    bufsz: usize,
+) -> CliResult {
+    let store_actor = store_actor_sqlite::try_new_from_url(
+        bufsz,
+        db_location(&namespace, db_path),
+        &namespace,
+        true,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .map_err(|e| CliError::Store(e.reason))?;
+
+    let director_w_persist = director::new(namespace.as_str(), bufsz, None, Some(store_actor));
+
+    let decoder_actor = export::new(bufsz, director_w_persist);
+
+    let input = file_actor::new(bufsz, decoder_actor, input, false);
+
+    match input.ask(Message::ReadAllCmd {}).await {
+        Ok(EndOfStream {}) => {
+            trace!("end of import");
+            Ok(())
+        }
+        Err(e) => Err(CliError::Input(e.reason)),
+        Ok(m) => Err(CliError::Io(format!("unexpected end of import: {m}"))),
     }
 }
 
@@ -313,6 +1569,23 @@ pub enum OptionVariant {
     Off,
 }
 
+/// an explicit `--shell` wins, otherwise the shell is inferred from the
+/// value of `SHELL`, ie: `/bin/zsh` is `zsh`
+///
+/// # Errors
+///
+/// Returns an error naming the `SHELL` value if neither identifies a shell
+pub fn completions_shell(shell: Option<Shell>, shell_env: Option<String>) -> Result<Shell, String> {
+    shell.map_or_else(
+        || match shell_env {
+            Some(env) => Shell::from_shell_path(&env)
+                .ok_or_else(|| format!("unsupported SHELL '{env}', use --shell")),
+            None => Err("SHELL is not set, use --shell".to_string()),
+        },
+        Ok,
+    )
+}
+
 pub fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
     generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
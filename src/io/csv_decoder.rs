@@ -0,0 +1,233 @@
+//! only used to support the cli ifc and `stdin_actor` - the CSV counterpart of the `json_decoder`
+//! for legacy data with one reading per row: `path,datetime,idx,value`.
+//!
+//! Consecutive rows with the same path and datetime are one observation and are forwarded as a
+//! single `Observations` message once a row for another observation, or the end of the stream,
+//! arrives. An optional `path,datetime,idx,value` header line and blank lines are skipped. A row
+//! that can not be parsed is reported and counted, the rest of the stream is still decoded.
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::message::Envelope;
+use crate::actors::message::Labels;
+use crate::actors::message::Message;
+use crate::actors::message::NvError;
+use crate::actors::message::NvResult;
+use crate::utils::nvtime::extract_datetime;
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::trace;
+
+/// the optional header line, the one the `csv_output_actor` prints
+pub const HEADER: &str = "path,datetime,idx,value";
+
+/// one row of the input
+#[derive(Debug, PartialEq)]
+pub struct Reading {
+    pub path: String,
+    pub datetime: OffsetDateTime,
+    pub idx: i32,
+    pub value: f64,
+}
+
+/// the rows read so far of the observation that is not forwarded yet
+struct Pending {
+    path: String,
+    datetime: OffsetDateTime,
+    values: State<f64>,
+    /// the envelope datetime of the first row, the journal sequence
+    sequence: OffsetDateTime,
+}
+
+pub struct CsvDecoder {
+    pub receiver: mpsc::Receiver<Envelope<f64>>,
+    pub output: Handle,
+    pending: Option<Pending>,
+    /// lines of the current stream that could not be decoded
+    undecodable: usize,
+}
+
+/// parse one `path,datetime,idx,value` row
+///
+/// # Errors
+///
+/// Returns a description of the first field that can not be parsed
+pub fn parse_row(text: &str) -> Result<Reading, String> {
+    let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+    let [path, datetime, idx, value] = fields[..] else {
+        return Err(format!("expected 4 fields but found {}", fields.len()));
+    };
+    if path.is_empty() {
+        return Err(String::from("empty path"));
+    }
+    let datetime = extract_datetime(datetime).map_err(|e| format!("bad datetime: {e}"))?;
+    let idx = idx.parse().map_err(|e| format!("bad idx '{idx}': {e}"))?;
+    let value = value
+        .parse()
+        .map_err(|e| format!("bad value '{value}': {e}"))?;
+    Ok(Reading {
+        path: path.to_string(),
+        datetime,
+        idx,
+        value,
+    })
+}
+
+#[async_trait]
+impl Actor for CsvDecoder {
+    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+        let Envelope {
+            message,
+            respond_to,
+            datetime,
+            ..
+        } = envelope;
+        match message {
+            Message::Content { text, .. } => self.handle_row(&text, respond_to, datetime).await,
+            Message::EndOfStream {} => {
+                self.flush().await;
+                if self.undecodable > 0 {
+                    // let the rest of the pipeline finish before reporting
+                    self.output
+                        .ask(Message::EndOfStream {})
+                        .await
+                        .map_err(|e| error!("cannot complete stream: {e}"))
+                        .ok();
+                    let reason = format!("{} lines could not be decoded", self.undecodable);
+                    self.undecodable = 0;
+                    respond_or_log_error(respond_to, Err(NvError { reason }));
+                } else {
+                    let senv = Envelope {
+                        message: Message::EndOfStream {},
+                        respond_to,
+                        ..Default::default()
+                    };
+                    self.send_or_log_error(senv).await;
+                }
+            }
+            m => {
+                self.flush().await;
+                let senv = Envelope {
+                    message: m,
+                    respond_to,
+                    ..Default::default()
+                };
+                self.send_or_log_error(senv).await;
+            }
+        }
+    }
+
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+
+    async fn start(&mut self) {}
+}
+
+impl CsvDecoder {
+    async fn handle_row(
+        &mut self,
+        text: &str,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        datetime: OffsetDateTime,
+    ) {
+        let text = text.trim();
+        if text.is_empty() || text.eq_ignore_ascii_case(HEADER) {
+            return;
+        }
+        match parse_row(text) {
+            Ok(reading) => {
+                trace!("csv parsed");
+                let same_observation = self.pending.as_ref().is_some_and(|pending| {
+                    pending.path == reading.path && pending.datetime == reading.datetime
+                });
+                if !same_observation {
+                    self.flush().await;
+                }
+                let pending = self.pending.get_or_insert_with(|| Pending {
+                    path: reading.path,
+                    datetime: reading.datetime,
+                    values: State::new(),
+                    sequence: datetime,
+                });
+                pending.values.insert(reading.idx, reading.value);
+            }
+            Err(error) => {
+                error!("error processing update: {error}");
+                self.undecodable += 1;
+                respond_or_log_error(
+                    respond_to,
+                    Err(NvError {
+                        reason: format!("csv parse error: {error}"),
+                    }),
+                );
+            }
+        }
+    }
+
+    /// forward the observation collected so far, if any
+    async fn flush(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            let senv = Envelope {
+                message: Message::Observations {
+                    path: pending.path,
+                    datetime: pending.datetime,
+                    values: pending.values,
+                    labels: Labels::new(),
+                },
+                datetime: pending.sequence,
+                ..Default::default()
+            };
+            self.send_or_log_error(senv).await;
+        }
+    }
+
+    async fn send_or_log_error(&self, envelope: Envelope<f64>)
+    where
+        Envelope<f64>: Send + std::fmt::Debug,
+    {
+        match self.output.send(envelope).await {
+            Ok(_) => (),
+            Err(e) => error!("cannot send: {:?}", e),
+        }
+    }
+
+    /// actor private constructor
+    const fn new(receiver: mpsc::Receiver<Envelope<f64>>, output: Handle) -> Self {
+        Self {
+            receiver,
+            output,
+            pending: None,
+            undecodable: 0,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize, output: Handle) -> Handle {
+    async fn start(mut actor: CsvDecoder) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = CsvDecoder::new(receiver, output);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
@@ -0,0 +1,205 @@
+//!This module is the spreadsheet friendly alternative to the `stdout_actor`.
+//!
+//!Each `StateReport` it receives is printed to the standard output as one `path,datetime,idx,value`
+//!row per index of its state, ie: `/actors/one,2023-01-11T23:17:57.000000000Z,1,1.5`, after a
+//!header row that is printed once.  The datetime is in ISO 8601 and the value is printed the same
+//!way whatever the locale so that the rows read back with `update --format csv`.  A path with a
+//!comma, a quote, or a line break is quoted.  Each observation of a `History` is printed the same
+//!way with the number of observations of all its pages on `stderr` like the `stdout_actor` does.
+//!Like the `stdout_actor`, it prints `Content` text as is and the reason an update was rejected
+//!and the `StreamSummary` of an update to `stderr`, and it answers `EndOfStream` once everything
+//!before it is printed.  Stats and alerts have no rows and are only logged.
+
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::message::Envelope;
+use crate::actors::message::Message;
+use crate::actors::value::Value;
+use crate::io::csv_decoder::HEADER;
+use crate::io::stdout_actor::print_page_of;
+use async_trait::async_trait;
+use std::borrow::Cow;
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::warn;
+
+pub struct CsvOutputActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    header_printed: bool,
+}
+
+/// a field of a row - quoted, with its quotes doubled, when it has a comma, a
+/// quote, or a line break
+fn field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// the CSV rows of a state report in index order - none for an empty state
+///
+/// # Errors
+///
+/// Returns a description of the error if the datetime can not be formatted
+pub fn state_report_rows<T: Value>(
+    path: &str,
+    datetime: OffsetDateTime,
+    values: &State<T>,
+) -> Result<Vec<String>, String> {
+    let datetime = datetime
+        .format(&Iso8601::DEFAULT)
+        .map_err(|e| e.to_string())?;
+    let path = field(path);
+    // `Display` of a number never depends on the locale
+    Ok(values
+        .iter()
+        .map(|(idx, value)| format!("{path},{datetime},{idx},{value}"))
+        .collect())
+}
+
+impl<T: Value> CsvOutputActor<T> {
+    /// print the rows of a state, after the header if they are the first
+    fn print_state(&mut self, path: &str, datetime: OffsetDateTime, values: &State<T>) {
+        match state_report_rows(path, datetime, values) {
+            Ok(rows) => {
+                for row in rows {
+                    if !self.header_printed {
+                        println!("{HEADER}");
+                        self.header_printed = true;
+                    }
+                    println!("{row}");
+                }
+            }
+            Err(e) => error!("cannot print state of {path} as csv: {e}"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Value> Actor<T> for CsvOutputActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        let Envelope {
+            message,
+            respond_to,
+            ..
+        } = envelope;
+
+        match &message {
+            Message::Content { text, .. } => println!("{text}"),
+            Message::StateReport {
+                path,
+                datetime,
+                values,
+                ..
+            } => {
+                self.print_state(path, *datetime, values);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::History {
+                path,
+                points,
+                total,
+            } => {
+                for (datetime, values) in points {
+                    self.print_state(path, *datetime, values);
+                }
+                print_page_of(path, points.len(), *total);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StateReports { reports, .. } => {
+                for report in reports {
+                    if let Message::StateReport {
+                        path,
+                        datetime,
+                        values,
+                        ..
+                    } = report
+                    {
+                        self.print_state(path, *datetime, values);
+                    }
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StatsReport { .. }
+            | Message::DirectorInfoReport { .. }
+            | Message::Alert { .. } => {
+                warn!("no csv rows for {message}");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            // the idx column stays the index so that the rows load back
+            Message::Observations { .. } | Message::IndexLabels { .. } => {
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Error { path, reason } => {
+                eprintln!("{path} error: {reason}");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StreamSummary {
+                ingested,
+                duplicates,
+            } => {
+                eprintln!("{ingested} observations ingested, {duplicates} duplicates skipped");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::EndOfStream {} => {
+                if let Some(respond_to) = respond_to {
+                    respond_to
+                        .send(Ok(Message::EndOfStream {}))
+                        .unwrap_or_else(|e| error!("cannot respond to ask: {e:?}"));
+                }
+            }
+            _ => {
+                warn!("unexpected: {message}");
+            }
+        }
+    }
+    async fn stop(&self) {}
+    async fn start(&mut self) {}
+}
+
+/// actor private constructor
+impl<T: Value> CsvOutputActor<T> {
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>) -> Self {
+        Self {
+            receiver,
+            header_printed: false,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize) -> Handle {
+    new_of_type(bufsz)
+}
+
+/// `new` for the state of a director of `T` values, ie: `i64`
+#[must_use]
+pub fn new_of_type<T: Value>(bufsz: usize) -> Handle<T> {
+    async fn start<T: Value>(mut actor: CsvOutputActor<T>) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = CsvOutputActor::new(receiver);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
@@ -0,0 +1,151 @@
+//!This module checks the input of an `update --dry-run` on its way to a director that has no
+//!journal and no output.
+//!
+//!The `DryRunActor` sits between the decoder and the director and passes every message on.  It
+//!counts the observations and the paths they are of, and at `EndOfStream` it asks the director for
+//!its gene mappings - the ones the input defined - and prints a summary with the paths that no
+//!mapping applies to before the `EndOfStream` is passed on.  Lines that could not be parsed are
+//!counted by the decoder in front of it, which fails the stream after the summary is printed.
+//!
+//!The module has a public constructor function `new` that returns a `Handle` to interact with the
+//!actor.
+
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::genes::gene::GeneType;
+use crate::actors::message::Envelope;
+use crate::actors::message::Message;
+use crate::actors::message::MtHint;
+use crate::actors::value::Value;
+use async_trait::async_trait;
+use std::collections::BTreeSet;
+use tokio::sync::mpsc;
+use tracing::error;
+
+pub struct DryRunActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Handle<T>,
+    /// observations of the current stream
+    observations: usize,
+    /// the paths of the observations of the current stream
+    paths: BTreeSet<String>,
+}
+
+#[async_trait]
+impl<T: Value> Actor<T> for DryRunActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        match &envelope.message {
+            Message::Observations { path, .. } => {
+                self.observations += 1;
+                self.paths.insert(path.clone());
+            }
+            Message::EndOfStream {} => {
+                self.print_summary().await;
+                self.observations = 0;
+                self.paths.clear();
+            }
+            _ => {}
+        }
+        if let Err(e) = self.output.send(envelope).await {
+            error!("cannot send: {e:?}");
+        }
+    }
+
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+
+    async fn start(&mut self) {}
+}
+
+impl<T: Value> DryRunActor<T> {
+    /// the paths of the stream that no gene mapping applies to - they would
+    /// get the default gene.  an aggregate mapping only applies to its own
+    /// path, not to the children it aggregates.
+    async fn unmapped(&self) -> Vec<&String> {
+        let mapped: Vec<(String, GeneType)> = match self
+            .output
+            .ask(Message::Query {
+                path: String::from("/"),
+                hint: MtHint::GeneMappingList,
+            })
+            .await
+        {
+            Ok(Message::GeneMappingList { mappings }) => mappings
+                .into_iter()
+                .map(|(path, gene_type, _)| (path, gene_type))
+                .collect(),
+            r => {
+                error!("cannot get the gene mappings: {r:?}");
+                vec![]
+            }
+        };
+        self.paths
+            .iter()
+            .filter(|path| {
+                !mapped.iter().any(|(mapping, gene_type)| {
+                    path.strip_prefix(mapping.trim_end_matches('/'))
+                        .is_some_and(|rest| {
+                            rest.is_empty()
+                                || (rest.starts_with('/') && *gene_type != GeneType::Aggregate)
+                        })
+                })
+            })
+            .collect()
+    }
+
+    async fn print_summary(&self) {
+        let unmapped = self.unmapped().await;
+        println!(
+            "dry run: {} observations of {} paths, nothing was journaled",
+            self.observations,
+            self.paths.len()
+        );
+        println!("dry run: {} paths have no gene mapping", unmapped.len());
+        for path in unmapped {
+            println!("dry run: {path} has no gene mapping");
+        }
+    }
+
+    /// actor private constructor
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>, output: Handle<T>) -> Self {
+        Self {
+            receiver,
+            output,
+            observations: 0,
+            paths: BTreeSet::new(),
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize, output: Handle) -> Handle {
+    new_of_type(bufsz, output)
+}
+
+/// `new` for a director of `T` values, ie: `i64`
+#[must_use]
+pub fn new_of_type<T: Value>(bufsz: usize, output: Handle<T>) -> Handle<T> {
+    async fn start<T: Value>(mut actor: DryRunActor<T>) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = DryRunActor::new(receiver, output);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
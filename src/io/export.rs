@@ -0,0 +1,233 @@
+//! the NDJSON format of `nv export` and the decoder `nv import` reads it back with.
+//!
+//! Every line of an export is either a gene mapping, the json of a `GeneMapping`, or an
+//! observation, the json of an `Observations` whose `datetime` is the journaled timestamp exactly
+//! as the store keeps it - the `OffsetDateTimeWrapper` number of seconds - so that an import
+//! journals the same timestamps:
+//! `{"datetime":"1673479077","values":{"1":1.5},"path":"/actors/one"}`.
+//!
+//! The decoder forwards each line as a `GeneMapping` or `Observations` message so that an import
+//! is applied like any other update - the genes validate every observation and duplicates of what
+//! is already journaled are rejected. A line that can not be decoded is reported and counted, the
+//! rest of the stream is still decoded.
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::message::Envelope;
+use crate::actors::message::GeneMapping;
+use crate::actors::message::Message;
+use crate::actors::message::NvError;
+use crate::actors::message::NvResult;
+use crate::io::json_decoder::Observations;
+use crate::utils::nvtime::OffsetDateTimeWrapper;
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::trace;
+
+/// the export line of a journaled observation or gene mapping
+///
+/// # Errors
+///
+/// Returns a description of the error if the message is neither or can not
+/// be serialized
+pub fn export_line(message: &Message<f64>) -> Result<String, String> {
+    match message {
+        Message::Observations {
+            path,
+            datetime,
+            values,
+            labels,
+        } => serde_json::to_string(&Observations {
+            datetime: OffsetDateTimeWrapper::new(*datetime)
+                .datetime_num
+                .to_string(),
+            values: values.clone(),
+            path: path.clone(),
+            labels: labels.clone(),
+        })
+        .map_err(|e| e.to_string()),
+        Message::GeneMapping {
+            path,
+            gene_type,
+            params,
+        } => serde_json::to_string(&GeneMapping {
+            path: path.clone(),
+            gene_type: *gene_type,
+            params: params.clone(),
+        })
+        .map_err(|e| e.to_string()),
+        m => Err(format!("cannot export {m}")),
+    }
+}
+
+/// the `GeneMapping` or `Observations` message of an export line
+///
+/// # Errors
+///
+/// Returns a description of the error if the line is not an export line
+pub fn parse_export_line(text: &str) -> Result<Message<f64>, String> {
+    if text.contains("gene_type") {
+        let gene_mapping: GeneMapping = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        return Ok(Message::GeneMapping {
+            path: gene_mapping.path,
+            gene_type: gene_mapping.gene_type,
+            params: gene_mapping.params,
+        });
+    }
+    let observations: Observations = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if observations.path.is_empty() {
+        return Err(String::from("missing field `path`"));
+    }
+    let datetime_num = observations
+        .datetime
+        .parse()
+        .map_err(|e| format!("bad datetime '{}': {e}", observations.datetime))?;
+    let datetime = OffsetDateTimeWrapper { datetime_num }
+        .to_ts()
+        .map_err(|e| e.reason)?;
+    Ok(Message::Observations {
+        path: observations.path,
+        datetime,
+        values: observations.values,
+        labels: observations.labels,
+    })
+}
+
+pub struct ExportDecoder {
+    pub receiver: mpsc::Receiver<Envelope<f64>>,
+    pub output: Handle,
+    /// lines of the current stream that could not be decoded
+    undecodable: usize,
+}
+
+#[async_trait]
+impl Actor for ExportDecoder {
+    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+        let Envelope {
+            message,
+            respond_to,
+            datetime,
+            ..
+        } = envelope;
+        match message {
+            Message::Content { text, .. } => self.handle_line(&text, respond_to, datetime).await,
+            Message::EndOfStream {} => {
+                if self.undecodable > 0 {
+                    // let the rest of the pipeline finish before reporting
+                    self.output
+                        .ask(Message::EndOfStream {})
+                        .await
+                        .map_err(|e| error!("cannot complete stream: {e}"))
+                        .ok();
+                    let reason = format!("{} lines could not be decoded", self.undecodable);
+                    self.undecodable = 0;
+                    respond_or_log_error(respond_to, Err(NvError { reason }));
+                } else {
+                    let senv = Envelope {
+                        message: Message::EndOfStream {},
+                        respond_to,
+                        ..Default::default()
+                    };
+                    self.send_or_log_error(senv).await;
+                }
+            }
+            m => {
+                let senv = Envelope {
+                    message: m,
+                    respond_to,
+                    ..Default::default()
+                };
+                self.send_or_log_error(senv).await;
+            }
+        }
+    }
+
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+
+    async fn start(&mut self) {}
+}
+
+impl ExportDecoder {
+    async fn handle_line(
+        &mut self,
+        text: &str,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        datetime: OffsetDateTime,
+    ) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        match parse_export_line(text) {
+            Ok(message) => {
+                trace!("export line parsed");
+                let senv = Envelope {
+                    message,
+                    respond_to,
+                    datetime,
+                    ..Default::default()
+                };
+                self.send_or_log_error(senv).await;
+            }
+            Err(error) => {
+                error!("error processing export line: {error}");
+                self.undecodable += 1;
+                respond_or_log_error(
+                    respond_to,
+                    Err(NvError {
+                        reason: format!("export parse error: {error}"),
+                    }),
+                );
+            }
+        }
+    }
+
+    async fn send_or_log_error(&self, envelope: Envelope<f64>)
+    where
+        Envelope<f64>: Send + std::fmt::Debug,
+    {
+        match self.output.send(envelope).await {
+            Ok(_) => (),
+            Err(e) => error!("cannot send: {:?}", e),
+        }
+    }
+
+    /// actor private constructor
+    const fn new(receiver: mpsc::Receiver<Envelope<f64>>, output: Handle) -> Self {
+        Self {
+            receiver,
+            output,
+            undecodable: 0,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize, output: Handle) -> Handle {
+    async fn start(mut actor: ExportDecoder) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = ExportDecoder::new(receiver, output);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
@@ -0,0 +1,109 @@
+//!This module implements the `FileActor`, the named file counterpart of the `StdinActor`. When a
+//!`ReadAllCmd` message is received, the actor opens its file and sends a `Content` message with
+//!each line to the output handle, exactly as the `StdinActor` does with the lines of the standard
+//!input stream. Once the end of the file is reached, an `EndOfStream` message is sent to the next
+//!hop to trigger any necessary cleanup and shutdown. A file that can not be opened, read, or - when
+//!the actor is created with `gzip` set - decompressed is reported to the sender of the `ReadAllCmd`
+//!as an error.
+
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::message::Envelope;
+use crate::actors::message::Message;
+use crate::actors::message::NvError;
+use crate::actors::value::Value;
+use crate::io::stdin_actor::forward_lines;
+use crate::io::stdin_actor::line_reader;
+use async_trait::async_trait;
+use tokio::fs::File;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::warn;
+
+/// the file actor is only used in CLI mode.  it gets a single command to
+/// read its file and it reads until the EOF.  once it sees EOF, it sends
+/// a `EndOfStream` msg to the next hop to trigger any cleanup and shutdown.
+pub struct FileActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Handle<T>,
+    pub path: String,
+    pub gzip: bool,
+}
+
+#[async_trait]
+impl<T: Value> Actor<T> for FileActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        let Envelope {
+            message,
+            respond_to,
+            ..
+        } = envelope;
+
+        if matches!(message, Message::ReadAllCmd {}) {
+            let file = match File::open(&self.path).await {
+                Ok(file) => file,
+                Err(e) => {
+                    error!("cannot open {}: {e}", self.path);
+                    respond_or_log_error(
+                        respond_to,
+                        Err(NvError {
+                            reason: format!("cannot open {}: {e}", self.path),
+                        }),
+                    );
+                    return;
+                }
+            };
+            forward_lines(line_reader(file, self.gzip), &self.output, respond_to).await;
+        } else {
+            warn!("unexpected: {message}");
+        }
+    }
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+    async fn start(&mut self) {}
+}
+
+/// actor private constructor
+impl<T: Value> FileActor<T> {
+    const fn new(
+        receiver: mpsc::Receiver<Envelope<T>>,
+        output: Handle<T>,
+        path: String,
+        gzip: bool,
+    ) -> Self {
+        Self {
+            receiver,
+            output,
+            path,
+            gzip,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new<T: Value>(bufsz: usize, output: Handle<T>, path: String, gzip: bool) -> Handle<T> {
+    async fn start<T: Value>(mut actor: FileActor<T>) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = FileActor::new(receiver, output, path, gzip);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
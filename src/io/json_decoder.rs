@@ -1,18 +1,26 @@
 //! only used to support the cli ifc and `stdin_actor`
+//!
+//! an update line is either one `Observations` object or a json array of them
+//! that are each forwarded as their own `Observations` message
 use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
+use crate::actors::actor::State;
 use crate::actors::message::Envelope;
 use crate::actors::message::GeneMapping;
+use crate::actors::message::Labels;
 use crate::actors::message::Message;
 use crate::actors::message::MtHint;
 use crate::actors::message::NvError;
 use crate::actors::message::NvResult;
 use crate::actors::message::PathQuery;
+use crate::actors::value::Value;
 use crate::utils::nvtime::extract_datetime;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::fmt;
 use time::OffsetDateTime;
 use tokio::sync::mpsc;
 extern crate serde;
@@ -21,16 +29,87 @@ use tracing::debug;
 use tracing::error;
 use tracing::trace;
 
+/// missing values and datetimes are left to `Observations::validate` so that
+/// they are reported as what they are and not as bad json
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Observations {
+pub struct Observations<T = f64> {
+    #[serde(default)]
     pub datetime: String,
-    pub values: HashMap<i32, f64>,
+    #[serde(default = "State::new")]
+    pub values: State<T>,
+    /// may be left out when the message it arrives in names the path
+    #[serde(default)]
     pub path: String,
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
 }
 
-pub struct JsonDecoder {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
-    pub output: Handle,
+/// why an observation payload was rejected - one case per field so that the
+/// API can answer each with its own 400
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// not json or a field of the wrong type
+    Malformed(String),
+    MissingPath,
+    EmptyValues,
+    /// the index of a `NaN` or infinite value
+    NonFinite(i32),
+    MalformedDatetime(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed(e) => write!(f, "json parse error: {e}"),
+            Self::MissingPath => write!(f, "missing field `path`"),
+            Self::EmptyValues => write!(f, "no values to observe"),
+            Self::NonFinite(idx) => write!(f, "value of index {idx} is not finite"),
+            Self::MalformedDatetime(e) => write!(f, "datetime parse error: {e}"),
+        }
+    }
+}
+
+impl From<ValidationError> for NvError {
+    fn from(e: ValidationError) -> Self {
+        Self {
+            reason: e.to_string(),
+        }
+    }
+}
+
+impl<T: Value> Observations<T> {
+    /// the `Observations` message of a payload - the first field that is
+    /// missing or out of range is the error
+    ///
+    /// # Errors
+    ///
+    /// Returns the `ValidationError` of the first bad field
+    pub fn validate(self) -> Result<Message<T>, ValidationError> {
+        if self.path.is_empty() {
+            return Err(ValidationError::MissingPath);
+        }
+        if self.values.is_empty() {
+            return Err(ValidationError::EmptyValues);
+        }
+        if let Some((idx, _)) = self.values.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(ValidationError::NonFinite(*idx));
+        }
+        let datetime = extract_datetime(&self.datetime)
+            .map_err(|e| ValidationError::MalformedDatetime(e.to_string()))?;
+        Ok(Message::Observations {
+            path: self.path,
+            datetime,
+            values: self.values,
+            labels: self.labels,
+        })
+    }
+}
+
+pub struct JsonDecoder<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Handle<T>,
+    /// lines of the current stream that could not be decoded
+    undecodable: usize,
 }
 
 fn extract_path_from_json(text: &str) -> Result<PathQuery, String> {
@@ -49,17 +128,24 @@ fn extract_gene_mapping_from_json(text: &str) -> Result<GeneMapping, String> {
     Ok(gene_mapping)
 }
 
-fn extract_values_from_json(text: &str) -> Result<Observations, String> {
-    let observations: Observations = match serde_json::from_str(text) {
-        Ok(o) => o,
-        Err(e) => return Err(e.to_string()),
-    };
-    Ok(observations)
+/// the observations of a line that is an array of them or else is a single one
+fn extract_values_from_json<T: Value>(text: &str) -> Result<Vec<Observations<T>>, ValidationError> {
+    let malformed = |e: serde_json::Error| ValidationError::Malformed(e.to_string());
+    if text.trim_start().starts_with('[') {
+        let batch: Vec<Observations<T>> = serde_json::from_str(text).map_err(malformed)?;
+        if batch.is_empty() {
+            return Err(ValidationError::EmptyValues);
+        }
+        return Ok(batch);
+    }
+    serde_json::from_str(text)
+        .map(|o| vec![o])
+        .map_err(malformed)
 }
 
 #[async_trait]
-impl Actor for JsonDecoder {
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+impl<T: Value> Actor<T> for JsonDecoder<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
         let Envelope {
             message,
             respond_to,
@@ -75,8 +161,22 @@ impl Actor for JsonDecoder {
             Message::Content {
                 text,
                 hint: MtHint::Update,
-                path: _,
-            } => self.handle_update_json(&text, respond_to, datetime).await,
+                path,
+            } => {
+                self.handle_update_json(&text, path, respond_to, datetime)
+                    .await;
+            }
+            Message::EndOfStream {} if self.undecodable > 0 => {
+                // let the rest of the pipeline finish before reporting
+                self.output
+                    .ask(Message::EndOfStream {})
+                    .await
+                    .map_err(|e| error!("cannot complete stream: {e}"))
+                    .ok();
+                let reason = format!("{} lines could not be decoded", self.undecodable);
+                self.undecodable = 0;
+                respond_or_log_error(respond_to, Err(NvError { reason }));
+            }
             Message::Content {
                 text: _,
                 hint: MtHint::GeneMappingQuery,
@@ -104,16 +204,18 @@ impl Actor for JsonDecoder {
         }
     }
 
-    async fn stop(&self) {}
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
 
     async fn start(&mut self) {}
 }
 
-impl JsonDecoder {
+impl<T: Value> JsonDecoder<T> {
     async fn handle_gene_mapping_json(
         &self,
         json_str: &str,
-        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<T>>>>,
         datetime: OffsetDateTime,
     ) {
         debug!("processing mapping update");
@@ -122,6 +224,7 @@ impl JsonDecoder {
                 let msg = Message::GeneMapping {
                     path: gene_mapping.path,
                     gene_type: gene_mapping.gene_type,
+                    params: gene_mapping.params,
                 };
 
                 let senv = Envelope {
@@ -147,7 +250,7 @@ impl JsonDecoder {
     async fn handle_gene_mapping_query(
         &self,
         path: Option<String>,
-        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<T>>>>,
         datetime: OffsetDateTime,
     ) {
         debug!("processing gene mapping query");
@@ -167,42 +270,44 @@ impl JsonDecoder {
     }
 
     async fn handle_update_json(
-        &self,
+        &mut self,
         json_str: &str,
-        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        path: Option<String>,
+        mut respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<T>>>>,
         datetime: OffsetDateTime,
     ) {
-        match extract_values_from_json(json_str) {
-            Ok(observations) => {
-                trace!("json parsed");
-                match extract_datetime(&observations.datetime) {
-                    Ok(dt) => {
-                        let msg = Message::Observations {
-                            path: observations.path,
-                            datetime: dt,
-                            values: observations.values,
-                        };
-
-                        let senv = Envelope {
-                            message: msg,
-                            respond_to,
-                            datetime,
-                            ..Default::default()
-                        };
-                        self.send_or_log_error(senv).await;
-                    }
-                    Err(e) => {
-                        error!("cannot parse datetime: {e}");
+        // the path of the message, ie: an mqtt topic, is the path of the observations.
+        // a line is rejected whole if any of its observations is not valid
+        let msgs = extract_values_from_json(json_str).and_then(|batch| {
+            batch
+                .into_iter()
+                .map(|mut observations| {
+                    if let Some(path) = &path {
+                        observations.path.clone_from(path);
                     }
+                    observations.validate()
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        match msgs {
+            Ok(msgs) => {
+                trace!("json parsed");
+                // the response to the line is the one to its last observation
+                let last = msgs.len() - 1;
+                for (n, msg) in msgs.into_iter().enumerate() {
+                    let senv = Envelope {
+                        message: msg,
+                        respond_to: if n == last { respond_to.take() } else { None },
+                        datetime,
+                        ..Default::default()
+                    };
+                    self.send_or_log_error(senv).await;
                 }
             }
             Err(error) => {
-                respond_or_log_error(
-                    respond_to,
-                    Err(NvError {
-                        reason: format!("json parse error: {error:?}"),
-                    }),
-                );
+                error!("error processing update: {error}");
+                self.undecodable += 1;
+                respond_or_log_error(respond_to, Err(error.into()));
             }
         }
     }
@@ -210,7 +315,7 @@ impl JsonDecoder {
     async fn handle_query_json(
         &self,
         json_str: &str,
-        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<T>>>>,
         datetime: OffsetDateTime,
     ) {
         match extract_path_from_json(json_str) {
@@ -240,9 +345,9 @@ impl JsonDecoder {
         }
     }
 
-    async fn send_or_log_error(&self, envelope: Envelope<f64>)
+    async fn send_or_log_error(&self, envelope: Envelope<T>)
     where
-        Envelope<f64>: Send + std::fmt::Debug,
+        Envelope<T>: Send + std::fmt::Debug,
     {
         match self.output.send(envelope).await {
             Ok(_) => (),
@@ -251,16 +356,24 @@ impl JsonDecoder {
     }
 
     /// actor private constructor
-    const fn new(receiver: mpsc::Receiver<Envelope<f64>>, output: Handle) -> Self {
-        Self { receiver, output }
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>, output: Handle<T>) -> Self {
+        Self {
+            receiver,
+            output,
+            undecodable: 0,
+        }
     }
 }
 
 /// actor handle public constructor
 #[must_use]
-pub fn new(bufsz: usize, output: Handle) -> Handle {
-    async fn start(mut actor: JsonDecoder) {
+pub fn new<T: Value>(bufsz: usize, output: Handle<T>) -> Handle<T> {
+    async fn start<T: Value>(mut actor: JsonDecoder<T>) {
         while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
             actor.handle_envelope(envelope).await;
         }
     }
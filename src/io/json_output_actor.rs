@@ -0,0 +1,316 @@
+//!This module is the `jq` friendly alternative to the `stdout_actor`.
+//!
+//!Each `StateReport` it receives is printed to the standard output as a single NDJSON line:
+//!`{"path":"/actors/one","datetime":"2023-01-11T23:17:57.000000000Z","values":{"1":1.5}}` with the
+//!datetime in ISO 8601 and the values keyed by the names of the `IndexLabels` of the namespace once
+//!the director has told it any, and each observation of a `History` is printed the same way with the
+//!number of observations of all its pages on `stderr` like the `stdout_actor` does.  A
+//!`StatsReport` is one line of its counts with its journal times in ISO 8601, a `DirectorInfoReport`
+//!is one line of its `actors` and `state_entries` counts, and an `Alert` is a
+//!line with the `path` and an `alert` object of its `idx`, `value`, and `threshold`.  Like the
+//!`stdout_actor`, it prints `Content` text as is and the reason an update was rejected and the
+//!`StreamSummary` of an update to `stderr`, and it answers `EndOfStream` once everything before it
+//!is printed.
+
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::message::label_values;
+use crate::actors::message::Envelope;
+use crate::actors::message::IndexLabels;
+use crate::actors::message::JournalStats;
+use crate::actors::message::Message;
+use crate::actors::value::Value;
+use crate::io::stdout_actor::print_page_of;
+use async_trait::async_trait;
+use serde::Serialize;
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::warn;
+
+pub struct JsonOutputActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    /// the names printed in place of the indices of the state
+    labels: IndexLabels,
+}
+
+#[derive(Serialize)]
+struct JsonStateReport<'a, V> {
+    path: &'a str,
+    datetime: String,
+    values: V,
+}
+
+/// the NDJSON line of a state report
+///
+/// # Errors
+///
+/// Returns a description of the error if the datetime can not be formatted
+pub fn state_report_line<T: Value>(
+    path: &str,
+    datetime: OffsetDateTime,
+    values: &State<T>,
+) -> Result<String, String> {
+    report_line(path, datetime, values)
+}
+
+/// the NDJSON line of a state report with its values keyed by their index
+/// labels, ie: `{"temperature_c":21.5,"2":3.0}`, and by index without any
+///
+/// # Errors
+///
+/// Returns a description of the error if the datetime can not be formatted
+pub fn labeled_state_report_line<T: Value>(
+    path: &str,
+    datetime: OffsetDateTime,
+    values: &State<T>,
+    labels: &IndexLabels,
+) -> Result<String, String> {
+    if labels.is_empty() {
+        return state_report_line(path, datetime, values);
+    }
+    report_line(path, datetime, label_values(values, labels))
+}
+
+fn report_line<V: Serialize>(
+    path: &str,
+    datetime: OffsetDateTime,
+    values: V,
+) -> Result<String, String> {
+    let datetime = datetime
+        .format(&Iso8601::DEFAULT)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_string(&JsonStateReport {
+        path,
+        datetime,
+        values,
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+struct JsonStatsReport<'a> {
+    path: &'a str,
+    paths: u64,
+    observations: u64,
+    earliest: Option<String>,
+    latest: Option<String>,
+    gene_mappings: u64,
+}
+
+/// the NDJSON line of a stats report - the journal times are null when
+/// there are no observations
+///
+/// # Errors
+///
+/// Returns a description of the error if a datetime can not be formatted
+pub fn stats_report_line(path: &str, stats: &JournalStats) -> Result<String, String> {
+    let format = |datetime: Option<OffsetDateTime>| {
+        datetime
+            .map(|datetime| datetime.format(&Iso8601::DEFAULT))
+            .transpose()
+            .map_err(|e| e.to_string())
+    };
+    serde_json::to_string(&JsonStatsReport {
+        path,
+        paths: stats.paths,
+        observations: stats.observations,
+        earliest: format(stats.earliest)?,
+        latest: format(stats.latest)?,
+        gene_mappings: stats.gene_mappings,
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+struct JsonAlert<'a, T> {
+    path: &'a str,
+    alert: JsonAlertCrossing<'a, T>,
+}
+
+#[derive(Serialize)]
+struct JsonAlertCrossing<'a, T> {
+    idx: i32,
+    value: &'a T,
+    threshold: &'a T,
+}
+
+/// the NDJSON line of an alert, ie:
+/// `{"path":"/actors/one","alert":{"idx":1,"value":81.5,"threshold":80.0}}`
+///
+/// # Errors
+///
+/// Returns a description of the error if the alert can not be serialized
+pub fn alert_line<T: Value>(
+    path: &str,
+    idx: i32,
+    value: &T,
+    threshold: &T,
+) -> Result<String, String> {
+    serde_json::to_string(&JsonAlert {
+        path,
+        alert: JsonAlertCrossing {
+            idx,
+            value,
+            threshold,
+        },
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[async_trait]
+impl<T: Value> Actor<T> for JsonOutputActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        let Envelope {
+            message,
+            respond_to,
+            ..
+        } = envelope;
+
+        match &message {
+            Message::Content { text, .. } => println!("{text}"),
+            Message::StateReport {
+                path,
+                datetime,
+                values,
+                ..
+            } => {
+                match labeled_state_report_line(path, *datetime, values, &self.labels) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => error!("cannot serialize state of {path}: {e}"),
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::History {
+                path,
+                points,
+                total,
+            } => {
+                for (datetime, values) in points {
+                    match labeled_state_report_line(path, *datetime, values, &self.labels) {
+                        Ok(line) => println!("{line}"),
+                        Err(e) => error!("cannot serialize history of {path}: {e}"),
+                    }
+                }
+                print_page_of(path, points.len(), *total);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StateReports { reports, .. } => {
+                for report in reports {
+                    if let Message::StateReport {
+                        path,
+                        datetime,
+                        values,
+                        ..
+                    } = report
+                    {
+                        match labeled_state_report_line(path, *datetime, values, &self.labels) {
+                            Ok(line) => println!("{line}"),
+                            Err(e) => error!("cannot serialize state of {path}: {e}"),
+                        }
+                    }
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StatsReport { path, stats } => {
+                match stats_report_line(path, stats) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => error!("cannot serialize stats of {path}: {e}"),
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::DirectorInfoReport { info } => {
+                match serde_json::to_string(info) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => error!("cannot serialize director info: {e}"),
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Alert {
+                path,
+                idx,
+                value,
+                threshold,
+            } => {
+                match alert_line(path, *idx, value, threshold) {
+                    Ok(line) => println!("{line}"),
+                    Err(e) => error!("cannot serialize alert of {path}: {e}"),
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Observations { .. } => respond_or_log_error(respond_to, Ok(message)),
+            Message::IndexLabels { labels } => {
+                self.labels.clone_from(labels);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Error { path, reason } => {
+                eprintln!("{path} error: {reason}");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            // not NDJSON - the summary is for the person running the update
+            Message::StreamSummary {
+                ingested,
+                duplicates,
+            } => {
+                eprintln!("{ingested} observations ingested, {duplicates} duplicates skipped");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::EndOfStream {} => {
+                if let Some(respond_to) = respond_to {
+                    respond_to
+                        .send(Ok(Message::EndOfStream {}))
+                        .unwrap_or_else(|e| error!("cannot respond to ask: {e:?}"));
+                }
+            }
+            _ => {
+                warn!("unexpected: {message}");
+            }
+        }
+    }
+    async fn stop(&self) {}
+    async fn start(&mut self) {}
+}
+
+/// actor private constructor
+impl<T: Value> JsonOutputActor<T> {
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>) -> Self {
+        Self {
+            receiver,
+            labels: IndexLabels::new(),
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize) -> Handle {
+    new_of_type(bufsz)
+}
+
+/// `new` for the state of a director of `T` values, ie: `i64`
+#[must_use]
+pub fn new_of_type<T: Value>(bufsz: usize) -> Handle<T> {
+    async fn start<T: Value>(mut actor: JsonOutputActor<T>) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = JsonOutputActor::new(receiver);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
@@ -0,0 +1,332 @@
+//! only used to support the cli ifc and `stdin_actor` - the `InfluxDB` line protocol counterpart
+//! of the `json_decoder` for the output of Telegraf agents:
+//! `measurement,tag=val field=1.0 timestamp`.
+//!
+//! Each line is one observation. The measurement and the tag values, in tag key order, are the
+//! path and the tags are also the labels, ie: `cpu,host=a,region=us usage=0.5` is an observation
+//! of `/cpu/a/us`. A field is the index the field table maps its name to - a field named with an
+//! integer is that index and a field the table has no index for is skipped. Integer, unsigned,
+//! float, and boolean fields are values and string fields are skipped. The timestamp is in
+//! nanoseconds and a line without one is observed now.
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::message::Envelope;
+use crate::actors::message::Labels;
+use crate::actors::message::Message;
+use crate::actors::message::NvError;
+use crate::actors::message::NvResult;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::trace;
+
+/// the index of the actor state each field name is written to
+pub type FieldIndices = HashMap<String, i32>;
+
+/// one line of the input
+#[derive(Debug, PartialEq)]
+pub struct Point {
+    pub path: String,
+    pub datetime: OffsetDateTime,
+    pub values: State<f64>,
+    pub labels: Labels,
+}
+
+pub struct LineProtocolDecoder {
+    pub receiver: mpsc::Receiver<Envelope<f64>>,
+    pub output: Handle,
+    pub fields: FieldIndices,
+    /// lines of the current stream that could not be decoded
+    undecodable: usize,
+}
+
+/// parse the `name=idx` pairs of the `--field-index` flag
+///
+/// # Errors
+///
+/// Returns a description of the first pair that is not a name and an integer
+pub fn parse_field_indices(pairs: &[String]) -> Result<FieldIndices, String> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let (name, idx) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected 'name=idx' but found '{pair}'"))?;
+            let idx = idx
+                .trim()
+                .parse()
+                .map_err(|e| format!("bad index in '{pair}': {e}"))?;
+            Ok((name.trim().to_string(), idx))
+        })
+        .collect()
+}
+
+/// split on every `sep` that is neither escaped with a backslash nor quoted
+fn split_unescaped(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// a field value as a float or `None` for a string field
+fn field_value(text: &str) -> Result<Option<f64>, String> {
+    if text.starts_with('"') {
+        return Ok(None);
+    }
+    match text {
+        "t" | "T" | "true" | "True" | "TRUE" => return Ok(Some(1.0)),
+        "f" | "F" | "false" | "False" | "FALSE" => return Ok(Some(0.0)),
+        _ => (),
+    }
+    let number = text
+        .strip_suffix('i')
+        .or_else(|| text.strip_suffix('u'))
+        .unwrap_or(text);
+    number
+        .parse()
+        .map(Some)
+        .map_err(|e| format!("bad field value '{text}': {e}"))
+}
+
+/// parse one `measurement,tag=val field=1.0 timestamp` line
+///
+/// # Errors
+///
+/// Returns a description of the first part of the line that can not be parsed
+pub fn parse_line(text: &str, fields: &FieldIndices) -> Result<Point, String> {
+    let sections: Vec<&str> = split_unescaped(text, ' ')
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect();
+    let (series, field_set, timestamp) = match sections[..] {
+        [series, field_set] => (series, field_set, None),
+        [series, field_set, timestamp] => (series, field_set, Some(timestamp)),
+        _ => {
+            return Err(format!(
+                "expected 2 or 3 sections but found {}",
+                sections.len()
+            ))
+        }
+    };
+
+    let mut series = split_unescaped(series, ',').into_iter();
+    let measurement = unescape(series.next().unwrap_or_default());
+    if measurement.is_empty() {
+        return Err(String::from("empty measurement"));
+    }
+    let mut labels = Labels::new();
+    for tag in series {
+        let [key, value] = split_unescaped(tag, '=')[..] else {
+            return Err(format!("bad tag '{tag}'"));
+        };
+        labels.insert(unescape(key), unescape(value));
+    }
+    // the labels are ordered by key - the canonical order of line protocol tags
+    let path = labels
+        .values()
+        .fold(format!("/{measurement}"), |path, value| {
+            format!("{path}/{value}")
+        });
+
+    let mut values = State::new();
+    for field in split_unescaped(field_set, ',') {
+        let [key, value] = split_unescaped(field, '=')[..] else {
+            return Err(format!("bad field '{field}'"));
+        };
+        let key = unescape(key);
+        let Some(idx) = fields.get(&key).copied().or_else(|| key.parse().ok()) else {
+            trace!("skipping unmapped field {key}");
+            continue;
+        };
+        if let Some(value) = field_value(value)? {
+            values.insert(idx, value);
+        }
+    }
+    if values.is_empty() {
+        return Err(String::from("no field is mapped to an index"));
+    }
+
+    let datetime = match timestamp {
+        Some(timestamp) => {
+            let nanos: i128 = timestamp
+                .parse()
+                .map_err(|e| format!("bad timestamp '{timestamp}': {e}"))?;
+            OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .map_err(|e| format!("bad timestamp '{timestamp}': {e}"))?
+        }
+        None => OffsetDateTime::now_utc(),
+    };
+
+    Ok(Point {
+        path,
+        datetime,
+        values,
+        labels,
+    })
+}
+
+#[async_trait]
+impl Actor for LineProtocolDecoder {
+    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+        let Envelope {
+            message,
+            respond_to,
+            datetime,
+            ..
+        } = envelope;
+        match message {
+            Message::Content { text, .. } => self.handle_line(&text, respond_to, datetime).await,
+            Message::EndOfStream {} if self.undecodable > 0 => {
+                // let the rest of the pipeline finish before reporting
+                self.output
+                    .ask(Message::EndOfStream {})
+                    .await
+                    .map_err(|e| error!("cannot complete stream: {e}"))
+                    .ok();
+                let reason = format!("{} lines could not be decoded", self.undecodable);
+                self.undecodable = 0;
+                respond_or_log_error(respond_to, Err(NvError { reason }));
+            }
+            m => {
+                let senv = Envelope {
+                    message: m,
+                    respond_to,
+                    ..Default::default()
+                };
+                self.send_or_log_error(senv).await;
+            }
+        }
+    }
+
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+
+    async fn start(&mut self) {}
+}
+
+impl LineProtocolDecoder {
+    async fn handle_line(
+        &mut self,
+        text: &str,
+        respond_to: Option<tokio::sync::oneshot::Sender<NvResult<Message<f64>>>>,
+        datetime: OffsetDateTime,
+    ) {
+        let text = text.trim();
+        // blank lines and comments
+        if text.is_empty() || text.starts_with('#') {
+            return;
+        }
+        match parse_line(text, &self.fields) {
+            Ok(point) => {
+                trace!("line protocol parsed");
+                let senv = Envelope {
+                    message: Message::Observations {
+                        path: point.path,
+                        datetime: point.datetime,
+                        values: point.values,
+                        labels: point.labels,
+                    },
+                    respond_to,
+                    datetime,
+                    ..Default::default()
+                };
+                self.send_or_log_error(senv).await;
+            }
+            Err(error) => {
+                error!("error processing update: {error}");
+                self.undecodable += 1;
+                respond_or_log_error(
+                    respond_to,
+                    Err(NvError {
+                        reason: format!("line protocol parse error: {error}"),
+                    }),
+                );
+            }
+        }
+    }
+
+    async fn send_or_log_error(&self, envelope: Envelope<f64>)
+    where
+        Envelope<f64>: Send + std::fmt::Debug,
+    {
+        match self.output.send(envelope).await {
+            Ok(_) => (),
+            Err(e) => error!("cannot send: {:?}", e),
+        }
+    }
+
+    /// actor private constructor
+    const fn new(
+        receiver: mpsc::Receiver<Envelope<f64>>,
+        output: Handle,
+        fields: FieldIndices,
+    ) -> Self {
+        Self {
+            receiver,
+            output,
+            fields,
+            undecodable: 0,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize, output: Handle, fields: FieldIndices) -> Handle {
+    async fn start(mut actor: LineProtocolDecoder) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = LineProtocolDecoder::new(receiver, output, fields);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
@@ -1,4 +1,12 @@
+pub mod csv_decoder;
+pub mod csv_output_actor;
+pub mod dry_run_actor;
+pub mod export;
+pub mod file_actor;
 pub mod json_decoder;
+pub mod json_output_actor;
+pub mod line_protocol_decoder;
 pub mod net;
+pub mod progress_actor;
 pub mod stdin_actor;
 pub mod stdout_actor;
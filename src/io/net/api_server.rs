@@ -1,31 +1,84 @@
 #![allow(clippy::useless_let_if_seq)]
+#![allow(clippy::result_large_err)]
 use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::director;
+use crate::actors::genes::gene::AggregateFn;
+use crate::actors::genes::gene::GeneParams;
 use crate::actors::genes::gene::GeneType;
+use crate::actors::genes::gene::IndexOperator;
+use crate::actors::genes::gene::IndexRange;
+use crate::actors::genes::gene::Threshold;
+use crate::actors::genes::gene::TimeScope;
+use crate::actors::message::label_values;
+use crate::actors::message::Labels;
 use crate::actors::message::Message;
 use crate::actors::message::MtHint;
+use crate::io::json_decoder::Observations;
+use crate::io::json_decoder::ValidationError;
+use crate::io::net::auth::AuthMiddleware;
+use crate::io::net::auth::Authenticator;
+use crate::utils::metrics;
 use crate::utils::nvtime::extract_datetime;
+use futures::{SinkExt, StreamExt};
 use poem::{
-    http::StatusCode, listener::TcpListener, web::Data, EndpointExt, Error, FromRequest, Request,
-    RequestBody, Result, Route,
+    endpoint::{make_sync, BoxEndpoint},
+    get, handler,
+    http::StatusCode,
+    listener::TcpListener,
+    web::sse::{Event as SseEvent, SSE},
+    web::websocket::{Message as WsMessage, WebSocket},
+    web::Data,
+    Body, EndpointExt, Error, FromRequest, IntoResponse, Request, RequestBody, Result, Route,
 };
 use std::ops::Deref;
+use tokio::sync::mpsc;
 
 use poem_openapi::{
-    param::Path,
-    payload::{Json, PlainText},
-    ApiResponse, Object, OpenApi, OpenApiService,
+    param::{Header, Path, Query},
+    payload::{Binary, Json, PlainText},
+    types::ToJSON,
+    ApiResponse, Enum, Object, OpenApi, OpenApiService,
 };
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use time::format_description::well_known::Iso8601;
+use time::macros::format_description;
+use time::Date;
 use tracing::debug;
+use tracing::error;
+use tracing::field::Empty;
 use tracing::info;
+use tracing::instrument;
+use tracing::Span;
+
+const DEFAULT_MAX_PAGE_SIZE: usize = 10_000;
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::from_secs(300);
 
 pub struct HttpServerConfig {
     pub port: u16,
     pub interface: String,
     pub external_host: String,
     pub namespace: String,
+    pub authenticator: Option<Arc<dyn Authenticator>>,
+    /// the most state indices returned in one `GET` response
+    pub max_page_size: usize,
+    /// how long a request waits on the actors before it is abandoned - the
+    /// journal load of an actor being resurrected for it is cancelled
+    pub request_timeout: Duration,
+    /// how long the response to a post with an `Idempotency-Key` is kept to
+    /// answer a retry of the post with
+    pub idempotency_window: Duration,
+    /// where `serve` writes the API spec before it starts listening
+    pub spec_out: Option<PathBuf>,
 }
 
 impl HttpServerConfig {
@@ -41,8 +94,49 @@ impl HttpServerConfig {
             interface: interface.unwrap_or_else(|| "127.0.0.1".to_string()),
             external_host: external_host.unwrap_or_else(|| "http://localhost:8800".to_string()),
             namespace,
+            authenticator: None,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW,
+            spec_out: None,
         }
     }
+
+    /// cap the number of state indices in one response, larger states are paged
+    #[must_use]
+    pub const fn with_max_page_size(mut self, max_page_size: usize) -> Self {
+        self.max_page_size = max_page_size;
+        self
+    }
+
+    /// give up on requests the actors have not answered within `request_timeout`
+    #[must_use]
+    pub const fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// answer a post that repeats the `Idempotency-Key` of one posted within
+    /// `idempotency_window` with the response to that one
+    #[must_use]
+    pub const fn with_idempotency_window(mut self, idempotency_window: Duration) -> Self {
+        self.idempotency_window = idempotency_window;
+        self
+    }
+
+    /// write the API spec to `spec_out` when the server starts
+    #[must_use]
+    pub fn with_spec_out(mut self, spec_out: PathBuf) -> Self {
+        self.spec_out = Some(spec_out);
+        self
+    }
+
+    /// require every API request to pass the authenticator
+    #[must_use]
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
 }
 
 impl fmt::Display for HttpServerConfig {
@@ -55,42 +149,432 @@ impl fmt::Display for HttpServerConfig {
     }
 }
 
+/// missing fields are left to `Observations::validate` so that each is
+/// answered with its own error code
 #[derive(Object)]
 pub struct ApiObservations {
+    #[oai(default)]
     pub datetime: String,
-    pub values: HashMap<i32, f64>,
+    #[oai(default)]
+    pub values: State<f64>,
+    #[oai(default)]
     pub path: String,
+    #[oai(default)]
+    pub labels: Labels,
 }
 
-#[derive(Object)]
+impl ApiObservations {
+    /// the `Observations` message of a body with its path normalized
+    fn validate(self) -> Result<Message<f64>, ValidationError> {
+        Observations {
+            path: if self.path.is_empty() {
+                self.path
+            } else {
+                normalize_path(&self.path)
+            },
+            datetime: self.datetime,
+            values: self.values,
+            labels: self.labels,
+        }
+        .validate()
+    }
+}
+
+#[derive(Object, Clone)]
 struct ApiStateReport {
     datetime: String,
     path: String,
-    values: HashMap<i32, f64>,
+    values: State<f64>,
+    /// the number of observations applied to the state - send it back as
+    /// `If-Match` to post only if no other observations were applied since
+    version: u64,
+    /// the page to ask for to continue through a paged state
+    #[oai(skip_serializing_if_is_none)]
+    next: Option<usize>,
+    /// `values` keyed by the index labels of the namespace, and by index
+    /// where an index has no label, when asked for with `labels=true`
+    #[oai(skip_serializing_if_is_none)]
+    labeled: Option<BTreeMap<String, f64>>,
+}
+
+/// the paths with observations journaled since a sequence and the sequence
+/// to list them since next time
+#[derive(Object)]
+struct ApiPathsSince {
+    paths: Vec<String>,
+    sequence: String,
+}
+
+/// how many observations of a path are journaled, when the first and the
+/// last of them were taken, and the version of its state
+#[derive(Object)]
+struct ApiPathStats {
+    path: String,
+    observations: u64,
+    first: Option<String>,
+    last: Option<String>,
+    version: u64,
 }
 
 #[derive(Object)]
 struct ApiGeneMapping {
     path: String,
     gene_type: String,
+    /// overrides of the gene defaults - left out for a gene that keeps them
+    #[oai(skip_serializing_if_is_none)]
+    params: Option<ApiGeneParams>,
+}
+
+/// the index ranges of a `GaugeAndAccum` gene, the `alpha` of an `Ewma` gene,
+/// the `window` of a `MovingAvg` gene, the `aggregate` function, `sum` or
+/// `mean`, of an `Aggregate` gene, the `thresholds` of an `Alert` gene, the
+/// operator `ranges` of a `Composite` gene, the `source_idx` and `delta_idx`
+/// of a `Delta` gene, or the `time_scope` of an `Accum` gene
+#[derive(Object)]
+struct ApiGeneParams {
+    #[oai(skip_serializing_if_is_none)]
+    guage_first_idx: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    guage_slots: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    accumulator_first_idx: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    accumulator_slots: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    alpha: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    window: Option<usize>,
+    #[oai(skip_serializing_if_is_none)]
+    aggregate: Option<ApiAggregateFn>,
+    #[oai(skip_serializing_if_is_none)]
+    thresholds: Option<BTreeMap<i32, ApiThreshold>>,
+    #[oai(skip_serializing_if_is_none)]
+    ranges: Option<Vec<ApiIndexRange>>,
+    #[oai(skip_serializing_if_is_none)]
+    source_idx: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    delta_idx: Option<i32>,
+    #[oai(skip_serializing_if_is_none)]
+    time_scope: Option<ApiTimeScope>,
+}
+
+/// the `slots` indexes from `first_idx` that a `Composite` gene applies
+/// `operator` to
+#[derive(Object, Clone, Copy)]
+struct ApiIndexRange {
+    first_idx: i32,
+    slots: i32,
+    operator: ApiIndexOperator,
+}
+
+#[derive(Enum, Clone, Copy)]
+#[oai(rename_all = "lowercase")]
+enum ApiIndexOperator {
+    Gauge,
+    Accum,
+    Min,
+    Max,
+}
+
+impl From<ApiIndexRange> for IndexRange {
+    fn from(range: ApiIndexRange) -> Self {
+        Self {
+            first_idx: range.first_idx,
+            slots: range.slots,
+            operator: match range.operator {
+                ApiIndexOperator::Gauge => IndexOperator::Gauge,
+                ApiIndexOperator::Accum => IndexOperator::Accum,
+                ApiIndexOperator::Min => IndexOperator::Min,
+                ApiIndexOperator::Max => IndexOperator::Max,
+            },
+        }
+    }
+}
+
+impl From<IndexRange> for ApiIndexRange {
+    fn from(range: IndexRange) -> Self {
+        Self {
+            first_idx: range.first_idx,
+            slots: range.slots,
+            operator: match range.operator {
+                IndexOperator::Gauge => ApiIndexOperator::Gauge,
+                IndexOperator::Accum => ApiIndexOperator::Accum,
+                IndexOperator::Min => ApiIndexOperator::Min,
+                IndexOperator::Max => ApiIndexOperator::Max,
+            },
+        }
+    }
+}
+
+/// the bounds an `Alert` gene keeps an index within
+#[derive(Object, Clone, Copy)]
+struct ApiThreshold {
+    #[oai(skip_serializing_if_is_none)]
+    low: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    high: Option<f64>,
+}
+
+impl From<ApiThreshold> for Threshold {
+    fn from(threshold: ApiThreshold) -> Self {
+        Self {
+            low: threshold.low,
+            high: threshold.high,
+        }
+    }
+}
+
+impl From<Threshold> for ApiThreshold {
+    fn from(threshold: Threshold) -> Self {
+        Self {
+            low: threshold.low,
+            high: threshold.high,
+        }
+    }
+}
+
+/// the value of an index crossed a threshold of its `Alert` gene
+#[derive(Object)]
+struct ApiAlert {
+    path: String,
+    idx: i32,
+    value: f64,
+    threshold: f64,
+}
+
+#[derive(Enum, Clone, Copy)]
+#[oai(rename_all = "lowercase")]
+enum ApiAggregateFn {
+    Sum,
+    Mean,
+}
+
+impl From<ApiAggregateFn> for AggregateFn {
+    fn from(function: ApiAggregateFn) -> Self {
+        match function {
+            ApiAggregateFn::Sum => Self::Sum,
+            ApiAggregateFn::Mean => Self::Mean,
+        }
+    }
+}
+
+impl From<AggregateFn> for ApiAggregateFn {
+    fn from(function: AggregateFn) -> Self {
+        match function {
+            AggregateFn::Sum => Self::Sum,
+            AggregateFn::Mean => Self::Mean,
+        }
+    }
+}
+
+/// the periods an `Accum` gene starts its sums fresh at
+#[derive(Enum, Clone, Copy)]
+#[oai(rename_all = "snake_case")]
+enum ApiTimeScope {
+    Forever,
+    Year,
+    Month,
+    Day,
+    HalfDay,
+    QuarterDay,
+    Hour,
+    QuarterHour,
+    TenMinutes,
+    Minute,
+}
+
+impl From<ApiTimeScope> for TimeScope {
+    fn from(scope: ApiTimeScope) -> Self {
+        match scope {
+            ApiTimeScope::Forever => Self::Forever,
+            ApiTimeScope::Year => Self::Year,
+            ApiTimeScope::Month => Self::Month,
+            ApiTimeScope::Day => Self::Day,
+            ApiTimeScope::HalfDay => Self::HalfDay,
+            ApiTimeScope::QuarterDay => Self::QuarterDay,
+            ApiTimeScope::Hour => Self::Hour,
+            ApiTimeScope::QuarterHour => Self::QuarterHour,
+            ApiTimeScope::TenMinutes => Self::TenMinutes,
+            ApiTimeScope::Minute => Self::Minute,
+        }
+    }
+}
+
+impl From<TimeScope> for ApiTimeScope {
+    fn from(scope: TimeScope) -> Self {
+        match scope {
+            TimeScope::Forever => Self::Forever,
+            TimeScope::Year => Self::Year,
+            TimeScope::Month => Self::Month,
+            TimeScope::Day => Self::Day,
+            TimeScope::HalfDay => Self::HalfDay,
+            TimeScope::QuarterDay => Self::QuarterDay,
+            TimeScope::Hour => Self::Hour,
+            TimeScope::QuarterHour => Self::QuarterHour,
+            TimeScope::TenMinutes => Self::TenMinutes,
+            TimeScope::Minute => Self::Minute,
+        }
+    }
+}
+
+impl From<ApiGeneParams> for GeneParams {
+    fn from(params: ApiGeneParams) -> Self {
+        Self {
+            guage_first_idx: params.guage_first_idx,
+            guage_slots: params.guage_slots,
+            accumulator_first_idx: params.accumulator_first_idx,
+            accumulator_slots: params.accumulator_slots,
+            alpha: params.alpha,
+            window: params.window,
+            aggregate: params.aggregate.map(AggregateFn::from),
+            thresholds: params.thresholds.map(|thresholds| {
+                thresholds
+                    .into_iter()
+                    .map(|(idx, threshold)| (idx, Threshold::from(threshold)))
+                    .collect()
+            }),
+            ranges: params
+                .ranges
+                .map(|ranges| ranges.into_iter().map(IndexRange::from).collect()),
+            source_idx: params.source_idx,
+            delta_idx: params.delta_idx,
+            time_scope: params.time_scope.map(TimeScope::from),
+        }
+    }
+}
+
+/// the params of a mapping as the API reports them - none if it keeps the
+/// gene defaults
+fn api_gene_params(params: GeneParams) -> Option<ApiGeneParams> {
+    (!params.is_empty()).then_some(ApiGeneParams {
+        guage_first_idx: params.guage_first_idx,
+        guage_slots: params.guage_slots,
+        accumulator_first_idx: params.accumulator_first_idx,
+        accumulator_slots: params.accumulator_slots,
+        alpha: params.alpha,
+        window: params.window,
+        aggregate: params.aggregate.map(ApiAggregateFn::from),
+        thresholds: params.thresholds.map(|thresholds| {
+            thresholds
+                .into_iter()
+                .map(|(idx, threshold)| (idx, ApiThreshold::from(threshold)))
+                .collect()
+        }),
+        ranges: params
+            .ranges
+            .map(|ranges| ranges.into_iter().map(ApiIndexRange::from).collect()),
+        source_idx: params.source_idx,
+        delta_idx: params.delta_idx,
+        time_scope: params.time_scope.map(ApiTimeScope::from),
+    })
+}
+
+#[derive(Object)]
+struct ApiGeneMappingResult {
+    path: String,
+    gene_type: String,
+    /// set when this mapping was not persisted
+    #[oai(skip_serializing_if_is_none)]
+    error: Option<String>,
+}
+
+#[derive(Object)]
+struct ApiBatchSummary {
+    /// observations journaled and applied
+    accepted: usize,
+    /// observations the journal already held
+    duplicates: usize,
+    /// observations that could not be parsed, journaled, or applied
+    errors: usize,
+}
+
+/// what went wrong with a request, for programs to match on
+#[derive(Enum, Clone, Copy)]
+#[oai(rename_all = "snake_case")]
+enum ApiErrorCode {
+    BadRequest,
+    NotFound,
+    /// the journal already has an observation of the path at its datetime,
+    /// or the gene mapping already exists
+    Duplicate,
+    /// the observation is older than the observation window allows
+    LateObservation,
+    /// the actor is not at the `If-Match` version
+    VersionConflict,
+    /// the path is over the server's rate limit, retry later
+    RateLimited,
+    /// a post with the same `Idempotency-Key` is still being applied, retry
+    /// later for its response
+    IdempotencyKeyInFlight,
+    /// the gene mapping is shared by every namespace so one namespace can
+    /// not delete it
+    SharedMapping,
+    ServerError,
+    MissingPath,
+    EmptyValues,
+    /// a value is `NaN` or infinite
+    NonFiniteValue,
+    MalformedDatetime,
+}
+
+impl From<&ValidationError> for ApiErrorCode {
+    fn from(e: &ValidationError) -> Self {
+        match e {
+            ValidationError::Malformed(_) => Self::BadRequest,
+            ValidationError::MissingPath => Self::MissingPath,
+            ValidationError::EmptyValues => Self::EmptyValues,
+            ValidationError::NonFinite(_) => Self::NonFiniteValue,
+            ValidationError::MalformedDatetime(_) => Self::MalformedDatetime,
+        }
+    }
+}
+
+/// the body of every 4xx and 5xx response of the API
+#[derive(Object, Clone)]
+struct ApiError {
+    /// what went wrong, for people to read
+    error: String,
+    code: ApiErrorCode,
+    /// the actor or gene mapping path of the request
+    #[oai(skip_serializing_if_is_none)]
+    path: Option<String>,
+}
+
+impl ApiError {
+    fn json(code: ApiErrorCode, path: Option<&str>, error: impl Into<String>) -> Json<Self> {
+        Json(Self {
+            error: error.into(),
+            code,
+            path: path.map(String::from),
+        })
+    }
 }
 
 #[derive(ApiResponse)]
+enum BatchPostResponse {
+    #[oai(status = 200)]
+    ApiBatchSummary(Json<ApiBatchSummary>),
+}
+
+#[derive(ApiResponse, Clone)]
 enum PostObservationResponse {
     #[oai(status = 200)]
     ApiStateReport(Json<ApiStateReport>),
 
     #[oai(status = 404)]
-    NotFound(PlainText<String>),
+    NotFound(Json<ApiError>),
 
     #[oai(status = 400)]
-    BadRequest(PlainText<String>),
+    BadRequest(Json<ApiError>),
 
     #[oai(status = 409)]
-    ConstraintViolation(PlainText<String>),
+    ConstraintViolation(Json<ApiError>),
+
+    #[oai(status = 429)]
+    TooManyRequests(Json<ApiError>),
 
     #[oai(status = 500)]
-    InternalServerError(PlainText<String>),
+    InternalServerError(Json<ApiError>),
 }
 
 #[derive(ApiResponse)]
@@ -99,10 +583,86 @@ enum GetStateResponse {
     ApiStateReport(Json<ApiStateReport>),
 
     #[oai(status = 404)]
-    NotFound(PlainText<String>),
+    NotFound(Json<ApiError>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum GetPathStatsResponse {
+    #[oai(status = 200)]
+    ApiPathStats(Json<ApiPathStats>),
+
+    #[oai(status = 404)]
+    NotFound(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum ResetResponse {
+    #[oai(status = 200)]
+    ApiStateReport(Json<ApiStateReport>),
+
+    #[oai(status = 404)]
+    NotFound(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum ListPathsResponse {
+    #[oai(status = 200)]
+    ApiPaths(Json<Vec<String>>),
+
+    #[oai(status = 200)]
+    ApiStateReports(Json<Vec<ApiStateReport>>),
+
+    #[oai(status = 200)]
+    ApiPathsSince(Json<ApiPathsSince>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum DeleteResponse {
+    #[oai(status = 200)]
+    Deleted(PlainText<String>),
+
+    #[oai(status = 404)]
+    NotFound(Json<ApiError>),
+
+    #[oai(status = 409)]
+    ConstraintViolation(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum GetSeriesResponse {
+    #[oai(status = 200)]
+    ApiSeries(Json<Vec<ApiObservations>>),
+
+    /// one `ApiObservations` per line, streamed as the journal is read
+    #[oai(status = 200, content_type = "application/x-ndjson")]
+    ApiSeriesLines(Binary<Body>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<ApiError>),
 
     #[oai(status = 500)]
-    InternalServerError(PlainText<String>),
+    InternalServerError(Json<ApiError>),
 }
 
 #[derive(ApiResponse)]
@@ -111,10 +671,10 @@ enum GetGeneMappingResponse {
     ApiGeneMapping(Json<ApiGeneMapping>),
 
     #[oai(status = 404)]
-    NotFound(PlainText<String>),
+    NotFound(Json<ApiError>),
 
     #[oai(status = 500)]
-    InternalServerError(PlainText<String>),
+    InternalServerError(Json<ApiError>),
 }
 
 #[derive(ApiResponse)]
@@ -122,18 +682,46 @@ enum PostGeneMappingResponse {
     #[oai(status = 200)]
     ApiGeneMapping(Json<ApiGeneMapping>),
 
+    #[oai(status = 400)]
+    BadRequest(Json<ApiError>),
+
     #[oai(status = 409)]
-    ConstraintViolation(PlainText<String>),
+    ConstraintViolation(Json<ApiError>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum ListGeneMappingsResponse {
+    #[oai(status = 200)]
+    ApiGeneMappings(Json<Vec<ApiGeneMapping>>),
+
+    #[oai(status = 500)]
+    InternalServerError(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+enum PostGeneMappingBatchResponse {
+    #[oai(status = 200)]
+    ApiGeneMappingResults(Json<Vec<ApiGeneMappingResult>>),
 
     #[oai(status = 500)]
-    InternalServerError(PlainText<String>),
+    InternalServerError(Json<ApiError>),
 }
 
-fn prepend_slash(mut s: String) -> String {
-    if !s.starts_with('/') {
-        s.insert(0, '/');
+/// the path of an actor as the CLI journals it - exactly one leading `/` and
+/// no repeated `/`, ie: `actors/one`, `/actors/one`, and `//actors//one` are
+/// all `/actors/one`.  a trailing `/` is kept so that a prefix stays a prefix.
+#[must_use]
+pub fn normalize_path(path: &str) -> String {
+    let mut normalized = String::with_capacity(path.len() + 1);
+    for c in std::iter::once('/').chain(path.chars()) {
+        if !(c == '/' && normalized.ends_with('/')) {
+            normalized.push(c);
+        }
     }
-    s
+    normalized
 }
 
 pub struct SharedHandle(Arc<Handle>);
@@ -163,113 +751,920 @@ impl<'a> FromRequest<'a> for SharedHandle {
     }
 }
 
+#[derive(Clone)]
+struct ApiLimits {
+    max_page_size: usize,
+    request_timeout: Duration,
+}
+
+/// the responses to the posts of observations that carried an
+/// `Idempotency-Key` by the path posted to and the key, kept for `window` so
+/// that a client retrying a post gets the first response instead of the
+/// observation being applied again.  a key is reserved before its post is
+/// applied so that a retry that arrives before the first post is answered
+/// is refused rather than applied a second time.
+#[derive(Clone)]
+struct IdempotencyCache {
+    window: Duration,
+    entries: Arc<Mutex<IdempotencyEntries>>,
+}
+
+type IdempotencyKey = (String, String);
+
+#[derive(Clone)]
+enum IdempotencyEntry {
+    /// the post of the key is being applied
+    InFlight,
+    Answered(PostObservationResponse),
+}
+
+#[derive(Default)]
+struct IdempotencyEntries {
+    responses: HashMap<IdempotencyKey, IdempotencyEntry>,
+    /// when each key was first seen, oldest first
+    seen: VecDeque<(Instant, IdempotencyKey)>,
+}
+
+impl IdempotencyEntries {
+    fn expire(&mut self, window: Duration) {
+        while self
+            .seen
+            .front()
+            .is_some_and(|(seen, _)| seen.elapsed() >= window)
+        {
+            if let Some((_, key)) = self.seen.pop_front() {
+                self.responses.remove(&key);
+            }
+        }
+    }
+}
+
+impl IdempotencyCache {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Arc::new(Mutex::new(IdempotencyEntries::default())),
+        }
+    }
+
+    /// the entry of `key` if it is still in the window, or a new
+    /// reservation of it for the post about to be applied
+    fn reserve(&self, key: IdempotencyKey) -> Result<ReservedKey, IdempotencyEntry> {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.expire(self.window);
+            if let Some(entry) = entries.responses.get(&key) {
+                return Err(entry.clone());
+            }
+            entries
+                .responses
+                .insert(key.clone(), IdempotencyEntry::InFlight);
+            entries.seen.push_back((Instant::now(), key.clone()));
+        }
+        Ok(ReservedKey {
+            cache: self.clone(),
+            key,
+            answered: false,
+        })
+    }
+}
+
+/// a key reserved for the post being applied - a post that is not answered
+/// with a response worth repeating, or is dropped before it is answered,
+/// releases the key for a retry
+struct ReservedKey {
+    cache: IdempotencyCache,
+    key: IdempotencyKey,
+    answered: bool,
+}
+
+impl ReservedKey {
+    fn answer(mut self, response: &PostObservationResponse) {
+        if let Ok(mut entries) = self.cache.entries.lock() {
+            if let Some(entry) = entries.responses.get_mut(&self.key) {
+                *entry = IdempotencyEntry::Answered(response.clone());
+                self.answered = true;
+            }
+        }
+    }
+}
+
+impl Drop for ReservedKey {
+    fn drop(&mut self) {
+        if self.answered {
+            return;
+        }
+        if let Ok(mut entries) = self.cache.entries.lock() {
+            if matches!(
+                entries.responses.get(&self.key),
+                Some(IdempotencyEntry::InFlight)
+            ) {
+                entries.responses.remove(&self.key);
+                entries.seen.retain(|(_, key)| key != &self.key);
+            }
+        }
+    }
+}
+
+/// one page of the ordered state and the number of the page after it, if any
+fn state_page(values: State<f64>, page: usize, page_size: usize) -> (State<f64>, Option<usize>) {
+    let skip = page.saturating_mul(page_size);
+    let next = if values.len() > skip.saturating_add(page_size) {
+        Some(page + 1)
+    } else {
+        None
+    };
+    let values = values.into_iter().skip(skip).take(page_size).collect();
+    (values, next)
+}
+
 struct ActorsApi;
 
+/// the `namespace` and `path` fields of the span of a request once its path
+/// is normalized
+fn record_path(path: &str) {
+    let span = Span::current();
+    span.record("namespace", metrics::namespace_of(path).as_str());
+    span.record("path", path);
+}
+
 #[OpenApi]
 impl ActorsApi {
-    #[oai(path = "/:namespace<.+/>:id", method = "get")]
-    async fn get_state(
+    /// the number of journaled observations of the actor, when the first and
+    /// the last of them were taken, and the version of its state - ie: to
+    /// find a sensor that stopped reporting without reading its history.
+    /// the state of an actor named `stats` can not be read
+    #[oai(path = "/:namespace<.+/>stats", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "PathStats"))]
+    async fn get_path_stats(
         &self,
         nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
         namespace: Path<String>,
-        id: Path<String>,
-    ) -> Result<GetStateResponse, poem::Error> {
-        let fullpath = format!("{}{}", namespace.as_str(), id.as_str());
-        let fullpath = prepend_slash(fullpath);
-        debug!("get state for {}", fullpath);
-        // query state of actor one from above updates
-        let cmd = Message::Query {
-            path: fullpath,
-            hint: MtHint::State,
+    ) -> Result<GetPathStatsResponse, poem::Error> {
+        let fullpath = normalize_path(namespace.trim_end_matches('/'));
+        record_path(&fullpath);
+        let id = fullpath.rsplit('/').next().unwrap_or_default().to_string();
+        debug!("get stats for {}", fullpath);
+        let cmd = Message::PathStats {
+            path: fullpath.clone(),
         };
-        match nv.ask(cmd).await {
-            Ok(Message::StateReport {
-                datetime: _,
-                path: _,
-                values,
-            }) if values.is_empty() => Ok(GetStateResponse::NotFound(PlainText(format!(
-                "No observations for id `{}`",
-                id.0
-            )))),
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::PathStatsReport {
+                path,
+                stats,
+                version,
+            }) => Ok(GetPathStatsResponse::ApiPathStats(Json(ApiPathStats {
+                path,
+                observations: stats.observations,
+                first: stats.first.map(|datetime| datetime.to_string()),
+                last: stats.last.map(|datetime| datetime.to_string()),
+                version,
+            }))),
+            Ok(Message::NotFound { .. }) => Ok(GetPathStatsResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&fullpath),
+                format!("No observations for id `{}`", id),
+            ))),
+            m => Ok(GetPathStatsResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for id {}: {:?}", id, m),
+            ))),
+        }
+    }
+
+    /// start the state of the actor over from empty and answer the empty
+    /// state, ie: to zero an accumulator at the rollover of a billing cycle -
+    /// unlike a delete the journal is kept and the reset is journaled among
+    /// the observations.  an actor can not be named `reset`.
+    #[oai(path = "/:namespace<.+/>reset", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Reset"))]
+    async fn post_reset(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+    ) -> Result<ResetResponse, poem::Error> {
+        let fullpath = normalize_path(namespace.trim_end_matches('/'));
+        record_path(&fullpath);
+        debug!("reset {}", fullpath);
+        let cmd = Message::Reset {
+            path: fullpath.clone(),
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
             Ok(Message::StateReport {
                 datetime,
                 path,
                 values,
-            }) => Ok(GetStateResponse::ApiStateReport(Json(ApiStateReport {
+                version,
+            }) => Ok(ResetResponse::ApiStateReport(Json(ApiStateReport {
                 datetime: datetime.to_string(),
                 path,
                 values,
+                version,
+                next: None,
+                labeled: None,
             }))),
-            m => Ok(GetStateResponse::InternalServerError(PlainText(format!(
-                "server error for id {}: {:?}",
-                id.0, m
-            )))),
+            Ok(Message::NotFound { .. }) => Ok(ResetResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&fullpath),
+                format!("No observations to reset for {fullpath}"),
+            ))),
+            m => Ok(ResetResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for {fullpath}: {m:?}"),
+            ))),
         }
     }
 
-    #[oai(path = "/:namespace<.+/>:id", method = "post")]
-    async fn post_observations(
+    /// the indices of the state are returned in order, `page_size` at a time
+    /// and never more than the server's `max_page_size` - a response that is
+    /// not the last page names the `next` page.  `bucket` selects one day,
+    /// ie: `2024-01-15`, of an actor with a day-scoped gene and an ISO 8601
+    /// `as_of` the state at that time, not found before the first observation.
+    /// with `labels=true` the values are also returned keyed by the index
+    /// labels of the namespace as `labeled`.  `idx`, ie: `?idx=1&idx=2`,
+    /// returns only those indices of the current state and leaves out the
+    /// ones the state does not have
+    #[allow(clippy::too_many_arguments)]
+    #[oai(path = "/:namespace<.+/>:id", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Query"))]
+    async fn get_state(
         &self,
         nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
         namespace: Path<String>,
         id: Path<String>,
-        body: Json<ApiObservations>,
-    ) -> Result<PostObservationResponse, poem::Error> {
-        let ns = namespace.trim_end_matches('/').to_string();
-        let ns = prepend_slash(ns);
-        debug!("post observations {}/{}", ns, id.as_str());
-        // record observation
-        if let Ok(dt) = extract_datetime(&body.0.datetime) {
-            let cmd = Message::Observations {
-                path: body.0.path,
-                datetime: dt,
-                values: body.0.values,
-            };
-
-            match nv.ask(cmd).await {
-                Ok(Message::StateReport {
-                    datetime: _,
-                    path: _,
-                    values,
-                }) if values.is_empty() => Ok(PostObservationResponse::NotFound(PlainText(
-                    format!("No actor resurected with id `{}`", id.0),
-                ))),
-                Ok(Message::StateReport {
-                    datetime,
-                    path,
-                    values,
-                }) => Ok(PostObservationResponse::ApiStateReport(Json(
-                    ApiStateReport {
-                        datetime: datetime.to_string(),
-                        path,
-                        values,
-                    },
-                ))),
-                Ok(Message::ConstraintViolation {}) => {
-                    Ok(PostObservationResponse::ConstraintViolation(PlainText(
-                        format!("contraint violation with id {}", id.0),
+        page: Query<Option<usize>>,
+        page_size: Query<Option<usize>>,
+        bucket: Query<Option<String>>,
+        as_of: Query<Option<String>>,
+        labels: Query<Option<bool>>,
+        idx: Query<Vec<i32>>,
+    ) -> Result<GetStateResponse, poem::Error> {
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
+        debug!("get state for {}", fullpath);
+        let indices = idx.0;
+        if !indices.is_empty() && (bucket.is_some() || as_of.is_some()) {
+            return Ok(GetStateResponse::BadRequest(ApiError::json(
+                ApiErrorCode::BadRequest,
+                Some(&fullpath),
+                String::from("idx can not be given with bucket or as_of"),
+            )));
+        }
+        let hint = match (bucket.0, as_of.0) {
+            (Some(_), Some(_)) => {
+                return Ok(GetStateResponse::BadRequest(ApiError::json(
+                    ApiErrorCode::BadRequest,
+                    Some(&fullpath),
+                    String::from("bucket and as_of can not both be given"),
+                )))
+            }
+            (None, Some(as_of)) => match extract_datetime(&as_of) {
+                Ok(datetime) => MtHint::StateAsOf { datetime },
+                Err(e) => {
+                    return Ok(GetStateResponse::BadRequest(ApiError::json(
+                        ApiErrorCode::BadRequest,
+                        Some(&fullpath),
+                        format!("as_of is not an ISO 8601 time: {}", e.reason),
                     )))
                 }
-                e => Ok(PostObservationResponse::InternalServerError(PlainText(
-                    format!("server error with id {}: {:?}", id.0, e),
-                ))),
+            },
+            (Some(day), None) => {
+                match Date::parse(&day, format_description!("[year]-[month]-[day]")) {
+                    Ok(day) => MtHint::Bucket(day),
+                    Err(e) => {
+                        return Ok(GetStateResponse::BadRequest(ApiError::json(
+                            ApiErrorCode::BadRequest,
+                            Some(&fullpath),
+                            format!("cannot parse bucket {day}: {e}"),
+                        )))
+                    }
+                }
+            }
+            (None, None) => MtHint::State,
+        };
+        // query state of actor one from above updates
+        let filtered = !indices.is_empty();
+        let cmd = if filtered {
+            Message::IndicesQuery {
+                path: fullpath.clone(),
+                indices,
             }
         } else {
-            // TODO: how can this be located near the parse???
-            Ok(PostObservationResponse::BadRequest(PlainText(format!(
-                "cannot parse datetime {} for id {}",
-                body.0.datetime, id.0
-            ))))
+            Message::Query {
+                path: fullpath.clone(),
+                hint,
+            }
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            // the indices asked for may all be missing from a state that has observations
+            Ok(Message::StateReport {
+                values, version, ..
+            }) if values.is_empty() && (!filtered || version == 0) => {
+                Ok(GetStateResponse::NotFound(ApiError::json(
+                    ApiErrorCode::NotFound,
+                    Some(&fullpath),
+                    format!("No observations for id `{}`", id.0),
+                )))
+            }
+            Ok(Message::StateReport {
+                datetime,
+                path,
+                values,
+                version,
+            }) => {
+                let page_size = page_size
+                    .0
+                    .unwrap_or(limits.max_page_size)
+                    .clamp(1, limits.max_page_size.max(1));
+                let page = page.0.unwrap_or(0);
+                let (values, next) = state_page(values, page, page_size);
+                let labeled = if labels.0.unwrap_or(false) {
+                    let query = Message::Query {
+                        path: fullpath.clone(),
+                        hint: MtHint::IndexLabels,
+                    };
+                    match nv.ask_timeout(query, limits.request_timeout).await {
+                        Ok(Message::IndexLabels { labels }) => Some(label_values(&values, &labels)),
+                        m => {
+                            return Ok(GetStateResponse::InternalServerError(ApiError::json(
+                                ApiErrorCode::ServerError,
+                                Some(&fullpath),
+                                format!("cannot get the index labels: {m:?}"),
+                            )))
+                        }
+                    }
+                } else {
+                    None
+                };
+                Ok(GetStateResponse::ApiStateReport(Json(ApiStateReport {
+                    datetime: datetime.to_string(),
+                    path,
+                    values,
+                    version,
+                    next,
+                    labeled,
+                })))
+            }
+            Ok(Message::NotFound { .. }) => Ok(GetStateResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&fullpath),
+                format!("No observations in bucket for id `{}`", id.0),
+            ))),
+            m => Ok(GetStateResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for id {}: {:?}", id.0, m),
+            ))),
+        }
+    }
+
+    /// the paths with state under a namespace in path order, `offset` paths
+    /// into the list and at most `limit` of them - never more than the
+    /// server's `max_page_size`.  with `state` the state of each path is
+    /// returned instead of its name, ie: to aggregate a tree of sensors.
+    /// with an ISO 8601 `since` only the paths with observations journaled
+    /// after it are listed along with the `sequence` to pass as `since` on
+    /// the next sync
+    #[allow(clippy::too_many_arguments)]
+    #[oai(path = "/:namespace<.+/>", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Query"))]
+    async fn list_paths(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+        limit: Query<Option<usize>>,
+        offset: Query<Option<usize>>,
+        state: Query<Option<bool>>,
+        since: Query<Option<String>>,
+    ) -> Result<ListPathsResponse, poem::Error> {
+        let prefix = normalize_path(&namespace.0);
+        record_path(&prefix);
+        debug!("list paths under {prefix}");
+        let limit = limit
+            .0
+            .unwrap_or(limits.max_page_size)
+            .clamp(1, limits.max_page_size.max(1));
+        let offset = offset.0.unwrap_or(0);
+        let since = match since.0.as_deref().map(extract_datetime).transpose() {
+            Ok(since) => since,
+            Err(e) => {
+                return Ok(ListPathsResponse::BadRequest(ApiError::json(
+                    ApiErrorCode::BadRequest,
+                    Some(&prefix),
+                    format!("since is not an ISO 8601 time: {}", e.reason),
+                )));
+            }
+        };
+        let hint = match (state.0.unwrap_or(false), since) {
+            (true, Some(_)) => {
+                return Ok(ListPathsResponse::BadRequest(ApiError::json(
+                    ApiErrorCode::BadRequest,
+                    Some(&prefix),
+                    "since lists paths and not their state",
+                )));
+            }
+            (true, None) => MtHint::Descendants { limit, offset },
+            (false, since) => MtHint::ListPaths {
+                limit,
+                offset,
+                since,
+            },
+        };
+        let cmd = Message::Query {
+            path: prefix.clone(),
+            hint,
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::PathList {
+                paths,
+                sequence: Some(sequence),
+            }) => Ok(ListPathsResponse::ApiPathsSince(Json(ApiPathsSince {
+                paths,
+                sequence: sequence
+                    .format(&Iso8601::DEFAULT)
+                    .unwrap_or_else(|_| sequence.to_string()),
+            }))),
+            Ok(Message::PathList { paths, .. }) => Ok(ListPathsResponse::ApiPaths(Json(paths))),
+            Ok(Message::StateReports { reports, .. }) => {
+                Ok(ListPathsResponse::ApiStateReports(Json(
+                    reports
+                        .into_iter()
+                        .filter_map(|report| match report {
+                            Message::StateReport {
+                                datetime,
+                                path,
+                                values,
+                                version,
+                            } => Some(ApiStateReport {
+                                datetime: datetime.to_string(),
+                                path,
+                                values,
+                                version,
+                                next: None,
+                                labeled: None,
+                            }),
+                            _ => None,
+                        })
+                        .collect(),
+                )))
+            }
+            m => Ok(ListPathsResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&prefix),
+                format!("server error for {prefix}: {m:?}"),
+            ))),
+        }
+    }
+
+    /// a post with an `Idempotency-Key` that repeats the key of a post to the
+    /// same path within the server's idempotency window is answered with the
+    /// response to that post and the observation is not applied again - a
+    /// post that failed with a server error or was rate limited is not
+    /// remembered, and a repeat of a post that is still being applied is a
+    /// conflict to retry.  a post with an `If-Match` version is applied only if the
+    /// actor is still at that version and is a conflict otherwise.
+    #[allow(clippy::too_many_arguments)]
+    #[oai(path = "/:namespace<.+/>:id", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Observations"))]
+    async fn post_observations(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        idempotency: Data<&IdempotencyCache>,
+        namespace: Path<String>,
+        id: Path<String>,
+        #[oai(name = "Idempotency-Key")] idempotency_key: Header<Option<String>>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        body: Json<ApiObservations>,
+    ) -> Result<PostObservationResponse, poem::Error> {
+        let started = Instant::now();
+        let ns = normalize_path(namespace.trim_end_matches('/'));
+        let fullpath = normalize_path(&format!("{ns}/{}", id.as_str()));
+        record_path(&fullpath);
+        debug!("post observations {fullpath}");
+        let reserved = match idempotency_key
+            .0
+            .map(|key| idempotency.reserve((fullpath.clone(), key)))
+        {
+            None => None,
+            Some(Ok(reserved)) => Some(reserved),
+            Some(Err(IdempotencyEntry::Answered(response))) => {
+                debug!("repeated post to {fullpath}");
+                return Ok(response);
+            }
+            Some(Err(IdempotencyEntry::InFlight)) => {
+                debug!("repeated post to {fullpath} is in flight");
+                return Ok(PostObservationResponse::ConstraintViolation(
+                    ApiError::json(
+                        ApiErrorCode::IdempotencyKeyInFlight,
+                        Some(&normalize_path(&body.0.path)),
+                        format!(
+                            "a post with the Idempotency-Key of id {} is in flight",
+                            id.0
+                        ),
+                    ),
+                ));
+            }
+        };
+        // an entity tag is quoted but a bare version is accepted too
+        let if_match = match if_match
+            .0
+            .as_deref()
+            .map(|v| v.trim().trim_matches('"').parse())
+        {
+            None => None,
+            Some(Ok(version)) => Some(version),
+            Some(Err(_)) => {
+                return Ok(PostObservationResponse::BadRequest(ApiError::json(
+                    ApiErrorCode::BadRequest,
+                    Some(&normalize_path(&body.0.path)),
+                    format!("If-Match is not a version for id {}", id.0),
+                )));
+            }
+        };
+        let response = self
+            .record_observations(nv, limits, &id, if_match, body)
+            .await;
+        metrics::observe_latency(&ns, started.elapsed());
+        if let (Some(reserved), Ok(response)) = (reserved, &response) {
+            if !matches!(
+                response,
+                PostObservationResponse::InternalServerError(_)
+                    | PostObservationResponse::TooManyRequests(_)
+            ) {
+                reserved.answer(response);
+            }
+        }
+        response
+    }
+
+    /// the observations are applied in order and every one is tried - a
+    /// duplicate or a failure is counted and the rest of the batch goes on.
+    /// an actor can not be named `batch`.
+    #[oai(path = "/:namespace<.+/>batch", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Observations"))]
+    async fn post_observations_batch(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+        body: Json<Vec<ApiObservations>>,
+    ) -> Result<BatchPostResponse, poem::Error> {
+        record_path(&normalize_path(&namespace.0));
+        debug!(
+            "post batch of {} observations to {}",
+            body.0.len(),
+            namespace.as_str()
+        );
+        let mut summary = ApiBatchSummary {
+            accepted: 0,
+            duplicates: 0,
+            errors: 0,
+        };
+        for observation in body.0 {
+            let Ok(cmd) = observation.validate() else {
+                summary.errors += 1;
+                continue;
+            };
+            match nv.ask_timeout(cmd, limits.request_timeout).await {
+                Ok(Message::StateReport { .. }) => summary.accepted += 1,
+                Ok(Message::ConstraintViolation) => summary.duplicates += 1,
+                _ => summary.errors += 1,
+            }
+        }
+        Ok(BatchPostResponse::ApiBatchSummary(Json(summary)))
+    }
+
+    /// the metrics need every outcome of a post so the work is done here and
+    /// timed by `post_observations`
+    async fn record_observations(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        id: &Path<String>,
+        if_match: Option<u64>,
+        body: Json<ApiObservations>,
+    ) -> Result<PostObservationResponse, poem::Error> {
+        let path = normalize_path(&body.0.path);
+        let dt = body.0.datetime.clone();
+        let observations = match body.0.validate() {
+            Ok(observations) => observations,
+            Err(e) => {
+                return Ok(PostObservationResponse::BadRequest(ApiError::json(
+                    (&e).into(),
+                    Some(&path),
+                    format!("{e} for id {}", id.0),
+                )))
+            }
+        };
+        let cmd = match if_match {
+            Some(version) => Message::ConditionalObservations {
+                version,
+                observations: Box::new(observations),
+            },
+            None => observations,
+        };
+
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::StateReport { values, .. }) if values.is_empty() => {
+                Ok(PostObservationResponse::NotFound(ApiError::json(
+                    ApiErrorCode::NotFound,
+                    Some(&path),
+                    format!("No actor resurected with id `{}`", id.0),
+                )))
+            }
+            Ok(Message::StateReport {
+                datetime,
+                path,
+                values,
+                version,
+            }) => Ok(PostObservationResponse::ApiStateReport(Json(
+                ApiStateReport {
+                    datetime: datetime.to_string(),
+                    path,
+                    values,
+                    version,
+                    next: None,
+                    labeled: None,
+                },
+            ))),
+            Ok(Message::ConstraintViolation) => Ok(PostObservationResponse::ConstraintViolation(
+                ApiError::json(
+                    ApiErrorCode::Duplicate,
+                    Some(&path),
+                    format!("duplicate ignored, {path} already has an observation at {dt}"),
+                ),
+            )),
+            Ok(late @ Message::LateReport { .. }) => {
+                Ok(PostObservationResponse::ConstraintViolation(
+                    ApiError::json(ApiErrorCode::LateObservation, Some(&path), late.to_string()),
+                ))
+            }
+            Ok(Message::VersionConflict { path, version }) => Ok(
+                PostObservationResponse::ConstraintViolation(ApiError::json(
+                    ApiErrorCode::VersionConflict,
+                    Some(&path),
+                    format!("{path} is at version {version}"),
+                )),
+            ),
+            Ok(Message::RateLimited { path }) => {
+                Ok(PostObservationResponse::TooManyRequests(ApiError::json(
+                    ApiErrorCode::RateLimited,
+                    Some(&path),
+                    format!("{path} is over its rate limit"),
+                )))
+            }
+            e => Ok(PostObservationResponse::InternalServerError(
+                ApiError::json(
+                    ApiErrorCode::ServerError,
+                    Some(&path),
+                    format!("server error with id {}: {:?}", id.0, e),
+                ),
+            )),
+        }
+    }
+
+    /// remove the state and journal of an actor - `mapping=true` also
+    /// removes the gene mapping of the path
+    #[oai(path = "/:namespace<.+/>:id", method = "delete")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Delete"))]
+    async fn delete(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+        id: Path<String>,
+        mapping: Query<Option<bool>>,
+    ) -> Result<DeleteResponse, poem::Error> {
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
+        debug!("delete {fullpath}");
+        let cmd = Message::Delete {
+            path: fullpath.clone(),
+            mapping: mapping.0.unwrap_or(false),
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::Delete { path, .. }) => Ok(DeleteResponse::Deleted(PlainText(format!(
+                "{path} deleted"
+            )))),
+            Ok(Message::NotFound { .. }) => Ok(DeleteResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&fullpath),
+                format!("No observations for id `{}`", id.0),
+            ))),
+            m => Ok(DeleteResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for id {}: {:?}", id.0, m),
+            ))),
         }
     }
 }
 
+/// the `label.<name>=<value>` query params of a request
+fn extract_label_filter(req: &Request) -> Result<Labels, String> {
+    let params: Vec<(String, String)> = req.params().map_err(|e| e.to_string())?;
+    Ok(params
+        .into_iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix("label.")
+                .map(|label| (label.to_string(), value))
+        })
+        .collect())
+}
+
+/// the content type of a series streamed one observation per line
+const NDJSON: &str = "application/x-ndjson";
+
+/// how many observations of a streamed series may wait for a slow client
+/// before the journal is read further
+const SERIES_BUFFER: usize = 64;
+
+fn accepts_ndjson(req: &Request) -> bool {
+    req.headers()
+        .get_all(poem::http::header::ACCEPT)
+        .iter()
+        .filter_map(|accept| accept.to_str().ok())
+        .flat_map(|accept| accept.split(','))
+        .any(|media_type| media_type.trim().starts_with(NDJSON))
+}
+
+fn observation_line(observation: Message<f64>) -> std::io::Result<String> {
+    match observation {
+        Message::Observations {
+            datetime,
+            path,
+            values,
+            labels,
+        } => {
+            let mut line = ApiObservations {
+                datetime: datetime.to_string(),
+                values,
+                path,
+                labels,
+            }
+            .to_json_string();
+            line.push('\n');
+            Ok(line)
+        }
+        Message::Error { reason, .. } => Err(std::io::Error::other(reason)),
+        m => Err(std::io::Error::other(format!("unexpected {m} in a series"))),
+    }
+}
+
+/// stream the series of `path` with a status that is only sent once its
+/// first row, or why there is none, is read - a later error ends the body
+/// early rather than completing it
+async fn series_lines(
+    nv: &SharedHandle,
+    limits: &ApiLimits,
+    path: String,
+    labels: Labels,
+) -> GetSeriesResponse {
+    let server_error = |reason: String| {
+        GetSeriesResponse::InternalServerError(ApiError::json(
+            ApiErrorCode::ServerError,
+            Some(&path),
+            reason,
+        ))
+    };
+    let (stream_to, mut rows) = mpsc::channel(SERIES_BUFFER);
+    let cmd = Message::SeriesStream {
+        path: path.clone(),
+        labels,
+        stream_to,
+    };
+    if let Err(e) = nv.tell(cmd).await {
+        return server_error(format!("cannot stream the series of {path}: {e}"));
+    }
+    let first = match tokio::time::timeout(limits.request_timeout, rows.recv()).await {
+        Ok(Some(Message::Error { reason, .. })) => return server_error(reason),
+        Ok(Some(first)) => first,
+        Ok(None) => return server_error(format!("the series of {path} was not streamed")),
+        Err(_) => return server_error(format!("the series of {path} timed out")),
+    };
+    debug!("streaming the series of {path}");
+    let lines = futures::stream::unfold((Some(first), Some(rows)), |(first, rows)| async move {
+        let mut rows = rows?;
+        let next = match first {
+            Some(first) => Some(first),
+            None => rows.recv().await,
+        };
+        let line = match next {
+            Some(Message::EndOfStream {}) => return None,
+            Some(observation) => observation_line(observation),
+            None => Err(std::io::Error::other("the series stream ended early")),
+        };
+        // nothing follows an error
+        let rows = line.is_ok().then_some(rows);
+        Some((line, (None, rows)))
+    });
+    GetSeriesResponse::ApiSeriesLines(Binary(Body::from_bytes_stream(lines)))
+}
+
+struct SeriesApi;
+
+#[OpenApi]
+impl SeriesApi {
+    /// the journaled observations of an actor in timestamp order, filtered
+    /// to those carrying every `label.<name>=<value>` query param.  with
+    /// `stream=true`, or an `Accept: application/x-ndjson` header, they are
+    /// streamed as NDJSON lines as the journal is read rather than returned
+    /// as one array, ie: to export a long series
+    #[allow(clippy::too_many_arguments)]
+    #[oai(path = "/:namespace<.+/>:id", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "SeriesQuery"))]
+    async fn get_series(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        req: &Request,
+        namespace: Path<String>,
+        id: Path<String>,
+        stream: Query<Option<bool>>,
+    ) -> Result<GetSeriesResponse, poem::Error> {
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
+        debug!("get series for {fullpath}");
+        let labels = match extract_label_filter(req) {
+            Ok(labels) => labels,
+            Err(e) => {
+                return Ok(GetSeriesResponse::BadRequest(ApiError::json(
+                    ApiErrorCode::BadRequest,
+                    Some(&fullpath),
+                    format!("cannot parse label filter for id {}: {e}", id.0),
+                )))
+            }
+        };
+
+        if stream.0.unwrap_or(false) || accepts_ndjson(req) {
+            return Ok(series_lines(&nv, &limits, fullpath, labels).await);
+        }
+
+        let cmd = Message::SeriesQuery {
+            path: fullpath.clone(),
+            labels,
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::Series { observations, .. }) => Ok(GetSeriesResponse::ApiSeries(Json(
+                observations
+                    .into_iter()
+                    .filter_map(|observation| match observation {
+                        Message::Observations {
+                            datetime,
+                            path,
+                            values,
+                            labels,
+                        } => Some(ApiObservations {
+                            datetime: datetime.to_string(),
+                            values,
+                            path,
+                            labels,
+                        }),
+                        _ => None,
+                    })
+                    .collect(),
+            ))),
+            m => Ok(GetSeriesResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for id {}: {:?}", id.0, m),
+            ))),
+        }
+    }
+}
+
+/// an unknown gene type is a `GaugeAndAccum`
 fn extract_gene_type(gene_type_str: &str) -> GeneType {
-    match gene_type_str {
+    parse_gene_type(gene_type_str).unwrap_or(GeneType::GaugeAndAccum)
+}
+
+fn parse_gene_type(gene_type_str: &str) -> Option<GeneType> {
+    Some(match gene_type_str {
         "Gauge" => GeneType::Gauge,
+        "Default" => GeneType::Default,
         "Accum" => GeneType::Accum,
-        _ => GeneType::GaugeAndAccum,
-    }
+        "Latest" => GeneType::Latest,
+        "DailyAccum" => GeneType::DailyAccum,
+        "Min" => GeneType::Min,
+        "Max" => GeneType::Max,
+        "Mean" => GeneType::Mean,
+        "Rate" => GeneType::Rate,
+        "Ewma" => GeneType::Ewma,
+        "MovingAvg" => GeneType::MovingAvg,
+        "Counter" => GeneType::Counter,
+        "Aggregate" => GeneType::Aggregate,
+        "Alert" => GeneType::Alert,
+        "Composite" => GeneType::Composite,
+        "Delta" => GeneType::Delta,
+        "GaugeAndAccum" => GeneType::GaugeAndAccum,
+        _ => return None,
+    })
 }
 
 struct GenesApi;
@@ -277,70 +1672,304 @@ struct GenesApi;
 #[OpenApi]
 impl GenesApi {
     #[oai(path = "/:namespace<.+/>:id", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Content"))]
     async fn get_gene(
         &self,
         nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
         namespace: Path<String>,
         id: Path<String>,
     ) -> Result<GetGeneMappingResponse, poem::Error> {
-        let fullpath = format!("{}{}", namespace.as_str(), id.as_str());
-        let fullpath = prepend_slash(fullpath);
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
         debug!("get gene for {}", fullpath);
         // query state of actor one from above updates
         let cmd: Message<f64> = Message::Content {
-            path: Some(fullpath),
+            path: Some(fullpath.clone()),
             text: String::new(),
             hint: MtHint::GeneMappingQuery,
         };
-        match nv.ask(cmd).await {
-            Ok(Message::GeneMapping { path, gene_type }) => Ok(
-                GetGeneMappingResponse::ApiGeneMapping(Json(ApiGeneMapping {
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::GeneMapping {
+                path,
+                gene_type,
+                params,
+            }) => Ok(GetGeneMappingResponse::ApiGeneMapping(Json(
+                ApiGeneMapping {
                     path,
                     gene_type: gene_type.to_string(),
-                })),
-            ),
-            Ok(Message::NotFound { path }) => Ok(GetGeneMappingResponse::NotFound(PlainText(
+                    params: api_gene_params(params),
+                },
+            ))),
+            Ok(Message::NotFound { path }) => Ok(GetGeneMappingResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&path),
                 format!("No gene mapping for `{path}`"),
             ))),
 
-            m => Ok(GetGeneMappingResponse::InternalServerError(PlainText(
+            m => Ok(GetGeneMappingResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
                 format!("server error for path {}: {:?}", id.0, m),
             ))),
         }
     }
 
+    /// the mappings of a namespace and of every path under it in path order
+    #[oai(path = "/:namespace<.+/>", method = "get")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "Query"))]
+    async fn list_genes(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+    ) -> Result<ListGeneMappingsResponse, poem::Error> {
+        let prefix = normalize_path(&namespace.0);
+        record_path(&prefix);
+        debug!("list gene mappings under {prefix}");
+        let cmd: Message<f64> = Message::Query {
+            path: prefix.clone(),
+            hint: MtHint::GeneMappingList,
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::GeneMappingList { mappings }) => {
+                Ok(ListGeneMappingsResponse::ApiGeneMappings(Json(
+                    mappings
+                        .into_iter()
+                        .map(|(path, gene_type, params)| ApiGeneMapping {
+                            path,
+                            gene_type: gene_type.to_string(),
+                            params: api_gene_params(params),
+                        })
+                        .collect(),
+                )))
+            }
+            m => Ok(ListGeneMappingsResponse::InternalServerError(
+                ApiError::json(
+                    ApiErrorCode::ServerError,
+                    Some(&prefix),
+                    format!("server error for {prefix}: {m:?}"),
+                ),
+            )),
+        }
+    }
+
+    /// the actors of the path keep their gene until they are resurrected - a
+    /// mapping every namespace shares is not deleted by one of them
+    #[oai(path = "/:namespace<.+/>:id", method = "delete")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "DeleteGeneMapping"))]
+    async fn delete_gene_mapping(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+        id: Path<String>,
+    ) -> Result<DeleteResponse, poem::Error> {
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
+        debug!("delete gene mapping for {fullpath}");
+        let cmd = Message::DeleteGeneMapping {
+            path: fullpath.clone(),
+        };
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::DeleteGeneMapping { path }) => Ok(DeleteResponse::Deleted(PlainText(
+                format!("gene mapping of {path} deleted"),
+            ))),
+            Ok(Message::NotFound { path }) => Ok(DeleteResponse::NotFound(ApiError::json(
+                ApiErrorCode::NotFound,
+                Some(&path),
+                format!("No gene mapping for `{path}`"),
+            ))),
+            Ok(Message::ConstraintViolation) => {
+                Ok(DeleteResponse::ConstraintViolation(ApiError::json(
+                    ApiErrorCode::SharedMapping,
+                    Some(&fullpath),
+                    format!("The gene mapping of `{fullpath}` is shared by every namespace"),
+                )))
+            }
+            m => Ok(DeleteResponse::InternalServerError(ApiError::json(
+                ApiErrorCode::ServerError,
+                Some(&fullpath),
+                format!("server error for id {}: {:?}", id.0, m),
+            ))),
+        }
+    }
+
     #[oai(path = "/:namespace<.+/>:id", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "GeneMapping"))]
     async fn post_gene_mapping(
         &self,
         nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
         namespace: Path<String>,
         id: Path<String>,
         body: Json<ApiGeneMapping>,
     ) -> Result<PostGeneMappingResponse, poem::Error> {
-        let fullpath = format!("{}{}", namespace.as_str(), id.as_str());
-        let fullpath = prepend_slash(fullpath);
+        let fullpath = normalize_path(&format!("{}{}", namespace.as_str(), id.as_str()));
+        record_path(&fullpath);
         debug!("post gene mapping for {fullpath}");
 
+        let gene_type = extract_gene_type(&body.0.gene_type);
+        let params: GeneParams = body.0.params.map(Into::into).unwrap_or_default();
+        if let Err(e) = director::configured_gene::<f64>(gene_type, &params) {
+            return Ok(PostGeneMappingResponse::BadRequest(ApiError::json(
+                ApiErrorCode::BadRequest,
+                Some(&fullpath),
+                e.reason,
+            )));
+        }
         let cmd = Message::GeneMapping {
-            path: fullpath,
-            gene_type: extract_gene_type(&body.0.gene_type),
+            path: fullpath.clone(),
+            gene_type,
+            params,
         };
 
-        match nv.ask(cmd).await {
-            Ok(Message::GeneMapping { path, gene_type }) => Ok(
-                PostGeneMappingResponse::ApiGeneMapping(Json(ApiGeneMapping {
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::GeneMapping {
+                path,
+                gene_type,
+                params,
+            }) => Ok(PostGeneMappingResponse::ApiGeneMapping(Json(
+                ApiGeneMapping {
                     path,
                     gene_type: gene_type.to_string(),
-                })),
-            ),
-            Ok(Message::ConstraintViolation {}) => {
-                Ok(PostGeneMappingResponse::ConstraintViolation(PlainText(
+                    params: api_gene_params(params),
+                },
+            ))),
+            Ok(Message::ConstraintViolation) => Ok(PostGeneMappingResponse::ConstraintViolation(
+                ApiError::json(
+                    ApiErrorCode::Duplicate,
+                    Some(&fullpath),
                     format!("contraint violation with id {}", id.0),
+                ),
+            )),
+            e => Ok(PostGeneMappingResponse::InternalServerError(
+                ApiError::json(
+                    ApiErrorCode::ServerError,
+                    Some(&fullpath),
+                    format!("server error with id {}: {:?}", id.0, e),
+                ),
+            )),
+        }
+    }
+
+    /// every mapping is applied in one store transaction and reported
+    /// individually - a failed or duplicate entry does not stop the rest
+    #[oai(path = "/batch", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "GeneMappingBatch"))]
+    async fn post_gene_mapping_batch(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        body: Json<Vec<ApiGeneMapping>>,
+    ) -> Result<PostGeneMappingBatchResponse, poem::Error> {
+        debug!("post gene mapping batch of {}", body.0.len());
+        self.apply_gene_mapping_batch(nv, limits, None, body.0)
+            .await
+    }
+
+    /// the batch of mappings of the paths under a namespace - a mapping of a
+    /// path outside of it is reported failed like an unknown gene type.  a
+    /// path can not be named `batch`.
+    #[oai(path = "/:namespace<.+/>batch", method = "post")]
+    #[instrument(skip_all, fields(namespace = Empty, path = Empty, msg = "GeneMappingBatch"))]
+    async fn post_namespace_gene_mapping_batch(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        namespace: Path<String>,
+        body: Json<Vec<ApiGeneMapping>>,
+    ) -> Result<PostGeneMappingBatchResponse, poem::Error> {
+        let prefix = normalize_path(&namespace.0);
+        record_path(&prefix);
+        debug!("post gene mapping batch of {} under {prefix}", body.0.len());
+        self.apply_gene_mapping_batch(nv, limits, Some(&prefix), body.0)
+            .await
+    }
+
+    /// the mappings that name a known gene type, and are under `prefix` when
+    /// there is one, are sent to the director as one batch and the rest are
+    /// reported failed in their place
+    async fn apply_gene_mapping_batch(
+        &self,
+        nv: Data<&SharedHandle>,
+        limits: Data<&ApiLimits>,
+        prefix: Option<&str>,
+        mappings: Vec<ApiGeneMapping>,
+    ) -> Result<PostGeneMappingBatchResponse, poem::Error> {
+        let checked: Vec<Result<(String, GeneType, GeneParams), ApiGeneMappingResult>> = mappings
+            .into_iter()
+            .map(|m| {
+                let path = normalize_path(&m.path);
+                let error = match (prefix, parse_gene_type(&m.gene_type)) {
+                    (Some(prefix), _)
+                        if !path.starts_with(prefix) && path != prefix.trim_end_matches('/') =>
+                    {
+                        format!("{path} is not under {prefix}")
+                    }
+                    (_, None) => format!("unknown gene type {}", m.gene_type),
+                    (_, Some(gene_type)) => {
+                        return Ok((
+                            path,
+                            gene_type,
+                            m.params.map(Into::into).unwrap_or_default(),
+                        ))
+                    }
+                };
+                Err(ApiGeneMappingResult {
+                    path,
+                    gene_type: m.gene_type,
+                    error: Some(error),
+                })
+            })
+            .collect();
+        let requested: Vec<(String, GeneType, GeneParams)> = checked
+            .iter()
+            .filter_map(|m| m.as_ref().ok().cloned())
+            .collect();
+        let cmd = Message::GeneMappingBatch {
+            mappings: requested,
+        };
+
+        match nv.ask_timeout(cmd, limits.request_timeout).await {
+            Ok(Message::GeneMappingBatchReport { results }) => {
+                let mut results = results.into_iter();
+                Ok(PostGeneMappingBatchResponse::ApiGeneMappingResults(Json(
+                    checked
+                        .into_iter()
+                        .map(|m| {
+                            let (path, requested, _) = match m {
+                                Ok(m) => m,
+                                Err(failed) => return failed,
+                            };
+                            match results.next() {
+                                Some((path, Ok(gene_type))) => ApiGeneMappingResult {
+                                    path,
+                                    gene_type: gene_type.to_string(),
+                                    error: None,
+                                },
+                                Some((path, Err(e))) => ApiGeneMappingResult {
+                                    path,
+                                    gene_type: requested.to_string(),
+                                    error: Some(e),
+                                },
+                                None => ApiGeneMappingResult {
+                                    path,
+                                    gene_type: requested.to_string(),
+                                    error: Some(String::from("not reported by the director")),
+                                },
+                            }
+                        })
+                        .collect(),
                 )))
             }
-            e => Ok(PostGeneMappingResponse::InternalServerError(PlainText(
-                format!("server error with id {}: {:?}", id.0, e),
-            ))),
+            e => Ok(PostGeneMappingBatchResponse::InternalServerError(
+                ApiError::json(
+                    ApiErrorCode::ServerError,
+                    None,
+                    format!("server error for gene mapping batch: {e:?}"),
+                ),
+            )),
         }
     }
 }
@@ -351,53 +1980,471 @@ impl Clone for SharedHandle {
     }
 }
 
-/// start a server on port and interface
+/// liveness probe - the server is up if it can answer at all
+#[handler]
+const fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+/// readiness probe - the director and the store it journals to must answer a
+/// `Ping` within the request timeout
+#[handler]
+async fn ready(nv: Data<&SharedHandle>, limits: Data<&ApiLimits>) -> StatusCode {
+    match nv.ask_timeout(Message::Ping, limits.request_timeout).await {
+        Ok(Message::Pong) => StatusCode::OK,
+        m => {
+            debug!("not ready: {m:?}");
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+}
+
+/// how many state reports may wait for a slow websocket client before reports
+/// are dropped
+const SUBSCRIPTION_BUFFER: usize = 16;
+
+fn state_report_json(report: Message<f64>) -> Option<String> {
+    match report {
+        Message::StateReport {
+            datetime,
+            path,
+            values,
+            version,
+        } => Some(
+            ApiStateReport {
+                datetime: datetime.to_string(),
+                path,
+                values,
+                version,
+                next: None,
+                labeled: None,
+            }
+            .to_json_string(),
+        ),
+        _ => None,
+    }
+}
+
+fn ws_frame(report: Message<f64>) -> Option<WsMessage> {
+    state_report_json(report).map(WsMessage::Text)
+}
+
+/// how many events may wait for a slow events client before it is dropped
+const EVENTS_BUFFER: usize = 64;
+
+/// how often an idle event stream is sent a comment to keep it open
+const EVENTS_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+fn sse_event(event: Message<f64>) -> Option<SseEvent> {
+    match event {
+        Message::Event { id, report } => {
+            state_report_json(*report).map(|data| SseEvent::message(data).id(id.to_string()))
+        }
+        _ => None,
+    }
+}
+
+fn sse_alert(event: Message<f64>) -> Option<SseEvent> {
+    match event {
+        Message::Event { id, report } => match *report {
+            Message::Alert {
+                path,
+                idx,
+                value,
+                threshold,
+            } => {
+                let data = ApiAlert {
+                    path,
+                    idx,
+                    value,
+                    threshold,
+                }
+                .to_json_string();
+                Some(
+                    SseEvent::message(data)
+                        .event_type("alert")
+                        .id(id.to_string()),
+                )
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// stream an `ApiStateReport` event for every state change of every actor
+/// under the namespace.  a client that reconnects with the `Last-Event-ID` of
+/// the last event it got is first sent the ones it missed that the server
+/// still has.  a client that falls behind is disconnected.
+#[handler]
+async fn sse_events(
+    poem::web::Path(namespace): poem::web::Path<String>,
+    req: &Request,
+    nv: Data<&SharedHandle>,
+) -> Result<SSE> {
+    stream_events(&namespace, req, &nv, sse_event).await
+}
+
+/// stream an `ApiAlert` event of type `alert` for every alert an actor under
+/// the namespace raises - the alerts share the ids and the resuming of the
+/// state change events
+#[handler]
+async fn sse_alerts(
+    poem::web::Path(namespace): poem::web::Path<String>,
+    req: &Request,
+    nv: Data<&SharedHandle>,
+) -> Result<SSE> {
+    stream_events(&namespace, req, &nv, sse_alert).await
+}
+
+async fn stream_events(
+    namespace: &str,
+    req: &Request,
+    nv: &SharedHandle,
+    render: fn(Message<f64>) -> Option<SseEvent>,
+) -> Result<SSE> {
+    let path = normalize_path(namespace.trim_end_matches('/'));
+    let after = req
+        .header("Last-Event-ID")
+        .and_then(|id| id.trim().parse().ok());
+    let (stream_to, events) = mpsc::channel(EVENTS_BUFFER);
+    let missed = match nv
+        .ask(Message::SubscribeEvents {
+            path: path.clone(),
+            after,
+            stream_to,
+        })
+        .await
+    {
+        Ok(Message::Events { events }) => events,
+        r => {
+            return Err(Error::from_string(
+                format!("cannot stream the events under {path}: {r:?}"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    };
+    debug!("streaming events under {path} after {after:?}");
+    // the director drops its sender when the client falls behind, ending the stream
+    let live = futures::stream::unfold(events, |mut events| async move {
+        events.recv().await.map(|event| (event, events))
+    });
+    let stream = futures::stream::iter(missed)
+        .chain(live)
+        .filter_map(move |event| async move { render(event) });
+    Ok(SSE::new(stream).keep_alive(EVENTS_KEEP_ALIVE))
+}
+
+/// push an `ApiStateReport` frame with the current state of the actor and
+/// then another every time its state changes.  the subscription ends when the
+/// client closes the socket.
+#[handler]
+fn ws_state(
+    poem::web::Path((namespace, id)): poem::web::Path<(String, String)>,
+    ws: WebSocket,
+    nv: Data<&SharedHandle>,
+) -> impl IntoResponse {
+    let path = normalize_path(&format!("{namespace}{id}"));
+    let nv = nv.clone();
+    ws.on_upgrade(move |socket| async move {
+        let (mut sink, mut client) = socket.split();
+        let (stream_to, mut reports) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let current = nv
+            .ask(Message::Subscribe {
+                path: path.clone(),
+                stream_to,
+            })
+            .await;
+        let Some(frame) = current.ok().and_then(ws_frame) else {
+            debug!("cannot subscribe to {path}");
+            return;
+        };
+        if sink.send(frame).await.is_err() {
+            return;
+        }
+        loop {
+            tokio::select! {
+                report = reports.recv() => match report.and_then(ws_frame) {
+                    Some(frame) => {
+                        if sink.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+                frame = client.next() => match frame {
+                    Some(Ok(WsMessage::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        // dropping the receiver ends the subscription at the actor
+        debug!("websocket for {path} closed");
+    })
+}
+
+/// the counters and latencies of every actor in the process in the Prometheus
+/// text exposition format
+#[handler]
+fn metrics_text() -> String {
+    metrics::render()
+}
+
+/// the actors the director holds in memory and the entries of their states,
+/// ie: `{"actors":2,"state_entries":5}`
+#[handler]
+async fn director_info(nv: Data<&SharedHandle>, limits: Data<&ApiLimits>) -> poem::Response {
+    match nv
+        .ask_timeout(Message::DirectorInfo, limits.request_timeout)
+        .await
+    {
+        Ok(Message::DirectorInfoReport { info }) => match serde_json::to_string(&info) {
+            Ok(body) => poem::Response::builder()
+                .content_type("application/json")
+                .body(body),
+            Err(e) => {
+                error!("cannot serialize director info: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+        m => {
+            debug!("no director info: {m:?}");
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        }
+    }
+}
+
+fn openapi_service<T: OpenApi>(api: T, server_config: &HttpServerConfig) -> OpenApiService<T, ()> {
+    OpenApiService::new(api, clap::crate_name!(), clap::crate_version!())
+        .server(format!("{}/api", server_config.external_host))
+}
+
+/// the OpenAPI spec of the actors, genes and series APIs as one JSON document
+/// with every path under the `/api` prefix its service is nested at
+#[must_use]
+pub fn api_spec(server_config: &HttpServerConfig) -> String {
+    let specs = [
+        ("/actors", openapi_service(ActorsApi, server_config).spec()),
+        ("/genes", openapi_service(GenesApi, server_config).spec()),
+        ("/series", openapi_service(SeriesApi, server_config).spec()),
+    ];
+    let mut merged = serde_json::Value::Null;
+    for (prefix, spec) in specs {
+        let Ok(mut spec) = serde_json::from_str::<serde_json::Value>(&spec) else {
+            continue;
+        };
+        let paths = spec["paths"].take();
+        let schemas = spec
+            .pointer_mut("/components/schemas")
+            .map(serde_json::Value::take);
+        let tags = spec["tags"].take();
+        if merged.is_null() {
+            merged = spec;
+        }
+        if let serde_json::Value::Object(paths) = paths {
+            for (path, item) in paths {
+                merged["paths"][format!("{prefix}{path}")] = item;
+            }
+        }
+        if let Some(serde_json::Value::Object(schemas)) = schemas {
+            for (name, schema) in schemas {
+                merged["components"]["schemas"][name] = schema;
+            }
+        }
+        if let serde_json::Value::Array(tags) = tags {
+            if !merged["tags"].is_array() {
+                merged["tags"] = serde_json::Value::Array(vec![]);
+            }
+            if let Some(merged_tags) = merged["tags"].as_array_mut() {
+                merged_tags.extend(tags);
+            }
+        }
+    }
+    merged.to_string()
+}
+
+/// the build that is serving and the namespace it serves
+fn version_info(server_config: &HttpServerConfig) -> String {
+    serde_json::json!({
+        "version": clap::crate_version!(),
+        "commit": env!("NV_GIT_COMMIT"),
+        "namespace": server_config.namespace,
+    })
+    .to_string()
+}
+
+/// assemble the routes that `serve` listens with - the API services, the optional spec UI, the
+/// `/health` and `/ready` probes, the `/version` build info, the `/metrics` scrape, the `/debug/director` counts, the `/api/spec.json` spec, the `/ws` state streams, the `/events` namespace streams and, when an authenticator is configured, the auth middleware in
+/// front of the API and the streams
+#[must_use]
+pub fn build_app(
+    nv: Arc<Handle>,
+    server_config: &HttpServerConfig,
+    uipath: Option<String>,
+    disable_ui: Option<bool>,
+) -> BoxEndpoint<'static> {
+    let disui = disable_ui.unwrap_or(false);
+    let spec = api_spec(server_config);
+    let version = version_info(server_config);
+
+    let actors_service = openapi_service(ActorsApi, server_config);
+    let genes_service = openapi_service(GenesApi, server_config);
+    let series_service = openapi_service(SeriesApi, server_config);
+
+    let mut app = Route::new();
+    if !disui {
+        let uip = uipath
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string();
+        let actors_ui = actors_service.swagger_ui();
+        let genes_ui = genes_service.swagger_ui();
+        let series_ui = series_service.swagger_ui();
+        app = app
+            .nest(format!("/{uip}/actors"), actors_ui)
+            .nest(format!("/{uip}/genes"), genes_ui)
+            .nest(format!("/{uip}/series"), series_ui);
+    }
+
+    let api = Route::new()
+        .nest("/actors", actors_service)
+        .nest("/genes", genes_service)
+        .nest("/series", series_service)
+        .at(
+            "/spec.json",
+            get(make_sync(move |_| {
+                poem::Response::builder()
+                    .content_type("application/json")
+                    .body(spec.clone())
+            })),
+        );
+    let ws = Route::new().at("/:namespace<.+/>:id", get(ws_state));
+    let events = Route::new().at("/:namespace<.+>", get(sse_events));
+    let alerts = Route::new().at("/:namespace<.+>", get(sse_alerts));
+    let (api, ws, events, alerts) = match &server_config.authenticator {
+        Some(authenticator) => (
+            api.with(AuthMiddleware::new(Arc::clone(authenticator)))
+                .boxed(),
+            ws.with(AuthMiddleware::new(Arc::clone(authenticator)))
+                .boxed(),
+            events
+                .with(AuthMiddleware::new(Arc::clone(authenticator)))
+                .boxed(),
+            alerts
+                .with(AuthMiddleware::new(Arc::clone(authenticator)))
+                .boxed(),
+        ),
+        None => (api.boxed(), ws.boxed(), events.boxed(), alerts.boxed()),
+    };
+
+    // the probes, version, metrics, and director counts are not part of the API - they are not in
+    // the spec and are not authenticated
+    app.at("/health", get(health))
+        .at("/ready", get(ready))
+        .at(
+            "/version",
+            get(make_sync(move |_| {
+                poem::Response::builder()
+                    .content_type("application/json")
+                    .body(version.clone())
+            })),
+        )
+        .at("/metrics", get(metrics_text))
+        .at("/debug/director", get(director_info))
+        .nest("/api", api)
+        .nest("/ws", ws)
+        .nest("/events", events)
+        .nest("/alerts", alerts)
+        .data(SharedHandle(nv))
+        .data(ApiLimits {
+            max_page_size: server_config.max_page_size,
+            request_timeout: server_config.request_timeout,
+        })
+        .data(IdempotencyCache::new(server_config.idempotency_window))
+        .boxed()
+}
+
+/// start a server on port and interface, first writing the API spec to
+/// `server_config.spec_out` if it is set, and run it until `SIGINT` or
+/// `SIGTERM`
+///
+/// # Errors
+///
+/// Returns `Err` if the spec can not be written or the server can not be started
+pub async fn serve(
+    nv: Arc<Handle>,
+    server_config: HttpServerConfig,
+    uipath: Option<String>,
+    disable_ui: Option<bool>,
+) -> Result<(), std::io::Error> {
+    serve_until(nv, server_config, uipath, disable_ui, shutdown_signal()).await
+}
+
+/// `serve` until `shutdown` completes - the requests in flight are given up
+/// to the request timeout to finish and then the director and its store are
+/// stopped so that every observation the API accepted is committed before
+/// this returns
 ///
 /// # Errors
 ///
-/// Returns `Err` if server can not be started
-pub async fn serve<'a>(
+/// Returns `Err` if the spec can not be written, the server can not be
+/// started, or the actors can not be stopped
+pub async fn serve_until(
     nv: Arc<Handle>,
     server_config: HttpServerConfig,
     uipath: Option<String>,
     disable_ui: Option<bool>,
+    shutdown: impl std::future::Future<Output = ()> + Send,
 ) -> Result<(), std::io::Error> {
     info!("starting server: {server_config}");
 
-    let disui = disable_ui.unwrap_or(false);
     let ifc_host_str = format!("{}:{}", server_config.interface, server_config.port);
     let swagger_api_target = format!("{}/api", server_config.external_host);
 
-    let actors_service =
-        OpenApiService::new(ActorsApi, clap::crate_name!(), clap::crate_version!())
-            .server(swagger_api_target.clone());
-
-    let genes_service = OpenApiService::new(GenesApi, clap::crate_name!(), clap::crate_version!())
-        .server(swagger_api_target.clone());
+    if let Some(spec_out) = &server_config.spec_out {
+        std::fs::write(spec_out, api_spec(&server_config))?;
+        info!("API spec written to {}", spec_out.display());
+    }
 
-    let app = {
-        if disui {
-            Route::new()
-                .nest("/api/actors", actors_service)
-                .nest("/api/genes", genes_service)
-                .data(SharedHandle(nv.clone()))
-        } else {
-            let uip = uipath
-                .unwrap_or_default()
-                .trim_start_matches('/')
-                .to_string();
-            let actors_ui = actors_service.swagger_ui();
-            let genes_ui = genes_service.swagger_ui();
-            Route::new()
-                .nest(format!("/{uip}/actors"), actors_ui)
-                .nest(format!("/{uip}/genes"), genes_ui)
-                .nest("/api/actors", actors_service)
-                .nest("/api/genes", genes_service)
-                .data(SharedHandle(nv.clone()))
-        }
-    };
+    let app = build_app(Arc::clone(&nv), &server_config, uipath, disable_ui);
 
-    let server = poem::Server::new(TcpListener::bind(ifc_host_str)).run(app);
+    let server = poem::Server::new(TcpListener::bind(ifc_host_str)).run_with_graceful_shutdown(
+        app,
+        shutdown,
+        Some(server_config.request_timeout),
+    );
     info!("navactor API is available at {}.", swagger_api_target);
-    server.await
+    server.await?;
+
+    info!("server stopped, stopping actors");
+    match nv.ask(Message::Shutdown).await {
+        Ok(Message::Stopped) => Ok(()),
+        Ok(m) => Err(std::io::Error::other(format!(
+            "unexpected stop response: {m}"
+        ))),
+        Err(e) => Err(std::io::Error::other(e.reason)),
+    }
+}
+
+/// completes on the first `SIGINT`, ie: ctrl-c, or, on unix, `SIGTERM`
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+            }
+            Err(e) => {
+                error!("cannot listen for SIGTERM: {e}");
+                tokio::signal::ctrl_c().await.ok();
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await.ok();
+    info!("shutdown signal received");
 }
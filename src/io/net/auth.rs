@@ -0,0 +1,134 @@
+//! This module defines how `nv serve` decides who is calling the API.
+//!
+//! Deployments plug in a scheme by implementing the `Authenticator` trait - a static shared token,
+//! a JWT check, or anything that can be decided from the incoming `Request`. The `AuthMiddleware`
+//! wraps the API routes, calls the configured authenticator for every request, and stashes the
+//! resulting `Principal` in the request data so that handlers can make per-principal decisions or
+//! record who made a change. Requests that do not authenticate are answered with a 401 before they
+//! reach the actors.
+//!
+//! `StaticTokenAuthenticator` is the implementation provided out of the box. It accepts a request
+//! carrying the configured token as either `Authorization: Bearer <token>` or `X-API-Key: <token>`.
+
+use poem::{http::StatusCode, Endpoint, Error, Middleware, Request, Result};
+use std::fmt;
+use std::sync::Arc;
+use tracing::debug;
+
+/// the identity of an authenticated caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+}
+
+/// returned when a request can not be authenticated
+#[derive(Debug, Clone)]
+pub struct AuthError {
+    pub reason: String,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unauthorized: {}", self.reason)
+    }
+}
+
+/// implement to plug an authentication scheme into the server
+pub trait Authenticator: Send + Sync {
+    /// decide who is making the request
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError`](struct.AuthError.html) if the request does not
+    /// carry acceptable credentials
+    fn authenticate(&self, req: &Request) -> Result<Principal, AuthError>;
+}
+
+/// the default authenticator - every caller presenting the one shared token
+/// is the same principal
+pub struct StaticTokenAuthenticator {
+    token: String,
+}
+
+impl StaticTokenAuthenticator {
+    #[must_use]
+    pub const fn new(token: String) -> Self {
+        Self { token }
+    }
+}
+
+impl Authenticator for StaticTokenAuthenticator {
+    fn authenticate(&self, req: &Request) -> Result<Principal, AuthError> {
+        let presented = req
+            .header("X-API-Key")
+            .or_else(|| {
+                req.header("Authorization")
+                    .and_then(|h| h.strip_prefix("Bearer "))
+                    .map(str::trim)
+            })
+            .ok_or_else(|| AuthError {
+                reason: "no api key".to_string(),
+            })?;
+
+        if constant_time_eq(presented.as_bytes(), self.token.as_bytes()) {
+            Ok(Principal {
+                name: "api-key".to_string(),
+            })
+        } else {
+            Err(AuthError {
+                reason: "invalid api key".to_string(),
+            })
+        }
+    }
+}
+
+/// compare without returning early at the first differing byte so that the
+/// time taken does not reveal how much of the token a caller guessed
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// poem middleware that runs the configured `Authenticator` ahead of the API
+pub struct AuthMiddleware {
+    authenticator: Arc<dyn Authenticator>,
+}
+
+impl AuthMiddleware {
+    #[must_use]
+    pub fn new(authenticator: Arc<dyn Authenticator>) -> Self {
+        Self { authenticator }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for AuthMiddleware {
+    type Output = AuthEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        AuthEndpoint {
+            inner: ep,
+            authenticator: Arc::clone(&self.authenticator),
+        }
+    }
+}
+
+/// the endpoint produced by `AuthMiddleware`
+pub struct AuthEndpoint<E> {
+    inner: E,
+    authenticator: Arc<dyn Authenticator>,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for AuthEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        match self.authenticator.authenticate(&req) {
+            Ok(principal) => {
+                debug!("authenticated {}", principal.name);
+                req.set_data(principal);
+                self.inner.call(req).await
+            }
+            Err(e) => Err(Error::from_string(e.to_string(), StatusCode::UNAUTHORIZED)),
+        }
+    }
+}
@@ -1 +1,3 @@
 pub mod api_server;
+pub mod auth;
+pub mod mqtt_source;
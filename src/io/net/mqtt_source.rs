@@ -0,0 +1,195 @@
+//! This module implements the `MqttSource` actor, the broker counterpart of the `StdinActor`.
+//! When a `ReadAllCmd` message is received, the actor connects to an MQTT broker, subscribes to a
+//! topic filter, and sends a `Content` message with each payload it receives to the output handle,
+//! a `json_decoder` feeding a `director`.  The topic of a message is the path of its actor, ie:
+//! a payload published to `actors/one` is an observation of `/actors/one`.
+//!
+//! A dropped connection is retried with a growing delay and the topic filter is subscribed to again
+//! every time the broker accepts the connection.  The actor only stops reading when its output is
+//! closed.
+
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::message::Envelope;
+use crate::actors::message::Message;
+use crate::actors::message::MtHint;
+use crate::actors::message::NvError;
+use async_trait::async_trait;
+use rumqttc::AsyncClient;
+use rumqttc::Event;
+use rumqttc::MqttOptions;
+use rumqttc::Packet;
+use rumqttc::QoS;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+pub const DEFAULT_PORT: u16 = 1883;
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+const MIN_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+pub struct MqttSource {
+    pub receiver: mpsc::Receiver<Envelope<f64>>,
+    pub output: Handle,
+    pub options: MqttOptions,
+    pub topic: String,
+}
+
+/// split a `host[:port]` broker address, with or without a `mqtt://` or
+/// `tcp://` scheme, into the host and the port
+///
+/// # Errors
+///
+/// Returns a description of the address if the host is missing or the port
+/// is not a number
+pub fn parse_broker(broker: &str) -> Result<(String, u16), String> {
+    let address = broker
+        .strip_prefix("mqtt://")
+        .or_else(|| broker.strip_prefix("tcp://"))
+        .unwrap_or(broker);
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|e| format!("bad port in broker '{broker}': {e}"))?,
+        ),
+        None => (address, DEFAULT_PORT),
+    };
+    if host.is_empty() {
+        return Err(format!("no host in broker '{broker}'"));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// the actor path of an MQTT topic
+#[must_use]
+pub fn topic_to_path(topic: &str) -> String {
+    if topic.starts_with('/') {
+        topic.to_string()
+    } else {
+        format!("/{topic}")
+    }
+}
+
+#[async_trait]
+impl Actor for MqttSource {
+    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+        let Envelope {
+            message,
+            respond_to,
+            ..
+        } = envelope;
+
+        if matches!(message, Message::ReadAllCmd {}) {
+            let reason = self.read_all().await;
+            respond_or_log_error(respond_to, Err(NvError { reason }));
+        } else {
+            warn!("unexpected: {message}");
+        }
+    }
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+    async fn start(&mut self) {}
+}
+
+impl MqttSource {
+    /// forward every message published to the topic until the output is
+    /// closed and return why reading stopped
+    async fn read_all(&self) -> String {
+        let (client, mut eventloop) = AsyncClient::new(self.options.clone(), 10);
+        let mut delay = MIN_RECONNECT_DELAY;
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("connected to mqtt broker, subscribing to {}", self.topic);
+                    delay = MIN_RECONNECT_DELAY;
+                    // a clean session forgets the subscription with the connection
+                    if let Err(e) = client.try_subscribe(self.topic.as_str(), QoS::AtLeastOnce) {
+                        error!("cannot subscribe to {}: {e}", self.topic);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let text = match String::from_utf8(publish.payload.to_vec()) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            error!("skipping payload published to {}: {e}", publish.topic);
+                            continue;
+                        }
+                    };
+                    let msg = Message::Content {
+                        text,
+                        hint: MtHint::Update,
+                        path: Some(topic_to_path(&publish.topic)),
+                    };
+                    if let Err(e) = self.output.tell(msg).await {
+                        return format!("cannot send message: {e}");
+                    }
+                }
+                Ok(event) => debug!("mqtt event: {event:?}"),
+                Err(e) => {
+                    warn!("mqtt connection failed, retrying in {delay:?}: {e}");
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+
+    /// actor private constructor
+    const fn new(
+        receiver: mpsc::Receiver<Envelope<f64>>,
+        output: Handle,
+        options: MqttOptions,
+        topic: String,
+    ) -> Self {
+        Self {
+            receiver,
+            output,
+            options,
+            topic,
+        }
+    }
+}
+
+/// actor handle public constructor - `client_id` must be unique among the
+/// clients of the broker
+#[must_use]
+pub fn new(
+    bufsz: usize,
+    output: Handle,
+    client_id: &str,
+    host: &str,
+    port: u16,
+    topic: String,
+) -> Handle {
+    async fn start(mut actor: MqttSource) {
+        while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
+            actor.handle_envelope(envelope).await;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(KEEP_ALIVE);
+
+    let actor = MqttSource::new(receiver, output, options, topic);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor));
+
+    actor_handle
+}
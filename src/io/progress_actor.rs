@@ -0,0 +1,106 @@
+//!This module reports the progress of a long `update --progress` on its way to the director.
+//!
+//!The `ProgressActor` sits between the decoder and the director and passes every message on.  It
+//!counts the observations and every `period` prints how many have been ingested so far to stderr,
+//!and once more at `EndOfStream`, so that a large load piped in shows it is moving without a state
+//!report printed for every line.
+//!
+//!The module has a public constructor function `new` that returns a `Handle` to interact with the
+//!actor.
+
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
+use crate::actors::actor::Actor;
+use crate::actors::actor::Handle;
+use crate::actors::message::Envelope;
+use crate::actors::message::Message;
+use crate::actors::value::Value;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tracing::error;
+
+pub struct ProgressActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Handle<T>,
+    /// observations of the current stream
+    observations: usize,
+}
+
+#[async_trait]
+impl<T: Value> Actor<T> for ProgressActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
+        match &envelope.message {
+            Message::Observations { .. } => self.observations += 1,
+            Message::EndOfStream {} => {
+                self.print_progress();
+                self.observations = 0;
+            }
+            _ => {}
+        }
+        if let Err(e) = self.output.send(envelope).await {
+            error!("cannot send: {e:?}");
+        }
+    }
+
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
+
+    async fn start(&mut self) {}
+}
+
+impl<T: Value> ProgressActor<T> {
+    fn print_progress(&self) {
+        eprintln!("{} observations ingested", self.observations);
+    }
+
+    /// actor private constructor
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>, output: Handle<T>) -> Self {
+        Self {
+            receiver,
+            output,
+            observations: 0,
+        }
+    }
+}
+
+/// actor handle public constructor
+#[must_use]
+pub fn new(bufsz: usize, output: Handle, period: Duration) -> Handle {
+    new_of_type(bufsz, output, period)
+}
+
+/// `new` for a director of `T` values, ie: `i64`
+#[must_use]
+pub fn new_of_type<T: Value>(bufsz: usize, output: Handle<T>, period: Duration) -> Handle<T> {
+    async fn start<T: Value>(mut actor: ProgressActor<T>, period: Duration) {
+        // the first tick is a period from now rather than right away
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                envelope = actor.receiver.recv() => match envelope {
+                    Some(envelope) if matches!(envelope.message, Message::Shutdown) => {
+                        shut_down(&actor, envelope.respond_to).await;
+                        break;
+                    }
+                    Some(envelope) => actor.handle_envelope(envelope).await,
+                    None => break,
+                },
+                _ = ticker.tick() => actor.print_progress(),
+            }
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel(bufsz);
+
+    let actor = ProgressActor::new(receiver, output);
+
+    let actor_handle = Handle::new(sender);
+
+    tokio::spawn(start(actor, period));
+
+    actor_handle
+}
@@ -5,31 +5,128 @@
 //!Once the end of the stream is reached, a `EndOfStream` message is sent to the next hop to
 //!trigger any necessary cleanup and shutdown. This actor is only used in `CLI` mode and is used to
 //!interact with the command-line interface by reading input commands from the user.
+//!
+//!A gzipped stream is decompressed before it is split into lines when the actor is created with
+//!`gzip` set. A stream that can not be read or decompressed is reported to the sender of the
+//!`ReadAllCmd` as an error once the lines read before the failure are processed.
 
+use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
+use crate::actors::actor::shut_down_output;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
 use crate::actors::message::Envelope;
 use crate::actors::message::Message;
 use crate::actors::message::MtHint;
+use crate::actors::message::NvError;
+use crate::actors::message::NvResult;
+use crate::actors::value::Value;
+use async_compression::tokio::bufread::GzipDecoder;
 use async_trait::async_trait;
 use tokio::io::stdin;
+use tokio::io::AsyncBufRead;
 use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
 use tokio::io::BufReader;
 use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tracing::error;
 use tracing::warn;
 
+/// the `Content` message of one line of input - a line with a `gene_type`
+/// is a gene mapping and any other line is an update
+pub(crate) fn line_message<T: Value>(text: String) -> Message<T> {
+    let hint = if text.contains("gene_type") {
+        MtHint::GeneMapping
+    } else {
+        MtHint::Update
+    };
+    Message::Content {
+        text,
+        hint,
+        path: None,
+    }
+}
+
+/// the buffered reader of `input`, decompressing it if it is gzipped
+pub(crate) fn line_reader<R>(input: R, gzip: bool) -> Box<dyn AsyncBufRead + Unpin + Send>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let input = BufReader::new(input);
+    if gzip {
+        let mut decoder = GzipDecoder::new(input);
+        // `cat a.gz b.gz` is a valid gzip stream
+        decoder.multiple_members(true);
+        Box::new(BufReader::new(decoder))
+    } else {
+        Box::new(input)
+    }
+}
+
+/// send every line of `reader` to `output` followed by an `EndOfStream` that
+/// answers `respond_to` - or an error if the reader fails part way
+pub(crate) async fn forward_lines<T: Value>(
+    reader: Box<dyn AsyncBufRead + Unpin + Send>,
+    output: &Handle<T>,
+    respond_to: Option<oneshot::Sender<NvResult<Message<T>>>>,
+) {
+    let mut lines = reader.lines();
+
+    let failure = loop {
+        match lines.next_line().await {
+            Ok(Some(text)) => match output.tell(line_message(text)).await {
+                Ok(()) => {}
+                Err(e) => {
+                    error!("cannot send message: {e:?}");
+                    return;
+                }
+            },
+            Ok(None) => break None,
+            Err(e) => {
+                error!("failed to read stream: {e:?}");
+                break Some(format!("failed to read stream: {e}"));
+            }
+        }
+    };
+
+    if let Some(reason) = failure {
+        // let the rest of the pipeline finish before reporting
+        output
+            .ask(Message::EndOfStream {})
+            .await
+            .map_err(|e| error!("cannot complete stream: {e}"))
+            .ok();
+        respond_or_log_error(respond_to, Err(NvError { reason }));
+        return;
+    }
+
+    let senv = Envelope {
+        message: Message::EndOfStream {},
+        respond_to,
+        ..Default::default()
+    };
+
+    match output.send(senv).await {
+        Ok(()) => {}
+        Err(e) => {
+            error!("cannot send end-of-stream message: {e:?}");
+        }
+    }
+}
+
 /// the stdin actor is only used in CLI mode.  it gets a single command to
 /// read from stdin and it reads until the EOF.  once it sees EOF, it sends
 /// a `EndOfStream` msg to the next hop to trigger any cleanup and shutdown.
-pub struct StdinActor {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
-    pub output: Handle,
+pub struct StdinActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    pub output: Handle<T>,
+    pub gzip: bool,
 }
 
 #[async_trait]
-impl Actor for StdinActor {
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+impl<T: Value> Actor<T> for StdinActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
         let Envelope {
             message,
             respond_to,
@@ -37,72 +134,45 @@ impl Actor for StdinActor {
         } = envelope;
 
         if matches!(message, Message::ReadAllCmd {}) {
-            let mut lines = BufReader::new(stdin()).lines();
-
-            while let Some(text) = lines.next_line().await.unwrap_or_else(|e| {
-                error!("failed to read stream: {e:?}");
-                None
-            }) {
-                let hint = if text.contains("gene_type") {
-                    MtHint::GeneMapping
-                } else {
-                    MtHint::Update
-                };
-                let msg = Message::Content {
-                    text,
-                    hint,
-                    path: None,
-                };
-                match self.output.tell(msg).await {
-                    Ok(()) => {}
-                    Err(e) => {
-                        error!("cannot send message: {e:?}");
-                        return;
-                    }
-                }
-            }
-
-            let complete_msg = Message::EndOfStream {};
-
-            let senv = Envelope {
-                message: complete_msg,
-                respond_to,
-                ..Default::default()
-            };
-
-            match self.output.send(senv).await {
-                Ok(()) => {}
-                Err(e) => {
-                    error!("cannot send end-of-stream message: {e:?}");
-                }
-            }
+            let reader = line_reader(stdin(), self.gzip);
+            forward_lines(reader, &self.output, respond_to).await;
         } else {
             warn!("unexpected: {message}");
         }
     }
-    async fn stop(&self) {}
+    async fn stop(&self) {
+        shut_down_output(&self.output).await;
+    }
     async fn start(&mut self) {}
 }
 
 /// actor private constructor
-impl StdinActor {
-    const fn new(receiver: mpsc::Receiver<Envelope<f64>>, output: Handle) -> Self {
-        Self { receiver, output }
+impl<T: Value> StdinActor<T> {
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>, output: Handle<T>, gzip: bool) -> Self {
+        Self {
+            receiver,
+            output,
+            gzip,
+        }
     }
 }
 
 /// actor handle public constructor
 #[must_use]
-pub fn new(bufsz: usize, output: Handle) -> Handle {
-    async fn start(mut actor: StdinActor) {
+pub fn new<T: Value>(bufsz: usize, output: Handle<T>, gzip: bool) -> Handle<T> {
+    async fn start<T: Value>(mut actor: StdinActor<T>) {
         while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
             actor.handle_envelope(envelope).await;
         }
     }
 
     let (sender, receiver) = mpsc::channel(bufsz);
 
-    let actor = StdinActor::new(receiver, output);
+    let actor = StdinActor::new(receiver, output, gzip);
 
     let actor_handle = Handle::new(sender);
 
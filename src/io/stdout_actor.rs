@@ -4,11 +4,18 @@
 //!can be beneficial. The module implements the `Actor` trait and defines its own `handle_envelope`
 //!and stop methods.
 //!
-//!It receives a channel of type `mpsc::Receiver<Envelope<f64>>` to receive messages to print, and
+//!It receives a channel of type `mpsc::Receiver<Envelope<T>>` to receive messages to print, and
 //!it pattern matches on the type of the incoming message.
 //!
 //!If the message is a `TextMsg`, it prints the message to the standard output. If the message is a
-//!`StateReport` or an `Update`, it prints the appropriate message with the path and values.
+//!`StateReport` or an `Update`, it prints the appropriate message with the path and values, keyed
+//!by the names of the `IndexLabels` of the namespace once the director has told it any, and a
+//!`History` is printed one observation per line followed on `stderr` by the number of
+//!observations of all its pages if it is one page of them, a `StatsReport` and a `DirectorInfoReport`
+//!one count per line, and an `Alert`
+//!as the index, its value, and the threshold it crossed. If the
+//!message is an `Error`, it prints the path and the reason the update was rejected to `stderr`, and
+//!the `StreamSummary` at the end of an update is printed to `stderr` too.
 //!
 //!When a message of type `EndOfStream` is received, it sends the message to the stream creator via
 //!`respond_to` if there is any.
@@ -17,10 +24,15 @@
 //!actor.
 
 use crate::actors::actor::respond_or_log_error;
+use crate::actors::actor::shut_down;
 use crate::actors::actor::Actor;
 use crate::actors::actor::Handle;
+use crate::actors::actor::State;
+use crate::actors::message::label_values;
 use crate::actors::message::Envelope;
+use crate::actors::message::IndexLabels;
 use crate::actors::message::Message;
+use crate::actors::value::Value;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 use tracing::error;
@@ -28,13 +40,15 @@ use tracing::warn;
 
 /// in CLI mode, printing to stdout is helpful and can enable `nv` to be used
 /// in combination with other *nix tools.
-pub struct StdoutActor {
-    pub receiver: mpsc::Receiver<Envelope<f64>>,
+pub struct StdoutActor<T = f64> {
+    pub receiver: mpsc::Receiver<Envelope<T>>,
+    /// the names printed in place of the indices of the state
+    labels: IndexLabels,
 }
 
 #[async_trait]
-impl Actor for StdoutActor {
-    async fn handle_envelope(&mut self, envelope: Envelope<f64>) {
+impl<T: Value> Actor<T> for StdoutActor<T> {
+    async fn handle_envelope(&mut self, envelope: Envelope<T>) {
         let Envelope {
             message,
             respond_to,
@@ -48,11 +62,70 @@ impl Actor for StdoutActor {
                 path: _,
             } => println!("{text}"),
             Message::StateReport { path, values, .. } => {
-                println!("{path} current state: {values:?}");
+                println!("{path} current state: {}", self.values_text(values));
                 respond_or_log_error(respond_to, Ok(message));
             }
             Message::Observations { path, values, .. } => {
-                println!("{path} new observations: {values:?}");
+                println!("{path} new observations: {}", self.values_text(values));
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::History {
+                path,
+                points,
+                total,
+            } => {
+                for (datetime, values) in points {
+                    println!("{path} {datetime}: {}", self.values_text(values));
+                }
+                print_page_of(path, points.len(), *total);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StateReports { reports, .. } => {
+                for report in reports {
+                    if let Message::StateReport { path, values, .. } = report {
+                        println!("{path} current state: {}", self.values_text(values));
+                    }
+                }
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StatsReport { path, stats } => {
+                let time = |datetime: Option<_>| {
+                    datetime.map_or_else(|| String::from("none"), |datetime| format!("{datetime}"))
+                };
+                println!("{path} paths: {}", stats.paths);
+                println!("{path} observations: {}", stats.observations);
+                println!("{path} earliest: {}", time(stats.earliest));
+                println!("{path} latest: {}", time(stats.latest));
+                println!("{path} gene mappings: {}", stats.gene_mappings);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::DirectorInfoReport { info } => {
+                println!("resident actors: {}", info.actors);
+                println!("state entries: {}", info.state_entries);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Alert {
+                path,
+                idx,
+                value,
+                threshold,
+            } => {
+                println!("{path} alert: idx {idx} at {value} crossed {threshold}");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::IndexLabels { labels } => {
+                self.labels.clone_from(labels);
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::Error { path, reason } => {
+                eprintln!("{path} error: {reason}");
+                respond_or_log_error(respond_to, Ok(message));
+            }
+            Message::StreamSummary {
+                ingested,
+                duplicates,
+            } => {
+                eprintln!("{ingested} observations ingested, {duplicates} duplicates skipped");
                 respond_or_log_error(respond_to, Ok(message));
             }
             Message::EndOfStream {} => {
@@ -71,18 +144,48 @@ impl Actor for StdoutActor {
     async fn start(&mut self) {}
 }
 
+/// a page of a history is followed by how many observations all the pages
+/// have, on `stderr` to leave the printed observations as they are
+pub fn print_page_of(path: &str, page: usize, total: u64) {
+    if u64::try_from(page).is_ok_and(|page| page < total) {
+        eprintln!("{path} {page} of {total} observations");
+    }
+}
+
 /// actor private constructor
-impl StdoutActor {
-    const fn new(receiver: mpsc::Receiver<Envelope<f64>>) -> Self {
-        Self { receiver }
+impl<T: Value> StdoutActor<T> {
+    const fn new(receiver: mpsc::Receiver<Envelope<T>>) -> Self {
+        Self {
+            receiver,
+            labels: IndexLabels::new(),
+        }
+    }
+
+    /// the values keyed by their index labels if the namespace has any
+    fn values_text(&self, values: &State<T>) -> String {
+        if self.labels.is_empty() {
+            format!("{values:?}")
+        } else {
+            format!("{:?}", label_values(values, &self.labels))
+        }
     }
 }
 
 /// actor handle public constructor
 #[must_use]
 pub fn new(bufsz: usize) -> Handle {
-    async fn start(mut actor: StdoutActor) {
+    new_of_type(bufsz)
+}
+
+/// `new` for the state of a director of `T` values, ie: `i64`
+#[must_use]
+pub fn new_of_type<T: Value>(bufsz: usize) -> Handle<T> {
+    async fn start<T: Value>(mut actor: StdoutActor<T>) {
         while let Some(envelope) = actor.receiver.recv().await {
+            if matches!(envelope.message, Message::Shutdown) {
+                shut_down(&actor, envelope.respond_to).await;
+                break;
+            }
             actor.handle_envelope(envelope).await;
         }
     }
@@ -1,23 +1,81 @@
 use clap::{CommandFactory, Parser};
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::sqlite_store::SqliteOptions;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use navactor::cli::config::with_config_file;
 use navactor::cli::ifc::{Cli, Commands};
 use navactor::cli::runner::{
-    configure, explain, inspect, print_completions, run_serve, update, OptionVariant,
+    compact, completions_shell, configure, delete, explain, export, import, ingest_mqtt, inspect,
+    label, print_completions, prune, replay, reset, resident_stats, run_serve, stats, update,
+    watch, CliError, CliResult, OptionVariant,
 };
 use navactor::io::net::api_server::HttpServerConfig;
+use navactor::io::net::auth::StaticTokenAuthenticator;
+use std::sync::Arc;
 use tokio::runtime::Runtime;
+use tracing::error;
 use tracing::info;
 
-fn match_command(pcli: Cli, runtime: &Runtime, memory_only: Option<OptionVariant>, bufsz: usize) {
-    match pcli.command {
+#[allow(clippy::too_many_arguments)]
+fn director_options(
+    observation_window: Option<u64>,
+    snapshot_interval: Option<usize>,
+    max_resident_actors: Option<usize>,
+    mailbox_high_water: Option<f64>,
+    max_rate_per_path: Option<f64>,
+    decimals: Option<u32>,
+    default_gene: Option<GeneType>,
+    resurrection_grace_ms: Option<u64>,
+) -> DirectorOptions {
+    DirectorOptions {
+        observation_window: observation_window
+            .map(|secs| time::Duration::seconds(i64::try_from(secs).unwrap_or(i64::MAX))),
+        snapshot_interval,
+        max_resident_actors,
+        mailbox_high_water,
+        max_rate_per_path,
+        decimals,
+        default_gene,
+        resurrection_grace: resurrection_grace_ms.map(std::time::Duration::from_millis),
+    }
+}
+
+fn match_command(
+    pcli: Cli,
+    runtime: &Runtime,
+    memory_only: Option<OptionVariant>,
+    bufsz: usize,
+) -> CliResult {
+    let mailbox_high_water = pcli.mailbox_high_water;
+    let result = match pcli.command {
         Commands::Serve {
             port,
             interface,
             external_host,
             namespace,
+            db_path,
             uipath,
             disable_ui,
             disable_wal,
             disable_duplicate_detection,
+            observation_window,
+            max_rate_per_path,
+            decimals,
+            default_gene,
+            snapshot_interval,
+            max_resident_actors,
+            resurrection_grace_ms,
+            max_replay_events,
+            max_connections,
+            busy_timeout_ms,
+            max_write_attempts,
+            synchronous,
+            request_timeout,
+            request_timeout_ms,
+            idempotency_window,
+            spec_out,
+            api_key,
         } => {
             let wal = match disable_wal {
                 Some(true) => OptionVariant::Off,
@@ -27,21 +85,80 @@ fn match_command(pcli: Cli, runtime: &Runtime, memory_only: Option<OptionVariant
                 Some(false) => OptionVariant::Off,
                 _ => OptionVariant::On,
             };
-            let server_config = HttpServerConfig::new(port, interface, external_host, namespace);
+            let mut server_config =
+                HttpServerConfig::new(port, interface, external_host, namespace)
+                    .with_idempotency_window(std::time::Duration::from_secs(idempotency_window));
+            if let Some(secs) = request_timeout {
+                server_config =
+                    server_config.with_request_timeout(std::time::Duration::from_secs(secs));
+            }
+            if let Some(millis) = request_timeout_ms {
+                server_config =
+                    server_config.with_request_timeout(std::time::Duration::from_millis(millis));
+            }
+            if let Some(spec_out) = spec_out {
+                server_config = server_config.with_spec_out(spec_out);
+            }
+            if let Some(api_key) = api_key {
+                server_config = server_config
+                    .with_authenticator(Arc::new(StaticTokenAuthenticator::new(api_key)));
+            }
             run_serve(
                 server_config,
+                db_path,
                 runtime,
                 uipath,
                 disable_ui,
+                memory_only.unwrap_or(OptionVariant::Off),
                 wal,
                 disable_duplicate_detection,
-            );
+                director_options(
+                    observation_window,
+                    snapshot_interval,
+                    max_resident_actors,
+                    mailbox_high_water,
+                    max_rate_per_path,
+                    decimals,
+                    default_gene,
+                    resurrection_grace_ms,
+                ),
+                StoreOptions {
+                    max_replay_events,
+                    sqlite: SqliteOptions {
+                        max_connections,
+                        busy_timeout: std::time::Duration::from_millis(busy_timeout_ms),
+                        synchronous,
+                    },
+                    max_write_attempts,
+                    ..Default::default()
+                },
+            )
         }
         Commands::Update {
             namespace,
+            db_path,
             silent,
             disable_wal,
             disable_duplicate_detection,
+            observation_window,
+            max_rate_per_path,
+            decimals,
+            default_gene,
+            snapshot_interval,
+            max_resident_actors,
+            max_replay_events,
+            max_connections,
+            busy_timeout_ms,
+            max_write_attempts,
+            synchronous,
+            format,
+            field_index,
+            output_format,
+            input,
+            gzip,
+            value_type,
+            dry_run,
+            progress,
         } => {
             let silent = match silent {
                 Some(true) => OptionVariant::On,
@@ -58,32 +175,193 @@ fn match_command(pcli: Cli, runtime: &Runtime, memory_only: Option<OptionVariant
             };
             update(
                 namespace,
+                db_path,
                 bufsz,
                 runtime,
                 silent,
                 memory_only,
                 wal,
                 disable_duplicate_detection,
-            );
+                director_options(
+                    observation_window,
+                    snapshot_interval,
+                    max_resident_actors,
+                    mailbox_high_water,
+                    max_rate_per_path,
+                    decimals,
+                    default_gene,
+                    None,
+                ),
+                StoreOptions {
+                    max_replay_events,
+                    sqlite: SqliteOptions {
+                        max_connections,
+                        busy_timeout: std::time::Duration::from_millis(busy_timeout_ms),
+                        synchronous,
+                    },
+                    max_write_attempts,
+                    ..Default::default()
+                },
+                format,
+                field_index,
+                output_format,
+                input,
+                gzip,
+                value_type,
+                dry_run,
+                progress.map(std::time::Duration::from_secs),
+            )
         }
-        Commands::Inspect { path } => inspect(path, bufsz, runtime),
-        Commands::Explain { path } => explain(path, bufsz, runtime),
-        Commands::Configure { path, gene } => configure(path, gene, bufsz, runtime),
+        Commands::IngestMqtt {
+            broker,
+            topic,
+            namespace,
+            db_path,
+            disable_wal,
+        } => {
+            let wal = match disable_wal {
+                Some(true) => OptionVariant::Off,
+                _ => OptionVariant::On,
+            };
+            ingest_mqtt(
+                &broker,
+                topic,
+                namespace,
+                db_path,
+                bufsz,
+                runtime,
+                memory_only.unwrap_or(OptionVariant::Off),
+                wal,
+            )
+        }
+        Commands::Inspect {
+            path,
+            db_path,
+            format,
+            from,
+            to,
+            limit,
+            offset,
+            recursive,
+            as_of,
+        } => inspect(
+            path, db_path, format, from, to, limit, offset, recursive, as_of, bufsz, runtime,
+        ),
+        Commands::Watch {
+            path,
+            db_path,
+            format,
+            interval,
+        } => watch(
+            path,
+            db_path,
+            format,
+            std::time::Duration::from_secs(interval.max(1)),
+            bufsz,
+            runtime,
+        ),
+        Commands::Replay {
+            path,
+            db_path,
+            format,
+        } => replay(path, db_path, format, bufsz, runtime),
+        Commands::Delete {
+            path,
+            db_path,
+            mapping,
+        } => delete(path, db_path, mapping, bufsz, runtime),
+        Commands::Reset { path, db_path } => reset(path, db_path, bufsz, runtime),
+        Commands::Stats {
+            resident: true,
+            url,
+            format,
+            ..
+        } => resident_stats(&url, format, bufsz, runtime),
+        Commands::Stats {
+            namespace,
+            db_path,
+            format,
+            ..
+        } => stats(
+            &namespace.unwrap_or_default(),
+            db_path,
+            format,
+            bufsz,
+            runtime,
+        ),
+        Commands::Prune {
+            namespace,
+            db_path,
+            older_than,
+            keep_last,
+        } => prune(&namespace, db_path, &older_than, keep_last, bufsz, runtime),
+        Commands::Compact {
+            namespace,
+            db_path,
+            before,
+        } => compact(namespace, db_path, &before, bufsz, runtime),
+        Commands::Export {
+            namespace,
+            db_path,
+            out,
+        } => export(&namespace, db_path, out, bufsz, runtime),
+        Commands::Import {
+            namespace,
+            db_path,
+            input,
+        } => import(namespace, db_path, input, bufsz, runtime),
+        Commands::Explain { path, db_path } => explain(path, db_path, bufsz, runtime),
+        Commands::Configure {
+            path,
+            db_path,
+            gene,
+            params,
+            dry_run,
+        } => configure(path, db_path, gene, params, dry_run, bufsz, runtime),
+        Commands::Label {
+            namespace,
+            idx,
+            name,
+            db_path,
+        } => label(&namespace, idx, name, db_path, bufsz, runtime),
         Commands::Completions { shell } => {
-            let mut cmd = Cli::command();
-            print_completions(shell, &mut cmd);
+            match completions_shell(shell, std::env::var("SHELL").ok()) {
+                Ok(shell) => {
+                    let mut cmd = Cli::command();
+                    print_completions(shell, &mut cmd);
+                    Ok(())
+                }
+                Err(e) => {
+                    error!("can not print completions: {e}");
+                    Err(CliError::Usage(e))
+                }
+            }
         }
-    }
+    };
 
     info!("nv stopped.");
+    result
 }
 
 fn main() {
-    tracing_subscriber::fmt::init();
-    info!("This will be logged to stdout");
+    // stdout is for the state printed by the output actors
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
     info!("nv started");
 
-    let pcli = Cli::parse();
+    let mut pcli = Cli::parse();
+    // the flags of the file are parsed ahead of the command line flags that override them
+    if let Some(config) = pcli.config.clone() {
+        let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+        match with_config_file(&config, &args) {
+            Ok(args) => pcli = Cli::parse_from(args),
+            Err(e) => {
+                error!("{e}");
+                std::process::exit(e.exit_code());
+            }
+        }
+    }
     let bufsz: usize = pcli.buffer.unwrap_or(8);
     let memory_only = pcli.memory_only.map(|m| {
         if m {
@@ -95,6 +373,12 @@ fn main() {
 
     let runtime = Runtime::new().unwrap_or_else(|e| panic!("Error creating runtime: {e}"));
 
-    match_command(pcli, &runtime, memory_only, bufsz);
+    let result = match_command(pcli, &runtime, memory_only, bufsz);
     info!("nv stopped.");
+    if let Err(e) = result {
+        error!("{e}");
+        // let the actors drop their connections before exiting
+        drop(runtime);
+        std::process::exit(e.exit_code());
+    }
 }
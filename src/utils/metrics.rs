@@ -0,0 +1,229 @@
+//! A module that provides a lightweight process-wide metrics registry. The actors count what they
+//! handle with `inc`, the directors report how full the mailboxes they send to are with
+//! `set_mailbox`, and the server times requests with `observe_latency`, and `render` writes
+//! everything in the Prometheus text exposition format for `GET /metrics`.
+//!
+//! Every counter is labeled with the namespace of the actor that counted it and the type of the
+//! message. The counters and histogram buckets are atomics - a lock is only held for writing when
+//! a label combination is seen for the first time.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// the upper bounds in seconds of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+const LATENCY_NAME: &str = "nv_post_observations_seconds";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Counter {
+    /// observations applied to their actor
+    Observations,
+    /// state queries answered by an actor
+    Queries,
+    /// observations the journal already holds
+    ConstraintViolations,
+    /// journal writes that failed
+    StoreErrors,
+}
+
+impl Counter {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Observations => "nv_observations_total",
+            Self::Queries => "nv_queries_total",
+            Self::ConstraintViolations => "nv_constraint_violations_total",
+            Self::StoreErrors => "nv_store_errors_total",
+        }
+    }
+
+    const fn help(self) -> &'static str {
+        match self {
+            Self::Observations => "Observations applied to their actor.",
+            Self::Queries => "State queries answered by an actor.",
+            Self::ConstraintViolations => "Observations rejected as duplicates by the journal.",
+            Self::StoreErrors => "Journal writes that failed.",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+type CounterKey = (Counter, String, String);
+
+/// the namespace of a director and the mailbox it reports, ie: `store`
+type MailboxKey = (String, String);
+
+#[derive(Default)]
+struct Mailbox {
+    queued: AtomicU64,
+    capacity: AtomicU64,
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: RwLock<BTreeMap<CounterKey, AtomicU64>>,
+    mailboxes: RwLock<BTreeMap<MailboxKey, Mailbox>>,
+    latencies: RwLock<BTreeMap<String, Histogram>>,
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::default)
+}
+
+/// finds or adds the entry for `key` and updates it while the map is locked
+/// for reading - a poisoned lock drops the update rather than the request
+fn with_entry<K: Ord + Clone, V: Default>(
+    map: &RwLock<BTreeMap<K, V>>,
+    key: &K,
+    update: impl Fn(&V),
+) {
+    if let Ok(entries) = map.read() {
+        if let Some(entry) = entries.get(key) {
+            update(entry);
+            return;
+        }
+    }
+    if let Ok(mut entries) = map.write() {
+        update(entries.entry(key.clone()).or_default());
+    }
+}
+
+/// the namespace label of a path is its first component, ie: `/actors` for
+/// `/actors/one`, the same as the namespace of the director it is sent to
+#[must_use]
+pub fn namespace_of(path: &str) -> String {
+    path.split('/')
+        .find(|c| !c.is_empty())
+        .map_or_else(|| String::from("/"), |c| format!("/{c}"))
+}
+
+/// count one `message_type` message handled for `namespace`
+pub fn inc(counter: Counter, namespace: &str, message_type: &str) {
+    let key = (counter, namespace.to_string(), message_type.to_string());
+    with_entry(&registry().counters, &key, |count| {
+        count.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+/// record how many of the `capacity` messages the `mailbox` of `namespace`
+/// can hold are waiting in it
+pub fn set_mailbox(namespace: &str, mailbox: &str, queued: usize, capacity: usize) {
+    let key = (namespace.to_string(), mailbox.to_string());
+    with_entry(&registry().mailboxes, &key, |m| {
+        m.queued
+            .store(u64::try_from(queued).unwrap_or(u64::MAX), Ordering::Relaxed);
+        m.capacity.store(
+            u64::try_from(capacity).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    });
+}
+
+/// record how long a `POST` of observations to `namespace` took
+pub fn observe_latency(namespace: &str, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    with_entry(&registry().latencies, &namespace.to_string(), |h| {
+        for (bucket, le) in h.buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= le {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        h.count.fetch_add(1, Ordering::Relaxed);
+        h.sum_micros.fetch_add(
+            u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    });
+}
+
+/// label values may not hold raw quotes, backslashes, or newlines
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// every metric in the Prometheus text exposition format
+#[must_use]
+pub fn render() -> String {
+    let mut out = String::new();
+    if let Ok(counters) = registry().counters.read() {
+        let mut current = None;
+        for ((counter, namespace, message_type), count) in counters.iter() {
+            if current != Some(*counter) {
+                current = Some(*counter);
+                let _ = writeln!(out, "# HELP {} {}", counter.name(), counter.help());
+                let _ = writeln!(out, "# TYPE {} counter", counter.name());
+            }
+            let _ = writeln!(
+                out,
+                "{}{{namespace=\"{}\",message_type=\"{}\"}} {}",
+                counter.name(),
+                escape(namespace),
+                escape(message_type),
+                count.load(Ordering::Relaxed)
+            );
+        }
+    }
+    if let Ok(mailboxes) = registry().mailboxes.read() {
+        let gauges = [
+            ("nv_mailbox_queued", "Messages waiting in a mailbox.", true),
+            ("nv_mailbox_capacity", "Messages a mailbox can hold.", false),
+        ];
+        for (name, help, queued) in gauges {
+            if mailboxes.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for ((namespace, mailbox), m) in mailboxes.iter() {
+                let gauge = if queued { &m.queued } else { &m.capacity };
+                let _ = writeln!(
+                    out,
+                    "{name}{{namespace=\"{}\",mailbox=\"{}\"}} {}",
+                    escape(namespace),
+                    escape(mailbox),
+                    gauge.load(Ordering::Relaxed)
+                );
+            }
+        }
+    }
+    if let Ok(latencies) = registry().latencies.read() {
+        let name = LATENCY_NAME;
+        if !latencies.is_empty() {
+            let _ = writeln!(out, "# HELP {name} Latency of POST observation requests.");
+            let _ = writeln!(out, "# TYPE {name} histogram");
+        }
+        for (namespace, h) in latencies.iter() {
+            let namespace = escape(namespace);
+            for (bucket, le) in h.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{{namespace=\"{namespace}\",le=\"{le}\"}} {}",
+                    bucket.load(Ordering::Relaxed)
+                );
+            }
+            let count = h.count.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{namespace=\"{namespace}\",le=\"+Inf\"}} {count}"
+            );
+            #[allow(clippy::cast_precision_loss)]
+            let sum = h.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+            let _ = writeln!(out, "{name}_sum{{namespace=\"{namespace}\"}} {sum}");
+            let _ = writeln!(out, "{name}_count{{namespace=\"{namespace}\"}} {count}");
+        }
+    }
+    out
+}
@@ -3,6 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 use time::format_description::well_known::Iso8601;
+use time::Duration;
 use time::OffsetDateTime;
 use tracing::error;
 use tracing::warn;
@@ -20,13 +21,22 @@ impl std::fmt::Display for TimeError {
     }
 }
 
-/// extract a datetime from an ISO8601 string
+/// an epoch timestamp of at least this many is in milliseconds - as seconds
+/// it would be more than a thousand years from now
+const EPOCH_MILLIS_FROM: i128 = 100_000_000_000;
+
+/// extract a datetime from an ISO8601 string or from a Unix epoch timestamp,
+/// ie: `1673478977` seconds, `1673478977.5` seconds to the millisecond, or
+/// `1673478977000` milliseconds
 ///
 /// # Errors
 ///
 /// Returns [`TimeError`](../struct.TimeError.html) if the
 /// string can not be parsed into datetime
 pub fn extract_datetime(datetime_str: &str) -> TimeResult {
+    if let Some(datetime) = epoch_datetime(datetime_str) {
+        return datetime;
+    }
     match OffsetDateTime::parse(datetime_str, &Iso8601::DEFAULT) {
         Ok(d) => Ok(d),
         Err(e) => {
@@ -38,6 +48,72 @@ pub fn extract_datetime(datetime_str: &str) -> TimeResult {
     }
 }
 
+/// the datetime of an epoch timestamp - `None` if the string is not a number
+/// and is left to the ISO8601 parser
+fn epoch_datetime(datetime_str: &str) -> Option<TimeResult> {
+    let (whole, fraction) = datetime_str.split_once('.').unwrap_or((datetime_str, ""));
+    let digits = whole.strip_prefix('-').unwrap_or(whole);
+    if digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let out_of_range = || TimeError {
+        reason: format!("epoch timestamp {datetime_str} is out of range"),
+    };
+    let Ok(number) = whole.parse::<i128>() else {
+        return Some(Err(out_of_range()));
+    };
+    let nanos = if number.abs() >= EPOCH_MILLIS_FROM {
+        number.checked_mul(1_000_000)
+    } else {
+        // the fraction of a second to the millisecond
+        let millis: i128 = format!("{fraction:0<3}")[..3].parse().unwrap_or(0);
+        let millis = if whole.starts_with('-') {
+            -millis
+        } else {
+            millis
+        };
+        number
+            .checked_mul(1_000_000_000)
+            .and_then(|nanos| nanos.checked_add(millis * 1_000_000))
+    };
+    Some(
+        nanos
+            .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).ok())
+            .ok_or_else(out_of_range),
+    )
+}
+
+/// parse a duration of a whole number of seconds, minutes, hours, days, or
+/// weeks, ie: `90s`, `15m`, `12h`, `30d`, or `2w`
+///
+/// # Errors
+///
+/// Returns [`TimeError`](../struct.TimeError.html) if the number or the unit
+/// is missing or unknown
+pub fn parse_duration(duration_str: &str) -> Result<Duration, TimeError> {
+    let duration_str = duration_str.trim();
+    let split = duration_str
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration_str.len());
+    let (number, unit) = duration_str.split_at(split);
+    let number: i64 = number.parse().map_err(|_| TimeError {
+        reason: format!("'{duration_str}' does not start with a number"),
+    })?;
+    match unit {
+        "s" => Ok(Duration::seconds(number)),
+        "m" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "d" => Ok(Duration::days(number)),
+        "w" => Ok(Duration::weeks(number)),
+        _ => Err(TimeError {
+            reason: format!("'{duration_str}' does not end with one of the units s, m, h, d, or w"),
+        }),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct OffsetDateTimeWrapper {
     pub datetime_num: i64,
@@ -0,0 +1,22 @@
+//! fixtures shared by the integration tests - each test crate pulls in the
+//! ones it needs with `mod common;`
+#![allow(dead_code)]
+
+use glob::glob;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::store_actor_sqlite;
+use std::fs;
+
+/// delete the sqlite db of a test and its wal and shm files
+pub fn remove_db(db_file_prefix: &str) {
+    for entry in glob(&format!("{db_file_prefix}.db*")).unwrap_or_else(|e| panic!("{e}")) {
+        fs::remove_file(entry.unwrap_or_else(|e| panic!("{e}"))).unwrap_or_else(|e| panic!("{e}"));
+    }
+}
+
+/// a director of `namespace` persisting to `{db_file_prefix}.db`
+pub fn setup_director(db_file_prefix: &str, namespace: &str) -> Handle {
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    director::new(namespace, 8, None, Some(store_actor))
+}
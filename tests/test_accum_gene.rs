@@ -1,11 +1,20 @@
+mod common;
+
 use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
 use navactor::actors::actor::State;
 use navactor::actors::genes::accum_gene::AccumGene;
 use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::gene::TimeScope;
+use navactor::actors::message::Labels;
 use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::macros::datetime;
 use time::OffsetDateTime;
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_accum_gene() {
     let mut state: State<f64> = State::new();
@@ -26,6 +35,7 @@ fn test_accum_gene() {
         path: String::from("/"),
         datetime: OffsetDateTime::now_utc(),
         values,
+        labels: Labels::new(),
     };
 
     let r = g1.apply_operators(state, msg);
@@ -44,3 +54,127 @@ fn test_accum_gene() {
     // accum is sum: 3.2 + 4.11 == 7.3.1
     assert_ulps_eq!(new_state.get(&199).unwrap(), &7.31, max_ulps = 4);
 }
+
+fn reading(datetime: OffsetDateTime, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(0, value);
+    Message::Observations {
+        path: String::from("/"),
+        datetime,
+        values,
+        labels: Labels::new(),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_hourly_accum_resets_per_hour() {
+    let g1 = AccumGene {
+        time_scope: TimeScope::Hour,
+        ..Default::default()
+    };
+
+    let mut state = State::new();
+    for (datetime, value) in [
+        (datetime!(2024-01-15 09:10 UTC), 1.0),
+        // out of order within the hour still accumulates
+        (datetime!(2024-01-15 09:50 UTC), 2.0),
+        (datetime!(2024-01-15 09:05 UTC), 4.0),
+    ] {
+        state = g1.apply_operators(state, reading(datetime, value)).unwrap();
+    }
+    assert_ulps_eq!(state.get(&0).unwrap(), &7.0, max_ulps = 4);
+
+    // the next hour starts fresh
+    state = g1
+        .apply_operators(state, reading(datetime!(2024-01-15 10:00 UTC), 8.0))
+        .unwrap();
+    assert_ulps_eq!(state.get(&0).unwrap(), &8.0, max_ulps = 4);
+
+    // a late report for the previous hour is ignored
+    state = g1
+        .apply_operators(state, reading(datetime!(2024-01-15 09:59 UTC), 16.0))
+        .unwrap();
+    assert_ulps_eq!(state.get(&0).unwrap(), &8.0, max_ulps = 4);
+}
+
+#[test]
+fn test_time_scope_buckets() {
+    let base = OffsetDateTime::UNIX_EPOCH;
+    assert_eq!(
+        TimeScope::Forever.bucket(base, datetime!(2024-01-15 09:10 UTC)),
+        None
+    );
+    assert_eq!(
+        TimeScope::Month.bucket(
+            datetime!(2023-11-30 00:00 UTC),
+            datetime!(2024-01-01 00:00 UTC)
+        ),
+        Some(2)
+    );
+    assert_eq!(
+        TimeScope::Day.bucket(base, datetime!(2024-01-15 23:59 +01:00)),
+        TimeScope::Day.bucket(base, datetime!(2024-01-15 22:59 UTC))
+    );
+    assert_eq!(
+        TimeScope::Minute.bucket(base, datetime!(1969-12-31 23:59:30 UTC)),
+        Some(-1)
+    );
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_hourly_meter_is_mapped_and_replayed() {
+    let db_file_prefix = "/tmp/hourly_meters";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/meters");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/meters"),
+            gene_type: GeneType::Accum,
+            params: GeneParams {
+                time_scope: Some(TimeScope::Hour),
+                ..Default::default()
+            },
+        })
+        .await
+        .unwrap();
+
+    let mut latest = None;
+    for (datetime, value) in [
+        (datetime!(2024-01-15 09:10 UTC), 1.0),
+        (datetime!(2024-01-15 09:50 UTC), 2.0),
+        (datetime!(2024-01-15 10:05 UTC), 8.0),
+        // a late report for the previous hour is ignored
+        (datetime!(2024-01-15 09:59 UTC), 16.0),
+    ] {
+        let mut message = reading(datetime, value);
+        if let Message::Observations { path, .. } = &mut message {
+            *path = String::from("/meters/one");
+        }
+        latest = Some(director.ask(message).await.unwrap());
+    }
+    match latest {
+        Some(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&0).unwrap(), &8.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    // a new director reads the time scope back and the replay starts the
+    // hour fresh again
+    let restarted = setup_director(db_file_prefix, "/meters");
+    match restarted
+        .ask(Message::Query {
+            path: String::from("/meters/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&0).unwrap(), &8.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
@@ -1,18 +1,19 @@
 use approx::assert_ulps_eq;
+use navactor::actors::actor::State;
 use navactor::actors::director;
 use navactor::actors::genes::gauge_and_accum_gene::GaugeAndAccumGene;
+use navactor::actors::message::Labels;
 use navactor::actors::message::Message;
 use navactor::actors::message::MtHint;
 use navactor::actors::state_actor;
 use navactor::io::json_decoder;
-use std::collections::HashMap;
 use time::OffsetDateTime;
 use tokio::runtime::Runtime;
 
 /**
  * create a state actor and send it updates via `Message::Observations` a hashmap
  */
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_actor_ask() {
     let rt = Runtime::new().unwrap();
@@ -25,7 +26,7 @@ fn test_actor_ask() {
         let state_actor = state_actor::new("/".to_string(), 8, gene, None); // parse input
 
         // set an initial state
-        let mut values = HashMap::new();
+        let mut values = State::new();
         values.insert(1, 1.9);
         values.insert(2, 2.9);
 
@@ -33,18 +34,20 @@ fn test_actor_ask() {
             path: String::from("/"),
             datetime: OffsetDateTime::now_utc(),
             values,
+            labels: Labels::new(),
         };
         let r = state_actor.tell(cmd).await;
         assert_eq!(r.ok(), Some(()));
 
         // update state
-        let mut values = HashMap::new();
+        let mut values = State::new();
         values.insert(1, 1.8);
         let datetime = OffsetDateTime::now_utc();
         let cmd = Message::Observations {
             path: String::from("/"),
             datetime,
             values,
+            labels: Labels::new(),
         };
         let reply = state_actor.ask(cmd).await;
 
@@ -53,7 +56,8 @@ fn test_actor_ask() {
             Ok(Message::StateReport {
                 datetime: _,
                 path: _,
-                values: _
+                values: _,
+                ..
             }),
         ));
 
@@ -61,6 +65,7 @@ fn test_actor_ask() {
             datetime: _,
             path: _,
             values: new_values,
+            ..
         }) = reply
         {
             // ensure that the initial state for 2 is still there but that the initial state for 1
@@ -76,7 +81,7 @@ fn test_actor_ask() {
 /**
  * create a decoder actor factory and send it updates via JSON in `Message::Content`
  */
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_decoder_ask() {
     let rt = Runtime::new().unwrap();
@@ -107,7 +112,8 @@ fn test_decoder_ask() {
             Ok(Message::StateReport {
                 datetime: _,
                 path: _,
-                values: _
+                values: _,
+                ..
             }),
         ));
 
@@ -115,6 +121,7 @@ fn test_decoder_ask() {
             datetime: _,
             path: _,
             values: new_values,
+            ..
         }) = reply
         {
             // ensure that the initial state for 2 is still there but that the initial state for 1
@@ -134,7 +141,7 @@ fn test_decoder_ask() {
 ///
 /// But for now we need the CLI to configure mappings and that is just params, no json.
 ///
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_decoder_ask_accum_and_gauge() {
     let rt = Runtime::new().unwrap();
@@ -173,7 +180,8 @@ fn test_decoder_ask_accum_and_gauge() {
                 Ok(Message::StateReport {
                     datetime: _,
                     path: _,
-                    values: _
+                    values: _,
+                    ..
                 }),
             ));
 
@@ -181,6 +189,7 @@ fn test_decoder_ask_accum_and_gauge() {
                 datetime: _,
                 path: _,
                 values: new_values,
+                ..
             }) = reply
             {
                 // ensure that the initial state for 2 is still there but that the initial state for 1
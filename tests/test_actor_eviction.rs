@@ -0,0 +1,111 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+fn observation(path: &str, idx: i32, n: i64) -> Message<f64> {
+    let mut values = State::new();
+    #[allow(clippy::cast_precision_loss)]
+    values.insert(idx, n as f64);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+/// an actor that is still live does not see an observation journaled behind
+/// the director's back but a resurrected one replays it
+#[tokio::test]
+async fn test_lru_eviction() {
+    let db_file_prefix = "/tmp/evicted";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        max_resident_actors: Some(2),
+        ..Default::default()
+    };
+    let director = director::new_with_options("/evicted", 8, None, Some(store_actor), options);
+
+    for (n, path) in ["/evicted/one", "/evicted/two"].iter().enumerate() {
+        director
+            .ask(observation(path, 1, i64::try_from(n).unwrap_or_default()))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    // one is now used more recently than two
+    state_of(&director, "/evicted/one").await;
+    // and three takes the place of two
+    director
+        .ask(observation("/evicted/three", 1, 2))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    let side_store = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    for path in ["/evicted/one", "/evicted/two", "/evicted/three"] {
+        side_store
+            .ask(observation(path, 9, 9))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    let three = state_of(&director, "/evicted/three").await;
+    assert_eq!(three.get(&1), Some(&2.0));
+    assert_eq!(three.get(&9), None);
+
+    // two is resurrected with its whole journal and one is evicted for it
+    let two = state_of(&director, "/evicted/two").await;
+    assert_eq!(two.get(&1), Some(&1.0));
+    assert_eq!(two.get(&9), Some(&9.0));
+
+    let one = state_of(&director, "/evicted/one").await;
+    assert_eq!(one.get(&1), Some(&0.0));
+    assert_eq!(one.get(&9), Some(&9.0));
+}
+
+/// without a limit every actor stays live
+#[tokio::test]
+async fn test_no_eviction_by_default() {
+    let db_file_prefix = "/tmp/resident";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/resident", 8, None, Some(store_actor));
+
+    for n in 0..3 {
+        director
+            .ask(observation(&format!("/resident/{n}"), 1, n))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    let side_store = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    side_store
+        .ask(observation("/resident/0", 9, 9))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    assert_eq!(state_of(&director, "/resident/0").await.get(&9), None);
+}
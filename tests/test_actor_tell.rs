@@ -9,8 +9,8 @@ use tokio::sync::oneshot;
 /**
  * Create a director actor factory and send it json via `Message::Content`.
  */
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::expect_used))]
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::expect_used)]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_actor_tell() {
     let rt = Runtime::new().unwrap();
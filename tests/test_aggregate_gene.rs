@@ -0,0 +1,137 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::AggregateFn;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+fn observation(path: &str, secs: i64, values: &[(i32, f64)]) -> Message<f64> {
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values: values.iter().copied().collect(),
+        labels: Labels::new(),
+    }
+}
+
+async fn observe(director: &Handle, path: &str, secs: i64, values: &[(i32, f64)]) {
+    director
+        .ask(observation(path, secs, values))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+async fn aggregate(director: &Handle, path: &str, function: AggregateFn) {
+    director
+        .ask(Message::GeneMapping {
+            path: String::from(path),
+            gene_type: GeneType::Aggregate,
+            params: GeneParams {
+                aggregate: Some(function),
+                ..Default::default()
+            },
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_aggregate_sums_children() {
+    let db_file_prefix = "/tmp/aggregate_gene";
+    remove_db(db_file_prefix);
+    let director_of = || {
+        let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+        director::new("/agg", 8, None, Some(store_actor))
+    };
+
+    let director = director_of();
+    aggregate(&director, "/agg/building/1", AggregateFn::Sum).await;
+    // the floors under it keep the default gauge - an aggregate mapping is not inherited
+    observe(&director, "/agg/building/1/floor1", 0, &[(1, 2.0)]).await;
+    observe(
+        &director,
+        "/agg/building/1/floor2",
+        0,
+        &[(1, 3.0), (2, 1.0)],
+    )
+    .await;
+    observe(&director, "/agg/building/1/floor1", 1, &[(1, 4.0)]).await;
+    assert_eq!(
+        state_of(&director, "/agg/building/1").await,
+        State::from([(1, 7.0), (2, 1.0)])
+    );
+    assert_eq!(
+        state_of(&director, "/agg/building/1/floor1").await,
+        State::from([(1, 4.0)])
+    );
+
+    // a resurrected aggregate is rebuilt from its children
+    let restarted = director_of();
+    assert_eq!(
+        state_of(&restarted, "/agg/building/1").await,
+        State::from([(1, 7.0), (2, 1.0)])
+    );
+}
+
+#[tokio::test]
+async fn test_aggregates_roll_up() {
+    let director = director::new("/agg", 8, None, None);
+    aggregate(&director, "/agg/campus", AggregateFn::Sum).await;
+    aggregate(&director, "/agg/campus/a", AggregateFn::Mean).await;
+    aggregate(&director, "/agg/campus/b", AggregateFn::Mean).await;
+    observe(&director, "/agg/campus/a/1", 0, &[(1, 2.0)]).await;
+    observe(&director, "/agg/campus/a/2", 0, &[(1, 4.0)]).await;
+    observe(&director, "/agg/campus/b/1", 0, &[(1, 10.0)]).await;
+
+    assert_eq!(
+        state_of(&director, "/agg/campus/a").await,
+        State::from([(1, 3.0)])
+    );
+    assert_eq!(
+        state_of(&director, "/agg/campus").await,
+        State::from([(1, 13.0)])
+    );
+}
+
+#[tokio::test]
+async fn test_aggregate_is_not_observed_directly() {
+    let director = director::new("/agg", 8, None, None);
+    aggregate(&director, "/agg/total", AggregateFn::Sum).await;
+    assert!(director
+        .ask(observation("/agg/total", 0, &[(1, 1.0)]))
+        .await
+        .is_err());
+}
+
+#[test]
+fn test_aggregate_mean_of_integers_is_rejected() {
+    let mean = GeneParams {
+        aggregate: Some(AggregateFn::Mean),
+        ..Default::default()
+    };
+    assert!(director::configured_gene::<i64>(GeneType::Aggregate, &mean).is_err());
+    assert!(director::configured_gene::<i64>(GeneType::Aggregate, &GeneParams::default()).is_ok());
+    assert!(director::configured_gene::<f64>(GeneType::Aggregate, &mean).is_ok());
+}
@@ -0,0 +1,205 @@
+use futures::StreamExt;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::alert_gene::AlertGene;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::gene::Threshold;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::state_actor;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use poem::web::sse::Event;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+fn reading(path: &str, n: i64, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+fn thresholds() -> GeneParams {
+    let mut thresholds = BTreeMap::new();
+    thresholds.insert(
+        1,
+        Threshold {
+            low: Some(10.0),
+            high: Some(80.0),
+        },
+    );
+    GeneParams {
+        thresholds: Some(thresholds),
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_alert_gene_crossings() {
+    let gene = AlertGene::with_params(&thresholds());
+    assert!(gene.validate().is_ok());
+
+    let before = gene
+        .apply_operators(State::new(), reading("/", 0, 50.0))
+        .unwrap();
+    let in_band = gene
+        .apply_operators(before.clone(), reading("/", 1, 70.0))
+        .unwrap();
+    assert!(gene.crossings(&before, &in_band).is_empty());
+
+    let above = gene
+        .apply_operators(in_band.clone(), reading("/", 2, 90.0))
+        .unwrap();
+    assert_eq!(gene.crossings(&in_band, &above), vec![(1, 90.0, 80.0)]);
+
+    // still above is not another crossing
+    let still_above = gene
+        .apply_operators(above.clone(), reading("/", 3, 95.0))
+        .unwrap();
+    assert!(gene.crossings(&above, &still_above).is_empty());
+
+    let below = gene
+        .apply_operators(still_above.clone(), reading("/", 4, 5.0))
+        .unwrap();
+    assert_eq!(gene.crossings(&still_above, &below), vec![(1, 5.0, 10.0)]);
+
+    assert!(AlertGene::default().validate().is_err());
+}
+
+/// the messages told to the output of a state actor
+async fn told(outputs: &mut mpsc::Receiver<Envelope<f64>>) -> Vec<Message<f64>> {
+    let mut messages = vec![];
+    while let Ok(Envelope { message, .. }) = outputs.try_recv() {
+        messages.push(message);
+    }
+    messages
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_crossing_the_high_bound_alerts_the_output_once() {
+    let (sender, mut outputs) = mpsc::channel(16);
+    let actor = state_actor::new(
+        String::from("/alerting/one"),
+        8,
+        Box::new(AlertGene::with_params(&thresholds())),
+        Some(Handle::new(sender)),
+    );
+    let alerts = |messages: Vec<Message<f64>>| -> Vec<Message<f64>> {
+        messages
+            .into_iter()
+            .filter(|message| matches!(message, Message::Alert { .. }))
+            .collect()
+    };
+
+    actor.ask(reading("/alerting/one", 0, 50.0)).await.unwrap();
+    actor.ask(reading("/alerting/one", 1, 60.0)).await.unwrap();
+    assert!(alerts(told(&mut outputs).await).is_empty());
+
+    actor.ask(reading("/alerting/one", 2, 85.0)).await.unwrap();
+    actor.ask(reading("/alerting/one", 3, 90.0)).await.unwrap();
+    let raised = alerts(told(&mut outputs).await);
+    assert_eq!(raised.len(), 1);
+    assert!(matches!(
+        &raised[0],
+        Message::Alert { path, idx: 1, value, threshold }
+            if path == "/alerting/one" && (*value - 85.0).abs() < f64::EPSILON && (*threshold - 80.0).abs() < f64::EPSILON
+    ));
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_director_publishes_alerts() {
+    let nv = director::new("/alerting", 8, None, None);
+    nv.ask(Message::GeneMapping {
+        path: String::from("/alerting"),
+        gene_type: GeneType::Alert,
+        params: thresholds(),
+    })
+    .await
+    .unwrap();
+    let rejected = nv
+        .ask(Message::GeneMapping {
+            path: String::from("/alerting/bad"),
+            gene_type: GeneType::Alert,
+            params: GeneParams::default(),
+        })
+        .await;
+    assert!(rejected.is_err());
+
+    let (stream_to, mut events) = mpsc::channel(16);
+    nv.ask(Message::SubscribeEvents {
+        path: String::from("/alerting"),
+        after: None,
+        stream_to,
+    })
+    .await
+    .unwrap();
+    nv.ask(reading("/alerting/one", 0, 50.0)).await.unwrap();
+    nv.ask(reading("/alerting/one", 1, 81.0)).await.unwrap();
+    nv.ask(reading("/alerting/one", 2, 82.0)).await.unwrap();
+
+    let mut alerts = 0;
+    while let Ok(event) = events.try_recv() {
+        if let Message::Event { report, .. } = event {
+            if matches!(*report, Message::Alert { idx: 1, .. }) {
+                alerts += 1;
+            }
+        }
+    }
+    assert_eq!(alerts, 1);
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_alerts_are_streamed() {
+    let nv: Arc<Handle> = Arc::new(director::new("/streamed", 8, None, None));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/streamed"),
+        gene_type: GeneType::Alert,
+        params: thresholds(),
+    })
+    .await
+    .unwrap();
+    let config = HttpServerConfig::new(None, None, None, String::from("streamed"));
+    let cli = TestClient::new(build_app(Arc::clone(&nv), &config, None, Some(true)));
+
+    let resp = cli.get("/alerts/streamed").send().await;
+    resp.assert_status_is_ok();
+    let mut events = resp.sse_stream();
+    nv.ask(reading("/streamed/one", 0, 50.0)).await.unwrap();
+    nv.ask(reading("/streamed/one", 1, 5.0)).await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await
+        .unwrap_or_else(|e| panic!("no event: {e}"));
+    match event {
+        Some(Event::Message {
+            id, event, data, ..
+        }) => {
+            assert_eq!(id, "3");
+            assert_eq!(event, "alert");
+            let alert: serde_json::Value = serde_json::from_str(&data).unwrap();
+            assert_eq!(
+                alert,
+                serde_json::json!({"path": "/streamed/one", "idx": 1, "value": 5.0, "threshold": 10.0})
+            );
+        }
+        e => panic!("not an alert: {e:?}"),
+    }
+}
@@ -0,0 +1,86 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::io::net::api_server::api_spec;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+fn client() -> TestClient<poem::endpoint::BoxEndpoint<'static>> {
+    let nv: Arc<Handle> = Arc::new(director::new("/errors", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("errors"));
+    TestClient::new(build_app(nv, &config, None, Some(true)))
+}
+
+#[tokio::test]
+async fn test_errors_have_a_json_body() {
+    let cli = client();
+
+    let resp = cli.get("/api/actors/errors/none").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_json(&json!({
+        "error": "No observations for id `none`",
+        "code": "not_found",
+        "path": "/errors/none",
+    }))
+    .await;
+
+    let resp = cli
+        .get("/api/actors/errors/none")
+        .query("bucket", &"yesterday")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    let json = resp.json().await;
+    let error = json.value().object();
+    error.get("code").assert_string("bad_request");
+    error.get("path").assert_string("/errors/none");
+
+    let observation = json!({
+        "path": "/errors/one",
+        "datetime": "2023-01-11T23:17:57Z",
+        "values": {"1": 1.0},
+    });
+    cli.post("/api/actors/errors/one")
+        .body_json(&observation)
+        .send()
+        .await
+        .assert_status_is_ok();
+    let resp = cli
+        .post("/api/actors/errors/one")
+        .header("If-Match", "0")
+        .body_json(&observation)
+        .send()
+        .await;
+    resp.assert_status(StatusCode::CONFLICT);
+    resp.assert_json(&json!({
+        "error": "/errors/one is at version 1",
+        "code": "version_conflict",
+        "path": "/errors/one",
+    }))
+    .await;
+
+    let resp = cli.delete("/api/genes/errors/none").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_json(&json!({
+        "error": "No gene mapping for `/errors/none`",
+        "code": "not_found",
+        "path": "/errors/none",
+    }))
+    .await;
+}
+
+#[test]
+fn test_error_responses_are_in_the_spec() {
+    let config = HttpServerConfig::new(None, None, None, String::from("errors"));
+    let spec: serde_json::Value =
+        serde_json::from_str(&api_spec(&config)).unwrap_or_else(|e| panic!("{e}"));
+    assert!(spec["components"]["schemas"]["ApiError"].is_object());
+    let not_found = &spec["paths"]["/actors/{namespace<.+}/>:id"]["get"]["responses"]["404"];
+    assert_eq!(
+        not_found["content"]["application/json; charset=utf-8"]["schema"]["$ref"],
+        json!("#/components/schemas/ApiError")
+    );
+}
@@ -0,0 +1,65 @@
+use navactor::actors::director;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::serve;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_spec_is_served() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("actors"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli.get("/api/spec.json").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/json");
+    let body = resp.0.into_body().into_string().await.unwrap_or_default();
+    let spec: serde_json::Value = serde_json::from_str(&body).unwrap_or_else(|e| panic!("{e}"));
+
+    assert!(spec["openapi"].is_string());
+    assert_eq!(spec["servers"][0]["url"], "http://localhost:8800/api");
+    let paths = spec["paths"]
+        .as_object()
+        .unwrap_or_else(|| panic!("no paths in {spec}"));
+    for prefix in ["/actors/", "/genes/", "/series/"] {
+        assert!(
+            paths.keys().any(|path| path.starts_with(prefix)),
+            "no {prefix} paths in {spec}"
+        );
+    }
+    assert!(paths.keys().any(|path| path == "/genes/batch"));
+    assert!(spec["components"]["schemas"]["ApiObservations"].is_object());
+}
+
+#[tokio::test]
+async fn test_spec_out_write_failure_stops_serve() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let config = HttpServerConfig::new(Some(0), None, None, String::from("actors"))
+        .with_spec_out(PathBuf::from("/nonexistent/navactor/spec.json"));
+
+    let r = serve(nv, config, None, Some(true)).await;
+    assert!(r.is_err(), "{r:?}");
+}
+
+#[tokio::test]
+async fn test_spec_out_is_written() {
+    let spec_out = std::env::temp_dir().join("navactor_test_spec.json");
+    let _ = std::fs::remove_file(&spec_out);
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    // the write happens before the listener is bound so an interface that can
+    // not be bound ends serve right after the spec is written
+    let config = HttpServerConfig::new(
+        Some(0),
+        Some(String::from("256.0.0.1")),
+        None,
+        String::from("actors"),
+    )
+    .with_spec_out(spec_out.clone());
+
+    let r = serve(nv, config, None, Some(true)).await;
+    assert!(r.is_err(), "{r:?}");
+    let spec = std::fs::read_to_string(&spec_out).unwrap_or_else(|e| panic!("{e}"));
+    assert!(spec.contains("/actors/"));
+}
@@ -0,0 +1,102 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use std::time::Duration;
+use std::time::Instant;
+use time::OffsetDateTime;
+
+const OBSERVATIONS: i64 = 20_000;
+
+fn observation(path: &str, n: i64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn accumulating_director(namespace: &str) -> Handle {
+    let nv = director::new(namespace, 64, None, None);
+    nv.ask(Message::GeneMapping {
+        path: String::from(namespace),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not map {namespace}: {e}"));
+    nv
+}
+
+async fn total(nv: &Handle, path: &str) -> Option<f64> {
+    match nv
+        .ask_timeout(
+            Message::Query {
+                path: String::from(path),
+                hint: MtHint::State,
+            },
+            Duration::from_secs(10),
+        )
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values.get(&1).copied(),
+        r => panic!("bad response to query: {r:?}"),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+#[tokio::test]
+async fn test_tell_many() {
+    let nv = accumulating_director("/bulk").await;
+    let started = Instant::now();
+    // runs of more messages than the mailbox holds
+    for batch in (0..OBSERVATIONS).collect::<Vec<_>>().chunks(1000) {
+        nv.tell_many(batch.iter().map(|&n| observation("/bulk/one", n)).collect())
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    // an empty batch queues nothing
+    nv.tell_many(vec![]).await.unwrap_or_else(|e| panic!("{e}"));
+    // the query is queued after every observation of the batches
+    assert_eq!(total(&nv, "/bulk/one").await, Some(OBSERVATIONS as f64));
+    println!(
+        "{OBSERVATIONS} observations told in batches in {:?}",
+        started.elapsed()
+    );
+}
+
+#[allow(clippy::cast_precision_loss)]
+#[tokio::test]
+async fn test_observations_sink() {
+    let nv = accumulating_director("/sunk").await;
+    let started = Instant::now();
+    let sink = nv.observations_sink();
+    for n in 0..OBSERVATIONS {
+        sink.send(observation("/sunk/one", n))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    drop(sink);
+    // the sink is not ordered with the queries of the handle so wait for
+    // the last observation to be applied
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while total(&nv, "/sunk/one").await != Some(OBSERVATIONS as f64) {
+        assert!(
+            Instant::now() < deadline,
+            "the sunk observations were not applied"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    println!(
+        "{OBSERVATIONS} observations sunk in {:?}",
+        started.elapsed()
+    );
+}
@@ -0,0 +1,230 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+const DB_FILE_PREFIX: &str = "/tmp/compact";
+const ACCUM: &str = "/compact/accum/one";
+const DAILY: &str = "/compact/daily/one";
+
+fn hour(n: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + n * 3600).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn director_of_db() -> Handle {
+    let store_actor = store_actor_sqlite::new(8, String::from(DB_FILE_PREFIX), false, false);
+    director::new("/compact", 8, None, Some(store_actor))
+}
+
+async fn ask(director: &Handle, message: Message<f64>) -> Message<f64> {
+    director
+        .ask(message)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn observe(director: &Handle, path: &str, n: i64) {
+    let mut values = State::new();
+    #[allow(clippy::cast_precision_loss)]
+    values.insert(1, n as f64);
+    ask(
+        director,
+        Message::Observations {
+            path: String::from(path),
+            datetime: hour(n),
+            values,
+            labels: Labels::new(),
+        },
+    )
+    .await;
+}
+
+async fn state_of(director: &Handle, path: &str) -> (State<f64>, u64) {
+    match ask(
+        director,
+        Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        },
+    )
+    .await
+    {
+        Message::StateReport {
+            values, version, ..
+        } => (values, version),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+async fn observations_of(path: &str) -> u64 {
+    let store = SqliteStore::open(DB_FILE_PREFIX, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let stats = store
+        .path_stats(path)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    store.close().await;
+    stats.observations
+}
+
+#[tokio::test]
+async fn test_compaction_resurrects_the_same_state() {
+    remove_db(DB_FILE_PREFIX);
+    let director = director_of_db();
+    for (path, gene_type) in [
+        ("/compact/accum", GeneType::Accum),
+        ("/compact/daily", GeneType::DailyAccum),
+    ] {
+        ask(
+            &director,
+            Message::GeneMapping {
+                path: String::from(path),
+                gene_type,
+                params: GeneParams::default(),
+            },
+        )
+        .await;
+    }
+    for n in 0..5 {
+        observe(&director, ACCUM, n).await;
+        observe(&director, DAILY, n).await;
+    }
+    ask(
+        &director,
+        Message::Reset {
+            path: String::from(ACCUM),
+        },
+    )
+    .await;
+    for n in 5..10 {
+        observe(&director, ACCUM, n).await;
+    }
+    let live = state_of(&director, ACCUM).await;
+    assert_eq!(live, (State::from([(1, 35.0)]), 10));
+
+    // the 7 observations and the reset before the cutoff are collapsed and
+    // the daily accumulator, whose gene keeps a bucket for every day, is not
+    let compacted = ask(
+        &director,
+        Message::Compact {
+            path: String::from("/compact/"),
+            before: hour(7),
+        },
+    )
+    .await;
+    assert!(
+        matches!(compacted, Message::Compacted { paths: 1, rows: 8 }),
+        "{compacted:?}"
+    );
+    assert_eq!(observations_of(ACCUM).await, 3);
+    assert_eq!(observations_of(DAILY).await, 5);
+
+    let resurrected = state_of(&director_of_db(), ACCUM).await;
+    assert_eq!(resurrected, live);
+
+    // nothing is left to compact before the same cutoff
+    let compacted = ask(
+        &director_of_db(),
+        Message::Compact {
+            path: String::from("/compact/"),
+            before: hour(7),
+        },
+    )
+    .await;
+    assert!(
+        matches!(compacted, Message::Compacted { paths: 0, rows: 0 }),
+        "{compacted:?}"
+    );
+
+    // a later compaction starts from the snapshot of the earlier one
+    let director = director_of_db();
+    observe(&director, ACCUM, 10).await;
+    let live = state_of(&director, ACCUM).await;
+    let compacted = ask(
+        &director,
+        Message::Compact {
+            path: String::from("/compact/"),
+            before: hour(20),
+        },
+    )
+    .await;
+    assert!(
+        matches!(compacted, Message::Compacted { paths: 1, rows: 4 }),
+        "{compacted:?}"
+    );
+    assert_eq!(observations_of(ACCUM).await, 0);
+    assert_eq!(state_of(&director_of_db(), ACCUM).await, live);
+    assert_eq!(live, (State::from([(1, 45.0)]), 11));
+}
+
+#[tokio::test]
+async fn test_compaction_needs_a_journal() {
+    let director = director::new("/compactmem", 8, None, None);
+    assert!(director
+        .ask(Message::Compact {
+            path: String::from("/compactmem/"),
+            before: hour(7),
+        })
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_moving_avg_is_not_compacted() {
+    let db_file_prefix = "/tmp/compactwindow";
+    remove_db(db_file_prefix);
+    let director_of_window_db = || {
+        let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+        director::new("/compact", 8, None, Some(store_actor))
+    };
+    let director = director_of_window_db();
+    ask(
+        &director,
+        Message::GeneMapping {
+            path: String::from("/compact/window"),
+            gene_type: GeneType::MovingAvg,
+            params: GeneParams {
+                window: Some(3),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+    for n in 0..5 {
+        observe(&director, "/compact/window/one", n).await;
+    }
+
+    // the window of the gene is not in the state so the journal is kept
+    let compacted = ask(
+        &director,
+        Message::Compact {
+            path: String::from("/compact/"),
+            before: hour(7),
+        },
+    )
+    .await;
+    assert!(
+        matches!(compacted, Message::Compacted { paths: 0, rows: 0 }),
+        "{compacted:?}"
+    );
+
+    // and the resurrected actor averages over the window of the live one
+    let director = director_of_window_db();
+    observe(&director, "/compact/window/one", 5).await;
+    assert_eq!(
+        state_of(&director, "/compact/window/one").await,
+        (State::from([(1, 4.0)]), 6)
+    );
+}
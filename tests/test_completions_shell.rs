@@ -0,0 +1,33 @@
+use clap_complete::Shell;
+use navactor::cli::runner::completions_shell;
+
+#[test]
+fn test_shell_inferred_from_env() {
+    assert_eq!(
+        completions_shell(None, Some(String::from("/bin/zsh"))),
+        Ok(Shell::Zsh)
+    );
+    assert_eq!(
+        completions_shell(None, Some(String::from("/usr/local/bin/bash"))),
+        Ok(Shell::Bash)
+    );
+    assert_eq!(
+        completions_shell(None, Some(String::from("fish"))),
+        Ok(Shell::Fish)
+    );
+}
+
+#[test]
+fn test_explicit_shell_overrides_env() {
+    assert_eq!(
+        completions_shell(Some(Shell::Fish), Some(String::from("/bin/zsh"))),
+        Ok(Shell::Fish)
+    );
+    assert_eq!(completions_shell(Some(Shell::Bash), None), Ok(Shell::Bash));
+}
+
+#[test]
+fn test_undetermined_shell_is_an_error() {
+    assert!(completions_shell(None, None).is_err());
+    assert!(completions_shell(None, Some(String::from("/bin/tcsh"))).is_err());
+}
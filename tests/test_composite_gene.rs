@@ -0,0 +1,150 @@
+mod common;
+
+use common::{remove_db, setup_director};
+use navactor::actors::actor::State;
+use navactor::actors::genes::composite_gene::CompositeGene;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::gene::IndexOperator;
+use navactor::actors::genes::gene::IndexRange;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::OffsetDateTime;
+
+fn reading(path: &str, n: i64, values: &[(i32, f64)]) -> Message<f64> {
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values: values.iter().copied().collect(),
+        labels: Labels::new(),
+    }
+}
+
+/// gauges at 0 to 9, accumulators at 10 to 19, and maxes at 20 to 29
+fn three_ranges() -> GeneParams {
+    let range = |first_idx, operator| IndexRange {
+        first_idx,
+        slots: 10,
+        operator,
+    };
+    GeneParams {
+        ranges: Some(vec![
+            range(0, IndexOperator::Gauge),
+            range(10, IndexOperator::Accum),
+            range(20, IndexOperator::Max),
+        ]),
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_composite_gene_dispatches_each_range() {
+    let gene = CompositeGene::with_params(&three_ranges());
+    assert!(Gene::<f64>::validate(&gene).is_ok());
+
+    let mut state = State::new();
+    for (n, value) in [(0, 3.0), (1, 7.0), (2, 5.0)] {
+        state = gene
+            .apply_operators(
+                state,
+                reading("/", n, &[(1, value), (11, value), (21, value)]),
+            )
+            .unwrap();
+    }
+    assert_eq!(state.get(&1), Some(&5.0));
+    assert_eq!(state.get(&11), Some(&15.0));
+    assert_eq!(state.get(&21), Some(&7.0));
+
+    assert!(gene
+        .apply_operators(state, reading("/", 3, &[(30, 1.0)]))
+        .is_err());
+
+    let mut overlapping = three_ranges();
+    if let Some(ranges) = overlapping.ranges.as_mut() {
+        ranges[1].first_idx = 5;
+    }
+    assert!(Gene::<f64>::validate(&CompositeGene::with_params(&overlapping)).is_err());
+    assert!(Gene::<f64>::validate(&CompositeGene::default()).is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_composite_ranges_are_persisted() {
+    let db_file_prefix = "/tmp/composite";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/composite");
+    let rejected = director
+        .ask(Message::GeneMapping {
+            path: String::from("/composite/bad"),
+            gene_type: GeneType::Composite,
+            params: GeneParams {
+                alpha: Some(0.5),
+                ..three_ranges()
+            },
+        })
+        .await;
+    assert!(rejected.is_err());
+
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/composite"),
+            gene_type: GeneType::Composite,
+            params: three_ranges(),
+        })
+        .await
+        .unwrap();
+    director
+        .ask(reading(
+            "/composite/one",
+            0,
+            &[(1, 2.0), (11, 2.0), (21, 2.0)],
+        ))
+        .await
+        .unwrap();
+    director
+        .ask(reading(
+            "/composite/one",
+            1,
+            &[(1, 1.0), (11, 1.0), (21, 1.0)],
+        ))
+        .await
+        .unwrap();
+
+    // a new director reads the mapping and its ranges back from the store
+    let restarted = setup_director(db_file_prefix, "/composite");
+    match restarted
+        .ask(Message::Content {
+            path: Some(String::from("/composite")),
+            text: String::new(),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+    {
+        Ok(Message::GeneMapping {
+            gene_type, params, ..
+        }) => {
+            assert_eq!(gene_type, GeneType::Composite);
+            assert_eq!(params, three_ranges());
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+    match restarted
+        .ask(Message::Query {
+            path: String::from("/composite/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_eq!(values.get(&1), Some(&1.0));
+            assert_eq!(values.get(&11), Some(&3.0));
+            assert_eq!(values.get(&21), Some(&2.0));
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
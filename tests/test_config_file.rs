@@ -0,0 +1,104 @@
+mod common;
+
+use clap::Parser;
+use common::remove_db;
+use navactor::cli::config::with_config_file;
+use navactor::cli::ifc::{Cli, Commands};
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+
+fn config_file(name: &str, toml: &str) -> PathBuf {
+    let path = PathBuf::from(format!("/tmp/{name}.toml"));
+    fs::write(&path, toml).unwrap_or_else(|e| panic!("{e}"));
+    path
+}
+
+fn args(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn test_command_line_overrides_config() {
+    let config = config_file(
+        "config_precedence",
+        r#"
+buffer = 16
+
+[serve]
+port = 9900
+interface = "0.0.0.0"
+disable_wal = true
+disable_ui = false
+
+[update]
+namespace = "other"
+"#,
+    );
+    let config_arg = config.to_string_lossy().to_string();
+    let with_config = with_config_file(
+        &config,
+        &args(&["nv", "--config", &config_arg, "serve", "--port", "9901"]),
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    let cli = Cli::try_parse_from(with_config).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(cli.buffer, Some(16));
+    match cli.command {
+        Commands::Serve {
+            port,
+            interface,
+            namespace,
+            disable_wal,
+            disable_ui,
+            ..
+        } => {
+            assert_eq!(port, Some(9901));
+            assert_eq!(interface.as_deref(), Some("0.0.0.0"));
+            // the default, the update table is not for serve
+            assert_eq!(namespace, "actors");
+            assert_eq!(disable_wal, Some(true));
+            assert_eq!(disable_ui, Some(false));
+        }
+        c => panic!("not serve: {c:?}"),
+    }
+}
+
+#[test]
+fn test_unknown_config_key_is_rejected() {
+    let config = config_file("config_unknown", "[serve]\nprot = 9900\n");
+    assert!(with_config_file(&config, &args(&["nv", "serve"])).is_err());
+}
+
+#[tokio::test]
+async fn test_config_file_alone_starts_a_server() {
+    let db_file_prefix = "/tmp/config_serve";
+    remove_db(db_file_prefix);
+    let config = config_file(
+        "config_serve",
+        &format!(
+            "[serve]\nport = 8873\nnamespace = \"configured\"\ndb_path = \"{db_file_prefix}.db\"\ndisable_ui = true\n"
+        ),
+    );
+    let mut server = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .arg("--config")
+        .arg(&config)
+        .arg("serve")
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    let mut healthy = false;
+    for _ in 0..100 {
+        if let Ok(resp) = reqwest::get("http://127.0.0.1:8873/health").await {
+            healthy = resp.status().is_success();
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    server.kill().unwrap_or_else(|e| panic!("{e}"));
+    server.wait().unwrap_or_else(|e| panic!("{e}"));
+    assert!(healthy);
+}
@@ -0,0 +1,56 @@
+use navactor::actors::genes::gene::GeneType;
+use navactor::cli::runner::configure;
+use navactor::cli::runner::resolve_configuration;
+use navactor::cli::runner::CliError;
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_dry_run_configure() {
+    let runtime = Runtime::new().unwrap();
+    let valid = r#"{"guage_slots": 10, "accumulator_first_idx": 10, "accumulator_slots": 10}"#;
+    let overlapping = r#"{"guage_slots": 150}"#;
+
+    assert_eq!(
+        resolve_configuration("/dryruncfg/one", GeneType::GaugeAndAccum, Some(valid)),
+        Ok(String::from(
+            "/dryruncfg/one -> GaugeAndAccum {\"guage_slots\":10,\"accumulator_first_idx\":10,\"accumulator_slots\":10}"
+        ))
+    );
+
+    match resolve_configuration("/dryruncfg/one", GeneType::GaugeAndAccum, Some(overlapping)) {
+        Err(e) => assert!(e.contains("overlaps"), "{e}"),
+        r => panic!("overlapping ranges should not validate: {r:?}"),
+    }
+    assert!(resolve_configuration("/dryruncfg/one", GeneType::Gauge, Some(valid)).is_err());
+    assert!(
+        resolve_configuration("/dryruncfg/one", GeneType::Gauge, Some("{\"bogus\": 1}")).is_err()
+    );
+
+    assert!(configure(
+        String::from("/dryruncfg/one"),
+        None,
+        GeneType::GaugeAndAccum,
+        Some(String::from(valid)),
+        true,
+        8,
+        &runtime,
+    )
+    .is_ok());
+    assert!(matches!(
+        configure(
+            String::from("/dryruncfg/two"),
+            None,
+            GeneType::GaugeAndAccum,
+            Some(String::from(overlapping)),
+            true,
+            8,
+            &runtime,
+        ),
+        Err(CliError::Usage(_))
+    ));
+
+    // neither dry run opened the namespace's store
+    assert!(!Path::new("dryruncfg.db").exists());
+}
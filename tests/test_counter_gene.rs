@@ -0,0 +1,74 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::monotonic_counter_gene::MonotonicCounterGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use time::OffsetDateTime;
+
+fn reading(path: &str, n: i64, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_counter_gene_survives_reset() {
+    let g1 = MonotonicCounterGene::default();
+
+    // 100, then +50, then a reset adds the whole 10, then +30
+    let mut state = State::new();
+    for (n, v) in [100.0, 150.0, 10.0, 40.0].into_iter().enumerate() {
+        state = g1
+            .apply_operators(state, reading("/", i64::try_from(n).unwrap(), v))
+            .unwrap();
+    }
+    assert_ulps_eq!(state[&1], 190.0, max_ulps = 4);
+    assert_ulps_eq!(state[&101], 40.0, max_ulps = 4);
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_counter_survives_restart() {
+    let db_file_prefix = "/tmp/counters";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/counters");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/counters"),
+            gene_type: GeneType::Counter,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap();
+    director
+        .ask(reading("/counters/one", 0, 100.0))
+        .await
+        .unwrap();
+    director
+        .ask(reading("/counters/one", 1, 150.0))
+        .await
+        .unwrap();
+
+    // the raw reading is replayed from the journal so the drop is seen as a reset
+    let restarted = setup_director(db_file_prefix, "/counters");
+    match restarted.ask(reading("/counters/one", 2, 10.0)).await {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values[&1], 160.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
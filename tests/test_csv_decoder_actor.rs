@@ -0,0 +1,65 @@
+use approx::assert_ulps_eq;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::csv_decoder;
+
+async fn state_of(director: &Handle, path: &str) -> navactor::actors::actor::State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[test]
+fn test_parse_row() {
+    let reading = csv_decoder::parse_row("/legacy/one, 2023-01-11T23:17:57Z, 2, 1.5")
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(reading.path, "/legacy/one");
+    assert_eq!(reading.idx, 2);
+    assert_ulps_eq!(reading.value, 1.5, max_ulps = 4);
+
+    assert!(csv_decoder::parse_row("/legacy/one,2023-01-11T23:17:57Z,two,1.5").is_err());
+    assert!(csv_decoder::parse_row("/legacy/one,2023-01-11T23:17:57Z,2,NaN-ish").is_err());
+    assert!(csv_decoder::parse_row("/legacy/one,2023-01-11T23:17:57Z,2").is_err());
+}
+
+#[tokio::test]
+async fn test_csv_rows_are_grouped() {
+    let director = director::new("/legacy", 8, None, None);
+    let decoder = csv_decoder::new(8, director);
+    let rows = [
+        "path,datetime,idx,value",
+        "/legacy/one,2023-01-11T23:17:57Z,1,1.0",
+        "/legacy/one,2023-01-11T23:17:57Z,2,2.0",
+        "/legacy/one,2023-01-11T23:17:57Z,3,oops",
+        "/legacy/two,2023-01-11T23:17:57Z,1,5.0",
+        "/legacy/one,2023-01-11T23:17:58Z,1,7.0",
+    ];
+    for text in rows {
+        decoder
+            .tell(Message::Content {
+                text: String::from(text),
+                hint: MtHint::Update,
+                path: None,
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    // the bad row is reported when the stream completes
+    assert!(decoder.ask(Message::EndOfStream {}).await.is_err());
+
+    let one = state_of(&decoder, "/legacy/one").await;
+    assert_eq!(one.len(), 2);
+    assert_ulps_eq!(one[&1], 7.0, max_ulps = 4);
+    assert_ulps_eq!(one[&2], 2.0, max_ulps = 4);
+    let two = state_of(&decoder, "/legacy/two").await;
+    assert_ulps_eq!(two[&1], 5.0, max_ulps = 4);
+}
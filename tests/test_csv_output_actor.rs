@@ -0,0 +1,77 @@
+use navactor::actors::actor::State;
+use navactor::io::csv_output_actor;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use time::macros::datetime;
+
+#[test]
+fn test_state_report_rows() {
+    let mut values: State<f64> = State::new();
+    values.insert(2, 1_000_000.25);
+    values.insert(1, 1.5);
+    values.insert(-3, -0.001);
+    let rows = csv_output_actor::state_report_rows(
+        "/actors/one",
+        datetime!(2023-01-11 23:17:57 UTC),
+        &values,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(
+        rows,
+        vec![
+            "/actors/one,2023-01-11T23:17:57.000000000Z,-3,-0.001",
+            "/actors/one,2023-01-11T23:17:57.000000000Z,1,1.5",
+            "/actors/one,2023-01-11T23:17:57.000000000Z,2,1000000.25",
+        ]
+    );
+
+    let rows = csv_output_actor::state_report_rows(
+        "/actors/one, \"two\"",
+        datetime!(2023-01-11 23:17:57 UTC),
+        &values,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(
+        rows[0],
+        "\"/actors/one, \"\"two\"\"\",2023-01-11T23:17:57.000000000Z,-3,-0.001"
+    );
+
+    assert!(csv_output_actor::state_report_rows(
+        "/actors/one",
+        datetime!(2023-01-11 23:17:57 UTC),
+        &State::<f64>::new()
+    )
+    .unwrap_or_else(|e| panic!("{e}"))
+    .is_empty());
+}
+
+#[test]
+fn test_update_prints_csv() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["--memory-only", "update", "--output-format", "csv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(
+                b"{ \"path\": \"/actors/csv\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }\n\
+                  { \"path\": \"/actors/csv\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"2\": 2.2} }\n",
+            )
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "path,datetime,idx,value\n\
+         /actors/csv,2023-01-11T23:17:57.000000000Z,1,1.1\n\
+         /actors/csv,2023-01-11T23:17:58.000000000Z,1,1.1\n\
+         /actors/csv,2023-01-11T23:17:58.000000000Z,2,2.2\n"
+    );
+}
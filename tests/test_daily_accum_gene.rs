@@ -0,0 +1,84 @@
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::macros::datetime;
+use time::OffsetDateTime;
+
+async fn observe(nv: &navactor::actors::actor::Handle, datetime: OffsetDateTime, value: f64) {
+    let mut values = State::new();
+    values.insert(1, value);
+    nv.ask(Message::Observations {
+        path: String::from("/meters/one"),
+        datetime,
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update meter: {e}"));
+}
+
+async fn bucket_total(cli: &TestClient<poem::endpoint::BoxEndpoint<'static>>, day: &str) -> f64 {
+    let resp = cli
+        .get("/api/actors/meters/one")
+        .query("bucket", &day)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    body["values"]["1"].as_f64().unwrap_or_default()
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_daily_totals_are_retained() {
+    let nv = Arc::new(director::new("/meters", 8, None, None));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/meters"),
+        gene_type: GeneType::DailyAccum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap();
+
+    observe(&nv, datetime!(2024-01-14 08:00 UTC), 1.0).await;
+    observe(&nv, datetime!(2024-01-14 20:00 UTC), 2.0).await;
+    observe(&nv, datetime!(2024-01-15 09:00 UTC), 10.0).await;
+    observe(&nv, datetime!(2024-01-16 00:30 UTC), 100.0).await;
+    observe(&nv, datetime!(2024-01-16 23:30 UTC), 200.0).await;
+    // a late report for an earlier day goes to that day's bucket
+    observe(&nv, datetime!(2024-01-15 23:59 UTC), 20.0).await;
+
+    let config = HttpServerConfig::new(None, None, None, String::from("meters"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    assert!((bucket_total(&cli, "2024-01-14").await - 3.0).abs() < f64::EPSILON);
+    assert!((bucket_total(&cli, "2024-01-15").await - 30.0).abs() < f64::EPSILON);
+    assert!((bucket_total(&cli, "2024-01-16").await - 300.0).abs() < f64::EPSILON);
+
+    // without a bucket the state is the current day
+    let resp = cli.get("/api/actors/meters/one").send().await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body["values"]["1"].as_f64(), Some(300.0));
+
+    let resp = cli
+        .get("/api/actors/meters/one")
+        .query("bucket", &"2024-01-17")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+
+    let resp = cli
+        .get("/api/actors/meters/one")
+        .query("bucket", &"yesterday")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use clap::Parser;
+use clap::ValueEnum;
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::cli::ifc::Cli;
+use navactor::cli::ifc::Commands;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+async fn map(director: &Handle, path: &str, gene_type: GeneType) {
+    director
+        .ask(Message::GeneMapping {
+            path: String::from(path),
+            gene_type,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("can not map {path}: {e}"));
+}
+
+/// the state of `path` after the same value of every index is observed twice
+async fn observed_twice(director: &Handle, path: &str) -> State<f64> {
+    let mut values = State::new();
+    values.insert(1, 2.0);
+    values.insert(150, 2.0);
+    let mut state = State::new();
+    for n in 0..2 {
+        state = match director
+            .ask(Message::Observations {
+                path: String::from(path),
+                datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                    .unwrap_or_else(|e| panic!("{e}")),
+                values: values.clone(),
+                labels: Labels::new(),
+            })
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => values,
+            r => panic!("bad response to observation: {r:?}"),
+        };
+    }
+    state
+}
+
+async fn observe(director: &Handle, path: &str, n: i64) -> State<f64> {
+    match director
+        .ask(Message::Observations {
+            path: String::from(path),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values: State::from([(1, 2.5)]),
+            labels: Labels::new(),
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_default_is_a_gauge_of_every_index() {
+    let director = director::new("/defaulted", 8, None, None);
+    map(&director, "/defaulted/default", GeneType::Default).await;
+    map(&director, "/defaulted/both", GeneType::GaugeAndAccum).await;
+
+    // the index a gauge-and-accumulator accumulates is a gauge of the default
+    assert_eq!(
+        observed_twice(&director, "/defaulted/default/one").await,
+        State::from([(1, 2.0), (150, 2.0)])
+    );
+    assert_eq!(
+        observed_twice(&director, "/defaulted/both/one").await,
+        State::from([(1, 2.0), (150, 4.0)])
+    );
+    // the same as a path without a mapping
+    assert_eq!(
+        observed_twice(&director, "/defaulted/unmapped/one").await,
+        State::from([(1, 2.0), (150, 2.0)])
+    );
+
+    // and it has no index ranges to configure
+    let params = GeneParams {
+        guage_slots: Some(10),
+        accumulator_first_idx: Some(10),
+        ..Default::default()
+    };
+    assert!(director::configured_gene::<f64>(GeneType::Default, &params).is_err());
+    assert!(director::configured_gene::<f64>(GeneType::GaugeAndAccum, &params).is_ok());
+}
+
+#[test]
+fn test_default_is_spelled_the_same_everywhere() {
+    assert_eq!(GeneType::Default.to_string(), "Default");
+    assert_eq!(
+        serde_json::to_string(&GeneType::Default).unwrap_or_else(|e| panic!("{e}")),
+        "\"Default\""
+    );
+    assert_eq!(GeneType::from_str("default", false), Ok(GeneType::Default));
+    assert_ne!(
+        GeneType::from_str("gauge-and-accum", false),
+        Ok(GeneType::Default)
+    );
+}
+
+#[tokio::test]
+async fn test_default_mapping_round_trips_through_the_api() {
+    let db_file_prefix = "/tmp/default_gene";
+    remove_db(db_file_prefix);
+    let client = || {
+        let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+        let nv: Arc<Handle> = Arc::new(director::new("/defaultapi", 8, None, Some(store_actor)));
+        let config = HttpServerConfig::new(None, None, None, String::from("defaultapi"));
+        TestClient::new(build_app(nv, &config, None, Some(true)))
+    };
+
+    let cli = client();
+    cli.post("/api/genes/defaultapi/one")
+        .body_json(&json!({"path": "/defaultapi/one", "gene_type": "Default"}))
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    // the persisted mapping is read back as a default mapping
+    let cli = client();
+    let resp = cli.get("/api/genes/defaultapi/one").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({"path": "/defaultapi/one", "gene_type": "Default"}))
+        .await;
+}
+
+#[tokio::test]
+async fn test_unmapped_paths_get_the_default_gene() {
+    let options = DirectorOptions {
+        default_gene: Some(GeneType::Accum),
+        ..Default::default()
+    };
+    let director = director::new_with_options("/defaults", 8, None, None, options);
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/defaults/gauges"),
+            gene_type: GeneType::Gauge,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    // an unmapped path accumulates
+    observe(&director, "/defaults/meters/one", 0).await;
+    assert_eq!(
+        observe(&director, "/defaults/meters/one", 1).await,
+        State::from([(1, 5.0)])
+    );
+
+    // and the mapping of an ancestor overrides the default
+    observe(&director, "/defaults/gauges/one", 0).await;
+    assert_eq!(
+        observe(&director, "/defaults/gauges/one", 1).await,
+        State::from([(1, 2.5)])
+    );
+
+    // without the option the default is a gauge
+    let director = director::new("/defaults", 8, None, None);
+    observe(&director, "/defaults/meters/one", 0).await;
+    assert_eq!(
+        observe(&director, "/defaults/meters/one", 1).await,
+        State::from([(1, 2.5)])
+    );
+}
+
+#[test]
+fn test_default_gene_flag() {
+    let cli = Cli::try_parse_from(["nv", "serve", "--default-gene", "accum"])
+        .unwrap_or_else(|e| panic!("{e}"));
+    match cli.command {
+        Commands::Serve { default_gene, .. } => assert_eq!(default_gene, Some(GeneType::Accum)),
+        c => panic!("bad command: {c:?}"),
+    }
+    let cli = Cli::try_parse_from(["nv", "update", "--default-gene", "gauge-and-accum"])
+        .unwrap_or_else(|e| panic!("{e}"));
+    match cli.command {
+        Commands::Update { default_gene, .. } => {
+            assert_eq!(default_gene, Some(GeneType::GaugeAndAccum));
+        }
+        c => panic!("bad command: {c:?}"),
+    }
+
+    // a default gene must work without params
+    for gene in ["aggregate", "alert", "composite", "nonsense"] {
+        assert!(
+            Cli::try_parse_from(["nv", "serve", "--default-gene", gene]).is_err(),
+            "{gene} was accepted"
+        );
+    }
+}
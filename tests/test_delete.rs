@@ -0,0 +1,109 @@
+mod common;
+
+use common::{remove_db, setup_director};
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::OffsetDateTime;
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+async fn mapping_of(director: &Handle, path: &str) -> Message<f64> {
+    director
+        .ask(Message::Content {
+            path: Some(String::from(path)),
+            text: String::new(),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_delete_actor() {
+    let db_file_prefix = "/tmp/retired";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/retired");
+    for path in ["/retired/one", "/retired/two"] {
+        director
+            .ask(Message::GeneMapping {
+                path: String::from(path),
+                gene_type: GeneType::Accum,
+                params: GeneParams::default(),
+            })
+            .await
+            .unwrap();
+        let mut values = State::new();
+        values.insert(1, 2.0);
+        director
+            .ask(Message::Observations {
+                path: String::from(path),
+                datetime: OffsetDateTime::now_utc(),
+                values,
+                labels: Labels::new(),
+            })
+            .await
+            .unwrap();
+    }
+
+    director
+        .ask(Message::Delete {
+            path: String::from("/retired/one"),
+            mapping: false,
+        })
+        .await
+        .unwrap();
+    director
+        .ask(Message::Delete {
+            path: String::from("/retired/two"),
+            mapping: true,
+        })
+        .await
+        .unwrap();
+    // nothing to delete is not an error
+    assert!(matches!(
+        director
+            .ask(Message::Delete {
+                path: String::from("/retired/never"),
+                mapping: true,
+            })
+            .await
+            .unwrap(),
+        Message::NotFound { .. }
+    ));
+
+    assert!(state_of(&director, "/retired/one").await.is_empty());
+
+    // the journal is gone for a new director too, and only the mapping asked for
+    let restarted = setup_director(db_file_prefix, "/retired");
+    assert!(state_of(&restarted, "/retired/one").await.is_empty());
+    assert!(state_of(&restarted, "/retired/two").await.is_empty());
+    assert!(matches!(
+        mapping_of(&restarted, "/retired/one").await,
+        Message::GeneMapping {
+            gene_type: GeneType::Accum,
+            ..
+        }
+    ));
+    assert!(matches!(
+        mapping_of(&restarted, "/retired/two").await,
+        Message::NotFound { .. }
+    ));
+}
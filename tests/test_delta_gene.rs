@@ -0,0 +1,116 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::delta_gene::DeltaGene;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use time::OffsetDateTime;
+
+const READINGS: [f64; 5] = [1000.0, 1012.5, 1012.5, 1040.0, 1041.25];
+
+fn reading(path: &str, n: usize, idx: i32, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(idx, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(
+            1_673_479_077 + i64::try_from(n).unwrap_or_default(),
+        )
+        .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+fn meter_params() -> GeneParams {
+    GeneParams {
+        source_idx: Some(1),
+        delta_idx: Some(2),
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_delta_is_the_difference_of_the_readings() {
+    let gene = DeltaGene::with_params(&meter_params());
+
+    let mut state = State::new();
+    state = gene
+        .apply_operators(state, reading("/", 0, 1, READINGS[0]))
+        .unwrap();
+    // no delta before a second reading
+    assert_eq!(state, State::from([(1, READINGS[0])]));
+    for (n, pair) in READINGS.windows(2).enumerate() {
+        state = gene
+            .apply_operators(state, reading("/", n + 1, 1, pair[1]))
+            .unwrap();
+        assert_ulps_eq!(state[&1], pair[1], max_ulps = 4);
+        assert_ulps_eq!(state[&2], pair[1] - pair[0], max_ulps = 4);
+    }
+
+    // the other indices are gauges and the delta is not reported
+    state = gene
+        .apply_operators(state, reading("/", 9, 3, 7.0))
+        .unwrap();
+    assert_ulps_eq!(state[&3], 7.0, max_ulps = 4);
+    assert!(gene
+        .apply_operators(state, reading("/", 10, 2, 1.0))
+        .is_err());
+}
+
+#[test]
+fn test_delta_idx_is_not_the_source() {
+    let params = GeneParams {
+        source_idx: Some(1),
+        delta_idx: Some(1),
+        ..Default::default()
+    };
+    assert!(director::configured_gene::<f64>(GeneType::Delta, &params).is_err());
+    assert!(director::configured_gene::<i64>(GeneType::Delta, &meter_params()).is_ok());
+    // and the indices are params of the delta gene only
+    assert!(director::configured_gene::<f64>(GeneType::Gauge, &meter_params()).is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_delta_survives_restart() {
+    let db_file_prefix = "/tmp/meters";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/meters");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/meters"),
+            gene_type: GeneType::Delta,
+            params: meter_params(),
+        })
+        .await
+        .unwrap();
+    for (n, value) in READINGS[..3].iter().enumerate() {
+        director
+            .ask(reading("/meters/one", n, 1, *value))
+            .await
+            .unwrap();
+    }
+
+    // the persisted mapping and the replayed previous reading pick up where
+    // the live actor left off
+    let restarted = setup_director(db_file_prefix, "/meters");
+    match restarted
+        .ask(reading("/meters/one", 3, 1, READINGS[3]))
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values[&1], READINGS[3], max_ulps = 4);
+            assert_ulps_eq!(values[&2], READINGS[3] - READINGS[2], max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+async fn observe(nv: &Handle, path: &str, secs: i64, value: f64) {
+    let mut values = State::new();
+    values.insert(1, value);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+/// a tree of accumulators - the state of each is the sum of its
+/// observations, not its newest one
+async fn building(db_file_prefix: &str) -> Handle {
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = director::new("/tree", 8, None, Some(store_actor));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/tree"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    for (path, secs, value) in [
+        ("/tree/building/1/floor/2", 0, 2.0),
+        ("/tree/building/1/floor/1", 0, 1.0),
+        ("/tree/building/1/floor/1", 1, 1.0),
+        ("/tree/building/10/floor/1", 0, 10.0),
+        ("/tree/building/2/floor/1", 0, 20.0),
+    ] {
+        observe(&nv, path, secs, value).await;
+    }
+    nv
+}
+
+#[tokio::test]
+async fn test_descendants_state() {
+    let db_file_prefix = "/tmp/descendants";
+    building(db_file_prefix).await;
+
+    // a fresh director resurrects every descendant from the journal
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = director::new("/tree", 8, None, Some(store_actor));
+    match nv
+        .ask(Message::Query {
+            path: String::from("/tree/building/1"),
+            hint: MtHint::Descendants {
+                limit: usize::MAX,
+                offset: 0,
+            },
+        })
+        .await
+    {
+        Ok(Message::StateReports { path, reports }) => {
+            assert_eq!(path, "/tree/building/1/");
+            let states: Vec<(String, Option<f64>)> = reports
+                .into_iter()
+                .map(|report| match report {
+                    Message::StateReport { path, values, .. } => (path, values.get(&1).copied()),
+                    m => panic!("not a state report: {m}"),
+                })
+                .collect();
+            assert_eq!(
+                states,
+                vec![
+                    (String::from("/tree/building/1/floor/1"), Some(2.0)),
+                    (String::from("/tree/building/1/floor/2"), Some(2.0)),
+                ]
+            );
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_descendants_api() {
+    let nv = Arc::new(building("/tmp/descendants_api").await);
+    let config = HttpServerConfig::new(None, None, None, String::from("tree"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli
+        .get("/api/actors/tree/building/?state=true&limit=2")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    let reports = json.value().array();
+    reports.assert_len(2);
+    reports
+        .get(0)
+        .object()
+        .get("path")
+        .assert_string("/tree/building/1/floor/1");
+    reports
+        .get(0)
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(2.0);
+    reports
+        .get(1)
+        .object()
+        .get("path")
+        .assert_string("/tree/building/1/floor/2");
+
+    // without state only the names of the paths are listed
+    let resp = cli.get("/api/actors/tree/building/").send().await;
+    resp.assert_status_is_ok();
+    let paths: Vec<String> = resp.json().await.value().deserialize();
+    assert_eq!(paths.len(), 4);
+}
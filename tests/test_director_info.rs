@@ -0,0 +1,167 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::DirectorInfo;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use serde_json::json;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const PORT: u16 = 18_876;
+
+async fn observe(director: &Handle, path: &str, indices: &[i32]) {
+    let mut values = State::new();
+    for idx in indices {
+        values.insert(*idx, 1.0);
+    }
+    director
+        .ask(Message::Observations {
+            path: String::from(path),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+async fn info_of(director: &Handle) -> DirectorInfo {
+    match director.ask(Message::DirectorInfo).await {
+        Ok(Message::DirectorInfoReport { info }) => info,
+        r => panic!("bad response to director info: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_director_counts_its_resident_actors() {
+    let director = director::new("/resident", 8, None, None);
+    assert_eq!(info_of(&director).await, DirectorInfo::default());
+
+    observe(&director, "/resident/one", &[1, 2]).await;
+    observe(&director, "/resident/two", &[1, 2, 3]).await;
+    assert_eq!(
+        info_of(&director).await,
+        DirectorInfo {
+            actors: 2,
+            state_entries: 5
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_evicted_actors_are_not_counted() {
+    let db_file_prefix = "/tmp/resident_evicted";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        max_resident_actors: Some(2),
+        ..Default::default()
+    };
+    let director = director::new_with_options("/resevict", 8, None, Some(store_actor), options);
+
+    observe(&director, "/resevict/one", &[1, 2, 3, 4]).await;
+    observe(&director, "/resevict/two", &[1]).await;
+    observe(&director, "/resevict/three", &[1, 2]).await;
+    assert_eq!(
+        info_of(&director).await,
+        DirectorInfo {
+            actors: 2,
+            state_entries: 3
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_debug_director_endpoint() {
+    let director = director::new("/resapi", 8, None, None);
+    observe(&director, "/resapi/one", &[1, 2]).await;
+    let nv: Arc<Handle> = Arc::new(director);
+    let config = HttpServerConfig::new(None, None, None, String::from("resapi"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli.get("/debug/director").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({"actors": 1, "state_entries": 2}))
+        .await;
+}
+
+#[tokio::test]
+async fn test_stats_runtime_reads_a_running_server() {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["serve", "--memory-only", "--disable-ui"])
+        .args(["--namespace", "resident", "--port", &PORT.to_string()])
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{PORT}");
+    let mut ready = false;
+    for _ in 0..100 {
+        if client.get(format!("{base}/health")).send().await.is_ok() {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let posted = client
+        .post(format!("{base}/api/actors/resident/one"))
+        .json(&json!({
+            "path": "/resident/one",
+            "datetime": "2023-01-11T23:17:57Z",
+            "values": {"1": 1.5, "2": 2.5},
+        }))
+        .send()
+        .await
+        .map(|resp| resp.status());
+
+    let stats = |format: &str| {
+        Command::new(env!("CARGO_BIN_EXE_nv"))
+            .args(["stats", "--runtime", "--url", &base, "--format", format])
+            .stderr(Stdio::null())
+            .output()
+            .unwrap_or_else(|e| panic!("can not run nv: {e}"))
+    };
+    let text = stats("text");
+    let ndjson = stats("json");
+    server.kill().unwrap_or_else(|e| panic!("{e}"));
+    server.wait().unwrap_or_else(|e| panic!("{e}"));
+
+    assert!(ready, "server never listened on {PORT}");
+    assert!(
+        posted.as_ref().is_ok_and(|status| status.is_success()),
+        "{posted:?}"
+    );
+    assert!(text.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&text.stdout),
+        "resident actors: 1\nstate entries: 2\n"
+    );
+    assert!(ndjson.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&ndjson.stdout),
+        "{\"actors\":1,\"state_entries\":2}\n"
+    );
+}
+
+#[test]
+fn test_stats_runtime_needs_a_server() {
+    let output = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["stats", "--runtime", "--url", "http://127.0.0.1:1"])
+        .stderr(Stdio::null())
+        .output()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    assert_eq!(output.status.code(), Some(4));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::remove_db;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// run `nv update` with the input and return its exit code and what it
+/// printed to stderr
+fn update(db_path: &str, input: &str) -> (Option<i32>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["update", "--db-path", db_path])
+        .env("RUST_LOG", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+const INPUT: &str = "{ \"path\": \"/tally/one\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }
+{ \"path\": \"/tally/one\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"1\": 1.1} }
+";
+
+#[test]
+fn test_update_tallies_skipped_duplicates() {
+    let db_file_prefix = "/tmp/duplicate_tally";
+    remove_db(db_file_prefix);
+    let db_path = format!("{db_file_prefix}.db");
+
+    let (code, printed) = update(&db_path, INPUT);
+    assert_eq!(code, Some(0));
+    assert!(printed.contains("2 observations ingested, 0 duplicates skipped"));
+
+    // a duplicate is skipped, not a failure
+    let more = "{ \"path\": \"/tally/one\", \"datetime\": \"2023-01-11T23:17:59+0000\", \"values\": {\"1\": 1.1} }\n";
+    let (code, printed) = update(&db_path, &format!("{INPUT}{more}"));
+    assert_eq!(code, Some(0));
+    assert!(printed.contains("1 observations ingested, 2 duplicates skipped"));
+}
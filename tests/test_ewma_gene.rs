@@ -0,0 +1,166 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::State;
+use navactor::actors::genes::ewma_gene::EwmaGene;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use sqlx::SqlitePool;
+use time::OffsetDateTime;
+
+fn reading(path: &str, n: i64, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_ewma_gene_converges() {
+    let g1 = EwmaGene {
+        alpha: 0.5,
+        ..Default::default()
+    };
+
+    // the first sample is taken as is
+    let mut state = g1
+        .apply_operators(State::new(), reading("/", 0, 4.0))
+        .unwrap();
+    assert_ulps_eq!(state.get(&1).unwrap(), &4.0, max_ulps = 4);
+
+    state = g1.apply_operators(state, reading("/", 1, 8.0)).unwrap();
+    assert_ulps_eq!(state.get(&1).unwrap(), &6.0, max_ulps = 4);
+
+    for n in 2..40 {
+        state = g1.apply_operators(state, reading("/", n, 8.0)).unwrap();
+    }
+    assert!((state.get(&1).unwrap() - 8.0).abs() < 1e-9);
+
+    assert!(EwmaGene {
+        alpha: 1.5,
+        ..Default::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_ewma_alpha_is_persisted() {
+    let db_file_prefix = "/tmp/smooth";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/smooth");
+    let rejected = director
+        .ask(Message::GeneMapping {
+            path: String::from("/smooth/bad"),
+            gene_type: GeneType::Ewma,
+            params: GeneParams {
+                alpha: Some(1.5),
+                ..Default::default()
+            },
+        })
+        .await;
+    assert!(rejected.is_err());
+
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/smooth"),
+            gene_type: GeneType::Ewma,
+            params: GeneParams {
+                alpha: Some(0.25),
+                ..Default::default()
+            },
+        })
+        .await
+        .unwrap();
+    director.ask(reading("/smooth/one", 0, 10.0)).await.unwrap();
+    director.ask(reading("/smooth/one", 1, 20.0)).await.unwrap();
+
+    // a new director reads the mapping and its alpha back from the store
+    let restarted = setup_director(db_file_prefix, "/smooth");
+    match restarted
+        .ask(Message::Content {
+            path: Some(String::from("/smooth")),
+            text: String::new(),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+    {
+        Ok(Message::GeneMapping {
+            gene_type, params, ..
+        }) => {
+            assert_eq!(gene_type, GeneType::Ewma);
+            assert_eq!(params.alpha, Some(0.25));
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    match restarted
+        .ask(Message::Query {
+            path: String::from("/smooth/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&1).unwrap(), &12.5, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_params_column_is_migrated() {
+    let db_file_prefix = "/tmp/params_migration";
+    remove_db(db_file_prefix);
+    let db_url = format!("sqlite://{db_file_prefix}.db?mode=rwc");
+
+    // a db created before gene mappings had params
+    let pool = SqlitePool::connect(&db_url).await.unwrap();
+    sqlx::query(
+        "CREATE TABLE gene_mappings (
+              path TEXT NOT NULL,
+              gene_type TEXT NOT NULL,
+              PRIMARY KEY (path)
+        )",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO gene_mappings VALUES ('/params_migration', '\"Accum\"')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let director = setup_director(db_file_prefix, "/smooth");
+    match director
+        .ask(Message::Content {
+            path: Some(String::from("/params_migration")),
+            text: String::new(),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+    {
+        Ok(Message::GeneMapping {
+            gene_type, params, ..
+        }) => {
+            assert_eq!(gene_type, GeneType::Accum);
+            assert!(params.is_empty());
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
@@ -0,0 +1,297 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// run `nv` with the args and stdin and return its exit code
+fn nv(args: &[&str], input: &str) -> Option<i32> {
+    nv_in(".", args, input)
+}
+
+/// `nv` run in the working directory `dir`
+fn nv_in(dir: &str, args: &[&str], input: &str) -> Option<i32> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    child
+        .wait()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"))
+        .code()
+}
+
+const GOOD: &str =
+    "{ \"path\": \"/actors/exit\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }\n";
+
+#[test]
+fn test_update_success_exits_zero() {
+    assert_eq!(nv(&["--memory-only", "update", "--silent"], GOOD), Some(0));
+}
+
+#[test]
+fn test_update_with_rejected_input_exits_non_zero() {
+    let input = format!("{GOOD}not json\n");
+    assert_eq!(
+        nv(&["--memory-only", "update", "--silent"], &input),
+        Some(3)
+    );
+
+    // index 500 is outside every range of the GaugeAndAccum gene
+    let input = "{ \"path\": \"/actors/exit\", \"gene_type\": \"GaugeAndAccum\"}\n{ \"path\": \"/actors/exit\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"500\": 1.1} }\n";
+    assert_eq!(nv(&["--memory-only", "update", "--silent"], input), Some(3));
+}
+
+#[test]
+fn test_update_with_unusable_store_exits_non_zero() {
+    assert_eq!(
+        nv(
+            &["update", "--silent", "--db-path", "/no/such/dir/actors.db"],
+            GOOD
+        ),
+        Some(5)
+    );
+}
+
+#[test]
+fn test_invalid_configuration_exits_non_zero() {
+    assert_eq!(
+        nv(
+            &[
+                "configure",
+                "/exitcfg/one",
+                "gauge-and-accum",
+                "--dry-run",
+                "--params",
+                "{\"guage_slots\": 150}",
+            ],
+            "",
+        ),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_update_csv() {
+    let input = "/actors/csv,2023-01-11T23:17:57Z,1,1.1\n/actors/csv,2023-01-11T23:17:57Z,2,2.2\n";
+    assert_eq!(
+        nv(
+            &["--memory-only", "update", "--silent", "--format", "csv"],
+            input
+        ),
+        Some(0)
+    );
+
+    let input = format!("{input}/actors/csv,2023-01-11T23:17:58Z,1,x\n");
+    assert_eq!(
+        nv(
+            &["--memory-only", "update", "--silent", "--format", "csv"],
+            &input
+        ),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_update_line_protocol() {
+    let args = [
+        "--memory-only",
+        "update",
+        "--silent",
+        "--format",
+        "lineprotocol",
+        "--field-index",
+        "usage_idle=1",
+    ];
+    assert_eq!(
+        nv(&args, "cpu,host=a usage_idle=97.5 1673479077000000000\n"),
+        Some(0)
+    );
+    assert_eq!(nv(&args, "cpu,host=a usage_user=2.5\n"), Some(3));
+
+    let args = [
+        "--memory-only",
+        "update",
+        "--format",
+        "lineprotocol",
+        "--field-index",
+        "x",
+    ];
+    assert_eq!(nv(&args, ""), Some(2));
+}
+
+#[test]
+fn test_update_from_input_file() {
+    let path = "/tmp/exit_codes_input.jsonl";
+    std::fs::write(path, GOOD).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(
+        nv(
+            &["--memory-only", "update", "--silent", "--input", path],
+            ""
+        ),
+        Some(0)
+    );
+    assert_ne!(
+        nv(
+            &[
+                "--memory-only",
+                "update",
+                "--silent",
+                "--input",
+                "/no/such/file"
+            ],
+            ""
+        ),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_nested_namespace_db_file() {
+    let dir = "/tmp/nv_nested_ns";
+    std::fs::remove_dir_all(dir).ok();
+    std::fs::create_dir_all(dir).unwrap_or_else(|e| panic!("{e}"));
+
+    // the db of a nested namespace is a file of the working directory
+    assert_eq!(
+        nv_in(dir, &["update", "--silent", "-n", "/acme/sensors"], GOOD),
+        Some(0)
+    );
+    assert!(std::path::Path::new(&format!("{dir}/acme_sensors.db")).exists());
+
+    // and the db path sets the file independently of the namespace
+    let db_path = format!("{dir}/journal/sensors.db");
+    std::fs::create_dir_all(format!("{dir}/journal")).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(
+        nv_in(
+            dir,
+            &[
+                "update",
+                "--silent",
+                "-n",
+                "/acme/sensors",
+                "--db-path",
+                &db_path
+            ],
+            GOOD
+        ),
+        Some(0)
+    );
+    assert!(std::path::Path::new(&db_path).exists());
+
+    // the commands that take a path read and write the same db path
+    for args in [
+        vec!["configure", "/actors/exit", "accum"],
+        vec!["explain", "/actors/exit"],
+        vec!["inspect", "/actors/exit"],
+        vec!["delete", "/actors/exit", "--mapping"],
+    ] {
+        let args = [args, vec!["--db-path", &db_path]].concat();
+        assert_eq!(nv_in(dir, &args, ""), Some(0), "{args:?}");
+    }
+    assert!(!std::path::Path::new(&format!("{dir}/actors.db")).exists());
+}
+
+#[test]
+fn test_prune() {
+    let db_path = "/tmp/nv_prune_exit.db";
+    std::fs::remove_file(db_path).ok();
+    assert_eq!(
+        nv(&["update", "--silent", "--db-path", db_path], GOOD),
+        Some(0)
+    );
+    assert_eq!(
+        nv(
+            &[
+                "prune",
+                "actors",
+                "--db-path",
+                db_path,
+                "--older-than",
+                "30d"
+            ],
+            ""
+        ),
+        Some(0)
+    );
+    assert_eq!(
+        nv(
+            &[
+                "prune",
+                "actors",
+                "--db-path",
+                db_path,
+                "--older-than",
+                "soon"
+            ],
+            ""
+        ),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let (from, to, out, again) = (
+        "/tmp/nv_export_from.db",
+        "/tmp/nv_export_to.db",
+        "/tmp/nv_export.ndjson",
+        "/tmp/nv_export_again.ndjson",
+    );
+    for file in [from, to, out, again] {
+        std::fs::remove_file(file).ok();
+    }
+    let input = "{ \"path\": \"/actors\", \"gene_type\": \"Accum\"}\n{ \"path\": \"/actors/exp\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.5} }\n{ \"path\": \"/actors/exp\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"1\": 2.5} }\n{ \"path\": \"/other/exp\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"1\": 2.5} }\n";
+    assert_eq!(
+        nv(&["update", "--silent", "--db-path", from], input),
+        Some(0)
+    );
+
+    assert_eq!(
+        nv(&["export", "actors", "--db-path", from, "--out", out], ""),
+        Some(0)
+    );
+    let exported = std::fs::read_to_string(out).unwrap_or_else(|e| panic!("{e}"));
+    // the mapping first and only the paths of the namespace
+    assert_eq!(exported.lines().count(), 3, "{exported}");
+    assert!(exported.starts_with("{\"path\":\"/actors\",\"gene_type\":\"Accum\"}"));
+
+    assert_eq!(
+        nv(&["import", "actors", "--db-path", to, "--in", out], ""),
+        Some(0)
+    );
+    // importing again journals nothing new
+    assert_eq!(
+        nv(&["import", "actors", "--db-path", to, "--in", out], ""),
+        Some(0)
+    );
+    assert_eq!(
+        nv(&["export", "actors", "--db-path", to, "--out", again], ""),
+        Some(0)
+    );
+    assert_eq!(
+        std::fs::read_to_string(again).unwrap_or_else(|e| panic!("{e}")),
+        exported
+    );
+
+    assert_eq!(
+        nv(
+            &[
+                "import",
+                "actors",
+                "--db-path",
+                to,
+                "--in",
+                "/no/such/export"
+            ],
+            ""
+        ),
+        Some(3)
+    );
+}
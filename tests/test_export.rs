@@ -0,0 +1,70 @@
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::export::export_line;
+use navactor::io::export::parse_export_line;
+use time::OffsetDateTime;
+
+#[test]
+fn test_observation_round_trip() {
+    let datetime =
+        OffsetDateTime::from_unix_timestamp(1_673_479_077).unwrap_or_else(|e| panic!("{e}"));
+    let mut values = State::new();
+    values.insert(1, 1.5);
+    values.insert(2, -0.1);
+    let mut labels = Labels::new();
+    labels.insert(String::from("site"), String::from("west"));
+    let observations = Message::Observations {
+        path: String::from("/actors/one"),
+        datetime,
+        values: values.clone(),
+        labels: labels.clone(),
+    };
+
+    let line = export_line(&observations).unwrap_or_else(|e| panic!("{e}"));
+    assert!(line.contains("\"datetime\":\"1673479077\""), "{line}");
+    match parse_export_line(&line) {
+        Ok(Message::Observations {
+            path,
+            datetime: imported,
+            values: imported_values,
+            labels: imported_labels,
+        }) => {
+            assert_eq!(path, "/actors/one");
+            assert_eq!(imported, datetime);
+            assert_eq!(imported_values, values);
+            assert_eq!(imported_labels, labels);
+        }
+        r => panic!("bad import of {line}: {r:?}"),
+    }
+}
+
+#[test]
+fn test_gene_mapping_round_trip() {
+    let mapping = Message::GeneMapping {
+        path: String::from("/actors"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    };
+    let line = export_line(&mapping).unwrap_or_else(|e| panic!("{e}"));
+    assert!(matches!(
+        parse_export_line(&line),
+        Ok(Message::GeneMapping {
+            gene_type: GeneType::Accum,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_bad_export_lines() {
+    // an iso8601 datetime is an update line, not an export line
+    assert!(parse_export_line(
+        "{\"datetime\":\"2023-01-11T23:17:57+0000\",\"values\":{\"1\":1.5},\"path\":\"/actors/one\"}"
+    )
+    .is_err());
+    assert!(parse_export_line("{\"datetime\":\"1673479077\",\"values\":{\"1\":1.5}}").is_err());
+    assert!(export_line(&Message::EndOfStream {}).is_err());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::file_actor;
+use navactor::io::json_decoder;
+use std::fs;
+
+#[tokio::test]
+async fn test_file_is_read_to_the_end() {
+    let db_file_prefix = "/tmp/file_actor";
+    remove_db(db_file_prefix);
+    let path = "/tmp/file_actor_input.jsonl";
+    fs::write(
+        path,
+        "{ \"path\": \"/actors/file\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.5} }\n\
+         { \"path\": \"/actors/file\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"2\": 2.5} }\n",
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+
+    let decoder = json_decoder::new(8, setup_director(db_file_prefix, "/actors"));
+    let input = file_actor::new(8, decoder, String::from(path), false);
+
+    match input.ask(Message::ReadAllCmd {}).await {
+        Ok(Message::EndOfStream {}) => (),
+        r => panic!("bad response from file actor: {r:?}"),
+    }
+
+    // every line was journaled before the end of the stream was answered
+    match setup_director(db_file_prefix, "/actors")
+        .ask(Message::Query {
+            path: String::from("/actors/file"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values[&1], 1.5, max_ulps = 4);
+            assert_ulps_eq!(values[&2], 2.5, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_missing_file_is_an_error() {
+    let director = director::new("/actors", 8, None, None);
+    let decoder = json_decoder::new(8, director);
+    let input = file_actor::new(8, decoder, String::from("/no/such/dir/input.jsonl"), false);
+
+    match input.ask(Message::ReadAllCmd {}).await {
+        Err(e) => assert!(e.reason.contains("/no/such/dir/input.jsonl")),
+        r => panic!("missing file was read: {r:?}"),
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use time::OffsetDateTime;
+
+fn observation(path: &str, n: i64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn observations_of(db_file_prefix: &str, path: &str) -> u64 {
+    let store = SqliteStore::open(db_file_prefix, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let stats = store
+        .path_stats(path)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    store.close().await;
+    stats.observations
+}
+
+#[tokio::test]
+async fn test_flush_commits_the_pending_batch() {
+    let db_file_prefix = "/tmp/flush_store";
+    remove_db(db_file_prefix);
+    // a batch that never fills up
+    let store_actor = store_actor_sqlite::new_with_options(
+        64,
+        String::from(db_file_prefix),
+        false,
+        false,
+        StoreOptions {
+            batch_size: 100,
+            ..Default::default()
+        },
+    );
+    for n in 0..10 {
+        store_actor
+            .tell(observation("/flush/one", n))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    assert!(matches!(
+        store_actor.ask(Message::Flush).await,
+        Ok(Message::Flushed)
+    ));
+    assert_eq!(observations_of(db_file_prefix, "/flush/one").await, 10);
+}
+
+#[tokio::test]
+async fn test_end_of_stream_completes_once_journaled() {
+    let db_file_prefix = "/tmp/flush_director";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new_with_options(
+        64,
+        String::from(db_file_prefix),
+        false,
+        false,
+        StoreOptions {
+            batch_size: 100,
+            ..Default::default()
+        },
+    );
+    let director = director::new("/flush", 64, None, Some(store_actor));
+    for n in 0..10 {
+        director
+            .tell(observation("/flush/two", n))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    assert!(matches!(
+        director.ask(Message::EndOfStream {}).await,
+        Ok(Message::EndOfStream {})
+    ));
+    assert_eq!(observations_of(db_file_prefix, "/flush/two").await, 10);
+}
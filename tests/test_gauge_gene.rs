@@ -2,10 +2,11 @@ use approx::assert_ulps_eq;
 use navactor::actors::actor::State;
 use navactor::actors::genes::gauge_gene::GaugeGene;
 use navactor::actors::genes::gene::Gene;
+use navactor::actors::message::Labels;
 use navactor::actors::message::Message;
 use time::OffsetDateTime;
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_gauge_gene() {
     let mut state: State<f64> = State::new();
@@ -26,6 +27,7 @@ fn test_gauge_gene() {
         path: String::from("/"),
         datetime: OffsetDateTime::now_utc(),
         values,
+        labels: Labels::new(),
     };
 
     let r = g1.apply_operators(state, msg);
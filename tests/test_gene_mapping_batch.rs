@@ -0,0 +1,149 @@
+mod common;
+
+use common::setup_director;
+use glob::glob;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::fs;
+use std::sync::Arc;
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_gene_mapping_batch_persists_all() {
+    let namespace = String::from("/gene_batch");
+    let db_file_prefix = format!("/tmp{namespace}");
+    for entry in glob(&format!("{db_file_prefix}.db*")).unwrap() {
+        fs::remove_file(entry.unwrap()).unwrap();
+    }
+    let server_config = HttpServerConfig::new(None, None, None, namespace.clone());
+
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let resp = cli
+        .post("/api/genes/batch")
+        .body_json(&json!([
+            {"path": "/gene_batch/blue", "gene_type": "Accum"},
+            {"path": "/gene_batch/green", "gene_type": "Gauge"},
+            {"path": "/gene_batch/blue", "gene_type": "Gauge"},
+            {"path": "gene_batch/red", "gene_type": "GaugeAndAccum"},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!([
+        {"path": "/gene_batch/blue", "gene_type": "Accum"},
+        {"path": "/gene_batch/green", "gene_type": "Gauge"},
+        {"path": "/gene_batch/blue", "gene_type": "Gauge", "error": "duplicate path in batch"},
+        {"path": "/gene_batch/red", "gene_type": "GaugeAndAccum"},
+    ]))
+    .await;
+    drop(cli);
+
+    // restart and confirm the mappings were read back from the store
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+    for (id, gene_type) in [
+        ("blue", "Accum"),
+        ("green", "Gauge"),
+        ("red", "GaugeAndAccum"),
+    ] {
+        let resp = cli.get(format!("/api/genes/gene_batch/{id}")).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_json(json!({"path": format!("/gene_batch/{id}"), "gene_type": gene_type}))
+            .await;
+    }
+    let resp = cli.get("/api/genes/gene_batch/yellow").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+
+    // blue is already mapped so the store rejects it but yellow still lands
+    let resp = cli
+        .post("/api/genes/batch")
+        .body_json(&json!([
+            {"path": "/gene_batch/blue", "gene_type": "Gauge"},
+            {"path": "/gene_batch/yellow", "gene_type": "Accum"},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let results = resp.json().await;
+    let results = results.value().array();
+    assert!(results.get(0).object().get_opt("error").is_some());
+    assert!(results.get(1).object().get_opt("error").is_none());
+
+    let resp = cli.get("/api/genes/gene_batch/yellow").send().await;
+    resp.assert_status_is_ok();
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_namespace_gene_mapping_batch_reports_each_failure() {
+    let namespace = String::from("/gene_ns_batch");
+    let db_file_prefix = format!("/tmp{namespace}");
+    for entry in glob(&format!("{db_file_prefix}.db*")).unwrap() {
+        fs::remove_file(entry.unwrap()).unwrap();
+    }
+    let server_config = HttpServerConfig::new(None, None, None, namespace.clone());
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let resp = cli
+        .post("/api/genes/gene_ns_batch/batch")
+        .body_json(&json!([
+            {"path": "/gene_ns_batch", "gene_type": "Gauge"},
+            {"path": "/gene_ns_batch/blue", "gene_type": "Accum"},
+            {"path": "/gene_ns_batch/green", "gene_type": "Guage"},
+            {"path": "/elsewhere/red", "gene_type": "Gauge"},
+            {"path": "/gene_ns_batch/red", "gene_type": "Latest"},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!([
+        {"path": "/gene_ns_batch", "gene_type": "Gauge"},
+        {"path": "/gene_ns_batch/blue", "gene_type": "Accum"},
+        {"path": "/gene_ns_batch/green", "gene_type": "Guage", "error": "unknown gene type Guage"},
+        {"path": "/elsewhere/red", "gene_type": "Gauge", "error": "/elsewhere/red is not under /gene_ns_batch/"},
+        {"path": "/gene_ns_batch/red", "gene_type": "Latest"},
+    ]))
+    .await;
+    drop(cli);
+
+    // the rest persisted and the failed ones did not
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+    for (id, gene_type) in [("blue", "Accum"), ("red", "Latest")] {
+        let resp = cli
+            .get(format!("/api/genes/gene_ns_batch/{id}"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_json(json!({"path": format!("/gene_ns_batch/{id}"), "gene_type": gene_type}))
+            .await;
+    }
+    let resp = cli.get("/api/genes/gene_ns_batch/green").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+fn client(db_file_prefix: &str) -> TestClient<poem::endpoint::BoxEndpoint<'static>> {
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv: Arc<Handle> = Arc::new(director::new("/mapped", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("mapped"));
+    TestClient::new(build_app(nv, &config, None, Some(true)))
+}
+
+#[tokio::test]
+async fn test_list_and_delete_gene_mappings() {
+    let db_file_prefix = "/tmp/gene_mappings_api";
+    remove_db(db_file_prefix);
+    let cli = client(db_file_prefix);
+
+    for mapping in [
+        json!({"path": "/mapped/b", "gene_type": "Accum"}),
+        json!({"path": "/mapped/a", "gene_type": "Ewma", "params": {"alpha": 0.5}}),
+        json!({"path": "/mappedx/c", "gene_type": "Latest"}),
+    ] {
+        let path = mapping["path"].as_str().unwrap_or_default().to_string();
+        cli.post(format!("/api/genes{path}"))
+            .body_json(&mapping)
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    let resp = cli.get("/api/genes/mapped/").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!([
+        {"path": "/mapped/a", "gene_type": "Ewma", "params": {"alpha": 0.5}},
+        {"path": "/mapped/b", "gene_type": "Accum"},
+    ]))
+    .await;
+
+    cli.delete("/api/genes/mapped/b")
+        .send()
+        .await
+        .assert_status_is_ok();
+    cli.delete("/api/genes/mapped/b")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    cli.get("/api/genes/mapped/b")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // the delete is persisted
+    let cli = client(db_file_prefix);
+    let resp = cli.get("/api/genes/mapped/").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!([
+        {"path": "/mapped/a", "gene_type": "Ewma", "params": {"alpha": 0.5}},
+    ]))
+    .await;
+}
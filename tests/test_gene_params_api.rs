@@ -0,0 +1,117 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+fn client(db_file_prefix: &str) -> TestClient<poem::endpoint::BoxEndpoint<'static>> {
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv: Arc<Handle> = Arc::new(director::new("/ranged", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("ranged"));
+    TestClient::new(build_app(nv, &config, None, Some(true)))
+}
+
+#[tokio::test]
+async fn test_gene_params_are_persisted() {
+    let db_file_prefix = "/tmp/gene_params_api";
+    remove_db(db_file_prefix);
+    let cli = client(db_file_prefix);
+
+    let mapping = json!({
+        "path": "/ranged/one",
+        "gene_type": "GaugeAndAccum",
+        "params": {"guage_first_idx": 0, "guage_slots": 2, "accumulator_first_idx": 2, "accumulator_slots": 2},
+    });
+    let resp = cli
+        .post("/api/genes/ranged/one")
+        .body_json(&mapping)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&mapping).await;
+
+    // index 2 accumulates under the custom ranges and would be a gauge by default
+    for (datetime, value) in [("2023-01-11T23:17:57Z", 1.5), ("2023-01-11T23:17:58Z", 2.0)] {
+        cli.post("/api/actors/ranged/one")
+            .body_json(
+                &json!({"path": "/ranged/one", "datetime": datetime, "values": {"2": value}}),
+            )
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+    drop(cli);
+
+    // the params are read back from the store
+    let cli = client(db_file_prefix);
+    let resp = cli.get("/api/genes/ranged/one").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&mapping).await;
+    let resp = cli.get("/api/actors/ranged/one").send().await;
+    resp.assert_status_is_ok();
+    let state = resp.json().await;
+    state
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("2")
+        .assert_f64(3.5);
+
+    // a mapping without params keeps the defaults and reports none
+    let resp = cli
+        .post("/api/genes/ranged/two")
+        .body_json(&json!({"path": "/ranged/two", "gene_type": "Accum"}))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({"path": "/ranged/two", "gene_type": "Accum"}))
+        .await;
+
+    let hourly =
+        json!({"path": "/ranged/three", "gene_type": "Accum", "params": {"time_scope": "hour"}});
+    let resp = cli
+        .post("/api/genes/ranged/three")
+        .body_json(&hourly)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(hourly).await;
+}
+
+#[tokio::test]
+async fn test_gene_params_that_do_not_apply_are_rejected() {
+    let db_file_prefix = "/tmp/gene_params_api_rejected";
+    remove_db(db_file_prefix);
+    let cli = client(db_file_prefix);
+
+    let resp = cli
+        .post("/api/genes/ranged/bad")
+        .body_json(
+            &json!({"path": "/ranged/bad", "gene_type": "Accum", "params": {"guage_slots": 2}}),
+        )
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    let resp = cli
+        .post("/api/genes/batch")
+        .body_json(&json!([
+            {"path": "/ranged/avg", "gene_type": "MovingAvg", "params": {"window": 4}},
+            {"path": "/ranged/worse", "gene_type": "Ewma", "params": {"window": 4}},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let results = resp.json().await;
+    let results = results.value().array();
+    assert!(results.get(0).object().get_opt("error").is_none());
+    assert!(results.get(1).object().get_opt("error").is_some());
+}
@@ -29,7 +29,7 @@ impl MockDirector {
     }
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_lookup_gene_type() {
     let path = "/domain/building/1/floor/3/room/5";
@@ -48,7 +48,7 @@ fn test_lookup_gene_type() {
     assert_eq!(gt.unwrap(), GeneType::Accum);
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_lookup_gene_type_short_path() {
     let path = "/domain/building";
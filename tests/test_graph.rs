@@ -4,12 +4,12 @@ use petgraph::graph::Graph;
 fn test_graph() {
     let mut graph = Graph::<(), ()>::new(); // directed and unlabeled
 
-    graph.extend_with_edges(&[(0, 1)]);
+    graph.extend_with_edges([(0, 1)]);
 
     assert_eq!(graph.node_count(), 2);
     assert_eq!(graph.edge_count(), 1);
 }
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_graph_labels() {
     let mut graph = Graph::new();
@@ -25,7 +25,7 @@ fn test_graph_labels() {
     assert_eq!(graph.edge_weight(cost_2).unwrap(), &1099);
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_unstable_indexing() {
     let mut graph = Graph::<(), ()>::new();
@@ -0,0 +1,92 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+async fn observe(nv: &Handle, path: &str) {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+async fn adjacency(nv: &Handle, path: &str) -> (Vec<String>, Vec<String>) {
+    match nv
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::Adjacency,
+        })
+        .await
+    {
+        Ok(Message::Adjacency {
+            parents, children, ..
+        }) => (parents, children),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+fn paths(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|path| String::from(*path)).collect()
+}
+
+#[tokio::test]
+async fn test_edges_survive_restart() {
+    let db_file_prefix = "/tmp/graph_edges";
+    remove_db(db_file_prefix);
+    let director_of = || {
+        let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+        director::new("/graph", 8, None, Some(store_actor))
+    };
+
+    let nv = director_of();
+    observe(&nv, "/graph/a/b/c").await;
+    observe(&nv, "/graph/a/d").await;
+    assert_eq!(
+        adjacency(&nv, "/graph/a").await,
+        (paths(&["/graph"]), paths(&["/graph/a/b", "/graph/a/d"]))
+    );
+
+    let restarted = director_of();
+    assert_eq!(
+        adjacency(&restarted, "/graph/a/b").await,
+        (paths(&["/graph/a"]), paths(&["/graph/a/b/c"]))
+    );
+    assert_eq!(
+        adjacency(&restarted, "/graph/").await,
+        (paths(&[]), paths(&["/graph/a"]))
+    );
+    assert_eq!(
+        adjacency(&restarted, "/graph/a/b/c").await,
+        (paths(&["/graph/a/b"]), paths(&[]))
+    );
+}
+
+#[tokio::test]
+async fn test_queries_do_not_add_edges() {
+    let nv = director::new("/graph", 8, None, None);
+    nv.ask(Message::Query {
+        path: String::from("/graph/typo/x"),
+        hint: MtHint::State,
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(adjacency(&nv, "/graph").await, (paths(&[]), paths(&[])));
+    observe(&nv, "/graph/x").await;
+    assert_eq!(
+        adjacency(&nv, "/graph").await,
+        (paths(&[]), paths(&["/graph/x"]))
+    );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use async_compression::tokio::write::GzipEncoder;
+use common::{remove_db, setup_director};
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::file_actor;
+use navactor::io::json_decoder;
+use std::fs;
+use tokio::io::AsyncWriteExt;
+
+async fn gzip(text: &str) -> Vec<u8> {
+    let mut encoder = GzipEncoder::new(Vec::new());
+    encoder
+        .write_all(text.as_bytes())
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    encoder.shutdown().await.unwrap_or_else(|e| panic!("{e}"));
+    encoder.into_inner()
+}
+
+#[tokio::test]
+async fn test_gzipped_file_is_decompressed() {
+    let db_file_prefix = "/tmp/backfill";
+    remove_db(db_file_prefix);
+    let path = "/tmp/backfill_input.jsonl.gz";
+    // two members, as `cat a.gz b.gz` writes them
+    let mut archive = gzip(
+        "{ \"path\": \"/backfill/one\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.5} }\n",
+    )
+    .await;
+    archive.extend(
+        gzip("{ \"path\": \"/backfill/one\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"2\": 2.5} }\n")
+            .await,
+    );
+    fs::write(path, archive).unwrap_or_else(|e| panic!("{e}"));
+
+    let decoder = json_decoder::new(8, setup_director(db_file_prefix, "/backfill"));
+    let input = file_actor::new(8, decoder, String::from(path), true);
+    match input.ask(Message::ReadAllCmd {}).await {
+        Ok(Message::EndOfStream {}) => (),
+        r => panic!("bad response from file actor: {r:?}"),
+    }
+
+    match setup_director(db_file_prefix, "/backfill")
+        .ask(Message::Query {
+            path: String::from("/backfill/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values[&1], 1.5, max_ulps = 4);
+            assert_ulps_eq!(values[&2], 2.5, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_plain_file_is_not_gzip() {
+    let path = "/tmp/backfill_plain.jsonl";
+    fs::write(
+        path,
+        "{ \"path\": \"/backfill/two\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.5} }\n",
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+
+    let decoder = json_decoder::new(8, director::new("/backfill", 8, None, None));
+    let input = file_actor::new(8, decoder, String::from(path), true);
+    match input.ask(Message::ReadAllCmd {}).await {
+        Err(e) => assert!(e.reason.contains("failed to read stream")),
+        r => panic!("plain file was decompressed: {r:?}"),
+    }
+}
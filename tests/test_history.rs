@@ -0,0 +1,150 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+use tokio::sync::oneshot;
+
+const PATH: &str = "/history/one";
+const START: i64 = 1_673_479_077;
+
+fn at(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(START + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// journal an observation of index `n` taken at `n` that arrived at `arrived`
+async fn journal(store_actor: &Handle, n: i32, arrived: i64) {
+    let mut values = State::new();
+    values.insert(n, f64::from(n));
+    let (send, recv) = oneshot::channel();
+    store_actor
+        .send(Envelope {
+            message: Message::Observations {
+                path: String::from(PATH),
+                datetime: at(i64::from(n)),
+                values,
+                labels: Labels::new(),
+            },
+            respond_to: Some(send),
+            datetime: at(arrived),
+            ..Default::default()
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    match recv.await {
+        Ok(Ok(Message::Persisted)) => {}
+        r => panic!("observation {n} not journaled: {r:?}"),
+    }
+}
+
+async fn history(director: &Handle, from: i64, to: i64) -> Vec<(OffsetDateTime, State<f64>)> {
+    page(director, from, to, usize::MAX, 0).await.0
+}
+
+/// a page of the history and the number of observations of all the pages
+async fn page(
+    director: &Handle,
+    from: i64,
+    to: i64,
+    limit: usize,
+    offset: usize,
+) -> (Vec<(OffsetDateTime, State<f64>)>, u64) {
+    match director
+        .ask(Message::Query {
+            path: String::from(PATH),
+            hint: MtHint::History {
+                from: at(from),
+                to: at(to),
+                limit,
+                offset,
+            },
+        })
+        .await
+    {
+        Ok(Message::History {
+            path,
+            points,
+            total,
+        }) => {
+            assert_eq!(path, PATH);
+            (points, total)
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_history_between_arrival_times() {
+    let db_file_prefix = "/tmp/history";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    // observation 3 was taken first but arrived last
+    for (n, arrived) in [(1, 10), (2, 20), (4, 30), (3, 40)] {
+        journal(&store_actor, n, arrived).await;
+    }
+    let director = director::new("/history", 8, None, Some(store_actor));
+
+    let points = history(&director, 20, 40).await;
+    let taken: Vec<OffsetDateTime> = points.iter().map(|(datetime, _)| *datetime).collect();
+    assert_eq!(taken, vec![at(2), at(4), at(3)]);
+    assert_eq!(points[2].1.get(&3), Some(&3.0));
+
+    assert!(history(&director, 41, 50).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_history_without_journal() {
+    let director = director::new("/history", 8, None, None);
+    let r = director
+        .ask(Message::Query {
+            path: String::from(PATH),
+            hint: MtHint::History {
+                from: at(0),
+                to: at(1),
+                limit: usize::MAX,
+                offset: 0,
+            },
+        })
+        .await;
+    assert!(r.is_err(), "{r:?}");
+}
+
+#[tokio::test]
+async fn test_history_pages() {
+    let db_file_prefix = "/tmp/history_pages";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    for n in 1..=7 {
+        journal(&store_actor, n, i64::from(n) * 10).await;
+    }
+    let director = director::new("/history", 8, None, Some(store_actor));
+
+    let everything = history(&director, 0, 100).await;
+    assert_eq!(everything.len(), 7);
+
+    let mut paged = vec![];
+    let mut offset = 0;
+    loop {
+        let (points, total) = page(&director, 0, 100, 3, offset).await;
+        assert_eq!(total, 7);
+        if points.is_empty() {
+            break;
+        }
+        assert!(points.len() <= 3);
+        offset += points.len();
+        paged.extend(points);
+    }
+    assert_eq!(paged, everything);
+
+    let (points, total) = page(&director, 25, 55, 2, 1).await;
+    assert_eq!(total, 3);
+    let taken: Vec<OffsetDateTime> = points.iter().map(|(datetime, _)| *datetime).collect();
+    assert_eq!(taken, vec![at(4), at(5)]);
+}
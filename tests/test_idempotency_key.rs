@@ -0,0 +1,183 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_repeated_idempotency_key_is_not_applied_again() {
+    let db_file_prefix = "/tmp/idempotency_key";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv: Handle = director::new("/idem", 8, None, Some(store_actor));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/idem"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    let config = HttpServerConfig::new(None, None, None, String::from("idem"));
+    let cli = TestClient::new(build_app(Arc::new(nv), &config, None, Some(true)));
+
+    let observation = |datetime: &str| {
+        json!({
+            "path": "/idem/one",
+            "datetime": datetime,
+            "values": {"1": 1.0},
+        })
+    };
+    let post = |key: &'static str, datetime: &'static str| {
+        cli.post("/api/actors/idem/one")
+            .header("Idempotency-Key", key)
+            .body_json(&observation(datetime))
+            .send()
+    };
+
+    let first = post("retry-1", "2023-01-11T23:17:57Z").await;
+    first.assert_status_is_ok();
+    let first = first
+        .json()
+        .await
+        .value()
+        .deserialize::<serde_json::Value>();
+    assert_eq!(first["values"]["1"], json!(1.0));
+
+    // the journal would reject the retry as a duplicate but it gets the first response
+    let repeat = post("retry-1", "2023-01-11T23:17:57Z").await;
+    repeat.assert_status_is_ok();
+    repeat.assert_json(&first).await;
+
+    let resp = cli.get("/api/actors/idem/one").send().await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(1.0);
+
+    // a new key is a new post
+    let other = post("retry-2", "2023-01-11T23:17:58Z").await;
+    other.assert_status_is_ok();
+    other
+        .json()
+        .await
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(2.0);
+}
+
+#[tokio::test]
+async fn test_concurrent_retries_are_applied_once() {
+    let db_file_prefix = "/tmp/idempotency_key_concurrent";
+    remove_db(db_file_prefix);
+    // without duplicate detection only the key keeps a retry from being applied
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, true);
+    let nv: Handle = director::new("/idemc", 8, None, Some(store_actor));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/idemc"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    let config = HttpServerConfig::new(None, None, None, String::from("idemc"));
+    let cli = TestClient::new(build_app(Arc::new(nv), &config, None, Some(true)));
+
+    let post = || {
+        cli.post("/api/actors/idemc/one")
+            .header("Idempotency-Key", "retry-1")
+            .body_json(&json!({
+                "path": "/idemc/one",
+                "datetime": "2023-01-11T23:17:57Z",
+                "values": {"1": 1.0},
+            }))
+            .send()
+    };
+
+    // the retry arrives while the first post is being applied
+    let (first, retry) = tokio::join!(post(), post());
+    first.assert_status_is_ok();
+    retry.assert_status(StatusCode::CONFLICT);
+    retry
+        .json()
+        .await
+        .value()
+        .object()
+        .get("code")
+        .assert_string("idempotency_key_in_flight");
+
+    // and a later one gets the response of the first
+    let later = post().await;
+    later.assert_status_is_ok();
+    let resp = cli.get("/api/actors/idemc/one").send().await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(1.0);
+}
+
+#[tokio::test]
+async fn test_idempotency_key_is_kept_by_the_normalized_path() {
+    let nv: Handle = director::new("/idemn", 8, None, None);
+    nv.ask(Message::GeneMapping {
+        path: String::from("/idemn"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    let config = HttpServerConfig::new(None, None, None, String::from("idemn"));
+    let cli = TestClient::new(build_app(Arc::new(nv), &config, None, Some(true)));
+
+    let post = |url: &'static str, datetime: &'static str| {
+        cli.post(url)
+            .header("Idempotency-Key", "retry-1")
+            .body_json(&json!({
+                "path": "/idemn/one",
+                "datetime": datetime,
+                "values": {"1": 1.0},
+            }))
+            .send()
+    };
+
+    // the same path spelled two ways is the same post
+    post("/api/actors/idemn//one", "2023-01-11T23:17:57Z")
+        .await
+        .assert_status_is_ok();
+    post("/api/actors/idemn/one", "2023-01-11T23:17:58Z")
+        .await
+        .assert_status_is_ok();
+
+    let resp = cli.get("/api/actors/idemn/one").send().await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(1.0);
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::label_values;
+use navactor::actors::message::IndexLabels;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::json_output_actor;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use time::macros::datetime;
+
+const DB_FILE_PREFIX: &str = "/tmp/index_labels";
+
+fn director_of_db() -> Handle {
+    let store_actor = store_actor_sqlite::new(8, String::from(DB_FILE_PREFIX), false, false);
+    director::new("/labeled", 8, None, Some(store_actor))
+}
+
+async fn index_labels(director: &Handle) -> IndexLabels {
+    match director
+        .ask(Message::Query {
+            path: String::from("/labeled"),
+            hint: MtHint::IndexLabels,
+        })
+        .await
+    {
+        Ok(Message::IndexLabels { labels }) => labels,
+        r => panic!("bad response to index labels query: {r:?}"),
+    }
+}
+
+#[test]
+fn test_label_values() {
+    let values: State<f64> = State::from([(1, 21.5), (2, 3.0)]);
+    let labels = IndexLabels::from([(1, String::from("temperature_c"))]);
+    assert_eq!(
+        label_values(&values, &labels),
+        BTreeMap::from([
+            (String::from("temperature_c"), 21.5),
+            (String::from("2"), 3.0)
+        ])
+    );
+
+    let line = json_output_actor::labeled_state_report_line(
+        "/labeled/one",
+        datetime!(2023-01-11 23:17:57 UTC),
+        &values,
+        &labels,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    let report: serde_json::Value = serde_json::from_str(&line).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(report["values"]["temperature_c"], 21.5);
+    assert_eq!(report["values"]["2"], 3.0);
+}
+
+#[tokio::test]
+async fn test_labels_are_persisted_and_served() {
+    remove_db(DB_FILE_PREFIX);
+    let director = director_of_db();
+    for (idx, name) in [(1, "temperature"), (1, "temperature_c"), (2, "humidity")] {
+        assert!(matches!(
+            director
+                .ask(Message::IndexLabel {
+                    idx,
+                    name: String::from(name),
+                })
+                .await,
+            Ok(Message::IndexLabel { .. })
+        ));
+    }
+    assert!(director
+        .ask(Message::IndexLabel {
+            idx: 3,
+            name: String::new(),
+        })
+        .await
+        .is_err());
+    let mut values = State::new();
+    values.insert(1, 21.5);
+    values.insert(3, 1.0);
+    director
+        .ask(Message::Observations {
+            path: String::from("/labeled/one"),
+            datetime: datetime!(2023-01-11 23:17:57 UTC),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    // a new director loads the latest label of each index
+    let director = director_of_db();
+    let expected = IndexLabels::from([
+        (1, String::from("temperature_c")),
+        (2, String::from("humidity")),
+    ]);
+    assert_eq!(index_labels(&director).await, expected);
+
+    let config = HttpServerConfig::new(None, None, None, String::from("labeled"));
+    let cli = TestClient::new(build_app(Arc::new(director), &config, None, Some(true)));
+    let resp = cli.get("/api/actors/labeled/one").send().await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    let report = json.value().object();
+    report.get("values").object().get("1").assert_f64(21.5);
+    assert!(report.get_opt("labeled").is_none());
+
+    let resp = cli
+        .get("/api/actors/labeled/one")
+        .query("labels", &true)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    let report = json.value().object();
+    report.get("values").object().get("1").assert_f64(21.5);
+    let labeled = report.get("labeled").object();
+    labeled.get("temperature_c").assert_f64(21.5);
+    labeled.get("3").assert_f64(1.0);
+}
+
+#[test]
+fn test_label_command_names_the_printed_state() {
+    let dir = std::env::temp_dir().join("navactor_label_command");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("{e}"));
+
+    let label = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .current_dir(&dir)
+        .args(["label", "labelcli", "1", "temperature_c"])
+        .output()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    assert_eq!(label.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&label.stdout).trim(),
+        "1 -> temperature_c"
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .current_dir(&dir)
+        .args([
+            "update",
+            "--namespace",
+            "labelcli",
+            "--output-format",
+            "json",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(
+                b"{\"path\": \"/labelcli/one\", \"datetime\": \"2023-01-11T23:17:57Z\", \"values\": {\"1\": 21.5, \"2\": 3.0}}\n",
+            )
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: serde_json::Value =
+        serde_json::from_str(stdout.trim()).unwrap_or_else(|e| panic!("not json '{stdout}': {e}"));
+    assert_eq!(report["values"]["temperature_c"], 21.5);
+    assert_eq!(report["values"]["2"], 3.0);
+}
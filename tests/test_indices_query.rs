@@ -0,0 +1,100 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+async fn observe(director: &Handle, path: &str) {
+    let values = State::from([(1, 1.5), (2, 2.5), (150, 3.5)]);
+    director
+        .ask(Message::Observations {
+            path: String::from(path),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+async fn indices_of(director: &Handle, path: &str, indices: Vec<i32>) -> State<f64> {
+    match director
+        .ask(Message::IndicesQuery {
+            path: String::from(path),
+            indices,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response to indices query: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_indices_query_projects_the_state() {
+    let director = director::new("/indices", 8, None, None);
+    observe(&director, "/indices/one").await;
+
+    assert_eq!(
+        indices_of(&director, "/indices/one", vec![150, 1]).await,
+        State::from([(1, 1.5), (150, 3.5)])
+    );
+    // an index the state does not have is left out
+    assert_eq!(
+        indices_of(&director, "/indices/one", vec![2, 99]).await,
+        State::from([(2, 2.5)])
+    );
+    assert!(indices_of(&director, "/indices/one", vec![99])
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_api_idx_filters_the_state() {
+    let director = director::new("/indicesapi", 8, None, None);
+    observe(&director, "/indicesapi/one").await;
+    let config = HttpServerConfig::new(None, None, None, String::from("indicesapi"));
+    let cli = TestClient::new(build_app(Arc::new(director), &config, None, Some(true)));
+
+    let resp = cli
+        .get("/api/actors/indicesapi/one?idx=1&idx=150&idx=99")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    let values = json.value().object().get("values").object();
+    values.get("1").assert_f64(1.5);
+    values.get("150").assert_f64(3.5);
+    assert!(values.get_opt("2").is_none());
+    assert!(values.get_opt("99").is_none());
+
+    // without idx every index is returned
+    let resp = cli.get("/api/actors/indicesapi/one").send().await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    json.value()
+        .object()
+        .get("values")
+        .object()
+        .get("2")
+        .assert_f64(2.5);
+
+    // none of the indices asked for is still the state of an observed path
+    let resp = cli.get("/api/actors/indicesapi/one?idx=99").send().await;
+    resp.assert_status_is_ok();
+    let resp = cli.get("/api/actors/indicesapi/two?idx=1").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+
+    let resp = cli
+        .get("/api/actors/indicesapi/one?idx=1&bucket=2023-01-11")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
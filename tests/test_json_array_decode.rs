@@ -0,0 +1,113 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+
+async fn update(decoder: &Handle, text: &str) -> Result<Message<f64>, String> {
+    decoder
+        .ask(Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(text),
+        })
+        .await
+        .map_err(|e| e.reason)
+}
+
+async fn state_of(director: &Handle, path: &str) -> (State<f64>, u64) {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport {
+            values, version, ..
+        }) => (values, version),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_an_array_is_one_update_per_element() {
+    let decoder = json_decoder::new(8, director::new("/arrays", 8, None, None));
+
+    let last = update(
+        &decoder,
+        r#"[
+            {"path": "/arrays/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+            {"path": "/arrays/one", "datetime": "2023-01-11T23:17:58Z", "values": {"2": 2.0}}
+        ]"#,
+    )
+    .await;
+    // the line is answered with the state after its last observation
+    match last {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_eq!(values, State::from([(1, 1.0), (2, 2.0)]));
+        }
+        r => panic!("bad response to the array: {r:?}"),
+    }
+
+    // and a single object still is a single update
+    update(
+        &decoder,
+        r#"{"path": "/arrays/two", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 3.0}}"#,
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+
+    let director = director::new("/arrays", 8, None, None);
+    let decoder = json_decoder::new(8, director);
+    update(
+        &decoder,
+        r#"[
+            {"path": "/arrays/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+            {"path": "/arrays/two", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 2.0}}
+        ]"#,
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    // the decoder passes everything else through to the director
+    assert_eq!(
+        state_of(&decoder, "/arrays/one").await,
+        (State::from([(1, 1.0)]), 1)
+    );
+    assert_eq!(
+        state_of(&decoder, "/arrays/two").await,
+        (State::from([(1, 2.0)]), 1)
+    );
+}
+
+#[tokio::test]
+async fn test_an_array_is_rejected_whole() {
+    let decoder = json_decoder::new(8, director::new("/badarrays", 8, None, None));
+
+    assert_eq!(
+        update(
+            &decoder,
+            r#"[
+                {"path": "/badarrays/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+                {"path": "/badarrays/one", "datetime": "2023-01-11T23:17:58Z", "values": {}}
+            ]"#,
+        )
+        .await
+        .err(),
+        Some(String::from("no values to observe"))
+    );
+    assert_eq!(
+        update(&decoder, "[]").await.err(),
+        Some(String::from("no values to observe"))
+    );
+    assert!(update(&decoder, r#"[{"path": "/badarrays/one"}, 1]"#)
+        .await
+        .is_err_and(|e| e.starts_with("json parse error")));
+
+    // nothing of the rejected lines was observed
+    assert_eq!(
+        state_of(&decoder, "/badarrays/one").await,
+        (State::new(), 0)
+    );
+}
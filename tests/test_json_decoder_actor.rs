@@ -1,14 +1,14 @@
-use navactor::utils::nvtime::extract_datetime;
-use navactor::io::json_decoder;
 use navactor::actors::message::Envelope;
 use navactor::actors::message::Message;
 use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
 use navactor::io::stdout_actor;
+use navactor::utils::nvtime::extract_datetime;
 use tokio::runtime::Runtime;
 use tokio::sync::oneshot;
 use tracing::debug;
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_json_decode() {
     let rt = Runtime::new().unwrap();
@@ -27,7 +27,7 @@ fn test_json_decode() {
         
         match json_decoder_actor.ask(cmd).await {
             Ok(r) => {
-                if let Message::Observations { datetime, path, values } = r {
+                if let Message::Observations { datetime, path, values, .. } = r {
                     assert_eq!(path, "/actors");
                     let keys: Vec<&i32> = values.keys().collect();
                     assert_eq!(keys.len(), 2);
@@ -38,11 +38,11 @@ fn test_json_decode() {
                     let baddt = extract_datetime("2022-01-11T23:17:57+0000").unwrap();
                     assert_ne!(baddt, datetime);
                 } else {
-                    assert!(false, "bad response from output actor: {r:?}");
+                    panic!("bad response from output actor: {r:?}");
                 }
             }
             Err(e) => {
-                assert!(false, "{e}");
+                panic!("{e}");
             }
         }
         //
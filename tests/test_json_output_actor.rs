@@ -0,0 +1,65 @@
+use navactor::actors::actor::State;
+use navactor::io::json_output_actor;
+use navactor::utils::nvtime::extract_datetime;
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use time::macros::datetime;
+
+fn parse(line: &str) -> Value {
+    serde_json::from_str(line).unwrap_or_else(|e| panic!("not json '{line}': {e}"))
+}
+
+#[test]
+fn test_state_report_line() {
+    let mut values: State<f64> = State::new();
+    values.insert(1, 1.5);
+    values.insert(2, 3.0);
+    let line = json_output_actor::state_report_line(
+        "/actors/one",
+        datetime!(2023-01-11 23:17:57 UTC),
+        &values,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert!(!line.contains('\n'));
+
+    let report = parse(&line);
+    assert_eq!(report["path"], "/actors/one");
+    assert_eq!(report["values"]["1"], 1.5);
+    assert_eq!(report["values"]["2"], 3.0);
+    let datetime = report["datetime"].as_str().unwrap_or_default();
+    assert_eq!(
+        extract_datetime(datetime).unwrap_or_else(|e| panic!("{e}")),
+        datetime!(2023-01-11 23:17:57 UTC)
+    );
+}
+
+#[test]
+fn test_update_prints_ndjson() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["--memory-only", "update", "--output-format", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(
+                b"{ \"path\": \"/actors/ndjson\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }\n\
+                  { \"path\": \"/actors/ndjson\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"2\": 2.2} }\n",
+            )
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let reports: Vec<Value> = stdout.lines().map(parse).collect();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[1]["path"], "/actors/ndjson");
+    assert_eq!(reports[1]["values"]["1"], 1.1);
+    assert_eq!(reports[1]["values"]["2"], 2.2);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{remove_db, setup_director};
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_series_filtered_by_label() {
+    let namespace = String::from("/labels");
+    let db_file_prefix = format!("/tmp{namespace}");
+    remove_db(&db_file_prefix);
+    let server_config = HttpServerConfig::new(None, None, None, namespace.clone());
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let observations = [
+        (
+            "2023-01-11T23:17:57Z",
+            1.0,
+            json!({"region": "west", "fw": "1.2"}),
+        ),
+        ("2023-01-11T23:17:58Z", 2.0, json!({"region": "east"})),
+        (
+            "2023-01-11T23:17:59Z",
+            3.0,
+            json!({"region": "west", "fw": "1.3"}),
+        ),
+        ("2023-01-11T23:18:00Z", 4.0, json!({})),
+    ];
+    for (datetime, value, labels) in observations {
+        let resp = cli
+            .post("/api/actors/labels/one")
+            .body_json(&json!({
+                "path": "/labels/one",
+                "datetime": datetime,
+                "values": {"1": value},
+                "labels": labels,
+            }))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+    }
+
+    let resp = cli
+        .get("/api/series/labels/one")
+        .query("label.region", &"west")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    let series = body.as_array().unwrap();
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0]["values"]["1"], json!(1.0));
+    assert_eq!(series[1]["values"]["1"], json!(3.0));
+    assert!(series
+        .iter()
+        .all(|o| o["labels"]["region"] == json!("west")));
+
+    // every label in the filter has to match
+    let resp = cli
+        .get("/api/series/labels/one")
+        .query("label.region", &"west")
+        .query("label.fw", &"1.3")
+        .send()
+        .await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    // no filter is the whole series
+    let resp = cli.get("/api/series/labels/one").send().await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body.as_array().unwrap().len(), 4);
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_labels_column_is_migrated() {
+    let namespace = String::from("/labels_migration");
+    let db_file_prefix = format!("/tmp{namespace}");
+    remove_db(&db_file_prefix);
+    let db_url = format!("sqlite://{db_file_prefix}.db?mode=rwc");
+
+    // a db created before observations had labels
+    let pool = SqlitePool::connect(&db_url).await.unwrap();
+    sqlx::query(
+        "CREATE TABLE updates (
+              path TEXT NOT NULL,
+              timestamp TEXT NOT NULL,
+              sequence TEXT NOT NULL,
+              values_str TEXT NOT NULL,
+              PRIMARY KEY (path, timestamp)
+        )",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO updates VALUES ('/labels_migration/one', '1673479077', '1673479077', '{\"1\":1.5}')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let server_config = HttpServerConfig::new(None, None, None, namespace.clone());
+    let app = build_app(
+        Arc::new(setup_director(&db_file_prefix, &namespace)),
+        &server_config,
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let resp = cli
+        .post("/api/actors/labels_migration/one")
+        .body_json(&json!({
+            "path": "/labels_migration/one",
+            "datetime": "2023-01-11T23:18:57Z",
+            "values": {"1": 2.5},
+            "labels": {"region": "west"},
+        }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    let resp = cli.get("/api/series/labels_migration/one").send().await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let resp = cli
+        .get("/api/series/labels_migration/one")
+        .query("label.region", &"west")
+        .send()
+        .await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
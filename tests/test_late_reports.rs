@@ -0,0 +1,131 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+fn at(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn observation(path: &str, secs: i64, value: f64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: at(secs),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn map(director: &Handle, path: &str, gene_type: GeneType) {
+    director
+        .ask(Message::GeneMapping {
+            path: String::from(path),
+            gene_type,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+async fn value_of(director: &Handle, path: &str) -> Option<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values.get(&1).copied(),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_gauge_rejects_late_reports() {
+    let director = director::new("/late", 8, None, None);
+    map(&director, "/late/gauge", GeneType::Gauge).await;
+
+    director
+        .ask(observation("/late/gauge/one", 10, 1.0))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    match director.ask(observation("/late/gauge/one", 5, 2.0)).await {
+        Ok(Message::LateReport {
+            path,
+            datetime,
+            latest,
+        }) => {
+            assert_eq!(path, "/late/gauge/one");
+            assert_eq!(datetime, at(5));
+            assert_eq!(latest, at(10));
+        }
+        r => panic!("late report should be rejected: {r:?}"),
+    }
+    assert_eq!(value_of(&director, "/late/gauge/one").await, Some(1.0));
+
+    // a report taken at the same time as the latest is not late
+    director
+        .ask(observation("/late/gauge/one", 10, 3.0))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(value_of(&director, "/late/gauge/one").await, Some(3.0));
+}
+
+#[tokio::test]
+async fn test_accum_accepts_late_reports() {
+    let director = director::new("/late", 8, None, None);
+    map(&director, "/late/accum", GeneType::Accum).await;
+
+    for (secs, value) in [(10, 1.0), (5, 2.0)] {
+        match director
+            .ask(observation("/late/accum/one", secs, value))
+            .await
+        {
+            Ok(Message::StateReport { .. }) => {}
+            r => panic!("observation should be applied: {r:?}"),
+        }
+    }
+    assert_eq!(value_of(&director, "/late/accum/one").await, Some(3.0));
+}
+
+/// the journaled late report is skipped again by the replay and the
+/// resurrected actor keeps rejecting reports from before its latest
+#[tokio::test]
+async fn test_late_reports_rejected_after_resurrection() {
+    let db_file_prefix = "/tmp/late_reports";
+    remove_db(db_file_prefix);
+    let director_of = || {
+        let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+        director::new("/late", 8, None, Some(store_actor))
+    };
+
+    let director = director_of();
+    map(&director, "/late/gauge", GeneType::Gauge).await;
+    for (secs, value) in [(10, 1.0), (5, 2.0), (20, 3.0)] {
+        director
+            .ask(observation("/late/gauge/one", secs, value))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    assert_eq!(value_of(&director, "/late/gauge/one").await, Some(3.0));
+
+    let resurrected = director_of();
+    assert_eq!(value_of(&resurrected, "/late/gauge/one").await, Some(3.0));
+    assert!(matches!(
+        resurrected
+            .ask(observation("/late/gauge/one", 15, 4.0))
+            .await,
+        Ok(Message::LateReport { .. })
+    ));
+}
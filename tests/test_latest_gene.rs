@@ -0,0 +1,92 @@
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::latest_gene::LatestGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::OffsetDateTime;
+use tokio::runtime::Runtime;
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_latest_gene() {
+    let mut state: State<f64> = State::new();
+    state.insert(0, 1.9);
+    state.insert(1, 2.7);
+
+    let mut values: State<f64> = State::new();
+    values.insert(1, 4.1);
+    values.insert(7, 0.3);
+
+    let g1 = LatestGene {
+        ..Default::default()
+    };
+
+    let msg = Message::Observations {
+        path: String::from("/"),
+        datetime: OffsetDateTime::now_utc(),
+        values: values.clone(),
+        labels: Labels::new(),
+    };
+
+    let r = g1.apply_operators(state, msg);
+    assert!(r.is_ok(), "{r:?}");
+    assert_eq!(r.unwrap(), values);
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_latest_observation_replaces_state() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let director = director::new("/", 8, None, None);
+        director
+            .ask(Message::GeneMapping {
+                path: String::from("/snapshots"),
+                gene_type: GeneType::Latest,
+                params: GeneParams::default(),
+            })
+            .await
+            .unwrap();
+
+        let mut first = State::new();
+        first.insert(1, 1.1);
+        first.insert(2, 2.2);
+        first.insert(3, 3.3);
+        director
+            .ask(Message::Observations {
+                path: String::from("/snapshots/one"),
+                datetime: OffsetDateTime::now_utc(),
+                values: first,
+                labels: Labels::new(),
+            })
+            .await
+            .unwrap();
+
+        let mut second = State::new();
+        second.insert(2, 9.9);
+        director
+            .ask(Message::Observations {
+                path: String::from("/snapshots/one"),
+                datetime: OffsetDateTime::now_utc(),
+                values: second.clone(),
+                labels: Labels::new(),
+            })
+            .await
+            .unwrap();
+
+        match director
+            .ask(Message::Query {
+                path: String::from("/snapshots/one"),
+                hint: MtHint::State,
+            })
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => assert_eq!(values, second),
+            r => panic!("bad response from director: {r:?}"),
+        }
+    });
+}
@@ -0,0 +1,82 @@
+use approx::assert_ulps_eq;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::line_protocol_decoder;
+use navactor::io::line_protocol_decoder::FieldIndices;
+use time::OffsetDateTime;
+
+fn fields() -> FieldIndices {
+    line_protocol_decoder::parse_field_indices(&[
+        String::from("usage_idle=1"),
+        String::from("usage_user=2"),
+    ])
+    .unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[test]
+fn test_parse_line() {
+    let point = line_protocol_decoder::parse_line(
+        "cpu,region=us,host=a usage_idle=97.5,usage_user=2i,active=t,note=\"a b\",7=1u 1673479077000000000",
+        &fields(),
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    // tag values in tag key order
+    assert_eq!(point.path, "/cpu/a/us");
+    assert_eq!(point.labels["host"], "a");
+    assert_eq!(point.datetime.unix_timestamp(), 1_673_479_077);
+    // the unmapped and string fields are skipped
+    assert_eq!(point.values.len(), 3);
+    assert_ulps_eq!(point.values[&1], 97.5, max_ulps = 4);
+    assert_ulps_eq!(point.values[&2], 2.0, max_ulps = 4);
+    assert_ulps_eq!(point.values[&7], 1.0, max_ulps = 4);
+
+    let before = OffsetDateTime::now_utc();
+    let point = line_protocol_decoder::parse_line("disk\\ io usage_idle=1.0", &fields())
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(point.path, "/disk io");
+    assert!(point.datetime >= before);
+
+    assert!(line_protocol_decoder::parse_line("cpu unmapped=1.0", &fields()).is_err());
+    assert!(line_protocol_decoder::parse_line("cpu usage_idle=oops", &fields()).is_err());
+    assert!(line_protocol_decoder::parse_line("cpu usage_idle=1.0 soon", &fields()).is_err());
+    assert!(line_protocol_decoder::parse_field_indices(&[String::from("usage_idle")]).is_err());
+}
+
+#[tokio::test]
+async fn test_line_protocol_updates_state() {
+    let director = director::new("/cpu", 8, None, None);
+    let decoder = line_protocol_decoder::new(8, director, fields());
+    let lines = [
+        "# telegraf output",
+        "cpu,host=a usage_idle=90.0,usage_user=10.0 1673479077000000000",
+        "cpu,host=a oops",
+        "cpu,host=a usage_idle=80.0 1673479078000000000",
+    ];
+    for text in lines {
+        decoder
+            .tell(Message::Content {
+                text: String::from(text),
+                hint: MtHint::Update,
+                path: None,
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    // the bad line is reported when the stream completes
+    assert!(decoder.ask(Message::EndOfStream {}).await.is_err());
+
+    match decoder
+        .ask(Message::Query {
+            path: String::from("/cpu/a"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values[&1], 80.0, max_ulps = 4);
+            assert_ulps_eq!(values[&2], 10.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
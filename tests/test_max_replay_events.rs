@@ -0,0 +1,136 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Journal;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use time::OffsetDateTime;
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_gauge_replays_only_the_most_recent_events() {
+    let db_file_prefix = "/tmp/max_replay";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::try_new_with_options(
+        8,
+        String::from(db_file_prefix),
+        false,
+        false,
+        StoreOptions {
+            max_replay_events: Some(10),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // every event reports a different index so the replayed ones can be counted
+    for n in 0..50 {
+        let mut values = State::new();
+        values.insert(n, f64::from(n));
+        let reply = store_actor
+            .ask(Message::Observations {
+                path: String::from("/replay/one"),
+                datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + i64::from(n))
+                    .unwrap(),
+                values,
+                labels: Labels::new(),
+            })
+            .await;
+        assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+    }
+
+    let director = director::new("/replay", 8, None, Some(store_actor));
+    match director
+        .ask(Message::Query {
+            path: String::from("/replay/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_eq!(values.len(), 10);
+            assert_eq!(values.keys().min(), Some(&40));
+            assert_eq!(values.get(&49), Some(&49.0));
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+/// the observed value of every observation, and `None` for a reset
+fn replayed(values: &[Message<f64>]) -> Vec<Option<f64>> {
+    values
+        .iter()
+        .map(|m| match m {
+            Message::Observations { values, .. } => values.get(&1).copied(),
+            Message::Reset { .. } => None,
+            m => panic!("not a journal event: {m:?}"),
+        })
+        .collect()
+}
+
+async fn journal(store: &SqliteStore, n: i64) {
+    let at =
+        OffsetDateTime::from_unix_timestamp(1_673_479_077 + n).unwrap_or_else(|e| panic!("{e}"));
+    #[allow(clippy::cast_precision_loss)]
+    let values = State::from([(1, n as f64)]);
+    store
+        .insert_update("/replay/two", at, at, &values, &Labels::new())
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+#[tokio::test]
+async fn test_capped_replay_is_in_replay_order() {
+    let db_file_prefix = "/tmp/max_replay_order";
+    remove_db(db_file_prefix);
+    let store = SqliteStore::open(db_file_prefix, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    // journaled out of order with a reset after the observation at 4
+    for n in [1, 4, 2] {
+        journal(&store, n).await;
+    }
+    Journal::<f64>::insert_reset(&store, "/replay/two", OffsetDateTime::now_utc())
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for n in [6, 5, 7] {
+        journal(&store, n).await;
+    }
+
+    let all: Vec<Message<f64>> = store
+        .get_values("/replay/two", 0, None)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let uncapped = replayed(&all);
+    assert_eq!(
+        uncapped,
+        vec![
+            Some(1.0),
+            Some(2.0),
+            Some(4.0),
+            None,
+            Some(5.0),
+            Some(6.0),
+            Some(7.0)
+        ]
+    );
+
+    // a cap keeps the newest of the same order
+    for max in [100, 4] {
+        let capped: Vec<Message<f64>> = store
+            .get_values("/replay/two", 0, Some(max))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+        let skip = uncapped.len().saturating_sub(max);
+        assert_eq!(replayed(&capped), uncapped[skip..]);
+    }
+    store.close().await;
+}
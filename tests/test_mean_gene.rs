@@ -0,0 +1,77 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::mean_gene::MeanGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use time::OffsetDateTime;
+
+async fn observe(director: &Handle, n: i64, reading: f64) -> State<f64> {
+    let mut values = State::new();
+    values.insert(1, reading);
+    match director
+        .ask(Message::Observations {
+            path: String::from("/means/one"),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_mean_gene_rejects_count_idx() {
+    let mut values: State<f64> = State::new();
+    values.insert(101, 4.0);
+
+    let msg = Message::Observations {
+        path: String::from("/"),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    };
+
+    let r = MeanGene::default().apply_operators(State::new(), msg);
+    assert!(r.is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_mean_survives_restart() {
+    let db_file_prefix = "/tmp/means";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/means");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/means"),
+            gene_type: GeneType::Mean,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap();
+
+    observe(&director, 0, 3.0).await;
+    observe(&director, 1, 6.0).await;
+    let state = observe(&director, 2, 9.0).await;
+    assert_ulps_eq!(state[&1], 6.0, max_ulps = 4);
+    assert_ulps_eq!(state[&101], 3.0, max_ulps = 4);
+
+    // a new director resurrects the actor and its counts from the journal
+    let restarted = setup_director(db_file_prefix, "/means");
+    let state = observe(&restarted, 3, 10.0).await;
+    assert_ulps_eq!(state[&1], 7.0, max_ulps = 4);
+    assert_ulps_eq!(state[&101], 4.0, max_ulps = 4);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_metrics_are_scraped() {
+    let db_file_prefix = "/tmp/metered";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/metered", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("metered"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let observation = json!({
+        "path": "/metered/one",
+        "datetime": "2023-01-11T23:17:57Z",
+        "values": {"1": 1.0},
+    });
+    cli.post("/api/actors/metered/one")
+        .body_json(&observation)
+        .send()
+        .await
+        .assert_status_is_ok();
+    // the same path and datetime again is a duplicate
+    cli.post("/api/actors/metered/one")
+        .body_json(&observation)
+        .send()
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    cli.get("/api/actors/metered/one")
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    let resp = cli.get("/metrics").send().await;
+    resp.assert_status_is_ok();
+    let text = resp.0.into_body().into_string().await.unwrap_or_default();
+    for line in [
+        "# TYPE nv_observations_total counter",
+        "nv_observations_total{namespace=\"/metered\",message_type=\"observations\"} 1",
+        "nv_queries_total{namespace=\"/metered\",message_type=\"state\"} 1",
+        "nv_constraint_violations_total{namespace=\"/metered\",message_type=\"observations\"} 1",
+        "# TYPE nv_post_observations_seconds histogram",
+        "nv_post_observations_seconds_count{namespace=\"/metered\"} 2",
+        "nv_post_observations_seconds_bucket{namespace=\"/metered\",le=\"+Inf\"} 2",
+        "# TYPE nv_mailbox_capacity gauge",
+        "nv_mailbox_capacity{namespace=\"/metered\",mailbox=\"director\"} 8",
+        "nv_mailbox_capacity{namespace=\"/metered\",mailbox=\"store\"} 8",
+    ] {
+        assert!(text.contains(line), "{line} missing from:\n{text}");
+    }
+}
+
+#[tokio::test]
+async fn test_mailbox_fill() {
+    let (sender, mut receiver) = mpsc::channel::<Envelope<f64>>(4);
+    let handle = Handle::new(sender);
+    for _ in 0..3 {
+        handle
+            .tell(Message::Ping)
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    assert_eq!((handle.queued(), handle.capacity()), (3, 4));
+    receiver.recv().await;
+    assert_eq!((handle.queued(), handle.capacity()), (2, 4));
+}
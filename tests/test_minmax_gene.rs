@@ -0,0 +1,116 @@
+mod common;
+
+use common::{remove_db, setup_director};
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::minmax_gene::MinMaxGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::OffsetDateTime;
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_minmax_gene() {
+    let mut state: State<f64> = State::new();
+    state.insert(0, 1.9);
+    state.insert(100, 1.9);
+
+    let mut values: State<f64> = State::new();
+    values.insert(0, 0.5);
+    values.insert(100, 0.5);
+
+    let msg = Message::Observations {
+        path: String::from("/"),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    };
+
+    let g1 = MinMaxGene {
+        min_slots: 100,
+        max_first_idx: 100,
+        max_slots: 100,
+        ..MinMaxGene::all_min()
+    };
+    let r = g1.apply_operators(state, msg).unwrap();
+    assert_eq!(r.get(&0), Some(&0.5));
+    assert_eq!(r.get(&100), Some(&1.9));
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_min_and_max_survive_replay() {
+    let db_file_prefix = "/tmp/temps";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/temps");
+    for (path, gene_type) in [
+        ("/temps/low", GeneType::Min),
+        ("/temps/high", GeneType::Max),
+    ] {
+        director
+            .ask(Message::GeneMapping {
+                path: String::from(path),
+                gene_type,
+                params: GeneParams::default(),
+            })
+            .await
+            .unwrap();
+    }
+
+    // the readings fall and then rise again so neither extreme is the last one reported
+    for (n, reading) in [5.0, 3.0, 1.0, 2.0, 4.0].into_iter().enumerate() {
+        let mut values = State::new();
+        values.insert(1, reading);
+        for path in ["/temps/low/one", "/temps/high/one"] {
+            director
+                .ask(Message::Observations {
+                    path: String::from(path),
+                    datetime: OffsetDateTime::from_unix_timestamp(
+                        1_673_479_077 + i64::try_from(n).unwrap(),
+                    )
+                    .unwrap(),
+                    values: values.clone(),
+                    labels: Labels::new(),
+                })
+                .await
+                .unwrap();
+        }
+    }
+    assert_eq!(
+        state_of(&director, "/temps/low/one").await.get(&1),
+        Some(&1.0)
+    );
+    assert_eq!(
+        state_of(&director, "/temps/high/one").await.get(&1),
+        Some(&5.0)
+    );
+
+    // a new director resurrects both actors from the journal
+    let restarted = setup_director(db_file_prefix, "/temps");
+    assert_eq!(
+        state_of(&restarted, "/temps/low/one").await.get(&1),
+        Some(&1.0)
+    );
+    assert_eq!(
+        state_of(&restarted, "/temps/high/one").await.get(&1),
+        Some(&5.0)
+    );
+}
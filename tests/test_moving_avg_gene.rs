@@ -0,0 +1,148 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::moving_avg_gene::MovingAvgGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use time::OffsetDateTime;
+
+fn reading(path: &str, n: i64, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_moving_avg_gene_ages_out() {
+    let g1 = MovingAvgGene {
+        window: 3,
+        ..Default::default()
+    };
+
+    // 3 + 2 values, the oldest two, 100 and 200, fall out of the window
+    let mut state = State::new();
+    for (n, v) in [100.0, 200.0, 1.0, 2.0, 3.0].into_iter().enumerate() {
+        state = g1
+            .apply_operators(state, reading("/", i64::try_from(n).unwrap(), v))
+            .unwrap();
+    }
+    assert_ulps_eq!(state.get(&1).unwrap(), &2.0, max_ulps = 4);
+
+    assert!(MovingAvgGene {
+        window: 0,
+        ..Default::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_moving_avg_window_is_persisted() {
+    let db_file_prefix = "/tmp/windows";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/windows");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/windows"),
+            gene_type: GeneType::MovingAvg,
+            params: GeneParams {
+                window: Some(2),
+                ..Default::default()
+            },
+        })
+        .await
+        .unwrap();
+    director
+        .ask(reading("/windows/one", 0, 10.0))
+        .await
+        .unwrap();
+    director
+        .ask(reading("/windows/one", 1, 20.0))
+        .await
+        .unwrap();
+
+    // a new director reads the window back and the replay refills it
+    let restarted = setup_director(db_file_prefix, "/windows");
+    match restarted
+        .ask(Message::Content {
+            path: Some(String::from("/windows")),
+            text: String::new(),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+    {
+        Ok(Message::GeneMapping {
+            gene_type, params, ..
+        }) => {
+            assert_eq!(gene_type, GeneType::MovingAvg);
+            assert_eq!(params.window, Some(2));
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    match restarted.ask(reading("/windows/one", 2, 40.0)).await {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&1).unwrap(), &30.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_moving_avg_window_is_reset() {
+    let db_file_prefix = "/tmp/windows_reset";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/windows");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/windows"),
+            gene_type: GeneType::MovingAvg,
+            params: GeneParams {
+                window: Some(3),
+                ..Default::default()
+            },
+        })
+        .await
+        .unwrap();
+    for (n, v) in [(0, 100.0), (1, 200.0)] {
+        director.ask(reading("/windows/one", n, v)).await.unwrap();
+    }
+    director
+        .ask(Message::Reset {
+            path: String::from("/windows/one"),
+        })
+        .await
+        .unwrap();
+
+    // the readings before the reset are not averaged in, live or replayed
+    match director.ask(reading("/windows/one", 2, 4.0)).await {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&1).unwrap(), &4.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+    let restarted = setup_director(db_file_prefix, "/windows");
+    match restarted.ask(reading("/windows/one", 3, 8.0)).await {
+        Ok(Message::StateReport { values, .. }) => {
+            assert_ulps_eq!(values.get(&1).unwrap(), &6.0, max_ulps = 4);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
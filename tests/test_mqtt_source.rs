@@ -0,0 +1,75 @@
+use approx::assert_ulps_eq;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+use navactor::io::net::mqtt_source;
+use std::time::Duration;
+
+#[test]
+fn test_broker_and_topic() {
+    assert_eq!(
+        mqtt_source::parse_broker("mqtt://broker.local:1884"),
+        Ok((String::from("broker.local"), 1884))
+    );
+    assert_eq!(
+        mqtt_source::parse_broker("broker.local"),
+        Ok((String::from("broker.local"), mqtt_source::DEFAULT_PORT))
+    );
+    assert!(mqtt_source::parse_broker("broker.local:mqtt").is_err());
+    assert!(mqtt_source::parse_broker(":1883").is_err());
+
+    assert_eq!(mqtt_source::topic_to_path("actors/one"), "/actors/one");
+    assert_eq!(mqtt_source::topic_to_path("/actors/one"), "/actors/one");
+}
+
+#[tokio::test]
+async fn test_topic_is_the_path() {
+    let director = director::new("/actors", 8, None, None);
+    let decoder = json_decoder::new(8, director);
+
+    // the payload of an mqtt message does not need a path of its own
+    let text = r#"{ "datetime": "2023-01-11T23:17:57+0000", "values": {"1": 1.5} }"#;
+    match decoder
+        .ask(Message::Content {
+            text: String::from(text),
+            hint: MtHint::Update,
+            path: Some(mqtt_source::topic_to_path("actors/mqtt")),
+        })
+        .await
+    {
+        Ok(Message::StateReport { path, values, .. }) => {
+            assert_eq!(path, "/actors/mqtt");
+            assert_ulps_eq!(values[&1], 1.5, max_ulps = 4);
+        }
+        r => panic!("bad response from decoder: {r:?}"),
+    }
+
+    // but without the topic it does
+    let r = decoder
+        .ask(Message::Content {
+            text: String::from(text),
+            hint: MtHint::Update,
+            path: None,
+        })
+        .await;
+    assert!(r.is_err());
+}
+
+#[tokio::test]
+async fn test_source_keeps_retrying() {
+    let director = director::new("/actors", 8, None, None);
+    let decoder = json_decoder::new(8, director);
+    let source = mqtt_source::new(
+        8,
+        decoder,
+        "nv-test",
+        "127.0.0.1",
+        1,
+        String::from("actors/#"),
+    );
+
+    // no broker is listening but the source does not give up
+    let r = tokio::time::timeout(Duration::from_secs(2), source.ask(Message::ReadAllCmd {})).await;
+    assert!(r.is_err());
+}
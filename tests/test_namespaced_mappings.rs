@@ -0,0 +1,196 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::fs;
+use std::sync::Arc;
+
+const PATH: &str = "/shared/one";
+
+fn director_of(db_file: &str, namespace: &str) -> Handle {
+    let store_actor =
+        store_actor_sqlite::new_from_url(8, String::from(db_file), namespace, false, false);
+    director::new(namespace, 8, None, Some(store_actor))
+}
+
+async fn mapping_of(director: &Handle) -> Option<GeneType> {
+    match director
+        .ask(Message::Content {
+            text: String::new(),
+            path: Some(String::from(PATH)),
+            hint: MtHint::GeneMappingQuery,
+        })
+        .await
+    {
+        Ok(Message::GeneMapping { gene_type, .. }) => Some(gene_type),
+        Ok(Message::NotFound { .. }) => None,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_namespaces_keep_independent_mappings() {
+    let db_file = "/tmp/shared_mappings.db";
+    remove_db(db_file.trim_end_matches(".db"));
+
+    for (namespace, gene_type) in [("/alpha", GeneType::Accum), ("/beta", GeneType::Gauge)] {
+        director_of(db_file, namespace)
+            .ask(Message::GeneMapping {
+                path: String::from(PATH),
+                gene_type,
+                params: GeneParams::default(),
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    assert_eq!(
+        mapping_of(&director_of(db_file, "/alpha")).await,
+        Some(GeneType::Accum)
+    );
+    assert_eq!(
+        mapping_of(&director_of(db_file, "beta")).await,
+        Some(GeneType::Gauge)
+    );
+    assert_eq!(mapping_of(&director_of(db_file, "/gamma")).await, None);
+
+    // deleting a mapping only removes the namespace's own
+    director_of(db_file, "/alpha")
+        .ask(Message::Delete {
+            path: String::from(PATH),
+            mapping: true,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(mapping_of(&director_of(db_file, "/alpha")).await, None);
+    assert_eq!(
+        mapping_of(&director_of(db_file, "/beta")).await,
+        Some(GeneType::Gauge)
+    );
+}
+
+#[tokio::test]
+async fn test_mappings_without_namespace_are_migrated() {
+    let db_file = "/tmp/unscoped_mappings.db";
+    remove_db(db_file.trim_end_matches(".db"));
+    fs::File::create(db_file).unwrap_or_else(|e| panic!("{e}"));
+    let legacy = sqlx::SqlitePool::connect(db_file)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for sql in [
+        "CREATE TABLE gene_mappings (
+              path TEXT NOT NULL,
+              gene_type TEXT NOT NULL,
+              params TEXT,
+              PRIMARY KEY (path)
+        )",
+        "INSERT INTO gene_mappings (path, gene_type) VALUES ('/shared/one', '\"Accum\"')",
+    ] {
+        sqlx::query(sql)
+            .execute(&legacy)
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    legacy.close().await;
+
+    let store = SqliteStore::connect(db_file, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    // the unscoped mapping is seen by every namespace until one maps the path
+    // itself
+    store
+        .insert_gene_mapping("beta", PATH, &GeneType::Gauge, &GeneParams::default())
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    store.close().await;
+
+    assert_eq!(
+        mapping_of(&director_of(db_file, "/alpha")).await,
+        Some(GeneType::Accum)
+    );
+    assert_eq!(
+        mapping_of(&director_of(db_file, "/beta")).await,
+        Some(GeneType::Gauge)
+    );
+
+    // deleting in one namespace leaves the unscoped mapping to the others
+    // and to the live mapping of the namespace itself
+    let beta = director_of(db_file, "/beta");
+    beta.ask(Message::Delete {
+        path: String::from(PATH),
+        mapping: true,
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(mapping_of(&beta).await, Some(GeneType::Accum));
+    for namespace in ["/alpha", "/beta", "/gamma"] {
+        assert_eq!(
+            mapping_of(&director_of(db_file, namespace)).await,
+            Some(GeneType::Accum),
+            "{namespace}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_shared_mapping_is_not_deleted_by_a_namespace() {
+    let db_file = "/tmp/undeleted_shared_mappings.db";
+    remove_db(db_file.trim_end_matches(".db"));
+    let store = SqliteStore::connect(db_file, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for (namespace, gene_type) in [("", GeneType::Accum), ("beta", GeneType::Gauge)] {
+        store
+            .insert_gene_mapping(namespace, PATH, &gene_type, &GeneParams::default())
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    store.close().await;
+
+    // the namespace's own mapping is deleted and the shared one maps the
+    // path again
+    let beta = director_of(db_file, "/beta");
+    assert_eq!(mapping_of(&beta).await, Some(GeneType::Gauge));
+    let delete = || Message::DeleteGeneMapping {
+        path: String::from(PATH),
+    };
+    assert!(matches!(
+        beta.ask(delete()).await,
+        Ok(Message::DeleteGeneMapping { .. })
+    ));
+    assert_eq!(mapping_of(&beta).await, Some(GeneType::Accum));
+
+    // and the shared one is not the namespace's to delete
+    assert!(matches!(
+        beta.ask(delete()).await,
+        Ok(Message::ConstraintViolation)
+    ));
+    assert_eq!(mapping_of(&beta).await, Some(GeneType::Accum));
+
+    let config = HttpServerConfig::new(None, None, None, String::from("shared"));
+    let cli = TestClient::new(build_app(Arc::new(beta), &config, None, Some(true)));
+    let resp = cli.delete("/api/genes/shared/one").send().await;
+    resp.assert_status(StatusCode::CONFLICT);
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("code")
+        .assert_string("shared_mapping");
+    cli.get("/api/genes/shared/one")
+        .send()
+        .await
+        .assert_status_is_ok();
+}
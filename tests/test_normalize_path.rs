@@ -0,0 +1,16 @@
+use navactor::io::net::api_server::normalize_path;
+
+#[test]
+fn test_normalize_path() {
+    for path in ["actors/one", "/actors/one", "//actors//one", "actors///one"] {
+        assert_eq!(normalize_path(path), "/actors/one", "{path}");
+    }
+}
+
+#[test]
+fn test_normalize_prefix() {
+    assert_eq!(normalize_path("actors/"), "/actors/");
+    assert_eq!(normalize_path("//actors//"), "/actors/");
+    assert_eq!(normalize_path(""), "/");
+    assert_eq!(normalize_path("///"), "/");
+}
@@ -0,0 +1,27 @@
+use navactor::utils::nvtime::extract_datetime;
+use time::macros::datetime;
+
+#[test]
+fn test_extract_datetime() {
+    let parsed = |text: &str| extract_datetime(text).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(parsed("1673478977"), datetime!(2023-01-11 23:16:17 UTC));
+    assert_eq!(parsed("1673478977000"), datetime!(2023-01-11 23:16:17 UTC));
+    assert_eq!(
+        parsed("1673478977123"),
+        datetime!(2023-01-11 23:16:17.123 UTC)
+    );
+    assert_eq!(parsed("1673478977.5"), datetime!(2023-01-11 23:16:17.5 UTC));
+    assert_eq!(
+        parsed("1673478977.1239"),
+        datetime!(2023-01-11 23:16:17.123 UTC)
+    );
+    assert_eq!(parsed("-1.5"), datetime!(1969-12-31 23:59:58.5 UTC));
+    assert_eq!(
+        parsed("2023-01-11T23:16:17+0000"),
+        datetime!(2023-01-11 23:16:17 UTC)
+    );
+
+    assert!(extract_datetime("").is_err());
+    assert!(extract_datetime("1673478977x").is_err());
+    assert!(extract_datetime("99999999999999999999999999999999999999").is_err());
+}
@@ -0,0 +1,166 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+use navactor::io::json_decoder::Observations;
+use navactor::io::json_decoder::ValidationError;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+fn observations(path: &str, datetime: &str, value: f64) -> Observations {
+    let mut values = State::new();
+    values.insert(1, value);
+    Observations {
+        datetime: String::from(datetime),
+        values,
+        path: String::from(path),
+        labels: Labels::new(),
+    }
+}
+
+#[test]
+fn test_validate() {
+    assert!(matches!(
+        observations("/valid/one", "2023-01-11T23:17:57Z", 1.0).validate(),
+        Ok(Message::Observations { .. })
+    ));
+    assert_eq!(
+        observations("", "2023-01-11T23:17:57Z", 1.0)
+            .validate()
+            .err(),
+        Some(ValidationError::MissingPath)
+    );
+    let mut empty = observations("/valid/one", "2023-01-11T23:17:57Z", 1.0);
+    empty.values.clear();
+    assert_eq!(empty.validate().err(), Some(ValidationError::EmptyValues));
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(
+            observations("/valid/one", "2023-01-11T23:17:57Z", value)
+                .validate()
+                .err(),
+            Some(ValidationError::NonFinite(1))
+        );
+    }
+    assert!(matches!(
+        observations("/valid/one", "yesterday", 1.0).validate(),
+        Err(ValidationError::MalformedDatetime(_))
+    ));
+}
+
+async fn update(decoder: &Handle, text: &str) -> String {
+    match decoder
+        .ask(Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(text),
+        })
+        .await
+    {
+        Err(e) => e.reason,
+        r => panic!("invalid observations accepted: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_decoder_rejects_each_field() {
+    let decoder = json_decoder::new(8, director::new("/valid", 8, None, None));
+
+    assert_eq!(
+        update(
+            &decoder,
+            r#"{"datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}}"#
+        )
+        .await,
+        "missing field `path`"
+    );
+    assert_eq!(
+        update(
+            &decoder,
+            r#"{"path": "/valid/one", "datetime": "2023-01-11T23:17:57Z", "values": {}}"#
+        )
+        .await,
+        "no values to observe"
+    );
+    assert_eq!(
+        update(
+            &decoder,
+            r#"{"path": "/valid/one", "datetime": "2023-01-11T23:17:57Z"}"#
+        )
+        .await,
+        "no values to observe"
+    );
+    assert!(update(
+        &decoder,
+        r#"{"path": "/valid/one", "datetime": "yesterday", "values": {"1": 1.0}}"#
+    )
+    .await
+    .starts_with("datetime parse error"));
+    assert!(
+        update(&decoder, r#"{"path": "/valid/one", "values": {"1": 1.0}}"#)
+            .await
+            .starts_with("datetime parse error")
+    );
+    assert!(
+        update(&decoder, r#"{"path": "/valid/one", "values": [1.0]}"#)
+            .await
+            .starts_with("json parse error")
+    );
+}
+
+#[tokio::test]
+async fn test_api_answers_each_field() {
+    let nv: Arc<Handle> = Arc::new(director::new("/validapi", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("validapi"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    for (body, code) in [
+        (
+            json!({"datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}}),
+            "missing_path",
+        ),
+        (
+            json!({"path": "/validapi/one", "datetime": "2023-01-11T23:17:57Z", "values": {}}),
+            "empty_values",
+        ),
+        (
+            json!({"path": "/validapi/one", "datetime": "2023-01-11T23:17:57Z"}),
+            "empty_values",
+        ),
+        (
+            json!({"path": "/validapi/one", "datetime": "yesterday", "values": {"1": 1.0}}),
+            "malformed_datetime",
+        ),
+    ] {
+        let resp = cli
+            .post("/api/actors/validapi/one")
+            .body_json(&body)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.json()
+            .await
+            .value()
+            .object()
+            .get("code")
+            .assert_string(code);
+    }
+
+    let resp = cli
+        .post("/api/actors/validapi/batch")
+        .body_json(&json!([
+            {"path": "/validapi/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+            {"path": "/validapi/one", "datetime": "2023-01-11T23:17:58Z", "values": {}},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({"accepted": 1, "duplicates": 0, "errors": 1}))
+        .await;
+}
@@ -0,0 +1,134 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+use tokio::runtime::Runtime;
+
+fn observation(datetime: &str, value: f64) -> Message<f64> {
+    Message::Content {
+        hint: MtHint::Update,
+        path: None,
+        text: format!(
+            "{{ \"path\": \"/actors/window\", \"datetime\": \"{datetime}\", \"values\": {{\"1\": {value}}} }}"
+        ),
+    }
+}
+
+/// a gauge rejects every late report so the window is tried on a gene that
+/// accepts them
+async fn accept_late_reports(director: &Handle) {
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/actors"),
+            gene_type: GeneType::GaugeAndAccum,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_observation_window() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let options = DirectorOptions {
+            observation_window: Some(time::Duration::seconds(60)),
+            ..Default::default()
+        };
+        let director = director::new_with_options("/", 8, None, None, options);
+        accept_late_reports(&director).await;
+        let json_decoder_actor = json_decoder::new(8, director);
+
+        let reply = json_decoder_actor
+            .ask(observation("2023-01-11T23:17:57+0000", 1.0))
+            .await;
+        assert!(matches!(reply, Ok(Message::StateReport { .. })));
+
+        // out of order but inside the window
+        match json_decoder_actor
+            .ask(observation("2023-01-11T23:17:07+0000", 2.0))
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => assert_eq!(values.get(&1), Some(&2.0)),
+            r => panic!("in-window observation should be applied: {r:?}"),
+        }
+
+        // older than the latest by more than the window
+        match json_decoder_actor
+            .ask(observation("2023-01-11T23:15:57+0000", 3.0))
+            .await
+        {
+            Err(e) => assert!(e.reason.contains("/actors/window"), "{e}"),
+            r => panic!("out-of-window observation should be rejected: {r:?}"),
+        }
+
+        // newer observations still move the window forward
+        match json_decoder_actor
+            .ask(observation("2023-01-11T23:20:57+0000", 4.0))
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => assert_eq!(values.get(&1), Some(&4.0)),
+            r => panic!("newer observation should be applied: {r:?}"),
+        }
+        assert!(json_decoder_actor
+            .ask(observation("2023-01-11T23:18:57+0000", 5.0))
+            .await
+            .is_err());
+    });
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_no_observation_window_by_default() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let director = director::new("/", 8, None, None);
+        accept_late_reports(&director).await;
+        let json_decoder_actor = json_decoder::new(8, director);
+
+        json_decoder_actor
+            .ask(observation("2023-01-11T23:17:57+0000", 1.0))
+            .await
+            .unwrap();
+        match json_decoder_actor
+            .ask(observation("2020-01-11T23:17:57+0000", 2.0))
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => assert_eq!(values.get(&1), Some(&2.0)),
+            r => panic!("observation should be applied: {r:?}"),
+        }
+    });
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_observation_window_wider_than_time() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        // what main makes of an --observation-window too big for a duration
+        let options = DirectorOptions {
+            observation_window: Some(time::Duration::seconds(i64::MAX)),
+            ..Default::default()
+        };
+        let director = director::new_with_options("/", 8, None, None, options);
+        accept_late_reports(&director).await;
+        let json_decoder_actor = json_decoder::new(8, director);
+
+        json_decoder_actor
+            .ask(observation("2023-01-11T23:17:57+0000", 1.0))
+            .await
+            .unwrap();
+        match json_decoder_actor
+            .ask(observation("1970-01-01T00:00:00+0000", 2.0))
+            .await
+        {
+            Ok(Message::StateReport { values, .. }) => assert_eq!(values.get(&1), Some(&2.0)),
+            r => panic!("observation should be applied: {r:?}"),
+        }
+    });
+}
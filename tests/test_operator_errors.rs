@@ -0,0 +1,73 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+const GENE_MAPPING: &str = "{ \"path\": \"/actors/ranged\", \"gene_type\": \"GaugeAndAccum\"}";
+const OUT_OF_RANGE: &str = "{ \"path\": \"/actors/ranged/1\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"500\": 1.9} }";
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_ask_error_names_offending_index() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let director = director::new("/", 8, None, None);
+        let json_decoder_actor = json_decoder::new(8, director);
+
+        let cmd = Message::Content {
+            hint: MtHint::GeneMapping,
+            path: None,
+            text: String::from(GENE_MAPPING),
+        };
+        assert!(json_decoder_actor.ask(cmd).await.is_ok());
+
+        let cmd = Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(OUT_OF_RANGE),
+        };
+        match json_decoder_actor.ask(cmd).await {
+            Err(e) => assert!(e.reason.contains("unsupported idx: 500"), "{e}"),
+            r => panic!("out of range idx accepted: {r:?}"),
+        }
+    });
+}
+
+/// in cli mode updates are 'tell' and the error must reach the output actor
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_tell_error_is_forwarded_to_output() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let (sender, mut receiver) = mpsc::channel::<Envelope<f64>>(8);
+        let output = Handle::new(sender);
+        let director = director::new("/", 8, Some(output), None);
+        let json_decoder_actor = json_decoder::new(8, director);
+
+        let cmd = Message::Content {
+            hint: MtHint::GeneMapping,
+            path: None,
+            text: String::from(GENE_MAPPING),
+        };
+        assert!(json_decoder_actor.ask(cmd).await.is_ok());
+
+        let cmd = Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(OUT_OF_RANGE),
+        };
+        assert!(json_decoder_actor.tell(cmd).await.is_ok());
+
+        match receiver.recv().await.map(|envelope| envelope.message) {
+            Some(Message::Error { path, reason }) => {
+                assert_eq!(path, "/actors/ranged/1");
+                assert!(reason.contains("unsupported idx: 500"), "{reason}");
+            }
+            m => panic!("expected an error report: {m:?}"),
+        }
+    });
+}
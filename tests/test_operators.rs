@@ -1,7 +1,10 @@
 use approx::assert_ulps_eq;
 use navactor::actors::actor::State;
 use navactor::actors::operator::Accumulator;
+use navactor::actors::operator::CounterOperator;
 use navactor::actors::operator::Gauge;
+use navactor::actors::operator::MaxOperator;
+use navactor::actors::operator::MinOperator;
 use navactor::actors::operator::Operator;
 use time::OffsetDateTime;
 
@@ -23,7 +26,7 @@ fn test_accumulator() {
     assert_eq!(r.ok(), Some(6.9));
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_accumulator_with_dec() {
     let mut state: State<f64> = State::new();
@@ -32,3 +35,48 @@ fn test_accumulator_with_dec() {
     let r = Accumulator::apply(&state, 0, 4.11, OffsetDateTime::now_utc());
     assert_ulps_eq!(r.ok().unwrap(), &7.31, max_ulps = 4);
 }
+
+#[test]
+fn test_min() {
+    let mut state: State<f64> = State::new();
+    state.insert(0, 1.9);
+
+    let r = MinOperator::apply(&state, 0, 5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(1.9));
+    let r = MinOperator::apply(&state, 0, -5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(-5.0));
+    let r = MinOperator::apply(&state, 1, 5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(5.0));
+}
+
+#[test]
+fn test_max() {
+    let mut state: State<f64> = State::new();
+    state.insert(0, 1.9);
+
+    let r = MaxOperator::apply(&state, 0, 5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(5.0));
+    let r = MaxOperator::apply(&state, 0, -5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(1.9));
+    let r = MaxOperator::apply(&state, 1, -5.0, OffsetDateTime::now_utc());
+    assert_eq!(r.ok(), Some(-5.0));
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_counter() {
+    let mut state: State<f64> = State::new();
+    state.insert(1, 150.0);
+    state.insert(101, 150.0);
+    assert_eq!(
+        CounterOperator::apply(&state, 1, 101, 170.0).unwrap(),
+        (170.0, 170.0)
+    );
+    // a drop is a reset
+    assert_eq!(
+        CounterOperator::apply(&state, 1, 101, 10.0).unwrap(),
+        (160.0, 10.0)
+    );
+    state.remove(&101);
+    assert!(CounterOperator::apply(&state, 1, 101, 10.0).is_err());
+}
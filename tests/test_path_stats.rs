@@ -0,0 +1,108 @@
+mod common;
+
+use common::{remove_db, setup_director};
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::ObservationStats;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+fn at(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn observe(nv: &Handle, path: &str, secs: i64) {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: at(secs),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+#[tokio::test]
+async fn test_path_stats_of_a_resurrected_actor() {
+    let db_file_prefix = "/tmp/path_stats";
+    remove_db(db_file_prefix);
+    let nv = setup_director(db_file_prefix, "/stats");
+    // taken out of order - first and last are the observation times
+    for secs in [10, 0, 20] {
+        observe(&nv, "/stats/one", secs).await;
+    }
+    observe(&nv, "/stats/one/child", 30).await;
+
+    let restarted = setup_director(db_file_prefix, "/stats");
+    match restarted
+        .ask(Message::PathStats {
+            path: String::from("/stats/one"),
+        })
+        .await
+    {
+        Ok(Message::PathStatsReport {
+            path,
+            stats,
+            version,
+        }) => {
+            assert_eq!(path, "/stats/one");
+            assert_eq!(
+                stats,
+                ObservationStats {
+                    observations: 3,
+                    first: Some(at(0)),
+                    last: Some(at(20)),
+                }
+            );
+            assert_eq!(version, 3);
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    let none = restarted
+        .ask(Message::PathStats {
+            path: String::from("/stats/none"),
+        })
+        .await;
+    assert!(matches!(none, Ok(Message::NotFound { .. })), "{none:?}");
+}
+
+#[tokio::test]
+async fn test_path_stats_api() {
+    let db_file_prefix = "/tmp/path_stats_api";
+    remove_db(db_file_prefix);
+    let nv: Arc<Handle> = Arc::new(setup_director(db_file_prefix, "/stats"));
+    observe(&nv, "/stats/one", 0).await;
+    observe(&nv, "/stats/one", 5).await;
+    let config = HttpServerConfig::new(None, None, None, String::from("stats"));
+    let cli = TestClient::new(build_app(Arc::clone(&nv), &config, None, Some(true)));
+
+    let resp = cli.get("/api/actors/stats/one/stats").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({
+        "path": "/stats/one",
+        "observations": 2,
+        "first": at(0).to_string(),
+        "last": at(5).to_string(),
+        "version": 2,
+    }))
+    .await;
+
+    // the state of the actor is still at its own path
+    cli.get("/api/actors/stats/one")
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    let resp = cli.get("/api/actors/stats/none/stats").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+}
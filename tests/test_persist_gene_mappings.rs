@@ -31,7 +31,7 @@ async fn shutdown_actors(json_decoder_actor: Handle) {
     assert!(matches!(result_message, Ok(Message::EndOfStream {}),));
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
+#[allow(clippy::unwrap_used)]
 #[test]
 fn test_store_gene_mapping() {
     let namespace = String::from("/gene_actors");
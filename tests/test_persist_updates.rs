@@ -1,21 +1,103 @@
+use futures::future::join_all;
 use glob::glob;
 use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
 use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::Labels;
 use navactor::actors::message::Message;
 use navactor::actors::message::MtHint;
 use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
 use navactor::io::json_decoder;
 use navactor::io::stdout_actor;
 use std::fs;
+use time::OffsetDateTime;
 use tokio::runtime::Runtime;
 use tracing::debug;
 
-async fn setup_actors(db_file_prefix: String, namespace: String) -> Handle {
-    let output_actor = stdout_actor::new(8);
+/// the journal backends the suite runs against - `Postgres` only when
+/// `NV_TEST_POSTGRES_URL` names a database the tests may write to
+#[derive(Debug, Clone)]
+enum Backend {
+    Sqlite(String),
+    Postgres(String),
+}
+
+fn postgres() -> Option<Backend> {
+    let backend = std::env::var("NV_TEST_POSTGRES_URL")
+        .ok()
+        .map(Backend::Postgres);
+    if backend.is_none() {
+        println!("skipping postgres: NV_TEST_POSTGRES_URL is not set");
+    }
+    backend
+}
+
+fn store(backend: &Backend) -> Handle {
+    batched_store(backend, 1)
+}
 
+fn batched_store(backend: &Backend, batch_size: usize) -> Handle {
     // do not configure to tolerate collisions because the "allow dupes" setting uses envelope
     // time and that causes collisions due to sub-millisecond execution of the test.
-    let store_actor = store_actor_sqlite::new(8, db_file_prefix, false, false);
+    match backend {
+        Backend::Sqlite(db_file_prefix) => store_actor_sqlite::new_with_options(
+            8,
+            db_file_prefix.clone(),
+            false,
+            false,
+            StoreOptions {
+                batch_size,
+                ..Default::default()
+            },
+        ),
+        Backend::Postgres(db_url) => store_actor_sqlite::new_from_url_with_options(
+            8,
+            db_url.clone(),
+            "",
+            false,
+            false,
+            StoreOptions {
+                batch_size,
+                ..Default::default()
+            },
+        ),
+    }
+}
+
+/// start from an empty journal of the `paths`
+#[allow(clippy::unwrap_used)]
+async fn reset(backend: &Backend, paths: &[&str]) {
+    match backend {
+        Backend::Sqlite(db_file_prefix) => {
+            debug!("deleting db files before starting test...");
+            for entry in glob(&format!("{db_file_prefix}.db*")).unwrap() {
+                let path = entry.unwrap();
+                debug!("deleting {path:?} before starting store test");
+                fs::remove_file(path).unwrap();
+            }
+        }
+        Backend::Postgres(_) => {
+            let store_actor = store(backend);
+            for path in paths {
+                let path = String::from(*path);
+                store_actor
+                    .ask(Message::Delete {
+                        path,
+                        mapping: true,
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+}
+
+async fn setup_actors(backend: &Backend, namespace: String) -> Handle {
+    let output_actor = stdout_actor::new(8);
+
+    let store_actor = store(backend);
 
     let director_w_persist = director::new(&namespace, 8, Some(output_actor), Some(store_actor));
 
@@ -31,18 +113,21 @@ async fn shutdown_actors(json_decoder_actor: Handle) {
     assert!(matches!(result_message, Ok(Message::EndOfStream {}),));
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::unwrap_used))]
 #[test]
 fn test_write_and_read_jrnl() {
-    let namespace = String::from("/actors");
-    let db_file_prefix = format!("/tmp/{namespace}");
+    write_and_read_jrnl(&Backend::Sqlite(String::from("/tmp//actors")));
+}
 
-    debug!("deleting db files before starting test...");
-    for entry in glob(&format!("{db_file_prefix}.db*")).unwrap() {
-        let path = entry.unwrap();
-        debug!("deleting {path:?} before starting store test");
-        fs::remove_file(path).unwrap();
+#[test]
+fn test_write_and_read_jrnl_postgres() {
+    if let Some(backend) = postgres() {
+        write_and_read_jrnl(&backend);
     }
+}
+
+#[allow(clippy::unwrap_used)]
+fn write_and_read_jrnl(backend: &Backend) {
+    let namespace = String::from("/actors");
 
     let get_actor_one_file = "tests/data/get_actor_one_state.json";
     let get_actor_one_json = match fs::read_to_string(get_actor_one_file) {
@@ -73,8 +158,10 @@ fn test_write_and_read_jrnl() {
 
     let rt = Runtime::new().unwrap();
     rt.block_on(async {
+        reset(backend, &["/actors/one"]).await;
+
         // create actors and new db file
-        let json_decoder_actor = setup_actors(db_file_prefix.clone(), namespace.clone()).await;
+        let json_decoder_actor = setup_actors(backend, namespace.clone()).await;
 
         // insert 1 update to the new db
         let cmd = Message::Content {
@@ -89,7 +176,7 @@ fn test_write_and_read_jrnl() {
         shutdown_actors(json_decoder_actor).await;
 
         // create actors and use previously created db file
-        let json_decoder_actor = setup_actors(db_file_prefix, namespace).await;
+        let json_decoder_actor = setup_actors(backend, namespace).await;
 
         // query state of actor one from above updates
         let cmd = Message::Content {
@@ -104,6 +191,7 @@ fn test_write_and_read_jrnl() {
                     datetime: _,
                     path,
                     values,
+                    ..
                 } = r
                 {
                     assert_eq!(path, "/actors/one");
@@ -111,11 +199,11 @@ fn test_write_and_read_jrnl() {
                     assert_eq!(keys.len(), 1);
                     assert_eq!(values.get(&3).unwrap(), &3.0);
                 } else {
-                    assert!(false, "bad response from output actor: {r:?}");
+                    panic!("bad response from output actor: {r:?}");
                 }
             }
             Err(e) => {
-                assert!(false, "{e}");
+                panic!("{e}");
             }
         };
 
@@ -141,6 +229,7 @@ fn test_write_and_read_jrnl() {
                     datetime: _,
                     path,
                     values,
+                    ..
                 } = r
                 {
                     assert_eq!(path, "/actors/one");
@@ -148,11 +237,11 @@ fn test_write_and_read_jrnl() {
                     assert_eq!(keys.len(), 1);
                     assert_eq!(values.get(&3).unwrap(), &300.01);
                 } else {
-                    assert!(false, "bad response from output actor: {r:?}");
+                    panic!("bad response from output actor: {r:?}");
                 }
             }
             Err(e) => {
-                assert!(false, "{e}");
+                panic!("{e}");
             }
         };
 
@@ -160,3 +249,268 @@ fn test_write_and_read_jrnl() {
         shutdown_actors(json_decoder_actor).await;
     });
 }
+
+#[test]
+fn test_duplicate_detection() {
+    duplicate_detection(&Backend::Sqlite(String::from("/tmp/dupes")));
+}
+
+#[test]
+fn test_duplicate_detection_postgres() {
+    if let Some(backend) = postgres() {
+        duplicate_detection(&backend);
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+fn duplicate_detection(backend: &Backend) {
+    let observation = |datetime: &str, value: f64| {
+        Message::Content {
+        text: format!(
+            "{{ \"path\": \"/dupes/one\", \"datetime\": \"{datetime}\", \"values\": {{\"1\": {value}}} }}"
+        ),
+        path: None,
+        hint: MtHint::Update,
+    }
+    };
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        reset(backend, &["/dupes/one"]).await;
+        let json_decoder_actor = setup_actors(backend, String::from("/dupes")).await;
+        for (datetime, value) in [
+            ("2023-01-11T23:18:11+0000", 1.0),
+            ("2023-01-11T23:18:12+0000", 2.0),
+            // the journal already holds this path and time
+            ("2023-01-11T23:18:11+0000", 5.0),
+        ] {
+            json_decoder_actor
+                .tell(observation(datetime, value))
+                .await
+                .unwrap();
+        }
+        shutdown_actors(json_decoder_actor).await;
+
+        // the rejected duplicate is not replayed
+        let json_decoder_actor = setup_actors(backend, String::from("/dupes")).await;
+        let query = Message::Content {
+            text: String::from("{ \"path\": \"/dupes/one\" }"),
+            path: None,
+            hint: MtHint::Query,
+        };
+        match json_decoder_actor.ask(query).await {
+            Ok(Message::StateReport { values, .. }) => {
+                assert_eq!(values.get(&1).unwrap(), &2.0);
+            }
+            r => panic!("bad response from output actor: {r:?}"),
+        }
+        shutdown_actors(json_decoder_actor).await;
+    });
+}
+
+#[test]
+fn test_snapshot_replay() {
+    snapshot_replay(&Backend::Sqlite(String::from("/tmp/snapped")));
+}
+
+#[test]
+fn test_snapshot_replay_postgres() {
+    if let Some(backend) = postgres() {
+        snapshot_replay(&backend);
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+fn snapshot_replay(backend: &Backend) {
+    let query = || Message::Query {
+        path: String::from("/snapped/one"),
+        hint: MtHint::State,
+    };
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        reset(backend, &["/snapped/one"]).await;
+        let options = DirectorOptions {
+            snapshot_interval: Some(2),
+            ..Default::default()
+        };
+        let director =
+            director::new_with_options("/snapped", 8, None, Some(store(backend)), options);
+        // every observation reports a different index so a replay that skips
+        // or repeats one is seen
+        for n in 0..5 {
+            let mut values = State::new();
+            values.insert(n, f64::from(n));
+            director
+                .ask(Message::Observations {
+                    path: String::from("/snapped/one"),
+                    datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + i64::from(n))
+                        .unwrap(),
+                    values,
+                    labels: Labels::new(),
+                })
+                .await
+                .unwrap();
+        }
+        let Ok(Message::StateReport { values: live, .. }) = director.ask(query()).await else {
+            panic!("no live state");
+        };
+
+        let director = director::new("/snapped", 8, None, Some(store(backend)));
+        match director.ask(query()).await {
+            Ok(Message::StateReport { values, .. }) => {
+                assert_eq!(values.len(), 5);
+                assert_eq!(values, live);
+            }
+            r => panic!("bad response from director: {r:?}"),
+        }
+    });
+}
+
+#[test]
+fn test_prune() {
+    prune(&Backend::Sqlite(String::from("/tmp/pruned")));
+}
+
+#[test]
+fn test_prune_postgres() {
+    if let Some(backend) = postgres() {
+        prune(&backend);
+    }
+}
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+fn prune(backend: &Backend) {
+    let observation = |path: &str, n: i32| {
+        let mut values = State::new();
+        values.insert(n, f64::from(n));
+        Message::Observations {
+            path: String::from(path),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + i64::from(n)).unwrap(),
+            values,
+            labels: Labels::new(),
+        }
+    };
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        reset(backend, &["/pruned/one", "/pruned/two"]).await;
+        let store_actor = store(backend);
+        for n in 0..3 {
+            store_actor
+                .ask(observation("/pruned/one", n))
+                .await
+                .unwrap();
+        }
+        store_actor
+            .ask(observation("/pruned/two", 7))
+            .await
+            .unwrap();
+
+        // nothing arrived before an hour ago
+        let hour_ago = OffsetDateTime::now_utc() - time::Duration::hours(1);
+        let reply = store_actor
+            .ask(Message::Prune {
+                path: String::from("/pruned/"),
+                before: hour_ago,
+                keep_last: 1,
+            })
+            .await;
+        assert!(
+            matches!(reply, Ok(Message::Pruned { rows: 0 })),
+            "{reply:?}"
+        );
+
+        // everything arrived before a minute from now but the newest of each
+        // path is kept
+        let soon = OffsetDateTime::now_utc() + time::Duration::minutes(1);
+        let reply = store_actor
+            .ask(Message::Prune {
+                path: String::from("/pruned/"),
+                before: soon,
+                keep_last: 1,
+            })
+            .await;
+        assert!(
+            matches!(reply, Ok(Message::Pruned { rows: 2 })),
+            "{reply:?}"
+        );
+
+        let director = director::new("/pruned", 8, None, Some(store_actor));
+        let one = state_of(&director, "/pruned/one").await;
+        assert_eq!(one.len(), 1);
+        assert_eq!(one.get(&2), Some(&2.0));
+        assert_eq!(state_of(&director, "/pruned/two").await.get(&7), Some(&7.0));
+    });
+}
+
+#[test]
+fn test_batched_round_trip() {
+    batched_round_trip(&Backend::Sqlite(String::from("/tmp/batched")));
+}
+
+#[test]
+fn test_batched_round_trip_postgres() {
+    if let Some(backend) = postgres() {
+        batched_round_trip(&backend);
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+fn batched_round_trip(backend: &Backend) {
+    let observation = |n: i32| {
+        let mut values = State::new();
+        values.insert(n, f64::from(n));
+        Message::Observations {
+            path: String::from("/batched/one"),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + i64::from(n)).unwrap(),
+            values,
+            labels: Labels::new(),
+        }
+    };
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        reset(backend, &["/batched/one"]).await;
+        let store_actor = batched_store(backend, 100);
+        // every observation is in flight at once so the batches fill up and
+        // each one is confirmed only after its batch commits
+        let replies = join_all((0..10_000).map(|n| store_actor.ask(observation(n)))).await;
+        for reply in replies {
+            assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+        }
+
+        // a duplicate in a batch is reported without failing the rest of it
+        let replies = join_all([
+            store_actor.ask(observation(0)),
+            store_actor.ask(observation(10_000)),
+        ])
+        .await;
+        assert!(
+            matches!(replies[0], Ok(Message::ConstraintViolation)),
+            "{:?}",
+            replies[0]
+        );
+        assert!(
+            matches!(replies[1], Ok(Message::Persisted)),
+            "{:?}",
+            replies[1]
+        );
+
+        let director = director::new("/batched", 8, None, Some(store_actor));
+        let values = state_of(&director, "/batched/one").await;
+        assert_eq!(values.len(), 10_001);
+        assert!((0..=10_000).all(|n| values.get(&n) == Some(&f64::from(n))));
+    });
+}
@@ -0,0 +1,17 @@
+use navactor::utils::nvtime::parse_duration;
+use time::Duration;
+
+#[test]
+fn test_parse_duration() {
+    let parsed = |text: &str| parse_duration(text).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(parsed("90s"), Duration::seconds(90));
+    assert_eq!(parsed("15m"), Duration::minutes(15));
+    assert_eq!(parsed("12h"), Duration::hours(12));
+    assert_eq!(parsed("30d"), Duration::days(30));
+    assert_eq!(parsed("2w"), Duration::weeks(2));
+
+    assert!(parse_duration("30").is_err());
+    assert!(parse_duration("d").is_err());
+    assert!(parse_duration("30y").is_err());
+    assert!(parse_duration("1.5h").is_err());
+}
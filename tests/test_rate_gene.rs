@@ -0,0 +1,139 @@
+mod common;
+
+use approx::assert_ulps_eq;
+use common::{remove_db, setup_director};
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::genes::rate_gene::RateGene;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use time::macros::datetime;
+use time::OffsetDateTime;
+
+fn reading(path: &str, datetime: OffsetDateTime, value: f64) -> Message<f64> {
+    let mut values: State<f64> = State::new();
+    values.insert(1, value);
+    Message::Observations {
+        path: String::from(path),
+        datetime,
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn observe(director: &Handle, datetime: OffsetDateTime, value: f64) -> State<f64> {
+    match director.ask(reading("/flows/one", datetime, value)).await {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_rate_gene() {
+    let g1 = RateGene {
+        ..Default::default()
+    };
+
+    let state = g1
+        .apply_operators(
+            State::new(),
+            reading("/", datetime!(2024-01-15 09:00:00 UTC), 100.0),
+        )
+        .unwrap();
+    assert!(state.is_empty());
+
+    let state = g1
+        .apply_operators(
+            state,
+            reading("/", datetime!(2024-01-15 09:00:10 UTC), 150.0),
+        )
+        .unwrap();
+    assert_ulps_eq!(state.get(&1).unwrap(), &5.0, max_ulps = 4);
+
+    // no time has passed since the previous sample
+    let r = g1.apply_operators(
+        state,
+        reading("/", datetime!(2024-01-15 09:00:10 UTC), 160.0),
+    );
+    assert!(r.is_err());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_rate_survives_restart() {
+    let db_file_prefix = "/tmp/flows";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/flows");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/flows"),
+            gene_type: GeneType::Rate,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap();
+
+    observe(&director, datetime!(2024-01-15 09:00:00 UTC), 100.0).await;
+    let state = observe(&director, datetime!(2024-01-15 09:00:10 UTC), 150.0).await;
+    assert_ulps_eq!(state.get(&1).unwrap(), &5.0, max_ulps = 4);
+
+    // the previous sample is replayed from the journal
+    let restarted = setup_director(db_file_prefix, "/flows");
+    let state = observe(&restarted, datetime!(2024-01-15 09:00:20 UTC), 170.0).await;
+    assert_ulps_eq!(state.get(&1).unwrap(), &2.0, max_ulps = 4);
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_rate_replays_past_a_late_report() {
+    let db_file_prefix = "/tmp/lateflows";
+    remove_db(db_file_prefix);
+
+    let director = setup_director(db_file_prefix, "/flows");
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/flows"),
+            gene_type: GeneType::Rate,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap();
+
+    observe(&director, datetime!(2024-01-15 09:00:00 UTC), 100.0).await;
+    observe(&director, datetime!(2024-01-15 09:00:10 UTC), 150.0).await;
+    let late = director
+        .ask(reading(
+            "/flows/one",
+            datetime!(2024-01-15 09:00:05 UTC),
+            120.0,
+        ))
+        .await;
+    assert!(!matches!(late, Ok(Message::StateReport { .. })), "{late:?}");
+    observe(&director, datetime!(2024-01-15 09:00:20 UTC), 170.0).await;
+
+    // a capped replay skips the late report too, and the observations
+    // journaled after it are still replayed
+    let store_actor = store_actor_sqlite::try_new_with_options(
+        8,
+        String::from(db_file_prefix),
+        false,
+        false,
+        StoreOptions {
+            max_replay_events: Some(100),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let restarted = director::new("/flows", 8, None, Some(store_actor));
+    let state = observe(&restarted, datetime!(2024-01-15 09:00:30 UTC), 200.0).await;
+    assert_ulps_eq!(state.get(&1).unwrap(), &3.0, max_ulps = 4);
+}
@@ -0,0 +1,121 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+const RATE: f64 = 5.0;
+
+fn limited_director(namespace: &str) -> Handle {
+    let options = DirectorOptions {
+        max_rate_per_path: Some(RATE),
+        ..Default::default()
+    };
+    director::new_with_options(namespace, 8, None, None, options)
+}
+
+async fn observe(nv: &Handle, path: &str, n: i64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"))
+}
+
+/// the number of a burst of 20 observations of `path` that were applied -
+/// the rest must be rate limited
+async fn burst(nv: &Handle, path: &str) -> usize {
+    let mut applied = 0;
+    for n in 0..20 {
+        match observe(nv, path, n).await {
+            Message::StateReport { .. } => applied += 1,
+            Message::RateLimited { path: limited } => assert_eq!(limited, path),
+            r => panic!("bad response to observation: {r:?}"),
+        }
+    }
+    applied
+}
+
+#[tokio::test]
+async fn test_a_burst_is_limited_per_path() {
+    let nv = limited_director("/limited");
+    // a second's worth, and one more if the burst took long enough to earn it
+    let applied = burst(&nv, "/limited/one").await;
+    assert!((5..=6).contains(&applied), "{applied} applied");
+
+    // another path has its own bucket
+    let applied = burst(&nv, "/limited/two").await;
+    assert!((5..=6).contains(&applied), "{applied} applied");
+
+    // the bucket refills with time
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert!(matches!(
+        observe(&nv, "/limited/one", 100).await,
+        Message::StateReport { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_rate_limited_posts() {
+    let nv: Arc<Handle> = Arc::new(limited_director("/limitedapi"));
+    let config = HttpServerConfig::new(None, None, None, String::from("limitedapi"));
+    let cli = TestClient::new(build_app(Arc::clone(&nv), &config, None, Some(true)));
+
+    let mut statuses = vec![];
+    for n in 0..10 {
+        let resp = cli
+            .post("/api/actors/limitedapi/one")
+            .body_json(&json!({
+                "path": "/limitedapi/one",
+                "datetime": format!("2023-01-11T23:17:{:02}Z", n),
+                "values": {"1": 1.0},
+            }))
+            .send()
+            .await;
+        statuses.push(resp.0.status());
+    }
+    assert_eq!(statuses[0], StatusCode::OK);
+    assert_eq!(statuses[9], StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_limited_paths_survive_the_sweep_of_full_buckets() {
+    // a bucket of one observation that takes two seconds to refill
+    let options = DirectorOptions {
+        max_rate_per_path: Some(0.5),
+        ..Default::default()
+    };
+    let nv = director::new_with_options("/swept", 8, None, None, options);
+    assert!(matches!(
+        observe(&nv, "/swept/limited", 0).await,
+        Message::StateReport { .. }
+    ));
+
+    // enough paths to have the director sweep its buckets - only the full
+    // ones may go
+    for n in 0..1100 {
+        observe(&nv, &format!("/swept/{n}"), 0).await;
+    }
+    assert!(matches!(
+        observe(&nv, "/swept/limited", 1).await,
+        Message::RateLimited { .. }
+    ));
+    assert!(matches!(
+        observe(&nv, "/swept/0", 1).await,
+        Message::RateLimited { .. }
+    ));
+}
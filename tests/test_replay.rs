@@ -0,0 +1,91 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+fn datetime(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn observe(nv: &Handle, path: &str, secs: i64, value: f64) {
+    let mut values = State::new();
+    values.insert(1, value);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: datetime(secs),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+async fn query(nv: &Handle, path: &str, hint: MtHint) -> Message<f64> {
+    nv.ask(Message::Query {
+        path: String::from(path),
+        hint,
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[tokio::test]
+async fn test_replay_reports_every_step() {
+    let db_file_prefix = "/tmp/replay";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = director::new("/replay", 8, None, Some(store_actor));
+    nv.ask(Message::GeneMapping {
+        path: String::from("/replay"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    for (secs, value) in [(0, 1.0), (1, 2.0), (2, 3.0)] {
+        observe(&nv, "/replay/one", secs, value).await;
+    }
+
+    match query(&nv, "/replay/one", MtHint::Replay).await {
+        Message::StateReports { path, reports } => {
+            assert_eq!(path, "/replay/one");
+            let steps: Vec<(OffsetDateTime, Option<f64>)> = reports
+                .into_iter()
+                .map(|report| match report {
+                    Message::StateReport {
+                        datetime, values, ..
+                    } => (datetime, values.get(&1).copied()),
+                    m => panic!("not a state report: {m}"),
+                })
+                .collect();
+            assert_eq!(
+                steps,
+                vec![
+                    (datetime(0), Some(1.0)),
+                    (datetime(1), Some(3.0)),
+                    (datetime(2), Some(6.0)),
+                ]
+            );
+        }
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    // the live actor is left alone
+    match query(&nv, "/replay/one", MtHint::State).await {
+        Message::StateReport { values, .. } => assert_eq!(values, State::from([(1, 6.0)])),
+        r => panic!("bad response from director: {r:?}"),
+    }
+    assert!(matches!(
+        query(&nv, "/replay/none", MtHint::Replay).await,
+        Message::NotFound { .. }
+    ));
+}
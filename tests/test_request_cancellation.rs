@@ -0,0 +1,234 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use sqlx::Connection;
+use sqlx::SqliteConnection;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const JOURNAL_ROWS: usize = 200;
+
+fn observation(path: &str, n: usize) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(
+            1_673_479_077 + i64::try_from(n).unwrap_or(0),
+        )
+        .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+/// a stand-in for the store actor that replays a long journal slowly and
+/// counts the rows it streams - it stops when the load is cancelled
+fn slow_store(streamed: Arc<AtomicUsize>) -> Handle {
+    let (sender, mut receiver) = mpsc::channel::<Envelope<f64>>(8);
+    tokio::spawn(async move {
+        while let Some(envelope) = receiver.recv().await {
+            match envelope.message {
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::Update,
+                } => {
+                    let cancel = envelope.cancel.unwrap_or_default();
+                    if let Some(stream_to) = envelope.stream_to {
+                        for n in 0..JOURNAL_ROWS {
+                            if cancel.is_cancelled() {
+                                break;
+                            }
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            let _ = stream_to.send(observation(&path, n)).await;
+                            streamed.fetch_add(1, Ordering::SeqCst);
+                        }
+                        if !cancel.is_cancelled() {
+                            let _ = stream_to.send(Message::EndOfStream {}).await;
+                        }
+                    }
+                }
+                Message::LoadCmd { .. } => {
+                    if let Some(stream_to) = envelope.stream_to {
+                        let _ = stream_to.send(Message::EndOfStream {}).await;
+                    }
+                }
+                _ => {
+                    if let Some(respond_to) = envelope.respond_to {
+                        let _ = respond_to.send(Ok(Message::Persisted));
+                    }
+                }
+            }
+        }
+    });
+    Handle::new(sender)
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_timed_out_query_cancels_the_journal_load() {
+    let streamed = Arc::new(AtomicUsize::new(0));
+    let store_actor = slow_store(streamed.clone());
+    let director = director::new("/actors", 8, None, Some(store_actor));
+
+    let reply = director
+        .ask_timeout(
+            Message::Query {
+                path: String::from("/actors/slow"),
+                hint: MtHint::State,
+            },
+            Duration::from_millis(50),
+        )
+        .await;
+    assert!(reply.is_err());
+
+    // give the director time to see the requester is gone and the store to stop
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let stopped_at = streamed.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(streamed.load(Ordering::SeqCst), stopped_at);
+    assert!(stopped_at < JOURNAL_ROWS / 2, "streamed {stopped_at} rows");
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_cancelled_load_stops_streaming_the_journal() {
+    let db_file_prefix = "/tmp/cancel_load";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    for n in 0..JOURNAL_ROWS {
+        let reply = store_actor.ask(observation("/cancel/one", n)).await;
+        assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+    }
+
+    // a one-row stream that is read slowly keeps the load loop waiting on every row
+    let (stream_to, mut stream_from) = mpsc::channel(1);
+    let cancel = CancellationToken::new();
+    store_actor
+        .send(Envelope {
+            message: Message::LoadCmd {
+                path: String::from("/cancel/one"),
+                hint: MtHint::Update,
+            },
+            stream_to: Some(stream_to),
+            cancel: Some(cancel.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let mut received = 0;
+    while received < 5 {
+        if let Some(Message::Observations { .. }) = stream_from.recv().await {
+            received += 1;
+        }
+    }
+    cancel.cancel();
+
+    let mut end_of_stream = false;
+    while let Some(message) = stream_from.recv().await {
+        match message {
+            Message::EndOfStream {} => end_of_stream = true,
+            _ => received += 1,
+        }
+    }
+    assert!(!end_of_stream);
+    assert!(received < 10, "received {received} rows");
+
+    // the store is free to serve the next request
+    let reply = store_actor
+        .ask(observation("/cancel/one", JOURNAL_ROWS))
+        .await;
+    assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_cancelled_load_stops_reading_the_db() {
+    let db_file_prefix = "/tmp/cancel_read";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    for n in 0..JOURNAL_ROWS {
+        let reply = store_actor.ask(observation("/cancel/read", n)).await;
+        assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+    }
+
+    // another connection holds the db so the read of the load waits on it
+    // for as long as the busy timeout
+    let mut lock = SqliteConnection::connect(&format!("sqlite://{db_file_prefix}.db"))
+        .await
+        .unwrap();
+    sqlx::query("BEGIN EXCLUSIVE")
+        .execute(&mut lock)
+        .await
+        .unwrap();
+
+    let (stream_to, mut stream_from) = mpsc::channel(8);
+    let cancel = CancellationToken::new();
+    store_actor
+        .send(Envelope {
+            message: Message::LoadCmd {
+                path: String::from("/cancel/read"),
+                hint: MtHint::Update,
+            },
+            stream_to: Some(stream_to),
+            cancel: Some(cancel.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    cancel.cancel();
+
+    // the stream closes without a row long before the read would time out
+    let closed = tokio::time::timeout(Duration::from_secs(1), stream_from.recv()).await;
+    assert!(matches!(closed, Ok(None)), "{closed:?}");
+
+    sqlx::query("COMMIT").execute(&mut lock).await.unwrap();
+    lock.close().await.unwrap();
+    let reply = store_actor
+        .ask(observation("/cancel/read", JOURNAL_ROWS))
+        .await;
+    assert!(matches!(reply, Ok(Message::Persisted)), "{reply:?}");
+}
+
+#[tokio::test]
+async fn test_stalled_actors_time_out_api_requests() {
+    // a director that never reads its mailbox
+    let (sender, _receiver) = mpsc::channel::<Envelope<f64>>(1);
+    let nv = Arc::new(Handle::new(sender));
+    let config = HttpServerConfig::new(None, None, None, String::from("stalled"))
+        .with_request_timeout(Duration::from_millis(50));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    for resp in [
+        cli.get("/api/actors/stalled/one").send().await,
+        cli.get("/api/genes/stalled/one").send().await,
+        cli.get("/api/genes/stalled/").send().await,
+        cli.delete("/api/genes/stalled/one").send().await,
+    ] {
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        let text = resp.0.into_body().into_string().await.unwrap_or_default();
+        assert!(text.contains("no reply within 50ms"), "{text}");
+    }
+    cli.get("/ready")
+        .send()
+        .await
+        .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+}
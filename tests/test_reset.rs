@@ -0,0 +1,201 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+fn setup_director(namespace: &str, db_path: &str) -> Handle {
+    let store_actor = store_actor_sqlite::new_from_url(
+        8,
+        String::from(db_path),
+        namespace.trim_start_matches('/'),
+        false,
+        false,
+    );
+    director::new(namespace, 8, None, Some(store_actor))
+}
+
+async fn accumulate(nv: &Handle, path: &str) {
+    nv.ask(Message::GeneMapping {
+        path: String::from(path),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not map {path}: {e}"));
+}
+
+async fn observe(nv: &Handle, path: &str, n: i64, value: f64) -> Option<f64> {
+    let mut values = State::new();
+    values.insert(1, value);
+    match nv
+        .ask(Message::Observations {
+            path: String::from(path),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values.get(&1).copied(),
+        r => panic!("bad response to observation: {r:?}"),
+    }
+}
+
+async fn total(nv: &Handle, path: &str) -> Option<f64> {
+    match nv
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values.get(&1).copied(),
+        r => panic!("bad response to query: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_totals_before_and_after_a_reset_are_independent() {
+    let db_file_prefix = "/tmp/reset";
+    remove_db(db_file_prefix);
+    let db_path = format!("{db_file_prefix}.db");
+    let nv = setup_director("/billing", &db_path);
+    accumulate(&nv, "/billing").await;
+
+    for (n, value) in [(0, 1.0), (1, 2.0), (2, 3.0)] {
+        observe(&nv, "/billing/one", n, value).await;
+    }
+    assert_eq!(total(&nv, "/billing/one").await, Some(6.0));
+
+    match nv
+        .ask(Message::Reset {
+            path: String::from("/billing/one"),
+        })
+        .await
+    {
+        Ok(Message::StateReport {
+            values, version, ..
+        }) => {
+            assert!(values.is_empty());
+            assert_eq!(version, 3);
+        }
+        r => panic!("bad response to reset: {r:?}"),
+    }
+    observe(&nv, "/billing/one", 3, 4.0).await;
+    assert_eq!(observe(&nv, "/billing/one", 4, 5.0).await, Some(9.0));
+
+    let none = nv
+        .ask(Message::Reset {
+            path: String::from("/billing/none"),
+        })
+        .await;
+    assert!(matches!(none, Ok(Message::NotFound { .. })), "{none:?}");
+
+    // the journal keeps every observation and a resurrection resets where the
+    // reset was journaled
+    let restarted = setup_director("/billing", &db_path);
+    assert_eq!(total(&restarted, "/billing/one").await, Some(9.0));
+    match restarted
+        .ask(Message::PathStats {
+            path: String::from("/billing/one"),
+        })
+        .await
+    {
+        Ok(Message::PathStatsReport { stats, .. }) => assert_eq!(stats.observations, 5),
+        r => panic!("bad response to stats: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_reset_api() {
+    let db_file_prefix = "/tmp/reset_api";
+    remove_db(db_file_prefix);
+    let nv: Arc<Handle> = Arc::new(setup_director("/resetapi", &format!("{db_file_prefix}.db")));
+    accumulate(&nv, "/resetapi").await;
+    observe(&nv, "/resetapi/one", 0, 2.0).await;
+    observe(&nv, "/resetapi/one", 1, 2.0).await;
+    let config = HttpServerConfig::new(None, None, None, String::from("resetapi"));
+    let cli = TestClient::new(build_app(Arc::clone(&nv), &config, None, Some(true)));
+
+    let resp = cli.post("/api/actors/resetapi/one/reset").send().await;
+    resp.assert_status_is_ok();
+    let report: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(report["path"], "/resetapi/one");
+    assert_eq!(report["values"], serde_json::json!({}));
+    assert_eq!(report["version"], 2);
+
+    assert_eq!(observe(&nv, "/resetapi/one", 2, 3.0).await, Some(3.0));
+
+    let resp = cli.post("/api/actors/resetapi/none/reset").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+}
+
+/// run `nv` with the args and input and return its exit code
+fn nv(args: &[&str], input: &str) -> Option<i32> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(args)
+        .env("RUST_LOG", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    child
+        .wait()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"))
+        .code()
+}
+
+#[tokio::test]
+async fn test_reset_command() {
+    let db_file_prefix = "/tmp/reset_cli";
+    remove_db(db_file_prefix);
+    let db_path = format!("{db_file_prefix}.db");
+    let reading = |n: u8| {
+        format!("{{ \"path\": \"/resetcli/one\", \"datetime\": \"2023-01-11T23:17:5{n}+0000\", \"values\": {{\"1\": 1.5}} }}\n")
+    };
+    let update = ["update", "--namespace", "resetcli", "--db-path", &db_path];
+
+    let mapping = "{ \"path\": \"/resetcli\", \"gene_type\": \"Accum\" }\n";
+    assert_eq!(
+        nv(&update, &format!("{mapping}{}{}", reading(0), reading(1))),
+        Some(0)
+    );
+    assert_eq!(
+        nv(&["reset", "/resetcli/one", "--db-path", &db_path], ""),
+        Some(0)
+    );
+    assert_eq!(
+        nv(&update, &format!("{}{}", reading(2), reading(3))),
+        Some(0)
+    );
+    assert_ne!(
+        nv(&["reset", "/resetcli/none", "--db-path", &db_path], ""),
+        Some(0)
+    );
+
+    let restarted = setup_director("/resetcli", &db_path);
+    assert_eq!(total(&restarted, "/resetcli/one").await, Some(3.0));
+}
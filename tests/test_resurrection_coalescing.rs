@@ -0,0 +1,150 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::Envelope;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+/// a stand-in for the store actor that counts journal loads and replays a
+/// single observation slowly enough for a burst of queries to queue up
+fn counting_store(loads: Arc<AtomicUsize>) -> Handle {
+    let (sender, mut receiver) = mpsc::channel::<Envelope<f64>>(8);
+    tokio::spawn(async move {
+        while let Some(envelope) = receiver.recv().await {
+            match envelope.message {
+                Message::LoadCmd {
+                    path,
+                    hint: MtHint::Update,
+                } => {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    if let Some(stream_to) = envelope.stream_to {
+                        let mut values = State::new();
+                        values.insert(1, 1.5);
+                        let _ = stream_to
+                            .send(Message::Observations {
+                                path,
+                                datetime: OffsetDateTime::now_utc(),
+                                values,
+                                labels: Labels::new(),
+                            })
+                            .await;
+                        let _ = stream_to.send(Message::EndOfStream {}).await;
+                    }
+                }
+                Message::LoadCmd { .. } => {
+                    if let Some(stream_to) = envelope.stream_to {
+                        let _ = stream_to.send(Message::EndOfStream {}).await;
+                    }
+                }
+                _ => {
+                    if let Some(respond_to) = envelope.respond_to {
+                        let _ = respond_to.send(Ok(Message::Persisted));
+                    }
+                }
+            }
+        }
+    });
+    Handle::new(sender)
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_burst_of_cold_queries_shares_one_resurrection() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let store_actor = counting_store(loads.clone());
+        let director = Arc::new(director::new("/actors", 8, None, Some(store_actor)));
+
+        let mut queries = Vec::new();
+        for _ in 0..20 {
+            let director = director.clone();
+            queries.push(tokio::spawn(async move {
+                director
+                    .ask(Message::Query {
+                        path: String::from("/actors/cold"),
+                        hint: MtHint::State,
+                    })
+                    .await
+            }));
+        }
+
+        for query in queries {
+            match query.await.unwrap() {
+                Ok(Message::StateReport { values, .. }) => {
+                    assert_eq!(values.get(&1), Some(&1.5));
+                }
+                r => panic!("bad response from director: {r:?}"),
+            }
+        }
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    });
+}
+
+fn cold_query() -> Message<f64> {
+    Message::Query {
+        path: String::from("/actors/cold"),
+        hint: MtHint::State,
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_grace_keeps_the_resurrection_for_the_queries_that_share_it() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let store_actor = counting_store(loads.clone());
+        let options = DirectorOptions {
+            resurrection_grace: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let director = Arc::new(director::new_with_options(
+            "/actors",
+            8,
+            None,
+            Some(store_actor),
+            options,
+        ));
+
+        // the first query gives up before the resurrection is done
+        let impatient = {
+            let director = director.clone();
+            tokio::spawn(async move {
+                director
+                    .ask_timeout(cold_query(), Duration::from_millis(30))
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let mut queries = Vec::new();
+        for _ in 0..5 {
+            let director = director.clone();
+            queries.push(tokio::spawn(
+                async move { director.ask(cold_query()).await },
+            ));
+        }
+
+        assert!(impatient.await.unwrap().is_err());
+        // but the queries that arrived within the grace still share it
+        for query in queries {
+            match query.await.unwrap() {
+                Ok(Message::StateReport { values, .. }) => {
+                    assert_eq!(values.get(&1), Some(&1.5));
+                }
+                r => panic!("bad response from director: {r:?}"),
+            }
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    });
+}
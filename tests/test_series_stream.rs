@@ -0,0 +1,168 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+const OBSERVATIONS: i64 = 300;
+
+/// a director whose `/streamed/one` has `OBSERVATIONS` observations, every
+/// other one labeled `parity=even`
+async fn observed_director(db_file_prefix: &str) -> Handle {
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/streamed", 8, None, Some(store_actor));
+    for n in 0..OBSERVATIONS {
+        let mut labels = Labels::new();
+        if n % 2 == 0 {
+            labels.insert(String::from("parity"), String::from("even"));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        director
+            .ask(Message::Observations {
+                path: String::from("/streamed/one"),
+                datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                    .unwrap_or_else(|e| panic!("{e}")),
+                values: State::from([(1, n as f64)]),
+                labels,
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    director
+}
+
+fn lines_of(body: &str) -> Vec<serde_json::Value> {
+    body.lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{e}: {line}")))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_director_streams_the_series() {
+    let director = observed_director("/tmp/series_stream").await;
+    let (stream_to, mut rows) = mpsc::channel(4);
+    director
+        .tell(Message::SeriesStream {
+            path: String::from("/streamed/one"),
+            labels: Labels::new(),
+            stream_to,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let mut streamed = 0;
+    loop {
+        match rows.recv().await {
+            Some(Message::Observations { values, .. }) => {
+                #[allow(clippy::cast_precision_loss)]
+                let expected = streamed as f64;
+                assert_eq!(values, State::from([(1, expected)]));
+                streamed += 1;
+            }
+            Some(Message::EndOfStream {}) => break,
+            m => panic!("bad series stream: {m:?}"),
+        }
+    }
+    assert_eq!(streamed, OBSERVATIONS);
+
+    // without a journal the stream is only the reason
+    let director = director::new("/streamedmem", 8, None, None);
+    let (stream_to, mut rows) = mpsc::channel(4);
+    director
+        .tell(Message::SeriesStream {
+            path: String::from("/streamedmem/one"),
+            labels: Labels::new(),
+            stream_to,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert!(matches!(rows.recv().await, Some(Message::Error { .. })));
+}
+
+#[tokio::test]
+async fn test_series_endpoint_streams_ndjson() {
+    let nv: Arc<Handle> = Arc::new(observed_director("/tmp/series_stream_api").await);
+    let config = HttpServerConfig::new(None, None, None, String::from("streamed"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    // the default is still one array
+    let resp = cli.get("/api/series/streamed/one").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/json; charset=utf-8");
+    let array: serde_json::Value = resp.json().await.value().deserialize();
+    let array = array
+        .as_array()
+        .unwrap_or_else(|| panic!("not an array"))
+        .clone();
+
+    let resp = cli
+        .get("/api/series/streamed/one")
+        .query("stream", &true)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/x-ndjson");
+    let lines = lines_of(&resp.0.into_body().into_string().await.unwrap_or_default());
+    assert_eq!(
+        lines.len(),
+        usize::try_from(OBSERVATIONS).unwrap_or_default()
+    );
+    assert_eq!(lines, array);
+    assert_eq!(lines[7]["values"]["1"], json!(7.0));
+
+    // the accept header asks for the same lines and the label filter applies
+    let resp = cli
+        .get("/api/series/streamed/one")
+        .header("Accept", "application/x-ndjson")
+        .query("label.parity", &"even")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let lines = lines_of(&resp.0.into_body().into_string().await.unwrap_or_default());
+    assert_eq!(
+        lines.len(),
+        usize::try_from(OBSERVATIONS / 2).unwrap_or_default()
+    );
+    assert!(lines.iter().all(|o| o["labels"]["parity"] == json!("even")));
+
+    // a path without observations is an empty stream
+    let resp = cli
+        .get("/api/series/streamed/none")
+        .query("stream", &true)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_text("").await;
+}
+
+#[tokio::test]
+async fn test_series_stream_needs_a_journal() {
+    let nv: Arc<Handle> = Arc::new(director::new("/streamedapi", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("streamedapi"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli
+        .get("/api/series/streamedapi/one")
+        .query("stream", &true)
+        .send()
+        .await;
+    resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("code")
+        .assert_string("server_error");
+}
@@ -0,0 +1,68 @@
+use serde_json::json;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const PORT: u16 = 18_874;
+
+#[tokio::test]
+async fn test_serve_memory_only() {
+    // the db file would be named after the namespace in the working directory
+    let dir = std::env::temp_dir().join("navactor_serve_memory_only");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("{e}"));
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .current_dir(&dir)
+        .args(["serve", "--memory-only", "--disable-ui"])
+        .args(["--namespace", "ephemeral", "--port", &PORT.to_string()])
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{PORT}");
+    let mut ready = false;
+    for _ in 0..100 {
+        if client.get(format!("{base}/health")).send().await.is_ok() {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let posted = client
+        .post(format!("{base}/api/actors/ephemeral/one"))
+        .json(&json!({
+            "path": "/ephemeral/one",
+            "datetime": "2023-01-11T23:17:57Z",
+            "values": {"1": 1.5},
+        }))
+        .send()
+        .await
+        .map(|resp| resp.status());
+    let state: Option<serde_json::Value> = match client
+        .get(format!("{base}/api/actors/ephemeral/one"))
+        .send()
+        .await
+    {
+        Ok(resp) => resp.json().await.ok(),
+        Err(_) => None,
+    };
+    server.kill().unwrap_or_else(|e| panic!("{e}"));
+    server.wait().unwrap_or_else(|e| panic!("{e}"));
+
+    assert!(ready, "server never listened on {PORT}");
+    assert!(
+        posted.as_ref().is_ok_and(|status| status.is_success()),
+        "{posted:?}"
+    );
+    let state = state.unwrap_or_else(|| panic!("no state of /ephemeral/one"));
+    assert_eq!(state["values"]["1"], json!(1.5));
+    let files: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("{e}"))
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name())
+        .collect();
+    assert!(files.is_empty(), "files were created: {files:?}");
+}
@@ -0,0 +1,136 @@
+use navactor::actors::director;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use navactor::io::net::auth::AuthError;
+use navactor::io::net::auth::Authenticator;
+use navactor::io::net::auth::Principal;
+use navactor::io::net::auth::StaticTokenAuthenticator;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::Request;
+use serde_json::json;
+use std::sync::Arc;
+
+/// lets in any caller that names a tenant
+struct TenantHeaderAuthenticator {}
+
+impl Authenticator for TenantHeaderAuthenticator {
+    fn authenticate(&self, req: &Request) -> Result<Principal, AuthError> {
+        req.header("X-Tenant")
+            .map(|name| Principal {
+                name: name.to_string(),
+            })
+            .ok_or_else(|| AuthError {
+                reason: "no tenant".to_string(),
+            })
+    }
+}
+
+fn server_config(authenticator: Arc<dyn Authenticator>) -> HttpServerConfig {
+    HttpServerConfig::new(None, None, None, String::from("actors"))
+        .with_authenticator(authenticator)
+}
+
+#[tokio::test]
+async fn test_custom_authenticator_allows_and_denies() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let app = build_app(
+        nv,
+        &server_config(Arc::new(TenantHeaderAuthenticator {})),
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let resp = cli.get("/api/actors/actors/one").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    // authenticated but there is no state for the actor yet
+    let resp = cli
+        .get("/api/actors/actors/one")
+        .header("X-Tenant", "blue")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_static_token_authenticator() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let app = build_app(
+        nv,
+        &server_config(Arc::new(StaticTokenAuthenticator::new(String::from(
+            "sekret",
+        )))),
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+
+    let resp = cli
+        .get("/api/actors/actors/one")
+        .header("X-API-Key", "wrong")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    // the token only counts when it is presented with the bearer scheme
+    for value in ["sekret", "Basic sekret", "Token sekret"] {
+        let resp = cli
+            .get("/api/actors/actors/one")
+            .header("Authorization", value)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    let resp = cli
+        .get("/api/actors/actors/one")
+        .header("Authorization", "Bearer sekret")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_api_key_protects_posts_but_not_health() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let app = build_app(
+        nv,
+        &server_config(Arc::new(StaticTokenAuthenticator::new(String::from(
+            "sekret",
+        )))),
+        None,
+        Some(true),
+    );
+    let cli = TestClient::new(app);
+    let observation = json!({
+        "path": "/actors/one",
+        "datetime": "2023-01-11T23:17:57Z",
+        "values": {"1": 1.5},
+    });
+
+    cli.get("/health").send().await.assert_status_is_ok();
+
+    cli.post("/api/actors/actors/one")
+        .body_json(&observation)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    cli.post("/api/actors/actors/one")
+        .header("X-API-Key", "sekret")
+        .body_json(&observation)
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    let resp = cli
+        .get("/api/actors/actors/one")
+        .header("Authorization", "Bearer sekret")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body["values"]["1"], json!(1.5));
+}
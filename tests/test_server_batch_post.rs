@@ -0,0 +1,72 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::director;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_batch_post_keeps_going() {
+    let nv = Arc::new(director::new("/backfill", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("backfill"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli
+        .post("/api/actors/backfill/batch")
+        .body_json(&json!([
+            {"path": "/backfill/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+            {"path": "/backfill/one", "datetime": "not a datetime", "values": {"1": 2.0}},
+            {"path": "/backfill/one", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 3.0}},
+            {"path": "/backfill/two", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 4.0}},
+        ]))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body, json!({"accepted": 3, "duplicates": 0, "errors": 1}));
+
+    let resp = cli.get("/api/actors/backfill/one").send().await;
+    resp.assert_status_is_ok();
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(body["values"]["1"], json!(3.0));
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_batch_post_counts_duplicates() {
+    let db_file_prefix = "/tmp/backfill";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/backfill", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("backfill"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let batch = json!([
+        {"path": "/backfill/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}},
+        {"path": "/backfill/one", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 2.0}},
+    ]);
+    let mut summaries = Vec::new();
+    for _ in 0..2 {
+        let resp = cli
+            .post("/api/actors/backfill/batch")
+            .body_json(&batch)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        let body: serde_json::Value = resp.json().await.value().deserialize();
+        summaries.push(body);
+    }
+    assert_eq!(
+        summaries[0],
+        json!({"accepted": 2, "duplicates": 0, "errors": 0})
+    );
+    assert_eq!(
+        summaries[1],
+        json!({"accepted": 0, "duplicates": 2, "errors": 0})
+    );
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_delete_actor_over_http() {
+    let db_file_prefix = "/tmp/deletable";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/deletable", 8, None, Some(store_actor)));
+    let mut values = State::new();
+    values.insert(1, 1.5);
+    nv.ask(Message::Observations {
+        path: String::from("/deletable/one"),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap();
+
+    let config = HttpServerConfig::new(None, None, None, String::from("deletable"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    cli.delete("/api/actors/deletable/one")
+        .send()
+        .await
+        .assert_status_is_ok();
+    cli.get("/api/actors/deletable/one")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    cli.delete("/api/actors/deletable/one")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_is_in_the_spec() {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("actors"));
+    let cli = TestClient::new(build_app(nv, &config, None, None));
+
+    let resp = cli.get("/actors").send().await;
+    resp.assert_status_is_ok();
+    let ui = resp.0.into_body().into_string().await.unwrap_or_default();
+    assert!(ui.contains("\"delete\""));
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Journal;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+
+async fn observe(nv: &Handle, path: &str) {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+async fn list(cli: &TestClient<poem::endpoint::BoxEndpoint<'static>>, uri: &str) -> Vec<String> {
+    let resp = cli.get(uri).send().await;
+    resp.assert_status_is_ok();
+    resp.json().await.value().deserialize()
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_list_paths() {
+    let db_file_prefix = "/tmp/listed";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/listed", 8, None, Some(store_actor)));
+    for path in [
+        "/listed/c",
+        "/listed/a",
+        "/listed/b",
+        "/listed/a_b/x",
+        "/listedx/z",
+    ] {
+        observe(&nv, path).await;
+    }
+
+    let config = HttpServerConfig::new(None, None, None, String::from("listed"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    assert_eq!(
+        list(&cli, "/api/actors/listed/").await,
+        vec!["/listed/a", "/listed/a_b/x", "/listed/b", "/listed/c"]
+    );
+    assert_eq!(
+        list(&cli, "/api/actors/listed/?limit=2&offset=1").await,
+        vec!["/listed/a_b/x", "/listed/b"]
+    );
+    // an underscore in the prefix is not a wildcard
+    assert_eq!(
+        list(&cli, "/api/actors/listed/a_b/").await,
+        vec!["/listed/a_b/x"]
+    );
+    assert!(list(&cli, "/api/actors/listed/a_/").await.is_empty());
+}
+
+fn at(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn iso(secs: i64) -> String {
+    at(secs)
+        .format(&Iso8601::DEFAULT)
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[tokio::test]
+async fn test_list_paths_since() {
+    let db_file_prefix = "/tmp/listed_since";
+    remove_db(db_file_prefix);
+    // journal the paths at known sequences
+    let store = SqliteStore::connect(&format!("{db_file_prefix}.db"), false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    for (path, secs) in [
+        ("/synced/before", 0),
+        ("/synced/after", 20),
+        ("/synced/after", 10),
+        ("/synced/later", 30),
+    ] {
+        Journal::<f64>::insert_update(&store, path, at(secs), at(secs), &values, &Labels::new())
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    Store::close(&store).await;
+
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/synced", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("synced"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli
+        .get(format!("/api/actors/synced/?since={}", iso(5)))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({
+        "paths": ["/synced/after", "/synced/later"],
+        "sequence": iso(30),
+    }))
+    .await;
+
+    // the sequence of a sync is the since of the next one
+    let resp = cli
+        .get(format!("/api/actors/synced/?since={}", iso(30)))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_json(&json!({ "paths": [], "sequence": iso(30) }))
+        .await;
+
+    cli.get("/api/actors/synced/?since=yesterday")
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,99 @@
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+async fn wide_actor(width: i32) -> Arc<navactor::actors::actor::Handle> {
+    let nv = Arc::new(director::new("/actors", 8, None, None));
+    let mut values = State::new();
+    for idx in 0..width {
+        values.insert(width - idx, f64::from(idx));
+    }
+    nv.ask(Message::Observations {
+        path: String::from("/actors/wide"),
+        datetime: OffsetDateTime::now_utc(),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update wide actor: {e}"));
+    nv
+}
+
+/// the indices of one page in numeric order - json object keys don't keep theirs
+fn page_keys(body: &serde_json::Value) -> Vec<i32> {
+    let mut keys: Vec<i32> = body["values"]
+        .as_object()
+        .map(|values| {
+            values
+                .keys()
+                .map(|k| k.parse().unwrap_or_default())
+                .collect()
+        })
+        .unwrap_or_default();
+    keys.sort_unstable();
+    keys
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_state_pages_are_disjoint_ordered_and_complete() {
+    let nv = wide_actor(25).await;
+    let config = HttpServerConfig::new(None, None, None, String::from("actors"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let mut seen = Vec::new();
+    let mut page = Some(0);
+    while let Some(p) = page {
+        let resp = cli
+            .get("/api/actors/actors/wide")
+            .query("page", &p)
+            .query("page_size", &10)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        let body: serde_json::Value = resp.json().await.value().deserialize();
+        let keys = page_keys(&body);
+        assert!(keys.len() <= 10);
+        // every page starts after the one before it ends
+        if let (Some(last), Some(first)) = (seen.last(), keys.first()) {
+            assert!(last < first);
+        }
+        seen.extend(keys);
+        page = body["next"].as_u64().map(|n| usize::try_from(n).unwrap());
+    }
+
+    assert_eq!(seen, (1..=25).collect::<Vec<i32>>());
+}
+
+#[allow(clippy::unwrap_used)]
+#[tokio::test]
+async fn test_state_page_size_is_capped() {
+    let nv = wide_actor(25).await;
+    let config =
+        HttpServerConfig::new(None, None, None, String::from("actors")).with_max_page_size(7);
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    // without paging parameters a wide state is still cut at the cap
+    let resp = cli.get("/api/actors/actors/wide").send().await;
+    resp.assert_status(StatusCode::OK);
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(page_keys(&body), (1..=7).collect::<Vec<i32>>());
+    assert_eq!(body["next"].as_u64(), Some(1));
+
+    let resp = cli
+        .get("/api/actors/actors/wide")
+        .query("page", &3)
+        .query("page_size", &100)
+        .send()
+        .await;
+    let body: serde_json::Value = resp.json().await.value().deserialize();
+    assert_eq!(page_keys(&body), (22..=25).collect::<Vec<i32>>());
+    assert!(body.get("next").is_none());
+}
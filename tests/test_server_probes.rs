@@ -0,0 +1,65 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::director;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use navactor::io::net::auth::StaticTokenAuthenticator;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_probes_skip_auth() {
+    let db_file_prefix = "/tmp/probed";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv = Arc::new(director::new("/probed", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("probed"))
+        .with_authenticator(Arc::new(StaticTokenAuthenticator::new(String::from(
+            "secret",
+        ))));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    cli.get("/health").send().await.assert_status_is_ok();
+    cli.get("/ready").send().await.assert_status_is_ok();
+    cli.get("/api/actors/probed/one")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_not_ready_without_db() {
+    // the db file can not be created so the store never opens it
+    let store_actor = store_actor_sqlite::new(8, String::from("/no/such/dir/probed"), false, false);
+    let nv = Arc::new(director::new("/probed", 8, None, Some(store_actor)));
+    let config = HttpServerConfig::new(None, None, None, String::from("probed"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    cli.get("/health").send().await.assert_status_is_ok();
+    cli.get("/ready")
+        .send()
+        .await
+        .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_version_names_the_build() {
+    let nv = Arc::new(director::new("/probed", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("probed"))
+        .with_authenticator(Arc::new(StaticTokenAuthenticator::new(String::from(
+            "secret",
+        ))));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli.get("/version").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/json");
+    let body = resp.0.into_body().into_string().await.unwrap_or_default();
+    let version: serde_json::Value = serde_json::from_str(&body).unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(version["namespace"], "probed");
+    assert!(version["commit"].as_str().is_some_and(|c| !c.is_empty()));
+}
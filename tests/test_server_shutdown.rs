@@ -0,0 +1,103 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use navactor::io::net::api_server::serve_until;
+use navactor::io::net::api_server::HttpServerConfig;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+const PORT: u16 = 18_871;
+
+fn director_of(db_file_prefix: &str) -> Handle {
+    let store_actor = store_actor_sqlite::new_with_options(
+        8,
+        String::from(db_file_prefix),
+        true,
+        false,
+        StoreOptions {
+            batch_size: 100,
+            ..Default::default()
+        },
+    );
+    director::new("/graceful", 8, None, Some(store_actor))
+}
+
+#[tokio::test]
+async fn test_shutdown_commits_accepted_observations() {
+    let db_file_prefix = "/tmp/graceful";
+    remove_db(db_file_prefix);
+    let nv = Arc::new(director_of(db_file_prefix));
+    let config = HttpServerConfig::new(Some(PORT), None, None, String::from("graceful"));
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_until(
+        Arc::clone(&nv),
+        config,
+        None,
+        Some(true),
+        async {
+            stop.await.ok();
+        },
+    ));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{PORT}");
+    let mut ready = false;
+    for _ in 0..100 {
+        if client.get(format!("{base}/health")).send().await.is_ok() {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(ready, "server never listened on {PORT}");
+
+    let resp = client
+        .post(format!("{base}/api/actors/graceful/one"))
+        .json(&json!({
+            "path": "/graceful/one",
+            "datetime": "2023-01-11T23:17:57Z",
+            "values": {"1": 1.5},
+        }))
+        .send()
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert!(resp.status().is_success(), "{resp:?}");
+
+    shutdown
+        .send(())
+        .unwrap_or_else(|()| panic!("server already gone"));
+    server
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    // the director is gone once serve returns
+    assert!(nv.ask(Message::Ping).await.is_err());
+    assert!(client.get(format!("{base}/health")).send().await.is_err());
+
+    match director_of(db_file_prefix)
+        .ask(Message::Query {
+            path: String::from("/graceful/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => assert_eq!(values.get(&1), Some(&1.5)),
+        r => panic!("bad response from restarted director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_stop_without_store() {
+    let nv = director::new("/graceful", 8, None, None);
+    assert!(matches!(nv.ask(Message::Stop).await, Ok(Message::Stopped)));
+    assert!(nv.ask(Message::Ping).await.is_err());
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::json_decoder;
+use navactor::io::stdout_actor;
+
+const DB_FILE_PREFIX: &str = "/tmp/shutdown";
+
+async fn update(decoder: &Handle, text: &str) {
+    match decoder
+        .ask(Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(text),
+        })
+        .await
+    {
+        Ok(Message::StateReport { .. }) => {}
+        r => panic!("bad response to update: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_the_whole_pipeline() {
+    remove_db(DB_FILE_PREFIX);
+    let store_actor = store_actor_sqlite::new(8, String::from(DB_FILE_PREFIX), false, false);
+    let output = stdout_actor::new(8);
+    let director = director::new("/shutdown", 8, Some(output), Some(store_actor));
+    let decoder = json_decoder::new(8, director);
+
+    update(
+        &decoder,
+        r#"{"path": "/shutdown/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": 1.0}}"#,
+    )
+    .await;
+    update(
+        &decoder,
+        r#"{"path": "/shutdown/two", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 2.0}}"#,
+    )
+    .await;
+
+    // the decoder answers once everything after it has stopped
+    assert!(matches!(
+        decoder.ask(Message::Shutdown).await,
+        Ok(Message::Stopped)
+    ));
+    assert!(decoder.ask(Message::Ping).await.is_err());
+
+    // and the store committed and closed its db before it stopped
+    let store = SqliteStore::open(DB_FILE_PREFIX, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for path in ["/shutdown/one", "/shutdown/two"] {
+        let stats = store
+            .path_stats(path)
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(stats.observations, 1, "{path}");
+    }
+    store.close().await;
+}
+
+#[tokio::test]
+async fn test_shutdown_without_a_store() {
+    let director = director::new("/shutdownmem", 8, Some(stdout_actor::new(8)), None);
+    match director
+        .ask(Message::Query {
+            path: String::from("/shutdownmem/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { .. }) => {}
+        r => panic!("bad response to query: {r:?}"),
+    }
+    assert!(matches!(
+        director.ask(Message::Shutdown).await,
+        Ok(Message::Stopped)
+    ));
+    assert!(director.ask(Message::Ping).await.is_err());
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Journal;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+const PATH: &str = "/snap/one";
+
+fn observation(n: i64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(0, 1.5);
+    #[allow(clippy::cast_precision_loss)]
+    values.insert(1, n as f64);
+    Message::Observations {
+        path: String::from(PATH),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn state_of(director: &navactor::actors::actor::Handle) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(PATH),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+/// apply 7 observations to a `gene_type` actor, snapshotting every
+/// `snapshot_interval`, and return the live and the resurrected state
+async fn live_and_resurrected(
+    db_file_prefix: &str,
+    gene_type: GeneType,
+    snapshot_interval: Option<usize>,
+) -> (State<f64>, State<f64>) {
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        snapshot_interval,
+        ..Default::default()
+    };
+    let director = director::new_with_options("/snap", 8, None, Some(store_actor), options);
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/snap"),
+            gene_type,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for n in 0..7 {
+        director
+            .ask(observation(n))
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    let live = state_of(&director).await;
+
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/snap", 8, None, Some(store_actor));
+    (live, state_of(&director).await)
+}
+
+#[tokio::test]
+async fn test_snapshot_resurrects_the_same_state() {
+    let (live, replayed) = live_and_resurrected("/tmp/snapshots_off", GeneType::Accum, None).await;
+    assert_eq!(live, replayed);
+    assert_eq!(live.get(&0), Some(&10.5));
+    assert_eq!(live.get(&1), Some(&21.0));
+
+    let (live_with_snapshot, resurrected) =
+        live_and_resurrected("/tmp/snapshots_on", GeneType::Accum, Some(3)).await;
+    assert_eq!(live_with_snapshot, live);
+    assert_eq!(resurrected, live);
+
+    // the snapshot after the 6th observation leaves one row to replay
+    let store = SqliteStore::open("/tmp/snapshots_on", false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let (last_row, snapshot): (i64, Message<f64>) = store
+        .get_snapshot(PATH)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+        .unwrap_or_else(|| panic!("no snapshot of {PATH}"));
+    assert!(matches!(
+        snapshot,
+        Message::Snapshot { values, .. } if values.get(&1) == Some(&15.0)
+    ));
+    let newer: Vec<Message<f64>> = store
+        .get_values(PATH, last_row, None)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(newer.len(), 1);
+}
+
+#[tokio::test]
+async fn test_delete_removes_the_snapshot() {
+    let db_file_prefix = "/tmp/snapshots_delete";
+    live_and_resurrected(db_file_prefix, GeneType::Accum, Some(2)).await;
+
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/snap", 8, None, Some(store_actor));
+    let reply = director
+        .ask(Message::Delete {
+            path: String::from(PATH),
+            mapping: false,
+        })
+        .await;
+    assert!(matches!(reply, Ok(Message::Delete { .. })), "{reply:?}");
+    assert!(state_of(&director).await.is_empty());
+
+    let store = SqliteStore::open(db_file_prefix, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert!(Journal::<f64>::get_snapshot(&store, PATH)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_genes_that_do_not_compact_are_not_snapshotted() {
+    // the moving average keeps its window in the gene and the daily
+    // accumulator the days before the latest one
+    for (db_file_prefix, gene_type) in [
+        ("/tmp/snapshots_window", GeneType::MovingAvg),
+        ("/tmp/snapshots_daily", GeneType::DailyAccum),
+    ] {
+        let (live, resurrected) = live_and_resurrected(db_file_prefix, gene_type, Some(3)).await;
+        assert_eq!(resurrected, live);
+
+        let store = SqliteStore::open(db_file_prefix, false)
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert!(
+            Journal::<f64>::get_snapshot(&store, PATH)
+                .await
+                .unwrap_or_else(|e| panic!("{e}"))
+                .is_none(),
+            "{gene_type} was snapshotted"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::message::Labels;
+use navactor::actors::sqlite_store::SqliteOptions;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::sqlite_store::SqliteSynchronous;
+use navactor::actors::store::Journal;
+use navactor::actors::store::Store;
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const WRITES: i64 = 200;
+
+async fn pragma(store: &SqliteStore, name: &str) -> i64 {
+    sqlx::query(&format!("PRAGMA {name};"))
+        .fetch_one(&store.dbconn)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+        .get(0)
+}
+
+#[tokio::test]
+async fn test_sqlite_options_set_the_pragmas() {
+    let db_file_prefix = "/tmp/sqlite_pragmas";
+    remove_db(db_file_prefix);
+    let db_file = format!("{db_file_prefix}.db");
+
+    let store = SqliteStore::connect(&db_file, true)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(pragma(&store, "busy_timeout").await, 5000);
+    // NORMAL is 1 and FULL is 2
+    assert_eq!(pragma(&store, "synchronous").await, 1);
+    Store::close(&store).await;
+
+    let store = SqliteStore::connect_with(
+        &db_file,
+        false,
+        &SqliteOptions {
+            max_connections: 2,
+            busy_timeout: Duration::from_millis(250),
+            synchronous: Some(SqliteSynchronous::Extra),
+        },
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(pragma(&store, "busy_timeout").await, 250);
+    assert_eq!(pragma(&store, "synchronous").await, 3);
+    Store::close(&store).await;
+}
+
+#[tokio::test]
+async fn test_readers_are_not_locked_out_by_a_writer() {
+    let db_file_prefix = "/tmp/sqlite_pool";
+    remove_db(db_file_prefix);
+    let db_file = format!("{db_file_prefix}.db");
+    let options = SqliteOptions {
+        max_connections: 4,
+        ..Default::default()
+    };
+    // separate pools like a server and an `nv inspect` sharing the db file
+    let writer = SqliteStore::connect_with(&db_file, true, &options)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let reader = Arc::new(
+        SqliteStore::connect_with(&db_file, true, &options)
+            .await
+            .unwrap_or_else(|e| panic!("{e}")),
+    );
+
+    let writing = tokio::spawn(async move {
+        let mut values: State<f64> = State::new();
+        values.insert(1, 1.0);
+        for n in 0..WRITES {
+            let datetime = OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                .unwrap_or_else(|e| panic!("{e}"));
+            Journal::<f64>::insert_update(
+                &writer,
+                "/pool/one",
+                datetime,
+                OffsetDateTime::now_utc(),
+                &values,
+                &Labels::new(),
+            )
+            .await
+            .unwrap_or_else(|e| panic!("write {n} failed: {e}"));
+        }
+        Store::close(&writer).await;
+    });
+
+    let readers: Vec<_> = (0..8)
+        .map(|r| {
+            let reader = Arc::clone(&reader);
+            tokio::spawn(async move {
+                let mut observations = 0;
+                for _ in 0..50 {
+                    Journal::<f64>::get_values(reader.as_ref(), "/pool/one", 0, None)
+                        .await
+                        .unwrap_or_else(|e| panic!("reader {r} failed: {e}"));
+                    let stats = reader
+                        .path_stats("/pool/one")
+                        .await
+                        .unwrap_or_else(|e| panic!("reader {r} failed: {e}"));
+                    assert!(stats.observations >= observations);
+                    observations = stats.observations;
+                }
+            })
+        })
+        .collect();
+
+    writing.await.unwrap_or_else(|e| panic!("{e}"));
+    for reading in readers {
+        reading.await.unwrap_or_else(|e| panic!("{e}"));
+    }
+    let stats = reader
+        .path_stats("/pool/one")
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(
+        stats.observations,
+        u64::try_from(WRITES).unwrap_or_default()
+    );
+}
@@ -0,0 +1,122 @@
+use futures::StreamExt;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use poem::web::sse::Event;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+async fn observe(nv: &Handle, path: &str, secs: i64) {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    nv.ask(Message::Observations {
+        path: String::from(path),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("can not update {path}: {e}"));
+}
+
+/// the id and path of the next `count` events of the stream
+async fn next_events(
+    events: &mut (impl futures::Stream<Item = Event> + Unpin),
+    count: usize,
+) -> Vec<(String, String)> {
+    let mut next = vec![];
+    while next.len() < count {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap_or_else(|e| panic!("no event: {e}"));
+        if let Some(Event::Message { id, data, .. }) = event {
+            let report: serde_json::Value =
+                serde_json::from_str(&data).unwrap_or_else(|e| panic!("{e}"));
+            next.push((id, report["path"].as_str().unwrap_or_default().to_string()));
+        }
+    }
+    next
+}
+
+#[tokio::test]
+async fn test_events_of_a_namespace_are_streamed() {
+    let nv: Arc<Handle> = Arc::new(director::new("/events", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("events"));
+    let cli = TestClient::new(build_app(Arc::clone(&nv), &config, None, Some(true)));
+
+    let resp = cli.get("/events/events").send().await;
+    resp.assert_status_is_ok();
+    let mut events = resp.sse_stream();
+    observe(&nv, "/events/a/one", 0).await;
+    observe(&nv, "/eventsx/two", 1).await;
+    observe(&nv, "/events/b/three", 2).await;
+    assert_eq!(
+        next_events(&mut events, 2).await,
+        vec![
+            (String::from("1"), String::from("/events/a/one")),
+            (String::from("3"), String::from("/events/b/three")),
+        ]
+    );
+
+    // a reconnect resumes after the last event it got
+    let resp = cli
+        .get("/events/events")
+        .header("Last-Event-ID", "1")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let mut resumed = resp.sse_stream();
+    observe(&nv, "/events/a/one", 3).await;
+    assert_eq!(
+        next_events(&mut resumed, 2).await,
+        vec![
+            (String::from("3"), String::from("/events/b/three")),
+            (String::from("4"), String::from("/events/a/one")),
+        ]
+    );
+
+    // a narrower prefix
+    let resp = cli
+        .get("/events/events/b")
+        .header("Last-Event-ID", "0")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let mut narrow = resp.sse_stream();
+    assert_eq!(
+        next_events(&mut narrow, 1).await,
+        vec![(String::from("3"), String::from("/events/b/three"))]
+    );
+}
+
+#[tokio::test]
+async fn test_slow_events_subscriber_is_dropped() {
+    let nv = director::new("/slow", 8, None, None);
+    let (stream_to, mut events) = mpsc::channel(1);
+    let subscribed = nv
+        .ask(Message::SubscribeEvents {
+            path: String::from("/slow"),
+            after: None,
+            stream_to,
+        })
+        .await;
+    assert!(matches!(subscribed, Ok(Message::Events { events }) if events.is_empty()));
+
+    // nobody reads the events so the second one finds the subscriber behind
+    for secs in 0..3 {
+        observe(&nv, "/slow/one", secs).await;
+    }
+    assert!(matches!(
+        events.recv().await,
+        Some(Message::Event { id: 1, .. })
+    ));
+    assert!(events.recv().await.is_none());
+}
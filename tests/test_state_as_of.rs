@@ -0,0 +1,182 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+const PATH: &str = "/asof/one";
+
+/// 2023-01-11T23:00:00Z plus `n` hours
+fn hour(n: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_478_000 + n * 3600).unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn ask(director: &Handle, message: Message<f64>) -> Message<f64> {
+    director
+        .ask(message)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// an accumulator of `PATH` that observed 1.0 at hours 0, 1, 3, and 4,
+/// and the one of hour 2 late, after the one of hour 4
+async fn director_of(db_file_prefix: &str, snapshot_interval: Option<usize>) -> Handle {
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        snapshot_interval,
+        ..Default::default()
+    };
+    let director = director::new_with_options("/asof", 8, None, Some(store_actor), options);
+    ask(
+        &director,
+        Message::GeneMapping {
+            path: String::from("/asof"),
+            gene_type: GeneType::Accum,
+            params: GeneParams::default(),
+        },
+    )
+    .await;
+    for n in [0, 1, 3, 4, 2] {
+        let mut values = State::new();
+        values.insert(1, 1.0);
+        ask(
+            &director,
+            Message::Observations {
+                path: String::from(PATH),
+                datetime: hour(n),
+                values,
+                labels: Labels::new(),
+            },
+        )
+        .await;
+    }
+    director
+}
+
+async fn state_as_of(director: &Handle, datetime: OffsetDateTime) -> State<f64> {
+    match ask(
+        director,
+        Message::Query {
+            path: String::from(PATH),
+            hint: MtHint::StateAsOf { datetime },
+        },
+    )
+    .await
+    {
+        Message::StateReport { values, .. } => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+async fn assert_states_as_of(director: &Handle) {
+    assert_eq!(
+        state_as_of(director, hour(0)).await,
+        State::from([(1, 1.0)])
+    );
+    // the late observation of hour 2 counts as of then
+    assert_eq!(
+        state_as_of(director, hour(2)).await,
+        State::from([(1, 3.0)])
+    );
+    assert_eq!(
+        state_as_of(director, hour(3)).await,
+        State::from([(1, 4.0)])
+    );
+    assert_eq!(
+        state_as_of(director, hour(9)).await,
+        State::from([(1, 5.0)])
+    );
+    assert_eq!(state_as_of(director, hour(-1)).await, State::new());
+
+    // the live actor is left alone
+    match ask(
+        director,
+        Message::Query {
+            path: String::from(PATH),
+            hint: MtHint::State,
+        },
+    )
+    .await
+    {
+        Message::StateReport { values, .. } => assert_eq!(values, State::from([(1, 5.0)])),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_state_as_of() {
+    let director = director_of("/tmp/state_as_of", None).await;
+    assert_states_as_of(&director).await;
+}
+
+#[tokio::test]
+async fn test_state_as_of_before_a_snapshot() {
+    let director = director_of("/tmp/state_as_of_snapshot", Some(2)).await;
+    assert_states_as_of(&director).await;
+}
+
+#[tokio::test]
+async fn test_state_as_of_needs_a_journal() {
+    let director = director::new("/asofmem", 8, None, None);
+    assert!(director
+        .ask(Message::Query {
+            path: String::from("/asofmem/one"),
+            hint: MtHint::StateAsOf { datetime: hour(0) },
+        })
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_get_state_as_of() {
+    let nv: Arc<Handle> = Arc::new(director_of("/tmp/state_as_of_api", None).await);
+    let config = HttpServerConfig::new(None, None, None, String::from("asof"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+
+    let resp = cli
+        .get("/api/actors/asof/one")
+        .query("as_of", &"2023-01-12T01:00:00Z")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("values")
+        .object()
+        .get("1")
+        .assert_f64(3.0);
+
+    for (as_of, status) in [
+        ("2023-01-11T22:00:00Z", StatusCode::NOT_FOUND),
+        ("3pm yesterday", StatusCode::BAD_REQUEST),
+    ] {
+        cli.get("/api/actors/asof/one")
+            .query("as_of", &as_of)
+            .send()
+            .await
+            .assert_status(status);
+    }
+    cli.get("/api/actors/asof/one")
+        .query("as_of", &"2023-01-12T01:00:00Z")
+        .query("bucket", &"2023-01-12")
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
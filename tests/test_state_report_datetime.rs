@@ -0,0 +1,88 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use time::OffsetDateTime;
+
+fn datetime(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn observe(nv: &Handle, secs: i64) -> OffsetDateTime {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    match nv
+        .ask(Message::Observations {
+            path: String::from("/observed/one"),
+            datetime: datetime(secs),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+    {
+        Ok(Message::StateReport { datetime, .. }) => datetime,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+async fn reported_datetime(nv: &Handle) -> OffsetDateTime {
+    match nv
+        .ask(Message::Query {
+            path: String::from("/observed/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { datetime, .. }) => datetime,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+fn director_of(db_file_prefix: &str, snapshot_interval: Option<usize>) -> Handle {
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        snapshot_interval,
+        ..Default::default()
+    };
+    director::new_with_options("/observed", 8, None, Some(store_actor), options)
+}
+
+async fn assert_reports_newest_observation(db_file_prefix: &str, snapshot_interval: Option<usize>) {
+    remove_db(db_file_prefix);
+    let nv = director_of(db_file_prefix, snapshot_interval);
+    // an accumulator applies a late report so the newest observation is not the last one
+    nv.ask(Message::GeneMapping {
+        path: String::from("/observed"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(observe(&nv, 0).await, datetime(0));
+    assert_eq!(observe(&nv, 20).await, datetime(20));
+    assert_eq!(observe(&nv, 10).await, datetime(20));
+    assert_eq!(reported_datetime(&nv).await, datetime(20));
+
+    // a resurrected actor reports the newest observation of its journal
+    let restarted = director_of(db_file_prefix, snapshot_interval);
+    assert_eq!(reported_datetime(&restarted).await, datetime(20));
+}
+
+#[tokio::test]
+async fn test_state_report_datetime_is_the_newest_observation() {
+    assert_reports_newest_observation("/tmp/state_report_datetime", None).await;
+}
+
+#[tokio::test]
+async fn test_state_report_datetime_survives_snapshots() {
+    assert_reports_newest_observation("/tmp/state_report_datetime_snapped", Some(2)).await;
+}
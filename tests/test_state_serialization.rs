@@ -0,0 +1,56 @@
+use navactor::actors::director;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::io::json_decoder;
+use tokio::runtime::Runtime;
+
+/// run one in-memory pipeline over the observations and return the serialized
+/// state of the actor that received them
+async fn serialized_state(observations: &[&str]) -> (String, String) {
+    let director = director::new("/", 8, None, None);
+    let json_decoder_actor = json_decoder::new(8, director);
+
+    let mut reply = None;
+    for text in observations {
+        let cmd = Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(*text),
+        };
+        reply = Some(json_decoder_actor.ask(cmd).await);
+    }
+
+    match reply {
+        Some(Ok(Message::StateReport { values, .. })) => (
+            serde_json::to_string(&values).unwrap_or_default(),
+            format!("{values:?}"),
+        ),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[test]
+fn test_serialized_state_is_deterministic() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let first = serialized_state(&[
+            "{ \"path\": \"/actors/det\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"99\": 9.9, \"3\": 3.3, \"150\": 1.5, \"1\": 1.1} }",
+            "{ \"path\": \"/actors/det\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"42\": 4.2, \"7\": 0.7} }",
+        ])
+        .await;
+
+        // same observations with the indices reported in a different order
+        let second = serialized_state(&[
+            "{ \"path\": \"/actors/det\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1, \"150\": 1.5, \"99\": 9.9, \"3\": 3.3} }",
+            "{ \"path\": \"/actors/det\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"7\": 0.7, \"42\": 4.2} }",
+        ])
+        .await;
+
+        assert_eq!(first, second);
+        assert_eq!(
+            first.0,
+            "{\"1\":1.1,\"3\":3.3,\"7\":0.7,\"42\":4.2,\"99\":9.9,\"150\":1.5}"
+        );
+    });
+}
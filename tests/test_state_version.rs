@@ -0,0 +1,163 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use serde_json::json;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+fn datetime(secs: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn observations(secs: i64) -> Message<f64> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    Message::Observations {
+        path: String::from("/versioned/one"),
+        datetime: datetime(secs),
+        values,
+        labels: Labels::new(),
+    }
+}
+
+async fn version_of(nv: &Handle) -> u64 {
+    match nv
+        .ask(Message::Query {
+            path: String::from("/versioned/one"),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { version, .. }) => version,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+fn director_of(db_file_prefix: &str, snapshot_interval: Option<usize>) -> Handle {
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        snapshot_interval,
+        ..Default::default()
+    };
+    director::new_with_options("/versioned", 8, None, Some(store_actor), options)
+}
+
+async fn assert_version_survives_restart(db_file_prefix: &str, snapshot_interval: Option<usize>) {
+    remove_db(db_file_prefix);
+    let nv = director_of(db_file_prefix, snapshot_interval);
+    nv.ask(Message::GeneMapping {
+        path: String::from("/versioned"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    for secs in 0..3 {
+        match nv.ask(observations(secs)).await {
+            Ok(Message::StateReport { version, .. }) => {
+                assert_eq!(version, u64::try_from(secs + 1).unwrap_or_default());
+            }
+            r => panic!("bad response from director: {r:?}"),
+        }
+    }
+    // a duplicate is not journaled and does not advance the version
+    assert!(matches!(
+        nv.ask(observations(2)).await,
+        Ok(Message::ConstraintViolation)
+    ));
+    assert_eq!(version_of(&nv).await, 3);
+
+    let restarted = director_of(db_file_prefix, snapshot_interval);
+    assert_eq!(version_of(&restarted).await, 3);
+}
+
+#[tokio::test]
+async fn test_version_counts_applied_observations() {
+    assert_version_survives_restart("/tmp/state_version", None).await;
+}
+
+#[tokio::test]
+async fn test_version_survives_snapshots() {
+    assert_version_survives_restart("/tmp/state_version_snapped", Some(2)).await;
+}
+
+#[tokio::test]
+async fn test_if_match_rejects_a_stale_version() {
+    let db_file_prefix = "/tmp/state_version_if_match";
+    remove_db(db_file_prefix);
+    let nv = director_of(db_file_prefix, None);
+    nv.ask(Message::GeneMapping {
+        path: String::from("/versioned"),
+        gene_type: GeneType::Accum,
+        params: GeneParams::default(),
+    })
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    let config = HttpServerConfig::new(None, None, None, String::from("versioned"));
+    let cli = TestClient::new(build_app(Arc::new(nv), &config, None, Some(true)));
+
+    let post = |datetime: &'static str, if_match: Option<&'static str>| {
+        let req = cli.post("/api/actors/versioned/one").body_json(&json!({
+            "path": "/versioned/one",
+            "datetime": datetime,
+            "values": {"1": 1.0},
+        }));
+        match if_match {
+            Some(version) => req.header("If-Match", version).send(),
+            None => req.send(),
+        }
+    };
+
+    let resp = post("2023-01-11T23:17:57Z", None).await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("version")
+        .assert_i64(1);
+
+    let resp = post("2023-01-11T23:17:58Z", Some("\"1\"")).await;
+    resp.assert_status_is_ok();
+    resp.json()
+        .await
+        .value()
+        .object()
+        .get("version")
+        .assert_i64(2);
+
+    // another writer got in first
+    let resp = post("2023-01-11T23:17:59Z", Some("1")).await;
+    resp.assert_status(StatusCode::CONFLICT);
+    resp.assert_json(&json!({
+        "error": "/versioned/one is at version 2",
+        "code": "version_conflict",
+        "path": "/versioned/one",
+    }))
+    .await;
+
+    post("2023-01-11T23:18:00Z", Some("latest"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let resp = cli.get("/api/actors/versioned/one").send().await;
+    resp.assert_status_is_ok();
+    let json = resp.json().await;
+    let state = json.value().object();
+    state.get("version").assert_i64(2);
+    state.get("values").object().get("1").assert_f64(2.0);
+}
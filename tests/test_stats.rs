@@ -0,0 +1,125 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use serde_json::Value;
+use std::process::Command;
+use time::OffsetDateTime;
+
+async fn populate(db_file: &str) {
+    let store_actor =
+        store_actor_sqlite::new_from_url(8, String::from(db_file), "counted", false, false);
+    let director = director::new("/counted", 8, None, Some(store_actor));
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/counted"),
+            gene_type: GeneType::Accum,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for (path, secs) in [
+        ("/counted/one", 0),
+        ("/counted/one", 1),
+        ("/counted/two", 2),
+    ] {
+        let mut values = State::new();
+        values.insert(1, 1.0);
+        director
+            .ask(Message::Observations {
+                path: String::from(path),
+                datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs)
+                    .unwrap_or_else(|e| panic!("{e}")),
+                values,
+                labels: Labels::new(),
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+}
+
+async fn store_of(db_file: &str) -> Handle {
+    store_actor_sqlite::try_new_from_url(
+        8,
+        String::from(db_file),
+        "counted",
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[tokio::test]
+async fn test_stats_count_the_namespace() {
+    let db_file = "/tmp/counted.db";
+    remove_db(db_file.trim_end_matches(".db"));
+    let before = OffsetDateTime::now_utc()
+        .replace_nanosecond(0)
+        .unwrap_or_else(|e| panic!("{e}"));
+    populate(db_file).await;
+
+    match store_of(db_file)
+        .await
+        .ask(Message::Stats {
+            path: String::from("/counted/"),
+        })
+        .await
+    {
+        Ok(Message::StatsReport { path, stats }) => {
+            assert_eq!(path, "/counted/");
+            assert_eq!(stats.paths, 2);
+            assert_eq!(stats.observations, 3);
+            assert_eq!(stats.gene_mappings, 1);
+            let earliest = stats.earliest.unwrap_or_else(|| panic!("no earliest"));
+            let latest = stats.latest.unwrap_or_else(|| panic!("no latest"));
+            assert!(before <= earliest && earliest <= latest, "{stats:?}");
+        }
+        r => panic!("bad response from store: {r:?}"),
+    }
+
+    // a prefix without observations
+    match store_of(db_file)
+        .await
+        .ask(Message::Stats {
+            path: String::from("/uncounted/"),
+        })
+        .await
+    {
+        Ok(Message::StatsReport { stats, .. }) => {
+            assert_eq!(stats.observations, 0);
+            assert!(stats.earliest.is_none() && stats.latest.is_none());
+        }
+        r => panic!("bad response from store: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_stats_prints_json() {
+    let db_file = "/tmp/counted_cli.db";
+    remove_db(db_file.trim_end_matches(".db"));
+    populate(db_file).await;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["stats", "counted", "--db-path", db_file, "--format", "json"])
+        .output()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: Value =
+        serde_json::from_str(stdout.trim()).unwrap_or_else(|e| panic!("not json '{stdout}': {e}"));
+    assert_eq!(report["path"], "/counted/");
+    assert_eq!(report["paths"], 2);
+    assert_eq!(report["observations"], 3);
+    assert_eq!(report["gene_mappings"], 1);
+    assert!(report["earliest"].is_string());
+}
@@ -0,0 +1,76 @@
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+async fn observe(director: &Handle, value: f64) {
+    let mut values = State::new();
+    values.insert(1, value);
+    director
+        .ask(Message::Observations {
+            path: String::from("/watched/one"),
+            datetime: OffsetDateTime::now_utc(),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("can not update: {e}"));
+}
+
+fn reported(report: Option<Message<f64>>) -> f64 {
+    match report {
+        Some(Message::StateReport { values, .. }) => values[&1],
+        r => panic!("bad report: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_subscriber_gets_every_change() {
+    let director = director::new("/watched", 8, None, None);
+    observe(&director, 1.0).await;
+
+    let (stream_to, mut reports) = mpsc::channel(8);
+    match director
+        .ask(Message::Subscribe {
+            path: String::from("/watched/one"),
+            stream_to,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => assert!((values[&1] - 1.0).abs() < f64::EPSILON),
+        r => panic!("bad response from director: {r:?}"),
+    }
+
+    observe(&director, 2.0).await;
+    observe(&director, 3.0).await;
+    assert!((reported(reports.recv().await) - 2.0).abs() < f64::EPSILON);
+    assert!((reported(reports.recv().await) - 3.0).abs() < f64::EPSILON);
+
+    // a closed subscription does not get in the way of updates
+    drop(reports);
+    observe(&director, 4.0).await;
+}
+
+#[tokio::test]
+async fn test_slow_subscriber_does_not_block() {
+    let director = director::new("/watched", 8, None, None);
+    let (stream_to, mut reports) = mpsc::channel(1);
+    director
+        .ask(Message::Subscribe {
+            path: String::from("/watched/one"),
+            stream_to,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("can not subscribe: {e}"));
+
+    // nothing is read until every update is applied - the reports that do not fit are dropped
+    for n in 0..5 {
+        observe(&director, f64::from(n)).await;
+    }
+    assert!(reported(reports.recv().await).abs() < f64::EPSILON);
+    observe(&director, 9.0).await;
+    assert!((reported(reports.recv().await) - 9.0).abs() < f64::EPSILON);
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::test::TestClient;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::span::Attributes;
+use tracing::span::Id;
+use tracing::span::Record;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
+
+type Span = (String, BTreeMap<String, String>);
+
+/// the name and the fields of every span that was created
+#[derive(Clone, Default)]
+struct Spans {
+    spans: Arc<Mutex<Vec<Span>>>,
+    ids: Arc<Mutex<HashMap<u64, usize>>>,
+}
+
+struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Spans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut Fields(&mut fields));
+        let mut spans = self.spans.lock().unwrap_or_else(|e| panic!("{e}"));
+        self.ids
+            .lock()
+            .unwrap_or_else(|e| panic!("{e}"))
+            .insert(id.into_u64(), spans.len());
+        spans.push((attrs.metadata().name().to_string(), fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let Some(i) = self
+            .ids
+            .lock()
+            .unwrap_or_else(|e| panic!("{e}"))
+            .get(&id.into_u64())
+            .copied()
+        else {
+            return;
+        };
+        let mut spans = self.spans.lock().unwrap_or_else(|e| panic!("{e}"));
+        values.record(&mut Fields(&mut spans[i].1));
+    }
+}
+
+impl Spans {
+    /// the fields of the first span named `name`
+    fn fields_of(&self, name: &str) -> BTreeMap<String, String> {
+        self.spans
+            .lock()
+            .unwrap_or_else(|e| panic!("{e}"))
+            .iter()
+            .find(|(span, _)| span == name)
+            .map(|(_, fields)| fields.clone())
+            .unwrap_or_else(|| panic!("no {name} span"))
+    }
+}
+
+fn expected(path: &str, msg: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (String::from("namespace"), String::from("/spans")),
+        (String::from("path"), String::from(path)),
+        (String::from("msg"), String::from(msg)),
+    ])
+}
+
+#[tokio::test]
+async fn test_spans_have_namespace_path_and_msg_fields() {
+    let spans = Spans::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(spans.clone()));
+
+    let db_file_prefix = "/tmp/tracing_spans";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let nv: Arc<Handle> = Arc::new(director::new("/spans", 8, None, Some(store_actor)));
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    let observations = Message::Observations {
+        path: String::from("/spans/one"),
+        datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077)
+            .unwrap_or_else(|e| panic!("{e}")),
+        values,
+        labels: Labels::new(),
+    };
+    assert_eq!(observations.kind(), "Observations");
+    nv.ask(observations).await.unwrap_or_else(|e| panic!("{e}"));
+
+    let config = HttpServerConfig::new(None, None, None, String::from("spans"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+    cli.get("/api/actors/spans/one")
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    assert_eq!(
+        spans.fields_of("handle_update_or_query"),
+        expected("/spans/one", "Observations")
+    );
+    assert_eq!(
+        spans.fields_of("handle_update"),
+        expected("/spans/one", "Observations")
+    );
+    assert_eq!(
+        spans.fields_of("get_state"),
+        expected("/spans/one", "Query")
+    );
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::genes::gene::Gene;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Journal;
+use navactor::actors::store::Store;
+use navactor::actors::store::UpdateOutcome;
+use navactor::actors::value::Value;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::Serializer;
+use std::fmt;
+use std::ops::Add;
+use std::ops::Sub;
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+/// a value that fails to serialize when it is negative
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize)]
+struct Fragile(f64);
+
+impl Serialize for Fragile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 < 0.0 {
+            Err(serde::ser::Error::custom("negative"))
+        } else {
+            serializer.serialize_f64(self.0)
+        }
+    }
+}
+
+impl Add for Fragile {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for Fragile {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl fmt::Display for Fragile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Fragile {
+    type Err = std::num::ParseFloatError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl Value for Fragile {
+    const NAME: &'static str = "fragile";
+
+    fn gene(
+        _gene_type: GeneType,
+        _params: &GeneParams,
+    ) -> Option<Box<dyn Gene<Self> + Send + Sync>> {
+        None
+    }
+}
+
+async fn insert(store: &SqliteStore, secs: i64, value: f64) -> Result<UpdateOutcome, String> {
+    let mut values = State::new();
+    values.insert(1, Fragile(value));
+    let datetime =
+        OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs).unwrap_or_else(|e| panic!("{e}"));
+    Journal::<Fragile>::insert_update(
+        store,
+        "/fragile/one",
+        datetime,
+        OffsetDateTime::now_utc(),
+        &values,
+        &Labels::new(),
+    )
+    .await
+    .map_err(|e| e.reason)
+}
+
+#[tokio::test]
+async fn test_values_that_can_not_be_serialized_are_not_journaled() {
+    let db_file_prefix = "/tmp/fragile";
+    remove_db(db_file_prefix);
+    let store = SqliteStore::connect(&format!("{db_file_prefix}.db"), false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    assert_eq!(insert(&store, 0, 1.5).await, Ok(UpdateOutcome::Persisted));
+    let failed = insert(&store, 1, -1.5).await;
+    assert!(
+        failed.as_ref().is_err_and(|e| e.contains("serialize")),
+        "{failed:?}"
+    );
+
+    // only the good row is journaled and it replays
+    let stats = store
+        .path_stats("/fragile/one")
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(stats.observations, 1);
+    let replayed = Journal::<Fragile>::get_values(&store, "/fragile/one", 0, None)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(replayed.len(), 1);
+    Store::close(&store).await;
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// run `nv update --dry-run` in `dir` with the input and return its exit
+/// code and what it printed
+fn dry_run(dir: &str, input: &str) -> (Option<i32>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .current_dir(dir)
+        .args(["update", "--namespace", "dry", "--dry-run"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+fn clean_dir(dir: &str) {
+    if Path::new(dir).exists() {
+        fs::remove_dir_all(dir).unwrap_or_else(|e| panic!("{e}"));
+    }
+    fs::create_dir_all(dir).unwrap_or_else(|e| panic!("{e}"));
+}
+
+const INPUT: &str = "{ \"path\": \"/dry/counted\", \"gene_type\": \"Accum\"}
+{ \"path\": \"/dry/counted/one\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }
+{ \"path\": \"/dry/counted/one\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"1\": 1.1} }
+{ \"path\": \"/dry/other\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }
+";
+
+#[test]
+fn test_dry_run_summarizes_without_journaling() {
+    let dir = "/tmp/update_dry_run";
+    clean_dir(dir);
+    let (code, printed) = dry_run(dir, INPUT);
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        printed,
+        "dry run: 3 observations of 2 paths, nothing was journaled
+dry run: 1 paths have no gene mapping
+dry run: /dry/other has no gene mapping
+"
+    );
+    assert!(!Path::new(dir).join("dry.db").exists());
+}
+
+#[test]
+fn test_dry_run_with_undecodable_lines_exits_non_zero() {
+    let dir = "/tmp/update_dry_run_bad";
+    clean_dir(dir);
+    let (code, printed) = dry_run(dir, &format!("{INPUT}not json\n"));
+    assert_eq!(code, Some(3));
+    assert!(printed.starts_with("dry run: 3 observations of 2 paths"));
+    assert!(!Path::new(dir).join("dry.db").exists());
+}
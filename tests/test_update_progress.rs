@@ -0,0 +1,77 @@
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::io::progress_actor;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const INPUT: &str = "{ \"path\": \"/progress/one\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }
+{ \"path\": \"/progress/one\", \"datetime\": \"2023-01-11T23:17:58+0000\", \"values\": {\"1\": 1.1} }
+{ \"path\": \"/progress/two\", \"datetime\": \"2023-01-11T23:17:57+0000\", \"values\": {\"1\": 1.1} }
+";
+
+#[tokio::test]
+async fn test_progress_passes_every_message_on() {
+    let director = director::new("/progress", 8, None, None);
+    let progress = progress_actor::new(8, director, Duration::from_secs(60));
+    let mut values = State::new();
+    values.insert(1, 1.5);
+    let reply = progress
+        .ask(Message::Observations {
+            path: String::from("/progress/one"),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert!(
+        matches!(&reply, Message::StateReport { path, .. } if path == "/progress/one"),
+        "{reply:?}"
+    );
+    assert!(matches!(
+        progress.ask(Message::EndOfStream {}).await,
+        Ok(Message::EndOfStream {})
+    ));
+}
+
+#[test]
+fn test_update_progress_counts_instead_of_printing_state() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["--memory-only", "update", "--namespace", "progress"])
+        .arg("--progress")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(INPUT.as_bytes())
+            .unwrap_or_else(|e| panic!("can not write to nv: {e}"));
+    }
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|e| panic!("nv did not finish: {e}"));
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .any(|line| line == "3 observations ingested"));
+}
+
+#[test]
+fn test_update_progress_of_zero_seconds_is_rejected() {
+    let status = Command::new(env!("CARGO_BIN_EXE_nv"))
+        .args(["--memory-only", "update", "--progress", "0"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap_or_else(|e| panic!("can not run nv: {e}"));
+    assert!(!status.success());
+}
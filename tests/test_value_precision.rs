@@ -0,0 +1,178 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store::Journal;
+use navactor::actors::store::Store;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::value::Value;
+use navactor::io::json_decoder;
+use navactor::io::net::api_server::build_app;
+use navactor::io::net::api_server::HttpServerConfig;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+fn datetime() -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(1_673_479_077).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn observation(path: &str, value: f64) -> Message<f64> {
+    Message::Observations {
+        path: String::from(path),
+        datetime: datetime(),
+        values: State::from([(1, value), (2, 1.0)]),
+        labels: Labels::new(),
+    }
+}
+
+async fn state_of(director: &Handle, path: &str) -> State<f64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values,
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+/// the journaled values of every path of the namespace
+async fn journal_of(db_file_prefix: &str, prefix: &str) -> Vec<State<f64>> {
+    let store = SqliteStore::open(db_file_prefix, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let journal: Vec<Message<f64>> = store
+        .get_journal(prefix)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    store.close().await;
+    journal
+        .into_iter()
+        .filter_map(|m| match m {
+            Message::Observations { values, .. } => Some(values),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_rounded() {
+    assert!((21.456_789.rounded(2) - 21.46).abs() < f64::EPSILON);
+    assert!((21.456_789.rounded(0) - 21.0).abs() < f64::EPSILON);
+    assert!((f64::MAX.rounded(2) - f64::MAX).abs() < f64::EPSILON);
+    assert_eq!(12_345_i64.rounded(2), 12_345);
+}
+
+#[tokio::test]
+async fn test_non_finite_values_are_never_journaled() {
+    let db_file_prefix = "/tmp/nonfinite";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/nonfinite", 8, None, Some(store_actor));
+
+    // what the csv and line protocol decoders parse from `NaN` and `inf`
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let e = director
+            .ask(observation("/nonfinite/one", value))
+            .await
+            .err()
+            .unwrap_or_else(|| panic!("{value} was observed"));
+        assert_eq!(e.reason, "value of index 1 is not finite");
+    }
+    assert_eq!(state_of(&director, "/nonfinite/one").await, State::new());
+    assert!(journal_of(db_file_prefix, "/nonfinite/").await.is_empty());
+
+    // and the store refuses to write one as a null
+    let store = SqliteStore::open(db_file_prefix, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let values = State::from([(1, f64::NAN)]);
+    assert!(store
+        .insert_update(
+            "/nonfinite/two",
+            datetime(),
+            datetime(),
+            &values,
+            &Labels::new()
+        )
+        .await
+        .is_err());
+    store.close().await;
+    assert!(journal_of(db_file_prefix, "/nonfinite/").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_nan_in_json_is_rejected() {
+    let decoder = json_decoder::new(8, director::new("/nanjson", 8, None, None));
+    let rejected = decoder
+        .ask(Message::Content {
+            hint: MtHint::Update,
+            path: None,
+            text: String::from(
+                r#"{"path": "/nanjson/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": NaN}}"#,
+            ),
+        })
+        .await;
+    assert!(
+        rejected
+            .as_ref()
+            .is_err_and(|e| e.reason.starts_with("json parse error")),
+        "{rejected:?}"
+    );
+
+    let nv: Arc<Handle> = Arc::new(director::new("/nanapi", 8, None, None));
+    let config = HttpServerConfig::new(None, None, None, String::from("nanapi"));
+    let cli = TestClient::new(build_app(nv, &config, None, Some(true)));
+    let resp = cli
+        .post("/api/actors/nanapi/one")
+        .content_type("application/json")
+        .body(
+            r#"{"path": "/nanapi/one", "datetime": "2023-01-11T23:17:57Z", "values": {"1": NaN}}"#,
+        )
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    cli.get("/api/actors/nanapi/one")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_values_are_rounded_before_they_are_journaled() {
+    let db_file_prefix = "/tmp/rounded";
+    remove_db(db_file_prefix);
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let options = DirectorOptions {
+        decimals: Some(2),
+        ..Default::default()
+    };
+    let director = director::new_with_options("/rounded", 8, None, Some(store_actor), options);
+
+    director
+        .ask(observation("/rounded/one", 21.456_789))
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let live = state_of(&director, "/rounded/one").await;
+    assert_eq!(live, State::from([(1, 21.46), (2, 1.0)]));
+    assert_eq!(
+        journal_of(db_file_prefix, "/rounded/").await,
+        vec![live.clone()]
+    );
+
+    // a resurrected actor has the state of the live one
+    let store_actor = store_actor_sqlite::new(8, String::from(db_file_prefix), false, false);
+    let director = director::new("/rounded", 8, None, Some(store_actor));
+    assert_eq!(state_of(&director, "/rounded/one").await, live);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::director::DirectorOptions;
+use navactor::actors::genes::gene::GeneParams;
+use navactor::actors::genes::gene::GeneType;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::MtHint;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use navactor::io::json_decoder;
+use time::OffsetDateTime;
+
+/// the first integer an `f64` can not hold exactly
+const BIG: i64 = 9_007_199_254_740_993;
+
+async fn director_of(db_file: &str) -> Handle<i64> {
+    let store_actor = store_actor_sqlite::try_new_from_url::<i64>(
+        8,
+        String::from(db_file),
+        "/big",
+        false,
+        false,
+        StoreOptions::default(),
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"));
+    director::new_of_type(
+        "/big",
+        8,
+        None,
+        Some(store_actor),
+        DirectorOptions::default(),
+    )
+}
+
+async fn value_of(director: &Handle<i64>, path: &str) -> Option<i64> {
+    match director
+        .ask(Message::Query {
+            path: String::from(path),
+            hint: MtHint::State,
+        })
+        .await
+    {
+        Ok(Message::StateReport { values, .. }) => values.get(&1).copied(),
+        r => panic!("bad response from director: {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_i64_state_survives_resurrection() {
+    let db_file = "/tmp/value_type_i64.db";
+    remove_db(db_file.trim_end_matches(".db"));
+
+    let director = director_of(db_file).await;
+    director
+        .ask(Message::GeneMapping {
+            path: String::from("/big"),
+            gene_type: GeneType::Accum,
+            params: GeneParams::default(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    for (secs, value) in [(0, BIG), (1, 2)] {
+        let mut values = State::new();
+        values.insert(1, value);
+        director
+            .ask(Message::Observations {
+                path: String::from("/big/one"),
+                datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + secs)
+                    .unwrap_or_else(|e| panic!("{e}")),
+                values,
+                labels: Labels::new(),
+            })
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    assert_eq!(value_of(&director, "/big/one").await, Some(BIG + 2));
+
+    let resurrected = director_of(db_file).await;
+    assert_eq!(value_of(&resurrected, "/big/one").await, Some(BIG + 2));
+}
+
+#[tokio::test]
+async fn test_json_decodes_i64_exactly() {
+    let director = director::new_of_type::<i64>("/big", 8, None, None, DirectorOptions::default());
+    // the decoder forwards the queries it can not decode to the director
+    let decoder = json_decoder::new(8, director);
+    decoder
+        .ask(Message::Content {
+            text: format!(
+                r#"{{"path": "/big/two", "datetime": "2023-01-11T23:17:57Z", "values": {{"1": {BIG}}}}}"#
+            ),
+            hint: MtHint::Update,
+            path: None,
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(value_of(&decoder, "/big/two").await, Some(BIG));
+
+    // a fraction is not an i64
+    assert!(decoder
+        .ask(Message::Content {
+            text: String::from(
+                r#"{"path": "/big/two", "datetime": "2023-01-11T23:17:58Z", "values": {"1": 1.5}}"#
+            ),
+            hint: MtHint::Update,
+            path: None,
+        })
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_i64_rejects_fractional_genes() {
+    let director = director::new_of_type::<i64>("/big", 8, None, None, DirectorOptions::default());
+    let r = director
+        .ask(Message::GeneMapping {
+            path: String::from("/big/avg"),
+            gene_type: GeneType::Mean,
+            params: GeneParams::default(),
+        })
+        .await;
+    assert!(r.is_err(), "{r:?}");
+}
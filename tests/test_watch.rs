@@ -0,0 +1,58 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::State;
+use navactor::actors::director;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::store_actor_sqlite;
+use navactor::cli::ifc::OutputFormat;
+use navactor::cli::runner::watch_until;
+use navactor::cli::runner::CliError;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn test_watch_follows_until_stopped() {
+    let db_file = "/tmp/watched.db";
+    remove_db(db_file.trim_end_matches(".db"));
+    let store_actor =
+        store_actor_sqlite::new_from_url(8, String::from(db_file), "watched", false, false);
+    let writer = director::new("/watched", 8, None, Some(store_actor));
+    let mut values = State::new();
+    values.insert(1, 1.5);
+    writer
+        .ask(Message::Observations {
+            path: String::from("/watched/one"),
+            datetime: OffsetDateTime::now_utc(),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    let r = watch_until(
+        String::from("/watched/one"),
+        Some(String::from(db_file)),
+        OutputFormat::Json,
+        Duration::from_millis(20),
+        8,
+        tokio::time::sleep(Duration::from_millis(100)),
+    )
+    .await;
+    assert!(r.is_ok(), "{r:?}");
+}
+
+#[tokio::test]
+async fn test_watch_without_db() {
+    let r = watch_until(
+        String::from("/watched/one"),
+        Some(String::from("/no/such/dir/watched.db")),
+        OutputFormat::Text,
+        Duration::from_millis(20),
+        8,
+        std::future::pending(),
+    )
+    .await;
+    assert!(matches!(r, Err(CliError::Store(_))), "{r:?}");
+}
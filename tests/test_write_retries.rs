@@ -0,0 +1,106 @@
+mod common;
+
+use common::remove_db;
+use navactor::actors::actor::Handle;
+use navactor::actors::actor::State;
+use navactor::actors::message::Labels;
+use navactor::actors::message::Message;
+use navactor::actors::message::NvResult;
+use navactor::actors::sqlite_store::SqliteOptions;
+use navactor::actors::sqlite_store::SqliteStore;
+use navactor::actors::store_actor_sqlite;
+use navactor::actors::store_actor_sqlite::StoreOptions;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// a store that gives up on a locked db right away and tries a write
+/// `max_write_attempts` times
+async fn store_of(db_file: &str, max_write_attempts: usize) -> Handle {
+    store_actor_sqlite::try_new_from_url(
+        8,
+        String::from(db_file),
+        "retry",
+        false,
+        false,
+        StoreOptions {
+            sqlite: SqliteOptions {
+                busy_timeout: Duration::from_millis(1),
+                ..Default::default()
+            },
+            max_write_attempts,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_or_else(|e| panic!("{e}"))
+}
+
+async fn observe(store: &Handle, n: i64) -> NvResult<Message<f64>> {
+    let mut values = State::new();
+    values.insert(1, 1.0);
+    store
+        .ask(Message::Observations {
+            path: String::from("/retry/one"),
+            datetime: OffsetDateTime::from_unix_timestamp(1_673_479_077 + n)
+                .unwrap_or_else(|e| panic!("{e}")),
+            values,
+            labels: Labels::new(),
+        })
+        .await
+}
+
+/// hold the write lock of the db from another connection for `held`
+async fn lock_db(db_file: &str, held: Duration) -> tokio::task::JoinHandle<()> {
+    let locker = SqliteStore::connect(db_file, false)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    let mut conn = locker
+        .dbconn
+        .acquire()
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut *conn)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    tokio::spawn(async move {
+        tokio::time::sleep(held).await;
+        sqlx::query("COMMIT")
+            .execute(&mut *conn)
+            .await
+            .unwrap_or_else(|e| panic!("{e}"));
+    })
+}
+
+#[tokio::test]
+async fn test_a_write_to_a_locked_db_is_tried_again() {
+    let db_file_prefix = "/tmp/write_retries";
+    remove_db(db_file_prefix);
+    let db_file = format!("{db_file_prefix}.db");
+    let store = store_of(&db_file, 10).await;
+
+    let unlocked = lock_db(&db_file, Duration::from_millis(100)).await;
+    let r = observe(&store, 0).await;
+    assert!(matches!(r, Ok(Message::Persisted)), "{r:?}");
+    unlocked.await.unwrap_or_else(|e| panic!("{e}"));
+
+    // a duplicate is answered right away and not tried again
+    let r = observe(&store, 0).await;
+    assert!(matches!(r, Ok(Message::ConstraintViolation)), "{r:?}");
+}
+
+#[tokio::test]
+async fn test_a_write_tried_once_fails_on_a_locked_db() {
+    let db_file_prefix = "/tmp/write_no_retries";
+    remove_db(db_file_prefix);
+    let db_file = format!("{db_file_prefix}.db");
+    let store = store_of(&db_file, 1).await;
+
+    let unlocked = lock_db(&db_file, Duration::from_millis(100)).await;
+    let r = observe(&store, 0).await;
+    assert!(r.is_err(), "{r:?}");
+    unlocked.await.unwrap_or_else(|e| panic!("{e}"));
+
+    let r = observe(&store, 0).await;
+    assert!(matches!(r, Ok(Message::Persisted)), "{r:?}");
+}